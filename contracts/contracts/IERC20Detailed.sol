@@ -0,0 +1,25 @@
+pragma solidity ^0.5.0;
+
+/**
+ * @dev Optional functions from the ERC20 standard.
+ * Tokens are not required to implement them, so the calls may revert.
+ */
+interface IERC20Detailed {
+    /**
+     * @dev Returns the name of the token.
+     */
+    function name() external view returns (string memory);
+
+    /**
+     * @dev Returns the symbol of the token, usually a shorter version of the
+     * name.
+     */
+    function symbol() external view returns (string memory);
+
+    /**
+     * @dev Returns the number of decimals used to get its user representation.
+     * For example, if `decimals` equals `2`, a balance of `505` tokens should
+     * be displayed to a user as `5,05` (`505 / 10 ** 2`).
+     */
+    function decimals() external view returns (uint8);
+}
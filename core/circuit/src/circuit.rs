@@ -2443,7 +2443,8 @@ fn continue_leftmost_subroot_to_root<E: RescueEngine, CS: ConstraintSystem<E>>(
     Ok(node_hash)
 }
 
-fn generate_maxchunk_polynomial<E: JubjubEngine>() -> Vec<E::Fr> {
+/// Coefficients of the polynomial mapping the operation type to the index of its last chunk.
+pub(crate) fn generate_maxchunk_polynomial<E: JubjubEngine>() -> Vec<E::Fr> {
     use crypto_exports::franklin_crypto::interpolation::interpolate;
 
     let get_xy = |op_type: u8, op_chunks: usize| {
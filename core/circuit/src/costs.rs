@@ -0,0 +1,130 @@
+//! Costs of the operations and blocks in the main circuit.
+//!
+//! Server decides whether the operation fits the block using the chunk costs table
+//! (`OPERATION_CHUNKS`), while the circuit has the chunk costs built into the polynomial
+//! constraining the operations layout. Report built here allows checking that both agree,
+//! along with the constraints count of the circuit for every supported block size.
+
+// External deps
+use crypto_exports::franklin_crypto::bellman::pairing::ff::{Field, PrimeField};
+use serde::{Deserialize, Serialize};
+// Workspace deps
+use models::node::{operations::OPERATION_CHUNKS, Engine, Fr};
+// Local deps
+use crate::circuit::generate_maxchunk_polynomial;
+
+/// Chunk cost of the operation type in the circuit and on the server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationChunksCost {
+    pub op_code: u8,
+    /// Amount of chunks enforced by the circuit, `None` if the circuit doesn't support
+    /// the operation type.
+    pub circuit_chunks: Option<usize>,
+    /// Amount of chunks used by the server to fill the blocks.
+    pub server_chunks: usize,
+}
+
+impl OperationChunksCost {
+    pub fn is_consistent(&self) -> bool {
+        self.circuit_chunks == Some(self.server_chunks)
+    }
+}
+
+/// Size of the circuit for the block size.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockSizeCosts {
+    pub block_chunks: usize,
+    /// Power of two of the universal setup used for the block size.
+    pub setup_power: u32,
+    /// Amount of the PLONK gates of the circuit, `None` if the circuit was not transpiled.
+    pub gates_count: Option<usize>,
+}
+
+impl BlockSizeCosts {
+    /// Checks that the circuit fits the configured universal setup.
+    pub fn is_consistent(&self) -> bool {
+        self.gates_count
+            .map(|gates_count| gates_count.next_power_of_two().trailing_zeros() <= self.setup_power)
+            .unwrap_or(true)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CircuitCostsReport {
+    pub operations: Vec<OperationChunksCost>,
+    pub block_sizes: Vec<BlockSizeCosts>,
+}
+
+impl CircuitCostsReport {
+    pub fn is_consistent(&self) -> bool {
+        self.operations
+            .iter()
+            .all(OperationChunksCost::is_consistent)
+            && self.block_sizes.iter().all(BlockSizeCosts::is_consistent)
+    }
+}
+
+/// Amount of chunks the circuit enforces for the operation type.
+/// Returns `None` if the operation type is not supported by the circuit.
+pub fn circuit_operation_chunks(op_code: u8) -> Option<usize> {
+    let x = Fr::from_str(&op_code.to_string()).unwrap();
+    let mut last_chunk = Fr::zero();
+    let mut x_power = Fr::one();
+    for coeff in generate_maxchunk_polynomial::<Engine>() {
+        let mut term = coeff;
+        term.mul_assign(&x_power);
+        last_chunk.add_assign(&term);
+        x_power.mul_assign(&x);
+    }
+
+    // Polynomial takes arbitrary values for the unsupported operation types,
+    // which don't fit into the small integer.
+    let repr = last_chunk.into_repr();
+    let limbs = repr.as_ref();
+    if limbs[1..].iter().any(|limb| *limb != 0) || limbs[0] >= u64::from(u8::max_value()) {
+        return None;
+    }
+    Some(limbs[0] as usize + 1)
+}
+
+/// Compares the chunk costs of all the operation types known to the server with the circuit.
+pub fn operation_chunks_costs() -> Vec<OperationChunksCost> {
+    OPERATION_CHUNKS
+        .iter()
+        .map(|(op_code, server_chunks)| OperationChunksCost {
+            op_code: *op_code,
+            circuit_chunks: circuit_operation_chunks(*op_code),
+            server_chunks: *server_chunks,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the server chunk costs table matches the circuit.
+    #[test]
+    fn operation_chunks_match_circuit() {
+        for cost in operation_chunks_costs() {
+            assert!(cost.is_consistent(), "Chunk costs mismatch: {:?}", cost);
+        }
+    }
+
+    #[test]
+    fn block_size_fits_setup() {
+        let mut costs = BlockSizeCosts {
+            block_chunks: 6,
+            setup_power: 21,
+            gates_count: None,
+        };
+        assert!(costs.is_consistent());
+        costs.gates_count = Some(1 << 21);
+        assert!(costs.is_consistent());
+        costs.gates_count = Some((1 << 21) + 1);
+        assert!(!costs.is_consistent());
+    }
+}
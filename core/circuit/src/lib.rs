@@ -3,6 +3,7 @@ extern crate log;
 pub mod account;
 pub mod allocated_structures;
 pub mod circuit;
+pub mod costs;
 pub mod element;
 pub mod exit_circuit;
 pub mod operation;
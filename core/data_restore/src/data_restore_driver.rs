@@ -1,4 +1,5 @@
 // External deps
+use futures::executor::block_on;
 use web3::{
     contract::Contract,
     types::{H160, H256},
@@ -318,10 +319,15 @@ impl<T: Transport> DataRestoreDriver<T> {
             )
             .expect("Updating events state: cant update events state");
 
+        let tokens: Vec<_> = token_events
+            .iter()
+            .map(|event| block_on(event.resolve_token(&self.web3)))
+            .collect();
+
         storage_interactor::save_events_state(
             &self.connection_pool,
             &block_events,
-            token_events.as_slice(),
+            &tokens,
             last_watched_eth_block_number,
         );
 
@@ -6,11 +6,13 @@ pub mod data_restore_driver;
 pub mod eth_tx_helpers;
 pub mod events;
 pub mod events_state;
+pub mod pubdata_auditor;
 pub mod rollup_ops;
 pub mod storage_interactor;
 pub mod tree_state;
 
 use crate::data_restore_driver::DataRestoreDriver;
+use crate::pubdata_auditor::PubdataAuditor;
 use clap::{App, Arg};
 use models::{
     config_options::ConfigurationOptions,
@@ -81,8 +83,25 @@ fn main() {
                 .takes_value(true)
                 .help("Expected tree root hash after restoring. This argument is ignored if mode is not `finite`")
         )
+        .arg(
+            Arg::with_name("audit")
+                .long("audit")
+                .help("Audits the public data of the committed blocks instead of restoring"),
+        )
         .get_matches();
 
+    if cli.is_present("audit") {
+        info!("Auditing the public data of the committed blocks");
+        let mut auditor =
+            PubdataAuditor::new(connection_pool, config_opts.available_block_chunk_sizes);
+        let diverged_blocks = auditor.run(cli.is_present("finite"));
+        if diverged_blocks > 0 {
+            error!("Public data of {} blocks diverges", diverged_blocks);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let (_event_loop, transport) =
         Http::new(&config_opts.web3_url).expect("failed to start web3 transport");
     let governance_addr = config_opts.governance_eth_addr;
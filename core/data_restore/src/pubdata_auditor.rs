@@ -0,0 +1,219 @@
+//! Public data self-audit.
+//!
+//! Auditor re-derives the state transitions of the committed blocks purely from their public
+//! data (exactly as it is sent to the contract as calldata), the same way the state is restored
+//! from Ethereum, and compares the resulting root hash with the committed one. Divergence means
+//! that the state can't be restored from the published data (e.g. because of a bug in the public
+//! data encoding), so it's flagged before anyone has to restore the state from the contract.
+//!
+//! Auditor only reads the server database. After the divergence the audit continues from the
+//! committed state of the diverged block, so every block is checked independently.
+
+// Built-in deps
+use std::{thread, time::Duration};
+// External deps
+use failure::format_err;
+// Workspace deps
+use models::node::{BlockNumber, Fr};
+use storage::ConnectionPool;
+// Local deps
+use crate::{rollup_ops::RollupOpsBlock, tree_state::TreeState};
+
+/// Interval between the checks for the new committed blocks.
+const AUDIT_INTERVAL: Duration = Duration::from_secs(10);
+
+pub struct PubdataAuditor {
+    connection_pool: ConnectionPool,
+    available_block_chunk_sizes: Vec<usize>,
+    /// State derived from the public data, as of the last audited block.
+    tree_state: TreeState,
+    /// Number of the blocks which root hash diverged from the committed one.
+    diverged_blocks: usize,
+}
+
+impl PubdataAuditor {
+    /// Creates the auditor starting from the genesis state.
+    pub fn new(connection_pool: ConnectionPool, available_block_chunk_sizes: Vec<usize>) -> Self {
+        let tree_state =
+            load_committed_tree_state(&connection_pool, 0, available_block_chunk_sizes.clone())
+                .expect("Can't load the genesis state");
+        Self {
+            connection_pool,
+            available_block_chunk_sizes,
+            tree_state,
+            diverged_blocks: 0,
+        }
+    }
+
+    /// Audits the committed blocks as they're committed.
+    /// If `finite` is set, audits the already committed blocks and exits.
+    /// Returns the number of the diverged blocks.
+    pub fn run(&mut self, finite: bool) -> usize {
+        loop {
+            if let Err(e) = self.audit_new_blocks() {
+                error!("Failed to audit the public data: {}", e);
+            }
+            if finite {
+                return self.diverged_blocks;
+            }
+            thread::sleep(AUDIT_INTERVAL);
+        }
+    }
+
+    fn audit_new_blocks(&mut self) -> Result<(), failure::Error> {
+        let last_committed_block = self
+            .connection_pool
+            .access_storage()?
+            .chain()
+            .block_schema()
+            .get_last_committed_block()?;
+
+        // Audited blocks were reverted, the blocks committed again have to be audited.
+        if last_committed_block < self.last_audited_block() {
+            info!(
+                "Blocks after {} are reverted, restarting the audit",
+                last_committed_block
+            );
+            self.resync(last_committed_block)?;
+        }
+
+        for block in self.last_audited_block() + 1..=last_committed_block {
+            self.audit_block(block)?;
+        }
+        Ok(())
+    }
+
+    fn audit_block(&mut self, block: BlockNumber) -> Result<(), failure::Error> {
+        let storage = self.connection_pool.access_storage()?;
+        let block_schema = storage.chain().block_schema();
+        let public_data = block_schema
+            .get_block_public_data(block)?
+            .ok_or_else(|| format_err!("No public data stored for the block {}", block))?;
+        let committed_block = block_schema
+            .get_block(block)?
+            .ok_or_else(|| format_err!("Block {} is not found", block))?;
+
+        let derived_root_hash = apply_public_data(
+            &mut self.tree_state,
+            block,
+            committed_block.fee_account,
+            &public_data,
+        );
+
+        match derived_root_hash {
+            Ok(root_hash) if root_hash == committed_block.new_root_hash => {
+                debug!("Public data of the block {} is consistent", block);
+            }
+            Ok(root_hash) => {
+                error!(
+                    "Public data of the block {} diverges: derived root hash {:?}, committed {:?}",
+                    block, root_hash, committed_block.new_root_hash
+                );
+                self.diverged_blocks += 1;
+                self.resync(block)?;
+            }
+            Err(e) => {
+                error!("Public data of the block {} can't be applied: {}", block, e);
+                self.diverged_blocks += 1;
+                self.resync(block)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replaces the derived state with the committed one as of the provided block.
+    fn resync(&mut self, block: BlockNumber) -> Result<(), failure::Error> {
+        self.tree_state = load_committed_tree_state(
+            &self.connection_pool,
+            block,
+            self.available_block_chunk_sizes.clone(),
+        )?;
+        Ok(())
+    }
+
+    fn last_audited_block(&self) -> BlockNumber {
+        self.tree_state.state.block_number
+    }
+}
+
+/// Applies the block public data to the state, returns the resulting root hash.
+pub fn apply_public_data(
+    tree_state: &mut TreeState,
+    block: BlockNumber,
+    fee_account: u32,
+    public_data: &[u8],
+) -> Result<Fr, failure::Error> {
+    let ops_block = RollupOpsBlock {
+        block_num: block,
+        ops: RollupOpsBlock::get_rollup_ops_from_data(public_data)?,
+        fee_account,
+    };
+    tree_state.update_tree_states_from_ops_block(&ops_block)?;
+    Ok(tree_state.root_hash())
+}
+
+fn load_committed_tree_state(
+    connection_pool: &ConnectionPool,
+    block: BlockNumber,
+    available_block_chunk_sizes: Vec<usize>,
+) -> Result<TreeState, failure::Error> {
+    let storage = connection_pool.access_storage()?;
+    let (block, accounts) = storage
+        .chain()
+        .state_schema()
+        .load_committed_state(Some(block))?;
+    let fee_account = match storage.chain().block_schema().get_block(block)? {
+        Some(stored_block) => stored_block.fee_account,
+        // Genesis block is not stored, its fee account is the first one.
+        None => 0,
+    };
+    Ok(TreeState::load(
+        block,
+        accounts,
+        0,
+        fee_account,
+        available_block_chunk_sizes,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::{Deposit, DepositOp, FranklinOp};
+    use num::BigUint;
+
+    #[test]
+    fn derived_root_hash() {
+        let deposit = FranklinOp::Deposit(Box::new(DepositOp {
+            priority_op: Deposit {
+                from: [1u8; 20].into(),
+                token: 1,
+                amount: BigUint::from(1000u32),
+                to: [7u8; 20].into(),
+            },
+            account_id: 0,
+        }));
+        let public_data = deposit.public_data();
+
+        let mut tree_state = TreeState::new(vec![10]);
+        let root_hash = apply_public_data(&mut tree_state, 1, 0, &public_data)
+            .expect("Failed to apply the public data");
+        assert_eq!(tree_state.state.block_number, 1);
+
+        let mut expected_state = TreeState::new(vec![10]);
+        let ops_block = RollupOpsBlock {
+            block_num: 1,
+            ops: vec![deposit],
+            fee_account: 0,
+        };
+        expected_state
+            .update_tree_states_from_ops_block(&ops_block)
+            .expect("Failed to apply the operations");
+        assert_eq!(root_hash, expected_state.root_hash());
+
+        // Truncated public data can't be parsed.
+        let mut tree_state = TreeState::new(vec![10]);
+        let truncated = &public_data[..public_data.len() - 1];
+        assert!(apply_public_data(&mut tree_state, 1, 0, truncated).is_err());
+    }
+}
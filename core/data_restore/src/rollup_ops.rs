@@ -89,6 +89,9 @@ impl RollupOpsBlock {
 
             let pre = current_pointer;
             let post = pre + pub_data_size;
+            if post > data.len() {
+                failure::bail!("Public data of the operation is truncated");
+            }
 
             let op = FranklinOp::from_public_data(&data[pre..post])?;
 
@@ -4,9 +4,9 @@ use std::{convert::TryFrom, str::FromStr};
 use web3::types::H256;
 // Workspace deps
 use models::{
-    node::{block::Block, AccountMap, AccountUpdate, AccountUpdates, FranklinOp},
+    node::{block::Block, AccountMap, AccountUpdate, AccountUpdates, FranklinOp, Token},
     prover_utils::EncodedProofPlonk,
-    Action, NewTokenEvent, Operation,
+    Action, Operation,
 };
 use storage::{
     data_restore::records::{
@@ -105,7 +105,7 @@ pub fn update_tree_state(
 pub fn save_events_state(
     connection_pool: &ConnectionPool,
     block_events: &[BlockEvent],
-    tokens: &[NewTokenEvent],
+    tokens: &[Token],
     last_watched_eth_block_number: u64,
 ) {
     let storage = connection_pool.access_storage().expect("db failed");
@@ -278,6 +278,8 @@ impl TreeState {
             &self.available_block_chunk_sizes,
             gas_limit,
             gas_limit,
+            // Seal time of the block is not published on Ethereum.
+            None,
         );
 
         self.state.block_number += 1;
@@ -322,6 +324,7 @@ impl TreeState {
             },
             block_index,
             created_at: chrono::Utc::now(),
+            invalid_reason: None,
         };
         ops.push(ExecutedOperations::PriorityOp(Box::new(exec_result)));
         self.current_unprocessed_priority_op += 1;
@@ -3,14 +3,19 @@ extern crate serde_derive;
 
 // Built-in deps
 use std::fmt;
+use std::sync::Arc;
 
 // External uses
+use failure::format_err;
 use futures::compat::Future01CompatExt;
 use web3::contract::tokens::Tokenize;
 use web3::contract::Options;
-use web3::types::{Address, BlockNumber, Bytes};
+use web3::helpers::{serialize, CallFuture};
+use web3::types::{Address, BlockNumber, Bytes, CallRequest};
 use web3::types::{H160, H256, U256, U64};
 use web3::{Error, Transport, Web3};
+// Local uses
+use self::signer::{EthereumSigner, PrivateKeySigner};
 
 pub mod signer;
 
@@ -20,14 +25,76 @@ pub mod signer;
 /// This is an emergency value, which will not be used normally.
 const FALLBACK_GAS_LIMIT: u64 = 3_000_000;
 
+/// Amount of the recent blocks used to estimate the EIP-1559 transaction fees.
+const FEE_HISTORY_BLOCKS: u64 = 10;
+
+/// Selector of the `Error(string)` call used by the contracts to encode the revert reason.
+const REVERT_REASON_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Type of the transactions sent by the client.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TxPricing {
+    /// Legacy transactions with the gas price.
+    Legacy,
+    /// EIP-1559 (type 2) transactions with the max fee and the priority fee per gas.
+    /// Priority fee is estimated as the provided percentile of the priority fees
+    /// paid in the recent blocks.
+    Eip1559 { priority_fee_percentile: f64 },
+}
+
+/// Response of the `eth_feeHistory` method.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistory {
+    /// Base fees of the requested blocks, and of the next block.
+    base_fee_per_gas: Vec<U256>,
+    /// Priority fees of the requested percentiles for every block.
+    #[serde(default)]
+    reward: Vec<Vec<U256>>,
+}
+
+/// Fees of the EIP-1559 transaction estimated based on the recent blocks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Eip1559Fees {
+    /// Base fee of the next block.
+    pub base_fee: U256,
+    /// Priority fee to be paid to the miner.
+    pub priority_fee: U256,
+}
+
+impl Eip1559Fees {
+    /// Maximum fee per gas the transaction should be able to pay. Base fee may grow
+    /// up to 12.5% per block, so doubling it keeps the transaction includable for
+    /// at least six full blocks.
+    pub fn max_fee_per_gas(&self) -> U256 {
+        self.base_fee * U256::from(2) + self.priority_fee
+    }
+
+    /// Priority fee for the transaction with the provided max fee.
+    ///
+    /// Transactions are priced with the single value (gas price for the legacy ones and
+    /// max fee for EIP-1559 ones), which is increased to replace the stuck transaction.
+    /// Replacement is accepted only if the priority fee is increased as well, so the
+    /// priority fee is scaled along with the max fee.
+    pub fn priority_fee_for(&self, max_fee_per_gas: U256) -> U256 {
+        let estimated_max_fee = self.max_fee_per_gas();
+        if estimated_max_fee.is_zero() {
+            return U256::zero();
+        }
+        let priority_fee = self.priority_fee * max_fee_per_gas / estimated_max_fee;
+        std::cmp::min(priority_fee, max_fee_per_gas)
+    }
+}
+
 #[derive(Clone)]
 pub struct ETHClient<T: Transport> {
-    private_key: H256,
+    signer: Arc<dyn EthereumSigner>,
     pub sender_account: Address,
     pub contract_addr: H160,
     pub contract: ethabi::Contract,
     pub chain_id: u8,
     pub gas_price_factor: f64,
+    pub tx_pricing: TxPricing,
     pub web3: Web3<T>,
 }
 
@@ -40,6 +107,7 @@ impl<T: Transport> fmt::Debug for ETHClient<T> {
             .field("contract_addr", &self.contract_addr)
             .field("chain_id", &self.chain_id)
             .field("gas_price_factor", &self.gas_price_factor)
+            .field("tx_pricing", &self.tx_pricing)
             .finish()
     }
 }
@@ -61,14 +129,40 @@ impl<T: Transport> ETHClient<T> {
         contract_eth_addr: H160,
         chain_id: u8,
         gas_price_factor: f64,
+        tx_pricing: TxPricing,
+    ) -> Self {
+        Self::with_signer(
+            transport,
+            contract,
+            operator_eth_addr,
+            Arc::new(PrivateKeySigner::new(operator_pk)),
+            contract_eth_addr,
+            chain_id,
+            gas_price_factor,
+            tx_pricing,
+        )
+    }
+
+    /// Creates the client signing the transactions with the provided signer
+    /// (e.g. the remote one) instead of the in-process private key.
+    pub fn with_signer(
+        transport: T,
+        contract: ethabi::Contract,
+        operator_eth_addr: H160,
+        signer: Arc<dyn EthereumSigner>,
+        contract_eth_addr: H160,
+        chain_id: u8,
+        gas_price_factor: f64,
+        tx_pricing: TxPricing,
     ) -> Self {
         Self {
             sender_account: operator_eth_addr,
-            private_key: operator_pk,
+            signer,
             contract_addr: contract_eth_addr,
             chain_id,
             contract,
             gas_price_factor,
+            tx_pricing,
             web3: Web3::new(transport),
         }
     }
@@ -103,13 +197,66 @@ impl<T: Transport> ETHClient<T> {
         self.web3.eth().block_number().compat().await
     }
 
+    /// Returns the chain id of the network the Ethereum node is connected to.
+    pub async fn chain_id(&self) -> Result<U256, Error> {
+        let request = self.web3.transport().execute("eth_chainId", vec![]);
+        CallFuture::new(request).compat().await
+    }
+
+    /// Returns the gas price to be used for the transactions, i.e. the max fee per gas
+    /// for the EIP-1559 transactions.
     pub async fn get_gas_price(&self) -> Result<U256, failure::Error> {
-        let mut network_gas_price = self.web3.eth().gas_price().compat().await?;
+        let mut network_gas_price = match self.tx_pricing {
+            TxPricing::Legacy => self.web3.eth().gas_price().compat().await?,
+            TxPricing::Eip1559 {
+                priority_fee_percentile,
+            } => self
+                .get_eip1559_fees(priority_fee_percentile)
+                .await?
+                .max_fee_per_gas(),
+        };
         let percent_gas_price_factor = U256::from((self.gas_price_factor * 100.0).round() as u64);
         network_gas_price = (network_gas_price * percent_gas_price_factor) / U256::from(100);
         Ok(network_gas_price)
     }
 
+    /// Estimates the EIP-1559 transaction fees based on the fee history of the recent blocks.
+    pub async fn get_eip1559_fees(
+        &self,
+        priority_fee_percentile: f64,
+    ) -> Result<Eip1559Fees, failure::Error> {
+        let params = vec![
+            serialize(&U256::from(FEE_HISTORY_BLOCKS)),
+            serialize(&BlockNumber::Latest),
+            serialize(&vec![priority_fee_percentile]),
+        ];
+        let request = self.web3.transport().execute("eth_feeHistory", params);
+        let history: FeeHistory = CallFuture::new(request).compat().await?;
+
+        let base_fee = *history
+            .base_fee_per_gas
+            .last()
+            .ok_or_else(|| format_err!("Fee history has no base fee"))?;
+        let rewards: Vec<U256> = history
+            .reward
+            .iter()
+            .filter_map(|block_rewards| block_rewards.first().cloned())
+            .collect();
+        let priority_fee = if rewards.is_empty() {
+            U256::zero()
+        } else {
+            let rewards_sum = rewards
+                .iter()
+                .fold(U256::zero(), |sum, reward| sum + *reward);
+            rewards_sum / U256::from(rewards.len())
+        };
+
+        Ok(Eip1559Fees {
+            base_fee,
+            priority_fee,
+        })
+    }
+
     /// Encodes the transaction data (smart contract method and its input) to the bytes
     /// without creating an actual transaction.
     pub fn encode_tx_data<P: Tokenize>(&self, func: &str, params: P) -> Vec<u8> {
@@ -155,17 +302,37 @@ impl<T: Transport> ETHClient<T> {
         };
 
         // form and sign tx
-        let tx = signer::RawTransaction {
-            chain_id: self.chain_id,
-            nonce,
-            to: Some(self.contract_addr),
-            value: options.value.unwrap_or_default(),
-            gas_price,
-            gas,
-            data,
+        let signed_tx = match self.tx_pricing {
+            TxPricing::Legacy => {
+                let tx = signer::RawTransaction {
+                    chain_id: self.chain_id,
+                    nonce,
+                    to: Some(self.contract_addr),
+                    value: options.value.unwrap_or_default(),
+                    gas_price,
+                    gas,
+                    data,
+                };
+                self.signer.sign_transaction(tx).await?
+            }
+            TxPricing::Eip1559 {
+                priority_fee_percentile,
+            } => {
+                // Gas price is used as the max fee per gas.
+                let fees = self.get_eip1559_fees(priority_fee_percentile).await?;
+                let tx = signer::Eip1559Transaction {
+                    chain_id: self.chain_id,
+                    nonce,
+                    max_priority_fee_per_gas: fees.priority_fee_for(gas_price),
+                    max_fee_per_gas: gas_price,
+                    gas,
+                    to: Some(self.contract_addr),
+                    value: options.value.unwrap_or_default(),
+                    data,
+                };
+                self.signer.sign_eip1559_transaction(tx).await?
+            }
         };
-
-        let signed_tx = tx.sign(&self.private_key);
         let hash = self
             .web3
             .web3()
@@ -200,6 +367,44 @@ impl<T: Transport> ETHClient<T> {
         self.sign_prepared_tx(data, options).await
     }
 
+    /// Estimates the gas required to execute the contract call with the provided
+    /// encoded data, sent from the operator account.
+    pub async fn estimate_gas(&self, data: Vec<u8>) -> Result<U256, Error> {
+        let request = CallRequest {
+            from: Some(self.sender_account),
+            to: self.contract_addr,
+            gas: None,
+            gas_price: None,
+            value: None,
+            data: Some(Bytes(data)),
+        };
+        self.web3.eth().estimate_gas(request, None).compat().await
+    }
+
+    /// Executes the contract call with the provided encoded data, sent from the operator
+    /// account, on the latest Ethereum state without creating a transaction.
+    /// Returns the revert reason if the call fails.
+    pub async fn dry_run(&self, data: Vec<u8>) -> Result<Option<String>, Error> {
+        let request = CallRequest {
+            from: Some(self.sender_account),
+            to: self.contract_addr,
+            gas: None,
+            gas_price: None,
+            value: None,
+            data: Some(Bytes(data)),
+        };
+        match self.web3.eth().call(request, None).compat().await {
+            Ok(output) => Ok(decode_revert_reason(&output.0)),
+            // Most of the nodes report the reverted call as an RPC error, which message
+            // contains the revert reason (and the raw output is provided as the error data).
+            Err(Error::Rpc(e)) => Ok(Some(match e.data {
+                Some(data) => format!("{} ({})", e.message, data),
+                None => e.message,
+            })),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Sends the transaction to the Ethereum blockchain.
     /// Transaction is expected to be encoded as the byte sequence.
     pub async fn send_raw_tx(&self, tx: Vec<u8>) -> Result<H256, failure::Error> {
@@ -211,3 +416,54 @@ impl<T: Transport> ETHClient<T> {
             .await?)
     }
 }
+
+/// Decodes the revert reason from the call output, if the output is the encoded
+/// `Error(string)` call.
+pub fn decode_revert_reason(output: &[u8]) -> Option<String> {
+    if output.len() < 4 || output[..4] != REVERT_REASON_SELECTOR {
+        return None;
+    }
+    match ethabi::decode(&[ethabi::ParamType::String], &output[4..])
+        .ok()?
+        .pop()
+    {
+        Some(ethabi::Token::String(reason)) => Some(reason),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the priority fee is scaled along with the increased max fee.
+    #[test]
+    fn eip1559_priority_fee_scaling() {
+        let fees = Eip1559Fees {
+            base_fee: 100.into(),
+            priority_fee: 10.into(),
+        };
+        assert_eq!(fees.max_fee_per_gas(), 210.into());
+        assert_eq!(fees.priority_fee_for(210.into()), 10.into());
+        assert_eq!(fees.priority_fee_for(420.into()), 20.into());
+
+        let empty_fees = Eip1559Fees {
+            base_fee: 0.into(),
+            priority_fee: 0.into(),
+        };
+        assert_eq!(empty_fees.priority_fee_for(100.into()), 0.into());
+    }
+
+    #[test]
+    fn revert_reason_decoding() {
+        let reason = ethabi::encode(&[ethabi::Token::String("wrong root hash".into())]);
+        let output = [&REVERT_REASON_SELECTOR[..], &reason[..]].concat();
+        assert_eq!(
+            decode_revert_reason(&output),
+            Some("wrong root hash".to_string())
+        );
+
+        assert_eq!(decode_revert_reason(&[]), None);
+        assert_eq!(decode_revert_reason(&reason), None);
+    }
+}
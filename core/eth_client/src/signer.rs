@@ -1,7 +1,12 @@
+// Built-in deps
+use std::fmt;
+// External uses
+use async_trait::async_trait;
+use failure::format_err;
 use parity_crypto::publickey::sign;
 use rlp::RlpStream;
 use tiny_keccak::keccak256;
-use web3::types::{H160, H256, U256};
+use web3::types::{Bytes, H160, H256, U256};
 
 /// Description of a Transaction, pending or in the chain.
 #[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
@@ -80,6 +85,85 @@ impl RawTransaction {
     }
 }
 
+/// Type of the EIP-1559 transactions (see EIP-2718 for the typed transactions envelope).
+const EIP1559_TX_TYPE: u8 = 2;
+
+/// Description of an EIP-1559 (type 2) transaction, which pays the base fee
+/// of the block and the priority fee to the miner instead of the gas price.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Eip1559Transaction {
+    /// Chain id: mainnet => 1, rinkeby => 4, ropsten => 43, etc.
+    pub chain_id: u8,
+    /// Nonce
+    pub nonce: U256,
+    /// Maximum fee per gas paid to the miner on top of the base fee
+    #[serde(rename = "maxPriorityFeePerGas")]
+    pub max_priority_fee_per_gas: U256,
+    /// Maximum total fee per gas (base fee and priority fee)
+    #[serde(rename = "maxFeePerGas")]
+    pub max_fee_per_gas: U256,
+    /// Gas amount
+    pub gas: U256,
+    /// Recipient (None when contract creation)
+    pub to: Option<H160>,
+    /// Transfered value
+    pub value: U256,
+    /// Input data
+    pub data: Vec<u8>,
+}
+
+impl Eip1559Transaction {
+    /// Signs and returns the transaction encoded as `0x02 || rlp(fields, signature)`.
+    /// Access list is always empty.
+    pub fn sign(&self, private_key: &H256) -> Vec<u8> {
+        let hash = self.hash();
+        let sig = sign(&(*private_key).into(), &hash.into()).expect("failed to sign eth message");
+        let mut tx = RlpStream::new();
+        tx.begin_unbounded_list();
+        self.encode(&mut tx);
+        tx.append(&sig.v());
+        let r_start = find_first_nonzero(sig.r());
+        let r = &sig.r()[r_start..];
+        tx.append(&r);
+        let s_start = find_first_nonzero(sig.s());
+        let s = &sig.s()[s_start..];
+        tx.append(&s);
+        tx.finalize_unbounded_list();
+
+        let mut signed_tx = vec![EIP1559_TX_TYPE];
+        signed_tx.extend(tx.out());
+        signed_tx
+    }
+
+    fn hash(&self) -> [u8; 32] {
+        let mut hash = RlpStream::new();
+        hash.begin_unbounded_list();
+        self.encode(&mut hash);
+        hash.finalize_unbounded_list();
+
+        let mut payload = vec![EIP1559_TX_TYPE];
+        payload.extend(hash.out());
+        keccak256(&payload)
+    }
+
+    fn encode(&self, s: &mut RlpStream) {
+        s.append(&U256::from(self.chain_id));
+        s.append(&self.nonce);
+        s.append(&self.max_priority_fee_per_gas);
+        s.append(&self.max_fee_per_gas);
+        s.append(&self.gas);
+        if let Some(ref t) = self.to {
+            s.append(t);
+        } else {
+            s.append(&vec![]);
+        }
+        s.append(&self.value);
+        s.append(&self.data);
+        // Access list.
+        s.begin_list(0);
+    }
+}
+
 fn ecdsa_sign(hash: [u8; 32], private_key: &H256, chain_id: u8) -> EcdsaSig {
     let sig = sign(&(*private_key).into(), &hash.into()).expect("failed to sign eth message");
 
@@ -97,3 +181,163 @@ pub struct EcdsaSig {
     r: Vec<u8>,
     s: Vec<u8>,
 }
+
+/// Signer of the Ethereum transactions sent from the operator account.
+#[async_trait]
+pub trait EthereumSigner: Send + Sync {
+    /// Signs the legacy transaction and returns it RLP-encoded.
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<Vec<u8>, failure::Error>;
+
+    /// Signs the EIP-1559 transaction and returns it encoded as the typed transaction.
+    async fn sign_eip1559_transaction(
+        &self,
+        tx: Eip1559Transaction,
+    ) -> Result<Vec<u8>, failure::Error>;
+}
+
+/// Signer using the private key stored in the server memory.
+#[derive(Clone)]
+pub struct PrivateKeySigner {
+    private_key: H256,
+}
+
+impl PrivateKeySigner {
+    pub fn new(private_key: H256) -> Self {
+        Self { private_key }
+    }
+}
+
+impl fmt::Debug for PrivateKeySigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // We do not want to have a private key in the debug representation.
+        f.debug_struct("PrivateKeySigner").finish()
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for PrivateKeySigner {
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<Vec<u8>, failure::Error> {
+        Ok(tx.sign(&self.private_key))
+    }
+
+    async fn sign_eip1559_transaction(
+        &self,
+        tx: Eip1559Transaction,
+    ) -> Result<Vec<u8>, failure::Error> {
+        Ok(tx.sign(&self.private_key))
+    }
+}
+
+/// Signer delegating the signing to the remote service (e.g. web3signer backed by an HSM)
+/// via the `eth_signTransaction` JSON RPC method, so the private key is not stored on
+/// the server.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    url: String,
+    address: H160,
+    client: reqwest::Client,
+}
+
+impl RemoteSigner {
+    /// Creates the signer of the transactions sent from the provided address, which key
+    /// is managed by the service available at the provided URL.
+    pub fn new(url: String, address: H160) -> Self {
+        Self {
+            url,
+            address,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn sign(&self, tx: serde_json::Value) -> Result<Vec<u8>, failure::Error> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_signTransaction",
+            "params": [tx],
+        });
+        let response = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(&request)?)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        parse_sign_response(&response)
+    }
+}
+
+#[async_trait]
+impl EthereumSigner for RemoteSigner {
+    async fn sign_transaction(&self, tx: RawTransaction) -> Result<Vec<u8>, failure::Error> {
+        self.sign(serde_json::json!({
+            "from": self.address,
+            "to": tx.to,
+            "gas": tx.gas,
+            "gasPrice": tx.gas_price,
+            "nonce": tx.nonce,
+            "value": tx.value,
+            "data": Bytes(tx.data),
+            "chainId": U256::from(tx.chain_id),
+        }))
+        .await
+    }
+
+    async fn sign_eip1559_transaction(
+        &self,
+        tx: Eip1559Transaction,
+    ) -> Result<Vec<u8>, failure::Error> {
+        self.sign(serde_json::json!({
+            "type": U256::from(EIP1559_TX_TYPE),
+            "from": self.address,
+            "to": tx.to,
+            "gas": tx.gas,
+            "maxFeePerGas": tx.max_fee_per_gas,
+            "maxPriorityFeePerGas": tx.max_priority_fee_per_gas,
+            "nonce": tx.nonce,
+            "value": tx.value,
+            "data": Bytes(tx.data),
+            "chainId": U256::from(tx.chain_id),
+        }))
+        .await
+    }
+}
+
+/// Extracts the signed transaction from the `eth_signTransaction` response. Signers
+/// return either the encoded transaction itself, or the object with the `raw` field.
+fn parse_sign_response(response: &[u8]) -> Result<Vec<u8>, failure::Error> {
+    let mut response: serde_json::Value = serde_json::from_slice(response)?;
+    if let Some(error) = response.get("error") {
+        failure::bail!("Remote signer error: {}", error);
+    }
+
+    let signed_tx = match response["result"].take() {
+        serde_json::Value::Object(mut result) => result
+            .remove("raw")
+            .ok_or_else(|| format_err!("No signed transaction in the remote signer response"))?,
+        result => result,
+    };
+    let signed_tx: Bytes = serde_json::from_value(signed_tx)?;
+    Ok(signed_tx.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remote_signer_response() {
+        let raw_response = br#"{"jsonrpc":"2.0","id":1,"result":"0x02f8"}"#;
+        assert_eq!(parse_sign_response(raw_response).unwrap(), vec![0x02, 0xf8]);
+
+        let object_response = br#"{"jsonrpc":"2.0","id":1,"result":{"raw":"0xf8","tx":{}}}"#;
+        assert_eq!(parse_sign_response(object_response).unwrap(), vec![0xf8]);
+
+        let error_response = br#"{"jsonrpc":"2.0","id":1,"error":{"code":-32000}}"#;
+        assert!(parse_sign_response(error_response).is_err());
+    }
+}
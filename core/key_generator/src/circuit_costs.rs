@@ -0,0 +1,40 @@
+// Workspace deps
+use circuit::costs::{operation_chunks_costs, BlockSizeCosts, CircuitCostsReport};
+use crypto_exports::bellman::plonk::transpile_with_gates_count;
+use models::config_options::AvailableBlockSizesConfig;
+// Local deps
+use crate::franklin_key::zksync_circuit;
+
+/// Prints the circuit costs report for the given block sizes as JSON.
+/// Exits with an error if the server chunk costs don't match the circuit, or the circuit
+/// doesn't fit the configured setup power.
+pub(crate) fn report_circuit_costs(config: AvailableBlockSizesConfig) {
+    let block_sizes = config
+        .blocks_chunks
+        .into_iter()
+        .zip(config.blocks_setup_power2.into_iter())
+        .map(|(block_chunks, setup_power)| {
+            log::info!("Transpiling circuit for block size {}", block_chunks);
+            let (gates_count, _) = transpile_with_gates_count(zksync_circuit(block_chunks))
+                .expect("failed to transpile");
+            BlockSizeCosts {
+                block_chunks,
+                setup_power,
+                gates_count: Some(gates_count),
+            }
+        })
+        .collect();
+    let report = CircuitCostsReport {
+        operations: operation_chunks_costs(),
+        block_sizes,
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&report).expect("failed to serialize report")
+    );
+    if !report.is_consistent() {
+        log::error!("Circuit costs are inconsistent with the server configuration");
+        std::process::exit(1);
+    }
+}
@@ -72,7 +72,7 @@ fn exit_circuit() -> impl Circuit<Engine> + Clone {
 }
 
 /// Creates instance of the main zkSync circuit with the given number chunks in block.
-fn zksync_circuit(block_chunks: usize) -> impl Circuit<Engine> + Clone {
+pub(crate) fn zksync_circuit(block_chunks: usize) -> impl Circuit<Engine> + Clone {
     let empty_operation = Operation {
         new_root: None,
         tx_type: None,
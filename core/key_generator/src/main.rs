@@ -9,12 +9,17 @@
 //! Only parameters that determine process of these generation is `SUPPORTED_BLOCK_CHUNKS_SIZES`
 //! and `SUPPORTED_BLOCK_CHUNKS_SIZES_SETUP_POWERS` that are read from env in config files.
 //! Before generating parameters universal setup keys should be downloaded using `zksync plonk-setup` command.
+//!
+//! `circuit-costs` command reports the constraint counts of the circuit for every supported
+//! block size and checks the chunk costs of the operations used by the server against the circuit.
 
+mod circuit_costs;
 mod franklin_key;
 mod verifier_contract_generator;
 
 use clap::{App, SubCommand};
 
+use crate::circuit_costs::report_circuit_costs;
 use crate::franklin_key::{make_plonk_blocks_verify_keys, make_plonk_exodus_verify_key};
 use crate::verifier_contract_generator::create_verifier_contract;
 use models::config_options::AvailableBlockSizesConfig;
@@ -28,6 +33,7 @@ fn main() {
             SubCommand::with_name("keys").about("Generate zkSync main circuit(for various block sizes), and exodus circuit verification keys"),
         )
         .subcommand(SubCommand::with_name("contract").about("Generate verifier contract based on verification keys"))
+        .subcommand(SubCommand::with_name("circuit-costs").about("Report the constraint counts and the operation chunk costs of the circuit"))
         .get_matches();
 
     let config = AvailableBlockSizesConfig::from_env();
@@ -37,5 +43,7 @@ fn main() {
         make_plonk_blocks_verify_keys(config);
     } else if cmd == "contract" {
         create_verifier_contract(config);
+    } else if cmd == "circuit-costs" {
+        report_circuit_costs(config);
     }
 }
@@ -131,10 +131,43 @@ fn smt_root_hash_cached(b: &mut Bencher<'_>) {
     );
 }
 
+/// Measures the time of obtaining a SMT root hash after updating a single account
+/// in the tree with the fully calculated cache.
+///
+/// Only the path of the updated account is expected to be recalculated.
+fn smt_root_hash_incremental(b: &mut Bencher<'_>) {
+    let depth = models::params::account_tree_depth();
+    let accounts: Vec<_> = (0..N_ACCOUNTS).map(gen_account).collect();
+
+    // Create a tree, fill it with some accounts and calculate the root hash.
+    let mut tree = RealSMT::new(depth);
+    for (id, account) in accounts.into_iter().enumerate() {
+        let id = id as u32;
+        tree.insert(id, account.clone());
+    }
+    let _ = tree.root_hash();
+    let updated_account = gen_account(N_ACCOUNTS);
+
+    let setup = || (tree.clone(), updated_account.clone());
+
+    b.iter_batched(
+        setup,
+        |(mut tree, account)| {
+            tree.insert(N_ACCOUNTS / 2, account);
+            let _hash = black_box(tree.root_hash());
+        },
+        BatchSize::SmallInput,
+    );
+}
+
 pub fn bench_merkle_tree(c: &mut Criterion) {
     c.bench_function("Parallel SMT create", smt_create);
     c.bench_function("Parallel SMT insert (empty)", smt_insert_empty);
     c.bench_function("Parallel SMT insert (filled)", smt_insert_filled);
     c.bench_function("Parallel SMT root hash", smt_root_hash);
     c.bench_function("Parallel SMT root hash (cached)", smt_root_hash_cached);
+    c.bench_function(
+        "Parallel SMT root hash (incremental)",
+        smt_root_hash_incremental,
+    );
 }
@@ -6,7 +6,9 @@ use std::str::FromStr;
 const ZKSYNC_CONTRACT_FILE: &str = "contracts/build/ZkSync.json";
 const GOVERNANCE_CONTRACT_FILE: &str = "contracts/build/Governance.json";
 const IERC20_CONTRACT_FILE: &str = "contracts/build/IERC20.json";
+const IERC20_DETAILED_CONTRACT_FILE: &str = "contracts/build/IERC20Detailed.json";
 const IEIP1271_CONTRACT_FILE: &str = "contracts/build/IEIP1271.json";
+const UPGRADE_GATEKEEPER_CONTRACT_FILE: &str = "contracts/build/UpgradeGatekeeper.json";
 
 fn read_file_to_json_value(path: &str) -> io::Result<serde_json::Value> {
     let contents = fs::read_to_string(path)?;
@@ -32,6 +34,15 @@ pub fn governance_contract() -> Contract {
     Contract::load(abi_string.as_bytes()).expect("governance contract abi")
 }
 
+pub fn upgrade_gatekeeper_contract() -> Contract {
+    let abi_string = read_file_to_json_value(UPGRADE_GATEKEEPER_CONTRACT_FILE)
+        .expect("couldn't read UPGRADE_GATEKEEPER_CONTRACT_FILE")
+        .get("abi")
+        .expect("couldn't get abi from UPGRADE_GATEKEEPER_CONTRACT_FILE")
+        .to_string();
+    Contract::load(abi_string.as_bytes()).expect("upgrade gatekeeper contract abi")
+}
+
 pub fn erc20_contract() -> Contract {
     let abi_string = read_file_to_json_value(IERC20_CONTRACT_FILE)
         .expect("couldn't read IERC20_CONTRACT_FILE")
@@ -41,6 +52,15 @@ pub fn erc20_contract() -> Contract {
     Contract::load(abi_string.as_bytes()).expect("erc20 contract abi")
 }
 
+pub fn erc20_detailed_contract() -> Contract {
+    let abi_string = read_file_to_json_value(IERC20_DETAILED_CONTRACT_FILE)
+        .expect("couldn't read IERC20_DETAILED_CONTRACT_FILE")
+        .get("abi")
+        .expect("couldn't get abi from IERC20_DETAILED_CONTRACT_FILE")
+        .to_string();
+    Contract::load(abi_string.as_bytes()).expect("erc20 detailed contract abi")
+}
+
 pub fn eip1271_contract() -> Contract {
     let abi_string = read_file_to_json_value(IEIP1271_CONTRACT_FILE)
         .expect("couldn't read IEIP1271_CONTRACT_FILE")
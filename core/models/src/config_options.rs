@@ -1,15 +1,72 @@
 // Built-in deps
 use std::env;
+use std::fs::read_to_string;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 // External uses
 use futures::{channel::mpsc, executor::block_on, SinkExt};
-use web3::types::{H160, H256};
+use web3::types::{H160, H256, U256};
 // Local uses
 use crate::params::block_chunk_sizes;
 use url::Url;
 
+/// Default maximum amount of transactions stored in the mempool.
+pub const DEFAULT_MEMPOOL_CAPACITY: usize = 100_000;
+/// Default minimum fee accepted for the transaction, as a percentage of the fee
+/// required by the fee model.
+pub const DEFAULT_MIN_FEE_PERCENT: u32 = 95;
+/// Default amount of Ethereum blocks before the priority operation expiration
+/// at which the block containing it is sealed immediately.
+pub const DEFAULT_PRIORITY_OP_DEADLINE_MARGIN: u64 = 240;
+/// Default time (in seconds) between the priority operation confirmation and its execution
+/// after which the operator is alerted.
+pub const DEFAULT_PRIORITY_OP_SLA: u64 = 900;
+/// Default interval (in milliseconds) between the miniblocks proposed to the state keeper.
+pub const DEFAULT_MINIBLOCK_INTERVAL_MS: u64 = 1000;
+/// Default amount of sealed blocks between the checkpoints of the state keeper state.
+pub const DEFAULT_STATE_CHECKPOINT_INTERVAL: u32 = 100;
+/// Default maximum amount of the sponsored transactions admitted per period.
+pub const DEFAULT_SPONSORED_TX_BUDGET: u64 = 1000;
+/// Default period (in seconds) after which the budget of the sponsored transactions is renewed.
+pub const DEFAULT_SPONSORED_TX_PERIOD: u64 = 60 * 60 * 24;
+/// Default maximum amount of the transactions sponsored by type for a single sender per period.
+pub const DEFAULT_SPONSORED_TX_MAX_PER_SENDER: u64 = 10;
+/// Default percentile of the recent priority fees used for the EIP-1559 transactions.
+pub const DEFAULT_PRIORITY_FEE_PERCENTILE: f64 = 50.0;
+/// Default balance of the operator account (in wei) below which the next operator
+/// account is used to send the transactions.
+pub const DEFAULT_OPERATOR_MIN_BALANCE: u64 = 1_000_000_000_000_000_000;
+/// Default balance of the operator account (in wei) below which the new blocks
+/// are not proposed anymore.
+pub const DEFAULT_OPERATOR_HALT_BALANCE: u64 = 100_000_000_000_000_000;
+/// Default amount of Ethereum blocks after the confirmation during which the confirmed
+/// `eth_sender` transactions are checked for being removed by a chain reorganization.
+pub const DEFAULT_REORG_WATCH_BLOCKS: u64 = 12;
+/// Default margin (in percent) added to the gas limit estimated by the Ethereum node
+/// for the `eth_sender` transactions.
+pub const DEFAULT_GAS_LIMIT_SAFETY_MARGIN: u64 = 20;
+/// Default maximum amount of the blocks committed by `eth_sender` ahead of the verified ones.
+pub const DEFAULT_MAX_UNVERIFIED_BLOCKS: u64 = 32;
+/// Whether the proofs received from the provers are verified by default.
+pub const DEFAULT_PROVER_VERIFY_PROOFS: bool = true;
+/// Default time (in seconds) after which the committed block is proved before the blocks
+/// with withdrawals, if the `withdrawals_first` prover job policy is used.
+pub const DEFAULT_PROVER_JOB_MAX_AGE: u64 = 600;
+/// Whether the provers publish the mock proofs by default.
+pub const DEFAULT_PROVER_MOCK_PROOFS: bool = false;
+/// Whether the prover API requires the provers to authenticate by default.
+pub const DEFAULT_PROVER_API_AUTH: bool = false;
+/// Default amount of threads generating the block witnesses, `0` means the number of CPUs.
+pub const DEFAULT_PROVER_WITNESS_THREADS: usize = 0;
+/// Default share of the failed and timed out jobs above which the prover is deprioritized.
+pub const DEFAULT_PROVER_MAX_FAILURE_RATE: f64 = 0.3;
+/// Default address of the exit proof server.
+pub const DEFAULT_EXIT_PROOF_SERVER_BIND: &str = "0.0.0.0:3050";
+/// Minimum length of the admin API secret, so it can't be guessed by brute force.
+pub const MIN_ADMIN_API_SECRET_LENGTH: usize = 32;
+
 /// If its placed inside thread::spawn closure it will notify channel when this thread panics.
 pub struct ThreadPanicNotify(pub mpsc::Sender<bool>);
 
@@ -53,14 +110,113 @@ where
         .unwrap_or_else(|e| panic!("Failed to parse environment variable {}: {:?}", name, e))
 }
 
+/// Settings of the known Ethereum network, loaded from the `etc/networks/<ETH_NETWORK>.json`
+/// file. Settings provided via the environment variables take precedence over the profile,
+/// except for the chain id, which must match the profile one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NetworkProfile {
+    pub chain_id: u8,
+    /// Address of the zkSync contract, if it is deployed to the network once and for all.
+    pub contract_addr: Option<H160>,
+    /// Address of the governance contract, if it is deployed to the network once and for all.
+    pub governance_addr: Option<H160>,
+    /// Address of the upgrade gatekeeper contract, if it is deployed to the network
+    /// once and for all.
+    pub upgrade_gatekeeper_addr: Option<H160>,
+    /// Amount of confirmations required to process the Ethereum events.
+    pub confirmations_for_eth_event: Option<u64>,
+    /// Amount of confirmations required to consider the `eth_sender` transaction final.
+    pub wait_confirmations: Option<u64>,
+}
+
+impl NetworkProfile {
+    /// Loads the profile of the network. Returns `None` if there is no profile for the network
+    /// (or `ZKSYNC_HOME` is not set), i.e. it's configured with the environment variables only.
+    /// Panics if the profile exists, but cannot be parsed.
+    pub fn load(network: &str) -> Option<Self> {
+        let mut file_path = PathBuf::from(env::var("ZKSYNC_HOME").ok()?);
+        file_path.push("etc");
+        file_path.push("networks");
+        file_path.push(network);
+        file_path.set_extension("json");
+        if !file_path.exists() {
+            return None;
+        }
+
+        let profile = read_to_string(&file_path)
+            .map_err(failure::Error::from)
+            .and_then(|profile| Ok(serde_json::from_str(&profile)?))
+            .unwrap_or_else(|e| panic!("Failed to load the network profile {}: {}", network, e));
+        Some(profile)
+    }
+}
+
+/// Obtains the setting value from the network profile, if it's not provided
+/// with the environment variable.
+/// Panics if neither the environment variable nor the network profile provides the value.
+fn env_or_profile<T, F>(name: &str, profile: &Option<NetworkProfile>, profile_value: F) -> T
+where
+    T: FromStr,
+    T::Err: std::fmt::Debug,
+    F: FnOnce(&NetworkProfile) -> Option<T>,
+{
+    if env::var(name).is_ok() {
+        return parse_env_with(name, |s| s.trim_start_matches("0x"));
+    }
+    profile.as_ref().and_then(profile_value).unwrap_or_else(|| {
+        panic!(
+            "Env var {} missing and not set by the network profile",
+            name
+        )
+    })
+}
+
+/// Obtains the chain id from the network profile or the `CHAIN_ID` environment variable.
+/// Panics if the chain id is provided with both of them, and the values differ.
+fn chain_id_from_env(profile: &Option<NetworkProfile>) -> u8 {
+    let chain_id = env_or_profile("CHAIN_ID", profile, |profile| Some(profile.chain_id));
+    if let Some(profile) = profile {
+        assert_eq!(
+            chain_id, profile.chain_id,
+            "CHAIN_ID doesn't match the chain id of the network profile"
+        );
+    }
+    chain_id
+}
+
 /// Configuration options for `eth_sender`.
 #[derive(Debug, Clone)]
 pub struct EthSenderOptions {
     pub expected_wait_time_block: u64,
     pub tx_poll_period: Duration,
+    /// Amount of confirmations required to consider the transaction final.
     pub wait_confirmations: u64,
+    /// Amount of blocks after the confirmation during which the transaction is checked
+    /// to still be included in the chain. Zero disables the reorg handling.
+    pub reorg_watch_blocks: u64,
     pub max_txs_in_flight: u64,
     pub is_enabled: bool,
+    /// Balance of the operator account (in wei) below which the operator is alerted
+    /// and the next operator account is used to send the transactions.
+    pub min_operator_balance: U256,
+    /// Balance of the operator account (in wei) below which the block production is halted
+    /// until the account is topped up or replaced.
+    pub halt_operator_balance: U256,
+    /// Margin (in percent) added to the gas limit estimated by the Ethereum node, which
+    /// is used instead of the precalculated gas limit if it's greater.
+    pub gas_limit_safety_margin: u64,
+    /// Whether the `verify` operation should be sent right after the `commit` operation
+    /// of the same block if the proof is ready by then, regardless of `max_txs_in_flight`.
+    pub send_verify_with_commit: bool,
+    /// Maximum amount of the blocks which are committed while their verification is not sent,
+    /// i.e. how far the commits may run ahead of the proofs.
+    pub max_unverified_blocks: u64,
+    /// Maximum amount of the transactions (including the resent ones) sent within an hour.
+    /// Transactions beyond the limit are deferred. No limit if not set.
+    pub max_txs_per_hour: Option<u64>,
+    /// Maximum gas price (in wei) the transactions are ever sent with. New transactions are
+    /// deferred while the network gas price is higher. No limit if not set.
+    pub max_gas_price: Option<U256>,
 }
 
 impl EthSenderOptions {
@@ -68,13 +224,92 @@ impl EthSenderOptions {
     /// Panics if any of options is missing or has inappropriate value.
     pub fn from_env() -> Self {
         let tx_poll_period_secs: u64 = parse_env("ETH_TX_POLL_PERIOD");
+        let network_profile = NetworkProfile::load(&get_env("ETH_NETWORK"));
 
         Self {
             expected_wait_time_block: parse_env("ETH_EXPECTED_WAIT_TIME_BLOCK"),
             tx_poll_period: Duration::new(tx_poll_period_secs, 0),
-            wait_confirmations: parse_env("ETH_WAIT_CONFIRMATIONS"),
+            wait_confirmations: env_or_profile("ETH_WAIT_CONFIRMATIONS", &network_profile, |p| {
+                p.wait_confirmations
+            }),
+            reorg_watch_blocks: if env::var("ETH_REORG_WATCH_BLOCKS").is_ok() {
+                parse_env("ETH_REORG_WATCH_BLOCKS")
+            } else {
+                DEFAULT_REORG_WATCH_BLOCKS
+            },
             max_txs_in_flight: parse_env("ETH_MAX_TXS_IN_FLIGHT"),
             is_enabled: parse_env("ETH_IS_ENABLED"),
+            min_operator_balance: if env::var("ETH_OPERATOR_MIN_BALANCE").is_ok() {
+                U256::from_dec_str(&get_env("ETH_OPERATOR_MIN_BALANCE"))
+                    .expect("Failed to parse environment variable ETH_OPERATOR_MIN_BALANCE")
+            } else {
+                DEFAULT_OPERATOR_MIN_BALANCE.into()
+            },
+            halt_operator_balance: if env::var("ETH_OPERATOR_HALT_BALANCE").is_ok() {
+                U256::from_dec_str(&get_env("ETH_OPERATOR_HALT_BALANCE"))
+                    .expect("Failed to parse environment variable ETH_OPERATOR_HALT_BALANCE")
+            } else {
+                DEFAULT_OPERATOR_HALT_BALANCE.into()
+            },
+            gas_limit_safety_margin: if env::var("ETH_GAS_LIMIT_SAFETY_MARGIN").is_ok() {
+                parse_env("ETH_GAS_LIMIT_SAFETY_MARGIN")
+            } else {
+                DEFAULT_GAS_LIMIT_SAFETY_MARGIN
+            },
+            send_verify_with_commit: if env::var("ETH_SEND_VERIFY_WITH_COMMIT").is_ok() {
+                parse_env("ETH_SEND_VERIFY_WITH_COMMIT")
+            } else {
+                false
+            },
+            max_unverified_blocks: if env::var("ETH_MAX_UNVERIFIED_BLOCKS").is_ok() {
+                parse_env("ETH_MAX_UNVERIFIED_BLOCKS")
+            } else {
+                DEFAULT_MAX_UNVERIFIED_BLOCKS
+            },
+            max_txs_per_hour: if env::var("ETH_MAX_TXS_PER_HOUR").is_ok() {
+                Some(parse_env("ETH_MAX_TXS_PER_HOUR"))
+            } else {
+                None
+            },
+            max_gas_price: if env::var("ETH_MAX_GAS_PRICE_GWEI").is_ok() {
+                let max_gas_price_gwei: u64 = parse_env("ETH_MAX_GAS_PRICE_GWEI");
+                Some(U256::from(max_gas_price_gwei) * U256::exp10(9))
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// Order in which the committed blocks are given to the provers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProverJobPolicy {
+    /// Blocks are proved in the order of commitment.
+    Fifo,
+    /// Blocks containing withdrawals are proved first, so the exits are finalized sooner.
+    /// Blocks waiting longer than `max_age` are proved before them to avoid the starvation,
+    /// since the blocks can be verified on Ethereum only in order.
+    WithdrawalsFirst { max_age: Duration },
+}
+
+impl ProverJobPolicy {
+    /// Parses the policy from `PROVER_JOB_POLICY` (`fifo` or `withdrawals_first`)
+    /// and `PROVER_JOB_MAX_AGE` (in seconds). FIFO is used by default.
+    pub fn from_env() -> Self {
+        let policy = env::var("PROVER_JOB_POLICY").unwrap_or_else(|_| "fifo".to_string());
+        match policy.as_str() {
+            "fifo" => ProverJobPolicy::Fifo,
+            "withdrawals_first" => {
+                let max_age = if env::var("PROVER_JOB_MAX_AGE").is_ok() {
+                    parse_env("PROVER_JOB_MAX_AGE")
+                } else {
+                    DEFAULT_PROVER_JOB_MAX_AGE
+                };
+                ProverJobPolicy::WithdrawalsFirst {
+                    max_age: Duration::from_secs(max_age),
+                }
+            }
+            _ => panic!("Unknown prover job policy: {}", policy),
         }
     }
 }
@@ -85,6 +320,22 @@ pub struct ProverOptions {
     pub heartbeat_interval: Duration,
     pub cycle_wait: Duration,
     pub gone_timeout: Duration,
+    /// Whether the server verifies the received proofs before sending them to the contract.
+    pub verify_proofs: bool,
+    pub job_policy: ProverJobPolicy,
+    /// Whether the provers must authenticate with the tokens issued via the admin API.
+    pub api_auth: bool,
+    /// Whether the provers publish the deterministic mock proofs instead of the real ones.
+    /// Mock proofs are accepted only by the dev verifier contract.
+    pub mock_proofs: bool,
+    /// Version of the circuit the blocks are proposed and proved under.
+    /// Provers prove only the blocks proposed under their version of the circuit.
+    pub circuit_version: String,
+    /// Amount of threads generating the block witnesses, `0` means the number of CPUs.
+    pub witness_threads: usize,
+    /// Share of the failed and timed out jobs above which the prover is considered unreliable
+    /// and gets the least urgent blocks.
+    pub max_failure_rate: f64,
 }
 
 impl ProverOptions {
@@ -96,12 +347,263 @@ impl ProverOptions {
         let heartbeat_interval = Duration::from_millis(parse_env("PROVER_HEARTBEAT_INTERVAL"));
         let cycle_wait = Duration::from_millis(parse_env("PROVER_CYCLE_WAIT"));
         let gone_timeout = Duration::from_millis(parse_env("PROVER_GONE_TIMEOUT"));
+        let verify_proofs = if env::var("PROVER_VERIFY_PROOFS").is_ok() {
+            parse_env("PROVER_VERIFY_PROOFS")
+        } else {
+            DEFAULT_PROVER_VERIFY_PROOFS
+        };
+        let mock_proofs = if env::var("PROVER_MOCK_PROOFS").is_ok() {
+            parse_env("PROVER_MOCK_PROOFS")
+        } else {
+            DEFAULT_PROVER_MOCK_PROOFS
+        };
+        let api_auth = if env::var("PROVER_API_AUTH").is_ok() {
+            parse_env("PROVER_API_AUTH")
+        } else {
+            DEFAULT_PROVER_API_AUTH
+        };
+        let witness_threads = if env::var("PROVER_WITNESS_THREADS").is_ok() {
+            parse_env("PROVER_WITNESS_THREADS")
+        } else {
+            DEFAULT_PROVER_WITNESS_THREADS
+        };
+        let max_failure_rate = if env::var("PROVER_MAX_FAILURE_RATE").is_ok() {
+            parse_env("PROVER_MAX_FAILURE_RATE")
+        } else {
+            DEFAULT_PROVER_MAX_FAILURE_RATE
+        };
+        // Keys are generated for the certain circuit, so the keys directory identifies it.
+        let circuit_version = if env::var("CIRCUIT_VERSION").is_ok() {
+            get_env("CIRCUIT_VERSION")
+        } else {
+            PathBuf::from(get_env("KEY_DIR"))
+                .file_name()
+                .expect("KEY_DIR has no directory name")
+                .to_string_lossy()
+                .to_string()
+        };
 
         Self {
             prepare_data_interval,
             heartbeat_interval,
             cycle_wait,
             gone_timeout,
+            verify_proofs,
+            job_policy: ProverJobPolicy::from_env(),
+            api_auth,
+            mock_proofs,
+            circuit_version,
+            witness_threads,
+            max_failure_rate,
+        }
+    }
+}
+
+/// Types of the transactions which can be sponsored, i.e. the ones paying the fee.
+pub const SPONSORED_TX_TYPES: [&str; 2] = ["Transfer", "Withdraw"];
+
+/// Transactions sponsored by the operator: they're admitted with the fee below the required
+/// one (e.g. zero fee), while the budget of the current period is not exhausted.
+#[derive(Debug, Clone)]
+pub struct SponsoredTxsOptions {
+    /// Senders whose transactions are sponsored.
+    pub senders: Vec<H160>,
+    /// Types of the sponsored transactions (e.g. `Transfer`), sponsored for any sender.
+    pub tx_types: Vec<String>,
+    /// Maximum amount of the sponsored transactions admitted per period.
+    pub budget: u64,
+    /// Period after which the budget is renewed.
+    pub period: Duration,
+    /// Maximum amount of the transactions sponsored by type for a single sender per period,
+    /// so a single sender can't exhaust the budget. Allowlisted senders are not limited.
+    pub max_per_sender: u64,
+}
+
+impl SponsoredTxsOptions {
+    /// Parses the sponsored transactions options from the environment variables.
+    /// Returns `None` if neither the senders (`SPONSORED_TX_SENDERS`) nor the transaction
+    /// types (`SPONSORED_TX_TYPES`) are allowlisted.
+    pub fn from_env() -> Option<Self> {
+        let list = |name: &str| -> Vec<String> {
+            if env::var(name).is_err() {
+                return Vec::new();
+            }
+            get_env(name)
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        };
+
+        let senders: Vec<H160> = list("SPONSORED_TX_SENDERS")
+            .into_iter()
+            .map(|address| {
+                address
+                    .trim_start_matches("0x")
+                    .parse()
+                    .expect("Failed to parse the sponsored sender address")
+            })
+            .collect();
+        let tx_types = list("SPONSORED_TX_TYPES");
+        // Misspelled type would silently sponsor nothing.
+        for tx_type in &tx_types {
+            if !SPONSORED_TX_TYPES.contains(&tx_type.as_str()) {
+                panic!(
+                    "Unknown transaction type in SPONSORED_TX_TYPES: {}, expected one of: {}",
+                    tx_type,
+                    SPONSORED_TX_TYPES.join(", ")
+                );
+            }
+        }
+        if senders.is_empty() && tx_types.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            senders,
+            tx_types,
+            budget: if env::var("SPONSORED_TX_BUDGET").is_ok() {
+                parse_env("SPONSORED_TX_BUDGET")
+            } else {
+                DEFAULT_SPONSORED_TX_BUDGET
+            },
+            period: Duration::from_secs(if env::var("SPONSORED_TX_PERIOD").is_ok() {
+                parse_env("SPONSORED_TX_PERIOD")
+            } else {
+                DEFAULT_SPONSORED_TX_PERIOD
+            }),
+            max_per_sender: if env::var("SPONSORED_TX_MAX_PER_SENDER").is_ok() {
+                parse_env("SPONSORED_TX_MAX_PER_SENDER")
+            } else {
+                DEFAULT_SPONSORED_TX_MAX_PER_SENDER
+            },
+        })
+    }
+}
+
+/// Configuration options for the admin API.
+#[derive(Clone)]
+pub struct AdminApiOptions {
+    pub bind_address: SocketAddr,
+    /// Secret which must be provided in the `Authorization` header of the requests.
+    pub secret: String,
+}
+
+impl AdminApiOptions {
+    /// Parses the admin API options from the environment variables.
+    /// Returns `None` if the admin API is disabled (`ADMIN_API_BIND` is not set).
+    /// Panics if the secret is missing or shorter than `MIN_ADMIN_API_SECRET_LENGTH`
+    /// while the admin API is enabled.
+    pub fn from_env() -> Option<Self> {
+        if env::var("ADMIN_API_BIND").is_err() {
+            return None;
+        }
+        let secret = get_env("ADMIN_API_SECRET");
+        if secret.trim().len() < MIN_ADMIN_API_SECRET_LENGTH {
+            panic!(
+                "ADMIN_API_SECRET must be at least {} characters long",
+                MIN_ADMIN_API_SECRET_LENGTH
+            );
+        }
+        Some(Self {
+            bind_address: parse_env("ADMIN_API_BIND"),
+            secret,
+        })
+    }
+}
+
+impl std::fmt::Debug for AdminApiOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminApiOptions")
+            .field("bind_address", &self.bind_address)
+            .field("secret", &"<hidden>")
+            .finish()
+    }
+}
+
+/// Triggers of the pending block sealing by the state keeper.
+/// Block is sealed once any of the triggers fires, or once it's full.
+#[derive(Debug, Clone)]
+pub struct BlockSealingOptions {
+    /// Interval between the miniblocks (batches of the mempool transactions)
+    /// proposed to the state keeper.
+    pub miniblock_interval: Duration,
+    /// Max number of miniblocks in one block.
+    pub max_miniblock_iterations: usize,
+    /// Max number of miniblocks for block with withdraw operations
+    /// (defaults to `max_minblock_iterations`).
+    pub max_miniblock_iterations_withdraw_block: usize,
+    /// Max time since the first operation was added to the block after which the block
+    /// is sealed regardless of its fullness. If not set, only miniblock iterations are limited.
+    pub block_seal_deadline: Option<Duration>,
+    /// Share of the largest block chunks (from `0` to `1`) after filling which the block
+    /// is sealed without waiting for the other triggers. If not set, only full blocks are sealed
+    /// on the filling.
+    pub min_chunk_fill_ratio: Option<f64>,
+    /// Whether the block containing a withdrawal is sealed right away, so the withdrawals
+    /// are completed as soon as possible.
+    pub seal_on_withdrawal: bool,
+    /// Idle time after which an empty block is sealed, so the commit/verify cadence is kept
+    /// steady when there are no transactions. If not set, empty blocks are never created.
+    pub padding_block_interval: Option<Duration>,
+    /// Amount of Ethereum blocks before the priority operation expiration at which
+    /// the pending block containing it is sealed without waiting for other operations.
+    pub priority_op_deadline_margin: u64,
+}
+
+impl BlockSealingOptions {
+    /// Parses the block sealing options from the environment variables.
+    /// Panics if `MINIBLOCKS_ITERATIONS` is missing or any of options has inappropriate value.
+    pub fn from_env() -> Self {
+        let max_miniblock_iterations_withdraw_block =
+            if env::var("WITHDRAW_BLOCK_MINIBLOCKS_ITERATIONS").is_ok() {
+                parse_env("WITHDRAW_BLOCK_MINIBLOCKS_ITERATIONS")
+            } else {
+                parse_env("MINIBLOCKS_ITERATIONS")
+            };
+        let min_chunk_fill_ratio = if env::var("BLOCK_SEAL_MIN_FILL_RATIO").is_ok() {
+            let ratio: f64 = parse_env("BLOCK_SEAL_MIN_FILL_RATIO");
+            assert!(
+                ratio > 0.0 && ratio <= 1.0,
+                "BLOCK_SEAL_MIN_FILL_RATIO must be in (0, 1]"
+            );
+            Some(ratio)
+        } else {
+            None
+        };
+        Self {
+            miniblock_interval: Duration::from_millis(
+                if env::var("MINIBLOCK_INTERVAL_MS").is_ok() {
+                    parse_env("MINIBLOCK_INTERVAL_MS")
+                } else {
+                    DEFAULT_MINIBLOCK_INTERVAL_MS
+                },
+            ),
+            max_miniblock_iterations: parse_env("MINIBLOCKS_ITERATIONS"),
+            max_miniblock_iterations_withdraw_block,
+            block_seal_deadline: if env::var("BLOCK_SEAL_DEADLINE_MS").is_ok() {
+                Some(Duration::from_millis(parse_env("BLOCK_SEAL_DEADLINE_MS")))
+            } else {
+                None
+            },
+            min_chunk_fill_ratio,
+            seal_on_withdrawal: if env::var("BLOCK_SEAL_ON_WITHDRAWAL").is_ok() {
+                parse_env("BLOCK_SEAL_ON_WITHDRAWAL")
+            } else {
+                false
+            },
+            padding_block_interval: if env::var("PADDING_BLOCK_INTERVAL_MS").is_ok() {
+                Some(Duration::from_millis(parse_env(
+                    "PADDING_BLOCK_INTERVAL_MS",
+                )))
+            } else {
+                None
+            },
+            priority_op_deadline_margin: if env::var("PRIORITY_OP_DEADLINE_MARGIN").is_ok() {
+                parse_env("PRIORITY_OP_DEADLINE_MARGIN")
+            } else {
+                DEFAULT_PRIORITY_OP_DEADLINE_MARGIN
+            },
         }
     }
 }
@@ -112,27 +614,65 @@ pub struct ConfigurationOptions {
     pub json_rpc_http_server_address: SocketAddr,
     pub json_rpc_ws_server_address: SocketAddr,
     pub web3_url: String,
+    /// Ethereum node endpoints used once `web3_url` is unavailable, in the order of preference.
+    pub web3_fallback_urls: Vec<String>,
     pub genesis_tx_hash: H256,
     pub contract_eth_addr: H160,
     pub governance_eth_addr: H160,
+    pub upgrade_gatekeeper_eth_addr: H160,
     pub operator_fee_eth_addr: H160,
     pub operator_commit_eth_addr: H160,
     pub operator_private_key: Option<H256>,
+    /// Reserve operator accounts (address and private key), which are used to send
+    /// the Ethereum transactions once the main operator account is drained.
+    /// Private keys are not required if the remote signer is used.
+    pub operator_reserve_accounts: Vec<(H160, Option<H256>)>,
+    /// URL of the remote signer service (`eth_signTransaction` JSON RPC), which signs
+    /// the operator transactions instead of the private keys stored on the server.
+    pub operator_remote_signer_url: Option<String>,
     pub chain_id: u8,
     pub gas_price_factor: f64,
+    /// If `true`, the EIP-1559 (type 2) transactions are sent to Ethereum instead of
+    /// the legacy ones. Should be enabled only for the networks supporting them.
+    pub eip1559_enabled: bool,
+    /// Percentile of the priority fees paid in the recent blocks, which is used as
+    /// the priority fee of the EIP-1559 transactions.
+    pub priority_fee_percentile: f64,
     pub prover_server_address: SocketAddr,
     pub confirmations_for_eth_event: u64,
     pub api_requests_caches_size: usize,
     pub available_block_chunk_sizes: Vec<usize>,
     pub eth_watch_poll_interval: Duration,
+    /// WebSocket endpoint of the Ethereum node. If set, `eth_watch` is notified about the new
+    /// blocks via the `newHeads` subscription, and polls the node only if the subscription fails.
+    pub web3_ws_url: Option<String>,
     pub eth_network: String,
     pub ticker_url: Url,
     pub idle_provers: u32,
-    /// Max number of miniblocks (produced every period of `TX_MINIBATCH_CREATE_TIME`) if one block.
-    pub max_miniblock_iterations: usize,
-    /// Max number of miniblocks for block with withdraw operations (defaults to `max_minblock_iterations`).
-    pub max_miniblock_iterations_withdraw_block: usize,
+    pub block_sealing: BlockSealingOptions,
     pub prometheus_export_port: u16,
+    /// If `true`, API server receives the new block notifications through the database
+    /// (`LISTEN`/`NOTIFY`) rather than directly from the server core.
+    pub api_notifications_via_db: bool,
+    /// Maximum amount of transactions stored in the mempool.
+    pub mempool_capacity: usize,
+    /// Minimum fee accepted for the transfers, as a percentage of the fee model estimation.
+    pub min_transfer_fee_percent: u32,
+    /// Minimum fee accepted for the withdrawals, as a percentage of the fee model estimation.
+    pub min_withdraw_fee_percent: u32,
+    /// Address of the liquidity provider serving the fast withdrawals.
+    /// If not set, fast withdrawals are not accepted.
+    pub fast_withdrawal_lp_address: Option<H160>,
+    /// Transactions admitted with zero fee, `None` if no transactions are sponsored.
+    pub sponsored_txs: Option<SponsoredTxsOptions>,
+    /// Amount of sealed blocks between the stored checkpoints of the committed state,
+    /// which speed up the state restoring on the server start. `0` disables the checkpoints.
+    pub state_checkpoint_interval: u32,
+    /// Maximum time between the priority operation confirmation and its execution,
+    /// the operator is alerted once it's exceeded.
+    pub priority_op_sla: Duration,
+    /// Admin API options, `None` if the admin API is disabled.
+    pub admin_api: Option<AdminApiOptions>,
 }
 
 impl ConfigurationOptions {
@@ -142,21 +682,34 @@ impl ConfigurationOptions {
         let mut available_block_chunk_sizes = block_chunk_sizes().to_vec();
         available_block_chunk_sizes.sort();
 
-        let max_miniblock_iterations_withdraw_block =
-            if env::var("WITHDRAW_BLOCK_MINIBLOCKS_ITERATIONS").is_ok() {
-                parse_env("WITHDRAW_BLOCK_MINIBLOCKS_ITERATIONS")
-            } else {
-                parse_env("MINIBLOCKS_ITERATIONS")
-            };
+        let eth_network: String = parse_env("ETH_NETWORK");
+        let network_profile = NetworkProfile::load(&eth_network);
 
         Self {
             rest_api_server_address: parse_env("REST_API_BIND"),
             json_rpc_http_server_address: parse_env("HTTP_RPC_API_BIND"),
             json_rpc_ws_server_address: parse_env("WS_API_BIND"),
             web3_url: get_env("WEB3_URL"),
+            web3_fallback_urls: if env::var("WEB3_FALLBACK_URLS").is_ok() {
+                get_env("WEB3_FALLBACK_URLS")
+                    .split(',')
+                    .map(|url| url.trim().to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            },
             genesis_tx_hash: parse_env_with("GENESIS_TX_HASH", |s| &s[2..]),
-            contract_eth_addr: parse_env_with("CONTRACT_ADDR", |s| &s[2..]),
-            governance_eth_addr: parse_env_with("GOVERNANCE_ADDR", |s| &s[2..]),
+            contract_eth_addr: env_or_profile("CONTRACT_ADDR", &network_profile, |p| {
+                p.contract_addr
+            }),
+            governance_eth_addr: env_or_profile("GOVERNANCE_ADDR", &network_profile, |p| {
+                p.governance_addr
+            }),
+            upgrade_gatekeeper_eth_addr: env_or_profile(
+                "UPGRADE_GATEKEEPER_ADDR",
+                &network_profile,
+                |p| p.upgrade_gatekeeper_addr,
+            ),
             operator_commit_eth_addr: parse_env_with("OPERATOR_COMMIT_ETH_ADDRESS", |s| &s[2..]),
             operator_fee_eth_addr: parse_env_with("OPERATOR_FEE_ETH_ADDRESS", |s| &s[2..]),
             operator_private_key: if env::var("OPERATOR_PRIVATE_KEY").is_ok() {
@@ -164,25 +717,133 @@ impl ConfigurationOptions {
             } else {
                 None
             },
-            chain_id: parse_env("CHAIN_ID"),
+            operator_reserve_accounts: operator_reserve_accounts_from_env(),
+            operator_remote_signer_url: if env::var("OPERATOR_REMOTE_SIGNER_URL").is_ok() {
+                Some(get_env("OPERATOR_REMOTE_SIGNER_URL"))
+            } else {
+                None
+            },
+            chain_id: chain_id_from_env(&network_profile),
             gas_price_factor: parse_env("GAS_PRICE_FACTOR"),
+            eip1559_enabled: if env::var("ETH_EIP1559_ENABLED").is_ok() {
+                parse_env("ETH_EIP1559_ENABLED")
+            } else {
+                false
+            },
+            priority_fee_percentile: if env::var("ETH_PRIORITY_FEE_PERCENTILE").is_ok() {
+                parse_env("ETH_PRIORITY_FEE_PERCENTILE")
+            } else {
+                DEFAULT_PRIORITY_FEE_PERCENTILE
+            },
             prover_server_address: parse_env("PROVER_SERVER_BIND"),
-            confirmations_for_eth_event: parse_env("CONFIRMATIONS_FOR_ETH_EVENT"),
+            confirmations_for_eth_event: env_or_profile(
+                "CONFIRMATIONS_FOR_ETH_EVENT",
+                &network_profile,
+                |p| p.confirmations_for_eth_event,
+            ),
             api_requests_caches_size: parse_env("API_REQUESTS_CACHES_SIZE"),
             available_block_chunk_sizes,
             eth_watch_poll_interval: Duration::from_millis(parse_env::<u64>(
                 "ETH_WATCH_POLL_INTERVAL",
             )),
-            eth_network: parse_env("ETH_NETWORK"),
+            web3_ws_url: if env::var("WEB3_WS_URL").is_ok() {
+                Some(get_env("WEB3_WS_URL"))
+            } else {
+                None
+            },
+            eth_network,
             ticker_url: parse_env("TICKER_URL"),
             idle_provers: parse_env("IDLE_PROVERS"),
-            max_miniblock_iterations: parse_env("MINIBLOCKS_ITERATIONS"),
-            max_miniblock_iterations_withdraw_block,
+            block_sealing: BlockSealingOptions::from_env(),
             prometheus_export_port: parse_env("PROMETHEUS_EXPORT_PORT"),
+            api_notifications_via_db: if env::var("API_NOTIFICATIONS_VIA_DB").is_ok() {
+                parse_env("API_NOTIFICATIONS_VIA_DB")
+            } else {
+                false
+            },
+            mempool_capacity: if env::var("MEMPOOL_CAPACITY").is_ok() {
+                parse_env("MEMPOOL_CAPACITY")
+            } else {
+                DEFAULT_MEMPOOL_CAPACITY
+            },
+            min_transfer_fee_percent: if env::var("MIN_TRANSFER_FEE_PERCENT").is_ok() {
+                parse_env("MIN_TRANSFER_FEE_PERCENT")
+            } else {
+                DEFAULT_MIN_FEE_PERCENT
+            },
+            min_withdraw_fee_percent: if env::var("MIN_WITHDRAW_FEE_PERCENT").is_ok() {
+                parse_env("MIN_WITHDRAW_FEE_PERCENT")
+            } else {
+                DEFAULT_MIN_FEE_PERCENT
+            },
+            fast_withdrawal_lp_address: if env::var("FAST_WITHDRAWAL_LP_ADDRESS").is_ok() {
+                Some(parse_env_with("FAST_WITHDRAWAL_LP_ADDRESS", |s| &s[2..]))
+            } else {
+                None
+            },
+            sponsored_txs: SponsoredTxsOptions::from_env(),
+            state_checkpoint_interval: if env::var("STATE_CHECKPOINT_INTERVAL").is_ok() {
+                parse_env("STATE_CHECKPOINT_INTERVAL")
+            } else {
+                DEFAULT_STATE_CHECKPOINT_INTERVAL
+            },
+            priority_op_sla: Duration::from_secs(if env::var("PRIORITY_OP_SLA").is_ok() {
+                parse_env("PRIORITY_OP_SLA")
+            } else {
+                DEFAULT_PRIORITY_OP_SLA
+            }),
+            admin_api: AdminApiOptions::from_env(),
         }
     }
 }
 
+/// Parses the reserve operator accounts from the comma-separated lists of the addresses
+/// (`OPERATOR_RESERVE_ETH_ADDRESSES`) and the private keys (`OPERATOR_RESERVE_PRIVATE_KEYS`).
+/// Private keys may be omitted if the remote signer is used.
+/// Panics if the lists have different lengths or contain inappropriate values.
+fn operator_reserve_accounts_from_env() -> Vec<(H160, Option<H256>)> {
+    if env::var("OPERATOR_RESERVE_ETH_ADDRESSES").is_err() {
+        return Vec::new();
+    }
+
+    let addresses: Vec<H160> = get_env("OPERATOR_RESERVE_ETH_ADDRESSES")
+        .split(',')
+        .map(|address| {
+            address
+                .trim()
+                .trim_start_matches("0x")
+                .parse()
+                .expect("Failed to parse the reserve operator address")
+        })
+        .collect();
+    if env::var("OPERATOR_RESERVE_PRIVATE_KEYS").is_err() {
+        return addresses
+            .into_iter()
+            .map(|address| (address, None))
+            .collect();
+    }
+
+    let private_keys: Vec<H256> = get_env("OPERATOR_RESERVE_PRIVATE_KEYS")
+        .split(',')
+        .map(|key| {
+            key.trim()
+                .trim_start_matches("0x")
+                .parse()
+                .expect("Failed to parse the reserve operator private key")
+        })
+        .collect();
+    assert_eq!(
+        addresses.len(),
+        private_keys.len(),
+        "Amounts of the reserve operator addresses and private keys mismatch"
+    );
+
+    addresses
+        .into_iter()
+        .zip(private_keys.into_iter().map(Some))
+        .collect()
+}
+
 /// Possible block chunks sizes and corresponding setup powers of two,
 /// this is only parameters needed to create verifying contract.
 #[derive(Debug)]
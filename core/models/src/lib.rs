@@ -14,22 +14,27 @@ pub mod params;
 pub mod primitives;
 pub mod prover_utils;
 pub mod serialization;
+#[cfg(any(test, feature = "test_utils"))]
+pub mod test_utils;
 
 // TODO: refactor, find new home for all this stuff
 
 pub use crypto_exports::franklin_crypto;
 pub use crypto_exports::rand;
 
+use crate::abi::erc20_detailed_contract;
 use crate::node::block::{Block, PendingBlock};
 use crate::node::BlockNumber;
-use crate::node::{AccountUpdates, TokenId};
+use crate::node::{AccountMap, AccountUpdates, Token, TokenId};
 use crate::prover_utils::EncodedProofPlonk;
 
 use failure::format_err;
 use franklin_crypto::bellman::pairing::ff::{PrimeField, PrimeFieldRepr};
-use futures::channel::oneshot;
+use futures::{channel::oneshot, compat::Future01CompatExt};
 use std::convert::TryFrom;
+use web3::contract::{Contract, Options};
 use web3::types::{Address, Log, U256};
+use web3::{Transport, Web3};
 
 /// Converts the field element into a byte array.
 pub fn fe_to_bytes<F: PrimeField>(value: &F) -> Vec<u8> {
@@ -148,6 +153,8 @@ pub struct Operation {
 pub enum CommitRequest {
     PendingBlock(PendingBlock, oneshot::Sender<()>),
     Block(BlockCommitRequest, oneshot::Sender<()>),
+    /// Checkpoint of the state as of the sealed block with the provided number.
+    StateCheckpoint(BlockNumber, AccountMap),
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -195,6 +202,48 @@ pub struct NewTokenEvent {
     pub id: TokenId,
 }
 
+impl NewTokenEvent {
+    /// Creates the token added by the event, with the precision reported by the token contract.
+    ///
+    /// `decimals` is optional for ERC20 tokens, so if it can't be queried, the default precision
+    /// is assumed. Symbol is not queried: it's chosen by the token creator, so it may impersonate
+    /// another token, so the placeholder symbol is stored, which can be updated by the operator.
+    pub async fn resolve_token<T: Transport>(&self, web3: &Web3<T>) -> Token {
+        let contract = Contract::new(web3.eth(), self.address, erc20_detailed_contract());
+        let decimals: Result<U256, _> = contract
+            .query("decimals", (), None, Options::default(), None)
+            .compat()
+            .await;
+        let decimals = match decimals {
+            Ok(decimals) if decimals <= U256::from(u8::max_value()) => decimals.as_u32() as u8,
+            Ok(decimals) => {
+                warn!(
+                    "Token {:?} reports invalid decimals {}, assuming {}",
+                    self.address,
+                    decimals,
+                    Token::DEFAULT_DECIMALS
+                );
+                Token::DEFAULT_DECIMALS
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to query decimals of the token {:?}, assuming {}: {}",
+                    self.address,
+                    Token::DEFAULT_DECIMALS,
+                    e
+                );
+                Token::DEFAULT_DECIMALS
+            }
+        };
+        Token::new(
+            self.id,
+            self.address,
+            &format!("ERC20-{}", self.id),
+            decimals,
+        )
+    }
+}
+
 impl TryFrom<Log> for NewTokenEvent {
     type Error = failure::Error;
 
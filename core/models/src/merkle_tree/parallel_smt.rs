@@ -36,7 +36,12 @@ type NodeRef = usize;
 ///
 /// Since this means that basically the tree is "full" all the time (all the empty indices
 /// are taken by the "default" element), the tree has fixed capacity and cannot be extended
-/// above that. The root hash is calculated for the full tree every time.
+/// above that.
+///
+/// Hashes of the interior nodes are cached once calculated, and modifying an element
+/// invalidates only the nodes on the path from its leaf to the root. Thus, after the
+/// initial calculation, the cost of obtaining the root hash depends on the amount of
+/// elements changed since the previous calculation, rather than on the size of the tree.
 ///
 /// [Merkle tree]: https://en.wikipedia.org/wiki/Merkle_tree
 #[derive(Debug)]
@@ -366,18 +371,19 @@ where
 
     /// Returns the Merkle root hash of the tree. This operation can cost up to O(N*logN):
     /// the root hash is calculated in this method, and it will build the whole hash tree
-    /// if this method was not called. The intermediate calculation results are caches though,
-    /// thus follow-up invocations will cost less.
+    /// if this method was not called. The intermediate calculation results are cached though,
+    /// thus follow-up invocations will only recalculate the paths of the changed elements,
+    /// which costs O(K*logN) for K changed elements.
     pub fn root_hash(&self) -> Hash {
         let (root_hash, intermediate_hashes) = self.get_hash(Self::ROOT_ITEM_IDX);
 
-        // Store all the intermediate hashes in the cache.
-        for (item_idx, hash) in intermediate_hashes {
-            self.cache
-                .write()
-                .expect("write lock")
-                .insert(item_idx, hash);
-        }
+        // Store all the intermediate hashes in the cache. Lock is obtained once,
+        // since the amount of updates may be significant after the bulk insertion.
+        self.cache
+            .write()
+            .expect("write lock")
+            .extend(intermediate_hashes);
+
         root_hash
     }
 
@@ -747,6 +753,33 @@ mod tests {
         assert_eq!(tree.root_hash(), 697_516_875);
     }
 
+    /// Checks that updating an element invalidates only the cached hashes on its path,
+    /// and the root hash calculated incrementally is the same as for the fresh tree.
+    #[test]
+    fn incremental_root_hash() {
+        let depth = 3;
+        let mut tree = TestSMT::new(depth);
+        for idx in 0..8 {
+            tree.insert(idx, TestLeaf(idx as u64));
+        }
+        tree.root_hash();
+        let cached_before = tree.cache.read().unwrap().len();
+
+        tree.insert(5, TestLeaf(42));
+
+        // Leaf itself and all of its parents up to the root.
+        let cached_after = tree.cache.read().unwrap().len();
+        assert_eq!(cached_before - cached_after, depth + 1);
+
+        let mut fresh_tree = TestSMT::new(depth);
+        for idx in 0..8 {
+            let value = if idx == 5 { 42 } else { idx as u64 };
+            fresh_tree.insert(idx, TestLeaf(value));
+        }
+        assert_eq!(tree.root_hash(), fresh_tree.root_hash());
+        assert_eq!(tree.cache.read().unwrap().len(), cached_before);
+    }
+
     /// Checks the correctness of the built Merkle proofs
     #[test]
     fn merkle_path_test() {
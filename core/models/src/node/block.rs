@@ -1,12 +1,14 @@
 use super::FranklinOp;
 use super::PriorityOp;
-use super::{AccountId, BlockNumber, Fr};
+use super::{AccountId, BlockNumber, Fr, TokenId};
 use crate::franklin_crypto::bellman::pairing::ff::{PrimeField, PrimeFieldRepr};
 use crate::node::SignedFranklinTx;
 use crate::params::CHUNK_BIT_WIDTH;
 use crate::serialization::*;
 use chrono::DateTime;
 use chrono::Utc;
+use num::{BigUint, Zero};
+use std::collections::BTreeMap;
 use web3::types::{H256, U256};
 
 #[derive(Clone, Debug)]
@@ -35,6 +37,11 @@ pub struct ExecutedPriorityOp {
     pub op: FranklinOp,
     pub block_index: u32,
     pub created_at: DateTime<Utc>,
+    /// Reason why the operation can't be applied as requested (e.g. full exit of the
+    /// missing account). Such an operation is still included into the block, but it
+    /// doesn't change the state.
+    #[serde(default)]
+    pub invalid_reason: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -87,6 +94,11 @@ pub struct Block {
     pub commit_gas_limit: U256,
     /// Gas limit to be set for the Verify Ethereum transaction.
     pub verify_gas_limit: U256,
+    /// Unix timestamp (in seconds) of the moment the block was sealed.
+    /// It's not a part of the public data, since the contract doesn't use it,
+    /// so it's unknown for the blocks restored from Ethereum.
+    #[serde(default)]
+    pub timestamp: Option<u64>,
 }
 
 impl Block {
@@ -101,6 +113,7 @@ impl Block {
         block_chunks_size: usize,
         commit_gas_limit: U256,
         verify_gas_limit: U256,
+        timestamp: Option<u64>,
     ) -> Self {
         Self {
             block_number,
@@ -111,6 +124,7 @@ impl Block {
             block_chunks_size,
             commit_gas_limit,
             verify_gas_limit,
+            timestamp,
         }
     }
 
@@ -125,6 +139,7 @@ impl Block {
         available_block_chunks_sizes: &[usize],
         commit_gas_limit: U256,
         verify_gas_limit: U256,
+        timestamp: Option<u64>,
     ) -> Self {
         let mut block = Self {
             block_number,
@@ -135,6 +150,7 @@ impl Block {
             block_chunks_size: 0,
             commit_gas_limit,
             verify_gas_limit,
+            timestamp,
         };
         block.block_chunks_size = block.smallest_block_size(available_block_chunks_sizes);
         block
@@ -183,7 +199,9 @@ impl Block {
         self.processed_priority_ops.1 - self.processed_priority_ops.0
     }
 
-    fn chunks_used(&self) -> usize {
+    /// Returns the amount of chunks actually occupied by the block operations,
+    /// not including the noop padding up to `block_chunks_size`.
+    pub fn chunks_used(&self) -> usize {
         self.block_transactions
             .iter()
             .filter_map(ExecutedOperations::get_executed_op)
@@ -191,6 +209,25 @@ impl Block {
             .sum()
     }
 
+    /// Returns the total amount of fees collected to the fee account by the block
+    /// operations, grouped by token. Tokens without collected fees are omitted.
+    pub fn collected_fees(&self) -> BTreeMap<TokenId, BigUint> {
+        let mut fees = BTreeMap::new();
+        let collected = self
+            .block_transactions
+            .iter()
+            .filter_map(ExecutedOperations::get_executed_op)
+            .filter_map(|op| op.try_get_tx().ok())
+            .filter_map(|tx| tx.fee());
+        for (token, amount) in collected {
+            if amount.is_zero() {
+                continue;
+            }
+            *fees.entry(token).or_insert_with(BigUint::zero) += amount;
+        }
+        fees
+    }
+
     fn smallest_block_size(&self, available_block_sizes: &[usize]) -> usize {
         let chunks_used = self.chunks_used();
         smallest_block_size_for_chunks(chunks_used, available_block_sizes)
@@ -209,6 +246,25 @@ impl Block {
 
         withdrawals_data
     }
+
+    /// Returns the hashes identifying the withdrawals of the block, in the order they are
+    /// added to the pending withdrawals queue of the contract: transaction hashes for the
+    /// withdraw transactions, and Ethereum transaction hashes for the full exits.
+    pub fn get_withdrawal_hashes(&self) -> Vec<Vec<u8>> {
+        self.block_transactions
+            .iter()
+            .filter(|block_tx| {
+                block_tx
+                    .get_executed_op()
+                    .and_then(FranklinOp::withdrawal_data)
+                    .is_some()
+            })
+            .map(|block_tx| match block_tx {
+                ExecutedOperations::Tx(exec_tx) => exec_tx.signed_tx.hash().as_ref().to_vec(),
+                ExecutedOperations::PriorityOp(exec_op) => exec_op.priority_op.eth_hash.clone(),
+            })
+            .collect()
+    }
 }
 
 // Get smallest block size given
@@ -227,3 +283,30 @@ pub fn smallest_block_size_for_chunks(
         available_block_sizes.last().unwrap()
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_smallest_block_size_for_chunks() {
+        let available_block_sizes = [6, 30, 74];
+
+        assert_eq!(smallest_block_size_for_chunks(0, &available_block_sizes), 6);
+        assert_eq!(smallest_block_size_for_chunks(6, &available_block_sizes), 6);
+        assert_eq!(
+            smallest_block_size_for_chunks(7, &available_block_sizes),
+            30
+        );
+        assert_eq!(
+            smallest_block_size_for_chunks(74, &available_block_sizes),
+            74
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_block_size_overflow() {
+        smallest_block_size_for_chunks(75, &[6, 30, 74]);
+    }
+}
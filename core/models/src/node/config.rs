@@ -3,7 +3,6 @@ use std::time::Duration;
 // HACK: hardcode some configuration options for now.
 pub const PADDING_SUB_INTERVAL: Duration = Duration::from_secs(10);
 pub const PROVER_GONE_TIMEOUT: Duration = Duration::from_secs(60);
-pub const TX_MINIBATCH_CREATE_TIME: Duration = Duration::from_millis(1000);
 pub const MAX_WITHDRAWALS_TO_COMPLETE_IN_A_CALL: u64 = 20;
 /// After server replica places its into leader_election table,
 /// it checks db to see who is current leader with this interval.
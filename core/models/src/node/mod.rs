@@ -251,4 +251,48 @@ mod test {
             assert_eq!(query, de);
         }
     }
+
+    #[test]
+    fn operation_chunks() {
+        for (op_code, chunks) in operations::OPERATION_CHUNKS.iter() {
+            assert_eq!(FranklinOp::chunks_by_op_code(*op_code), Some(*chunks));
+        }
+        assert_eq!(FranklinOp::chunks_by_op_code(0xff), None);
+
+        let transfer = FranklinTx::Transfer(Box::new(Transfer::new(
+            0,
+            Default::default(),
+            Default::default(),
+            0,
+            0u32.into(),
+            0u32.into(),
+            0,
+            None,
+        )));
+        assert_eq!(transfer.chunks(true), TransferOp::CHUNKS);
+        assert_eq!(transfer.chunks(false), TransferToNewOp::CHUNKS);
+    }
+
+    #[test]
+    fn created_account_id() {
+        let tx = Transfer::new(
+            0,
+            Default::default(),
+            Default::default(),
+            0,
+            0u32.into(),
+            0u32.into(),
+            0,
+            None,
+        );
+        let transfer_to_new = FranklinOp::TransferToNew(Box::new(TransferToNewOp {
+            tx: tx.clone(),
+            from: 1,
+            to: 2,
+        }));
+        assert_eq!(transfer_to_new.created_account_id(), Some(2));
+
+        let transfer = FranklinOp::Transfer(Box::new(TransferOp { tx, from: 1, to: 2 }));
+        assert_eq!(transfer.created_account_id(), None);
+    }
 }
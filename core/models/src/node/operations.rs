@@ -19,6 +19,20 @@ use failure::{ensure, format_err};
 use num::{BigUint, FromPrimitive, ToPrimitive};
 use web3::types::Address;
 
+/// Amount of chunks occupied in the block by every operation type, indexed by the
+/// operation code. Chunk costs must match the layout of the operations in the circuit,
+/// since the server relies on this table to decide whether the operation fits the block.
+pub const OPERATION_CHUNKS: [(u8, usize); 8] = [
+    (NoopOp::OP_CODE, NoopOp::CHUNKS),
+    (DepositOp::OP_CODE, DepositOp::CHUNKS),
+    (TransferToNewOp::OP_CODE, TransferToNewOp::CHUNKS),
+    (WithdrawOp::OP_CODE, WithdrawOp::CHUNKS),
+    (CloseOp::OP_CODE, CloseOp::CHUNKS),
+    (TransferOp::OP_CODE, TransferOp::CHUNKS),
+    (FullExitOp::OP_CODE, FullExitOp::CHUNKS),
+    (ChangePubKeyOp::OP_CODE, ChangePubKeyOp::CHUNKS),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositOp {
     pub priority_op: Deposit,
@@ -607,19 +621,18 @@ impl FranklinOp {
         }
     }
 
+    /// Returns the amount of chunks occupied by the operation with the provided code.
+    pub fn chunks_by_op_code(op_type: u8) -> Option<usize> {
+        OPERATION_CHUNKS
+            .iter()
+            .find(|(op_code, _)| *op_code == op_type)
+            .map(|(_, chunks)| *chunks)
+    }
+
     pub fn public_data_length(op_type: u8) -> Result<usize, failure::Error> {
-        match op_type {
-            NoopOp::OP_CODE => Ok(NoopOp::CHUNKS),
-            DepositOp::OP_CODE => Ok(DepositOp::CHUNKS),
-            TransferToNewOp::OP_CODE => Ok(TransferToNewOp::CHUNKS),
-            WithdrawOp::OP_CODE => Ok(WithdrawOp::CHUNKS),
-            CloseOp::OP_CODE => Ok(CloseOp::CHUNKS),
-            TransferOp::OP_CODE => Ok(TransferOp::CHUNKS),
-            FullExitOp::OP_CODE => Ok(FullExitOp::CHUNKS),
-            ChangePubKeyOp::OP_CODE => Ok(ChangePubKeyOp::CHUNKS),
-            _ => Err(format_err!("Wrong operation type: {}", &op_type)),
-        }
-        .map(|chunks| chunks * CHUNK_BYTES)
+        Self::chunks_by_op_code(op_type)
+            .map(|chunks| chunks * CHUNK_BYTES)
+            .ok_or_else(|| format_err!("Wrong operation type: {}", &op_type))
     }
 
     pub fn try_get_tx(&self) -> Result<FranklinTx, failure::Error> {
@@ -642,6 +655,15 @@ impl FranklinOp {
             _ => Err(format_err!("Wrong operation type")),
         }
     }
+
+    /// Returns the ID of the account created by the operation, if any.
+    /// Transfer to the address without an account creates the account for the recipient.
+    pub fn created_account_id(&self) -> Option<AccountId> {
+        match self {
+            FranklinOp::TransferToNew(op) => Some(op.to),
+            _ => None,
+        }
+    }
 }
 
 impl From<NoopOp> for FranklinOp {
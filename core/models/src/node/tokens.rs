@@ -46,6 +46,9 @@ pub struct TokenGenesisListItem {
 }
 
 impl Token {
+    /// Precision assumed for the tokens which don't report it (the same as "ETH" has).
+    pub const DEFAULT_DECIMALS: u8 = 18;
+
     pub fn new(id: TokenId, address: Address, symbol: &str, decimals: u8) -> Self {
         Self {
             id,
@@ -2,7 +2,7 @@ use super::{Nonce, TokenId};
 
 use crate::node::{
     is_fee_amount_packable, is_token_amount_packable, pack_fee_amount, pack_token_amount,
-    public_key_from_private, AccountId, CloseOp, TransferOp, WithdrawOp,
+    public_key_from_private, AccountId, CloseOp, TransferOp, TransferToNewOp, WithdrawOp,
 };
 use crypto::{digest::Digest, sha2::Sha256};
 use num::{BigUint, ToPrimitive};
@@ -84,6 +84,46 @@ impl<'de> Deserialize<'de> for TxHash {
     }
 }
 
+/// Checks that the validity window of the transaction is not empty.
+fn is_validity_window_correct(valid_from: Option<u64>, valid_until: Option<u64>) -> bool {
+    match (valid_from, valid_until) {
+        (Some(valid_from), Some(valid_until)) => valid_from <= valid_until,
+        _ => true,
+    }
+}
+
+/// Formats the validity window to be included into the message signed by the Ethereum keys.
+///
+/// The zkSync signature covers the transaction bytes verified by the circuit, which have
+/// no place for the validity window, so the window is authenticated by the Ethereum
+/// signature. Message is unchanged for the transactions without a validity window.
+fn validity_window_message(valid_from: Option<u64>, valid_until: Option<u64>) -> String {
+    let mut message = String::new();
+    if let Some(valid_from) = valid_from {
+        message.push_str(&format!("\nValid from: {}", valid_from));
+    }
+    if let Some(valid_until) = valid_until {
+        message.push_str(&format!("\nValid until: {}", valid_until));
+    }
+    message
+}
+
+/// Serializes the validity window to be included into the transaction hash, so the
+/// transactions differing only in the validity window have different hashes.
+///
+/// Window can't be included into the bytes signed by the zkSync key, since the circuit
+/// reconstructs these bytes from the operation fields (see `get_bytes`). Bytes are empty
+/// for the transactions without a validity window, so their hashes are unchanged.
+fn validity_window_bytes(valid_from: Option<u64>, valid_until: Option<u64>) -> Vec<u8> {
+    if valid_from.is_none() && valid_until.is_none() {
+        return Vec::new();
+    }
+    let mut out = Vec::with_capacity(16);
+    out.extend_from_slice(&valid_from.unwrap_or(0).to_be_bytes());
+    out.extend_from_slice(&valid_until.unwrap_or_else(u64::max_value).to_be_bytes());
+    out
+}
+
 /// Stores precomputed signature verification result to speedup tx execution
 #[derive(Debug, Clone)]
 enum VerifiedSignatureCache {
@@ -112,6 +152,12 @@ pub struct Transfer {
     #[serde(with = "BigUintSerdeAsRadix10Str")]
     pub fee: BigUint,
     pub nonce: Nonce,
+    /// Unix timestamp (in seconds) since which the transaction can be executed.
+    #[serde(default)]
+    pub valid_from: Option<u64>,
+    /// Unix timestamp (in seconds) until which the transaction can be executed.
+    #[serde(default)]
+    pub valid_until: Option<u64>,
     pub signature: TxSignature,
     #[serde(skip)]
     cached_signer: VerifiedSignatureCache,
@@ -141,6 +187,8 @@ impl Transfer {
             amount,
             fee,
             nonce,
+            valid_from: None,
+            valid_until: None,
             signature: signature.clone().unwrap_or_default(),
             cached_signer: VerifiedSignatureCache::NotCached,
         };
@@ -190,7 +238,8 @@ impl Transfer {
             && is_fee_amount_packable(&self.fee)
             && self.account_id <= max_account_id()
             && self.token <= max_token_id()
-            && self.to != Address::zero();
+            && self.to != Address::zero()
+            && is_validity_window_correct(self.valid_from, self.valid_until);
         if valid {
             let signer = self.verify_signature();
             valid = valid && signer.is_some();
@@ -199,6 +248,14 @@ impl Transfer {
         valid
     }
 
+    /// Verifies the signature and caches the result, so the subsequent
+    /// `verify_signature` calls don't perform the expensive check again.
+    pub fn cache_signer(&mut self) {
+        if let VerifiedSignatureCache::NotCached = self.cached_signer {
+            self.cached_signer = VerifiedSignatureCache::Cached(self.verify_signature());
+        }
+    }
+
     pub fn verify_signature(&self) -> Option<PubKeyHash> {
         if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
             cached_signer.clone()
@@ -216,13 +273,14 @@ impl Transfer {
             To: {to:?}\n\
             Nonce: {nonce}\n\
             Fee: {fee} {token}\n\
-            Account Id: {account_id}",
+            Account Id: {account_id}{validity_window}",
             amount = format_units(&self.amount, decimals),
             token = token_symbol,
             to = self.to,
             nonce = self.nonce,
             fee = format_units(&self.fee, decimals),
             account_id = self.account_id,
+            validity_window = validity_window_message(self.valid_from, self.valid_until),
         )
     }
 }
@@ -239,6 +297,12 @@ pub struct Withdraw {
     #[serde(with = "BigUintSerdeAsRadix10Str")]
     pub fee: BigUint,
     pub nonce: Nonce,
+    /// Unix timestamp (in seconds) since which the transaction can be executed.
+    #[serde(default)]
+    pub valid_from: Option<u64>,
+    /// Unix timestamp (in seconds) until which the transaction can be executed.
+    #[serde(default)]
+    pub valid_until: Option<u64>,
     pub signature: TxSignature,
     #[serde(skip)]
     cached_signer: VerifiedSignatureCache,
@@ -268,6 +332,8 @@ impl Withdraw {
             amount,
             fee,
             nonce,
+            valid_from: None,
+            valid_until: None,
             signature: signature.clone().unwrap_or_default(),
             cached_signer: VerifiedSignatureCache::NotCached,
         };
@@ -314,7 +380,8 @@ impl Withdraw {
         let mut valid = self.amount <= BigUint::from(u128::max_value())
             && is_fee_amount_packable(&self.fee)
             && self.account_id <= max_account_id()
-            && self.token <= max_token_id();
+            && self.token <= max_token_id()
+            && is_validity_window_correct(self.valid_from, self.valid_until);
 
         if valid {
             let signer = self.verify_signature();
@@ -324,6 +391,14 @@ impl Withdraw {
         valid
     }
 
+    /// Verifies the signature and caches the result, so the subsequent
+    /// `verify_signature` calls don't perform the expensive check again.
+    pub fn cache_signer(&mut self) {
+        if let VerifiedSignatureCache::NotCached = self.cached_signer {
+            self.cached_signer = VerifiedSignatureCache::Cached(self.verify_signature());
+        }
+    }
+
     pub fn verify_signature(&self) -> Option<PubKeyHash> {
         if let VerifiedSignatureCache::Cached(cached_signer) = &self.cached_signer {
             cached_signer.clone()
@@ -341,13 +416,14 @@ impl Withdraw {
             To: {to:?}\n\
             Nonce: {nonce}\n\
             Fee: {fee} {token}\n\
-            Account Id: {account_id}",
+            Account Id: {account_id}{validity_window}",
             amount = format_units(&self.amount, decimals),
             token = token_symbol,
             to = self.to,
             nonce = self.nonce,
             fee = format_units(&self.fee, decimals),
             account_id = self.account_id,
+            validity_window = validity_window_message(self.valid_from, self.valid_until),
         )
     }
 }
@@ -495,12 +571,14 @@ impl std::ops::Deref for SignedFranklinTx {
 
 impl FranklinTx {
     pub fn hash(&self) -> TxHash {
-        let bytes = match self {
+        let mut bytes = match self {
             FranklinTx::Transfer(tx) => tx.get_bytes(),
             FranklinTx::Withdraw(tx) => tx.get_bytes(),
             FranklinTx::Close(tx) => tx.get_bytes(),
             FranklinTx::ChangePubKey(tx) => tx.get_bytes(),
         };
+        let (valid_from, valid_until) = self.validity_window();
+        bytes.extend(validity_window_bytes(valid_from, valid_until));
 
         let mut hasher = Sha256::new();
         hasher.input(&bytes);
@@ -518,6 +596,16 @@ impl FranklinTx {
         }
     }
 
+    /// Returns the address receiving the funds moved by the transaction, if any.
+    /// For withdrawals it's the Ethereum address receiving the withdrawn funds.
+    pub fn recipient(&self) -> Option<Address> {
+        match self {
+            FranklinTx::Transfer(tx) => Some(tx.to),
+            FranklinTx::Withdraw(tx) => Some(tx.to),
+            FranklinTx::Close(_) | FranklinTx::ChangePubKey(_) => None,
+        }
+    }
+
     pub fn nonce(&self) -> Nonce {
         match self {
             FranklinTx::Transfer(tx) => tx.nonce,
@@ -527,6 +615,54 @@ impl FranklinTx {
         }
     }
 
+    /// Returns the token and the amount of the fee paid by the transaction.
+    /// Returns `None` for the transactions without fee.
+    pub fn fee(&self) -> Option<(TokenId, BigUint)> {
+        match self {
+            FranklinTx::Transfer(tx) => Some((tx.token, tx.fee.clone())),
+            FranklinTx::Withdraw(tx) => Some((tx.token, tx.fee.clone())),
+            FranklinTx::Close(_) | FranklinTx::ChangePubKey(_) => None,
+        }
+    }
+
+    /// Returns the validity window of the transaction as a pair of
+    /// `valid_from` and `valid_until` unix timestamps.
+    ///
+    /// Unlike the rest of the transaction fields, the window is not signed by the zkSync key
+    /// (the circuit has no place for it), thus the transactions with a window are accepted
+    /// only along with the Ethereum signature of the message containing it.
+    pub fn validity_window(&self) -> (Option<u64>, Option<u64>) {
+        match self {
+            FranklinTx::Transfer(tx) => (tx.valid_from, tx.valid_until),
+            FranklinTx::Withdraw(tx) => (tx.valid_from, tx.valid_until),
+            FranklinTx::Close(_) | FranklinTx::ChangePubKey(_) => (None, None),
+        }
+    }
+
+    /// Checks whether the validity window of the transaction has expired at the provided time.
+    pub fn is_expired_at(&self, timestamp: u64) -> bool {
+        match self.validity_window() {
+            (_, Some(valid_until)) => timestamp > valid_until,
+            _ => false,
+        }
+    }
+
+    /// Checks whether the transaction can be executed at the provided unix timestamp.
+    pub fn is_valid_at(&self, timestamp: u64) -> bool {
+        let (valid_from, _) = self.validity_window();
+        !self.is_expired_at(timestamp) && valid_from.map(|from| from <= timestamp).unwrap_or(true)
+    }
+
+    /// Verifies the zkSync signature of the transaction in advance and caches the result
+    /// (for the transactions supporting it), so it won't be verified during the execution.
+    pub fn cache_signer(&mut self) {
+        match self {
+            FranklinTx::Transfer(tx) => tx.cache_signer(),
+            FranklinTx::Withdraw(tx) => tx.cache_signer(),
+            FranklinTx::Close(_) | FranklinTx::ChangePubKey(_) => {}
+        }
+    }
+
     pub fn check_correctness(&mut self) -> bool {
         match self {
             FranklinTx::Transfer(tx) => tx.check_correctness(),
@@ -545,6 +681,16 @@ impl FranklinTx {
         }
     }
 
+    /// Returns the amount of chunks occupied by the transaction in the block.
+    /// Transfer to the account that doesn't exist yet creates it, and thus
+    /// is executed as a `TransferToNew` operation.
+    pub fn chunks(&self, recipient_exists: bool) -> usize {
+        match self {
+            FranklinTx::Transfer(_) if !recipient_exists => TransferToNewOp::CHUNKS,
+            _ => self.min_chunks(),
+        }
+    }
+
     pub fn min_chunks(&self) -> usize {
         match self {
             FranklinTx::Transfer(_) => TransferOp::CHUNKS,
@@ -986,6 +1132,42 @@ mod test {
         rng.gen::<u16>().min(max_token_id())
     }
 
+    #[test]
+    fn test_validity_window() {
+        let mut transfer = Transfer::new(
+            0,
+            Default::default(),
+            Default::default(),
+            0,
+            0u32.into(),
+            0u32.into(),
+            0,
+            None,
+        );
+        let message = transfer.get_ethereum_sign_message("ETH", 18);
+        let hash = FranklinTx::Transfer(Box::new(transfer.clone())).hash();
+        transfer.valid_from = Some(100);
+        transfer.valid_until = Some(200);
+        assert_eq!(
+            transfer.get_ethereum_sign_message("ETH", 18),
+            format!("{}\nValid from: 100\nValid until: 200", message)
+        );
+
+        let tx = FranklinTx::Transfer(Box::new(transfer.clone()));
+        assert_ne!(tx.hash(), hash);
+        assert!(!tx.is_valid_at(99));
+        assert!(tx.is_valid_at(100));
+        assert!(tx.is_valid_at(200));
+        assert!(tx.is_expired_at(201));
+        assert!(!tx.is_valid_at(201));
+
+        transfer.valid_from = Some(300);
+        assert!(!is_validity_window_correct(
+            transfer.valid_from,
+            transfer.valid_until
+        ));
+    }
+
     #[test]
     fn test_print_transfer_for_protocol() {
         let mut rng = XorShiftRng::from_seed([1, 2, 3, 4]);
@@ -7,7 +7,7 @@ use crate::franklin_crypto::bellman::pairing::ff::ScalarEngine;
 use crate::franklin_crypto::bellman::pairing::ff::{
     BitIterator, Field, PrimeField, PrimeFieldRepr,
 };
-use crate::franklin_crypto::bellman::pairing::{CurveAffine, Engine};
+use crate::franklin_crypto::bellman::pairing::{CurveAffine, EncodedPoint, Engine};
 use crate::franklin_crypto::jubjub::{edwards, JubjubEngine, Unknown};
 use bigdecimal::BigDecimal;
 use failure::bail;
@@ -130,6 +130,22 @@ pub fn serialize_g1_for_ethereum(point: &<Bn256 as Engine>::G1Affine) -> (U256,
     (x, y)
 }
 
+/// Restores the point serialized by `serialize_g1_for_ethereum`.
+pub fn deserialize_g1_for_ethereum(
+    (x, y): (U256, U256),
+) -> Result<<Bn256 as Engine>::G1Affine, failure::Error> {
+    if x.is_zero() && y.is_zero() {
+        return Ok(<Bn256 as Engine>::G1Affine::zero());
+    }
+    let mut uncompressed = <<Bn256 as Engine>::G1Affine as CurveAffine>::Uncompressed::empty();
+    x.to_big_endian(&mut uncompressed.as_mut()[0..32]);
+    y.to_big_endian(&mut uncompressed.as_mut()[32..64]);
+
+    uncompressed
+        .into_affine()
+        .map_err(|e| failure::format_err!("Invalid G1 point: {}", e))
+}
+
 pub fn serialize_g2_for_ethereum(
     point: &<Bn256 as Engine>::G2Affine,
 ) -> ((U256, U256), (U256, U256)) {
@@ -156,6 +172,18 @@ pub fn serialize_fe_for_ethereum(field_element: &<Bn256 as ScalarEngine>::Fr) ->
     U256::from_big_endian(&be_bytes[..])
 }
 
+/// Restores the field element serialized by `serialize_fe_for_ethereum`.
+pub fn deserialize_fe_for_ethereum(
+    value: U256,
+) -> Result<<Bn256 as ScalarEngine>::Fr, failure::Error> {
+    let mut be_bytes = [0u8; 32];
+    value.to_big_endian(&mut be_bytes);
+    let mut repr = <<Bn256 as ScalarEngine>::Fr as PrimeField>::Repr::default();
+    repr.read_be(&be_bytes[..])?;
+    <Bn256 as ScalarEngine>::Fr::from_repr(repr)
+        .map_err(|e| failure::format_err!("Invalid field element: {}", e))
+}
+
 pub fn unpack_edwards_point<E: JubjubEngine>(
     serialized: [u8; 32],
     params: &E::Params,
@@ -585,4 +613,25 @@ mod test {
         let out: Vec<bool> = BitIteratorLe::new(&test_vector).collect();
         assert_eq!(reference, out);
     }
+
+    #[test]
+    fn ethereum_serialization_roundtrip() {
+        use crate::franklin_crypto::bellman::pairing::CurveProjective;
+        use crate::node::Fr;
+
+        let fe = Fr::from_str("12345").unwrap();
+        let serialized = serialize_fe_for_ethereum(&fe);
+        assert_eq!(deserialize_fe_for_ethereum(serialized).unwrap(), fe);
+
+        let point = <Bn256 as Engine>::G1::one().into_affine();
+        let serialized = serialize_g1_for_ethereum(&point);
+        assert_eq!(deserialize_g1_for_ethereum(serialized).unwrap(), point);
+
+        let zero = <Bn256 as Engine>::G1Affine::zero();
+        let serialized = serialize_g1_for_ethereum(&zero);
+        assert_eq!(deserialize_g1_for_ethereum(serialized).unwrap(), zero);
+
+        // Coordinates of the point not on the curve.
+        assert!(deserialize_g1_for_ethereum((U256::from(1), U256::from(1))).is_err());
+    }
 }
@@ -0,0 +1,123 @@
+//! Checks of the circuit keys used by the provers and the server.
+//!
+//! Verification keys are compared with the keccak256 hashes embedded into the binary
+//! (see `key_hashes.json`), so neither provers nor the server start with the keys
+//! which don't match the circuit the code was released with.
+//! Hashes of the new key set are added to `key_hashes.json` along with the packed keys.
+
+use crate::node::H256;
+use crate::prover_utils::fs_utils::{
+    get_block_verification_key_path, get_exodus_verification_key_path, get_keys_root_dir,
+};
+use failure::format_err;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::path::Path;
+
+lazy_static! {
+    /// Expected hashes of the key files by the key set name.
+    static ref EXPECTED_KEY_HASHES: HashMap<String, HashMap<String, H256>> =
+        serde_json::from_str(include_str!("key_hashes.json"))
+            .expect("invalid embedded key hashes");
+}
+
+/// Name of the used key set, e.g. `plonk-3772d44/account-32_balance-11`.
+fn key_set_name() -> String {
+    let keys_root_dir = get_keys_root_dir();
+    let name = keys_root_dir
+        .iter()
+        .rev()
+        .take(2)
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>();
+    format!("{}/{}", name[1], name[0])
+}
+
+/// Computes the keccak256 hash of the key file.
+pub fn key_file_hash(path: &Path) -> Result<H256, failure::Error> {
+    let contents = std::fs::read(path)
+        .map_err(|e| format_err!("Failed to read key file {}: {}", path.display(), e))?;
+    Ok(tiny_keccak::keccak256(&contents).into())
+}
+
+/// Checks that the key file exists and has the expected hash.
+fn check_key_file(
+    expected_hashes: &HashMap<String, H256>,
+    path: &Path,
+) -> Result<(), failure::Error> {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let expected_hash = expected_hashes
+        .get(&file_name)
+        .ok_or_else(|| format_err!("No expected hash for the key file {}", file_name))?;
+    let hash = key_file_hash(path)?;
+    failure::ensure!(
+        hash == *expected_hash,
+        "Key file {} has hash {:?}, expected {:?}",
+        path.display(),
+        hash,
+        expected_hash
+    );
+    Ok(())
+}
+
+/// Checks the verification keys of the exit circuit and of the main circuit for
+/// the given block sizes against the embedded hashes.
+/// Returns an error if any key is missing or doesn't match.
+pub fn check_verification_keys(block_chunk_sizes: &[usize]) -> Result<(), failure::Error> {
+    let key_set = key_set_name();
+    let expected_hashes = EXPECTED_KEY_HASHES
+        .get(&key_set)
+        .ok_or_else(|| format_err!("Key set {} is unknown, no expected hashes", key_set))?;
+
+    check_key_file(expected_hashes, &get_exodus_verification_key_path())?;
+    for block_chunks in block_chunk_sizes {
+        check_key_file(
+            expected_hashes,
+            &get_block_verification_key_path(*block_chunks),
+        )?;
+    }
+    log::info!("Verification keys of the key set {} are valid", key_set);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the embedded hashes can be parsed and contain the default key set.
+    #[test]
+    fn embedded_key_hashes() {
+        let dev_key_set = EXPECTED_KEY_HASHES
+            .get("plonk-3772d44/account-32_balance-11")
+            .expect("no hashes for the default key set");
+        assert!(dev_key_set.contains_key("verification_exit.key"));
+        for block_chunks in &[6, 30, 74, 150, 334, 678] {
+            let key_name = format!("verification_block_{}.key", block_chunks);
+            assert!(dev_key_set.contains_key(&key_name));
+        }
+    }
+
+    #[test]
+    fn key_file_hash_mismatch() {
+        let path = std::env::temp_dir().join("verification_block_6.key");
+        std::fs::write(&path, b"not a key").unwrap();
+
+        let mut expected_hashes = HashMap::new();
+        expected_hashes.insert(
+            "verification_block_6.key".to_string(),
+            tiny_keccak::keccak256(b"not a key").into(),
+        );
+        assert!(check_key_file(&expected_hashes, &path).is_ok());
+
+        expected_hashes.insert("verification_block_6.key".to_string(), H256::zero());
+        assert!(check_key_file(&expected_hashes, &path).is_err());
+
+        expected_hashes.clear();
+        assert!(check_key_file(&expected_hashes, &path).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,7 +1,11 @@
+use crate::franklin_crypto::bellman::pairing::Engine as PairingEngine;
 use crate::franklin_crypto::bellman::Circuit;
 use crate::node::U256;
 use crate::node::{Engine, Fr};
-use crate::primitives::{serialize_fe_for_ethereum, serialize_g1_for_ethereum};
+use crate::primitives::{
+    deserialize_fe_for_ethereum, deserialize_g1_for_ethereum, serialize_fe_for_ethereum,
+    serialize_g1_for_ethereum,
+};
 use crate::prover_utils::fs_utils::{
     get_block_verification_key_path, get_exodus_verification_key_path,
 };
@@ -18,7 +22,9 @@ use std::fs::File;
 use std::sync::{Arc, Mutex};
 
 pub mod fs_utils;
+pub mod key_checks;
 pub mod network_utils;
+pub mod proof_system;
 
 pub const SETUP_MIN_POW2: u32 = 20;
 pub const SETUP_MAX_POW2: u32 = 26;
@@ -39,6 +45,21 @@ impl PlonkVerificationKey {
             VerificationKey::read(File::open(get_exodus_verification_key_path())?)?;
         Ok(Self(verification_key))
     }
+
+    /// Checks the proof received from the prover before sending it to the contract.
+    /// Returns an error if the proof can't be decoded.
+    pub fn verify_encoded_proof(&self, proof: &EncodedProofPlonk) -> Result<bool, failure::Error> {
+        let mut proof = deserialize_proof(proof)?;
+        // Domain size is not sent to the contract, so it's taken from the key.
+        proof.n = self.0.n;
+        failure::ensure!(
+            proof.num_inputs == self.0.num_inputs,
+            "Invalid amount of proof inputs: {}, expected {}",
+            proof.num_inputs,
+            self.0.num_inputs
+        );
+        Ok(verify::<_, RollingKeccakTranscript<Fr>>(&proof, &self.0)?)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -56,6 +77,32 @@ impl Default for EncodedProofPlonk {
     }
 }
 
+impl EncodedProofPlonk {
+    /// Deterministic dummy proof of the block with the given public data commitment.
+    /// It is accepted only by the dev verifier contract (with `DUMMY_VERIFIER` enabled).
+    pub fn mock(public_data_commitment: &Fr) -> Self {
+        Self {
+            inputs: vec![serialize_fe_for_ethereum(public_data_commitment)],
+            ..Default::default()
+        }
+    }
+}
+
+/// Time spent by the prover on the stages of the block proof computation, in milliseconds.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProofTimings {
+    /// Fetching of the block witness from the server.
+    pub witness_load_ms: u64,
+    /// Circuit synthesis and preparation of the setup polynomials.
+    /// Close to zero if the setup prepared for the previous block of the same size is reused.
+    pub synthesis_ms: u64,
+    /// Proof computation (FFTs and multiexponentiations) along with its self-check.
+    /// `prove_by_steps` doesn't report its stages, so the witness assignment is included too.
+    pub proving_ms: u64,
+    /// Whole round, from the witness request to the computed proof.
+    pub total_ms: u64,
+}
+
 pub struct SetupForStepByStepProver {
     setup_polynomials: SetupPolynomials<Engine, PlonkCsWidth4WithNextStepParams>,
     hints: Vec<(usize, TranspilationVariant)>,
@@ -203,6 +250,69 @@ pub fn serialize_proof(
     }
 }
 
+/// Amount of the wires of the circuit, which determines the amount of the proof elements.
+const PROOF_STATE_WIDTH: usize = 4;
+
+/// Restores the proof serialized by `serialize_proof`.
+/// Domain size of the restored proof is not set, since it's not serialized.
+pub fn deserialize_proof(
+    encoded: &EncodedProofPlonk,
+) -> Result<Proof<Engine, PlonkCsWidth4WithNextStepParams>, failure::Error> {
+    let mut proof = Proof::empty();
+    proof.num_inputs = encoded.inputs.len();
+    proof.input_values = encoded
+        .inputs
+        .iter()
+        .map(|input| deserialize_fe_for_ethereum(*input))
+        .collect::<Result<_, _>>()?;
+
+    let mut reader = EncodedProofReader(encoded.proof.iter());
+    for _ in 0..PROOF_STATE_WIDTH {
+        proof.wire_commitments.push(reader.read_point()?);
+    }
+    proof.grand_product_commitment = reader.read_point()?;
+    for _ in 0..PROOF_STATE_WIDTH {
+        proof.quotient_poly_commitments.push(reader.read_point()?);
+    }
+    for _ in 0..PROOF_STATE_WIDTH {
+        proof.wire_values_at_z.push(reader.read_fe()?);
+    }
+    proof.wire_values_at_z_omega.push(reader.read_fe()?);
+    proof.grand_product_at_z_omega = reader.read_fe()?;
+    proof.quotient_polynomial_at_z = reader.read_fe()?;
+    proof.linearization_polynomial_at_z = reader.read_fe()?;
+    for _ in 0..PROOF_STATE_WIDTH - 1 {
+        proof.permutation_polynomials_at_z.push(reader.read_fe()?);
+    }
+    proof.opening_at_z_proof = reader.read_point()?;
+    proof.opening_at_z_omega_proof = reader.read_point()?;
+    failure::ensure!(reader.0.next().is_none(), "Encoded proof is too long");
+
+    Ok(proof)
+}
+
+/// Reads the values of the encoded proof in the order of serialization.
+struct EncodedProofReader<'a>(std::slice::Iter<'a, U256>);
+
+impl EncodedProofReader<'_> {
+    fn read_value(&mut self) -> Result<U256, failure::Error> {
+        self.0
+            .next()
+            .cloned()
+            .ok_or_else(|| failure::format_err!("Encoded proof is too short"))
+    }
+
+    fn read_fe(&mut self) -> Result<Fr, failure::Error> {
+        deserialize_fe_for_ethereum(self.read_value()?)
+    }
+
+    fn read_point(&mut self) -> Result<<Engine as PairingEngine>::G1Affine, failure::Error> {
+        let x = self.read_value()?;
+        let y = self.read_value()?;
+        deserialize_g1_for_ethereum((x, y))
+    }
+}
+
 /// Reads universal setup from disk or downloads from network.
 pub fn get_universal_setup_monomial_form(
     power_of_two: u32,
@@ -0,0 +1,179 @@
+//! Abstraction over the proof system used to prove the blocks.
+//!
+//! Provers and the server exchange the proofs as `EncodedProofPlonk`, i.e. the public inputs
+//! and the proof encoded into the words accepted by the verifier contract, so the prover server
+//! protocol doesn't depend on the proof system. Another proof system is added by implementing
+//! `ProofSystem` for it (along with the verifier contract accepting its proofs) and choosing it
+//! in the prover and the server instead of `PlonkProofSystem`.
+
+// Built-in deps
+use std::fmt;
+// Workspace deps
+use crate::franklin_crypto::bellman::Circuit;
+use crate::node::{Engine, Fr};
+use crate::prover_utils::{EncodedProofPlonk, PlonkVerificationKey, SetupForStepByStepProver};
+
+/// Verification of the block proofs, used by the server to check the proofs
+/// before sending them to the contract.
+pub trait ProofVerifier: fmt::Debug + Send + Sync {
+    /// Checks the proof of the block of the given size with the given public data commitment.
+    /// Returns the rejection reason if the proof is invalid. Errors are returned only if
+    /// the proof can't be checked at all, e.g. the verification key is missing.
+    fn verify(
+        &self,
+        block_size: usize,
+        public_data_commitment: &Fr,
+        proof: &EncodedProofPlonk,
+    ) -> Result<Option<String>, failure::Error>;
+}
+
+/// Generation of the block proofs, used by the provers.
+pub trait ProofSystem: ProofVerifier {
+    /// Data prepared for the block size once and reused for all the blocks of that size.
+    type Setup: Send;
+
+    /// Prepares the setup for the blocks of the same size as the given block circuit.
+    fn prepare_setup<C: Circuit<Engine> + Clone>(
+        &self,
+        circuit: C,
+        block_size: usize,
+    ) -> Result<Self::Setup, failure::Error>;
+
+    /// Computes the proof of the block circuit using the setup prepared for its size.
+    fn prove<C: Circuit<Engine> + Clone>(
+        &self,
+        setup: &Self::Setup,
+        circuit: C,
+        block_size: usize,
+        public_data_commitment: &Fr,
+    ) -> Result<EncodedProofPlonk, failure::Error>;
+}
+
+/// PLONK with the universal setup, the proof system of the main verifier contract.
+#[derive(Debug, Clone, Default)]
+pub struct PlonkProofSystem {
+    /// Whether the universal setup is downloaded from the network instead of the local files.
+    pub download_setup_from_network: bool,
+}
+
+impl ProofVerifier for PlonkProofSystem {
+    fn verify(
+        &self,
+        block_size: usize,
+        _public_data_commitment: &Fr,
+        proof: &EncodedProofPlonk,
+    ) -> Result<Option<String>, failure::Error> {
+        let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(block_size)?;
+        let reason = match vk.verify_encoded_proof(proof) {
+            Ok(true) => None,
+            Ok(false) => Some("proof verification failed".to_string()),
+            Err(e) => Some(format!("malformed proof: {}", e)),
+        };
+        Ok(reason)
+    }
+}
+
+impl ProofSystem for PlonkProofSystem {
+    type Setup = SetupForStepByStepProver;
+
+    fn prepare_setup<C: Circuit<Engine> + Clone>(
+        &self,
+        circuit: C,
+        _block_size: usize,
+    ) -> Result<Self::Setup, failure::Error> {
+        SetupForStepByStepProver::prepare_setup_for_step_by_step_prover(
+            circuit,
+            self.download_setup_from_network,
+        )
+    }
+
+    fn prove<C: Circuit<Engine> + Clone>(
+        &self,
+        setup: &Self::Setup,
+        circuit: C,
+        block_size: usize,
+        _public_data_commitment: &Fr,
+    ) -> Result<EncodedProofPlonk, failure::Error> {
+        let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(block_size)?;
+        setup.gen_step_by_step_proof_using_prepared_setup(circuit, &vk)
+    }
+}
+
+/// Deterministic mock proofs, accepted only by the dev verifier contract.
+/// Used in the dev environment, where the real proofs are too expensive to compute.
+#[derive(Debug, Clone, Default)]
+pub struct MockProofSystem;
+
+impl ProofVerifier for MockProofSystem {
+    fn verify(
+        &self,
+        _block_size: usize,
+        public_data_commitment: &Fr,
+        proof: &EncodedProofPlonk,
+    ) -> Result<Option<String>, failure::Error> {
+        let reason = if *proof == EncodedProofPlonk::mock(public_data_commitment) {
+            None
+        } else {
+            Some("not a mock proof of the block".to_string())
+        };
+        Ok(reason)
+    }
+}
+
+impl ProofSystem for MockProofSystem {
+    type Setup = ();
+
+    fn prepare_setup<C: Circuit<Engine> + Clone>(
+        &self,
+        _circuit: C,
+        _block_size: usize,
+    ) -> Result<Self::Setup, failure::Error> {
+        Ok(())
+    }
+
+    fn prove<C: Circuit<Engine> + Clone>(
+        &self,
+        _setup: &Self::Setup,
+        _circuit: C,
+        _block_size: usize,
+        public_data_commitment: &Fr,
+    ) -> Result<EncodedProofPlonk, failure::Error> {
+        Ok(EncodedProofPlonk::mock(public_data_commitment))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::franklin_crypto::bellman::pairing::ff::PrimeField;
+
+    #[test]
+    fn mock_proof_system() {
+        let commitment = Fr::from_str("42").unwrap();
+        let proof = MockProofSystem
+            .prove(&(), EmptyCircuit, 6, &commitment)
+            .unwrap();
+        assert_eq!(
+            MockProofSystem.verify(6, &commitment, &proof).unwrap(),
+            None
+        );
+
+        let other_commitment = Fr::from_str("43").unwrap();
+        assert!(MockProofSystem
+            .verify(6, &other_commitment, &proof)
+            .unwrap()
+            .is_some());
+    }
+
+    #[derive(Clone)]
+    struct EmptyCircuit;
+
+    impl Circuit<Engine> for EmptyCircuit {
+        fn synthesize<CS: crate::franklin_crypto::bellman::ConstraintSystem<Engine>>(
+            self,
+            _cs: &mut CS,
+        ) -> Result<(), crate::franklin_crypto::bellman::SynthesisError> {
+            Ok(())
+        }
+    }
+}
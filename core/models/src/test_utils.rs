@@ -0,0 +1,62 @@
+//! Fixtures shared by the unit tests of the crates operating on the zkSync transactions.
+//! Available under the `test_utils` feature.
+
+use crate::node::{
+    priv_key_from_fs, Account, AccountId, AccountMap, Address, Nonce, PrivateKey, PubKeyHash,
+    Transfer, Withdraw,
+};
+use crypto_exports::rand::{Rng, SeedableRng, XorShiftRng};
+
+/// Address of the test account with the provided ID (zero address is never used).
+pub fn address(account_id: u64) -> Address {
+    Address::from_low_u64_be(account_id + 1)
+}
+
+/// Creates `count` accounts having the provided ETH balance, along with their keys.
+/// Keys are generated from the fixed seed, so they are the same for every call.
+pub fn accounts_with_keys(count: u64, balance: u32) -> (AccountMap, Vec<PrivateKey>) {
+    let rng = &mut XorShiftRng::from_seed([1, 2, 3, 4]);
+    let mut accounts = AccountMap::default();
+    let mut keys = Vec::new();
+    for account_id in 0..count {
+        let private_key = priv_key_from_fs(rng.gen());
+        let mut account = Account::default_with_address(&address(account_id));
+        account.pub_key_hash = PubKeyHash::from_privkey(&private_key);
+        account.set_balance(0, balance.into());
+        accounts.insert(account_id as AccountId, account);
+        keys.push(private_key);
+    }
+    (accounts, keys)
+}
+
+/// Unsigned ETH transfer with zero nonce.
+pub fn transfer(from: Address, to: Address, amount: u64, fee: u32) -> Transfer {
+    Transfer::new(0, from, to, 0, amount.into(), fee.into(), 0, None)
+}
+
+/// Unsigned ETH withdrawal with zero nonce.
+pub fn withdraw(from: Address, to: Address, amount: u64, fee: u32) -> Withdraw {
+    Withdraw::new(0, from, to, 0, amount.into(), fee.into(), 0, None)
+}
+
+/// ETH transfer between the accounts created by `accounts_with_keys`, signed by the sender.
+pub fn signed_transfer(
+    keys: &[PrivateKey],
+    from: u64,
+    to: u64,
+    amount: u32,
+    fee: u32,
+    nonce: Nonce,
+) -> Transfer {
+    Transfer::new_signed(
+        from as AccountId,
+        address(from),
+        address(to),
+        0,
+        amount.into(),
+        fee.into(),
+        nonce,
+        &keys[from as usize],
+    )
+    .expect("Failed to sign the transfer")
+}
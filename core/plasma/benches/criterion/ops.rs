@@ -273,6 +273,46 @@ fn insert_account(b: &mut Bencher<'_>) {
     );
 }
 
+/// Bench for `PlasmaState::execute_txs_in_parallel`.
+///
+/// Transfers are sent between the pairs of accounts, so every transfer can be executed
+/// independently of the other ones.
+fn execute_txs_in_parallel(b: &mut Bencher<'_>) {
+    let (keys, state) = generate_state();
+
+    let transfers: Vec<_> = (0..ACCOUNTS_AMOUNT / 2)
+        .map(|pair| {
+            let (from, to) = (pair * 2, pair * 2 + 1);
+            let (private_key, _) = keys.get(&from).expect("Can't key the private key");
+            let from_account = state.get_account(from).expect("Can't get the account");
+            let to_account = state.get_account(to).expect("Can't get the account");
+
+            let transfer = Transfer::new_signed(
+                from,
+                from_account.address,
+                to_account.address,
+                ETH_TOKEN_ID,
+                10u32.into(),
+                1u32.into(),
+                0,
+                private_key,
+            )
+            .expect("failed to sign transfer");
+            FranklinTx::Transfer(Box::new(transfer))
+        })
+        .collect();
+
+    let setup = || (state.clone(), transfers.clone());
+
+    b.iter_batched(
+        setup,
+        |(mut state, transfers)| {
+            let _ = state.execute_txs_in_parallel(black_box(transfers));
+        },
+        BatchSize::SmallInput,
+    );
+}
+
 pub fn bench_ops(c: &mut Criterion) {
     const INPUT_SIZE: Throughput = Throughput::Elements(1);
 
@@ -294,6 +334,10 @@ pub fn bench_ops(c: &mut Criterion) {
     group.bench_function("PlasmaState::apply_deposit_tx bench", apply_deposit_tx);
     group.bench_function("PlasmaState::apply_full_exit_tx bench", apply_full_exit_tx);
     group.bench_function("PlasmaState::insert_account bench", insert_account);
+    group.bench_function(
+        "PlasmaState::execute_txs_in_parallel bench",
+        execute_txs_in_parallel,
+    );
 
     group.finish();
 }
@@ -0,0 +1,295 @@
+//! Parallel execution of the transactions which don't touch the same accounts.
+//!
+//! Transactions are partitioned into the groups by the accounts they touch, so the
+//! transactions of the different groups are independent of each other. Groups are
+//! distributed among the worker threads, and every worker executes its groups on the
+//! separate state containing only the accounts of these groups. Once all the workers are
+//! done, the changed accounts are inserted into the main state sequentially.
+//!
+//! Transfers to the addresses without an account create the new accounts, and the id of
+//! the created account depends on the amount of the existing ones. Thus such transfers,
+//! along with all the transactions connected to them, are executed sequentially on the
+//! main state (in parallel with the workers).
+
+// Built-in deps
+use std::collections::{HashMap, HashSet};
+// External uses
+use failure::Error;
+use rayon::prelude::*;
+// Workspace uses
+use models::node::{AccountId, AccountMap, Address, FranklinTx};
+// Local uses
+use crate::state::{OpSuccess, PlasmaState};
+
+/// Transaction along with its position in the executed list.
+type IndexedTx = (usize, FranklinTx);
+
+/// Disjoint-set forest over the transactions and the "creates accounts" marker,
+/// which has the index equal to the amount of the transactions.
+struct DisjointSet {
+    parent: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+        }
+    }
+
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parent[node] != node {
+            self.parent[node] = self.parent[self.parent[node]];
+            node = self.parent[node];
+        }
+        node
+    }
+
+    fn union(&mut self, lhs: usize, rhs: usize) {
+        let (lhs, rhs) = (self.find(lhs), self.find(rhs));
+        if lhs != rhs {
+            self.parent[lhs.max(rhs)] = lhs.min(rhs);
+        }
+    }
+}
+
+/// Returns the addresses of the accounts touched by the transaction.
+fn touched_addresses(tx: &FranklinTx) -> Vec<Address> {
+    match tx {
+        FranklinTx::Transfer(transfer) => vec![transfer.from, transfer.to],
+        _ => vec![tx.account()],
+    }
+}
+
+/// Groups of the transactions that can be executed independently of each other.
+#[derive(Debug, Default, PartialEq)]
+struct TxGroups {
+    /// Transactions that must be executed on the main state, since they create accounts
+    /// or are connected to the ones creating accounts.
+    sequential: Vec<usize>,
+    /// Groups of the transactions not touching the accounts of the other groups.
+    independent: Vec<Vec<usize>>,
+}
+
+/// Partitions the transactions into groups, so that the transactions touching the
+/// same account are in the same group. Transactions keep their relative order within
+/// the group.
+fn partition_txs(state: &PlasmaState, txs: &[FranklinTx]) -> TxGroups {
+    let creates_accounts = txs.len();
+    let mut sets = DisjointSet::new(txs.len() + 1);
+    let mut first_tx_by_address = HashMap::new();
+
+    for (idx, tx) in txs.iter().enumerate() {
+        for address in touched_addresses(tx) {
+            let first_tx = *first_tx_by_address.entry(address).or_insert(idx);
+            sets.union(first_tx, idx);
+        }
+        if let FranklinTx::Transfer(transfer) = tx {
+            if state.get_account_by_address(&transfer.to).is_none() {
+                sets.union(creates_accounts, idx);
+            }
+        }
+    }
+
+    let sequential_root = sets.find(creates_accounts);
+    let mut groups = TxGroups::default();
+    let mut group_by_root = HashMap::new();
+    for idx in 0..txs.len() {
+        let root = sets.find(idx);
+        if root == sequential_root {
+            groups.sequential.push(idx);
+            continue;
+        }
+
+        let group = *group_by_root.entry(root).or_insert_with(|| {
+            groups.independent.push(Vec::new());
+            groups.independent.len() - 1
+        });
+        groups.independent[group].push(idx);
+    }
+    groups
+}
+
+impl PlasmaState {
+    /// Executes the transactions, executing the independent ones in parallel.
+    /// Results are the same as the sequential `execute_tx` calls in the provided order
+    /// would produce, and are returned in the same order.
+    pub fn execute_txs_in_parallel(
+        &mut self,
+        txs: Vec<FranklinTx>,
+    ) -> Vec<Result<OpSuccess, Error>> {
+        let groups = partition_txs(self, &txs);
+        let txs_count = txs.len();
+        let mut txs: Vec<_> = txs.into_iter().map(Some).collect();
+        let mut take_txs = |indices: Vec<usize>| -> Vec<IndexedTx> {
+            indices
+                .into_iter()
+                .map(|idx| (idx, txs[idx].take().expect("Tx is taken twice")))
+                .collect()
+        };
+
+        let sequential_txs = take_txs(groups.sequential);
+
+        // Groups are distributed among the workers, so the overhead of creating
+        // the worker states doesn't depend on the amount of the groups.
+        let workers_count = rayon::current_num_threads().min(groups.independent.len());
+        let mut workers_txs = vec![Vec::new(); workers_count];
+        for (group_idx, group) in groups.independent.into_iter().enumerate() {
+            workers_txs[group_idx % workers_count].extend(take_txs(group));
+        }
+        let workers: Vec<_> = workers_txs
+            .into_iter()
+            .map(|txs| (self.worker_state(&txs), txs))
+            .collect();
+
+        let (sequential_results, workers_results) = rayon::join(
+            || execute_indexed_txs(self, sequential_txs),
+            || {
+                workers
+                    .into_par_iter()
+                    .map(|(mut state, txs)| {
+                        let results = execute_indexed_txs(&mut state, txs);
+                        (state, results)
+                    })
+                    .collect::<Vec<_>>()
+            },
+        );
+
+        let mut results: Vec<_> = (0..txs_count).map(|_| None).collect();
+        for (idx, result) in sequential_results {
+            results[idx] = Some(result);
+        }
+        for (state, worker_results) in workers_results {
+            let mut changed_accounts = HashSet::new();
+            for (idx, result) in worker_results {
+                if let Ok(success) = &result {
+                    changed_accounts.extend(success.updates.iter().map(|(id, _)| *id));
+                }
+                results[idx] = Some(result);
+            }
+            self.insert_changed_accounts(&state, changed_accounts);
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("Tx was not executed"))
+            .collect()
+    }
+
+    /// Creates the state containing only the existing accounts touched by the transactions.
+    fn worker_state(&self, txs: &[IndexedTx]) -> PlasmaState {
+        let accounts: AccountMap = txs
+            .iter()
+            .flat_map(|(_, tx)| touched_addresses(tx))
+            .filter_map(|address| self.get_account_by_address(&address))
+            .collect();
+        PlasmaState::from_acc_map(accounts, self.block_number)
+    }
+
+    /// Moves the accounts changed by the worker into the main state.
+    fn insert_changed_accounts(&mut self, worker: &PlasmaState, accounts: HashSet<AccountId>) {
+        for account_id in accounts {
+            let account = worker
+                .get_account(account_id)
+                .expect("Changed account is missing in the worker state");
+            self.insert_account(account_id, account);
+        }
+    }
+}
+
+fn execute_indexed_txs(
+    state: &mut PlasmaState,
+    txs: Vec<IndexedTx>,
+) -> Vec<(usize, Result<OpSuccess, Error>)> {
+    txs.into_iter()
+        .map(|(idx, tx)| (idx, state.execute_tx(tx)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::{Nonce, PrivateKey};
+    use models::test_utils::{accounts_with_keys, address, signed_transfer};
+
+    const ACCOUNTS: u64 = 6;
+    /// Address without an account in the test state.
+    const NEW_ACCOUNT: u64 = 100;
+
+    /// Creates the state with the accounts having some ETH balance, along with their keys.
+    fn test_state() -> (PlasmaState, Vec<PrivateKey>) {
+        let (accounts, keys) = accounts_with_keys(ACCOUNTS, 1000);
+        (PlasmaState::from_acc_map(accounts, 1), keys)
+    }
+
+    fn transfer(keys: &[PrivateKey], from: u64, to: u64, nonce: Nonce) -> FranklinTx {
+        FranklinTx::Transfer(Box::new(signed_transfer(keys, from, to, 10, 0, nonce)))
+    }
+
+    #[test]
+    fn partitioning() {
+        let (state, keys) = test_state();
+        let txs = vec![
+            transfer(&keys, 0, 1, 0),
+            transfer(&keys, 2, 3, 0),
+            transfer(&keys, 1, 4, 0),
+            transfer(&keys, 5, NEW_ACCOUNT, 0),
+            transfer(&keys, 3, 2, 0),
+        ];
+
+        let groups = partition_txs(&state, &txs);
+        assert_eq!(
+            groups,
+            TxGroups {
+                sequential: vec![3],
+                independent: vec![vec![0, 2], vec![1, 4]],
+            }
+        );
+    }
+
+    /// Checks that the results of the parallel execution match the sequential one,
+    /// including the failed transactions and the created accounts.
+    #[test]
+    fn same_results_as_sequential() {
+        let (mut parallel_state, keys) = test_state();
+        let mut sequential_state = parallel_state.clone();
+        let txs = vec![
+            transfer(&keys, 0, 1, 0),
+            transfer(&keys, 2, 3, 0),
+            transfer(&keys, 1, 4, 0),
+            transfer(&keys, 0, NEW_ACCOUNT, 1),
+            transfer(&keys, 3, 2, 0),
+            transfer(&keys, 5, 5, 0),
+            // Nonce mismatch.
+            transfer(&keys, 2, 3, 5),
+        ];
+
+        let parallel_results = parallel_state.execute_txs_in_parallel(txs.clone());
+        assert_eq!(parallel_results.len(), txs.len());
+        for (tx, parallel_result) in txs.into_iter().zip(parallel_results) {
+            let sequential_result = sequential_state.execute_tx(tx);
+            match (sequential_result, parallel_result) {
+                (Ok(sequential), Ok(parallel)) => assert_eq!(
+                    format!("{:?}", sequential.updates),
+                    format!("{:?}", parallel.updates)
+                ),
+                (Err(sequential), Err(parallel)) => {
+                    assert_eq!(sequential.to_string(), parallel.to_string())
+                }
+                (sequential, parallel) => panic!(
+                    "Results mismatch, sequential: {:?}, parallel: {:?}",
+                    sequential, parallel
+                ),
+            }
+        }
+        assert_eq!(parallel_state.root_hash(), sequential_state.root_hash());
+        assert_eq!(
+            parallel_state
+                .get_account_by_address(&address(NEW_ACCOUNT))
+                .map(|(id, _)| id),
+            sequential_state
+                .get_account_by_address(&address(NEW_ACCOUNT))
+                .map(|(id, _)| id)
+        );
+    }
+}
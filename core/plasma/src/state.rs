@@ -116,16 +116,51 @@ impl PlasmaState {
     }
 
     pub fn chunks_for_tx(&self, franklin_tx: &FranklinTx) -> usize {
-        match franklin_tx {
-            FranklinTx::Transfer(tx) => {
-                if self.get_account_by_address(&tx.to).is_some() {
-                    TransferOp::CHUNKS
-                } else {
-                    TransferToNewOp::CHUNKS
+        let recipient_exists = match franklin_tx {
+            FranklinTx::Transfer(tx) => self.get_account_by_address(&tx.to).is_some(),
+            _ => true,
+        };
+        franklin_tx.chunks(recipient_exists)
+    }
+
+    /// Checks whether the priority operation can be applied to the current state as requested.
+    ///
+    /// Priority operations can't be rejected, so the operation is executed regardless of the
+    /// result: e.g. the full exit of the missing account withdraws nothing. However, such an
+    /// operation should be recorded as invalid before its inclusion into the block.
+    pub fn validate_priority_op(&self, op: &FranklinPriorityOp) -> Result<(), Error> {
+        match op {
+            FranklinPriorityOp::Deposit(op) => {
+                ensure!(
+                    op.token <= params::max_token_id(),
+                    "Deposit token {} is not supported",
+                    op.token
+                );
+                if self.get_account_by_address(&op.to).is_none() {
+                    ensure!(
+                        self.get_free_account_id() <= max_account_id(),
+                        "There is no free account id for the deposit recipient"
+                    );
                 }
             }
-            _ => franklin_tx.min_chunks(),
+            FranklinPriorityOp::FullExit(op) => {
+                ensure!(
+                    op.token <= params::max_token_id(),
+                    "Full exit token {} is not supported",
+                    op.token
+                );
+                let account = self.get_account(op.account_id).ok_or_else(|| {
+                    format_err!("Full exit account {} does not exist", op.account_id)
+                })?;
+                ensure!(
+                    account.address == op.eth_address,
+                    "Full exit account {} is not owned by the {:?} address",
+                    op.account_id,
+                    op.eth_address
+                );
+            }
         }
+        Ok(())
     }
 
     /// Priority op execution should not fail.
@@ -446,7 +481,19 @@ impl PlasmaState {
         self.balance_tree.insert(id, account);
     }
 
-    #[allow(dead_code)]
+    /// Reverts the provided account updates, restoring the state preceding them.
+    /// Updates must be the latest ones applied to the state.
+    pub fn revert_account_updates(&mut self, updates: &[(AccountId, AccountUpdate)]) {
+        for (account_id, update) in updates.iter().rev() {
+            let account =
+                Account::apply_update(self.get_account(*account_id), update.reversed_update());
+            match account {
+                Some(account) => self.insert_account(*account_id, account),
+                None => self.remove_account(*account_id),
+            }
+        }
+    }
+
     fn remove_account(&mut self, id: AccountId) {
         if let Some(account) = self.get_account(id) {
             self.account_id_by_address.remove(&account.address);
@@ -0,0 +1,166 @@
+//! Benchmark of the prover on the synthetic block.
+//!
+//! Runs the same stages as the prover does for the real block, without the server:
+//! witness generation, setup preparation and proving. Reported timings and peak memory
+//! are used to size the hardware for the block sizes without running the full network.
+
+// Built-in deps
+use std::fmt;
+use std::time::Instant;
+// External deps
+use crypto_exports::ff::PrimeField;
+use num::BigUint;
+// Workspace deps
+use circuit::witness::{deposit::DepositWitness, utils::WitnessBuilder, Witness};
+use models::circuit::{account::CircuitAccount, CircuitAccountTree};
+use models::node::{operations::DepositOp, Account, Address, Deposit, Fr};
+use models::prover_utils::proof_system::{PlonkProofSystem, ProofSystem};
+// Local deps
+use crate::prover_data::ProverData;
+
+/// Timings of the prover stages for the synthetic block.
+#[derive(Debug, Clone)]
+pub struct BenchReport {
+    pub block_size: usize,
+    /// Amount of the operations in the synthetic block.
+    pub operations: usize,
+    pub witness_ms: u64,
+    pub setup_ms: u64,
+    pub proving_ms: u64,
+    /// Peak resident memory of the prover process, `None` if it can't be determined.
+    pub peak_memory_mb: Option<u64>,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Block size: {} chunks, {} operations",
+            self.block_size, self.operations
+        )?;
+        writeln!(f, "Witness generation: {} ms", self.witness_ms)?;
+        writeln!(f, "Setup preparation: {} ms", self.setup_ms)?;
+        writeln!(f, "Proving: {} ms", self.proving_ms)?;
+        writeln!(
+            f,
+            "Total: {} ms",
+            self.witness_ms + self.setup_ms + self.proving_ms
+        )?;
+        match self.peak_memory_mb {
+            Some(peak_memory_mb) => write!(f, "Peak memory: {} MB", peak_memory_mb),
+            None => write!(f, "Peak memory: unknown"),
+        }
+    }
+}
+
+/// Builds the witness of the block filled with deposits to the new accounts,
+/// which is the most common operation in the real blocks.
+pub fn synthetic_block(block_size: usize) -> Result<(ProverData, usize), failure::Error> {
+    failure::ensure!(
+        block_size >= DepositOp::CHUNKS,
+        "Block size {} can't fit a single operation",
+        block_size
+    );
+    let mut circuit_account_tree = CircuitAccountTree::new(models::params::account_tree_depth());
+    let fee_account_id = 0;
+    let fee_account = Account::default_with_address(&Address::default());
+    circuit_account_tree.insert(fee_account_id, CircuitAccount::from(fee_account));
+
+    let mut witness_accum = WitnessBuilder::new(&mut circuit_account_tree, fee_account_id, 1);
+
+    let operations = block_size / DepositOp::CHUNKS;
+    for account_id in 1..=operations as u32 {
+        let address = Address::from_low_u64_be(u64::from(account_id));
+        let deposit_op = DepositOp {
+            priority_op: Deposit {
+                from: address,
+                token: 0,
+                amount: BigUint::from(1u32),
+                to: address,
+            },
+            account_id,
+        };
+        let deposit_witness =
+            DepositWitness::apply_tx(&mut witness_accum.account_tree, &deposit_op);
+        let deposit_operations = deposit_witness.calculate_operations(());
+        let pub_data_from_witness = deposit_witness.get_pubdata();
+        witness_accum.add_operation_with_pubdata(deposit_operations, pub_data_from_witness);
+    }
+    witness_accum.extend_pubdata_with_noops(block_size);
+    witness_accum.collect_fees(&[]);
+    witness_accum.calculate_pubdata_commitment();
+
+    let prover_data = ProverData {
+        public_data_commitment: witness_accum.pubdata_commitment.unwrap(),
+        old_root: witness_accum.initial_root_hash,
+        initial_used_subtree_root: witness_accum.initial_used_subtree_root_hash,
+        new_root: witness_accum.root_after_fees.unwrap(),
+        validator_address: Fr::from_str(&witness_accum.fee_account_id.to_string())
+            .expect("failed to parse"),
+        operations: witness_accum.operations,
+        validator_balances: witness_accum.fee_account_balances.unwrap(),
+        validator_audit_path: witness_accum.fee_account_audit_path.unwrap(),
+        validator_account: witness_accum.fee_account_witness.unwrap(),
+    };
+    Ok((prover_data, operations))
+}
+
+/// Peak resident memory of the current process in megabytes.
+/// Only available on Linux, where it's read from `/proc/self/status`.
+pub fn peak_memory_mb() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find(|line| line.starts_with("VmHWM:"))
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb / 1024)
+}
+
+/// Proves the synthetic block of the given size and measures every stage.
+pub fn run_bench(
+    block_size: usize,
+    download_setup_from_network: bool,
+) -> Result<BenchReport, failure::Error> {
+    log::info!("Generating witness for the block of size {}", block_size);
+    let witness_started = Instant::now();
+    let (prover_data, operations) = synthetic_block(block_size)?;
+    let witness_ms = witness_started.elapsed().as_millis() as u64;
+
+    let proof_system = PlonkProofSystem {
+        download_setup_from_network,
+    };
+    log::info!("Preparing setup");
+    let setup_started = Instant::now();
+    let setup = proof_system.prepare_setup(prover_data.clone().into_circuit(1), block_size)?;
+    let setup_ms = setup_started.elapsed().as_millis() as u64;
+
+    log::info!("Computing proof");
+    let proving_started = Instant::now();
+    let commitment = prover_data.public_data_commitment;
+    proof_system.prove(&setup, prover_data.into_circuit(1), block_size, &commitment)?;
+    let proving_ms = proving_started.elapsed().as_millis() as u64;
+
+    Ok(BenchReport {
+        block_size,
+        operations,
+        witness_ms,
+        setup_ms,
+        proving_ms,
+        peak_memory_mb: peak_memory_mb(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthetic_block_is_full() {
+        let (prover_data, operations) = synthetic_block(30).unwrap();
+        assert_eq!(operations, 5);
+        assert_eq!(prover_data.operations.len(), 30);
+
+        assert!(synthetic_block(DepositOp::CHUNKS - 1).is_err());
+    }
+}
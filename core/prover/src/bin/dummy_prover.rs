@@ -78,17 +78,25 @@ impl<C: ApiClient> ProverImpl<C> for DummyProver<C> {
         }
 
         log::info!("got job id: {}, block {}", job_id, block);
-        let _instance = self.api_client.prover_data(block).map_err(|err| {
+        let instance = self.api_client.prover_data(block).map_err(|err| {
             BabyProverError::Api(format!(
                 "could not get prover data for block {}: {}",
                 block, err
             ))
         })?;
+        let public_data_commitment = instance.pub_data_commitment.ok_or_else(|| {
+            BabyProverError::Internal(format!("no public data commitment for block {}", block))
+        })?;
 
         log::info!("starting to compute proof for block {}", block,);
 
+        // Mock proof is the same for the same block, so it can be checked by the server.
         self.api_client
-            .publish(block, EncodedProofPlonk::default())
+            .publish(
+                block,
+                EncodedProofPlonk::mock(&public_data_commitment),
+                None,
+            )
             .map_err(|e| BabyProverError::Api(format!("failed to publish proof: {}", e)))?;
 
         log::info!("finished and published proof for block {}", block);
@@ -1,6 +1,53 @@
-use prover::cli_utils::main_for_prover_impl;
-use prover::plonk_step_by_step_prover::PlonkStepByStepProver;
+use clap::{value_t, App, AppSettings, Arg, SubCommand};
+use models::config_options::parse_env;
+use models::prover_utils::key_checks::check_verification_keys;
+use prover::bench::run_bench;
+use prover::cli_utils::run_prover_impl;
+use prover::plonk_step_by_step_prover::{PlonkStepByStepProver, PlonkStepByStepProverConfig};
+use prover::resources::ProverResources;
+use prover::ProverConfig;
 
 fn main() {
-    main_for_prover_impl::<PlonkStepByStepProver<prover::client::ApiClient>>();
+    let cli = App::new("Plonk step by step prover")
+        .author("Matter Labs")
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .arg(
+            Arg::with_name("worker_name")
+                .help("Name of the worker. Must be unique!")
+                .required(true)
+                .index(1),
+        )
+        .subcommand(
+            SubCommand::with_name("bench")
+                .about("Proves the synthetic block and prints the timings of the proving stages")
+                .arg(
+                    Arg::with_name("block_size")
+                        .long("block-size")
+                        .help("Size of the block in chunks")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    if let Some(bench) = cli.subcommand_matches("bench") {
+        env_logger::init();
+        let block_size = value_t!(bench, "block_size", usize).unwrap_or_else(|e| e.exit());
+        // Benchmark runs under the same resource limits as the prover.
+        ProverResources::from_env()
+            .apply()
+            .expect("failed to apply prover resource limits");
+        let report = run_bench(block_size, parse_env("PROVER_DOWNLOAD_SETUP"))
+            .expect("Prover benchmark failed");
+        println!("{}", report);
+        return;
+    }
+
+    // Proofs computed with the keys which don't match the circuit would be rejected anyway.
+    let config = PlonkStepByStepProverConfig::from_env();
+    check_verification_keys(&config.block_sizes)
+        .expect("Refusing to start with invalid verification keys");
+
+    let worker_name = cli.value_of("worker_name").unwrap();
+    run_prover_impl::<PlonkStepByStepProver<prover::client::ApiClient>>(worker_name);
 }
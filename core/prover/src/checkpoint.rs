@@ -0,0 +1,101 @@
+//! Checkpoints of the computed proofs.
+//!
+//! Proof of the large block is computed for hours, so it's stored on disk before being
+//! published. If the prover crashes or can't reach the server before the proof is published,
+//! the restarted prover publishes the stored proof instead of proving the block again.
+//! Checkpoints are identified by the block number along with its public data commitment,
+//! so the proof of a reverted block is never published for another block with the same number.
+
+// Built-in deps
+use std::fs;
+use std::path::PathBuf;
+// External deps
+use failure::format_err;
+// Workspace deps
+use models::fe_to_hex;
+use models::node::Fr;
+use models::prover_utils::EncodedProofPlonk;
+
+/// Directory with the proofs computed but not yet published by the prover.
+#[derive(Debug, Clone)]
+pub struct ProofCheckpoints {
+    dir: PathBuf,
+}
+
+impl ProofCheckpoints {
+    /// Creates the checkpoints directory if it doesn't exist.
+    pub fn new(dir: PathBuf) -> Result<Self, failure::Error> {
+        fs::create_dir_all(&dir).map_err(|e| {
+            format_err!(
+                "Failed to create checkpoints directory {}: {}",
+                dir.display(),
+                e
+            )
+        })?;
+        Ok(Self { dir })
+    }
+
+    fn block_prefix(block: i64) -> String {
+        format!("block_{}_", block)
+    }
+
+    fn proof_path(&self, block: i64, commitment: &Fr) -> PathBuf {
+        self.dir.join(format!(
+            "{}{}.json",
+            Self::block_prefix(block),
+            fe_to_hex(commitment)
+        ))
+    }
+
+    /// Stores the computed proof of the block.
+    /// The proof is written to a temporary file first, so the crash in the middle of
+    /// the write doesn't leave a corrupted checkpoint.
+    pub fn store(
+        &self,
+        block: i64,
+        commitment: &Fr,
+        proof: &EncodedProofPlonk,
+    ) -> Result<(), failure::Error> {
+        let path = self.proof_path(block, commitment);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(proof)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Loads the stored proof of the block with the given public data commitment.
+    /// Returns `None` if there is no valid checkpoint for the block.
+    pub fn load(&self, block: i64, commitment: &Fr) -> Option<EncodedProofPlonk> {
+        let path = self.proof_path(block, commitment);
+        let contents = fs::read(&path).ok()?;
+        serde_json::from_slice(&contents)
+            .map_err(|e| {
+                log::warn!("Invalid proof checkpoint {}: {}", path.display(), e);
+            })
+            .ok()
+    }
+
+    /// Removes the checkpoints of the block, including the ones made for the reverted blocks
+    /// with the same number.
+    pub fn remove(&self, block: i64) {
+        let prefix = Self::block_prefix(block);
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("Failed to read checkpoints directory: {}", e);
+                return;
+            }
+        };
+        for entry in entries.filter_map(Result::ok) {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                if let Err(e) = fs::remove_file(entry.path()) {
+                    log::warn!(
+                        "Failed to remove proof checkpoint {}: {}",
+                        entry.path().display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
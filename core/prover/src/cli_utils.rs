@@ -17,7 +17,15 @@ use crate::{client, start, ApiClient, ProverConfig, ProverImpl, ShutdownRequest}
 fn api_client_from_env(worker_name: &str) -> client::ApiClient {
     let server_api_url = parse_env("PROVER_SERVER_URL");
     let request_timout = Duration::from_secs(parse_env::<u64>("REQ_SERVER_TIMEOUT"));
-    client::ApiClient::new(&server_api_url, worker_name, request_timout)
+    let auth_token = std::env::var("PROVER_API_TOKEN").ok();
+    let circuit_version = ProverOptions::from_env().circuit_version;
+    client::ApiClient::new(
+        &server_api_url,
+        worker_name,
+        request_timout,
+        auth_token,
+        Some(circuit_version),
+    )
 }
 
 pub fn main_for_prover_impl<P: ProverImpl<client::ApiClient> + 'static + Send + Sync>() {
@@ -32,6 +40,13 @@ pub fn main_for_prover_impl<P: ProverImpl<client::ApiClient> + 'static + Send +
         .get_matches();
     let worker_name = cli.value_of("worker_name").unwrap();
 
+    run_prover_impl::<P>(worker_name);
+}
+
+/// Registers the prover with the given worker name on the server and runs it until it's stopped.
+pub fn run_prover_impl<P: ProverImpl<client::ApiClient> + 'static + Send + Sync>(
+    worker_name: &str,
+) {
     // used env
     let heartbeat_interval = ProverOptions::from_env().heartbeat_interval;
     let prover_config = <P as ProverImpl<client::ApiClient>>::Config::from_env();
@@ -74,10 +89,28 @@ pub fn main_for_prover_impl<P: ProverImpl<client::ApiClient> + 'static + Send +
 
     // Register prover
     let prover_id = api_client
-        .register_prover(0)
+        .register_prover(0, prover.capabilities())
         .expect("failed to register prover");
     shutdown_request.set_prover_id(prover_id);
 
+    // Keep notifying the server that the prover is alive, so its jobs aren't reassigned.
+    // Drain request is handled the same way as the termination signal.
+    {
+        let api_client = api_client.clone();
+        let shutdown_request = shutdown_request.clone();
+        thread::spawn(move || loop {
+            thread::sleep(heartbeat_interval);
+            match api_client.prover_heartbeat(prover_id) {
+                Ok(true) if !shutdown_request.get() => {
+                    log::info!("Drain requested, stopping after the currently working round");
+                    shutdown_request.set();
+                }
+                Ok(_) => {}
+                Err(e) => log::error!("prover heartbeat request erred: {}", e),
+            }
+        });
+    }
+
     // Start prover
     let (exit_err_tx, exit_err_rx) = mpsc::channel();
     let jh = thread::spawn(move || {
@@ -1,4 +1,5 @@
 // Built-in deps
+use std::cell::RefCell;
 use std::str::FromStr;
 use std::time::{self, Duration};
 // External deps
@@ -6,19 +7,35 @@ use backoff::Operation;
 use failure::bail;
 use failure::format_err;
 use log::*;
-use reqwest::Url;
+use reqwest::{header, Url};
 use serde::{Deserialize, Serialize};
 // Workspace deps
 use crate::client;
 use crate::prover_data::ProverData;
+use crate::transport;
 use circuit::circuit::FranklinCircuit;
 use models::node::Engine;
-use models::prover_utils::EncodedProofPlonk;
+use models::prover_utils::{EncodedProofPlonk, ProofTimings};
 
 #[derive(Serialize, Deserialize)]
 pub struct ProverReq {
     pub name: String,
     pub block_size: usize,
+    /// Version of the circuit the prover proves blocks with.
+    /// Server's current version is assumed if not set.
+    #[serde(default)]
+    pub circuit_version: Option<String>,
+    /// Capabilities of the prover, sent only on registration.
+    #[serde(default)]
+    pub capabilities: Option<ProverCapabilities>,
+}
+
+/// Description of the prover hardware and supported blocks, recorded in the prover registry.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProverCapabilities {
+    /// Block sizes the prover can prove.
+    pub block_sizes: Vec<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -32,10 +49,28 @@ pub struct WorkingOnReq {
     pub prover_run_id: i32,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct HeartbeatReq {
+    pub prover_id: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HeartbeatRes {
+    /// Whether the prover is requested to finish its current job and stop.
+    #[serde(default)]
+    pub drain: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PublishReq {
     pub block: u32,
     pub proof: EncodedProofPlonk,
+    /// Name of the prover which computed the proof.
+    #[serde(default)]
+    pub worker: Option<String>,
+    /// Time spent on the proof computation stages, if measured by the prover.
+    #[serde(default)]
+    pub timings: Option<ProofTimings>,
 }
 
 #[derive(Debug, Clone)]
@@ -43,31 +78,51 @@ pub struct ApiClient {
     register_url: Url,
     block_to_prove_url: Url,
     working_on_url: Url,
+    heartbeat_url: Url,
     prover_data_url: Url,
     publish_url: Url,
     stopped_url: Url,
     worker: String,
+    circuit_version: Option<String>,
     // client keeps connection pool inside, so it is recommended to reuse it (see docstring for reqwest::Client)
     http_client: reqwest::blocking::Client,
 }
 
 impl ApiClient {
-    pub fn new(base_url: &Url, worker: &str, req_server_timeout: time::Duration) -> Self {
+    /// Creates the client for the prover server API.
+    /// If `auth_token` is provided, it is sent with every request in the `Authorization` header.
+    /// If `circuit_version` is provided, only the blocks proposed under it are requested.
+    pub fn new(
+        base_url: &Url,
+        worker: &str,
+        req_server_timeout: time::Duration,
+        auth_token: Option<String>,
+        circuit_version: Option<String>,
+    ) -> Self {
         if worker == "" {
             panic!("worker name cannot be empty")
         }
+        let mut headers = header::HeaderMap::new();
+        if let Some(token) = auth_token {
+            let value = header::HeaderValue::from_str(&format!("Bearer {}", token))
+                .expect("invalid prover API token");
+            headers.insert(header::AUTHORIZATION, value);
+        }
         let http_client = reqwest::blocking::ClientBuilder::new()
             .timeout(req_server_timeout)
+            .default_headers(headers)
             .build()
             .expect("Failed to create request client");
         Self {
             register_url: base_url.join("/register").unwrap(),
             block_to_prove_url: base_url.join("/block_to_prove").unwrap(),
             working_on_url: base_url.join("/working_on").unwrap(),
+            heartbeat_url: base_url.join("/heartbeat").unwrap(),
             prover_data_url: base_url.join("/prover_data").unwrap(),
             publish_url: base_url.join("/publish").unwrap(),
             stopped_url: base_url.join("/stopped").unwrap(),
             worker: worker.to_string(),
+            circuit_version,
             http_client,
         }
     }
@@ -97,6 +152,91 @@ impl ApiClient {
             })
     }
 
+    /// Downloads the compressed witness of the block by chunks and returns it decompressed.
+    /// Chunks received before the failure are kept in `downloaded`, so the retried download
+    /// is resumed from the last received chunk. Download starts over if the witness
+    /// is changed on the server, which is detected by its `ETag`.
+    /// Servers which don't support the compressed witnesses send the whole witness at once.
+    fn download_witness(
+        &self,
+        block: i64,
+        downloaded: &mut Vec<u8>,
+        etag: &mut Option<String>,
+    ) -> Result<Vec<u8>, failure::Error> {
+        loop {
+            let last = downloaded.len() + transport::WITNESS_DOWNLOAD_CHUNK_SIZE - 1;
+            let res = self
+                .http_client
+                .get(self.prover_data_url.as_str())
+                .header(header::ACCEPT_ENCODING, transport::ZSTD_ENCODING)
+                .header(
+                    header::RANGE,
+                    format!("bytes={}-{}", downloaded.len(), last),
+                )
+                .json(&block)
+                .send()
+                .map_err(|e| format_err!("failed to request prover data: {}", e))?;
+            if res.status() == reqwest::StatusCode::NOT_FOUND {
+                bail!("ProverData for block {} is not ready yet", block);
+            }
+            let compressed = res
+                .headers()
+                .get(header::CONTENT_ENCODING)
+                .map(|value| value == transport::ZSTD_ENCODING)
+                .unwrap_or(false);
+            if res.status() == reqwest::StatusCode::OK && !compressed {
+                let serialized = res
+                    .bytes()
+                    .map_err(|e| format_err!("failed to read prover data response: {}", e))?;
+                return Ok(serialized.to_vec());
+            }
+            if res.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                bail!("prover data request failed with status: {}", res.status());
+            }
+
+            let received_etag = res
+                .headers()
+                .get(header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from);
+            if *etag != received_etag {
+                if !downloaded.is_empty() {
+                    warn!("witness for block {} has changed, downloading again", block);
+                    downloaded.clear();
+                    *etag = None;
+                    continue;
+                }
+                *etag = received_etag;
+            }
+            let (offset, total_len) = res
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(transport::parse_content_range)
+                .ok_or_else(|| format_err!("invalid Content-Range of the prover data"))?;
+            failure::ensure!(
+                offset == downloaded.len() as u64,
+                "unexpected prover data chunk offset"
+            );
+            let chunk = res
+                .bytes()
+                .map_err(|e| format_err!("failed to read prover data response: {}", e))?;
+            downloaded.extend_from_slice(&chunk);
+            trace!(
+                "downloaded {} of {} bytes of the witness for block {}",
+                downloaded.len(),
+                total_len,
+                block
+            );
+            if downloaded.len() as u64 >= total_len {
+                let compressed = std::mem::replace(downloaded, Vec::new());
+                *etag = None;
+                return transport::decompress(&compressed)
+                    .map_err(|e| format_err!("failed to decompress prover data: {}", e));
+            }
+        }
+    }
+
     fn get_backoff() -> backoff::ExponentialBackoff {
         let mut backoff = backoff::ExponentialBackoff::default();
         backoff.current_interval = Duration::from_secs(1);
@@ -107,7 +247,11 @@ impl ApiClient {
         backoff
     }
 
-    pub fn register_prover(&self, block_size: usize) -> Result<i32, failure::Error> {
+    pub fn register_prover(
+        &self,
+        block_size: usize,
+        capabilities: ProverCapabilities,
+    ) -> Result<i32, failure::Error> {
         let op = || -> Result<i32, failure::Error> {
             info!("Registering prover...");
             let res = self
@@ -116,10 +260,15 @@ impl ApiClient {
                 .json(&client::ProverReq {
                     name: self.worker.clone(),
                     block_size,
+                    circuit_version: self.circuit_version.clone(),
+                    capabilities: Some(capabilities.clone()),
                 })
                 .send();
 
             let res = res.map_err(|e| format_err!("register request failed: {}", e))?;
+            if res.status() == reqwest::StatusCode::UNAUTHORIZED {
+                bail!("prover is not authorized, check the PROVER_API_TOKEN value");
+            }
             let text = res
                 .text()
                 .map_err(|e| format_err!("failed to read register response: {}", e))?;
@@ -130,6 +279,30 @@ impl ApiClient {
 
         Ok(self.with_retries(&op)?)
     }
+
+    /// Notifies the server that the prover is alive, so its jobs are not reassigned.
+    /// Returns `true` if the prover is requested to drain, i.e. finish its current job and stop.
+    pub fn prover_heartbeat(&self, prover_id: i32) -> Result<bool, failure::Error> {
+        trace!("sending heartbeat for prover {}", prover_id);
+        let res = self
+            .http_client
+            .post(self.heartbeat_url.as_str())
+            .json(&client::HeartbeatReq { prover_id })
+            .send()
+            .map_err(|e| format_err!("failed to send heartbeat request: {}", e))?;
+        if res.status() == reqwest::StatusCode::GONE {
+            bail!("prover {} is considered dead by the server", prover_id)
+        } else if res.status() != reqwest::StatusCode::OK {
+            bail!("heartbeat request failed with status: {}", res.status())
+        } else {
+            // Servers without drain support respond with an empty body.
+            let drain = res
+                .json::<client::HeartbeatRes>()
+                .map(|res| res.drain)
+                .unwrap_or(false);
+            Ok(drain)
+        }
+    }
 }
 
 impl crate::ApiClient for ApiClient {
@@ -142,6 +315,8 @@ impl crate::ApiClient for ApiClient {
                 .json(&client::ProverReq {
                     name: self.worker.clone(),
                     block_size,
+                    circuit_version: self.circuit_version.clone(),
+                    capabilities: None,
                 })
                 .send()
                 .map_err(|e| format_err!("block to prove request failed: {}", e))?;
@@ -159,7 +334,7 @@ impl crate::ApiClient for ApiClient {
         Ok(self.with_retries(&op)?)
     }
 
-    fn working_on(&self, job_id: i32) -> Result<(), failure::Error> {
+    fn working_on(&self, job_id: i32) -> Result<bool, failure::Error> {
         trace!("sending working_on {}", job_id);
         let res = self
             .http_client
@@ -169,26 +344,28 @@ impl crate::ApiClient for ApiClient {
             })
             .send()
             .map_err(|e| format_err!("failed to send working on request: {}", e))?;
-        if res.status() != reqwest::StatusCode::OK {
+        if res.status() == reqwest::StatusCode::GONE {
+            warn!(
+                "lease of the job {} has expired, the block is reverted or reassigned",
+                job_id
+            );
+            Ok(false)
+        } else if res.status() != reqwest::StatusCode::OK {
             bail!("working on request failed with status: {}", res.status())
         } else {
-            Ok(())
+            Ok(true)
         }
     }
 
     fn prover_data(&self, block: i64) -> Result<FranklinCircuit<'_, Engine>, failure::Error> {
+        // Download progress is kept between the retries.
+        let downloaded = RefCell::new(Vec::new());
+        let etag = RefCell::new(None);
         let op = || -> Result<ProverData, failure::Error> {
             trace!("sending prover_data");
-            let res = self
-                .http_client
-                .get(self.prover_data_url.as_str())
-                .json(&block)
-                .send()
-                .map_err(|e| format_err!("failed to request prover data: {}", e))?;
-            let text = res
-                .text()
-                .map_err(|e| format_err!("failed to read prover data response: {}", e))?;
-            let res: Option<ProverData> = serde_json::from_str(&text)
+            let serialized =
+                self.download_witness(block, &mut downloaded.borrow_mut(), &mut etag.borrow_mut())?;
+            let res: Option<ProverData> = serde_json::from_slice(&serialized)
                 .map_err(|e| format_err!("failed to parse prover data response: {}", e))?;
             Ok(res.ok_or_else(|| format_err!("ProverData for block {} is not ready yet", block))?)
         };
@@ -197,17 +374,27 @@ impl crate::ApiClient for ApiClient {
         Ok(prover_data.into_circuit(block))
     }
 
-    fn publish(&self, block: i64, proof: EncodedProofPlonk) -> Result<(), failure::Error> {
+    fn publish(
+        &self,
+        block: i64,
+        proof: EncodedProofPlonk,
+        timings: Option<ProofTimings>,
+    ) -> Result<(), failure::Error> {
         let op = move || -> Result<(), failure::Error> {
             trace!("Trying publish proof {}", block);
             let proof = proof.clone();
+            let body = serde_json::to_vec(&client::PublishReq {
+                block: block as u32,
+                proof,
+                worker: Some(self.worker.clone()),
+                timings: timings.clone(),
+            })?;
             let res = self
                 .http_client
                 .post(self.publish_url.as_str())
-                .json(&client::PublishReq {
-                    block: block as u32,
-                    proof,
-                })
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(header::CONTENT_ENCODING, transport::ZSTD_ENCODING)
+                .body(transport::compress(&body))
                 .send()
                 .map_err(|e| format_err!("failed to send publish request: {}", e))?;
             let status = res.status();
@@ -1,9 +1,13 @@
+pub mod bench;
+pub mod checkpoint;
 pub mod cli_utils;
 pub mod client;
 pub mod exit_proof;
 pub mod plonk_step_by_step_prover;
 pub mod prover_data;
+pub mod resources;
 pub mod serialization;
+pub mod transport;
 
 // Built-in deps
 use std::sync::{
@@ -18,7 +22,11 @@ use std::{
 // External deps
 use rand::Rng;
 // Workspace deps
-use models::{config_options::ProverOptions, node::Engine, prover_utils::EncodedProofPlonk};
+use models::{
+    config_options::ProverOptions,
+    node::Engine,
+    prover_utils::{EncodedProofPlonk, ProofTimings},
+};
 
 const ABSENT_PROVER_ID: i32 = -1;
 
@@ -81,16 +89,27 @@ pub trait ProverImpl<C: ApiClient> {
     ) -> Result<(), BabyProverError>;
     /// Returns client reference and config needed for heartbeat.
     fn get_heartbeat_options(&self) -> (&C, Duration);
+    /// Capabilities of the prover reported to the server on registration.
+    fn capabilities(&self) -> client::ProverCapabilities {
+        client::ProverCapabilities::default()
+    }
 }
 
 pub trait ApiClient: Debug {
     fn block_to_prove(&self, block_size: usize) -> Result<Option<(i64, i32)>, failure::Error>;
-    fn working_on(&self, job_id: i32) -> Result<(), failure::Error>;
+    /// Renews the lease of the job. Returns `false` if the job is no longer assigned
+    /// to the prover, e.g. the block was reverted or reassigned, so the job must be abandoned.
+    fn working_on(&self, job_id: i32) -> Result<bool, failure::Error>;
     fn prover_data(
         &self,
         block: i64,
     ) -> Result<circuit::circuit::FranklinCircuit<'_, Engine>, failure::Error>;
-    fn publish(&self, block: i64, p: EncodedProofPlonk) -> Result<(), failure::Error>;
+    fn publish(
+        &self,
+        block: i64,
+        p: EncodedProofPlonk,
+        timings: Option<ProofTimings>,
+    ) -> Result<(), failure::Error>;
     fn prover_stopped(&self, prover_run_id: i32) -> Result<(), failure::Error>;
 }
 
@@ -232,9 +251,16 @@ fn keep_sending_work_heartbeats<C: ApiClient>(
         }
         if job_id != 0 {
             log::trace!("sending working_on request for job_id: {}", job_id);
-            let ret = client.working_on(job_id);
-            if let Err(e) = ret {
-                log::error!("working_on request erred: {}", e);
+            match client.working_on(job_id) {
+                Ok(true) => {}
+                Ok(false) => {
+                    log::warn!(
+                        "Job with ID {} is no longer assigned to the prover, stopping heartbeats",
+                        job_id
+                    );
+                    job_id = 0;
+                }
+                Err(e) => log::error!("working_on request erred: {}", e),
             }
         }
     }
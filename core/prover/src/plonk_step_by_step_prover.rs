@@ -1,26 +1,41 @@
+use crate::checkpoint::ProofCheckpoints;
+use crate::client::ProverCapabilities;
+use crate::resources::ProverResources;
 use crate::{ApiClient, BabyProverError, ProverConfig, ProverImpl};
 use models::config_options::{get_env, parse_env};
-use models::prover_utils::{PlonkVerificationKey, SetupForStepByStepProver};
+use models::prover_utils::{
+    proof_system::{PlonkProofSystem, ProofSystem},
+    ProofTimings,
+};
+use std::env;
+use std::path::PathBuf;
 use std::sync::{mpsc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// We prepare some data before making proof for each block size, so we cache it in case next block
 /// would be of our size
 struct PreparedComputations {
     block_size: usize,
-    setup: SetupForStepByStepProver,
+    setup: <PlonkProofSystem as ProofSystem>::Setup,
 }
 
 pub struct PlonkStepByStepProver<C: ApiClient> {
     config: PlonkStepByStepProverConfig,
+    proof_system: PlonkProofSystem,
     prepared_computations: Mutex<Option<PreparedComputations>>,
     api_client: C,
     heartbeat_interval: Duration,
+    checkpoints: Option<ProofCheckpoints>,
 }
 
 pub struct PlonkStepByStepProverConfig {
     pub block_sizes: Vec<usize>,
     pub download_setup_from_network: bool,
+    /// Directory to store the computed proofs until they're published.
+    /// Checkpoints are disabled if not set.
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Threads, cores and memory available to the prover.
+    pub resources: ProverResources,
 }
 
 impl ProverConfig for PlonkStepByStepProverConfig {
@@ -31,10 +46,28 @@ impl ProverConfig for PlonkStepByStepProverConfig {
                 .map(|p| p.parse().unwrap())
                 .collect(),
             download_setup_from_network: parse_env("PROVER_DOWNLOAD_SETUP"),
+            checkpoint_dir: if env::var("PROVER_CHECKPOINT_DIR").is_ok() {
+                Some(get_env("PROVER_CHECKPOINT_DIR").into())
+            } else {
+                None
+            },
+            resources: ProverResources::from_env(),
         }
     }
 }
 
+impl<C: ApiClient> PlonkStepByStepProver<C> {
+    /// Checks whether the job is still assigned to the prover, so the expensive steps
+    /// are skipped for the reverted or reassigned blocks.
+    /// If the server can't be reached, the job is considered active.
+    fn job_is_active(&self, job_id: i32) -> bool {
+        self.api_client.working_on(job_id).unwrap_or_else(|e| {
+            log::error!("failed to check the state of the job {}: {}", job_id, e);
+            true
+        })
+    }
+}
+
 impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
     type Config = PlonkStepByStepProverConfig;
 
@@ -44,11 +77,24 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
         heartbeat_interval: Duration,
     ) -> Self {
         assert!(!config.block_sizes.is_empty());
+        config
+            .resources
+            .apply()
+            .expect("failed to apply prover resource limits");
+        let checkpoints = config
+            .checkpoint_dir
+            .clone()
+            .map(|dir| ProofCheckpoints::new(dir).expect("failed to initialize proof checkpoints"));
+        let proof_system = PlonkProofSystem {
+            download_setup_from_network: config.download_setup_from_network,
+        };
         PlonkStepByStepProver {
             config,
+            proof_system,
             prepared_computations: Mutex::new(None),
             api_client,
             heartbeat_interval,
+            checkpoints,
         }
     }
 
@@ -105,12 +151,29 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
         if job_id == 0 {
             return Ok(());
         }
+        let round_started = Instant::now();
         let instance = self.api_client.prover_data(block).map_err(|err| {
             BabyProverError::Api(format!(
                 "could not get prover data for block {}: {}",
                 block, err
             ))
         })?;
+        let commitment = instance.pub_data_commitment.ok_or_else(|| {
+            BabyProverError::Internal(format!("no public data commitment for block {}", block))
+        })?;
+
+        // Proof may be already computed before the prover restart.
+        if let Some(checkpoints) = &self.checkpoints {
+            if let Some(proof) = checkpoints.load(block, &commitment) {
+                log::info!("publishing checkpointed proof for block {}", block);
+                // Checkpoint is removed even if the proof is rejected, so it's not retried forever.
+                let published = self.api_client.publish(block, proof, None);
+                checkpoints.remove(block);
+                published
+                    .map_err(|e| BabyProverError::Api(format!("failed to publish proof: {}", e)))?;
+                return Ok(());
+            }
+        }
 
         log::info!(
             "starting to compute proof for block {}, size: {}",
@@ -118,6 +181,9 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
             block_size
         );
 
+        let witness_load_ms = round_started.elapsed().as_millis() as u64;
+
+        let synthesis_started = Instant::now();
         // we do this way here so old precomp is dropped
         let valid_cached_precomp = {
             self.prepared_computations
@@ -129,30 +195,32 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
         let precomp = if let Some(precomp) = valid_cached_precomp {
             precomp
         } else {
-            let setup = SetupForStepByStepProver::prepare_setup_for_step_by_step_prover(
-                instance.clone(),
-                self.config.download_setup_from_network,
-            )
-            .map_err(|e| {
-                BabyProverError::Internal(format!(
-                    "Failed to prepare setup for block_size: {}, err: {}",
-                    block_size, e
-                ))
-            })?;
+            let setup = self
+                .proof_system
+                .prepare_setup(instance.clone(), block_size)
+                .map_err(|e| {
+                    BabyProverError::Internal(format!(
+                        "Failed to prepare setup for block_size: {}, err: {}",
+                        block_size, e
+                    ))
+                })?;
             PreparedComputations { block_size, setup }
         };
+        let synthesis_ms = synthesis_started.elapsed().as_millis() as u64;
 
-        let vk = PlonkVerificationKey::read_verification_key_for_main_circuit(block_size).map_err(
-            |e| {
-                BabyProverError::Internal(format!(
-                    "Failed to read vk for block: {}, size: {}, err: {}",
-                    block, block_size, e
-                ))
-            },
-        )?;
-        let verified_proof = precomp
-            .setup
-            .gen_step_by_step_proof_using_prepared_setup(instance, &vk)
+        if !self.job_is_active(job_id) {
+            log::warn!(
+                "Block {} is reverted or reassigned, abandoning the job",
+                block
+            );
+            *self.prepared_computations.lock().unwrap() = Some(precomp);
+            return Ok(());
+        }
+
+        let proving_started = Instant::now();
+        let verified_proof = self
+            .proof_system
+            .prove(&precomp.setup, instance, block_size, &commitment)
             .map_err(|e| {
                 BabyProverError::Internal(format!(
                     "Failed to create verified proof for block: {}, size: {}, err: {}",
@@ -160,11 +228,40 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
                 ))
             })?;
 
+        let timings = ProofTimings {
+            witness_load_ms,
+            synthesis_ms,
+            proving_ms: proving_started.elapsed().as_millis() as u64,
+            total_ms: round_started.elapsed().as_millis() as u64,
+        };
+        log::info!("proof for block {} computed, timings: {:?}", block, timings);
+
         *self.prepared_computations.lock().unwrap() = Some(precomp);
 
+        if let Some(checkpoints) = &self.checkpoints {
+            if let Err(e) = checkpoints.store(block, &commitment, &verified_proof) {
+                log::error!(
+                    "failed to store proof checkpoint for block {}: {}",
+                    block,
+                    e
+                );
+            }
+        }
+
+        if !self.job_is_active(job_id) {
+            log::warn!(
+                "Block {} is reverted or reassigned, the proof is not published",
+                block
+            );
+            return Ok(());
+        }
+
         self.api_client
-            .publish(block, verified_proof)
+            .publish(block, verified_proof, Some(timings))
             .map_err(|e| BabyProverError::Api(format!("failed to publish proof: {}", e)))?;
+        if let Some(checkpoints) = &self.checkpoints {
+            checkpoints.remove(block);
+        }
 
         log::info!("finished and published proof for block {}", block);
         Ok(())
@@ -173,4 +270,10 @@ impl<C: ApiClient> ProverImpl<C> for PlonkStepByStepProver<C> {
     fn get_heartbeat_options(&self) -> (&C, Duration) {
         (&self.api_client, self.heartbeat_interval)
     }
+
+    fn capabilities(&self) -> ProverCapabilities {
+        ProverCapabilities {
+            block_sizes: self.config.block_sizes.clone(),
+        }
+    }
 }
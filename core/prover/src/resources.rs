@@ -0,0 +1,221 @@
+//! Limits of the hardware resources used by the prover.
+//!
+//! PLONK prover spawns a worker thread per available core, so by default it occupies
+//! the whole machine. To co-locate several provers (or a prover and the witness generator)
+//! on the same host, the prover process can be pinned to a set of cores, which also limits
+//! the amount of the worker threads, and can be given an address space limit.
+//! Pinning cores of a single NUMA node keeps the memory of the prover local to that node.
+
+// Built-in deps
+use std::env;
+// External deps
+use failure::{bail, format_err};
+// Workspace deps
+use models::config_options::{get_env, parse_env};
+
+/// Maximum amount of cores in the CPU affinity mask.
+#[cfg(target_os = "linux")]
+const MAX_CORES: usize = libc::CPU_SETSIZE as usize;
+#[cfg(not(target_os = "linux"))]
+const MAX_CORES: usize = 1024;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProverResources {
+    /// Maximum amount of the proving threads, all the available cores are used if not set.
+    pub threads: Option<usize>,
+    /// Cores the prover is pinned to, e.g. `0-15,32-47`. Not pinned if not set.
+    pub cpu_cores: Option<Vec<usize>>,
+    /// Address space limit of the prover process in megabytes. Allocations above
+    /// the limit fail instead of pushing the co-located processes out of memory.
+    pub memory_limit_mb: Option<u64>,
+}
+
+impl ProverResources {
+    pub fn from_env() -> Self {
+        Self {
+            threads: if env::var("PROVER_THREADS").is_ok() {
+                Some(parse_env("PROVER_THREADS"))
+            } else {
+                None
+            },
+            cpu_cores: if env::var("PROVER_CPU_CORES").is_ok() {
+                Some(
+                    parse_cpu_list(&get_env("PROVER_CPU_CORES"))
+                        .expect("PROVER_CPU_CORES is invalid"),
+                )
+            } else {
+                None
+            },
+            memory_limit_mb: if env::var("PROVER_MEMORY_LIMIT_MB").is_ok() {
+                Some(parse_env("PROVER_MEMORY_LIMIT_MB"))
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Cores the prover should run on, given the cores currently available to the process.
+    /// Returns `None` if the affinity should be left intact.
+    fn cores_to_pin(&self, available: &[usize]) -> Option<Vec<usize>> {
+        if self.cpu_cores.is_none() && self.threads.is_none() {
+            return None;
+        }
+        let mut cores = self.cpu_cores.clone().unwrap_or_else(|| available.to_vec());
+        if let Some(threads) = self.threads {
+            cores.truncate(threads);
+        }
+        Some(cores)
+    }
+
+    /// Applies the limits to the current process.
+    /// Must be called before any thread is spawned, since the CPU affinity is inherited
+    /// only by the threads spawned after it's set.
+    ///
+    /// CPU pinning is supported only on Linux, the memory limit only on Unix platforms.
+    pub fn apply(&self) -> Result<(), failure::Error> {
+        // Affinity is not queried unless the pinning is requested, so the prover
+        // without the limits runs on any platform.
+        let pinning_requested = self.cpu_cores.is_some() || self.threads.is_some();
+        if pinning_requested {
+            if let Some(cores) = self.cores_to_pin(&current_affinity()?) {
+                if cores.is_empty() {
+                    bail!("No cores left for the prover");
+                }
+                set_affinity(&cores)?;
+                // Thread pools which don't consult the affinity are limited explicitly.
+                env::set_var("RAYON_NUM_THREADS", cores.len().to_string());
+                log::info!("Prover is pinned to {} cores: {:?}", cores.len(), cores);
+            }
+        }
+        if let Some(memory_limit_mb) = self.memory_limit_mb {
+            set_memory_limit(memory_limit_mb * 1024 * 1024)?;
+            log::info!("Prover memory is limited to {} MB", memory_limit_mb);
+        }
+        Ok(())
+    }
+}
+
+/// Parses the list of cores in the `taskset`/`cpuset` format, e.g. `0-3,8,10-11`.
+pub fn parse_cpu_list(list: &str) -> Result<Vec<usize>, failure::Error> {
+    let mut cores = Vec::new();
+    for range in list.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        let mut bounds = range.splitn(2, '-').map(|bound| {
+            bound
+                .trim()
+                .parse::<usize>()
+                .map_err(|e| format_err!("Invalid core number in {}: {}", range, e))
+        });
+        let first = bounds.next().unwrap()?;
+        let last = bounds.next().transpose()?.unwrap_or(first);
+        if last < first || last >= MAX_CORES {
+            bail!("Invalid cores range: {}", range);
+        }
+        cores.extend(first..=last);
+    }
+    cores.sort();
+    cores.dedup();
+    if cores.is_empty() {
+        bail!("Empty cores list");
+    }
+    Ok(cores)
+}
+
+#[cfg(target_os = "linux")]
+fn current_affinity() -> Result<Vec<usize>, failure::Error> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let res =
+        unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) };
+    if res != 0 {
+        bail!(
+            "Failed to get CPU affinity: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok((0..MAX_CORES)
+        .filter(|core| unsafe { libc::CPU_ISSET(*core, &set) })
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(cores: &[usize]) -> Result<(), failure::Error> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for core in cores {
+        unsafe { libc::CPU_SET(*core, &mut set) };
+    }
+    let res = unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) };
+    if res != 0 {
+        bail!(
+            "Failed to set CPU affinity to {:?}: {}",
+            cores,
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_affinity() -> Result<Vec<usize>, failure::Error> {
+    bail!("CPU pinning is not supported on this platform");
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_cores: &[usize]) -> Result<(), failure::Error> {
+    bail!("CPU pinning is not supported on this platform");
+}
+
+#[cfg(unix)]
+fn set_memory_limit(bytes: u64) -> Result<(), failure::Error> {
+    let limit = libc::rlimit {
+        rlim_cur: bytes as libc::rlim_t,
+        rlim_max: bytes as libc::rlim_t,
+    };
+    let res = unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) };
+    if res != 0 {
+        bail!(
+            "Failed to set memory limit: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_memory_limit(_bytes: u64) -> Result<(), failure::Error> {
+    bail!("Memory limit is not supported on this platform");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cpu_list_parsing() {
+        assert_eq!(parse_cpu_list("3").unwrap(), vec![3]);
+        assert_eq!(parse_cpu_list("0-3").unwrap(), vec![0, 1, 2, 3]);
+        assert_eq!(
+            parse_cpu_list("8, 0-1,10-11,1").unwrap(),
+            vec![0, 1, 8, 10, 11]
+        );
+        assert!(parse_cpu_list("").is_err());
+        assert!(parse_cpu_list("3-1").is_err());
+        assert!(parse_cpu_list("a-b").is_err());
+        assert!(parse_cpu_list("0-100000").is_err());
+    }
+
+    #[test]
+    fn cores_to_pin() {
+        let available = vec![0, 1, 2, 3, 4, 5];
+        let mut resources = ProverResources::default();
+        assert_eq!(resources.cores_to_pin(&available), None);
+
+        resources.threads = Some(4);
+        assert_eq!(resources.cores_to_pin(&available), Some(vec![0, 1, 2, 3]));
+
+        resources.cpu_cores = Some(vec![4, 5]);
+        assert_eq!(resources.cores_to_pin(&available), Some(vec![4, 5]));
+
+        resources.threads = None;
+        resources.cpu_cores = Some(vec![2, 3, 4]);
+        assert_eq!(resources.cores_to_pin(&available), Some(vec![2, 3, 4]));
+    }
+}
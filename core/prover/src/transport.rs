@@ -0,0 +1,111 @@
+//! Encoding of the witnesses and proofs exchanged between the server and the provers.
+//!
+//! Witnesses of the large blocks are hundreds of megabytes of JSON, so they're sent
+//! compressed with zstd (`Content-Encoding: zstd`) and downloaded by chunks using
+//! the `Range` requests. If the connection breaks, the download is resumed from the last
+//! received chunk instead of starting over. Proofs are compressed the same way on publish.
+
+// Built-in deps
+use std::io;
+
+/// Value of the `Content-Encoding` and `Accept-Encoding` headers for the zstd-compressed bodies.
+pub const ZSTD_ENCODING: &str = "zstd";
+
+/// Size of the witness part requested at once by the prover.
+pub const WITNESS_DOWNLOAD_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+/// Compression level used for the transferred data.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compresses the body of the request or response.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    zstd::encode_all(data, COMPRESSION_LEVEL).expect("in-memory zstd compression failed")
+}
+
+/// Decompresses the body of the request or response.
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::decode_all(data)
+}
+
+/// Checks whether the value of the `Accept-Encoding` header allows the zstd encoding.
+pub fn accepts_zstd(accept_encoding: &str) -> bool {
+    accept_encoding
+        .split(',')
+        .any(|encoding| encoding.split(';').next().unwrap_or("").trim() == ZSTD_ENCODING)
+}
+
+/// Parses the `Range` header of the form `bytes=<first>-[<last>]`.
+/// Returns the offset and the maximum length of the requested range.
+/// Multiple ranges and suffix ranges are not supported, since provers never request them.
+pub fn parse_range(range: &str) -> Option<(u64, Option<u64>)> {
+    let range = range.trim();
+    if !range.starts_with("bytes=") {
+        return None;
+    }
+    let mut bounds = range["bytes=".len()..].splitn(2, '-');
+    let first: u64 = bounds.next()?.trim().parse().ok()?;
+    let last = bounds.next()?.trim();
+    if last.is_empty() {
+        return Some((first, None));
+    }
+    let last: u64 = last.parse().ok()?;
+    if last < first {
+        return None;
+    }
+    Some((first, Some(last - first + 1)))
+}
+
+/// Parses the `Content-Range` header of the form `bytes <first>-<last>/<total>`.
+/// Returns the offset of the received part and the length of the whole body.
+pub fn parse_content_range(content_range: &str) -> Option<(u64, u64)> {
+    let content_range = content_range.trim();
+    if !content_range.starts_with("bytes ") {
+        return None;
+    }
+    let mut parts = content_range["bytes ".len()..].splitn(2, '/');
+    let range = parts.next()?;
+    let total: u64 = parts.next()?.trim().parse().ok()?;
+    // Range is `*` for the empty body.
+    let first = if range == "*" {
+        total
+    } else {
+        range.splitn(2, '-').next()?.trim().parse().ok()?
+    };
+    Some((first, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_roundtrip() {
+        let data = vec![42u8; 10_000];
+        let compressed = compress(&data);
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), data);
+        assert!(decompress(b"not compressed").is_err());
+    }
+
+    #[test]
+    fn accept_encoding() {
+        assert!(accepts_zstd("zstd"));
+        assert!(accepts_zstd("gzip, zstd;q=0.5"));
+        assert!(!accepts_zstd("gzip, deflate"));
+        assert!(!accepts_zstd(""));
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("bytes=0-99"), Some((0, Some(100))));
+        assert_eq!(parse_range("bytes=100-"), Some((100, None)));
+        assert_eq!(parse_range("bytes=100-99"), None);
+        assert_eq!(parse_range("bytes=-100"), None);
+        assert_eq!(parse_range("items=0-99"), None);
+
+        assert_eq!(parse_content_range("bytes 0-99/1000"), Some((0, 1000)));
+        assert_eq!(parse_content_range("bytes 900-999/1000"), Some((900, 1000)));
+        assert_eq!(parse_content_range("bytes */1000"), Some((1000, 1000)));
+        assert_eq!(parse_content_range("bytes 0-99"), None);
+    }
+}
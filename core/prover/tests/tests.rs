@@ -17,10 +17,11 @@ use models::{
         block::smallest_block_size_for_chunks, operations::DepositOp, Account, Address, Deposit,
         Engine, Fr,
     },
-    prover_utils::EncodedProofPlonk,
+    prover_utils::{EncodedProofPlonk, ProofTimings},
 };
 // Local deps
 use prover::{
+    checkpoint::ProofCheckpoints,
     plonk_step_by_step_prover::{PlonkStepByStepProver, PlonkStepByStepProverConfig},
     prover_data::ProverData,
     ProverImpl,
@@ -47,6 +48,8 @@ fn prover_sends_heartbeat_requests_and_exits_on_stop_signal() {
         let config = PlonkStepByStepProverConfig {
             block_sizes: vec![block_size_chunks],
             download_setup_from_network: false,
+            checkpoint_dir: None,
+            resources: Default::default(),
         };
         let p = PlonkStepByStepProver::create_from_config(
             config,
@@ -93,6 +96,8 @@ fn prover_proves_a_block_and_publishes_result() {
         let config = PlonkStepByStepProverConfig {
             block_sizes: vec![block_size_chunks],
             download_setup_from_network: false,
+            checkpoint_dir: None,
+            resources: Default::default(),
         };
         let p = PlonkStepByStepProver::create_from_config(
             config,
@@ -118,6 +123,30 @@ fn prover_proves_a_block_and_publishes_result() {
         .expect("didn't receive proof"); // if proof is received - then proof is verified
 }
 
+/// Checks that the stored proof checkpoint is loaded only for the same block contents,
+/// and that the checkpoints of the block are removed.
+#[test]
+fn proof_checkpoints() {
+    let dir = std::env::temp_dir().join("prover_proof_checkpoints_test");
+    let checkpoints = ProofCheckpoints::new(dir.clone()).expect("failed to create checkpoints");
+    let commitment = Fr::from_str("1").unwrap();
+    let other_commitment = Fr::from_str("2").unwrap();
+    let proof = EncodedProofPlonk::default();
+
+    assert!(checkpoints.load(1, &commitment).is_none());
+    checkpoints.store(1, &commitment, &proof).unwrap();
+    checkpoints.store(2, &commitment, &proof).unwrap();
+    assert_eq!(checkpoints.load(1, &commitment), Some(proof.clone()));
+    // Reverted block with the same number has another commitment.
+    assert!(checkpoints.load(1, &other_commitment).is_none());
+
+    checkpoints.remove(1);
+    assert!(checkpoints.load(1, &commitment).is_none());
+    assert_eq!(checkpoints.load(2, &commitment), Some(proof));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
 fn new_test_data_for_prover() -> ProverData {
     let mut circuit_account_tree = CircuitAccountTree::new(models::params::account_tree_depth());
     let fee_account_id = 0;
@@ -185,7 +214,7 @@ impl<F: Fn() -> Option<ProverData>> prover::ApiClient for MockApiClient<F> {
         Ok(*block_to_prove)
     }
 
-    fn working_on(&self, job: i32) -> Result<(), failure::Error> {
+    fn working_on(&self, job: i32) -> Result<bool, failure::Error> {
         let stored = self.block_to_prove.lock().unwrap();
         if let Some((_, stored)) = *stored {
             if stored != job {
@@ -193,7 +222,7 @@ impl<F: Fn() -> Option<ProverData>> prover::ApiClient for MockApiClient<F> {
             }
             let _ = self.heartbeats_tx.lock().unwrap().send(());
         }
-        Ok(())
+        Ok(true)
     }
 
     fn prover_data(&self, block: i64) -> Result<FranklinCircuit<'_, Engine>, failure::Error> {
@@ -207,7 +236,12 @@ impl<F: Fn() -> Option<ProverData>> prover::ApiClient for MockApiClient<F> {
         Err(failure::format_err!("mock not configured"))
     }
 
-    fn publish(&self, _block: i64, p: EncodedProofPlonk) -> Result<(), failure::Error> {
+    fn publish(
+        &self,
+        _block: i64,
+        p: EncodedProofPlonk,
+        _timings: Option<ProofTimings>,
+    ) -> Result<(), failure::Error> {
         // No more blocks to prove. We're only testing single rounds.
         let mut block_to_prove = self.block_to_prove.lock().unwrap();
         *block_to_prove = None;
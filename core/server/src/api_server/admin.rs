@@ -0,0 +1,743 @@
+//! Admin API used by the operator to manage the server at runtime.
+//!
+//! The API is served on the separate address (`ADMIN_API_BIND`), which must be reachable
+//! only from the operator infrastructure. Every request must carry the
+//! `Authorization: Bearer <ADMIN_API_SECRET>` header.
+//!
+//! Endpoints:
+//! - `GET /denylist` - list of the denylisted addresses.
+//! - `POST /denylist` - adds the address to the denylist (`{ "address": ..., "reason": ... }`).
+//! - `DELETE /denylist/{address}` - removes the address from the denylist.
+//! - `GET /denied_txs?limit=N` - latest transactions rejected because of the denylist.
+//! - `GET /prover_tokens` - list of the issued prover API tokens (without the tokens themselves).
+//! - `POST /prover_tokens` - issues a new prover API token (`{ "name": ... }`).
+//! - `DELETE /prover_tokens/{id}` - revokes the prover API token.
+//! - `GET /prover_timings?period=N` - average proof computation timings per prover and block
+//!   size for the last `N` seconds (for the whole history if not set).
+//! - `GET /provers` - all the provers which have ever registered, with the outcomes of their
+//!   jobs and whether they're considered reliable.
+//! - `POST /reverted_blocks` - cancels the prover jobs of the blocks reverted after the
+//!   given one (`{ "lastBlock": ... }`) and purges their witnesses and proofs.
+//! - `POST /revert_blocks` - reverts the unverified blocks after the given one
+//!   (`{ "lastBlock": ... }`) which commits are not confirmed on Ethereum: their transactions
+//!   are returned to the mempool, and their prover jobs and Ethereum operations are cancelled.
+//!   Block production is halted and the server is restarted to restore the state keeper from
+//!   the database, so the blocks are proposed again. Blocks with the confirmed commits must
+//!   be reverted in the contract instead.
+//! - `GET /dashboard` - summary of the server state for the operations dashboard: commit and
+//!   verification lag (blocks not confirmed on Ethereum yet and the age of the oldest one),
+//!   proving queue, `eth_sender` queue, last checked operator balance and mempool depth.
+//! - `GET /circuit_costs` - chunk costs of the operations in the circuit compared with the ones
+//!   used by the server, and the setup powers of the supported block sizes. Constraint counts
+//!   are reported by the `key_generator circuit-costs` command, since they require the circuit
+//!   transpilation.
+//!
+//! Changes of the denylist are applied by the mempool right away.
+//! The issued prover token is returned only once, the server stores only its hash.
+//! Reverting the blocks restarts the server, so it's unavailable for a while.
+
+// Built-in uses
+use std::net::SocketAddr;
+use std::time::Duration;
+// External uses
+use actix_web::{
+    http::header, middleware, web, App, HttpRequest, HttpResponse, HttpServer,
+    Result as ActixResult,
+};
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, SinkExt};
+// Workspace uses
+use circuit::costs::{operation_chunks_costs, BlockSizeCosts, CircuitCostsReport};
+use models::config_options::{
+    AdminApiOptions, AvailableBlockSizesConfig, ProverOptions, ThreadPanicNotify,
+};
+use models::node::{Address, BlockNumber};
+use storage::{ConnectionPool, StorageProcessor};
+// Local uses
+use crate::mempool::MempoolRequest;
+use crate::prover_server::auth::{generate_token, token_hash};
+use crate::utils::current_zksync_info::CurrentZksyncInfo;
+
+/// Maximum amount of the denied transactions returned at once.
+const MAX_DENIED_TXS_LIMIT: i64 = 1000;
+
+#[derive(Clone)]
+struct AdminState {
+    connection_pool: ConnectionPool,
+    mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    secret: String,
+    circuit_costs: CircuitCostsReport,
+    /// Failure rate above which the prover is considered unreliable.
+    max_failure_rate: f64,
+    current_zksync_info: CurrentZksyncInfo,
+    /// Restarts the server once the blocks are reverted.
+    stop_signal_sender: mpsc::Sender<bool>,
+}
+
+impl AdminState {
+    fn access_storage(&self) -> ActixResult<StorageProcessor> {
+        self.connection_pool
+            .access_storage_fragile()
+            .map_err(|err| {
+                vlog::warn!("DB await timeout: '{}';", err);
+                HttpResponse::RequestTimeout().finish().into()
+            })
+    }
+
+    /// Notifies the mempool that the denylist was changed.
+    fn reload_denylist(&self) -> ActixResult<()> {
+        let mut mempool_request_sender = self.mempool_request_sender.clone();
+        futures::executor::block_on(mempool_request_sender.send(MempoolRequest::ReloadDenylist))
+            .map_err(|err| {
+                vlog::warn!("Failed to send the denylist reload request: '{}'", err);
+                HttpResponse::InternalServerError().finish().into()
+            })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeniedAddressResp {
+    address: Address,
+    reason: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DeniedTxResp {
+    tx_hash: String,
+    address: Address,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddDeniedAddressRequest {
+    address: Address,
+    reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeniedTxsQuery {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProverTimingsQuery {
+    period: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProverTimingsResp {
+    worker: String,
+    block_size: i64,
+    proofs_count: i64,
+    avg_witness_load_ms: i64,
+    avg_synthesis_ms: i64,
+    avg_proving_ms: i64,
+    avg_total_ms: i64,
+    max_total_ms: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProverRegistryResp {
+    worker: String,
+    circuit_version: Option<String>,
+    capabilities: serde_json::Value,
+    first_registered_at: DateTime<Utc>,
+    last_registered_at: DateTime<Utc>,
+    completed_jobs: i64,
+    failed_jobs: i64,
+    timed_out_jobs: i64,
+    avg_proving_ms: Option<i64>,
+    failure_rate: f64,
+    /// Whether the prover gets the most urgent blocks.
+    reliable: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProverTokenResp {
+    id: i64,
+    name: String,
+    created_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevertedBlocksRequest {
+    /// Last block which is not reverted.
+    last_block: BlockNumber,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CancelledProverJobResp {
+    id: i32,
+    block_number: i64,
+    worker: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevertBlocksRequest {
+    /// Last block which is kept.
+    last_block: BlockNumber,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevertBlocksResp {
+    removed_blocks: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChainLagResp {
+    /// Last block confirmed on Ethereum.
+    last_confirmed_block: i64,
+    /// Amount of the committed blocks not confirmed on Ethereum yet.
+    blocks: i64,
+    /// Time since the oldest of such blocks was sealed.
+    oldest_block_age_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProvingQueueResp {
+    queue_depth: i64,
+    jobs_in_progress: i64,
+    oldest_unproven_block_age_secs: Option<i64>,
+    avg_proof_ms: Option<i64>,
+    active_provers: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EthSenderQueueResp {
+    /// Operations waiting to be sent.
+    queued_ops: i64,
+    /// Sent transactions waiting for the confirmation.
+    unconfirmed_txs: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OperatorBalanceResp {
+    address: Address,
+    /// Balance in wei as a decimal string.
+    balance: String,
+    checked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DashboardResp {
+    last_committed_block: i64,
+    commit_lag: ChainLagResp,
+    verify_lag: ChainLagResp,
+    proving_queue: ProvingQueueResp,
+    eth_sender_queue: EthSenderQueueResp,
+    /// `None` until the balance is checked by `eth_sender` for the first time.
+    operator_balance: Option<OperatorBalanceResp>,
+    /// Amount of the transactions in the mempool.
+    mempool_depth: i64,
+    block_production_halted: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueProverTokenRequest {
+    name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct IssuedProverTokenResp {
+    id: i64,
+    token: String,
+}
+
+/// Checks that the request is authorized with the admin secret.
+fn check_auth(req: &HttpRequest, secret: &str) -> ActixResult<()> {
+    let expected = format!("Bearer {}", secret);
+    let authorized = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value == expected)
+        .unwrap_or(false);
+
+    if authorized {
+        Ok(())
+    } else {
+        Err(HttpResponse::Unauthorized().finish().into())
+    }
+}
+
+fn internal_error(err: impl std::fmt::Display) -> actix_web::Error {
+    vlog::warn!("Internal Server Error: '{}'", err);
+    HttpResponse::InternalServerError().finish().into()
+}
+
+fn handle_get_denylist(req: HttpRequest, data: web::Data<AdminState>) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let denylist: Vec<_> = storage
+        .denylist_schema()
+        .load_denylist()
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|stored| DeniedAddressResp {
+            address: Address::from_slice(&stored.address),
+            reason: stored.reason,
+            created_at: stored.created_at,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(denylist))
+}
+
+fn handle_add_denied_address(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request: web::Json<AddDeniedAddressRequest>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    storage
+        .denylist_schema()
+        .add_address(&request.address, &request.reason)
+        .map_err(internal_error)?;
+    vlog::info!(
+        "Address {:?} was denylisted, reason: {}",
+        request.address,
+        request.reason
+    );
+    data.reload_denylist()?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn handle_remove_denied_address(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request_path: web::Path<Address>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let address = request_path.into_inner();
+    let storage = data.access_storage()?;
+    let removed = storage
+        .denylist_schema()
+        .remove_address(&address)
+        .map_err(internal_error)?;
+    if !removed {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    vlog::info!("Address {:?} was removed from the denylist", address);
+    data.reload_denylist()?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn handle_get_denied_txs(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request_query: web::Query<DeniedTxsQuery>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let limit = request_query.limit.unwrap_or(MAX_DENIED_TXS_LIMIT);
+    if limit <= 0 || limit > MAX_DENIED_TXS_LIMIT {
+        return Err(HttpResponse::BadRequest().finish().into());
+    }
+
+    let storage = data.access_storage()?;
+    let denied_txs: Vec<_> = storage
+        .denylist_schema()
+        .load_denied_txs(limit)
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|stored| DeniedTxResp {
+            tx_hash: format!("sync-tx:{}", hex::encode(&stored.tx_hash)),
+            address: Address::from_slice(&stored.address),
+            created_at: stored.created_at,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(denied_txs))
+}
+
+fn handle_get_prover_tokens(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let tokens: Vec<_> = storage
+        .prover_schema()
+        .load_prover_tokens()
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|stored| ProverTokenResp {
+            id: stored.id,
+            name: stored.name,
+            created_at: DateTime::from_utc(stored.created_at, Utc),
+            revoked_at: stored
+                .revoked_at
+                .map(|revoked_at| DateTime::from_utc(revoked_at, Utc)),
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+fn handle_issue_prover_token(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request: web::Json<IssueProverTokenRequest>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    if request.name.is_empty() {
+        return Err(HttpResponse::BadRequest().finish().into());
+    }
+
+    let token = generate_token();
+    let storage = data.access_storage()?;
+    let id = storage
+        .prover_schema()
+        .add_prover_token(&request.name, &token_hash(&token))
+        .map_err(internal_error)?;
+    vlog::info!("Prover token {} was issued for '{}'", id, request.name);
+
+    Ok(HttpResponse::Ok().json(IssuedProverTokenResp { id, token }))
+}
+
+fn handle_revoke_prover_token(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request_path: web::Path<i64>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let id = request_path.into_inner();
+    let storage = data.access_storage()?;
+    let revoked = storage
+        .prover_schema()
+        .revoke_prover_token(id)
+        .map_err(internal_error)?;
+    if !revoked {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    vlog::info!("Prover token {} was revoked", id);
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+fn handle_get_prover_timings(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request_query: web::Query<ProverTimingsQuery>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let period = request_query.period.map(Duration::from_secs);
+
+    let storage = data.access_storage()?;
+    let timings: Vec<_> = storage
+        .prover_schema()
+        .load_proof_timings_stats(period)
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|stats| ProverTimingsResp {
+            worker: stats.worker,
+            block_size: stats.block_size,
+            proofs_count: stats.proofs_count,
+            avg_witness_load_ms: stats.avg_witness_load_ms,
+            avg_synthesis_ms: stats.avg_synthesis_ms,
+            avg_proving_ms: stats.avg_proving_ms,
+            avg_total_ms: stats.avg_total_ms,
+            max_total_ms: stats.max_total_ms,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(timings))
+}
+
+fn handle_get_provers(req: HttpRequest, data: web::Data<AdminState>) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let provers: Vec<_> = storage
+        .prover_schema()
+        .load_prover_registry()
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|entry| ProverRegistryResp {
+            failure_rate: entry.failure_rate(),
+            reliable: entry.is_reliable(data.max_failure_rate),
+            worker: entry.worker,
+            circuit_version: entry.circuit_version,
+            capabilities: entry.capabilities,
+            first_registered_at: DateTime::from_utc(entry.first_registered_at, Utc),
+            last_registered_at: DateTime::from_utc(entry.last_registered_at, Utc),
+            completed_jobs: entry.completed_jobs,
+            failed_jobs: entry.failed_jobs,
+            timed_out_jobs: entry.timed_out_jobs,
+            avg_proving_ms: entry.avg_proving_ms,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(provers))
+}
+
+fn handle_reverted_blocks(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request: web::Json<RevertedBlocksRequest>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let cancelled: Vec<_> = storage
+        .prover_schema()
+        .cancel_reverted_blocks_jobs(request.last_block)
+        .map_err(internal_error)?
+        .into_iter()
+        .map(|run| CancelledProverJobResp {
+            id: run.id,
+            block_number: run.block_number,
+            worker: run.worker,
+        })
+        .collect();
+    vlog::info!(
+        "Blocks after {} are reverted, cancelled {} prover jobs",
+        request.last_block,
+        cancelled.len()
+    );
+
+    Ok(HttpResponse::Ok().json(cancelled))
+}
+
+fn handle_revert_blocks(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+    request: web::Json<RevertBlocksRequest>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let last_confirmed_block = storage
+        .chain()
+        .block_schema()
+        .get_last_confirmed_committed_block()
+        .map_err(internal_error)?;
+    if last_confirmed_block > request.last_block {
+        return Ok(HttpResponse::BadRequest().body(format!(
+            "Commit of the block {} is confirmed on Ethereum, it must be reverted in the contract",
+            last_confirmed_block
+        )));
+    }
+
+    // Block production is halted until the restart, so no new blocks are sealed meanwhile.
+    data.current_zksync_info.set_blocks_reverted(true);
+    let removed_blocks = match storage
+        .chain()
+        .block_schema()
+        .rollback_blocks(request.last_block)
+    {
+        Ok(removed_blocks) => removed_blocks,
+        Err(err) => {
+            data.current_zksync_info.set_blocks_reverted(false);
+            return Ok(HttpResponse::BadRequest().body(err.to_string()));
+        }
+    };
+    if removed_blocks == 0 {
+        data.current_zksync_info.set_blocks_reverted(false);
+        return Ok(HttpResponse::Ok().json(RevertBlocksResp { removed_blocks }));
+    }
+
+    vlog::error!(
+        "{} blocks after {} are reverted by the operator, restarting the server",
+        removed_blocks,
+        request.last_block
+    );
+    let mut stop_signal_sender = data.stop_signal_sender.clone();
+    futures::executor::block_on(stop_signal_sender.send(true)).map_err(internal_error)?;
+
+    Ok(HttpResponse::Ok().json(RevertBlocksResp { removed_blocks }))
+}
+
+fn handle_get_dashboard(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    let storage = data.access_storage()?;
+    let chain_lag = storage
+        .chain()
+        .block_schema()
+        .load_chain_lag_stats()
+        .map_err(internal_error)?;
+    let proving_queue = storage
+        .prover_schema()
+        .proving_queue_stats()
+        .map_err(internal_error)?;
+    let eth_queue = storage
+        .ethereum_schema()
+        .load_queue_stats()
+        .map_err(internal_error)?;
+    let mempool_depth = storage
+        .chain()
+        .mempool_schema()
+        .count_txs()
+        .map_err(internal_error)?;
+
+    let dashboard = DashboardResp {
+        last_committed_block: chain_lag.last_committed_block,
+        commit_lag: ChainLagResp {
+            last_confirmed_block: chain_lag.last_confirmed_commit_block,
+            blocks: chain_lag.last_committed_block - chain_lag.last_confirmed_commit_block,
+            oldest_block_age_secs: chain_lag.oldest_unconfirmed_commit_age_secs,
+        },
+        verify_lag: ChainLagResp {
+            last_confirmed_block: chain_lag.last_confirmed_verify_block,
+            blocks: chain_lag.last_committed_block - chain_lag.last_confirmed_verify_block,
+            oldest_block_age_secs: chain_lag.oldest_unconfirmed_verify_age_secs,
+        },
+        proving_queue: ProvingQueueResp {
+            queue_depth: proving_queue.queue_depth,
+            jobs_in_progress: proving_queue.jobs_in_progress,
+            oldest_unproven_block_age_secs: proving_queue.oldest_unproven_block_age_secs,
+            avg_proof_ms: proving_queue.avg_proof_ms,
+            active_provers: proving_queue.active_provers,
+        },
+        eth_sender_queue: EthSenderQueueResp {
+            queued_ops: eth_queue.queued_ops,
+            unconfirmed_txs: eth_queue.unconfirmed_txs,
+        },
+        operator_balance: data.current_zksync_info.operator_balance().map(|operator| {
+            OperatorBalanceResp {
+                address: operator.address,
+                balance: operator.balance.to_string(),
+                checked_at: operator.checked_at,
+            }
+        }),
+        mempool_depth,
+        block_production_halted: data.current_zksync_info.is_block_production_halted(),
+    };
+
+    Ok(HttpResponse::Ok().json(dashboard))
+}
+
+fn handle_get_circuit_costs(
+    req: HttpRequest,
+    data: web::Data<AdminState>,
+) -> ActixResult<HttpResponse> {
+    check_auth(&req, &data.secret)?;
+    Ok(HttpResponse::Ok().json(&data.circuit_costs))
+}
+
+/// Builds the circuit costs report without the constraint counts.
+fn circuit_costs_report() -> CircuitCostsReport {
+    let config = AvailableBlockSizesConfig::from_env();
+    let block_sizes = config
+        .blocks_chunks
+        .into_iter()
+        .zip(config.blocks_setup_power2.into_iter())
+        .map(|(block_chunks, setup_power)| BlockSizeCosts {
+            block_chunks,
+            setup_power,
+            gates_count: None,
+        })
+        .collect();
+    let report = CircuitCostsReport {
+        operations: operation_chunks_costs(),
+        block_sizes,
+    };
+    if !report.is_consistent() {
+        vlog::error!(
+            "Chunk costs of the server don't match the circuit: {:?}",
+            report
+        );
+    }
+    report
+}
+
+fn start_server(state: AdminState, bind_to: SocketAddr) {
+    HttpServer::new(move || {
+        App::new()
+            .data(state.clone())
+            .wrap(middleware::Logger::default())
+            .route("/denylist", web::get().to(handle_get_denylist))
+            .route("/denylist", web::post().to(handle_add_denied_address))
+            .route(
+                "/denylist/{address}",
+                web::delete().to(handle_remove_denied_address),
+            )
+            .route("/denied_txs", web::get().to(handle_get_denied_txs))
+            .route("/prover_tokens", web::get().to(handle_get_prover_tokens))
+            .route("/prover_tokens", web::post().to(handle_issue_prover_token))
+            .route(
+                "/prover_tokens/{id}",
+                web::delete().to(handle_revoke_prover_token),
+            )
+            .route("/prover_timings", web::get().to(handle_get_prover_timings))
+            .route("/provers", web::get().to(handle_get_provers))
+            .route("/reverted_blocks", web::post().to(handle_reverted_blocks))
+            .route("/revert_blocks", web::post().to(handle_revert_blocks))
+            .route("/dashboard", web::get().to(handle_get_dashboard))
+            .route("/circuit_costs", web::get().to(handle_get_circuit_costs))
+    })
+    .bind(bind_to)
+    .unwrap()
+    .shutdown_timeout(1)
+    .start();
+}
+
+/// Starts the admin API server.
+pub(super) fn start_admin_server_thread_detached(
+    connection_pool: ConnectionPool,
+    options: AdminApiOptions,
+    mempool_request_sender: mpsc::Sender<MempoolRequest>,
+    current_zksync_info: CurrentZksyncInfo,
+    panic_notify: mpsc::Sender<bool>,
+) {
+    std::thread::Builder::new()
+        .name("actix-admin-api".to_string())
+        .spawn(move || {
+            let _panic_sentinel = ThreadPanicNotify(panic_notify.clone());
+
+            let runtime = actix_rt::System::new("admin-api-server");
+
+            let state = AdminState {
+                connection_pool,
+                mempool_request_sender,
+                secret: options.secret,
+                circuit_costs: circuit_costs_report(),
+                max_failure_rate: ProverOptions::from_env().max_failure_rate,
+                current_zksync_info,
+                stop_signal_sender: panic_notify,
+            };
+            start_server(state, options.bind_address);
+            runtime.run().unwrap_or_default();
+        })
+        .expect("Admin api server thread");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn authorization() {
+        let authorized = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .to_http_request();
+        assert!(check_auth(&authorized, "secret").is_ok());
+
+        let wrong_secret = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer other")
+            .to_http_request();
+        assert!(check_auth(&wrong_secret, "secret").is_err());
+
+        let missing = TestRequest::default().to_http_request();
+        assert!(check_auth(&missing, "secret").is_err());
+    }
+}
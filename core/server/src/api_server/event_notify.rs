@@ -1,5 +1,6 @@
 use super::rpc_server::{ETHOpInfoResp, TransactionInfoResp};
 use crate::api_server::rpc_server::{BlockInfo, ResponseAccountState};
+use crate::mempool::ReplacedTxNotify;
 use crate::state_keeper::{ExecutedOpId, ExecutedOpsNotify, StateKeeperRequest};
 use crate::utils::token_db_cache::TokenDBCache;
 use failure::{bail, format_err};
@@ -389,6 +390,8 @@ impl OperationNotifier {
                             committed: true,
                             verified: false,
                         }),
+                        // Operations of the pending block are not exposed by the state keeper.
+                        created_account_id: None,
                     },
                 );
                 return Ok(());
@@ -407,6 +410,9 @@ impl OperationNotifier {
                     committed: receipt.success,
                     verified: receipt.verified,
                 }),
+                created_account_id: receipt
+                    .created_account_id
+                    .map(|account_id| account_id as AccountId),
             };
             match action {
                 ActionType::COMMIT => {
@@ -513,6 +519,10 @@ impl OperationNotifier {
                                 committed: true,
                                 verified: action == ActionType::VERIFY,
                             }),
+                            created_account_id: tx
+                                .op
+                                .as_ref()
+                                .and_then(|op| op.created_account_id()),
                         };
                         for sub in subs {
                             self.send_once(&sub.sink, rec.clone());
@@ -551,6 +561,23 @@ impl OperationNotifier {
         )
     }
 
+    fn handle_replaced_tx(&mut self, notify: ReplacedTxNotify) {
+        let resp = TransactionInfoResp {
+            executed: false,
+            success: Some(false),
+            fail_reason: Some(format!("Replaced by tx {}", notify.new_tx.to_string())),
+            block: None,
+            created_account_id: None,
+        };
+        for action in &[ActionType::COMMIT, ActionType::VERIFY] {
+            if let Some(subs) = self.tx_subs.remove(&(notify.replaced_tx.clone(), *action)) {
+                for sub in subs {
+                    self.send_once(&sub.sink, resp.clone());
+                }
+            }
+        }
+    }
+
     fn handle_new_block(&mut self, op: Operation) -> Result<(), failure::Error> {
         let storage = self.db_pool.access_storage_fragile()?;
         let action = op.action.get_type();
@@ -606,11 +633,16 @@ impl OperationNotifier {
     }
 }
 
+/// Starts the notifier of the subscribers. Flush requests are responded once the notifications
+/// received before the request are sent, so they're not lost on the server shutdown.
+#[allow(clippy::too_many_arguments)]
 pub fn start_sub_notifier(
     db_pool: ConnectionPool,
     mut new_block_stream: mpsc::Receiver<Operation>,
     mut subscription_stream: mpsc::Receiver<EventNotifierRequest>,
     mut executed_tx_stream: mpsc::Receiver<ExecutedOpsNotify>,
+    mut replaced_tx_stream: mpsc::Receiver<ReplacedTxNotify>,
+    mut flush_requests: mpsc::Receiver<oneshot::Sender<()>>,
     state_keeper_requests: mpsc::Sender<StateKeeperRequest>,
     panic_notify: mpsc::Sender<bool>,
     api_requests_caches_size: usize,
@@ -653,6 +685,11 @@ pub fn start_sub_notifier(
                                     .unwrap_or_default();
                             }
                         },
+                        replaced_tx = replaced_tx_stream.next() => {
+                            if let Some(replaced_tx) = replaced_tx {
+                                notifier.handle_replaced_tx(replaced_tx);
+                            }
+                        },
                         new_sub = subscription_stream.next() => {
                             if let Some(new_sub) = new_sub {
                                 notifier.handle_notify_req(new_sub).await
@@ -660,6 +697,21 @@ pub fn start_sub_notifier(
                                     .unwrap_or_default();
                             }
                         },
+                        flush_request = flush_requests.next() => {
+                            if let Some(response) = flush_request {
+                                while let Ok(Some(new_block)) = new_block_stream.try_next() {
+                                    notifier.handle_new_block(new_block)
+                                        .map_err(|e| warn!("Failed to handle new block: {}",e))
+                                        .unwrap_or_default();
+                                }
+                                while let Ok(Some(new_exec_batch)) = executed_tx_stream.try_next() {
+                                    notifier.handle_new_executed_batch(new_exec_batch)
+                                        .map_err(|e| warn!("Failed to handle new exec batch: {}",e))
+                                        .unwrap_or_default();
+                                }
+                                response.send(()).unwrap_or_default();
+                            }
+                        },
                         complete => break,
                     }
                 }
@@ -3,28 +3,39 @@
 //! `mod rest` - api is used for block explorer.
 //! `mod rpc_server` - JSON rpc via HTTP (for request reply functions)
 //! `mod rpc_subscriptions` - JSON rpc via WebSocket (for request reply functions and subscriptions)
+//! `mod operations_listener` - source of the new block notifications backed by the database
+//! `mod admin` - API for the operator to manage the server at runtime
+//! `mod sponsored_txs` - budget of the transactions admitted with zero fee
 
+// Built-in uses
+use std::sync::{Arc, RwLock};
 // External uses
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 // Workspace uses
 use models::{config_options::ConfigurationOptions, Operation};
 use storage::ConnectionPool;
 // Local uses
+use self::sponsored_txs::SponsoredTxs;
 use crate::fee_ticker::TickerRequest;
 use crate::{
     eth_watch::EthWatchRequest,
-    mempool::MempoolRequest,
+    mempool::{MempoolRequest, ReplacedTxNotify},
     signature_checker,
     state_keeper::{ExecutedOpsNotify, StateKeeperRequest},
     utils::current_zksync_info::CurrentZksyncInfo,
 };
 
+mod admin;
 mod event_notify;
 mod loggers;
+mod operations_listener;
 mod ops_counter;
 mod rest;
 pub mod rpc_server;
 mod rpc_subscriptions;
+mod sponsored_txs;
+
+pub use self::operations_listener::start_operations_listener;
 
 #[allow(clippy::too_many_arguments)]
 pub fn start_api_server(
@@ -33,6 +44,8 @@ pub fn start_api_server(
     panic_notify: mpsc::Sender<bool>,
     mempool_request_sender: mpsc::Sender<MempoolRequest>,
     executed_tx_receiver: mpsc::Receiver<ExecutedOpsNotify>,
+    replaced_tx_receiver: mpsc::Receiver<ReplacedTxNotify>,
+    notifier_flush_receiver: mpsc::Receiver<oneshot::Sender<()>>,
     state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
     ticker_request_sender: mpsc::Sender<TickerRequest>,
@@ -47,6 +60,21 @@ pub fn start_api_server(
         panic_notify.clone(),
     );
 
+    let sponsored_txs = config_options
+        .sponsored_txs
+        .clone()
+        .map(|options| Arc::new(RwLock::new(SponsoredTxs::new(options))));
+
+    if let Some(admin_api_options) = config_options.admin_api.clone() {
+        admin::start_admin_server_thread_detached(
+            connection_pool.clone(),
+            admin_api_options,
+            mempool_request_sender.clone(),
+            current_zksync_info.clone(),
+            panic_notify.clone(),
+        );
+    }
+
     rest::start_server_thread_detached(
         connection_pool.clone(),
         config_options.rest_api_server_address,
@@ -62,6 +90,8 @@ pub fn start_api_server(
         connection_pool.clone(),
         mempool_request_sender.clone(),
         executed_tx_receiver,
+        replaced_tx_receiver,
+        notifier_flush_receiver,
         state_keeper_request_sender.clone(),
         sign_check_sender.clone(),
         eth_watcher_request_sender.clone(),
@@ -69,6 +99,7 @@ pub fn start_api_server(
         panic_notify.clone(),
         config_options.api_requests_caches_size,
         current_zksync_info.clone(),
+        sponsored_txs.clone(),
     );
 
     rpc_server::start_rpc_server(
@@ -81,5 +112,6 @@ pub fn start_api_server(
         ticker_request_sender,
         panic_notify,
         current_zksync_info,
+        sponsored_txs,
     );
 }
@@ -0,0 +1,111 @@
+//! Operations listener is an alternative source of the new block notifications
+//! for the `OperationNotifier`.
+//!
+//! Instead of receiving the operations directly from the committer and `eth_sender`
+//! (which is only possible if the API server runs in the same process as the server core),
+//! the listener subscribes to the Postgres notifications channel and loads announced
+//! operations from the database. This way several API servers can serve subscriptions
+//! against the same database.
+
+// Built-in deps
+use std::time::Duration;
+// External uses
+use futures::{channel::mpsc, executor::block_on, SinkExt};
+// Workspace uses
+use models::{config_options::ThreadPanicNotify, Operation};
+use storage::{
+    notifications::{OperationNotification, OperationsListener},
+    ConnectionPool,
+};
+
+/// Maximum time to block on the notifications connection during a single iteration.
+const NOTIFICATIONS_POLL_TIMEOUT: Duration = Duration::from_secs(1);
+/// Interval between attempts to re-establish the listening connection.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+fn load_operation(
+    db_pool: &ConnectionPool,
+    notification: OperationNotification,
+) -> Result<Option<Operation>, failure::Error> {
+    let storage = db_pool.access_storage()?;
+    let stored_op = storage
+        .chain()
+        .operations_schema()
+        .get_operation(notification.block_number, notification.action);
+
+    match stored_op {
+        Some(stored_op) => Ok(Some(stored_op.into_op(&storage)?)),
+        None => Ok(None),
+    }
+}
+
+fn connect_listener() -> OperationsListener {
+    loop {
+        match OperationsListener::connect() {
+            Ok(listener) => return listener,
+            Err(err) => {
+                log::warn!(
+                    "Unable to listen to the operations notifications: {}, retrying in {:?}",
+                    err,
+                    RECONNECT_INTERVAL
+                );
+                std::thread::sleep(RECONNECT_INTERVAL);
+            }
+        }
+    }
+}
+
+/// Starts a thread that listens to the database notifications and returns the stream
+/// of the announced operations.
+pub fn start_operations_listener(
+    db_pool: ConnectionPool,
+    panic_notify: mpsc::Sender<bool>,
+) -> mpsc::Receiver<Operation> {
+    let (mut op_notify_sender, op_notify_receiver) = mpsc::channel(256);
+
+    std::thread::Builder::new()
+        .name("operations-listener".to_string())
+        .spawn(move || {
+            let _panic_sentinel = ThreadPanicNotify(panic_notify);
+
+            let mut listener = connect_listener();
+            loop {
+                let notifications =
+                    match listener.wait_for_notifications(NOTIFICATIONS_POLL_TIMEOUT) {
+                        Ok(notifications) => notifications,
+                        Err(err) => {
+                            log::warn!("Operations listener connection failed: {}", err);
+                            listener = connect_listener();
+                            continue;
+                        }
+                    };
+
+                for notification in notifications {
+                    let operation = match load_operation(&db_pool, notification) {
+                        Ok(Some(operation)) => operation,
+                        Ok(None) => {
+                            log::warn!(
+                                "Notified operation {} is not stored in the database",
+                                notification.to_payload()
+                            );
+                            continue;
+                        }
+                        Err(err) => {
+                            log::warn!(
+                                "Failed to load notified operation {}: {}",
+                                notification.to_payload(),
+                                err
+                            );
+                            continue;
+                        }
+                    };
+
+                    block_on(op_notify_sender.send(operation))
+                        .expect("operations notifications receiver dropped");
+                }
+            }
+        })
+        .expect("failed to start operations listener");
+
+    op_notify_receiver
+}
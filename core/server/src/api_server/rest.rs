@@ -6,6 +6,7 @@ use actix_web::{
     web::{self},
     App, HttpResponse, HttpServer, Result as ActixResult,
 };
+use chrono::{DateTime, Utc};
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt,
@@ -24,6 +25,7 @@ use storage::chain::operations_ext::{
     records::{PriorityOpReceiptResponse, TxReceiptResponse},
     SearchDirection,
 };
+use storage::pagination::OperationCursor;
 use storage::{ConnectionPool, StorageProcessor};
 use tokio::{runtime::Runtime, time};
 use web3::types::H160;
@@ -349,9 +351,9 @@ fn handle_get_tokens(data: web::Data<AppState>) -> ActixResult<HttpResponse> {
     Ok(HttpResponse::Ok().json(vec_tokens))
 }
 
-/// Sends an EthWatchRequest asking for an unconfirmed priority op
+/// Sends an EthWatchRequest asking for a priority op which is not executed yet
 /// with given hash. If no such priority op exists, returns Ok(None).
-pub(crate) async fn get_unconfirmed_op_by_hash(
+pub(crate) async fn get_ongoing_op_by_hash(
     eth_watcher_request_sender: &mpsc::Sender<EthWatchRequest>,
     eth_hash: &[u8],
 ) -> Result<Option<(EthBlockId, PriorityOp)>, failure::Error> {
@@ -359,9 +361,9 @@ pub(crate) async fn get_unconfirmed_op_by_hash(
 
     let eth_watcher_response = oneshot::channel();
 
-    // Find unconfirmed op with given hash
+    // Find ongoing op with given hash
     eth_watcher_request_sender
-        .send(EthWatchRequest::GetUnconfirmedOpByHash {
+        .send(EthWatchRequest::GetOngoingOpByHash {
             eth_hash: eth_hash.to_vec(),
             resp: eth_watcher_response.0,
         })
@@ -388,19 +390,19 @@ pub(crate) async fn get_unconfirmed_op_by_hash(
 /// which is the block number of Ethereum tx of the priority operation,
 /// it enables tracking the number of blocks (confirmations) user needs to wait
 /// before the priority op is included into zkSync block.
-/// Currently returns Some(TxByHashResponse) if PriorityOp is Deposit, and None in other cases.
-fn deposit_op_to_tx_by_hash(
+fn priority_op_to_tx_by_hash(
     tokens: &HashMap<TokenId, Token>,
     op: &PriorityOp,
     eth_block: EthBlockId,
-) -> Option<TxByHashResponse> {
+) -> TxByHashResponse {
+    // As the time of creation is indefinite, we always will provide the current time.
+    let current_time = chrono::Utc::now();
+    let created_at = chrono::NaiveDateTime::from_timestamp(current_time.timestamp(), 0)
+        .format("%Y-%m-%dT%H:%M:%S%.6f")
+        .to_string();
+
     match &op.data {
         FranklinPriorityOp::Deposit(deposit) => {
-            // As the time of creation is indefinite, we always will provide the current time.
-            let current_time = chrono::Utc::now();
-            let naive_current_time =
-                chrono::NaiveDateTime::from_timestamp(current_time.timestamp(), 0);
-
             // Account ID may not exist for depositing ops, so it'll be `null`.
             let account_id: Option<u32> = None;
 
@@ -420,7 +422,7 @@ fn deposit_op_to_tx_by_hash(
                 "eth_block_number": eth_block,
             });
 
-            Some(TxByHashResponse {
+            TxByHashResponse {
                 tx_type: "Deposit".into(),
                 from: format!("{:?}", deposit.from),
                 to: format!("{:?}", deposit.to),
@@ -429,14 +431,41 @@ fn deposit_op_to_tx_by_hash(
                 fee: None,
                 block_number: -1,
                 nonce: -1,
-                created_at: naive_current_time
-                    .format("%Y-%m-%dT%H:%M:%S%.6f")
-                    .to_string(),
+                created_at,
                 fail_reason: None,
                 tx: tx_json,
-            })
+            }
+        }
+        FranklinPriorityOp::FullExit(full_exit) => {
+            let token_symbol = tokens.get(&full_exit.token).map(|t| t.symbol.clone());
+
+            // Withdrawn amount is known only once the operation is executed,
+            // so `withdraw_amount` is `null` in the meantime.
+            let tx_json = serde_json::json!({
+                "priority_op": {
+                    "account_id": full_exit.account_id,
+                    "eth_address": full_exit.eth_address,
+                    "token": token_symbol
+                },
+                "withdraw_amount": null,
+                "type": "FullExit",
+                "eth_block_number": eth_block,
+            });
+
+            TxByHashResponse {
+                tx_type: "FullExit".into(),
+                from: format!("{:?}", full_exit.eth_address),
+                to: format!("{:?}", full_exit.eth_address),
+                token: full_exit.token as i32,
+                amount: "unknown amount".into(),
+                fee: None,
+                block_number: -1,
+                nonce: -1,
+                created_at,
+                fail_reason: None,
+                tx: tx_json,
+            }
         }
-        _ => None,
     }
 }
 
@@ -494,92 +523,36 @@ fn priority_op_to_tx_history(
     }
 }
 
-fn handle_get_account_transactions_history(
-    data: web::Data<AppState>,
-    request_path: web::Path<(Address, u64, u64)>,
-) -> ActixResult<HttpResponse> {
-    let (address, mut offset, mut limit) = request_path.into_inner();
-
-    const MAX_LIMIT: u64 = 100;
-    if limit > MAX_LIMIT {
-        return Err(HttpResponse::BadRequest().finish().into());
-    }
-
-    let storage = data.access_storage()?;
+/// Loads the priority operations of the account which are not executed yet, from the newest
+/// to the oldest one. Ongoing deposits must be reported within the transactions history.
+fn load_ongoing_txs_history(
+    data: &web::Data<AppState>,
+    storage: &StorageProcessor,
+    address: Address,
+    limit: u64,
+) -> ActixResult<Vec<TransactionsHistoryItem>> {
     let tokens = storage.tokens_schema().load_tokens().map_err(|err| {
-        vlog::warn!(
-            "Internal Server Error: '{}'; input: ({}, {}, {})",
-            err,
-            address,
-            offset,
-            limit,
-        );
+        vlog::warn!("Internal Server Error: '{}'; input: ({})", err, address);
         HttpResponse::InternalServerError().finish()
     })?;
 
     let eth_watcher_request_sender = data.eth_watcher_request_sender.clone();
-    // Fetch ongoing deposits, since they must be reported within the transactions history.
     let mut ongoing_ops = futures::executor::block_on(async move {
         get_ongoing_priority_ops(&eth_watcher_request_sender, address).await
     })
     .map_err(|err| {
-        vlog::warn!(
-            "Internal Server Error: '{}'; input: ({}, {}, {})",
-            err,
-            address,
-            offset,
-            limit,
-        );
+        vlog::warn!("Internal Server Error: '{}'; input: ({})", err, address);
         HttpResponse::InternalServerError().finish()
     })?;
 
-    // Sort operations by block number from smaller (older) to greater (newer).
+    // Sort operations by block number from greater (newer) to smaller (older).
     ongoing_ops.sort_by(|lhs, rhs| rhs.0.cmp(&lhs.0));
 
-    // Collect the unconfirmed priority operations with respect to the
-    // `offset` and `limit` parameters.
-    let mut ongoing_transactions_history: Vec<_> = ongoing_ops
+    Ok(ongoing_ops
         .iter()
         .map(|(block, op)| priority_op_to_tx_history(&tokens, *block, op))
-        .skip(offset as usize)
         .take(limit as usize)
-        .collect();
-
-    // Now we must include committed transactions, thus we have to modify `offset` and
-    // `limit` values.
-    if !ongoing_transactions_history.is_empty() {
-        // We've taken at least one transaction, this means
-        // offset is consumed completely, and limit is reduced.
-        offset = 0;
-        limit -= ongoing_transactions_history.len() as u64;
-    } else {
-        // Decrement the offset by the number of pending deposits
-        // that are soon to be added to the db. `ongoing_ops` consists
-        // of the deposits related to a target account only.
-        let num_account_ongoing_deposits = ongoing_ops.len() as u64;
-        offset = offset.saturating_sub(num_account_ongoing_deposits);
-    }
-
-    let mut transactions_history = storage
-        .chain()
-        .operations_ext_schema()
-        .get_account_transactions_history(&address, offset, limit)
-        .map_err(|err| {
-            vlog::warn!(
-                "Internal Server Error: '{}'; input: ({}, {}, {})",
-                err,
-                address,
-                offset,
-                limit,
-            );
-            HttpResponse::InternalServerError().finish()
-        })?;
-
-    // Append ongoing operations to the end of the end of the list, as the history
-    // goes from oldest tx to the newest tx.
-    transactions_history.append(&mut ongoing_transactions_history);
-
-    Ok(HttpResponse::Ok().json(transactions_history))
+        .collect())
 }
 
 #[derive(Debug, Deserialize)]
@@ -588,7 +561,7 @@ struct TxHistoryQuery {
     limit: Option<u64>,
 }
 
-fn parse_tx_id(data: &str, storage: &StorageProcessor) -> ActixResult<(u64, u64)> {
+fn parse_tx_id(data: &str, storage: &StorageProcessor) -> ActixResult<OperationCursor> {
     if data.is_empty() || data == "-" {
         let last_block_id = storage
             .chain()
@@ -601,19 +574,11 @@ fn parse_tx_id(data: &str, storage: &StorageProcessor) -> ActixResult<(u64, u64)
 
         let next_block_id = last_block_id + 1;
 
-        return Ok((next_block_id as u64, 0));
-    }
-
-    let parts: Vec<u64> = data
-        .split(',')
-        .map(|val| val.parse().map_err(|_| HttpResponse::BadRequest().finish()))
-        .collect::<Result<Vec<u64>, HttpResponse>>()?;
-
-    if parts.len() != 2 {
-        return Err(HttpResponse::BadRequest().finish().into());
+        return Ok(OperationCursor::new(next_block_id as u64, 0));
     }
 
-    Ok((parts[0], parts[1]))
+    data.parse()
+        .map_err(|_| HttpResponse::BadRequest().finish().into())
 }
 
 fn handle_get_account_transactions_history_older_than(
@@ -627,7 +592,7 @@ fn handle_get_account_transactions_history_older_than(
         .as_ref()
         .map(|s| s.as_ref())
         .unwrap_or("-");
-    let limit = request_query.limit.unwrap_or(MAX_LIMIT);
+    let mut limit = request_query.limit.unwrap_or(MAX_LIMIT);
 
     const MAX_LIMIT: u64 = 100;
     if limit > MAX_LIMIT {
@@ -635,10 +600,18 @@ fn handle_get_account_transactions_history_older_than(
     }
     let storage = data.access_storage()?;
 
+    // The first page starts with the ongoing operations, as the newest ones.
+    let mut transactions_history = if tx_id.is_empty() || tx_id == "-" {
+        load_ongoing_txs_history(&data, &storage, address, limit)?
+    } else {
+        Vec::new()
+    };
+    limit -= transactions_history.len() as u64;
+
     let tx_id = parse_tx_id(&tx_id, &storage)?;
 
     let direction = SearchDirection::Older;
-    let transactions_history = storage
+    let mut executed_history = storage
         .chain()
         .operations_ext_schema()
         .get_account_transactions_history_from(&address, tx_id, direction, limit)
@@ -652,6 +625,7 @@ fn handle_get_account_transactions_history_older_than(
             );
             HttpResponse::InternalServerError().finish()
         })?;
+    transactions_history.append(&mut executed_history);
 
     Ok(HttpResponse::Ok().json(transactions_history))
 }
@@ -698,53 +672,66 @@ fn handle_get_account_transactions_history_newer_than(
     if limit > 0 {
         // We've got some free space, so load unconfirmed operations to
         // fill the rest of the limit.
+        // Unprocessed operations must be in the end (as the newest ones).
+        let mut txs = load_ongoing_txs_history(&data, &storage, address, limit)?;
+        transactions_history.append(&mut txs);
+    }
 
-        let eth_watcher_request_sender = data.eth_watcher_request_sender.clone();
-        let storage = data.access_storage()?;
-        let tokens = storage.tokens_schema().load_tokens().map_err(|err| {
-            vlog::warn!(
-                "Internal Server Error: '{}'; input: ({}, {:?}, {})",
-                err,
-                address,
-                tx_id,
-                limit,
-            );
-            HttpResponse::InternalServerError().finish()
-        })?;
+    Ok(HttpResponse::Ok().json(transactions_history))
+}
 
-        // Fetch ongoing deposits, since they must be reported within the transactions history.
-        let mut ongoing_ops = futures::executor::block_on(async move {
-            get_ongoing_priority_ops(&eth_watcher_request_sender, address).await
-        })
+#[derive(Debug, Deserialize)]
+struct RejectedTxsQuery {
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RejectedTxResponse {
+    tx_hash: String,
+    reason: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Returns the latest transactions of the account rejected upon submission,
+/// along with the rejection reasons. Only the transactions rejected after the
+/// signature check are recorded.
+fn handle_get_account_rejected_txs(
+    data: web::Data<AppState>,
+    request_path: web::Path<Address>,
+    request_query: web::Query<RejectedTxsQuery>,
+) -> ActixResult<HttpResponse> {
+    let address = request_path.into_inner();
+
+    const MAX_LIMIT: i64 = 100;
+    let limit = request_query.limit.unwrap_or(MAX_LIMIT);
+    if limit <= 0 || limit > MAX_LIMIT {
+        return Err(HttpResponse::BadRequest().finish().into());
+    }
+
+    let storage = data.access_storage()?;
+    let rejected_txs: Vec<_> = storage
+        .chain()
+        .mempool_schema()
+        .load_rejected_txs(&address, limit)
         .map_err(|err| {
             vlog::warn!(
-                "Internal Server Error: '{}'; input: ({}, {:?}, {})",
+                "Internal Server Error: '{}'; input: ({}, {})",
                 err,
                 address,
-                tx_id,
-                limit,
+                limit
             );
             HttpResponse::InternalServerError().finish()
-        })?;
-
-        // Sort operations by block number from smaller (older) to greater (newer).
-        ongoing_ops.sort_by(|lhs, rhs| rhs.0.cmp(&lhs.0));
-
-        // Collect the unconfirmed priority operations with respect to the
-        // `limit` parameters.
-        let mut txs: Vec<_> = ongoing_ops
-            .iter()
-            .map(|(block, op)| priority_op_to_tx_history(&tokens, *block, op))
-            .take(limit as usize)
-            .collect();
-
-        // Merge `txs` and `transactions_history` and reassign the `transactions_history` to the
-        // merged list.
-        // Unprocessed operations must be in the end (as the newest ones).
-        transactions_history.append(&mut txs);
-    }
+        })?
+        .into_iter()
+        .map(|stored| RejectedTxResponse {
+            tx_hash: format!("sync-tx:{}", hex::encode(&stored.tx_hash)),
+            reason: stored.reason,
+            created_at: stored.created_at,
+        })
+        .collect();
 
-    Ok(HttpResponse::Ok().json(transactions_history))
+    Ok(HttpResponse::Ok().json(rejected_txs))
 }
 
 fn handle_get_executed_transaction_by_hash(
@@ -796,8 +783,8 @@ fn handle_get_tx_by_hash(
 
     // Or try to find this priority op in eth_watcher
     let eth_watcher_request_sender = data.eth_watcher_request_sender.clone();
-    let unconfirmed_op = futures::executor::block_on(async {
-        get_unconfirmed_op_by_hash(&eth_watcher_request_sender, &hash).await
+    let ongoing_op = futures::executor::block_on(async {
+        get_ongoing_op_by_hash(&eth_watcher_request_sender, &hash).await
     })
     .map_err(|err| {
         vlog::warn!(
@@ -810,13 +797,13 @@ fn handle_get_tx_by_hash(
 
     // If eth watcher has a priority op with given hash, transform it
     // to TxByHashResponse and assign it to res.
-    if let Some((eth_block, priority_op)) = unconfirmed_op {
+    if let Some((eth_block, priority_op)) = ongoing_op {
         let tokens = storage.tokens_schema().load_tokens().map_err(|err| {
             vlog::warn!("Internal Server Error: '{}';", err);
             HttpResponse::InternalServerError().finish()
         })?;
 
-        res = deposit_op_to_tx_by_hash(&tokens, &priority_op, eth_block);
+        res = Some(priority_op_to_tx_by_hash(&tokens, &priority_op, eth_block));
     }
 
     // Return res
@@ -949,10 +936,6 @@ fn start_server(state: AppState, bind_to: SocketAddr) {
                     .route("/testnet_config", web::get().to(handle_get_testnet_config))
                     .route("/status", web::get().to(handle_get_network_status))
                     .route("/tokens", web::get().to(handle_get_tokens))
-                    .route(
-                        "/account/{address}/history/{offset}/{limit}",
-                        web::get().to(handle_get_account_transactions_history),
-                    )
                     .route(
                         "/account/{address}/history/older_than",
                         web::get().to(handle_get_account_transactions_history_older_than),
@@ -961,6 +944,10 @@ fn start_server(state: AppState, bind_to: SocketAddr) {
                         "/account/{address}/history/newer_than",
                         web::get().to(handle_get_account_transactions_history_newer_than),
                     )
+                    .route(
+                        "/account/{address}/rejected_txs",
+                        web::get().to(handle_get_account_rejected_txs),
+                    )
                     .route(
                         "/transactions/{tx_hash}",
                         web::get().to(handle_get_executed_transaction_by_hash),
@@ -1,6 +1,9 @@
 use std::{
     collections::HashMap,
-    sync::{Arc, RwLock},
+    sync::{
+        mpsc::{sync_channel, SyncSender},
+        Arc, RwLock,
+    },
 };
 // External uses
 use futures::{
@@ -15,9 +18,10 @@ use num::{BigUint, ToPrimitive};
 use models::{
     config_options::{ConfigurationOptions, ThreadPanicNotify},
     node::{
+        is_token_amount_packable,
         tx::{TxEthSignature, TxHash},
-        Account, AccountId, Address, FranklinPriorityOp, FranklinTx, Nonce, PriorityOp, PubKeyHash,
-        Token, TokenId, TokenLike, TxFeeTypes,
+        Account, AccountId, AccountUpdate, Address, FranklinPriorityOp, FranklinTx, Nonce,
+        PriorityOp, PubKeyHash, Token, TokenId, TokenLike, Transfer, TxFeeTypes,
     },
     primitives::{BigUintSerdeAsRadix10Str, BigUintSerdeWrapper},
 };
@@ -31,7 +35,7 @@ use storage::{
 
 // Local uses
 use crate::{
-    api_server::ops_counter::ChangePubKeyOpsCounter,
+    api_server::{ops_counter::ChangePubKeyOpsCounter, sponsored_txs::SponsoredTxs},
     eth_watch::{EthBlockId, EthWatchRequest},
     fee_ticker::{Fee, TickerRequest},
     mempool::{MempoolRequest, TxAddError},
@@ -98,7 +102,11 @@ impl DepositingAccountBalances {
     ) -> Result<Self> {
         let mut balances = HashMap::new();
 
-        for op in pending_ops.deposits {
+        let deposits = pending_ops
+            .deposits
+            .into_iter()
+            .chain(pending_ops.confirmed_deposits);
+        for op in deposits {
             let token_symbol = if op.token_id == 0 {
                 "ETH".to_string()
             } else {
@@ -137,6 +145,10 @@ pub struct AccountInfoResp {
     depositing: DepositingAccountBalances,
     pub committed: ResponseAccountState,
     pub verified: ResponseAccountState,
+    /// State including the operations of the block which is not sealed yet.
+    /// Provided only upon request.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending: Option<ResponseAccountState>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -154,6 +166,94 @@ pub struct TransactionInfoResp {
     pub success: Option<bool>,
     pub fail_reason: Option<String>,
     pub block: Option<BlockInfo>,
+    /// ID of the account created by the transaction, e.g. the recipient of the transfer
+    /// to the address which had no account.
+    pub created_account_id: Option<AccountId>,
+}
+
+/// Change of the account state made by the transaction.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AccountChange {
+    #[serde(rename_all = "camelCase")]
+    Create {
+        account_id: AccountId,
+        address: Address,
+    },
+    #[serde(rename_all = "camelCase")]
+    Delete {
+        account_id: AccountId,
+        address: Address,
+    },
+    #[serde(rename_all = "camelCase")]
+    Balance {
+        account_id: AccountId,
+        token: TokenId,
+        #[serde(with = "BigUintSerdeAsRadix10Str")]
+        old_balance: BigUint,
+        #[serde(with = "BigUintSerdeAsRadix10Str")]
+        new_balance: BigUint,
+        old_nonce: Nonce,
+        new_nonce: Nonce,
+    },
+    #[serde(rename_all = "camelCase")]
+    PubKeyHash {
+        account_id: AccountId,
+        old_pub_key_hash: PubKeyHash,
+        new_pub_key_hash: PubKeyHash,
+        old_nonce: Nonce,
+        new_nonce: Nonce,
+    },
+}
+
+impl From<(AccountId, AccountUpdate)> for AccountChange {
+    fn from((account_id, update): (AccountId, AccountUpdate)) -> Self {
+        match update {
+            AccountUpdate::Create { address, .. } => Self::Create {
+                account_id,
+                address,
+            },
+            AccountUpdate::Delete { address, .. } => Self::Delete {
+                account_id,
+                address,
+            },
+            AccountUpdate::UpdateBalance {
+                old_nonce,
+                new_nonce,
+                balance_update: (token, old_balance, new_balance),
+            } => Self::Balance {
+                account_id,
+                token,
+                old_balance,
+                new_balance,
+                old_nonce,
+                new_nonce,
+            },
+            AccountUpdate::ChangePubKeyHash {
+                old_pub_key_hash,
+                new_pub_key_hash,
+                old_nonce,
+                new_nonce,
+            } => Self::PubKeyHash {
+                account_id,
+                old_pub_key_hash,
+                new_pub_key_hash,
+                old_nonce,
+                new_nonce,
+            },
+        }
+    }
+}
+
+/// Result of the transaction execution against the current state, which is not applied.
+/// Note that fees are credited to the fee account only once the block is sealed,
+/// so only the payer side of the fee is reflected in the changes.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunResp {
+    pub success: bool,
+    pub fail_reason: Option<String>,
+    pub changes: Vec<AccountChange>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -209,7 +309,7 @@ impl OngoingDeposit {
 
 /// Information about ongoing deposits for certain recipient address.
 ///
-/// Please note that since this response is based on the events that are
+/// Please note that since the unconfirmed deposits are based on the events that are
 /// currently awaiting confirmations, this information is approximate:
 /// blocks on Ethereum can be reverted, and final list of executed deposits
 /// can differ from the this estimation.
@@ -218,18 +318,20 @@ impl OngoingDeposit {
 pub struct OngoingDepositsResp {
     /// Address for which response is served.
     address: Address,
-    /// List of tuples (Eth block number, Deposit operation) of ongoing
-    /// deposit operations.
+    /// Deposit operations seen on Ethereum, but not having enough confirmations yet.
     deposits: Vec<OngoingDeposit>,
+    /// Deposit operations having enough confirmations, which are waiting
+    /// to be executed in zkSync.
+    confirmed_deposits: Vec<OngoingDeposit>,
 
     /// Amount of confirmations required for every deposit to be processed.
     confirmations_for_eth_event: u64,
 
     /// Estimated block number for deposits completions:
-    /// all the deposit operations for provided address are expected to be
+    /// all the unconfirmed deposit operations for provided address are expected to be
     /// accepted in the zkSync network upon reaching this blocks.
     ///
-    /// Can be `None` if there are no ongoing deposits.
+    /// Can be `None` if there are no unconfirmed deposits.
     estimated_deposits_approval_block: Option<u64>,
 }
 
@@ -238,6 +340,14 @@ pub enum RpcErrorCodes {
     NonceMismatch = 101,
     IncorrectTx = 103,
     FeeTooLow = 104,
+    NonceAlreadyQueued = 105,
+    TxExpired = 106,
+    TransferToZeroAddress = 107,
+    UnsupportedToken = 108,
+    AmountNotPackable = 109,
+    FastProcessingNotSupported = 110,
+    IncorrectFastWithdrawal = 111,
+    AddressDenied = 112,
 
     MissingEthSignature = 200,
     EIP1271SignatureVerificationFail = 201,
@@ -247,6 +357,10 @@ pub enum RpcErrorCodes {
     Other = 300,
     AccountCloseDisabled = 301,
     OperationsLimitReached = 302,
+    ReadOnlyMode = 303,
+    MempoolFull = 304,
+    ShuttingDown = 305,
+    ExodusMode = 306,
 }
 
 impl From<TxAddError> for RpcErrorCodes {
@@ -261,6 +375,18 @@ impl From<TxAddError> for RpcErrorCodes {
             TxAddError::ChangePkNotAuthorized => Self::ChangePkNotAuthorized,
             TxAddError::Other => Self::Other,
             TxAddError::DbError => Self::Other,
+            TxAddError::ReadOnlyMode => Self::ReadOnlyMode,
+            TxAddError::NonceAlreadyQueued => Self::NonceAlreadyQueued,
+            TxAddError::MempoolFull => Self::MempoolFull,
+            TxAddError::TxExpired => Self::TxExpired,
+            TxAddError::TransferToZeroAddress => Self::TransferToZeroAddress,
+            TxAddError::UnsupportedToken => Self::UnsupportedToken,
+            TxAddError::AmountNotPackable => Self::AmountNotPackable,
+            TxAddError::FastProcessingNotSupported => Self::FastProcessingNotSupported,
+            TxAddError::IncorrectFastWithdrawal => Self::IncorrectFastWithdrawal,
+            TxAddError::AddressDenied => Self::AddressDenied,
+            TxAddError::ShuttingDown => Self::ShuttingDown,
+            TxAddError::ExodusMode => Self::ExodusMode,
         }
     }
 }
@@ -273,10 +399,13 @@ impl Into<ErrorCode> for RpcErrorCodes {
 
 #[rpc]
 pub trait Rpc {
+    /// Returns the account state. If `include_pending` is set, the state including the
+    /// operations of the block which is not sealed yet is returned as well.
     #[rpc(name = "account_info", returns = "AccountInfoResp")]
     fn account_info(
         &self,
         addr: Address,
+        include_pending: Option<bool>,
     ) -> Box<dyn futures01::Future<Item = AccountInfoResp, Error = Error> + Send>;
 
     #[rpc(name = "ethop_info")]
@@ -290,8 +419,17 @@ pub trait Rpc {
         &self,
         tx: Box<FranklinTx>,
         signature: Box<Option<TxEthSignature>>,
+        fast_processing: Option<bool>,
     ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send>;
 
+    /// Executes the transaction against the current state without applying it,
+    /// and returns the changes of the accounts it would make. Signatures are not checked.
+    #[rpc(name = "tx_dry_run", returns = "DryRunResp")]
+    fn tx_dry_run(
+        &self,
+        tx: Box<FranklinTx>,
+    ) -> Box<dyn futures01::Future<Item = DryRunResp, Error = Error> + Send>;
+
     #[rpc(name = "contract_address")]
     fn contract_address(&self) -> Result<ContractAddressResp>;
 
@@ -337,6 +475,19 @@ pub struct RpcApp {
 
     /// Counter for ChangePubKey operations to filter the spam.
     ops_counter: Arc<RwLock<ChangePubKeyOpsCounter>>,
+
+    /// Minimum accepted fees as a percentage of the fee required by the fee model.
+    min_transfer_fee_percent: u32,
+    min_withdraw_fee_percent: u32,
+
+    /// Address of the liquidity provider serving the fast withdrawals, if any.
+    fast_withdrawal_lp_address: Option<Address>,
+
+    /// Transactions admitted with zero fee, shared by all the API servers.
+    sponsored_txs: Option<Arc<RwLock<SponsoredTxs>>>,
+
+    /// Sender of the rejected transactions to be recorded in the database.
+    rejected_txs_recorder: SyncSender<RejectedTx>,
 }
 
 impl RpcApp {
@@ -350,8 +501,10 @@ impl RpcApp {
         eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
         ticker_request_sender: mpsc::Sender<TickerRequest>,
         current_zksync_info: CurrentZksyncInfo,
+        sponsored_txs: Option<Arc<RwLock<SponsoredTxs>>>,
     ) -> Self {
         let token_cache = TokenDBCache::new(connection_pool.clone());
+        let rejected_txs_recorder = start_rejected_txs_recorder(connection_pool.clone());
 
         let api_requests_caches_size = config_options.api_requests_caches_size;
         let confirmations_for_eth_event = config_options.confirmations_for_eth_event;
@@ -374,6 +527,15 @@ impl RpcApp {
             current_zksync_info,
 
             ops_counter: Arc::new(RwLock::new(ChangePubKeyOpsCounter::new())),
+
+            min_transfer_fee_percent: config_options.min_transfer_fee_percent,
+            min_withdraw_fee_percent: config_options.min_withdraw_fee_percent,
+
+            fast_withdrawal_lp_address: config_options.fast_withdrawal_lp_address,
+
+            sponsored_txs,
+
+            rejected_txs_recorder,
         }
     }
 
@@ -393,7 +555,7 @@ impl RpcApp {
         self.token_cache
             .get_token(token_id)
             .map_err(rpc_message)?
-            .ok_or_else(|| rpc_message("Token not found in the DB"))
+            .ok_or_else(|| tx_add_error(TxAddError::UnsupportedToken))
     }
 
     /// Returns a message that user has to sign to send the transaction.
@@ -418,6 +580,73 @@ impl RpcApp {
     }
 }
 
+/// Maximum amount of the rejected transactions kept in the database.
+const MAX_STORED_REJECTED_TXS: i64 = 100_000;
+/// Maximum amount of the rejected transactions of the same sender kept in the database.
+const MAX_STORED_REJECTED_TXS_PER_SENDER: i64 = 100;
+/// Maximum amount of the rejected transactions waiting to be recorded. Rejections exceeding
+/// it are not recorded, so a burst of rejections doesn't slow down the API.
+const REJECTED_TXS_QUEUE_SIZE: usize = 1024;
+
+/// Transaction rejected upon submission, waiting to be recorded.
+#[derive(Debug)]
+struct RejectedTx {
+    tx_hash: TxHash,
+    sender: Address,
+    reason: String,
+}
+
+/// Starts the thread recording the rejected transactions, so the reason of the rejection
+/// can be requested later. Returns the sender of the transactions to be recorded.
+fn start_rejected_txs_recorder(connection_pool: ConnectionPool) -> SyncSender<RejectedTx> {
+    let (sender, receiver) = sync_channel::<RejectedTx>(REJECTED_TXS_QUEUE_SIZE);
+    std::thread::Builder::new()
+        .name("rejected_txs_recorder".to_string())
+        .spawn(move || {
+            for rejected_tx in receiver {
+                let storage = match connection_pool.access_storage_fragile() {
+                    Ok(storage) => storage,
+                    Err(err) => {
+                        log::warn!("Failed to store the rejected tx: {}", err);
+                        continue;
+                    }
+                };
+                storage
+                    .chain()
+                    .mempool_schema()
+                    .store_rejected_tx(
+                        rejected_tx.tx_hash.as_ref(),
+                        &rejected_tx.sender,
+                        &rejected_tx.reason,
+                        MAX_STORED_REJECTED_TXS,
+                        MAX_STORED_REJECTED_TXS_PER_SENDER,
+                    )
+                    .unwrap_or_else(|err| log::warn!("Failed to store the rejected tx: {}", err));
+            }
+        })
+        .expect("Rejected txs recorder thread");
+    sender
+}
+
+/// Queues the rejected transaction to be recorded. Failure to record the transaction
+/// doesn't affect the response.
+fn record_rejected_tx(recorder: &SyncSender<RejectedTx>, tx: &FranklinTx, error: &Error) {
+    // Read-only server may be connected to the replica database.
+    let read_only_mode: ErrorCode = RpcErrorCodes::ReadOnlyMode.into();
+    if error.code == read_only_mode {
+        return;
+    }
+
+    let rejected_tx = RejectedTx {
+        tx_hash: tx.hash(),
+        sender: tx.account(),
+        reason: error.message.clone(),
+    };
+    if let Err(err) = recorder.try_send(rejected_tx) {
+        log::warn!("Rejected tx is not recorded: {}", err);
+    }
+}
+
 pub(crate) async fn get_ongoing_priority_ops(
     eth_watcher_request_sender: &mpsc::Sender<EthWatchRequest>,
     address: Address,
@@ -450,6 +679,39 @@ pub(crate) async fn get_ongoing_priority_ops(
         .map_err(|_| Error::internal_error())
 }
 
+/// Obtains the deposits from or to the address which have enough confirmations,
+/// including the ones which were already executed.
+pub(crate) async fn get_confirmed_deposits(
+    eth_watcher_request_sender: &mpsc::Sender<EthWatchRequest>,
+    address: Address,
+) -> Result<Vec<PriorityOp>> {
+    let mut eth_watcher_request_sender = eth_watcher_request_sender.clone();
+
+    let eth_watcher_response = oneshot::channel();
+
+    eth_watcher_request_sender
+        .send(EthWatchRequest::GetConfirmedDeposits {
+            address,
+            resp: eth_watcher_response.0,
+        })
+        .await
+        .map_err(|err| {
+            log::warn!(
+                "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
+                file!(),
+                line!(),
+                column!(),
+                err
+            );
+            Error::internal_error()
+        })?;
+
+    eth_watcher_response
+        .1
+        .await
+        .map_err(|_| Error::internal_error())
+}
+
 impl RpcApp {
     fn access_storage(&self) -> Result<StorageProcessor> {
         self.connection_pool
@@ -479,6 +741,17 @@ impl RpcApp {
             })
             .collect();
 
+        // Confirmed deposits are kept in the priority queue for a while after the execution.
+        let mut confirmed_deposits = Vec::new();
+        for op in get_confirmed_deposits(&self.eth_watcher_request_sender, address).await? {
+            if self
+                .get_executed_priority_operation(op.serial_id as u32)?
+                .is_none()
+            {
+                confirmed_deposits.push(OngoingDeposit::new(op.eth_block, op));
+            }
+        }
+
         let estimated_deposits_approval_block = if !deposits.is_empty() {
             // We have to wait `confirmations_for_eth_event` blocks after the most
             // recent deposit operation.
@@ -491,6 +764,7 @@ impl RpcApp {
         Ok(OngoingDepositsResp {
             address,
             deposits,
+            confirmed_deposits,
             confirmations_for_eth_event,
             estimated_deposits_approval_block,
         })
@@ -663,12 +937,186 @@ impl RpcApp {
 
         Ok(verified_state)
     }
+
+    /// Checks the submitted transaction and sends it to the mempool.
+    fn submit_tx(
+        &self,
+        tx: Box<FranklinTx>,
+        signature: Box<Option<TxEthSignature>>,
+        fast_processing: Option<bool>,
+    ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send> {
+        if tx.is_close() {
+            return Box::new(futures01::future::err(Error {
+                code: RpcErrorCodes::AccountCloseDisabled.into(),
+                message: "Account close tx is disabled.".to_string(),
+                data: None,
+            }));
+        }
+
+        // Transactions accepted once the exodus mode can be triggered are likely to be lost.
+        if self.current_zksync_info.is_exodus_mode()
+            || self.current_zksync_info.is_exodus_imminent()
+        {
+            return Box::new(futures01::future::err(tx_add_error(TxAddError::ExodusMode)));
+        }
+
+        if let FranklinTx::Transfer(transfer) = tx.as_ref() {
+            if let Err(e) = check_transfer_target(transfer) {
+                return Box::new(futures01::future::err(tx_add_error(e)));
+            }
+        }
+
+        let fast_processing = fast_processing.unwrap_or_default();
+        let lp_address = self.fast_withdrawal_lp_address;
+        if let Err(e) = check_fast_processing(&tx, fast_processing, lp_address) {
+            return Box::new(futures01::future::err(tx_add_error(e)));
+        }
+
+        let msg_to_sign = match self.get_tx_info_message_to_sign(&tx) {
+            Ok(res) => res,
+            Err(e) => return Box::new(futures01::future::err(e)),
+        };
+
+        let tx_fee_info = match tx.as_ref() {
+            FranklinTx::Withdraw(withdraw) => Some((
+                TxFeeTypes::Withdraw,
+                TokenLike::Id(withdraw.token),
+                withdraw.to,
+                withdraw.fee.clone(),
+                self.min_withdraw_fee_percent,
+            )),
+            FranklinTx::Transfer(transfer) => Some((
+                TxFeeTypes::Transfer,
+                TokenLike::Id(transfer.token),
+                transfer.to,
+                transfer.fee.clone(),
+                self.min_transfer_fee_percent,
+            )),
+            _ => None,
+        };
+
+        let mut mempool_sender = self.mempool_request_sender.clone();
+        let sign_verify_channel = self.sign_verify_request_sender.clone();
+        let ticker_request_sender = self.ticker_request_sender.clone();
+        let ops_counter = self.ops_counter.clone();
+        let sponsored_txs = self.sponsored_txs.clone();
+        let rejected_txs_recorder = self.rejected_txs_recorder.clone();
+        let mempool_resp = async move {
+            // Allowlisted transaction with the fee too low uses the sponsorship budget only once
+            // its signature is verified, so the budget can't be exhausted by forged transactions.
+            let mut sponsored_fee = None;
+            if let Some((tx_type, token, address, provided_fee, min_fee_percent)) = tx_fee_info {
+                let required_fee =
+                    Self::ticker_request(ticker_request_sender, tx_type, address, token.clone())
+                        .await?;
+                if let Err(err) = check_tx_fee(&required_fee, &provided_fee, min_fee_percent) {
+                    let allowlisted = sponsored_txs.as_ref().map_or(false, |sponsored_txs| {
+                        sponsored_txs.read().expect("Read lock").is_allowlisted(&tx)
+                    });
+                    if !allowlisted {
+                        warn!(
+                            "User provided fee is too low, required: {:?}, provided: {}, token: {:?}",
+                            required_fee, provided_fee, token
+                        );
+                        return Err(err);
+                    }
+                    sponsored_fee = Some((required_fee.total_fee, err));
+                }
+            }
+
+            let verified_tx = verify_tx_info_message_signature(
+                &tx,
+                *signature.clone(),
+                msg_to_sign,
+                sign_verify_channel,
+            )
+            .await?;
+
+            // Rejections are recorded only once the signature is verified, so the records
+            // of the account can't be flooded with the forged transactions.
+            let result = async {
+                // Check whether operations limit for this account was reached.
+                // We must do it after we've checked that transaction is correct to avoid
+                // the situation when somebody sends incorrect transactions to deny changing
+                // the pubkey for some account ID.
+                if let FranklinTx::ChangePubKey(tx) = tx.as_ref() {
+                    let mut ops_counter_lock = ops_counter.write().expect("Write lock");
+
+                    if let Err(error) = ops_counter_lock.check_allowanse(&tx) {
+                        return Err(Error {
+                            code: RpcErrorCodes::OperationsLimitReached.into(),
+                            message: error.to_string(),
+                            data: None,
+                        });
+                    }
+                }
+
+                // Budget is reserved right before the mempool admission, and refunded
+                // if the transaction is rejected.
+                let sponsorship = match (sponsored_fee, &sponsored_txs) {
+                    (Some((required_fee, err)), Some(sponsored_txs)) => {
+                        let mut sponsored_txs_lock = sponsored_txs.write().expect("Write lock");
+                        match sponsored_txs_lock.sponsor(&tx, &required_fee) {
+                            Ok(sponsorship) => Some(sponsorship),
+                            Err(error) => {
+                                warn!("Tx {} is not sponsored: {}", tx.hash().to_string(), error);
+                                return Err(err);
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+
+                let hash = tx.hash();
+                let mempool_result = async {
+                    let mempool_resp = oneshot::channel();
+                    mempool_sender
+                        .send(MempoolRequest::NewTx(Box::new(verified_tx), mempool_resp.0))
+                        .await
+                        .map_err(|err| {
+                            log::warn!(
+                                "[{}:{}:{}] Internal Server Error: '{}'; input: <Tx: '{:?}', signature: '{:?}'>",
+                                file!(),
+                                line!(),
+                                column!(),
+                                err,
+                                tx,
+                                signature,
+                            );
+                            Error::internal_error()
+                        })?;
+                    let tx_add_result = mempool_resp.1.await.unwrap_or(Err(TxAddError::Other));
+
+                    tx_add_result.map(|_| hash).map_err(tx_add_error)
+                }
+                .await;
+
+                if let (Some(sponsorship), Some(sponsored_txs)) = (sponsorship, &sponsored_txs) {
+                    let mut sponsored_txs_lock = sponsored_txs.write().expect("Write lock");
+                    if mempool_result.is_ok() {
+                        sponsored_txs_lock.confirm(sponsorship);
+                    } else {
+                        sponsored_txs_lock.refund(sponsorship);
+                    }
+                }
+                mempool_result
+            }
+            .await;
+            if let Err(err) = &result {
+                record_rejected_tx(&rejected_txs_recorder, &tx, err);
+            }
+            result
+        };
+
+        Box::new(mempool_resp.boxed().compat())
+    }
 }
 
 impl Rpc for RpcApp {
     fn account_info(
         &self,
         address: Address,
+        include_pending: Option<bool>,
     ) -> Box<dyn futures01::Future<Item = AccountInfoResp, Error = Error> + Send> {
         // TODO: this method now has a lot debug output, to be removed as soon as problem is detected.
         use std::time::Instant;
@@ -680,7 +1128,7 @@ impl Rpc for RpcApp {
         let account_state_resp = async move {
             let state_keeper_response = oneshot::channel();
             state_keeper_request_sender
-                .send(StateKeeperRequest::GetAccount(
+                .send(StateKeeperRequest::GetAccountStates(
                     address,
                     state_keeper_response.0,
                 ))
@@ -697,7 +1145,7 @@ impl Rpc for RpcApp {
                     Error::internal_error()
                 })?;
 
-            let committed_account_state = state_keeper_response.1.await.map_err(|err| {
+            let account_states = state_keeper_response.1.await.map_err(|err| {
                 log::warn!(
                     "[{}:{}:{}] Internal Server Error: '{}'; input: {}",
                     file!(),
@@ -709,14 +1157,26 @@ impl Rpc for RpcApp {
                 Error::internal_error()
             })?;
 
-            let (id, committed) = committed_account_state
-                .map(|(id, account)| {
-                    let restored_state =
-                        ResponseAccountState::try_restore(account, &self_.token_cache)?;
-                    Ok((Some(id), restored_state))
-                })
-                .transpose()?
-                .unwrap_or_default();
+            let (id, committed, pending) = match account_states {
+                Some(states) => {
+                    let committed = match states.sealed {
+                        Some(account) => {
+                            ResponseAccountState::try_restore(account, &self_.token_cache)?
+                        }
+                        None => ResponseAccountState::default(),
+                    };
+                    let pending = if include_pending.unwrap_or(false) {
+                        Some(ResponseAccountState::try_restore(
+                            states.pending,
+                            &self_.token_cache,
+                        )?)
+                    } else {
+                        None
+                    };
+                    (Some(states.id), committed, pending)
+                }
+                None => (None, ResponseAccountState::default(), None),
+            };
 
             let verified = self_.get_verified_account_state(&address)?;
 
@@ -736,6 +1196,7 @@ impl Rpc for RpcApp {
                 committed,
                 verified,
                 depositing,
+                pending,
             })
         };
 
@@ -778,6 +1239,9 @@ impl Rpc for RpcApp {
                     committed: true,
                     verified: stored_receipt.verified,
                 }),
+                created_account_id: stored_receipt
+                    .created_account_id
+                    .map(|account_id| account_id as AccountId),
             }
         } else {
             TransactionInfoResp {
@@ -785,6 +1249,7 @@ impl Rpc for RpcApp {
                 success: None,
                 fail_reason: None,
                 block: None,
+                created_account_id: None,
             }
         })
     }
@@ -793,111 +1258,52 @@ impl Rpc for RpcApp {
         &self,
         tx: Box<FranklinTx>,
         signature: Box<Option<TxEthSignature>>,
+        fast_processing: Option<bool>,
     ) -> Box<dyn futures01::Future<Item = TxHash, Error = Error> + Send> {
-        if tx.is_close() {
-            return Box::new(futures01::future::err(Error {
-                code: RpcErrorCodes::AccountCloseDisabled.into(),
-                message: "Account close tx is disabled.".to_string(),
-                data: None,
-            }));
-        }
-
-        let msg_to_sign = match self.get_tx_info_message_to_sign(&tx) {
-            Ok(res) => res,
-            Err(e) => return Box::new(futures01::future::err(e)),
-        };
-
-        let tx_fee_info = match tx.as_ref() {
-            FranklinTx::Withdraw(withdraw) => Some((
-                TxFeeTypes::Withdraw,
-                TokenLike::Id(withdraw.token),
-                withdraw.to,
-                withdraw.fee.clone(),
-            )),
-            FranklinTx::Transfer(transfer) => Some((
-                TxFeeTypes::Transfer,
-                TokenLike::Id(transfer.token),
-                transfer.to,
-                transfer.fee.clone(),
-            )),
-            _ => None,
-        };
-
-        let mut mempool_sender = self.mempool_request_sender.clone();
-        let sign_verify_channel = self.sign_verify_request_sender.clone();
-        let ticker_request_sender = self.ticker_request_sender.clone();
-        let ops_counter = self.ops_counter.clone();
-        let mempool_resp = async move {
-            if let Some((tx_type, token, address, provided_fee)) = tx_fee_info {
-                let required_fee =
-                    Self::ticker_request(ticker_request_sender, tx_type, address, token.clone())
-                        .await?;
-                // We allow fee to be 5% off the required fee
-                let scaled_provided_fee =
-                    provided_fee.clone() * BigUint::from(105u32) / BigUint::from(100u32);
-                if required_fee.total_fee >= scaled_provided_fee {
-                    warn!(
-                        "User provided fee is too low, required: {:?}, provided: {} (scaled: {}), token: {:?}",
-                        required_fee, provided_fee, scaled_provided_fee, token
-                    );
-                    return Err(Error {
-                        code: RpcErrorCodes::from(TxAddError::TxFeeTooLow).into(),
-                        message: TxAddError::TxFeeTooLow.to_string(),
-                        data: None,
-                    });
-                }
-            }
-
-            let verified_tx = verify_tx_info_message_signature(
-                &tx,
-                *signature.clone(),
-                msg_to_sign,
-                sign_verify_channel,
-            )
-            .await?;
-
-            // Check whether operations limit for this account was reached.
-            // We must do it after we've checked that transaction is correct to avoid the situation
-            // when somebody sends incorrect transactions to deny changing the pubkey for some account ID.
-            if let FranklinTx::ChangePubKey(tx) = tx.as_ref() {
-                let mut ops_counter_lock = ops_counter.write().expect("Write lock");
-
-                if let Err(error) = ops_counter_lock.check_allowanse(&tx) {
-                    return Err(Error {
-                        code: RpcErrorCodes::OperationsLimitReached.into(),
-                        message: error.to_string(),
-                        data: None,
-                    });
-                }
-            }
+        self.submit_tx(tx, signature, fast_processing)
+    }
 
-            let hash = tx.hash();
-            let mempool_resp = oneshot::channel();
-            mempool_sender
-                .send(MempoolRequest::NewTx(Box::new(verified_tx), mempool_resp.0))
+    fn tx_dry_run(
+        &self,
+        tx: Box<FranklinTx>,
+    ) -> Box<dyn futures01::Future<Item = DryRunResp, Error = Error> + Send> {
+        let mut state_keeper_request_sender = self.state_keeper_request_sender.clone();
+        let dry_run_resp = async move {
+            let state_keeper_response = oneshot::channel();
+            state_keeper_request_sender
+                .send(StateKeeperRequest::DryRunTx(tx, state_keeper_response.0))
                 .await
                 .map_err(|err| {
                     log::warn!(
-                        "[{}:{}:{}] Internal Server Error: '{}'; input: <Tx: '{:?}', signature: '{:?}'>",
+                        "[{}:{}:{}] Internal Server Error: '{}'; input: N/A",
                         file!(),
                         line!(),
                         column!(),
-                        err,
-                        tx,
-                        signature,
+                        err
                     );
                     Error::internal_error()
                 })?;
-            let tx_add_result = mempool_resp.1.await.unwrap_or(Err(TxAddError::Other));
 
-            tx_add_result.map(|_| hash).map_err(|e| Error {
-                code: RpcErrorCodes::from(e).into(),
-                message: e.to_string(),
-                data: None,
+            let dry_run_result = state_keeper_response
+                .1
+                .await
+                .map_err(|_| Error::internal_error())?;
+
+            Ok(match dry_run_result {
+                Ok(updates) => DryRunResp {
+                    success: true,
+                    fail_reason: None,
+                    changes: updates.into_iter().map(AccountChange::from).collect(),
+                },
+                Err(fail_reason) => DryRunResp {
+                    success: false,
+                    fail_reason: Some(fail_reason),
+                    changes: Vec::new(),
+                },
             })
         };
 
-        Box::new(mempool_resp.boxed().compat())
+        Box::new(dry_run_resp.boxed().compat())
     }
 
     fn contract_address(&self) -> Result<ContractAddressResp> {
@@ -987,6 +1393,7 @@ pub fn start_rpc_server(
     ticker_request_sender: mpsc::Sender<TickerRequest>,
     panic_notify: mpsc::Sender<bool>,
     current_zksync_info: CurrentZksyncInfo,
+    sponsored_txs: Option<Arc<RwLock<SponsoredTxs>>>,
 ) {
     let addr = config_options.json_rpc_http_server_address;
     std::thread::Builder::new()
@@ -1004,6 +1411,7 @@ pub fn start_rpc_server(
                 eth_watcher_request_sender,
                 ticker_request_sender,
                 current_zksync_info,
+                sponsored_txs,
             );
             rpc_app.extend(&mut io);
 
@@ -1081,9 +1489,175 @@ async fn verify_tx_info_message_signature(
         .map_err(rpc_message)
 }
 
+fn tx_add_error(error: TxAddError) -> Error {
+    Error {
+        code: RpcErrorCodes::from(error).into(),
+        message: error.to_string(),
+        data: None,
+    }
+}
+
+/// Checks the target of the transfer before its admission, so the user receives
+/// the specific rejection reason rather than the generic "incorrect tx" one.
+/// Existence of the token is checked upon the sign message construction.
+fn check_transfer_target(transfer: &Transfer) -> std::result::Result<(), TxAddError> {
+    if transfer.to == Address::zero() {
+        return Err(TxAddError::TransferToZeroAddress);
+    }
+    if !is_token_amount_packable(&transfer.amount) {
+        return Err(TxAddError::AmountNotPackable);
+    }
+    Ok(())
+}
+
+/// Checks that the withdrawal is flagged for the fast processing if and only if it's
+/// sent to the liquidity provider, since such withdrawals are kept in the mempool
+/// until the liquidity provider sends the matching transfer (see `fast_withdrawals`).
+fn check_fast_processing(
+    tx: &FranklinTx,
+    fast_processing: bool,
+    lp_address: Option<Address>,
+) -> std::result::Result<(), TxAddError> {
+    let sent_to_lp = match (tx, lp_address) {
+        (FranklinTx::Withdraw(withdraw), Some(lp_address)) => {
+            withdraw.to == lp_address && withdraw.from != lp_address
+        }
+        _ => false,
+    };
+
+    if fast_processing {
+        if !tx.is_withdraw() || lp_address.is_none() {
+            return Err(TxAddError::FastProcessingNotSupported);
+        }
+        if !sent_to_lp {
+            return Err(TxAddError::IncorrectFastWithdrawal);
+        }
+    } else if sent_to_lp {
+        return Err(TxAddError::IncorrectFastWithdrawal);
+    }
+    Ok(())
+}
+
+/// Checks that the fee provided by the user is not lower than `min_fee_percent`
+/// percents of the fee required by the fee model.
+///
+/// Rejection contains the required and the minimum accepted fees, so the client
+/// is able to resubmit the transaction with an appropriate fee.
+fn check_tx_fee(required_fee: &Fee, provided_fee: &BigUint, min_fee_percent: u32) -> Result<()> {
+    let min_fee =
+        required_fee.total_fee.clone() * BigUint::from(min_fee_percent) / BigUint::from(100u32);
+    if *provided_fee >= min_fee {
+        return Ok(());
+    }
+
+    Err(Error {
+        code: RpcErrorCodes::from(TxAddError::TxFeeTooLow).into(),
+        message: TxAddError::TxFeeTooLow.to_string(),
+        data: Some(serde_json::json!({
+            "feeType": required_fee.fee_type,
+            "requiredFee": required_fee.total_fee.to_string(),
+            "minFee": min_fee.to_string(),
+            "providedFee": provided_fee.to_string(),
+        })),
+    })
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::fee_ticker::OutputFeeType;
+    use models::test_utils::{self, address};
+
+    #[test]
+    fn transfer_target_check() {
+        let (from, to) = (address(1), address(2));
+        let transfer = |to: Address, amount: u64| test_utils::transfer(from, to, amount, 0);
+
+        assert!(check_transfer_target(&transfer(to, 100)).is_ok());
+        assert!(matches!(
+            check_transfer_target(&transfer(Address::zero(), 100)),
+            Err(TxAddError::TransferToZeroAddress)
+        ));
+        // Amount with too many significant digits can't be packed.
+        assert!(matches!(
+            check_transfer_target(&transfer(to, 123_456_789_123_456)),
+            Err(TxAddError::AmountNotPackable)
+        ));
+    }
+
+    #[test]
+    fn account_change_serialization() {
+        let update = AccountUpdate::UpdateBalance {
+            old_nonce: 1,
+            new_nonce: 2,
+            balance_update: (0, 100u32.into(), 90u32.into()),
+        };
+        let change = AccountChange::from((5, update));
+        assert_eq!(
+            serde_json::to_value(&change).unwrap(),
+            serde_json::json!({
+                "type": "balance",
+                "accountId": 5,
+                "token": 0,
+                "oldBalance": "100",
+                "newBalance": "90",
+                "oldNonce": 1,
+                "newNonce": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn fast_processing_check() {
+        let (user, lp) = (address(1), address(2));
+        let withdraw =
+            |to: Address| FranklinTx::Withdraw(Box::new(test_utils::withdraw(user, to, 100, 0)));
+        let transfer = FranklinTx::Transfer(Box::new(test_utils::transfer(user, lp, 100, 0)));
+
+        assert!(check_fast_processing(&withdraw(lp), true, Some(lp)).is_ok());
+        assert!(check_fast_processing(&withdraw(user), false, Some(lp)).is_ok());
+        assert!(check_fast_processing(&transfer, false, Some(lp)).is_ok());
+        assert!(matches!(
+            check_fast_processing(&withdraw(lp), true, None),
+            Err(TxAddError::FastProcessingNotSupported)
+        ));
+        assert!(matches!(
+            check_fast_processing(&transfer, true, Some(lp)),
+            Err(TxAddError::FastProcessingNotSupported)
+        ));
+        assert!(matches!(
+            check_fast_processing(&withdraw(user), true, Some(lp)),
+            Err(TxAddError::IncorrectFastWithdrawal)
+        ));
+        // Withdrawal to the liquidity provider must be flagged.
+        assert!(matches!(
+            check_fast_processing(&withdraw(lp), false, Some(lp)),
+            Err(TxAddError::IncorrectFastWithdrawal)
+        ));
+    }
+
+    #[test]
+    fn min_fee_check() {
+        let required_fee = Fee {
+            fee_type: OutputFeeType::Transfer,
+            gas_tx_amount: 0u32.into(),
+            gas_price_wei: 0u32.into(),
+            gas_fee: 0u32.into(),
+            zkp_fee: 1000u32.into(),
+            total_fee: 1000u32.into(),
+        };
+
+        assert!(check_tx_fee(&required_fee, &1000u32.into(), 100).is_ok());
+        assert!(check_tx_fee(&required_fee, &950u32.into(), 95).is_ok());
+
+        let err = check_tx_fee(&required_fee, &949u32.into(), 95).unwrap_err();
+        let expected_code: ErrorCode = RpcErrorCodes::FeeTooLow.into();
+        assert_eq!(err.code, expected_code);
+        let data = err.data.expect("rejection data is missing");
+        assert_eq!(data["minFee"], "950");
+        assert_eq!(data["requiredFee"], "1000");
+        assert_eq!(data["providedFee"], "949");
+    }
 
     #[test]
     fn tx_fee_type_serialization() {
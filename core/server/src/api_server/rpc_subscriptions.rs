@@ -1,10 +1,10 @@
 #![allow(clippy::needless_return)]
 
 // Built-in deps
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 // External uses
 use crate::eth_watch::EthWatchRequest;
-use futures::channel::mpsc;
+use futures::channel::{mpsc, oneshot};
 use jsonrpc_core::{MetaIoHandler, Result};
 use jsonrpc_derive::rpc;
 use jsonrpc_pubsub::{typed::Subscriber, PubSubHandler, Session, SubscriptionId};
@@ -22,7 +22,8 @@ use crate::fee_ticker::TickerRequest;
 use crate::{
     api_server::event_notify::{start_sub_notifier, EventNotifierRequest, EventSubscribeRequest},
     api_server::rpc_server::{ETHOpInfoResp, ResponseAccountState, TransactionInfoResp},
-    mempool::MempoolRequest,
+    api_server::sponsored_txs::SponsoredTxs,
+    mempool::{MempoolRequest, ReplacedTxNotify},
     signature_checker::VerifyTxSignatureRequest,
     state_keeper::{ExecutedOpsNotify, StateKeeperRequest},
     utils::current_zksync_info::CurrentZksyncInfo,
@@ -187,6 +188,8 @@ pub fn start_ws_server(
     db_pool: ConnectionPool,
     mempool_request_sender: mpsc::Sender<MempoolRequest>,
     executed_tx_receiver: mpsc::Receiver<ExecutedOpsNotify>,
+    replaced_tx_receiver: mpsc::Receiver<ReplacedTxNotify>,
+    notifier_flush_receiver: mpsc::Receiver<oneshot::Sender<()>>,
     state_keeper_request_sender: mpsc::Sender<StateKeeperRequest>,
     sign_verify_request_sender: mpsc::Sender<VerifyTxSignatureRequest>,
     eth_watcher_request_sender: mpsc::Sender<EthWatchRequest>,
@@ -194,6 +197,7 @@ pub fn start_ws_server(
     panic_notify: mpsc::Sender<bool>,
     each_cache_size: usize,
     current_zksync_info: CurrentZksyncInfo,
+    sponsored_txs: Option<Arc<RwLock<SponsoredTxs>>>,
 ) {
     let addr = config_options.json_rpc_ws_server_address;
 
@@ -210,6 +214,7 @@ pub fn start_ws_server(
         eth_watcher_request_sender,
         ticker_request_sender,
         current_zksync_info,
+        sponsored_txs,
     );
     req_rpc_app.extend(&mut io);
 
@@ -222,6 +227,8 @@ pub fn start_ws_server(
         op_recv,
         event_sub_receiver,
         executed_tx_receiver,
+        replaced_tx_receiver,
+        notifier_flush_receiver,
         state_keeper_request_sender,
         panic_notify.clone(),
         each_cache_size,
@@ -0,0 +1,346 @@
+//! This module contains a structure for admitting the transactions sponsored by the operator.
+//!
+//! Transactions of the allowlisted senders, or of the allowlisted types, are admitted
+//! with the fee below the one required by the fee model (e.g. zero fee), so the applications
+//! can subsidize the onboarding of their users. Sponsored transactions are still executed
+//! by the operator at its own cost, so their amount is limited by the budget renewed
+//! every period. Transactions sponsored by type are also limited per sender, so
+//! a single sender can't exhaust the budget of everyone else.
+//!
+//! Transactions paying the required fee are admitted as usual and don't use the budget.
+//! Budget is reserved before the transaction is sent to the mempool, and is refunded
+//! if the mempool rejects it. Every sponsored transaction is accounted: the amount of them
+//! and the waived fees are reported in the metrics, and the summary is logged once
+//! the period is over.
+
+// Built-in deps.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    time::{Duration, Instant},
+};
+// External deps.
+use num::{BigUint, Zero};
+// Workspace deps.
+use models::{
+    config_options::SponsoredTxsOptions,
+    node::{tx::TxHash, Address, FranklinTx, TokenId},
+};
+// Local deps.
+use crate::utils::metrics_registry::metrics_registry;
+
+/// `SponsoredTxs` keeps track of the budget of the sponsored transactions.
+///
+/// Within the period, no more than `budget` transactions are sponsored, and no more than
+/// `max_per_sender` of them are sponsored by type for the same sender.
+/// Once the period is over, the budget is renewed.
+#[derive(Debug, Clone)]
+pub struct SponsoredTxs {
+    senders: HashSet<Address>,
+    tx_types: HashSet<String>,
+    budget: u64,
+    period: Duration,
+    period_start: Instant,
+    max_per_sender: u64,
+    /// Amount of the transactions sponsored within the current period.
+    sponsored: u64,
+    /// Amount of the transactions sponsored by type within the current period, per sender.
+    sponsored_by_type: HashMap<Address, u64>,
+    /// Fees waived within the current period, per token.
+    waived_fees: BTreeMap<TokenId, BigUint>,
+}
+
+/// Budget reserved for the sponsored transaction until the mempool admits or rejects it.
+#[derive(Debug)]
+#[must_use]
+pub struct Sponsorship {
+    tx_hash: TxHash,
+    /// Sender of the transaction sponsored by type, `None` for the allowlisted sender.
+    sponsored_by_type: Option<Address>,
+    token: TokenId,
+    waived_fee: BigUint,
+    /// Start of the period the budget was reserved in.
+    period_start: Instant,
+}
+
+impl SponsoredTxs {
+    pub fn new(options: SponsoredTxsOptions) -> Self {
+        Self {
+            senders: options.senders.into_iter().collect(),
+            tx_types: options.tx_types.into_iter().collect(),
+            budget: options.budget,
+            period: options.period,
+            period_start: Instant::now(),
+            max_per_sender: options.max_per_sender,
+            sponsored: 0,
+            sponsored_by_type: HashMap::new(),
+            waived_fees: BTreeMap::new(),
+        }
+    }
+
+    /// Checks whether the transaction is sponsored by its sender or by its type.
+    pub fn is_allowlisted(&self, tx: &FranklinTx) -> bool {
+        self.senders.contains(&tx.account()) || self.tx_types.contains(tx_type_name(tx))
+    }
+
+    /// Reserves the budget for the allowlisted transaction paying the fee below `required_fee`.
+    /// Returns an error if the transaction is not allowlisted, or the budget is exhausted,
+    /// or the sender not in the allowlist has exhausted its share of the budget.
+    ///
+    /// Reservation must be either confirmed once the transaction is admitted to the mempool,
+    /// or refunded if it's rejected.
+    pub fn sponsor(
+        &mut self,
+        tx: &FranklinTx,
+        required_fee: &BigUint,
+    ) -> Result<Sponsorship, failure::Error> {
+        self.renew_budget_if_needed();
+
+        if !self.is_allowlisted(tx) {
+            failure::bail!("Transaction is not sponsored");
+        }
+        let (token, provided_fee) = match tx.fee() {
+            Some(fee) => fee,
+            None => failure::bail!("Transaction doesn't pay fee"),
+        };
+        if self.sponsored >= self.budget {
+            failure::bail!("Budget of the sponsored transactions is exhausted. Try again later");
+        }
+        let sender = tx.account();
+        let sponsored_by_type = if self.senders.contains(&sender) {
+            None
+        } else {
+            let sender_sponsored = self.sponsored_by_type.get(&sender).copied().unwrap_or(0);
+            if sender_sponsored >= self.max_per_sender {
+                failure::bail!(
+                    "Sender has exhausted its sponsored transactions for the period. Try again later"
+                );
+            }
+            Some(sender)
+        };
+
+        let waived_fee = if *required_fee > provided_fee {
+            required_fee - &provided_fee
+        } else {
+            BigUint::zero()
+        };
+        self.sponsored += 1;
+        if let Some(sender) = sponsored_by_type {
+            *self.sponsored_by_type.entry(sender).or_default() += 1;
+        }
+        *self.waived_fees.entry(token).or_default() += &waived_fee;
+        self.report_budget_left();
+
+        Ok(Sponsorship {
+            tx_hash: tx.hash(),
+            sponsored_by_type,
+            token,
+            waived_fee,
+            period_start: self.period_start,
+        })
+    }
+
+    /// Accounts the sponsored transaction admitted to the mempool.
+    pub fn confirm(&self, sponsorship: Sponsorship) {
+        metrics_registry().increment_counter(
+            "sponsored_txs",
+            "Transactions admitted with zero fee",
+            1.0,
+        );
+        log::debug!(
+            "Sponsored tx {} (token: {}, waived fee: {}), {} of {} in the current period",
+            sponsorship.tx_hash.to_string(),
+            sponsorship.token,
+            sponsorship.waived_fee,
+            self.sponsored,
+            self.budget
+        );
+    }
+
+    /// Returns the budget reserved for the transaction rejected by the mempool.
+    /// Reservations of the previous periods are not refunded, since the budget
+    /// is already renewed.
+    pub fn refund(&mut self, sponsorship: Sponsorship) {
+        if sponsorship.period_start != self.period_start {
+            return;
+        }
+        self.sponsored = self.sponsored.saturating_sub(1);
+        if let Some(sender) = sponsorship.sponsored_by_type {
+            if let Some(sender_sponsored) = self.sponsored_by_type.get_mut(&sender) {
+                *sender_sponsored = sender_sponsored.saturating_sub(1);
+            }
+        }
+        if let Some(waived_fee) = self.waived_fees.get_mut(&sponsorship.token) {
+            let refunded = sponsorship.waived_fee.min(waived_fee.clone());
+            *waived_fee -= refunded;
+        }
+        self.report_budget_left();
+    }
+
+    /// Returns the amount of the transactions sponsored within the current period,
+    /// and the fees waived for them.
+    pub fn period_usage(&self) -> (u64, &BTreeMap<TokenId, BigUint>) {
+        (self.sponsored, &self.waived_fees)
+    }
+
+    fn renew_budget_if_needed(&mut self) {
+        if self.period_start.elapsed() < self.period {
+            return;
+        }
+
+        if self.sponsored > 0 {
+            log::info!(
+                "Sponsored {} txs in the last period, waived fees per token: {:?}",
+                self.sponsored,
+                self.waived_fees
+                    .iter()
+                    .map(|(token, fee)| (*token, fee.to_string()))
+                    .collect::<Vec<_>>()
+            );
+        }
+        self.period_start = Instant::now();
+        self.sponsored = 0;
+        self.sponsored_by_type.clear();
+        self.waived_fees.clear();
+        self.report_budget_left();
+    }
+
+    fn report_budget_left(&self) {
+        metrics_registry().set_gauge(
+            "sponsored_txs_budget_left",
+            "Sponsored transactions left in the budget of the current period",
+            (self.budget - self.sponsored) as f64,
+        );
+    }
+}
+
+/// Returns the name of the transaction type, as it's serialized.
+fn tx_type_name(tx: &FranklinTx) -> &'static str {
+    match tx {
+        FranklinTx::Transfer(_) => "Transfer",
+        FranklinTx::Withdraw(_) => "Withdraw",
+        FranklinTx::Close(_) => "Close",
+        FranklinTx::ChangePubKey(_) => "ChangePubKey",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::test_utils::{self, address};
+
+    fn transfer(from: Address, token: TokenId, fee: u32) -> FranklinTx {
+        let mut transfer = test_utils::transfer(from, address(100), 10, fee);
+        transfer.token = token;
+        FranklinTx::Transfer(Box::new(transfer))
+    }
+
+    fn withdraw(from: Address) -> FranklinTx {
+        FranklinTx::Withdraw(Box::new(test_utils::withdraw(from, from, 10, 0)))
+    }
+
+    fn options(senders: Vec<Address>, tx_types: Vec<&str>, budget: u64) -> SponsoredTxsOptions {
+        SponsoredTxsOptions {
+            senders,
+            tx_types: tx_types.into_iter().map(String::from).collect(),
+            budget,
+            period: Duration::from_secs(60 * 60),
+            max_per_sender: 2,
+        }
+    }
+
+    /// Checks that the transactions are sponsored by the sender or by the type.
+    #[test]
+    fn allowlist() {
+        let (sponsored, other) = (address(1), address(2));
+        let sponsored_txs = SponsoredTxs::new(options(vec![sponsored], vec!["Transfer"], 10));
+
+        assert!(sponsored_txs.is_allowlisted(&transfer(sponsored, 0, 0)));
+        assert!(sponsored_txs.is_allowlisted(&withdraw(sponsored)));
+        assert!(sponsored_txs.is_allowlisted(&transfer(other, 0, 0)));
+        assert!(!sponsored_txs.is_allowlisted(&withdraw(other)));
+    }
+
+    /// Checks that no more transactions than the budget allows are sponsored,
+    /// and that the waived fees are accounted.
+    #[test]
+    fn budget() {
+        let sender = address(1);
+        let mut sponsored_txs = SponsoredTxs::new(options(vec![sender], vec![], 2));
+        let required_fee = BigUint::from(100u32);
+
+        let sponsorship = sponsored_txs
+            .sponsor(&transfer(sender, 0, 0), &required_fee)
+            .expect("Tx within the budget must be sponsored");
+        sponsored_txs.confirm(sponsorship);
+        let sponsorship = sponsored_txs
+            .sponsor(&transfer(sender, 1, 40), &required_fee)
+            .expect("Tx within the budget must be sponsored");
+        sponsored_txs.confirm(sponsorship);
+        assert!(sponsored_txs
+            .sponsor(&transfer(sender, 0, 0), &required_fee)
+            .is_err());
+
+        let (count, waived_fees) = sponsored_txs.period_usage();
+        assert_eq!(count, 2);
+        assert_eq!(waived_fees[&0], BigUint::from(100u32));
+        assert_eq!(waived_fees[&1], BigUint::from(60u32));
+
+        // Budget reserved for the rejected transaction is refunded.
+        let mut sponsored_txs = SponsoredTxs::new(options(vec![sender], vec![], 1));
+        let sponsorship = sponsored_txs
+            .sponsor(&transfer(sender, 0, 0), &required_fee)
+            .expect("Tx within the budget must be sponsored");
+        assert!(sponsored_txs
+            .sponsor(&transfer(sender, 0, 0), &required_fee)
+            .is_err());
+        sponsored_txs.refund(sponsorship);
+        let (count, waived_fees) = sponsored_txs.period_usage();
+        assert_eq!(count, 0);
+        assert_eq!(waived_fees[&0], BigUint::zero());
+        assert!(sponsored_txs
+            .sponsor(&transfer(sender, 0, 0), &required_fee)
+            .is_ok());
+
+        // Transactions not in the allowlist are never sponsored.
+        let mut sponsored_txs = SponsoredTxs::new(options(vec![sender], vec![], 2));
+        let other = transfer(address(2), 0, 0);
+        assert!(sponsored_txs.sponsor(&other, &required_fee).is_err());
+    }
+
+    /// Checks that the senders not in the allowlist get a limited amount of the transactions
+    /// sponsored by type, while the allowlisted senders are limited by the budget only.
+    #[test]
+    fn per_sender_limit() {
+        let (allowlisted, other) = (address(1), address(2));
+        let mut sponsored_txs = SponsoredTxs::new(options(vec![allowlisted], vec!["Transfer"], 10));
+        let required_fee = BigUint::from(100u32);
+
+        for _ in 0..3 {
+            let sponsorship = sponsored_txs
+                .sponsor(&transfer(allowlisted, 0, 0), &required_fee)
+                .expect("Tx of the allowlisted sender must be sponsored");
+            sponsored_txs.confirm(sponsorship);
+        }
+
+        let sponsorship = sponsored_txs
+            .sponsor(&transfer(other, 0, 0), &required_fee)
+            .expect("Tx within the sender limit must be sponsored");
+        sponsored_txs.confirm(sponsorship);
+        let sponsorship = sponsored_txs
+            .sponsor(&transfer(other, 0, 0), &required_fee)
+            .expect("Tx within the sender limit must be sponsored");
+        assert!(sponsored_txs
+            .sponsor(&transfer(other, 0, 0), &required_fee)
+            .is_err());
+        // Limit reserved for the rejected transaction is refunded.
+        sponsored_txs.refund(sponsorship);
+        assert!(sponsored_txs
+            .sponsor(&transfer(other, 0, 0), &required_fee)
+            .is_ok());
+
+        // Other senders are not affected.
+        assert!(sponsored_txs
+            .sponsor(&transfer(address(3), 0, 0), &required_fee)
+            .is_ok());
+    }
+}
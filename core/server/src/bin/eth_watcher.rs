@@ -1,5 +1,6 @@
 use futures::{channel::mpsc, SinkExt};
 use log::*;
+use models::config_options::{DEFAULT_PRIORITY_OP_DEADLINE_MARGIN, DEFAULT_PRIORITY_OP_SLA};
 use server::eth_watch::{EthWatch, EthWatchRequest};
 use std::time::Duration;
 use tokio::{runtime::Runtime, time};
@@ -14,6 +15,14 @@ fn main() {
         [2..]
         .parse()
         .expect("Failed to parse CONTRACT_ADDR");
+    let governance_address = std::env::var("GOVERNANCE_ADDR")
+        .expect("GOVERNANCE_ADDR env var not found")[2..]
+        .parse()
+        .expect("Failed to parse GOVERNANCE_ADDR");
+    let upgrade_gatekeeper_address = std::env::var("UPGRADE_GATEKEEPER_ADDR")
+        .expect("UPGRADE_GATEKEEPER_ADDR env var not found")[2..]
+        .parse()
+        .expect("Failed to parse UPGRADE_GATEKEEPER_ADDR");
     let (web3_event_loop_handle, transport) = web3::transports::Http::new(&web3_url).unwrap();
     let web3 = web3::Web3::new(transport);
 
@@ -23,8 +32,15 @@ fn main() {
         web3,
         web3_event_loop_handle,
         contract_address,
+        governance_address,
+        upgrade_gatekeeper_address,
         0,
         eth_req_receiver,
+        None,
+        None,
+        None,
+        DEFAULT_PRIORITY_OP_DEADLINE_MARGIN,
+        Duration::from_secs(DEFAULT_PRIORITY_OP_SLA),
     );
 
     main_runtime.spawn(watcher.run());
@@ -0,0 +1,18 @@
+//! Serves the exit proofs for the exodus mode, see `server::exit_proof_server`.
+//! Correct verified state should be present in the db
+//! (could be restored using `data-restore` module).
+
+use server::exit_proof_server::exit_proof_server_bind_address;
+use storage::ConnectionPool;
+
+fn main() {
+    env_logger::init();
+
+    let bind_address = exit_proof_server_bind_address();
+    let connection_pool = ConnectionPool::new(Some(2));
+
+    let runtime = actix_rt::System::new("exit-proof-server");
+    server::exit_proof_server::start_exit_proof_server(connection_pool, bind_address);
+    log::info!("Exit proof server is listening on {}", bind_address);
+    runtime.run().expect("exit proof server failed");
+}
@@ -3,22 +3,11 @@
 
 use clap::{App, Arg};
 use log::info;
-use models::node::{AccountId, Address, TokenId, TokenLike};
-use models::prover_utils::EncodedProofPlonk;
-use num::BigUint;
-use serde::Serialize;
+use models::node::{AccountId, TokenLike};
+use server::exit_proof_server::generate_exit_proof;
 use std::time::Instant;
 use storage::ConnectionPool;
 
-#[derive(Serialize, Debug)]
-struct ExitProofData {
-    token_id: TokenId,
-    account_id: AccountId,
-    account_address: Address,
-    amount: BigUint,
-    proof: EncodedProofPlonk,
-}
-
 fn main() {
     env_logger::init();
 
@@ -64,33 +53,16 @@ fn main() {
         .expect("Db access fail")
         .expect("Token not found")
         .id;
-    let address = storage
-        .chain()
-        .account_schema()
-        .last_verified_state_for_account(account_id)
-        .expect("DB access fail")
-        .expect("Account not found in the db")
-        .address;
-    let accounts = storage
+    let (verified_block, accounts) = storage
         .chain()
         .state_schema()
         .load_verified_state()
-        .expect("Failed to load verified state")
-        .1;
+        .expect("Failed to load verified state");
 
     info!("Resotred state from db: {} s", timer.elapsed().as_secs());
 
-    let (proof, amount) =
-        prover::exit_proof::create_exit_proof(accounts, account_id, address, token_id)
-            .expect("Failed to generate exit proof");
-
-    let proof_data = ExitProofData {
-        token_id,
-        account_id,
-        account_address: address,
-        amount,
-        proof,
-    };
+    let proof_data = generate_exit_proof(verified_block, accounts, account_id, token_id)
+        .expect("Failed to generate exit proof");
 
     println!(
         "{}",
@@ -2,21 +2,23 @@
 //! and sends them to `StateKeeper`
 //!
 //! It does it in small batches, called here `miniblocks`, which are smaller that full blocks.
+//! Miniblocks are proposed every `MINIBLOCK_INTERVAL_MS`.
 //!
 //! Right now logic of this actor is simple, but in future consensus will replace it using the same API.
 
+// Built-in deps
+use std::time::Duration;
 // External deps
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt,
 };
 use tokio::{runtime::Runtime, task::JoinHandle, time};
-// Workspace deps
-use models::node::config::TX_MINIBATCH_CREATE_TIME;
 // Local deps
 use crate::{
     mempool::{GetBlockRequest, MempoolRequest, ProposedBlock},
     state_keeper::StateKeeperRequest,
+    utils::current_zksync_info::CurrentZksyncInfo,
 };
 
 fn create_mempool_req(
@@ -66,10 +68,12 @@ impl BlockProposer {
 pub fn run_block_proposer_task(
     mempool_requests: mpsc::Sender<MempoolRequest>,
     mut statekeeper_requests: mpsc::Sender<StateKeeperRequest>,
+    current_zksync_info: CurrentZksyncInfo,
+    miniblock_interval: Duration,
     runtime: &Runtime,
 ) -> JoinHandle<()> {
     runtime.spawn(async move {
-        let mut timer = time::interval(TX_MINIBATCH_CREATE_TIME);
+        let mut timer = time::interval(miniblock_interval);
 
         let last_unprocessed_prior_op_chan = oneshot::channel();
         statekeeper_requests
@@ -92,6 +96,12 @@ pub fn run_block_proposer_task(
         loop {
             timer.tick().await;
 
+            // No new blocks are proposed while `eth_sender` can't send them to Ethereum,
+            // the contracts may be upgraded to the unknown version, or the server is
+            // shutting down.
+            if current_zksync_info.is_block_production_halted() {
+                continue;
+            }
             block_proposer.commit_new_tx_mini_batch().await;
         }
     })
@@ -7,7 +7,10 @@ use tokio::{runtime::Runtime, task::JoinHandle, time};
 // Workspace uses
 use crate::eth_sender::ETHSenderRequest;
 use crate::mempool::MempoolRequest;
-use models::{node::block::PendingBlock, Action, BlockCommitRequest, CommitRequest, Operation};
+use models::{
+    node::{block::PendingBlock, AccountMap, BlockNumber},
+    Action, BlockCommitRequest, CommitRequest, Operation,
+};
 use storage::ConnectionPool;
 
 const PROOF_POLL_INTERVAL: Duration = Duration::from_secs(1);
@@ -38,6 +41,9 @@ async fn handle_new_commit_task(
 
                 notifier.send(()).expect("state keeper receiver dropped");
             }
+            CommitRequest::StateCheckpoint(block_number, accounts) => {
+                save_state_checkpoint(block_number, &accounts, &pool);
+            }
         }
     }
 }
@@ -56,6 +62,23 @@ fn save_pending_block(pending_block: PendingBlock, pool: &ConnectionPool) {
         .expect("committer must commit the pending block into db");
 }
 
+/// Stores the state checkpoint. Requests are handled in order, so the checkpoint is
+/// stored after the block it corresponds to. Checkpoints only speed up the state
+/// restoring, thus failure to store one is not critical.
+fn save_state_checkpoint(block_number: BlockNumber, accounts: &AccountMap, pool: &ConnectionPool) {
+    let storage = pool
+        .access_storage()
+        .expect("db connection fail for committer");
+
+    log::trace!("persist state checkpoint for block #{}", block_number);
+
+    storage
+        .chain()
+        .state_schema()
+        .store_checkpoint(block_number, accounts)
+        .unwrap_or_else(|e| warn!("Failed to store the state checkpoint: {}", e));
+}
+
 async fn commit_block(
     request: BlockCommitRequest,
     pool: &ConnectionPool,
@@ -8,10 +8,11 @@ use std::collections::VecDeque;
 use std::str::FromStr;
 // External uses
 use num::BigUint;
-use web3::types::{H256, U256};
+use web3::types::{Address, H256, U256};
 // Workspace uses
 use models::{
     ethereum::{ETHOperation, EthOpId, InsertedOperationResponse, OperationType},
+    node::BlockNumber,
     Operation,
 };
 use storage::ConnectionPool;
@@ -35,8 +36,17 @@ pub(super) trait DatabaseAccess {
         raw_tx: Vec<u8>,
     ) -> Result<InsertedOperationResponse, failure::Error>;
 
-    /// Adds a tx hash entry associated with some Ethereum operation to the database.
-    fn add_hash_entry(&self, eth_op_id: i64, hash: &H256) -> Result<(), failure::Error>;
+    /// Adds a tx hash entry associated with some Ethereum operation to the database,
+    /// along with the signed transaction.
+    fn add_hash_entry(
+        &self,
+        eth_op_id: i64,
+        hash: &H256,
+        signed_raw_tx: &[u8],
+    ) -> Result<(), failure::Error>;
+
+    /// Loads the stored signed transaction with the provided hash, if any.
+    fn load_signed_tx(&self, hash: &H256) -> Result<Option<Vec<u8>>, failure::Error>;
 
     /// Adds a new tx info to the previously started Ethereum operation.
     fn update_eth_tx(
@@ -46,8 +56,37 @@ pub(super) trait DatabaseAccess {
         new_gas_value: U256,
     ) -> Result<(), failure::Error>;
 
+    /// Changes the nonce of the unconfirmed operation.
+    fn update_eth_tx_nonce(&self, eth_op_id: EthOpId, new_nonce: i64)
+        -> Result<(), failure::Error>;
+
+    /// Loads the nonce to be assigned to the next operation.
+    fn load_next_nonce(&self) -> Result<i64, failure::Error>;
+
+    /// Overrides the nonce to be assigned to the next operation.
+    fn update_next_nonce(&self, nonce: i64) -> Result<(), failure::Error>;
+
     /// Marks an operation as completed in the database.
-    fn confirm_operation(&self, hash: &H256) -> Result<(), failure::Error>;
+    /// If the amount of gas used by the transaction is known, it is stored as well.
+    fn confirm_operation(&self, hash: &H256, gas_used: Option<U256>) -> Result<(), failure::Error>;
+
+    /// Marks the previously confirmed operation as not completed in the database.
+    fn revert_confirmation(&self, eth_op_id: EthOpId) -> Result<(), failure::Error>;
+
+    /// Adds the withdrawals of the verified block to the pending withdrawals queue.
+    fn add_pending_withdrawals(
+        &self,
+        block_number: BlockNumber,
+        withdrawal_hashes: &[Vec<u8>],
+    ) -> Result<(), failure::Error>;
+
+    /// Marks up to `max_withdrawals` oldest pending withdrawals as completed by the
+    /// `completeWithdrawals` transaction. Returns the amount of completed withdrawals.
+    fn complete_withdrawals(
+        &self,
+        complete_tx_hash: &H256,
+        max_withdrawals: u64,
+    ) -> Result<usize, failure::Error>;
 
     /// Loads the stored Ethereum operations stats.
     fn load_stats(&self) -> Result<ETHStats, failure::Error>;
@@ -58,6 +97,12 @@ pub(super) trait DatabaseAccess {
     /// Updates the stored gas price limit.
     fn update_gas_price_limit(&self, value: U256) -> Result<(), failure::Error>;
 
+    /// Loads the address of the operator account used to send the transactions.
+    fn load_operator_address(&self) -> Result<Option<Address>, failure::Error>;
+
+    /// Updates the address of the operator account used to send the transactions.
+    fn update_operator_address(&self, address: &Address) -> Result<(), failure::Error>;
+
     /// Performs several database operations within one database transaction.
     fn transaction<F, T>(&self, f: F) -> Result<T, failure::Error>
     where
@@ -109,10 +154,22 @@ impl DatabaseAccess for Database {
         )?)
     }
 
-    fn add_hash_entry(&self, eth_op_id: i64, hash: &H256) -> Result<(), failure::Error> {
+    fn add_hash_entry(
+        &self,
+        eth_op_id: i64,
+        hash: &H256,
+        signed_raw_tx: &[u8],
+    ) -> Result<(), failure::Error> {
         let storage = self.db_pool.access_storage()?;
 
-        Ok(storage.ethereum_schema().add_hash_entry(eth_op_id, hash)?)
+        Ok(storage
+            .ethereum_schema()
+            .add_hash_entry(eth_op_id, hash, signed_raw_tx)?)
+    }
+
+    fn load_signed_tx(&self, hash: &H256) -> Result<Option<Vec<u8>>, failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage.ethereum_schema().load_signed_tx(hash)?)
     }
 
     fn update_eth_tx(
@@ -129,9 +186,65 @@ impl DatabaseAccess for Database {
         )?)
     }
 
-    fn confirm_operation(&self, hash: &H256) -> Result<(), failure::Error> {
+    fn update_eth_tx_nonce(
+        &self,
+        eth_op_id: EthOpId,
+        new_nonce: i64,
+    ) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage
+            .ethereum_schema()
+            .update_eth_tx_nonce(eth_op_id, new_nonce)?)
+    }
+
+    fn load_next_nonce(&self) -> Result<i64, failure::Error> {
         let storage = self.db_pool.access_storage()?;
-        Ok(storage.ethereum_schema().confirm_eth_tx(hash)?)
+        Ok(storage.ethereum_schema().load_next_nonce()?)
+    }
+
+    fn update_next_nonce(&self, nonce: i64) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage.ethereum_schema().update_next_nonce(nonce)?)
+    }
+
+    fn confirm_operation(&self, hash: &H256, gas_used: Option<U256>) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        storage.transaction(|| {
+            storage.ethereum_schema().confirm_eth_tx(hash)?;
+            if let Some(gas_used) = gas_used {
+                storage.ethereum_schema().report_gas_used(hash, gas_used)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn revert_confirmation(&self, eth_op_id: EthOpId) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage
+            .ethereum_schema()
+            .revert_eth_tx_confirmation(eth_op_id)?)
+    }
+
+    fn add_pending_withdrawals(
+        &self,
+        block_number: BlockNumber,
+        withdrawal_hashes: &[Vec<u8>],
+    ) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage
+            .ethereum_schema()
+            .add_pending_withdrawals(block_number, withdrawal_hashes)?)
+    }
+
+    fn complete_withdrawals(
+        &self,
+        complete_tx_hash: &H256,
+        max_withdrawals: u64,
+    ) -> Result<usize, failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage
+            .ethereum_schema()
+            .complete_withdrawals(complete_tx_hash, max_withdrawals)?)
     }
 
     fn load_stats(&self) -> Result<ETHStats, failure::Error> {
@@ -152,6 +265,17 @@ impl DatabaseAccess for Database {
         Ok(())
     }
 
+    fn load_operator_address(&self) -> Result<Option<Address>, failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        Ok(storage.ethereum_schema().load_operator_address()?)
+    }
+
+    fn update_operator_address(&self, address: &Address) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        storage.ethereum_schema().update_operator_address(address)?;
+        Ok(())
+    }
+
     fn transaction<F, T>(&self, f: F) -> Result<T, failure::Error>
     where
         F: FnOnce() -> Result<T, failure::Error>,
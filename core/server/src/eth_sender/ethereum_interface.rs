@@ -1,14 +1,19 @@
 // Built-in deps
+use std::sync::Arc;
 // External uses
-use failure::ensure;
+use failure::{ensure, format_err};
 use futures::{compat::Future01CompatExt, executor::block_on};
 use web3::contract::tokens::Tokenize;
 use web3::contract::Options;
-use web3::transports::{EventLoopHandle, Http};
-use web3::types::{TransactionReceipt, H256, U256};
+use web3::transports::EventLoopHandle;
+use web3::types::{Address, TransactionReceipt, H256, U256};
 // Workspace uses
 use super::ExecutedTxStatus;
-use eth_client::{ETHClient, SignedCallResult};
+use crate::utils::failover_transport::FailoverTransport;
+use eth_client::{
+    signer::{EthereumSigner, PrivateKeySigner, RemoteSigner},
+    ETHClient, SignedCallResult, TxPricing,
+};
 use models::abi::zksync_contract;
 use models::config_options::ConfigurationOptions;
 use std::time::Duration;
@@ -37,9 +42,43 @@ pub(super) trait EthereumInterface {
     /// Gets the actual block number.
     fn block_number(&self) -> Result<u64, failure::Error>;
 
+    /// Gets the chain id of the network the Ethereum node is connected to.
+    fn chain_id(&self) -> Result<u64, failure::Error>;
+
+    /// Returns the amount of the Ethereum node endpoint switches, so the state can be
+    /// reconciled with the new endpoint.
+    fn endpoint_switches(&self) -> usize;
+
     /// Gets the current gas price.
     fn gas_price(&self) -> Result<U256, failure::Error>;
 
+    /// Returns the addresses of all the configured operator accounts, the main one first.
+    fn operator_addresses(&self) -> Vec<Address>;
+
+    /// Returns the address of the operator account used to send the transactions.
+    fn operator_address(&self) -> Address;
+
+    /// Switches to the operator account with the provided address, so it's used
+    /// to sign the transactions and to obtain the nonces.
+    fn use_operator(&mut self, address: Address) -> Result<(), failure::Error>;
+
+    /// Gets the balance of the provided account.
+    fn balance(&self, address: Address) -> Result<U256, failure::Error>;
+
+    /// Gets the nonce of the operator account based on the latest mined block.
+    fn current_nonce(&self) -> Result<U256, failure::Error>;
+
+    /// Gets the nonce of the operator account including the transactions in the mempool.
+    fn pending_nonce(&self) -> Result<U256, failure::Error>;
+
+    /// Estimates the gas required to execute the contract call with the provided data
+    /// on the latest Ethereum state.
+    fn estimate_gas(&self, data: Vec<u8>) -> Result<U256, failure::Error>;
+
+    /// Executes the contract call with the provided data on the latest Ethereum state
+    /// without sending a transaction. Returns the revert reason if the call fails.
+    fn dry_run(&self, data: Vec<u8>) -> Result<Option<String>, failure::Error>;
+
     /// Sends a signed transaction to the Ethereum blockchain.
     fn send_tx(&self, signed_tx: &SignedCallResult) -> Result<(), failure::Error>;
 
@@ -56,37 +95,74 @@ pub(super) trait EthereumInterface {
     ) -> Result<SignedCallResult, failure::Error>;
 }
 
-/// Wrapper over `ETHClient` using `Http` transport.
-/// Supposed to be an actual Ethereum intermediator for the `ETHSender`.
+/// Wrapper over `ETHClient` using `Http` transport (switching between the configured
+/// endpoints upon failures). Supposed to be an actual Ethereum intermediator for the `ETHSender`.
 #[derive(Debug)]
 pub struct EthereumHttpClient {
-    eth_client: ETHClient<Http>,
+    /// Clients for every operator account, the main one first.
+    eth_clients: Vec<ETHClient<FailoverTransport>>,
+    transport: FailoverTransport,
+    /// Index of the client of the currently used operator account.
+    active_client: usize,
     // We have to prevent handle from drop, since it will cause event loop termination.
     _event_loop: EventLoopHandle,
 }
 
 impl EthereumHttpClient {
     pub fn new(options: &ConfigurationOptions) -> Result<Self, failure::Error> {
-        let (_event_loop, transport) = Http::new(&options.web3_url)?;
+        let (_event_loop, transport) = FailoverTransport::from_config(options)?;
 
-        let eth_client = ETHClient::new(
-            transport,
-            zksync_contract(),
+        let tx_pricing = if options.eip1559_enabled {
+            TxPricing::Eip1559 {
+                priority_fee_percentile: options.priority_fee_percentile,
+            }
+        } else {
+            TxPricing::Legacy
+        };
+
+        let main_account = (
             options.operator_commit_eth_addr,
-            options
-                .operator_private_key
-                .expect("Operator private key is required for eth_sender"),
-            options.contract_eth_addr,
-            options.chain_id,
-            options.gas_price_factor,
+            options.operator_private_key,
         );
+        let eth_clients = std::iter::once(main_account)
+            .chain(options.operator_reserve_accounts.iter().cloned())
+            .map(|(address, private_key)| {
+                let signer: Arc<dyn EthereumSigner> = match &options.operator_remote_signer_url {
+                    Some(url) => Arc::new(RemoteSigner::new(url.clone(), address)),
+                    None => Arc::new(PrivateKeySigner::new(
+                        private_key.expect("Operator private key is required for eth_sender"),
+                    )),
+                };
+                ETHClient::with_signer(
+                    transport.clone(),
+                    zksync_contract(),
+                    address,
+                    signer,
+                    options.contract_eth_addr,
+                    options.chain_id,
+                    options.gas_price_factor,
+                    tx_pricing,
+                )
+            })
+            .collect();
 
         Ok(Self {
-            eth_client,
+            eth_clients,
+            transport,
+            active_client: 0,
             _event_loop,
         })
     }
 
+    /// Returns the transport, so the endpoints health can be checked.
+    pub fn transport(&self) -> &FailoverTransport {
+        &self.transport
+    }
+
+    fn eth_client(&self) -> &ETHClient<FailoverTransport> {
+        &self.eth_clients[self.active_client]
+    }
+
     /// Sleep is required before each Ethereum query because infura blocks requests that are made too often
     fn sleep(&self) {
         std::thread::sleep(SLEEP_DURATION);
@@ -97,7 +173,7 @@ impl EthereumInterface for EthereumHttpClient {
     fn get_tx_status(&self, hash: &H256) -> Result<Option<ExecutedTxStatus>, failure::Error> {
         self.sleep();
         let receipt = block_on(
-            self.eth_client
+            self.eth_client()
                 .web3
                 .eth()
                 .transaction_receipt(*hash)
@@ -114,6 +190,7 @@ impl EthereumInterface for EthereumHttpClient {
                     .block_number()?
                     .saturating_sub(tx_block_number.as_u64());
                 let success = status.as_u64() == 1;
+                let gas_used = receipt.as_ref().and_then(|receipt| receipt.gas_used);
 
                 // Set the receipt only for failures.
                 let receipt = if success {
@@ -125,6 +202,7 @@ impl EthereumInterface for EthereumHttpClient {
                 Ok(Some(ExecutedTxStatus {
                     confirmations,
                     success,
+                    gas_used,
                     receipt,
                 }))
             }
@@ -134,12 +212,21 @@ impl EthereumInterface for EthereumHttpClient {
 
     fn block_number(&self) -> Result<u64, failure::Error> {
         self.sleep();
-        Ok(block_on(self.eth_client.web3.eth().block_number().compat()).map(|n| n.as_u64())?)
+        Ok(block_on(self.eth_client().web3.eth().block_number().compat()).map(|n| n.as_u64())?)
+    }
+
+    fn chain_id(&self) -> Result<u64, failure::Error> {
+        self.sleep();
+        Ok(block_on(self.eth_client().chain_id()).map(|id| id.as_u64())?)
+    }
+
+    fn endpoint_switches(&self) -> usize {
+        self.transport.switches()
     }
 
     fn send_tx(&self, signed_tx: &SignedCallResult) -> Result<(), failure::Error> {
         self.sleep();
-        let hash = block_on(self.eth_client.send_raw_tx(signed_tx.raw_tx.clone()))?;
+        let hash = block_on(self.eth_client().send_raw_tx(signed_tx.raw_tx.clone()))?;
         ensure!(
             hash == signed_tx.hash,
             "Hash from signer and Ethereum node mismatch"
@@ -149,11 +236,58 @@ impl EthereumInterface for EthereumHttpClient {
 
     fn gas_price(&self) -> Result<U256, failure::Error> {
         self.sleep();
-        block_on(self.eth_client.get_gas_price())
+        block_on(self.eth_client().get_gas_price())
+    }
+
+    fn operator_addresses(&self) -> Vec<Address> {
+        self.eth_clients
+            .iter()
+            .map(|client| client.sender_account)
+            .collect()
+    }
+
+    fn operator_address(&self) -> Address {
+        self.eth_client().sender_account
+    }
+
+    fn use_operator(&mut self, address: Address) -> Result<(), failure::Error> {
+        self.active_client = self
+            .eth_clients
+            .iter()
+            .position(|client| client.sender_account == address)
+            .ok_or_else(|| format_err!("Unknown operator account: {:#x}", address))?;
+        Ok(())
+    }
+
+    fn balance(&self, address: Address) -> Result<U256, failure::Error> {
+        self.sleep();
+        Ok(block_on(
+            self.eth_client().web3.eth().balance(address, None).compat(),
+        )?)
+    }
+
+    fn current_nonce(&self) -> Result<U256, failure::Error> {
+        self.sleep();
+        Ok(block_on(self.eth_client().current_nonce())?)
+    }
+
+    fn pending_nonce(&self) -> Result<U256, failure::Error> {
+        self.sleep();
+        Ok(block_on(self.eth_client().pending_nonce())?)
+    }
+
+    fn estimate_gas(&self, data: Vec<u8>) -> Result<U256, failure::Error> {
+        self.sleep();
+        Ok(block_on(self.eth_client().estimate_gas(data))?)
+    }
+
+    fn dry_run(&self, data: Vec<u8>) -> Result<Option<String>, failure::Error> {
+        self.sleep();
+        Ok(block_on(self.eth_client().dry_run(data))?)
     }
 
     fn encode_tx_data<P: Tokenize>(&self, func: &str, params: P) -> Vec<u8> {
-        self.eth_client.encode_tx_data(func, params)
+        self.eth_client().encode_tx_data(func, params)
     }
 
     fn sign_prepared_tx(
@@ -162,6 +296,6 @@ impl EthereumInterface for EthereumHttpClient {
         options: Options,
     ) -> Result<SignedCallResult, failure::Error> {
         self.sleep();
-        block_on(self.eth_client.sign_prepared_tx(data, options))
+        block_on(self.eth_client().sign_prepared_tx(data, options))
     }
 }
@@ -18,6 +18,9 @@ mod tests;
 /// transactions only), which guarantees that we will increase the
 /// gas price for transactions that were not mined by the network
 /// within a reasonable time.
+///
+/// Additionally, the upper limit never exceeds the maximum gas price configured
+/// by the operator, if any.
 #[derive(Debug)]
 pub(super) struct GasAdjuster<ETH: EthereumInterface, DB: DatabaseAccess> {
     /// Collected statistics about recently used gas prices.
@@ -26,6 +29,8 @@ pub(super) struct GasAdjuster<ETH: EthereumInterface, DB: DatabaseAccess> {
     last_price_renewal: Instant,
     /// Timestamp of the last sample added to the `statistics`.
     last_sample_added: Instant,
+    /// Maximum gas price configured by the operator.
+    max_gas_price: Option<U256>,
 
     _etherum_client: PhantomData<ETH>,
     _db: PhantomData<DB>,
@@ -40,12 +45,19 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> GasAdjuster<ETH, DB> {
             statistics: GasStatistics::new(gas_price_limit),
             last_price_renewal: Instant::now(),
             last_sample_added: Instant::now(),
+            max_gas_price: None,
 
             _etherum_client: PhantomData,
             _db: PhantomData,
         }
     }
 
+    /// Sets the maximum gas price the transactions are ever sent with.
+    pub fn with_max_gas_price(mut self, max_gas_price: Option<U256>) -> Self {
+        self.max_gas_price = max_gas_price;
+        self
+    }
+
     /// Calculates a new gas amount for the replacement of the stuck tx.
     /// Replacement price is usually suggested to be at least 10% higher, we make it 15% higher.
     pub fn get_gas_price(
@@ -128,18 +140,29 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> GasAdjuster<ETH, DB> {
 
     /// Returns current max gas price that can be used to send transactions.
     pub fn get_current_max_price(&self) -> U256 {
-        self.statistics.get_limit()
+        let limit = self.statistics.get_limit();
+        match self.max_gas_price {
+            Some(max_gas_price) => std::cmp::min(limit, max_gas_price),
+            None => limit,
+        }
     }
 
     /// Get estimate of the average gas prices used for past transactions based on the current gas_limit.
     pub fn get_average_gas_price(&self) -> U256 {
-        let scale_factor = parameters::limit_scale_factor();
-        let divider = U256::from((scale_factor * 100.0f64).round() as u64);
-        let multiplier = U256::from(100);
-        self.statistics.get_limit() * multiplier / divider
+        average_gas_price(self.statistics.get_limit())
     }
 }
 
+/// Estimates the average gas price used for past transactions given the gas price limit.
+/// Since the limit is calculated as the average price scaled by `limit_scale_factor`,
+/// the estimation reverts this scaling.
+pub(crate) fn average_gas_price(gas_price_limit: U256) -> U256 {
+    let scale_factor = parameters::limit_scale_factor();
+    let divider = U256::from((scale_factor * 100.0f64).round() as u64);
+    let multiplier = U256::from(100);
+    gas_price_limit * multiplier / divider
+}
+
 /// Helper structure responsible for collecting the data about recent transactions,
 /// calculating the average gas price, and providing the gas price limit.
 #[derive(Debug)]
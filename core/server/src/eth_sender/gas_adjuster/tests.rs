@@ -114,6 +114,25 @@ fn initial_upper_gas_limit() {
     assert_eq!(scaled_gas, PRICE_LIMIT.into());
 }
 
+/// Checks that the configured maximum gas price is never exceeded, even if the
+/// dynamic limit is higher.
+#[test]
+fn configured_max_gas_price() {
+    const PRICE_LIMIT: u64 = 1000;
+    const MAX_GAS_PRICE: u64 = 500;
+
+    let (mut ethereum, db) = eth_and_db_clients();
+    db.update_gas_price_limit(PRICE_LIMIT.into()).unwrap();
+
+    let mut gas_adjuster: GasAdjuster<MockEthereum, MockDatabase> =
+        GasAdjuster::new(&db).with_max_gas_price(Some(MAX_GAS_PRICE.into()));
+    assert_eq!(gas_adjuster.get_current_max_price(), MAX_GAS_PRICE.into());
+
+    ethereum.gas_price = PRICE_LIMIT.into();
+    let scaled_gas = gas_adjuster.get_gas_price(&ethereum, None).unwrap();
+    assert_eq!(scaled_gas, MAX_GAS_PRICE.into());
+}
+
 /// Checks the gas price limit scaling algorithm:
 /// We are successively keep requesting the gas price with the
 /// ethereum client suggesting the price far beyond the current limit
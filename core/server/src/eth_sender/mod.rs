@@ -4,8 +4,8 @@
 //! every transaction is executed successfully and confirmed.
 
 // Built-in deps
-use std::collections::VecDeque;
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 // External uses
 use futures::{
     channel::{mpsc, oneshot},
@@ -14,13 +14,13 @@ use futures::{
 use tokio::{runtime::Runtime, task::JoinHandle, time};
 use web3::{
     contract::Options,
-    types::{TransactionReceipt, H256, U256},
+    types::{Address, TransactionReceipt, H256, U256},
 };
 // Workspace uses
 use eth_client::SignedCallResult;
 use models::{
     config_options::{ConfigurationOptions, EthSenderOptions},
-    ethereum::{ETHOperation, OperationType},
+    ethereum::{ETHOperation, EthOpId, OperationType},
     node::config,
     Action, Operation,
 };
@@ -30,17 +30,28 @@ use self::{
     database::{Database, DatabaseAccess},
     ethereum_interface::{EthereumHttpClient, EthereumInterface},
     gas_adjuster::GasAdjuster,
+    rate_limiter::TxRateLimiter,
     transactions::*,
     tx_queue::{TxData, TxQueue, TxQueueBuilder},
 };
-use crate::{gas_counter::GasCounter, utils::current_zksync_info::CurrentZksyncInfo};
+use crate::{
+    gas_counter::GasCounter,
+    utils::{
+        backoff::{is_transient_error, ExponentialBackoff},
+        current_zksync_info::CurrentZksyncInfo,
+        metrics_registry::metrics_registry,
+    },
+};
 
 mod database;
 mod ethereum_interface;
 mod gas_adjuster;
+mod rate_limiter;
 mod transactions;
 mod tx_queue;
 
+pub(crate) use self::gas_adjuster::average_gas_price;
+
 #[cfg(test)]
 mod tests;
 
@@ -48,12 +59,19 @@ mod tests;
 pub enum ETHSenderRequest {
     SendOperation(Operation),
     GetAverageUsedGasPrice(oneshot::Sender<U256>),
+    /// Responds once the operations received before the request are processed by the next
+    /// iteration, i.e. sent to Ethereum unless the submission limits defer them.
+    /// Used on the server shutdown.
+    Flush(oneshot::Sender<()>),
 }
 
-/// Wait this amount of time if we hit rate limit on infura https://infura.io/docs/ethereum/json-rpc/ratelimits
-const RATE_LIMIT_BACKOFF_PERIOD: Duration = Duration::from_secs(30);
-/// Rate limit error will contain this response code
-const RATE_LIMIT_HTTP_CODE: &str = "429";
+/// Minimum delay before the next Ethereum node call once the node failed with the transient
+/// error (e.g. we hit rate limit on infura https://infura.io/docs/ethereum/json-rpc/ratelimits).
+const MIN_BACKOFF_DELAY: Duration = Duration::from_secs(1);
+/// Maximum delay before the next Ethereum node call once the node failed with the transient error.
+const MAX_BACKOFF_DELAY: Duration = Duration::from_secs(2 * 60);
+/// Interval between the checks of the operator account balance.
+const OPERATOR_BALANCE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
 /// `TxCheckMode` enum determines the policy on the obtaining the tx status.
 /// The latest sent transaction can be pending (we're still waiting for it),
@@ -97,6 +115,10 @@ enum TxCheckMode {
 /// keep the list of all sent transaction hashes for one particular operations, since we can't be
 /// sure which one will be committed; thus we have to track all of them.
 ///
+/// Gas price is increased up to the limit maintained by the `GasAdjuster`. Once the stuck
+/// transaction was sent with the maximum price, no more transactions are sent for it, and
+/// the operator is alerted on every `expected_wait_time_block` blocks until it's mined.
+///
 /// Note: make sure to save signed tx to db before sending it to ETH, this way we can be sure
 /// that state is always recoverable.
 ///
@@ -114,6 +136,74 @@ enum TxCheckMode {
 /// 2. Withdraw operations (only if both commit/verify for the same block operations were sent).
 /// 3. Commit operations.
 ///
+/// If `send_verify_with_commit` option is set, the verify operation of the block proven before
+/// its commit operation was sent is sent right after the commit one.
+///
+/// Blocks are committed without waiting for the proofs of the previous ones, so the proving
+/// doesn't slow down the commits. However, the amount of the committed blocks waiting for
+/// the verification is limited by the `max_unverified_blocks` option, since the contract
+/// allows to revert the committed blocks which are not verified in time.
+///
+/// # Chain reorganizations
+///
+/// Transaction is considered confirmed once it has `wait_confirmations` confirmations.
+/// After that the confirmed operation is watched for `reorg_watch_blocks` blocks: if its
+/// transaction disappears from the chain (or is re-executed with a failure) because of
+/// a reorg, the confirmation is reverted in the database and the operation is tracked as
+/// an ongoing one again. Watched operations are not persisted, so confirmations observed
+/// right before the restart are considered final.
+///
+/// # Operator accounts
+///
+/// Transactions are sent from a single operator account at a time, since the order of
+/// transactions sent from different accounts is not guaranteed. Once the balance of the
+/// account is below `min_operator_balance`, the operator is alerted, and `ETHSender` stops
+/// sending new transactions until the already sent ones are confirmed. After that, the next
+/// configured operator account with enough balance is used (accounts are tried in the
+/// round-robin order), and the nonce is recovered for it.
+///
+/// If the balance of the used account is below `halt_operator_balance`, the block production
+/// is halted (see `CurrentZksyncInfo`), so the block proposer stops proposing new blocks
+/// instead of creating the operations that can't be sent. Once the account is topped up or
+/// replaced, the block production is resumed.
+///
+/// # Submission budget
+///
+/// Operator may limit the amount of transactions sent within an hour (`max_txs_per_hour`)
+/// and the gas price the transactions are ever sent with (`max_gas_price`). New operations
+/// are kept in the queue while the budget is exceeded, and stuck transactions are not
+/// resent until the hourly limit allows it, or above the maximum gas price.
+///
+/// # Ethereum node endpoints
+///
+/// Ethereum node endpoint may be switched to the fallback one (see `FailoverTransport`), which
+/// may not know about the transactions sent via the previous endpoint. Once the switch is
+/// observed, the nonce is recovered the same way as on startup, so the transactions unknown
+/// to the new endpoint are sent again.
+///
+/// Once the Ethereum node call fails with a transient error (the node is unavailable or rate
+/// limits the requests), the rest of the iteration is skipped, and the next one is delayed
+/// with the jittered exponential backoff, so the failing node isn't hammered by the retries.
+///
+/// # Metrics
+///
+/// Along with the incidents (resent, reorged and failed transactions), `ETHSender` reports
+/// the amount of queued and in-flight operations, the confirmation latency and the gas price
+/// of the confirmed operations, and the gas spent, so a stuck pipeline can be alerted on.
+/// Confirmation latency is measured from the first send of the operation, and is not reported
+/// for the operations sent before the restart.
+///
+/// # Shutdown
+///
+/// On the server shutdown, the queue is flushed (see `ETHSenderRequest::Flush`), so the
+/// operations of the last sealed block are sent before the server stops. Operations left
+/// in the queue are restored from the database on the next start.
+///
+/// # Exodus mode
+///
+/// Once the contract enters the exodus mode (see `CurrentZksyncInfo::is_exodus_mode`), no new
+/// transactions are sent, since the contract rejects them. Already sent ones are still tracked.
+///
 /// # Failure policy
 ///
 /// By default, `ETHSender` expects no transactions to fail, and thus upon a failure it will
@@ -123,6 +213,8 @@ enum TxCheckMode {
 struct ETHSender<ETH: EthereumInterface, DB: DatabaseAccess> {
     /// Ongoing operations queue.
     ongoing_ops: VecDeque<ETHOperation>,
+    /// Recently confirmed operations checked for being reverted by a reorg.
+    watched_ops: VecDeque<WatchedOperation>,
     /// Connection to the database.
     db: DB,
     /// Ethereum intermediator.
@@ -139,6 +231,21 @@ struct ETHSender<ETH: EthereumInterface, DB: DatabaseAccess> {
     options: EthSenderOptions,
     /// struct to communicate current verified block number to api server
     current_zksync_info: CurrentZksyncInfo,
+    /// Flag showing that the operator account should be switched once the sent
+    /// transactions are confirmed.
+    operator_switch_pending: bool,
+    /// Timestamp of the last operator account balance check.
+    last_balance_check: Option<Instant>,
+    /// Limiter of the amount of transactions sent within an hour.
+    rate_limiter: TxRateLimiter,
+    /// Moments the ongoing operations were sent for the first time.
+    sent_at: HashMap<EthOpId, Instant>,
+    /// Amount of the Ethereum node endpoint switches the state is reconciled with.
+    endpoint_switches: usize,
+    /// Delay before the next Ethereum node call after the transient errors.
+    backoff: ExponentialBackoff,
+    /// Flush requests to respond to after the current iteration.
+    flush_requests: Vec<oneshot::Sender<()>>,
 }
 
 impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
@@ -161,13 +268,18 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             .with_commit_operations_count(stats.commit_ops)
             .with_verify_operations_count(stats.verify_ops)
             .with_withdraw_operations_count(stats.withdraw_ops)
+            .with_send_verify_with_commit(options.send_verify_with_commit)
+            .with_max_unverified_blocks(options.max_unverified_blocks as usize)
             .build();
 
-        let gas_adjuster = GasAdjuster::new(&db);
+        let gas_adjuster = GasAdjuster::new(&db).with_max_gas_price(options.max_gas_price);
+        let rate_limiter = TxRateLimiter::new(options.max_txs_per_hour);
+        let endpoint_switches = ethereum.endpoint_switches();
 
         let mut sender = Self {
             ethereum,
             ongoing_ops,
+            watched_ops: VecDeque::new(),
             db,
             rx_for_eth,
             op_notify,
@@ -175,8 +287,23 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             gas_adjuster,
             options,
             current_zksync_info,
+            operator_switch_pending: false,
+            last_balance_check: None,
+            rate_limiter,
+            sent_at: HashMap::new(),
+            endpoint_switches,
+            backoff: ExponentialBackoff::new(MIN_BACKOFF_DELAY, MAX_BACKOFF_DELAY),
+            flush_requests: Vec::new(),
         };
 
+        sender
+            .select_operator()
+            .expect("Can't select the operator account");
+
+        sender
+            .recover_nonce()
+            .expect("Can't recover the operator nonce");
+
         // Add all the unprocessed operations to the queue.
         for operation in unprocessed_ops {
             info!(
@@ -191,6 +318,129 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         sender
     }
 
+    /// Reconciles the stored nonces with the actual nonce of the operator account, since
+    /// they may diverge if the server was stopped in the middle of sending, or if the
+    /// account was used to send other transactions.
+    ///
+    /// Unconfirmed operations without mined transactions must have consecutive nonces
+    /// starting from the account nonce, otherwise they will never be mined. Operations
+    /// breaking the sequence (e.g. ones with the nonce consumed by another transaction,
+    /// or ones following a gap) get the new nonces preserving their order, and are resent
+    /// right away. Operations unknown to the Ethereum node (e.g. ones stored right before
+    /// the restart) get their stored signed transactions sent again as is, or are resent
+    /// right away as well if there is no stored signed transaction.
+    fn recover_nonce(&mut self) -> Result<(), failure::Error> {
+        let current_nonce = self.ethereum.current_nonce()?;
+        let pending_nonce = self.ethereum.pending_nonce()?;
+        let current_block = self.ethereum.block_number()?;
+
+        let mut next_nonce = current_nonce;
+        for op in self.ongoing_ops.iter_mut() {
+            if Self::is_mined(&self.ethereum, op)? {
+                // Confirmation will be handled by the usual workflow.
+                next_nonce = std::cmp::max(next_nonce, op.nonce + U256::from(1));
+                continue;
+            }
+
+            let nonce_changed = op.nonce != next_nonce;
+            if nonce_changed {
+                warn!(
+                    "Nonce {} of the ETH Operation <id: {}> can't be used, replacing it with {}",
+                    op.nonce, op.id, next_nonce
+                );
+                op.nonce = next_nonce;
+                self.db
+                    .update_eth_tx_nonce(op.id, next_nonce.as_u64() as i64)?;
+            }
+            let unknown_to_node = !nonce_changed && op.nonce >= pending_nonce;
+            if nonce_changed
+                || (unknown_to_node && !Self::resend_signed_tx(&self.ethereum, &self.db, op)?)
+            {
+                // Make the operation stuck, so its transaction is sent on the next iteration.
+                op.last_deadline_block = current_block;
+                self.db
+                    .update_eth_tx(op.id, current_block as i64, op.last_used_gas_price)?;
+            }
+            next_nonce += U256::from(1);
+        }
+
+        // Transactions in the mempool not related to the operations can't be replaced,
+        // so the new operations should be sent after them.
+        let next_nonce = std::cmp::max(next_nonce, pending_nonce).as_u64() as i64;
+        let stored_next_nonce = self.db.load_next_nonce()?;
+        if stored_next_nonce != next_nonce {
+            warn!(
+                "Stored operator nonce {} doesn't match the Ethereum state, replacing it with {}",
+                stored_next_nonce, next_nonce
+            );
+            self.db.update_next_nonce(next_nonce)?;
+        }
+
+        Ok(())
+    }
+
+    /// Recovers the nonce once the Ethereum node endpoint is switched, since the new endpoint
+    /// may not know about the transactions sent via the previous one.
+    fn keep_endpoint_reconciled(&mut self) {
+        let endpoint_switches = self.ethereum.endpoint_switches();
+        if endpoint_switches == self.endpoint_switches {
+            return;
+        }
+
+        info!("Ethereum node endpoint was switched, recovering the operator nonce");
+        match self.recover_nonce() {
+            Ok(()) => self.endpoint_switches = endpoint_switches,
+            Err(e) => warn!(
+                "Failed to reconcile with the new Ethereum node endpoint: {}",
+                e
+            ),
+        }
+    }
+
+    /// Checks whether any of the operation transactions is included into a block.
+    fn is_mined(ethereum: &ETH, op: &ETHOperation) -> Result<bool, failure::Error> {
+        for tx_hash in &op.used_tx_hashes {
+            if ethereum.get_tx_status(tx_hash)?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Sends the latest signed transaction of the operation once again, e.g. if it was lost by
+    /// the Ethereum node while the server was down. Returns `false` if the transaction can't
+    /// be resent, so the new one should be sent instead.
+    fn resend_signed_tx(
+        ethereum: &ETH,
+        db: &DB,
+        op: &ETHOperation,
+    ) -> Result<bool, failure::Error> {
+        let hash = *op
+            .used_tx_hashes
+            .last()
+            .expect("No transactions for the operation");
+        let raw_tx = match db.load_signed_tx(&hash)? {
+            Some(raw_tx) => raw_tx,
+            None => return Ok(false),
+        };
+
+        info!(
+            "Resending the stored tx of ETH Operation <id: {}>: {:#x}",
+            op.id, hash
+        );
+        let signed_tx = SignedCallResult {
+            raw_tx,
+            gas_price: op.last_used_gas_price,
+            nonce: op.nonce,
+            hash,
+        };
+        if let Err(e) = ethereum.send_tx(&signed_tx) {
+            warn!("Failed to resend the stored tx {:#x}: {}", hash, e);
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
     /// Main routine of `ETHSender`.
     pub async fn run(mut self) {
         loop {
@@ -203,6 +453,11 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
                 self.proceed_next_operations().await;
                 // Update the gas adjuster to maintain the up-to-date max gas price limit.
                 self.gas_adjuster.keep_updated(&self.ethereum, &self.db);
+                self.keep_operator_balance_checked();
+            }
+
+            for response in self.flush_requests.drain(..) {
+                response.send(()).unwrap_or_default();
             }
         }
     }
@@ -224,6 +479,9 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
                 ETHSenderRequest::GetAverageUsedGasPrice(response_sender) => response_sender
                     .send(self.gas_adjuster.get_average_gas_price())
                     .unwrap_or_default(),
+                ETHSenderRequest::Flush(response_sender) => {
+                    self.flush_requests.push(response_sender);
+                }
             }
         }
     }
@@ -236,8 +494,19 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
     async fn proceed_next_operations(&mut self) {
         // Queue for storing all the operations that were not finished at this iteration.
         let mut new_ongoing_ops = VecDeque::new();
+        // Once the Ethereum node fails with the transient error, it's not called until
+        // the next iteration.
+        let mut node_failed = false;
 
-        while let Some(tx) = self.tx_queue.pop_front() {
+        self.keep_endpoint_reconciled();
+
+        if self.operator_switch_pending && self.ongoing_ops.is_empty() {
+            self.switch_operator().unwrap_or_else(|e| {
+                warn!("Failed to switch the operator account: {}", e);
+            });
+        }
+
+        while let Some(tx) = self.pop_tx_to_send() {
             if let Err(e) = self.initialize_operation(tx.clone()) {
                 warn!(
                     "[{}:{}:{}] Error while trying to complete uncommitted op: {}",
@@ -246,17 +515,20 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
                     column!(),
                     e
                 );
-                if e.to_string().contains(RATE_LIMIT_HTTP_CODE) {
-                    warn!(
-                        "Received rate limit response, waiting for {}s",
-                        RATE_LIMIT_BACKOFF_PERIOD.as_secs()
-                    );
-                    time::delay_for(RATE_LIMIT_BACKOFF_PERIOD).await;
-                }
+                node_failed = self.back_off_on_transient_error(&e).await;
 
                 // Return the unperformed operation to the queue, since failing the
                 // operation initialization means that it was not stored in the database.
+                // It will be retried on the next iteration.
                 self.tx_queue.return_popped(tx);
+                break;
+            }
+        }
+
+        if !node_failed {
+            if let Err(e) = self.check_reorgs() {
+                warn!("Failed to check the confirmed operations for reorgs: {}", e);
+                node_failed = self.back_off_on_transient_error(&e).await;
             }
         }
 
@@ -266,24 +538,22 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             // network issue which won't appear the next time, so we report the situation to the
             // log and consider the operation pending (meaning that we won't process it on this
             // step, but will try to do so on the next one).
-            let commitment = match self.perform_commitment_step(&mut current_op) {
-                Ok(commitment) => commitment,
-                Err(e) => {
-                    warn!(
-                        "[{}:{}:{}] Error while trying to complete uncommitted op: {}",
-                        file!(),
-                        line!(),
-                        column!(),
-                        e
-                    );
-                    if e.to_string().contains(RATE_LIMIT_HTTP_CODE) {
+            let commitment = if node_failed {
+                OperationCommitment::Pending
+            } else {
+                match self.perform_commitment_step(&mut current_op) {
+                    Ok(commitment) => commitment,
+                    Err(e) => {
                         warn!(
-                            "Received rate limit response, waiting for {}s",
-                            RATE_LIMIT_BACKOFF_PERIOD.as_secs()
+                            "[{}:{}:{}] Error while trying to complete uncommitted op: {}",
+                            file!(),
+                            line!(),
+                            column!(),
+                            e
                         );
-                        time::delay_for(RATE_LIMIT_BACKOFF_PERIOD).await;
+                        node_failed = self.back_off_on_transient_error(&e).await;
+                        OperationCommitment::Pending
                     }
-                    OperationCommitment::Pending
                 }
             };
 
@@ -325,6 +595,239 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
 
         // Store the ongoing operations for the next round.
         self.ongoing_ops = new_ongoing_ops;
+
+        if !node_failed {
+            self.backoff.reset();
+        }
+        self.report_queue_metrics();
+    }
+
+    /// Waits with the exponential backoff if the error is a transient error of the Ethereum
+    /// node, so the failing node isn't called again right away. Returns whether it was.
+    async fn back_off_on_transient_error(&mut self, error: &failure::Error) -> bool {
+        if !is_transient_error(error) {
+            return false;
+        }
+
+        let delay = self.backoff.next_delay();
+        warn!(
+            "Ethereum node is not available, waiting for {}ms before the next call",
+            delay.as_millis()
+        );
+        time::delay_for(delay).await;
+        true
+    }
+
+    /// Reports the amount of the operations waiting to be sent and the sent ones
+    /// not confirmed yet.
+    fn report_queue_metrics(&self) {
+        let registry = metrics_registry();
+        registry.set_gauge(
+            "eth_sender_queued_ops",
+            "Amount of the operations waiting in the queue to be sent",
+            self.tx_queue.len() as f64,
+        );
+        registry.set_gauge(
+            "eth_sender_in_flight_ops",
+            "Amount of the sent operations which are not confirmed yet",
+            self.ongoing_ops.len() as f64,
+        );
+        registry.set_gauge(
+            "eth_sender_unverified_blocks",
+            "Amount of the committed blocks which verification is not sent yet",
+            self.tx_queue.unverified_blocks() as f64,
+        );
+    }
+
+    /// Reports the confirmation latency, the gas price and the gas spent
+    /// for the confirmed operation.
+    fn report_confirmation_metrics(&mut self, op: &ETHOperation, gas_used: Option<U256>) {
+        let registry = metrics_registry();
+        if let Some(sent_at) = self.sent_at.remove(&op.id) {
+            registry.set_gauge(
+                "eth_sender_confirmation_latency_seconds",
+                "Time passed between the first send and the confirmation of the last operation",
+                sent_at.elapsed().as_secs_f64(),
+            );
+        }
+        registry.set_gauge(
+            "eth_sender_gas_price_gwei",
+            "Gas price of the last transaction sent for the last confirmed operation",
+            (op.last_used_gas_price / U256::exp10(9)).low_u64() as f64,
+        );
+        if let Some(gas_used) = gas_used {
+            registry.increment_counter(
+                "eth_sender_gas_used",
+                "Total amount of gas used by the confirmed transactions",
+                gas_used.low_u64() as f64,
+            );
+            registry.increment_counter(
+                "eth_sender_fee_spent_gwei",
+                "Total fee (in gwei) paid for the confirmed transactions",
+                (gas_used * op.last_used_gas_price / U256::exp10(9)).low_u64() as f64,
+            );
+        }
+    }
+
+    /// Pops the next transaction to send, unless the operator account is going to be
+    /// switched: new transactions must not be mined before the already sent ones.
+    fn pop_tx_to_send(&mut self) -> Option<TxData> {
+        // Contract rejects the blocks in the exodus mode, so no transactions are sent anymore.
+        if self.operator_switch_pending || self.current_zksync_info.is_exodus_mode() {
+            return None;
+        }
+
+        let tx = self.tx_queue.pop_front()?;
+        if !self.submission_budget_available() {
+            self.tx_queue.return_popped(tx);
+            return None;
+        }
+        Some(tx)
+    }
+
+    /// Checks whether one more new transaction can be sent within the L1 submission budget:
+    /// submissions are deferred once the hourly transactions limit is reached, or while
+    /// the network gas price exceeds the maximum one.
+    fn submission_budget_available(&mut self) -> bool {
+        if !self.rate_limiter.can_send(Instant::now()) {
+            debug!("Hourly transactions limit is reached, new transactions are deferred");
+            return false;
+        }
+
+        let max_gas_price = match self.options.max_gas_price {
+            Some(max_gas_price) => max_gas_price,
+            None => return true,
+        };
+        match self.ethereum.gas_price() {
+            Ok(network_price) if network_price > max_gas_price => {
+                warn!(
+                    "Network gas price {} exceeds the maximum one {}, deferring new transactions",
+                    network_price, max_gas_price
+                );
+                false
+            }
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Failed to get the network gas price: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Selects the operator account to send the transactions: the one used before the
+    /// restart if it's still configured, and the main one otherwise.
+    fn select_operator(&mut self) -> Result<(), failure::Error> {
+        let operators = self.ethereum.operator_addresses();
+        let operator = match self.db.load_operator_address()? {
+            Some(address) if operators.contains(&address) => address,
+            Some(address) => {
+                warn!(
+                    "Operator account {:#x} is not configured anymore, using the main one",
+                    address
+                );
+                operators[0]
+            }
+            None => operators[0],
+        };
+
+        self.use_operator(operator)
+    }
+
+    fn use_operator(&mut self, address: Address) -> Result<(), failure::Error> {
+        self.ethereum.use_operator(address)?;
+        self.db.update_operator_address(&address)?;
+        info!("Using the operator account {:#x}", address);
+        Ok(())
+    }
+
+    /// Checks the operator account balance if the check interval has passed.
+    fn keep_operator_balance_checked(&mut self) {
+        let check_required = self
+            .last_balance_check
+            .map(|last_check| last_check.elapsed() >= OPERATOR_BALANCE_CHECK_INTERVAL)
+            .unwrap_or(true);
+        if !check_required {
+            return;
+        }
+
+        match self.check_operator_balance() {
+            Ok(()) => self.last_balance_check = Some(Instant::now()),
+            Err(e) => warn!("Failed to check the operator account balance: {}", e),
+        }
+    }
+
+    /// Reports the operator account balance, schedules the switch to the next
+    /// operator account if the balance is too low, and halts the block production
+    /// if the balance is not enough to keep sending the transactions.
+    fn check_operator_balance(&mut self) -> Result<(), failure::Error> {
+        let operator = self.ethereum.operator_address();
+        let balance = self.ethereum.balance(operator)?;
+        metrics_registry().set_gauge(
+            "eth_sender_operator_balance_gwei",
+            "Balance of the operator account used to send the transactions",
+            (balance / U256::exp10(9)).low_u64() as f64,
+        );
+        self.current_zksync_info
+            .set_operator_balance(operator, balance);
+
+        if balance < self.options.min_operator_balance && !self.operator_switch_pending {
+            error!(
+                "Operator account {:#x} balance is too low: {} wei, switching to the next one",
+                operator, balance
+            );
+            self.operator_switch_pending = true;
+        }
+
+        let halt_required = balance < self.options.halt_operator_balance;
+        if halt_required != self.current_zksync_info.is_operator_balance_low() {
+            if halt_required {
+                error!(
+                    "Operator account {:#x} balance {} wei is below the halt threshold, \
+                     block production is stopped",
+                    operator, balance
+                );
+            } else {
+                info!(
+                    "Operator account {:#x} balance is {} wei, block production is resumed",
+                    operator, balance
+                );
+            }
+            self.current_zksync_info
+                .set_operator_balance_low(halt_required);
+        }
+
+        Ok(())
+    }
+
+    /// Switches to the next operator account with enough balance. If all the accounts
+    /// are drained, the current one keeps being used.
+    fn switch_operator(&mut self) -> Result<(), failure::Error> {
+        let operators = self.ethereum.operator_addresses();
+        let current = self.ethereum.operator_address();
+        let current_idx = operators
+            .iter()
+            .position(|address| *address == current)
+            .unwrap_or_default();
+
+        for offset in 1..operators.len() {
+            let candidate = operators[(current_idx + offset) % operators.len()];
+            if self.ethereum.balance(candidate)? >= self.options.min_operator_balance {
+                self.use_operator(candidate)?;
+                self.recover_nonce()?;
+                self.operator_switch_pending = false;
+                // Balance of the new account is checked right away, so the block
+                // production halted because of the previous one is resumed.
+                self.last_balance_check = None;
+                return Ok(());
+            }
+        }
+
+        error!(
+            "All the operator accounts are drained, keep using {:#x}",
+            current
+        );
+        self.operator_switch_pending = false;
+        Ok(())
     }
 
     /// Stores the new operation in the database and sends the corresponding transaction.
@@ -333,6 +836,24 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         let deadline_block = self.get_deadline_block(current_block);
         let gas_price = self.gas_adjuster.get_gas_price(&self.ethereum, None)?;
 
+        // Check that the transaction won't fail before storing and sending it. Transactions
+        // following the pending ones can't be executed on the latest state, so the check is
+        // performed only if there are no other ongoing operations.
+        if self.ongoing_ops.is_empty() {
+            if let Some(reason) = self.ethereum.dry_run(tx.raw.clone())? {
+                metrics_registry().increment_counter(
+                    "eth_sender_dry_run_failures",
+                    "Amount of the operations which dry run before sending has failed",
+                    1.0,
+                );
+                error!(
+                    "Dry run of the {:?} operation has failed, the transaction is not sent: {}",
+                    tx.op_type, reason
+                );
+                failure::bail!("Dry run has failed: {}", reason);
+            }
+        }
+
         let (new_op, signed_tx) = self.db.transaction(|| {
             // First, we should store the operation in the database and obtain the assigned
             // operation ID and nonce. Without them we won't be able to sign the transaction.
@@ -358,11 +879,16 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             };
 
             // Sign the transaction.
-            let signed_tx = Self::sign_new_tx(&self.ethereum, &new_op)?;
+            let signed_tx = Self::sign_new_tx(
+                &self.ethereum,
+                &new_op,
+                self.options.gas_limit_safety_margin,
+            )?;
 
             // With signed tx, update the hash in the operation entry and in the db.
             new_op.used_tx_hashes.push(signed_tx.hash);
-            self.db.add_hash_entry(new_op.id, &signed_tx.hash)?;
+            self.db
+                .add_hash_entry(new_op.id, &signed_tx.hash, &signed_tx.raw_tx)?;
 
             Ok((new_op, signed_tx))
         })?;
@@ -370,12 +896,14 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         // We should store the operation as `ongoing` **before** sending it as well,
         // so if sending will fail, we won't forget about it.
         self.ongoing_ops.push_back(new_op.clone());
+        self.sent_at.insert(new_op.id, Instant::now());
 
         // After storing all the tx data in the database, we can finally send the tx.
         info!(
             "Sending new tx: [ETH Operation <id: {}, type: {:?}>. ETH tx: {}. ZKSync operation: {}]",
             new_op.id, new_op.op_type, self.eth_tx_description(&signed_tx), self.zksync_operation_description(&new_op),
         );
+        self.rate_limiter.report_sent(Instant::now());
         self.ethereum.send_tx(&signed_tx).unwrap_or_else(|e| {
             // Sending tx error is not critical: this will result in transaction being considered stuck,
             // and resent. We can't do anything about this failure either, since it's most probably is not
@@ -445,12 +973,19 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
                     // Transaction is pending, nothing to do yet.
                     return Ok(OperationCommitment::Pending);
                 }
-                TxCheckOutcome::Committed => {
+                TxCheckOutcome::Committed(gas_used) => {
                     info!(
                         "Confirmed: [ETH Operation <id: {}, type: {:?}>. Tx hash: <{:#x}>. ZKSync operation: {}]",
                         op.id, op.op_type, tx_hash, self.zksync_operation_description(op),
                     );
-                    self.db.confirm_operation(tx_hash)?;
+                    self.db.transaction(|| {
+                        self.db.confirm_operation(tx_hash, gas_used)?;
+                        Self::update_pending_withdrawals(&self.db, op, tx_hash)
+                    })?;
+                    op.confirmed = true;
+                    op.final_hash = Some(*tx_hash);
+                    self.report_confirmation_metrics(op, gas_used);
+                    self.watch_for_reorgs(op, current_block);
                     return Ok(OperationCommitment::Committed);
                 }
                 TxCheckOutcome::Stuck => {
@@ -474,6 +1009,24 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         // Reaching this point will mean that the latest transaction got stuck.
         // We should create another tx based on it, and send it.
         let deadline_block = self.get_deadline_block(current_block);
+        if op.last_used_gas_price >= self.gas_adjuster.get_current_max_price() {
+            // The gas price can't be increased anymore, and the replacement with the same
+            // price will be rejected by the Ethereum node. Keep waiting for the sent
+            // transactions, and remind the operator about it on every deadline.
+            self.report_gas_price_cap_reached(op);
+            op.last_deadline_block = deadline_block;
+            self.db
+                .update_eth_tx(op.id, deadline_block as i64, op.last_used_gas_price)?;
+            return Ok(OperationCommitment::Pending);
+        }
+        if !self.rate_limiter.can_send(Instant::now()) {
+            // Stuck transaction will be resent once the hourly limit allows it.
+            debug!(
+                "Hourly transactions limit is reached, deferring <ETH Operation id: {}> resend",
+                op.id
+            );
+            return Ok(OperationCommitment::Pending);
+        }
         // Raw tx contents are the same for every transaction, so we just
         // create a new one from the old one with updated parameters.
         let new_tx = self.create_supplement_tx(deadline_block, op)?;
@@ -481,7 +1034,8 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         self.db.transaction(|| {
             self.db
                 .update_eth_tx(op.id, deadline_block as i64, new_tx.gas_price)?;
-            self.db.add_hash_entry(op.id, &new_tx.hash)?;
+            self.db
+                .add_hash_entry(op.id, &new_tx.hash, &new_tx.raw_tx)?;
             Ok(())
         })?;
 
@@ -490,11 +1044,151 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             op.id,
             self.eth_tx_description(&new_tx),
         );
+        self.rate_limiter.report_sent(Instant::now());
         self.ethereum.send_tx(&new_tx)?;
+        metrics_registry().increment_counter(
+            "eth_sender_resubmitted_txs",
+            "Amount of transactions resent with the increased gas price",
+            1.0,
+        );
 
         Ok(OperationCommitment::Pending)
     }
 
+    /// Updates the pending withdrawals queue once the operation is confirmed: withdrawals
+    /// of the verified block are added to the queue, and the `completeWithdrawals` transaction
+    /// completes the oldest ones (the same way the contract processes its queue).
+    fn update_pending_withdrawals(
+        db: &DB,
+        op: &ETHOperation,
+        tx_hash: &H256,
+    ) -> Result<(), failure::Error> {
+        match op.op_type {
+            OperationType::Verify => {
+                let block = &op
+                    .op
+                    .as_ref()
+                    .expect("No zkSync operation for Verify")
+                    .block;
+                let withdrawal_hashes = block.get_withdrawal_hashes();
+                if !withdrawal_hashes.is_empty() {
+                    db.add_pending_withdrawals(block.block_number, &withdrawal_hashes)?;
+                }
+            }
+            OperationType::Withdraw => {
+                let completed = db
+                    .complete_withdrawals(tx_hash, config::MAX_WITHDRAWALS_TO_COMPLETE_IN_A_CALL)?;
+                info!(
+                    "{} withdrawals are completed by the tx <{:#x}>",
+                    completed, tx_hash
+                );
+            }
+            OperationType::Commit => {}
+        }
+
+        Ok(())
+    }
+
+    /// Starts watching the confirmed operation for being reverted by a reorg.
+    fn watch_for_reorgs(&mut self, op: &ETHOperation, current_block: u64) {
+        if self.options.reorg_watch_blocks == 0 {
+            return;
+        }
+        self.watched_ops.push_back(WatchedOperation {
+            op: op.clone(),
+            watch_until_block: current_block + self.options.reorg_watch_blocks,
+        });
+    }
+
+    /// Checks that the transactions of the watched operations are still included in the
+    /// chain. Operations reverted by a reorg get their confirmation reverted in the database,
+    /// and are returned to the ongoing operations to be tracked (or resent) again.
+    fn check_reorgs(&mut self) -> Result<(), failure::Error> {
+        if self.watched_ops.is_empty() {
+            return Ok(());
+        }
+
+        let current_block = self.ethereum.block_number()?;
+        self.watched_ops
+            .retain(|watched| current_block <= watched.watch_until_block);
+
+        let deadline_block = self.get_deadline_block(current_block);
+        let mut reverted_ids = Vec::new();
+        for watched in &self.watched_ops {
+            let final_hash = watched
+                .op
+                .final_hash
+                .expect("Confirmed operation must have the final hash");
+            match self.ethereum.get_tx_status(&final_hash)? {
+                Some(status) if status.success => {}
+                _ => {
+                    // Reverting the confirmation is idempotent, so in case of error
+                    // the operation will be handled again on the next check.
+                    let op = &watched.op;
+                    self.db.transaction(|| {
+                        self.db.revert_confirmation(op.id)?;
+                        self.db
+                            .update_eth_tx(op.id, deadline_block as i64, op.last_used_gas_price)
+                    })?;
+                    reverted_ids.push(op.id);
+                }
+            }
+        }
+
+        let (reverted, watched): (Vec<_>, VecDeque<_>) = self
+            .watched_ops
+            .drain(..)
+            .partition(|watched| reverted_ids.contains(&watched.op.id));
+        self.watched_ops = watched;
+
+        // Reverted operations precede the ongoing ones, so they're put to the front
+        // of the queue keeping their order.
+        for watched in reverted.into_iter().rev() {
+            let mut op = watched.op;
+            warn!(
+                "Confirmed tx was removed by reorg: [ETH Operation <id: {}, type: {:?}>. \
+                 Tx hash: <{:#x}>. ZKSync operation: {}]",
+                op.id,
+                op.op_type,
+                op.final_hash.unwrap_or_default(),
+                self.zksync_operation_description(&op),
+            );
+            metrics_registry().increment_counter(
+                "eth_sender_reorged_txs",
+                "Amount of confirmed transactions removed from the chain by reorgs",
+                1.0,
+            );
+
+            op.confirmed = false;
+            op.final_hash = None;
+            op.last_deadline_block = deadline_block;
+            self.tx_queue.report_reverted_commitment();
+            self.ongoing_ops.push_front(op);
+        }
+
+        Ok(())
+    }
+
+    /// Alerts the operator that the operation is stuck even though its transaction was
+    /// sent with the maximum allowed gas price, so the manual intervention may be required
+    /// (e.g. increasing the `ETH_GAS_PRICE_LIMIT_SCALE_FACTOR`).
+    fn report_gas_price_cap_reached(&self, op: &ETHOperation) {
+        error!(
+            "Operation is stuck with the maximum gas price: [ETH Operation <id: {}, type: {:?}>. \
+             Gas price: {}, sent txs: {}. ZKSync operation: {}]",
+            op.id,
+            op.op_type,
+            op.last_used_gas_price,
+            op.used_tx_hashes.len(),
+            self.zksync_operation_description(op),
+        );
+        metrics_registry().increment_counter(
+            "eth_sender_gas_price_cap_reached",
+            "Amount of times the stuck operation could not be resent with the increased gas price",
+            1.0,
+        );
+    }
+
     /// Handles a transaction execution failure by reporting the issue to the log
     /// and terminating the node.
     fn failure_handler(&self, receipt: &TransactionReceipt) -> ! {
@@ -526,7 +1220,7 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             Some(status) if status.success => {
                 // Check if transaction has enough confirmations.
                 if status.confirmations >= self.options.wait_confirmations {
-                    TxCheckOutcome::Committed
+                    TxCheckOutcome::Committed(status.gas_used)
                 } else {
                     TxCheckOutcome::Pending
                 }
@@ -556,15 +1250,19 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
     }
 
     /// Creates a new Ethereum operation.
-    fn sign_new_tx(ethereum: &ETH, op: &ETHOperation) -> Result<SignedCallResult, failure::Error> {
+    fn sign_new_tx(
+        ethereum: &ETH,
+        op: &ETHOperation,
+        gas_limit_safety_margin: u64,
+    ) -> Result<SignedCallResult, failure::Error> {
         let tx_options = {
             let mut options = Options::default();
             options.nonce = Some(op.nonce);
             options.gas_price = Some(op.last_used_gas_price);
 
-            // We set the gas limit for commit / verify operations as pre-calculated estimation.
-            // This estimation is a higher bound based on a pre-calculated cost of every operation in the block.
-            let gas_limit = Self::gas_limit_for_op(op);
+            // We set the gas limit for commit / verify operations as pre-calculated estimation
+            // cross-checked with the estimation of the Ethereum node.
+            let gas_limit = Self::gas_limit_for_tx(ethereum, op, gas_limit_safety_margin);
 
             assert!(
                 gas_limit > 0.into(),
@@ -588,15 +1286,53 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
         Ok(signed_tx)
     }
 
-    /// Calculates the gas limit for transaction to be send, depending on the type of operation.
+    /// Chooses the gas limit for the transaction of the operation. Pre-calculated limit is
+    /// cross-checked with the `eth_estimateGas` result increased by the safety margin, and the
+    /// greater one is used.
+    ///
+    /// Estimation is not available for the transactions that can't be executed on the latest
+    /// Ethereum state yet (e.g. ones following the pending transactions), so the pre-calculated
+    /// limit is used for them.
+    fn gas_limit_for_tx(ethereum: &ETH, op: &ETHOperation, safety_margin: u64) -> U256 {
+        let precalculated_limit = Self::gas_limit_for_op(op);
+
+        match ethereum.estimate_gas(op.encoded_tx_data.clone()) {
+            Ok(estimation) => {
+                let estimated_limit =
+                    estimation * U256::from(100 + safety_margin) / U256::from(100);
+                if estimated_limit > precalculated_limit {
+                    warn!(
+                        "Gas limit estimated by the Ethereum node exceeds the pre-calculated one \
+                         for <ETH Operation id: {}>: {} > {}",
+                        op.id, estimated_limit, precalculated_limit
+                    );
+                    estimated_limit
+                } else {
+                    precalculated_limit
+                }
+            }
+            Err(e) => {
+                debug!(
+                    "Gas estimation for <ETH Operation id: {}> failed: {}",
+                    op.id, e
+                );
+                precalculated_limit
+            }
+        }
+    }
+
+    /// Calculates the pre-calculated gas limit for transaction to be send, depending on the
+    /// type of operation. This estimation is a higher bound based on a pre-calculated cost of
+    /// every operation in the block.
     fn gas_limit_for_op(op: &ETHOperation) -> U256 {
         match op.op_type {
             OperationType::Commit => {
-                op.op
+                let block = &op
+                    .op
                     .as_ref()
                     .expect("No zkSync operation for Commit")
-                    .block
-                    .commit_gas_limit
+                    .block;
+                GasCounter::block_commit_gas_limit(block)
             }
             OperationType::Verify => {
                 op.op
@@ -640,7 +1376,11 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
             .gas_adjuster
             .get_gas_price(&self.ethereum, Some(old_tx_gas_price))?;
         let nonce = stuck_tx.nonce;
-        let gas_limit = Self::gas_limit_for_op(stuck_tx);
+        let gas_limit = Self::gas_limit_for_tx(
+            &self.ethereum,
+            stuck_tx,
+            self.options.gas_limit_safety_margin,
+        );
 
         assert!(
             gas_limit > 0.into(),
@@ -751,6 +1491,22 @@ impl<ETH: EthereumInterface, DB: DatabaseAccess> ETHSender<ETH, DB> {
 }
 
 #[must_use]
+/// Ensures that the Ethereum node is connected to the configured network, so the operator
+/// transactions are never sent to another network by mistake.
+fn check_chain_id<ETH: EthereumInterface>(
+    ethereum: &ETH,
+    expected_chain_id: u8,
+) -> Result<(), failure::Error> {
+    let chain_id = ethereum.chain_id()?;
+    failure::ensure!(
+        chain_id == u64::from(expected_chain_id),
+        "Ethereum node is connected to the chain {}, while the configured chain id is {}",
+        chain_id,
+        expected_chain_id
+    );
+    Ok(())
+}
+
 pub fn start_eth_sender(
     runtime: &Runtime,
     pool: ConnectionPool,
@@ -761,6 +1517,8 @@ pub fn start_eth_sender(
 ) -> JoinHandle<()> {
     let ethereum =
         EthereumHttpClient::new(&config_options).expect("Ethereum client creation failed");
+    check_chain_id(&ethereum, config_options.chain_id).expect("Ethereum network check failed");
+    runtime.spawn(ethereum.transport().clone().run_health_checks());
 
     let db = Database::new(pool);
 
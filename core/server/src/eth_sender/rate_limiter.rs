@@ -0,0 +1,79 @@
+// Built-in deps
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Period within which the amount of sent transactions is limited.
+const RATE_LIMIT_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Limiter of the amount of Ethereum transactions (both new and resent ones)
+/// sent within the last hour.
+#[derive(Debug)]
+pub(super) struct TxRateLimiter {
+    /// Maximum amount of transactions sent within an hour, no limit if not set.
+    max_txs_per_hour: Option<u64>,
+    /// Moments of the transactions sent within the last hour, the oldest first.
+    sent_txs: VecDeque<Instant>,
+}
+
+impl TxRateLimiter {
+    pub fn new(max_txs_per_hour: Option<u64>) -> Self {
+        Self {
+            max_txs_per_hour,
+            sent_txs: VecDeque::new(),
+        }
+    }
+
+    /// Checks whether one more transaction can be sent at the provided moment.
+    pub fn can_send(&mut self, now: Instant) -> bool {
+        let max_txs_per_hour = match self.max_txs_per_hour {
+            Some(max_txs_per_hour) => max_txs_per_hour,
+            None => return true,
+        };
+
+        while let Some(sent_at) = self.sent_txs.front() {
+            if now.duration_since(*sent_at) < RATE_LIMIT_PERIOD {
+                break;
+            }
+            self.sent_txs.pop_front();
+        }
+        (self.sent_txs.len() as u64) < max_txs_per_hour
+    }
+
+    /// Reports the transaction sent at the provided moment.
+    pub fn report_sent(&mut self, now: Instant) {
+        if self.max_txs_per_hour.is_some() {
+            self.sent_txs.push_back(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hourly_limit() {
+        let start = Instant::now();
+        let mut limiter = TxRateLimiter::new(Some(2));
+
+        assert!(limiter.can_send(start));
+        limiter.report_sent(start);
+        limiter.report_sent(start + Duration::from_secs(60));
+        assert!(!limiter.can_send(start + Duration::from_secs(120)));
+
+        // The first transaction is out of the window.
+        assert!(limiter.can_send(start + RATE_LIMIT_PERIOD));
+        limiter.report_sent(start + RATE_LIMIT_PERIOD);
+        assert!(!limiter.can_send(start + RATE_LIMIT_PERIOD));
+    }
+
+    #[test]
+    fn no_limit() {
+        let now = Instant::now();
+        let mut limiter = TxRateLimiter::new(None);
+        for _ in 0..100 {
+            limiter.report_sent(now);
+        }
+        assert!(limiter.can_send(now));
+    }
+}
@@ -6,12 +6,13 @@ use std::collections::{HashMap, VecDeque};
 // External uses
 use futures::channel::mpsc;
 use web3::contract::{tokens::Tokenize, Options};
-use web3::types::{H256, U256};
+use web3::types::{Address, H256, U256};
 // Workspace uses
 use eth_client::SignedCallResult;
 use models::{
     config_options::EthSenderOptions,
     ethereum::{ETHOperation, EthOpId, InsertedOperationResponse, OperationType},
+    node::BlockNumber,
     Action, Operation,
 };
 // Local uses
@@ -23,6 +24,8 @@ use crate::eth_sender::ETHSenderRequest;
 use crate::utils::current_zksync_info::CurrentZksyncInfo;
 
 const CHANNEL_CAPACITY: usize = 16;
+/// Chain id of the network the mock Ethereum node is connected to.
+pub(in crate::eth_sender) const CHAIN_ID: u64 = 9;
 
 /// Mock database is capable of recording all the incoming requests for the further analysis.
 #[derive(Debug, Default)]
@@ -32,6 +35,10 @@ pub(in crate::eth_sender) struct MockDatabase {
     confirmed_operations: RefCell<HashMap<i64, ETHOperation>>,
     nonce: Cell<i64>,
     gas_price_limit: Cell<U256>,
+    operator_address: Cell<Option<Address>>,
+    signed_txs: RefCell<HashMap<H256, Vec<u8>>>,
+    /// Pending withdrawals queue along with the hashes of the completing transactions.
+    pending_withdrawals: RefCell<Vec<(Vec<u8>, Option<H256>)>>,
     pending_op_id: Cell<EthOpId>,
     stats: RefCell<ETHStats>,
 }
@@ -50,6 +57,15 @@ impl MockDatabase {
 
         let unconfirmed_operations: HashMap<i64, ETHOperation> =
             restore_state.iter().map(|op| (op.id, op.clone())).collect();
+        // Mock signing doesn't change the tx data, so it's stored as the signed tx.
+        let signed_txs: HashMap<H256, Vec<u8>> = restore_state
+            .iter()
+            .flat_map(|op| {
+                op.used_tx_hashes
+                    .iter()
+                    .map(move |hash| (*hash, op.encoded_tx_data.clone()))
+            })
+            .collect();
 
         let gas_price_limit: u64 = models::config_options::parse_env("ETH_GAS_PRICE_DEFAULT_LIMIT");
 
@@ -60,10 +76,21 @@ impl MockDatabase {
             pending_op_id: Cell::new(pending_op_id as EthOpId),
             stats: RefCell::new(stats),
             unconfirmed_operations: RefCell::new(unconfirmed_operations),
+            signed_txs: RefCell::new(signed_txs),
             ..Default::default()
         }
     }
 
+    /// Ensures that the withdrawal with the provided hash is completed by the provided transaction.
+    pub fn assert_withdrawal_completed(&self, withdrawal_hash: &[u8], complete_tx_hash: &H256) {
+        let pending_withdrawals = self.pending_withdrawals.borrow();
+        let (_, stored_tx_hash) = pending_withdrawals
+            .iter()
+            .find(|(hash, _)| hash.as_slice() == withdrawal_hash)
+            .expect("Withdrawal is not stored");
+        assert_eq!(stored_tx_hash.as_ref(), Some(complete_tx_hash));
+    }
+
     /// Ensures that the provided transaction is stored in the database and not confirmed yet.
     pub fn assert_stored(&self, tx: &ETHOperation) {
         assert_eq!(self.unconfirmed_operations.borrow().get(&tx.id), Some(tx));
@@ -131,7 +158,12 @@ impl DatabaseAccess for MockDatabase {
     }
 
     /// Adds a tx hash entry associated with some Ethereum operation to the database.
-    fn add_hash_entry(&self, eth_op_id: i64, hash: &H256) -> Result<(), failure::Error> {
+    fn add_hash_entry(
+        &self,
+        eth_op_id: i64,
+        hash: &H256,
+        signed_raw_tx: &[u8],
+    ) -> Result<(), failure::Error> {
         assert!(
             self.unconfirmed_operations
                 .borrow()
@@ -143,10 +175,17 @@ impl DatabaseAccess for MockDatabase {
         let mut op = ops[&eth_op_id].clone();
         op.used_tx_hashes.push(*hash);
         ops.insert(eth_op_id, op);
+        self.signed_txs
+            .borrow_mut()
+            .insert(*hash, signed_raw_tx.to_vec());
 
         Ok(())
     }
 
+    fn load_signed_tx(&self, hash: &H256) -> Result<Option<Vec<u8>>, failure::Error> {
+        Ok(self.signed_txs.borrow().get(hash).cloned())
+    }
+
     fn update_eth_tx(
         &self,
         eth_op_id: EthOpId,
@@ -169,7 +208,35 @@ impl DatabaseAccess for MockDatabase {
         Ok(())
     }
 
-    fn confirm_operation(&self, hash: &H256) -> Result<(), failure::Error> {
+    fn update_eth_tx_nonce(
+        &self,
+        eth_op_id: EthOpId,
+        new_nonce: i64,
+    ) -> Result<(), failure::Error> {
+        let mut ops = self.unconfirmed_operations.borrow_mut();
+        let op = ops
+            .get_mut(&eth_op_id)
+            .expect("Attempt to update tx that is not unconfirmed");
+        op.nonce = new_nonce.into();
+
+        Ok(())
+    }
+
+    fn load_next_nonce(&self) -> Result<i64, failure::Error> {
+        Ok(self.nonce.get())
+    }
+
+    fn update_next_nonce(&self, nonce: i64) -> Result<(), failure::Error> {
+        self.nonce.set(nonce);
+
+        Ok(())
+    }
+
+    fn confirm_operation(
+        &self,
+        hash: &H256,
+        _gas_used: Option<U256>,
+    ) -> Result<(), failure::Error> {
         let mut unconfirmed_operations = self.unconfirmed_operations.borrow_mut();
         let mut op_idx: Option<i64> = None;
         for operation in unconfirmed_operations.values_mut() {
@@ -195,6 +262,49 @@ impl DatabaseAccess for MockDatabase {
         Ok(())
     }
 
+    fn revert_confirmation(&self, eth_op_id: EthOpId) -> Result<(), failure::Error> {
+        let mut operation = self
+            .confirmed_operations
+            .borrow_mut()
+            .remove(&eth_op_id)
+            .expect("Request to revert the confirmation of operation that was not confirmed");
+        operation.confirmed = false;
+        operation.final_hash = None;
+        self.unconfirmed_operations
+            .borrow_mut()
+            .insert(eth_op_id, operation);
+
+        Ok(())
+    }
+
+    fn add_pending_withdrawals(
+        &self,
+        _block_number: BlockNumber,
+        withdrawal_hashes: &[Vec<u8>],
+    ) -> Result<(), failure::Error> {
+        self.pending_withdrawals
+            .borrow_mut()
+            .extend(withdrawal_hashes.iter().map(|hash| (hash.clone(), None)));
+
+        Ok(())
+    }
+
+    fn complete_withdrawals(
+        &self,
+        complete_tx_hash: &H256,
+        max_withdrawals: u64,
+    ) -> Result<usize, failure::Error> {
+        let mut pending_withdrawals = self.pending_withdrawals.borrow_mut();
+        let completed = pending_withdrawals
+            .iter_mut()
+            .filter(|(_, tx_hash)| tx_hash.is_none())
+            .take(max_withdrawals as usize)
+            .map(|(_, tx_hash)| *tx_hash = Some(*complete_tx_hash))
+            .count();
+
+        Ok(completed)
+    }
+
     fn load_gas_price_limit(&self) -> Result<U256, failure::Error> {
         Ok(self.gas_price_limit.get())
     }
@@ -209,6 +319,16 @@ impl DatabaseAccess for MockDatabase {
         Ok(self.stats.borrow().clone())
     }
 
+    fn load_operator_address(&self) -> Result<Option<Address>, failure::Error> {
+        Ok(self.operator_address.get())
+    }
+
+    fn update_operator_address(&self, address: &Address) -> Result<(), failure::Error> {
+        self.operator_address.set(Some(*address));
+
+        Ok(())
+    }
+
     fn transaction<F, T>(&self, f: F) -> Result<T, failure::Error>
     where
         F: FnOnce() -> Result<T, failure::Error>,
@@ -221,7 +341,19 @@ impl DatabaseAccess for MockDatabase {
 #[derive(Debug)]
 pub(in crate::eth_sender) struct MockEthereum {
     pub block_number: u64,
+    pub chain_id: u64,
+    pub endpoint_switches: usize,
     pub gas_price: U256,
+    pub current_nonce: U256,
+    pub pending_nonce: U256,
+    pub operators: Vec<Address>,
+    pub active_operator: Address,
+    /// Balances of the accounts, unknown accounts have the maximum balance.
+    pub balances: HashMap<Address, U256>,
+    /// Result of the gas estimation, the estimation fails if not set.
+    pub gas_estimation: Option<U256>,
+    /// Revert reason reported by the dry run of the transactions.
+    pub dry_run_revert: Option<String>,
     pub tx_statuses: RefCell<HashMap<H256, ExecutedTxStatus>>,
     pub sent_txs: RefCell<HashMap<H256, SignedCallResult>>,
 }
@@ -230,7 +362,16 @@ impl Default for MockEthereum {
     fn default() -> Self {
         Self {
             block_number: 1,
+            chain_id: CHAIN_ID,
+            endpoint_switches: 0,
             gas_price: 100.into(),
+            current_nonce: 0.into(),
+            pending_nonce: 0.into(),
+            operators: vec![Address::zero()],
+            active_operator: Address::zero(),
+            balances: HashMap::new(),
+            gas_estimation: None,
+            dry_run_revert: None,
             tx_statuses: Default::default(),
             sent_txs: Default::default(),
         }
@@ -273,11 +414,17 @@ impl MockEthereum {
         let status = ExecutedTxStatus {
             confirmations,
             success: true,
+            gas_used: Some(100_000.into()),
             receipt: None,
         };
         self.tx_statuses.borrow_mut().insert(tx_hash, status);
     }
 
+    /// Removes the execution status of the transaction, as if it was reverted by a reorg.
+    pub fn revert_execution(&mut self, hash: &H256) {
+        self.tx_statuses.borrow_mut().remove(hash);
+    }
+
     /// Same as `add_successfull_execution`, but marks the transaction as a failure.
     pub fn add_failed_execution(&mut self, hash: &H256, confirmations: u64) {
         self.block_number += confirmations;
@@ -285,6 +432,7 @@ impl MockEthereum {
         let status = ExecutedTxStatus {
             confirmations,
             success: false,
+            gas_used: Some(100_000.into()),
             receipt: Some(Default::default()),
         };
         self.tx_statuses.borrow_mut().insert(*hash, status);
@@ -300,10 +448,58 @@ impl EthereumInterface for MockEthereum {
         Ok(self.block_number)
     }
 
+    fn chain_id(&self) -> Result<u64, failure::Error> {
+        Ok(self.chain_id)
+    }
+
+    fn endpoint_switches(&self) -> usize {
+        self.endpoint_switches
+    }
+
     fn gas_price(&self) -> Result<U256, failure::Error> {
         Ok(self.gas_price)
     }
 
+    fn operator_addresses(&self) -> Vec<Address> {
+        self.operators.clone()
+    }
+
+    fn operator_address(&self) -> Address {
+        self.active_operator
+    }
+
+    fn use_operator(&mut self, address: Address) -> Result<(), failure::Error> {
+        assert!(self.operators.contains(&address), "Unknown operator");
+        self.active_operator = address;
+
+        Ok(())
+    }
+
+    fn balance(&self, address: Address) -> Result<U256, failure::Error> {
+        Ok(self
+            .balances
+            .get(&address)
+            .cloned()
+            .unwrap_or_else(U256::max_value))
+    }
+
+    fn current_nonce(&self) -> Result<U256, failure::Error> {
+        Ok(self.current_nonce)
+    }
+
+    fn pending_nonce(&self) -> Result<U256, failure::Error> {
+        Ok(self.pending_nonce)
+    }
+
+    fn estimate_gas(&self, _data: Vec<u8>) -> Result<U256, failure::Error> {
+        self.gas_estimation
+            .ok_or_else(|| failure::format_err!("Transaction execution would fail"))
+    }
+
+    fn dry_run(&self, _data: Vec<u8>) -> Result<Option<String>, failure::Error> {
+        Ok(self.dry_run_revert.clone())
+    }
+
     fn send_tx(&self, signed_tx: &SignedCallResult) -> Result<(), failure::Error> {
         self.sent_txs
             .borrow_mut()
@@ -347,7 +543,7 @@ pub(in crate::eth_sender) fn default_eth_sender() -> (
     mpsc::Sender<ETHSenderRequest>,
     mpsc::Receiver<Operation>,
 ) {
-    build_eth_sender(1, Vec::new(), Default::default())
+    build_eth_sender(1, Vec::new(), Default::default(), Default::default())
 }
 
 /// Creates an `ETHSender` with mock Ethereum connection/database and no operations in DB
@@ -360,7 +556,12 @@ pub(in crate::eth_sender) fn concurrent_eth_sender(
     mpsc::Sender<ETHSenderRequest>,
     mpsc::Receiver<Operation>,
 ) {
-    build_eth_sender(max_txs_in_flight, Vec::new(), Default::default())
+    build_eth_sender(
+        max_txs_in_flight,
+        Vec::new(),
+        Default::default(),
+        Default::default(),
+    )
 }
 
 /// Creates an `ETHSender` with mock Ethereum connection/database and restores its state "from DB".
@@ -375,7 +576,28 @@ pub(in crate::eth_sender) fn restored_eth_sender(
 ) {
     const MAX_TXS_IN_FLIGHT: u64 = 1;
 
-    build_eth_sender(MAX_TXS_IN_FLIGHT, restore_state, stats)
+    let restore_state: Vec<_> = restore_state.into_iter().collect();
+    // Restored transactions are known to the Ethereum node, but not mined yet.
+    let ethereum = MockEthereum {
+        pending_nonce: (restore_state.len() as u64).into(),
+        ..Default::default()
+    };
+
+    build_eth_sender(MAX_TXS_IN_FLIGHT, restore_state, stats, ethereum)
+}
+
+/// Creates an `ETHSender` with the provided state of the operator account on the Ethereum,
+/// restoring its state "from DB".
+/// Returns the `ETHSender` itself along with communication channels to interact with it.
+pub(in crate::eth_sender) fn recovered_eth_sender(
+    restore_state: impl IntoIterator<Item = ETHOperation>,
+    ethereum: MockEthereum,
+) -> (
+    ETHSender<MockEthereum, MockDatabase>,
+    mpsc::Sender<ETHSenderRequest>,
+    mpsc::Receiver<Operation>,
+) {
+    build_eth_sender(1, restore_state, Default::default(), ethereum)
 }
 
 /// Helper method for configurable creation of `ETHSender`.
@@ -383,12 +605,12 @@ fn build_eth_sender(
     max_txs_in_flight: u64,
     restore_state: impl IntoIterator<Item = ETHOperation>,
     stats: ETHStats,
+    ethereum: MockEthereum,
 ) -> (
     ETHSender<MockEthereum, MockDatabase>,
     mpsc::Sender<ETHSenderRequest>,
     mpsc::Receiver<Operation>,
 ) {
-    let ethereum = MockEthereum::default();
     let db = MockDatabase::with_restorable_state(restore_state, stats);
 
     let (operation_sender, operation_receiver) = mpsc::channel(CHANNEL_CAPACITY);
@@ -398,8 +620,16 @@ fn build_eth_sender(
         max_txs_in_flight,
         expected_wait_time_block: super::EXPECTED_WAIT_TIME_BLOCKS,
         wait_confirmations: super::WAIT_CONFIRMATIONS,
+        reorg_watch_blocks: super::REORG_WATCH_BLOCKS,
         tx_poll_period: Default::default(),
         is_enabled: true,
+        min_operator_balance: super::MIN_OPERATOR_BALANCE.into(),
+        halt_operator_balance: super::HALT_OPERATOR_BALANCE.into(),
+        gas_limit_safety_margin: super::GAS_LIMIT_SAFETY_MARGIN,
+        send_verify_with_commit: false,
+        max_unverified_blocks: super::MAX_UNVERIFIED_BLOCKS,
+        max_txs_per_hour: None,
+        max_gas_price: None,
     };
 
     let current_zksync_info = CurrentZksyncInfo::with_block_number(0);
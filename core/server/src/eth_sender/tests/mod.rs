@@ -6,20 +6,30 @@ use models::ethereum::ETHOperation;
 // Local uses
 use self::mock::{
     concurrent_eth_sender, create_signed_tx, create_signed_withdraw_tx, default_eth_sender,
-    restored_eth_sender,
+    recovered_eth_sender, restored_eth_sender, MockDatabase, MockEthereum, CHAIN_ID,
 };
 use super::{
+    check_chain_id,
+    gas_adjuster::GasAdjuster,
+    rate_limiter::TxRateLimiter,
     transactions::{ETHStats, ExecutedTxStatus, TxCheckOutcome},
     ETHSender, TxCheckMode,
 };
 use crate::eth_sender::database::DatabaseAccess;
 use crate::eth_sender::ethereum_interface::EthereumInterface;
 use crate::eth_sender::ETHSenderRequest;
+use crate::utils::metrics_registry::metrics_registry;
 use futures::executor::block_on;
 use std::time::Duration;
+use web3::types::{Address, U256};
 
 const EXPECTED_WAIT_TIME_BLOCKS: u64 = 30;
 const WAIT_CONFIRMATIONS: u64 = 1;
+const REORG_WATCH_BLOCKS: u64 = 10;
+const GAS_LIMIT_SAFETY_MARGIN: u64 = 20;
+const MIN_OPERATOR_BALANCE: u64 = 100;
+const HALT_OPERATOR_BALANCE: u64 = 10;
+const MAX_UNVERIFIED_BLOCKS: u64 = 32;
 
 pub mod mock;
 mod test_data;
@@ -92,6 +102,7 @@ fn transaction_state() {
     let committed_response = ExecutedTxStatus {
         confirmations: WAIT_CONFIRMATIONS,
         success: true,
+        gas_used: Some(100_000.into()),
         receipt: None,
     };
     eth_sender
@@ -102,6 +113,7 @@ fn transaction_state() {
     let pending_response = ExecutedTxStatus {
         confirmations: WAIT_CONFIRMATIONS - 1,
         success: true,
+        gas_used: Some(100_000.into()),
         receipt: None,
     };
     eth_sender
@@ -112,6 +124,7 @@ fn transaction_state() {
     let failed_response = ExecutedTxStatus {
         confirmations: WAIT_CONFIRMATIONS,
         success: false,
+        gas_used: Some(100_000.into()),
         receipt: Some(Default::default()),
     };
     eth_sender
@@ -130,7 +143,7 @@ fn transaction_state() {
                 current_block + committed_response.confirmations,
             )
             .unwrap(),
-        TxCheckOutcome::Committed
+        TxCheckOutcome::Committed(committed_response.gas_used)
     );
 
     // Pending operation (no enough confirmations).
@@ -203,6 +216,7 @@ fn transaction_state() {
 /// - we send the two sequential operations (commit and verify);
 /// - they are successfully committed to the Ethereum;
 /// - `completeWithdrawals` tx is sent to the Ethereum;
+/// - withdrawals of the verified block are marked with the `completeWithdrawals` tx hash;
 /// - notification is sent after `verify` operation is committed.
 #[test]
 fn operation_commitment_workflow() {
@@ -286,6 +300,11 @@ fn operation_commitment_workflow() {
     withdraw_op_tx.final_hash = Some(withdraw_op_tx.used_tx_hashes[0]);
     eth_sender.db.assert_confirmed(&withdraw_op_tx);
 
+    // The full exit of the verified block should be marked as completed by this transaction.
+    eth_sender
+        .db
+        .assert_withdrawal_completed(&[], &withdraw_op_tx.used_tx_hashes[0]);
+
     // We should be notified about verify operation being completed.
     assert_eq!(
         receiver.try_next().unwrap().unwrap().id,
@@ -343,6 +362,50 @@ fn stuck_transaction() {
     eth_sender.db.assert_confirmed(&stuck_tx);
 }
 
+/// Checks that the stuck transaction sent with the maximum gas price is not resent,
+/// and the operation keeps waiting for it.
+#[test]
+fn stuck_transaction_with_max_gas_price() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+    // Make the network price equal to the gas price limit.
+    eth_sender
+        .db
+        .update_gas_price_limit(eth_sender.ethereum.gas_price)
+        .unwrap();
+    eth_sender.gas_adjuster = GasAdjuster::new(&eth_sender.db);
+
+    let operation = test_data::commit_operation(0);
+    sender
+        .try_send(ETHSenderRequest::SendOperation(operation.clone()))
+        .unwrap();
+
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+
+    let eth_op_id = 0;
+    let nonce = 0;
+    let deadline_block = eth_sender.get_deadline_block(eth_sender.ethereum.block_number);
+    let mut stuck_tx = create_signed_tx(eth_op_id, &eth_sender, &operation, deadline_block, nonce);
+
+    // Skip some blocks and expect sender to only extend the deadline.
+    eth_sender.ethereum.block_number += EXPECTED_WAIT_TIME_BLOCKS;
+    block_on(eth_sender.proceed_next_operations());
+
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+    stuck_tx.last_deadline_block = eth_sender.get_deadline_block(eth_sender.ethereum.block_number);
+    eth_sender.db.assert_stored(&stuck_tx);
+
+    // The only sent transaction is still confirmed.
+    eth_sender
+        .ethereum
+        .add_successfull_execution(stuck_tx.used_tx_hashes[0], WAIT_CONFIRMATIONS);
+    block_on(eth_sender.proceed_next_operations());
+
+    stuck_tx.confirmed = true;
+    stuck_tx.final_hash = Some(stuck_tx.used_tx_hashes[0]);
+    eth_sender.db.assert_confirmed(&stuck_tx);
+}
+
 /// This test verifies that with multiple operations received all-together,
 /// their order is respected and no processing of the next operation is started until
 /// the previous one is committed.
@@ -539,6 +602,235 @@ fn restore_state() {
     assert!(receiver.try_next().unwrap().is_some());
 }
 
+/// Checks that the operation with the nonce consumed by another transaction
+/// gets the new nonce on startup and is resent right away.
+#[test]
+fn nonce_recovery_consumed_nonce() {
+    let stored_operation = {
+        let (eth_sender, _, _) = default_eth_sender();
+        let deadline_block = eth_sender.get_deadline_block(1);
+        create_signed_tx(
+            0,
+            &eth_sender,
+            &test_data::commit_operation(0),
+            deadline_block,
+            0,
+        )
+    };
+
+    // Nonce 0 was used by some other transaction of the operator account.
+    let ethereum = MockEthereum {
+        current_nonce: 1.into(),
+        pending_nonce: 1.into(),
+        ..Default::default()
+    };
+    let (mut eth_sender, _, _) = recovered_eth_sender(vec![stored_operation.clone()], ethereum);
+
+    let mut expected_operation = stored_operation;
+    expected_operation.nonce = 1.into();
+    expected_operation.last_deadline_block = eth_sender.ethereum.block_number;
+    assert_eq!(eth_sender.ongoing_ops[0], expected_operation);
+    eth_sender.db.assert_stored(&expected_operation);
+    assert_eq!(eth_sender.db.load_next_nonce().unwrap(), 2);
+
+    // Transaction with the new nonce is sent on the first iteration.
+    block_on(eth_sender.proceed_next_operations());
+    let sent_txs = eth_sender.ethereum.sent_txs.borrow();
+    assert_eq!(sent_txs.len(), 1);
+    assert!(sent_txs.values().all(|tx| tx.nonce == 1.into()));
+}
+
+/// Checks that the mined transactions are left as is on startup, and the
+/// operation following the nonce gap is moved to the gap.
+#[test]
+fn nonce_recovery_gap() {
+    let (mined_operation, gap_operation) = {
+        let (eth_sender, _, _) = default_eth_sender();
+        let deadline_block = eth_sender.get_deadline_block(1);
+        let mined_operation = create_signed_tx(
+            0,
+            &eth_sender,
+            &test_data::commit_operation(0),
+            deadline_block,
+            0,
+        );
+        let gap_operation = create_signed_tx(
+            1,
+            &eth_sender,
+            &test_data::commit_operation(1),
+            deadline_block,
+            2,
+        );
+        (mined_operation, gap_operation)
+    };
+
+    // The first transaction is mined, and the nonce 1 is not used by anyone.
+    let mut ethereum = MockEthereum {
+        current_nonce: 1.into(),
+        pending_nonce: 1.into(),
+        ..Default::default()
+    };
+    ethereum.add_successfull_execution(mined_operation.used_tx_hashes[0], WAIT_CONFIRMATIONS);
+    let (eth_sender, _, _) = recovered_eth_sender(
+        vec![mined_operation.clone(), gap_operation.clone()],
+        ethereum,
+    );
+
+    eth_sender.db.assert_stored(&mined_operation);
+    let mut expected_operation = gap_operation;
+    expected_operation.nonce = 1.into();
+    expected_operation.last_deadline_block = eth_sender.ethereum.block_number;
+    eth_sender.db.assert_stored(&expected_operation);
+    assert_eq!(eth_sender.db.load_next_nonce().unwrap(), 2);
+}
+
+/// Checks that the stored signed transaction unknown to the Ethereum node is sent
+/// again as is on startup.
+#[test]
+fn signed_tx_resent_on_restart() {
+    let stored_operation = {
+        let (eth_sender, _, _) = default_eth_sender();
+        let deadline_block = eth_sender.get_deadline_block(1);
+        create_signed_tx(
+            0,
+            &eth_sender,
+            &test_data::commit_operation(0),
+            deadline_block,
+            0,
+        )
+    };
+
+    // Transaction was stored, but the Ethereum node doesn't know about it.
+    let ethereum = MockEthereum::default();
+    let (eth_sender, _, _) = recovered_eth_sender(vec![stored_operation.clone()], ethereum);
+
+    eth_sender.db.assert_stored(&stored_operation);
+    eth_sender
+        .ethereum
+        .assert_sent(&stored_operation.used_tx_hashes[0]);
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+}
+
+/// Checks that the transaction unknown to the new Ethereum node endpoint is sent
+/// again once the endpoint is switched.
+#[test]
+fn signed_tx_resent_on_endpoint_switch() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+    sender
+        .try_send(ETHSenderRequest::SendOperation(
+            test_data::commit_operation(0),
+        ))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+    let tx_hash = eth_sender.ongoing_ops[0].used_tx_hashes[0];
+    eth_sender.ethereum.assert_sent(&tx_hash);
+
+    // Transaction is not resent while the endpoint isn't switched.
+    eth_sender.ethereum.sent_txs.borrow_mut().clear();
+    block_on(eth_sender.proceed_next_operations());
+    assert!(eth_sender.ethereum.sent_txs.borrow().is_empty());
+
+    // New endpoint doesn't know about the sent transaction.
+    eth_sender.ethereum.endpoint_switches += 1;
+    block_on(eth_sender.proceed_next_operations());
+    eth_sender.ethereum.assert_sent(&tx_hash);
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+}
+
+/// Checks that once the operator account is drained, new transactions are sent from
+/// the next operator account only after the already sent ones are confirmed.
+#[test]
+fn operator_switch() {
+    let main_operator = Address::from_low_u64_be(1);
+    let reserve_operator = Address::from_low_u64_be(2);
+    let ethereum = MockEthereum {
+        operators: vec![main_operator, reserve_operator],
+        ..Default::default()
+    };
+    let (mut eth_sender, mut sender, _) = recovered_eth_sender(Vec::new(), ethereum);
+    assert_eq!(eth_sender.ethereum.operator_address(), main_operator);
+
+    let operations = vec![
+        test_data::commit_operation(0),
+        test_data::commit_operation(1),
+    ];
+    sender
+        .try_send(ETHSenderRequest::SendOperation(operations[0].clone()))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+    let sent_op = eth_sender.ongoing_ops[0].clone();
+
+    // Main operator account is drained, so no new transactions are sent until
+    // the already sent one is confirmed.
+    eth_sender.ethereum.balances.insert(main_operator, 0.into());
+    eth_sender.check_operator_balance().unwrap();
+    assert!(eth_sender.operator_switch_pending);
+
+    sender
+        .try_send(ETHSenderRequest::SendOperation(operations[1].clone()))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    eth_sender
+        .ethereum
+        .add_successfull_execution(sent_op.used_tx_hashes[0], WAIT_CONFIRMATIONS);
+    block_on(eth_sender.proceed_next_operations());
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+
+    // Reserve operator account has its own nonce.
+    eth_sender.ethereum.current_nonce = 5.into();
+    eth_sender.ethereum.pending_nonce = 5.into();
+    block_on(eth_sender.proceed_next_operations());
+
+    assert!(!eth_sender.operator_switch_pending);
+    assert_eq!(eth_sender.ethereum.operator_address(), reserve_operator);
+    assert_eq!(
+        eth_sender.db.load_operator_address().unwrap(),
+        Some(reserve_operator)
+    );
+    assert_eq!(eth_sender.ongoing_ops.len(), 1);
+    assert_eq!(eth_sender.ongoing_ops[0].nonce, 5.into());
+    eth_sender
+        .ethereum
+        .assert_sent(&eth_sender.ongoing_ops[0].used_tx_hashes[0]);
+}
+
+/// Checks that the block production is halted once the operator balance is below
+/// the halt threshold, and is resumed after the account is topped up.
+#[test]
+fn block_production_halt() {
+    let operator = Address::from_low_u64_be(1);
+    let ethereum = MockEthereum {
+        operators: vec![operator],
+        ..Default::default()
+    };
+    let (mut eth_sender, _, _) = recovered_eth_sender(Vec::new(), ethereum);
+
+    // Balance is low, but still enough to keep sending the transactions.
+    eth_sender
+        .ethereum
+        .balances
+        .insert(operator, HALT_OPERATOR_BALANCE.into());
+    eth_sender.check_operator_balance().unwrap();
+    assert!(eth_sender.operator_switch_pending);
+    assert!(!eth_sender.current_zksync_info.is_block_production_halted());
+
+    eth_sender
+        .ethereum
+        .balances
+        .insert(operator, (HALT_OPERATOR_BALANCE - 1).into());
+    eth_sender.check_operator_balance().unwrap();
+    assert!(eth_sender.current_zksync_info.is_block_production_halted());
+
+    eth_sender
+        .ethereum
+        .balances
+        .insert(operator, MIN_OPERATOR_BALANCE.into());
+    eth_sender.check_operator_balance().unwrap();
+    assert!(!eth_sender.current_zksync_info.is_block_production_halted());
+}
+
 /// Checks that even after getting the first transaction stuck and sending the next
 /// one, confirmation for the first (stuck) transaction is processed and leads
 /// to the operation commitment.
@@ -734,3 +1026,212 @@ fn concurrent_operations_order() {
         assert!(receiver.try_next().unwrap().is_some());
     }
 }
+
+/// Checks that the confirmed operation which transaction was removed by a reorg gets its
+/// confirmation reverted and is tracked again, while the older confirmations are final.
+#[test]
+fn reorged_transaction() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+
+    let operation = test_data::commit_operation(0);
+    sender
+        .try_send(ETHSenderRequest::SendOperation(operation.clone()))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+
+    let mut expected_tx = eth_sender.ongoing_ops[0].clone();
+    let tx_hash = expected_tx.used_tx_hashes[0];
+    eth_sender
+        .ethereum
+        .add_successfull_execution(tx_hash, WAIT_CONFIRMATIONS);
+    block_on(eth_sender.proceed_next_operations());
+
+    expected_tx.confirmed = true;
+    expected_tx.final_hash = Some(tx_hash);
+    eth_sender.db.assert_confirmed(&expected_tx);
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert_eq!(eth_sender.watched_ops.len(), 1);
+
+    // Transaction is removed by a reorg, so the operation is unconfirmed again.
+    eth_sender.ethereum.revert_execution(&tx_hash);
+    block_on(eth_sender.proceed_next_operations());
+
+    expected_tx.confirmed = false;
+    expected_tx.final_hash = None;
+    let deadline_block = eth_sender.get_deadline_block(eth_sender.ethereum.block_number);
+    expected_tx.last_deadline_block = deadline_block;
+    eth_sender.db.assert_stored(&expected_tx);
+    assert_eq!(eth_sender.ongoing_ops.len(), 1);
+    assert!(eth_sender.watched_ops.is_empty());
+
+    // Transaction is included into the chain again.
+    eth_sender
+        .ethereum
+        .add_successfull_execution(tx_hash, WAIT_CONFIRMATIONS);
+    block_on(eth_sender.proceed_next_operations());
+
+    expected_tx.confirmed = true;
+    expected_tx.final_hash = Some(tx_hash);
+    eth_sender.db.assert_confirmed(&expected_tx);
+    assert!(eth_sender.ongoing_ops.is_empty());
+
+    // Once the watch period is over, the confirmation is final.
+    eth_sender.ethereum.block_number += REORG_WATCH_BLOCKS + 1;
+    eth_sender.ethereum.revert_execution(&tx_hash);
+    block_on(eth_sender.proceed_next_operations());
+
+    eth_sender.db.assert_confirmed(&expected_tx);
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert!(eth_sender.watched_ops.is_empty());
+}
+
+/// Checks that the gas limit estimated by the Ethereum node is used only if it exceeds
+/// the pre-calculated one, and the pre-calculated limit is used if estimation fails.
+#[test]
+fn gas_limit_estimation() {
+    let (mut eth_sender, _, _) = default_eth_sender();
+    let deadline_block = eth_sender.get_deadline_block(eth_sender.ethereum.block_number);
+    let operation = test_data::commit_operation(0);
+    let op = create_signed_tx(0, &eth_sender, &operation, deadline_block, 0);
+    let precalculated_limit = ETHSender::<MockEthereum, MockDatabase>::gas_limit_for_op(&op);
+
+    let gas_limit_for_tx = |ethereum: &MockEthereum| {
+        ETHSender::<MockEthereum, MockDatabase>::gas_limit_for_tx(
+            ethereum,
+            &op,
+            GAS_LIMIT_SAFETY_MARGIN,
+        )
+    };
+
+    // Estimation is not available.
+    assert_eq!(gas_limit_for_tx(&eth_sender.ethereum), precalculated_limit);
+
+    // Estimation with the margin is lower than the pre-calculated limit.
+    eth_sender.ethereum.gas_estimation = Some(precalculated_limit / U256::from(2));
+    assert_eq!(gas_limit_for_tx(&eth_sender.ethereum), precalculated_limit);
+
+    // Estimation with the margin exceeds the pre-calculated limit.
+    eth_sender.ethereum.gas_estimation = Some(precalculated_limit);
+    let expected_limit =
+        precalculated_limit * U256::from(100 + GAS_LIMIT_SAFETY_MARGIN) / U256::from(100);
+    assert_eq!(gas_limit_for_tx(&eth_sender.ethereum), expected_limit);
+}
+
+/// Checks that the operation which dry run fails is not sent, and is sent once
+/// the dry run succeeds.
+#[test]
+fn dry_run_failure() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+    eth_sender.ethereum.dry_run_revert = Some("Wrong root hash".into());
+
+    let operation = test_data::commit_operation(0);
+    sender
+        .try_send(ETHSenderRequest::SendOperation(operation))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+
+    block_on(eth_sender.proceed_next_operations());
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert!(eth_sender.ethereum.sent_txs.borrow().is_empty());
+
+    eth_sender.ethereum.dry_run_revert = None;
+    block_on(eth_sender.proceed_next_operations());
+    assert_eq!(eth_sender.ongoing_ops.len(), 1);
+    eth_sender
+        .ethereum
+        .assert_sent(&eth_sender.ongoing_ops[0].used_tx_hashes[0]);
+}
+
+/// Checks that new transactions are deferred once the hourly limit is reached or
+/// the network gas price exceeds the maximum one.
+#[test]
+fn submission_budget() {
+    let (mut eth_sender, mut sender, _) = concurrent_eth_sender(2);
+    eth_sender.rate_limiter = TxRateLimiter::new(Some(1));
+
+    let operations = vec![
+        test_data::commit_operation(0),
+        test_data::commit_operation(1),
+    ];
+    for operation in &operations {
+        sender
+            .try_send(ETHSenderRequest::SendOperation(operation.clone()))
+            .unwrap();
+    }
+    retrieve_all_operations(&mut eth_sender);
+
+    // Only one transaction fits into the hourly limit.
+    block_on(eth_sender.proceed_next_operations());
+    assert_eq!(eth_sender.ongoing_ops.len(), 1);
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+
+    // Network gas price is too high.
+    eth_sender.rate_limiter = TxRateLimiter::new(None);
+    eth_sender.options.max_gas_price = Some(eth_sender.ethereum.gas_price - U256::from(1));
+    block_on(eth_sender.proceed_next_operations());
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 1);
+
+    eth_sender.options.max_gas_price = Some(eth_sender.ethereum.gas_price);
+    block_on(eth_sender.proceed_next_operations());
+    assert_eq!(eth_sender.ethereum.sent_txs.borrow().len(), 2);
+}
+
+/// Checks that no transactions are sent once the contract enters the exodus mode.
+#[test]
+fn exodus_mode() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+    eth_sender.current_zksync_info.set_exodus_mode();
+
+    sender
+        .try_send(ETHSenderRequest::SendOperation(
+            test_data::commit_operation(0),
+        ))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert!(eth_sender.ethereum.sent_txs.borrow().is_empty());
+}
+
+/// Checks that the Ethereum node connected to another network is detected.
+#[test]
+fn chain_id_check() {
+    let mut ethereum = MockEthereum::default();
+    assert!(check_chain_id(&ethereum, CHAIN_ID as u8).is_ok());
+
+    ethereum.chain_id = 1;
+    assert!(check_chain_id(&ethereum, CHAIN_ID as u8).is_err());
+}
+
+/// Checks that the operation send moment is tracked until the confirmation,
+/// and the pipeline metrics are reported.
+#[test]
+fn confirmation_metrics() {
+    let (mut eth_sender, mut sender, _) = default_eth_sender();
+
+    sender
+        .try_send(ETHSenderRequest::SendOperation(
+            test_data::commit_operation(0),
+        ))
+        .unwrap();
+    retrieve_all_operations(&mut eth_sender);
+    block_on(eth_sender.proceed_next_operations());
+
+    let op = eth_sender.ongoing_ops[0].clone();
+    assert!(eth_sender.sent_at.contains_key(&op.id));
+    assert!(metrics_registry()
+        .value("eth_sender_in_flight_ops")
+        .is_some());
+    assert!(metrics_registry().value("eth_sender_queued_ops").is_some());
+
+    eth_sender
+        .ethereum
+        .add_successfull_execution(op.used_tx_hashes[0], WAIT_CONFIRMATIONS);
+    block_on(eth_sender.proceed_next_operations());
+
+    assert!(eth_sender.ongoing_ops.is_empty());
+    assert!(eth_sender.sent_at.is_empty());
+    assert!(metrics_registry().value("eth_sender_gas_used").unwrap() >= 100_000.0);
+}
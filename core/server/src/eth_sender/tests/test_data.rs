@@ -34,6 +34,7 @@ fn get_operation(id: i64, block_number: u32, action: Action) -> Operation {
             })),
             block_index: 0,
             created_at: DateTime::from(SystemTime::UNIX_EPOCH),
+            invalid_reason: None,
         }))
     };
     Operation {
@@ -48,6 +49,7 @@ fn get_operation(id: i64, block_number: u32, action: Action) -> Operation {
             50,
             1_000_000.into(),
             1_500_000.into(),
+            None,
         ),
         accounts_updated: Vec::new(),
     }
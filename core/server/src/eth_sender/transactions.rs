@@ -5,8 +5,9 @@
 
 // Built-in deps
 // External uses
-use web3::types::TransactionReceipt;
+use web3::types::{TransactionReceipt, U256};
 // Workspace uses
+use models::ethereum::ETHOperation;
 use storage::ethereum::records::ETHStats as StorageETHStats;
 
 /// Collected statistics of the amount of operations sent to the Ethereum.
@@ -40,6 +41,8 @@ pub(super) struct ExecutedTxStatus {
     pub confirmations: u64,
     /// Whether transaction was executed successfully or failed.
     pub success: bool,
+    /// Amount of gas used by the transaction.
+    pub gas_used: Option<U256>,
     /// Receipt for a transaction. Will be set to `Some` only if the transaction
     /// failed during execution.
     pub receipt: Option<TransactionReceipt>,
@@ -48,8 +51,9 @@ pub(super) struct ExecutedTxStatus {
 /// The result of the check for the Ethereum transaction commitment.
 #[derive(Debug, PartialEq)]
 pub enum TxCheckOutcome {
-    /// Transaction was committed and confirmed.
-    Committed,
+    /// Transaction was committed and confirmed. Contains the amount of gas
+    /// used by the transaction, if it was reported by the Ethereum node.
+    Committed(Option<U256>),
     /// Transaction is pending yet.
     Pending,
     /// Transaction is considered stuck, a replacement should be made.
@@ -58,6 +62,15 @@ pub enum TxCheckOutcome {
     Failed(Box<TransactionReceipt>),
 }
 
+/// Confirmed operation which is checked to still be included in the chain,
+/// since its transaction can be removed by a chain reorganization.
+#[derive(Debug, Clone)]
+pub struct WatchedOperation {
+    pub op: ETHOperation,
+    /// Number of the block after which the operation is considered final.
+    pub watch_until_block: u64,
+}
+
 /// Enumeration denoting if the operation was successfully committed, or not yet.
 #[derive(Debug, PartialEq, Eq)]
 pub enum OperationCommitment {
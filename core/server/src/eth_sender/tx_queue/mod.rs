@@ -59,6 +59,9 @@ pub struct TxQueueBuilder {
     commit_operations_count: usize,
     verify_operations_count: usize,
     withdraw_operations_count: usize,
+
+    send_verify_with_commit: bool,
+    max_unverified_blocks: usize,
 }
 
 impl TxQueueBuilder {
@@ -70,6 +73,8 @@ impl TxQueueBuilder {
             commit_operations_count: 0,
             verify_operations_count: 0,
             withdraw_operations_count: 0,
+            send_verify_with_commit: false,
+            max_unverified_blocks: usize::MAX,
         }
     }
 
@@ -105,6 +110,24 @@ impl TxQueueBuilder {
         }
     }
 
+    /// Sets whether the `verify` operation which is ready by the moment its `commit`
+    /// operation is sent should be sent right after it, regardless of the pending txs limit.
+    pub fn with_send_verify_with_commit(self, send_verify_with_commit: bool) -> Self {
+        Self {
+            send_verify_with_commit,
+            ..self
+        }
+    }
+
+    /// Sets the maximum amount of the blocks which `commit` operations are sent,
+    /// while their `verify` operations are not.
+    pub fn with_max_unverified_blocks(self, max_unverified_blocks: usize) -> Self {
+        Self {
+            max_unverified_blocks,
+            ..self
+        }
+    }
+
     /// Finishes the queue building process.
     pub fn build(self) -> TxQueue {
         // Block numbers are indexed starting from 1, so we have to increment.
@@ -117,6 +140,10 @@ impl TxQueueBuilder {
             commit_operations: CounterQueue::new(self.commit_operations_count),
             verify_operations: SparseQueue::new(verify_operations_next_block),
             withdraw_operations: CounterQueue::new(self.withdraw_operations_count),
+
+            send_verify_with_commit: self.send_verify_with_commit,
+            commit_just_popped: false,
+            max_unverified_blocks: self.max_unverified_blocks,
         }
     }
 }
@@ -133,8 +160,18 @@ impl TxQueueBuilder {
 ///     are prioritized unless the amount of sent `commit` and `verify` operations is equal:
 ///     if so, we should send the `commit` operation first).
 ///   - Otherwise, if `withdraw` queue contains elements, a `withdraw` operation is yielded.
-///   - Otherwise, if `commit` queue is not empty, a `commit` operation is yielded.
+///   - Otherwise, if `commit` queue is not empty, a `commit` operation is yielded, unless
+///     the amount of the blocks committed ahead of the sent `verify` operations reached
+///     the `max_unverified_blocks` limit.
 /// 3. If all the queues are empty, no operation is returned.
+///
+/// This way blocks are committed while the previous ones are still being proven, but
+/// the pipeline of the committed blocks waiting for the verification is bounded.
+///
+/// If `send_verify_with_commit` is set, and the `verify` operation for the block is already
+/// in the queue by the moment its `commit` operation is yielded, the `verify` operation is
+/// yielded right after it even if the pending txs limit is met. This way blocks proven
+/// before being committed (e.g. during the low-traffic periods) are finalized faster.
 #[derive(Debug)]
 pub struct TxQueue {
     max_pending_txs: usize,
@@ -143,6 +180,11 @@ pub struct TxQueue {
     commit_operations: CounterQueue<TxData>,
     verify_operations: SparseQueue<TxData>,
     withdraw_operations: CounterQueue<TxData>,
+
+    send_verify_with_commit: bool,
+    /// Flag showing that the last yielded operation was `commit`.
+    commit_just_popped: bool,
+    max_unverified_blocks: usize,
 }
 
 impl TxQueue {
@@ -213,11 +255,17 @@ impl TxQueue {
         // We've incremented the counter when transaction was popped.
         // Now it's returned and counter should be decremented back.
         self.sent_pending_txs -= 1;
+        self.commit_just_popped = false;
     }
 
     /// Gets the next transaction to send, according to the transaction sending policy.
     /// For details, see the structure doc-comment.
     pub fn pop_front(&mut self) -> Option<TxData> {
+        if let Some(verify_operation) = self.pop_paired_verify_operation() {
+            self.sent_pending_txs += 1;
+            return Some(verify_operation);
+        }
+
         if self.sent_pending_txs >= self.max_pending_txs {
             return None;
         }
@@ -226,12 +274,30 @@ impl TxQueue {
         match self.get_next_operation() {
             Some(op) => {
                 self.sent_pending_txs += 1;
+                self.commit_just_popped = op.op_type == OperationType::Commit;
                 Some(op)
             }
             None => None,
         }
     }
 
+    /// Takes the `verify` operation for the block which `commit` operation was just yielded,
+    /// if sending them together is enabled.
+    fn pop_paired_verify_operation(&mut self) -> Option<TxData> {
+        let commit_just_popped = std::mem::replace(&mut self.commit_just_popped, false);
+        if !self.send_verify_with_commit || !commit_just_popped {
+            return None;
+        }
+
+        // Commit operations are counted by the block numbers, so the `verify` operation for
+        // the just committed block has the ID equal to the amount of `commit` operations.
+        if self.verify_operations.next_id() == self.commit_operations.get_count() {
+            self.verify_operations.pop_front()
+        } else {
+            None
+        }
+    }
+
     /// Obtains the next operation from the underlying queues.
     /// This method does not use/affect `sent_pending_tx` counter.
     fn get_next_operation(&mut self) -> Option<TxData> {
@@ -257,8 +323,10 @@ impl TxQueue {
 
         // 3. Finally, check the commit queue.
 
-        if let Some(commit_operation) = self.commit_operations.pop_front() {
-            return Some(commit_operation);
+        if self.unverified_blocks() < self.max_unverified_blocks {
+            if let Some(commit_operation) = self.commit_operations.pop_front() {
+                return Some(commit_operation);
+            }
         }
 
         // 4. There are no operations to process, return `None`.
@@ -266,6 +334,18 @@ impl TxQueue {
         None
     }
 
+    /// Returns the amount of the blocks which `commit` operations are sent,
+    /// while their `verify` operations are not.
+    pub fn unverified_blocks(&self) -> usize {
+        // `verify` operations are indexed by the block numbers starting from 1.
+        self.commit_operations.get_count() + 1 - self.verify_operations.next_id()
+    }
+
+    /// Returns the amount of operations waiting in the queue to be sent.
+    pub fn len(&self) -> usize {
+        self.commit_operations.len() + self.verify_operations.len() + self.withdraw_operations.len()
+    }
+
     /// Notifies the queue about the transaction being confirmed on the Ethereum blockchain.
     /// Decrements the amount of transactions "in the fly".
     pub fn report_commitment(&mut self) {
@@ -276,6 +356,12 @@ impl TxQueue {
 
         self.sent_pending_txs -= 1;
     }
+
+    /// Notifies the queue about the confirmed transaction being removed from the
+    /// Ethereum blockchain, so it's "in the fly" again.
+    pub fn report_reverted_commitment(&mut self) {
+        self.sent_pending_txs += 1;
+    }
 }
 
 #[cfg(test)]
@@ -319,6 +405,8 @@ mod tests {
             vec![WITHDRAW_MARK, 1],
         ));
 
+        assert_eq!(queue.len(), 6);
+
         // Retrieve the next {MAX_IN_FLY} operations.
 
         // The first operation should be `commit`, since we can't send `verify` before the commitment.
@@ -336,6 +424,7 @@ mod tests {
 
         // After that we have {MAX_IN_FLY} operations, and `pop_front` should yield nothing.
         assert_eq!(queue.pop_front(), None);
+        assert_eq!(queue.len(), 3);
 
         // Report that one operation is completed.
         queue.report_commitment();
@@ -392,4 +481,97 @@ mod tests {
             vec![COMMIT_MARK, 0],
         ));
     }
+
+    /// Checks that the `verify` operation which is ready by the moment of its `commit`
+    /// operation is yielded right after it, even if the pending txs limit is met.
+    #[test]
+    fn verify_sent_with_commit() {
+        const MAX_IN_FLY: usize = 1;
+        const COMMIT_MARK: u8 = 0;
+        const VERIFY_MARK: u8 = 1;
+
+        let mut queue = TxQueueBuilder::new(MAX_IN_FLY)
+            .with_send_verify_with_commit(true)
+            .build();
+
+        queue.add_commit_operation(TxData::from_raw(
+            OperationType::Commit,
+            vec![COMMIT_MARK, 0],
+        ));
+        queue.add_commit_operation(TxData::from_raw(
+            OperationType::Commit,
+            vec![COMMIT_MARK, 1],
+        ));
+        queue.add_verify_operation(
+            1,
+            TxData::from_raw(OperationType::Verify, vec![VERIFY_MARK, 0]),
+        );
+
+        // Verify operation is yielded right after the commit operation for the same block.
+        let op_1 = queue.pop_front().unwrap();
+        assert_eq!(op_1.raw, vec![COMMIT_MARK, 0]);
+        let op_2 = queue.pop_front().unwrap();
+        assert_eq!(op_2.raw, vec![VERIFY_MARK, 0]);
+        assert_eq!(queue.sent_pending_txs, 2);
+        assert_eq!(queue.pop_front(), None);
+
+        queue.report_commitment();
+        queue.report_commitment();
+
+        // Verify operation for the next block is not ready yet, so the limit is respected.
+        let op_3 = queue.pop_front().unwrap();
+        assert_eq!(op_3.raw, vec![COMMIT_MARK, 1]);
+        assert_eq!(queue.pop_front(), None);
+
+        // Once ready, verify operation is sent according to the usual policy.
+        queue.add_verify_operation(
+            2,
+            TxData::from_raw(OperationType::Verify, vec![VERIFY_MARK, 1]),
+        );
+        assert_eq!(queue.pop_front(), None);
+        queue.report_commitment();
+        let op_4 = queue.pop_front().unwrap();
+        assert_eq!(op_4.raw, vec![VERIFY_MARK, 1]);
+    }
+
+    /// Checks that the blocks are not committed ahead of the sent `verify` operations
+    /// beyond the `max_unverified_blocks` limit.
+    #[test]
+    fn unverified_blocks_limit() {
+        const MAX_IN_FLY: usize = 10;
+        const MAX_UNVERIFIED_BLOCKS: usize = 2;
+        const COMMIT_MARK: u8 = 0;
+        const VERIFY_MARK: u8 = 1;
+
+        let mut queue = TxQueueBuilder::new(MAX_IN_FLY)
+            .with_max_unverified_blocks(MAX_UNVERIFIED_BLOCKS)
+            .build();
+
+        for block in 0..4 {
+            queue.add_commit_operation(TxData::from_raw(
+                OperationType::Commit,
+                vec![COMMIT_MARK, block],
+            ));
+        }
+
+        // Blocks are committed while the previous ones are not verified, up to the limit.
+        let op_1 = queue.pop_front().unwrap();
+        assert_eq!(op_1.raw, vec![COMMIT_MARK, 0]);
+        let op_2 = queue.pop_front().unwrap();
+        assert_eq!(op_2.raw, vec![COMMIT_MARK, 1]);
+        assert_eq!(queue.unverified_blocks(), MAX_UNVERIFIED_BLOCKS);
+        assert_eq!(queue.pop_front(), None);
+
+        // Once the first block is verified, the next one is committed.
+        queue.add_verify_operation(
+            1,
+            TxData::from_raw(OperationType::Verify, vec![VERIFY_MARK, 0]),
+        );
+        let op_3 = queue.pop_front().unwrap();
+        assert_eq!(op_3.raw, vec![VERIFY_MARK, 0]);
+        let op_4 = queue.pop_front().unwrap();
+        assert_eq!(op_4.raw, vec![COMMIT_MARK, 2]);
+        assert_eq!(queue.unverified_blocks(), MAX_UNVERIFIED_BLOCKS);
+        assert_eq!(queue.pop_front(), None);
+    }
 }
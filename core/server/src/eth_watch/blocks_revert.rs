@@ -0,0 +1,70 @@
+// External uses
+use ethabi::ParamType;
+use failure::format_err;
+use web3::types::{Log, H256};
+// Workspace deps
+use models::node::BlockNumber;
+// Local deps
+use super::EthBlockId;
+
+/// `BlocksRevert` event of the zkSync contract, emitted once the committed blocks which
+/// were not verified in time are reverted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlocksRevertEvent {
+    pub total_blocks_verified: BlockNumber,
+    /// Last block which remains committed, the blocks after it are reverted.
+    pub total_blocks_committed: BlockNumber,
+    pub eth_block: EthBlockId,
+}
+
+impl BlocksRevertEvent {
+    pub fn topic(contract: &ethabi::Contract) -> H256 {
+        contract
+            .event("BlocksRevert")
+            .expect("main contract abi error")
+            .signature()
+    }
+
+    /// Parses the `BlocksRevert` event log of the zkSync contract.
+    pub fn parse(log: &Log) -> Result<Self, failure::Error> {
+        let (total_blocks_verified, total_blocks_committed) = Self::decode_data(&log.data.0)?;
+        let eth_block = log
+            .block_number
+            .ok_or_else(|| format_err!("Blocks revert event without the block number: {:?}", log))?
+            .as_u64();
+
+        Ok(Self {
+            total_blocks_verified,
+            total_blocks_committed,
+            eth_block,
+        })
+    }
+
+    /// Decodes the event arguments, which are not indexed.
+    fn decode_data(data: &[u8]) -> Result<(BlockNumber, BlockNumber), failure::Error> {
+        let tokens = ethabi::decode(&[ParamType::Uint(32), ParamType::Uint(32)], data)
+            .map_err(|e| format_err!("Failed to decode blocks revert event: {}", e))?;
+        let mut blocks = tokens.into_iter().map(|token| {
+            token
+                .to_uint()
+                .map(|block| block.as_u32())
+                .ok_or_else(|| format_err!("Blocks revert event argument is not a number"))
+        });
+        let total_blocks_verified = blocks.next().unwrap()?;
+        let total_blocks_committed = blocks.next().unwrap()?;
+        Ok((total_blocks_verified, total_blocks_committed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethabi::Token;
+
+    #[test]
+    fn blocks_revert_data() {
+        let data = ethabi::encode(&[Token::Uint(10.into()), Token::Uint(12.into())]);
+        assert_eq!(BlocksRevertEvent::decode_data(&data).unwrap(), (10, 12));
+        assert!(BlocksRevertEvent::decode_data(&data[..32]).is_err());
+    }
+}
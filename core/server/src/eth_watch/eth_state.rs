@@ -1,11 +1,15 @@
 // Built-in deps
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 // External uses
+use web3::types::H256;
 // Workspace deps
 use models::node::PriorityOp;
 // Local deps
 use super::{received_ops::ReceivedPriorityOp, EthBlockId};
 
+/// Maximum amount of the processed blocks which hashes are tracked to detect the reorgs.
+const MAX_TRACKED_BLOCKS: usize = 64;
+
 /// Gathered state of the Ethereum network.
 /// Contains information about the known token types and incoming
 /// priority operations (such as `Deposit` and `FullExit`).
@@ -29,6 +33,9 @@ pub struct ETHState {
     /// Queue of priority operations that passed the confirmation
     /// threshold and are waiting to be executed.
     priority_queue: HashMap<u64, ReceivedPriorityOp>,
+    /// Hashes of the recently processed blocks (the last known block at the moment
+    /// of every processing), used to detect the chain reorganizations.
+    block_hashes: BTreeMap<EthBlockId, H256>,
 }
 
 impl ETHState {
@@ -36,11 +43,13 @@ impl ETHState {
         last_ethereum_block: u64,
         unconfirmed_queue: Vec<(EthBlockId, PriorityOp)>,
         priority_queue: HashMap<u64, ReceivedPriorityOp>,
+        block_hashes: BTreeMap<EthBlockId, H256>,
     ) -> Self {
         Self {
             last_ethereum_block,
             unconfirmed_queue,
             priority_queue,
+            block_hashes,
         }
     }
 
@@ -55,4 +64,129 @@ impl ETHState {
     pub fn unconfirmed_queue(&self) -> &[(EthBlockId, PriorityOp)] {
         &self.unconfirmed_queue
     }
+
+    pub fn block_hashes(&self) -> &BTreeMap<EthBlockId, H256> {
+        &self.block_hashes
+    }
+
+    /// Returns the priority operations with the serial ids starting from the provided one.
+    pub fn ops_starting_from(
+        &self,
+        first_serial_id: u64,
+    ) -> impl Iterator<Item = &ReceivedPriorityOp> {
+        self.priority_queue
+            .iter()
+            .filter(move |(serial_id, _)| **serial_id >= first_serial_id)
+            .map(|(_, op)| op)
+    }
+
+    /// Returns the tracked block hashes extended with the hash of the newly processed block.
+    /// Only the `MAX_TRACKED_BLOCKS` most recent blocks are kept.
+    pub fn block_hashes_with(&self, block: EthBlockId, hash: H256) -> BTreeMap<EthBlockId, H256> {
+        let mut block_hashes = self.block_hashes.clone();
+        block_hashes.insert(block, hash);
+        while block_hashes.len() > MAX_TRACKED_BLOCKS {
+            let oldest_block = *block_hashes.keys().next().unwrap();
+            block_hashes.remove(&oldest_block);
+        }
+        block_hashes
+    }
+
+    /// Returns the state as if only the blocks up to the provided one were processed,
+    /// along with the priority operations accepted from the blocks after it.
+    /// Unconfirmed operations are dropped, since they are reloaded on every update.
+    pub fn rolled_back(&self, block: EthBlockId) -> (Self, Vec<PriorityOp>) {
+        let (priority_queue, reverted): (HashMap<_, _>, HashMap<_, _>) = self
+            .priority_queue
+            .clone()
+            .into_iter()
+            .partition(|(_, op)| op.as_ref().eth_block <= block);
+        let mut reverted_ops: Vec<_> = reverted
+            .into_iter()
+            .map(|(_, op)| op.as_ref().clone())
+            .collect();
+        reverted_ops.sort_by_key(|op| op.serial_id);
+
+        let block_hashes = self
+            .block_hashes
+            .range(..=block)
+            .map(|(block, hash)| (*block, *hash))
+            .collect();
+        let state = Self::new(block, Vec::new(), priority_queue, block_hashes);
+        (state, reverted_ops)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::{Deposit, FranklinPriorityOp};
+
+    fn priority_op(serial_id: u64, eth_block: EthBlockId) -> PriorityOp {
+        PriorityOp {
+            serial_id,
+            data: FranklinPriorityOp::Deposit(Deposit {
+                from: Default::default(),
+                token: 0,
+                amount: 100u32.into(),
+                to: Default::default(),
+            }),
+            deadline_block: eth_block + 100,
+            eth_hash: vec![serial_id as u8; 32],
+            eth_block,
+        }
+    }
+
+    /// Checks that the rollback removes the operations and block hashes of the orphaned blocks.
+    #[test]
+    fn rollback() {
+        let priority_queue = vec![priority_op(0, 10), priority_op(1, 11), priority_op(2, 12)]
+            .into_iter()
+            .map(|op| (op.serial_id, op.into()))
+            .collect();
+        let block_hashes = (10..=13)
+            .map(|block| (block, H256::from_low_u64_be(block)))
+            .collect();
+        let state = ETHState::new(13, Vec::new(), priority_queue, block_hashes);
+
+        let (state, reverted_ops) = state.rolled_back(10);
+        assert_eq!(state.last_ethereum_block(), 10);
+        assert_eq!(state.priority_queue().len(), 1);
+        assert!(state.priority_queue().contains_key(&0));
+        assert_eq!(
+            state.block_hashes().keys().cloned().collect::<Vec<_>>(),
+            vec![10]
+        );
+
+        let reverted: Vec<_> = reverted_ops.iter().map(|op| op.serial_id).collect();
+        assert_eq!(reverted, vec![1, 2]);
+    }
+
+    #[test]
+    fn ops_starting_from() {
+        let priority_queue = (0..5)
+            .map(|serial_id| (serial_id, priority_op(serial_id, 10).into()))
+            .collect();
+        let state = ETHState::new(10, Vec::new(), priority_queue, BTreeMap::new());
+
+        let mut serial_ids: Vec<_> = state
+            .ops_starting_from(3)
+            .map(|op| op.as_ref().serial_id)
+            .collect();
+        serial_ids.sort();
+        assert_eq!(serial_ids, vec![3, 4]);
+        assert_eq!(state.ops_starting_from(5).count(), 0);
+    }
+
+    /// Checks that only the most recent block hashes are tracked.
+    #[test]
+    fn tracked_blocks_limit() {
+        let mut state = ETHState::default();
+        for block in 0..(MAX_TRACKED_BLOCKS as u64 + 5) {
+            let block_hashes = state.block_hashes_with(block, H256::from_low_u64_be(block));
+            state = ETHState::new(block, Vec::new(), HashMap::new(), block_hashes);
+        }
+        assert_eq!(state.block_hashes().len(), MAX_TRACKED_BLOCKS);
+        assert_eq!(state.block_hashes().keys().next(), Some(&5));
+    }
 }
@@ -0,0 +1,84 @@
+// Local deps
+use super::EthBlockId;
+
+/// Status of the exodus mode of the zkSync contract.
+///
+/// The contract enters the exodus mode once anyone calls `triggerExodusIfNeeded` after
+/// the oldest priority request not verified yet is expired. Blocks can't be committed
+/// or verified afterwards, and users withdraw their funds with the exit proofs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExodusStatus {
+    /// Priority requests are verified in time.
+    Inactive,
+    /// The oldest priority request not verified in the contract expires within the margin
+    /// (or is already expired), so the exodus mode can be triggered soon.
+    Imminent,
+    /// Exodus mode is entered in the contract.
+    Active,
+}
+
+impl ExodusStatus {
+    /// Determines the status from the contract state: whether the exodus mode is entered, and
+    /// the expiration block of the oldest priority request not verified yet (if any).
+    pub fn from_contract_state(
+        exodus_mode: bool,
+        first_request_expiration: Option<EthBlockId>,
+        current_eth_block: EthBlockId,
+        margin: u64,
+    ) -> Self {
+        if exodus_mode {
+            return ExodusStatus::Active;
+        }
+        match first_request_expiration {
+            Some(expiration) if current_eth_block + margin >= expiration => ExodusStatus::Imminent,
+            _ => ExodusStatus::Inactive,
+        }
+    }
+
+    /// Block production is halted once the exodus mode is entered, since the blocks
+    /// can't be committed anymore.
+    pub fn halts_block_production(self) -> bool {
+        self == ExodusStatus::Active
+    }
+
+    /// New transactions are rejected once the exodus mode can be triggered, since they're
+    /// likely to be lost: funds are withdrawn from the last verified state.
+    pub fn rejects_txs(self) -> bool {
+        self != ExodusStatus::Inactive
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_from_contract_state() {
+        let margin = 10;
+        assert_eq!(
+            ExodusStatus::from_contract_state(false, None, 1000, margin),
+            ExodusStatus::Inactive
+        );
+        assert_eq!(
+            ExodusStatus::from_contract_state(false, Some(1011), 1000, margin),
+            ExodusStatus::Inactive
+        );
+        assert_eq!(
+            ExodusStatus::from_contract_state(false, Some(1010), 1000, margin),
+            ExodusStatus::Imminent
+        );
+        assert_eq!(
+            ExodusStatus::from_contract_state(false, Some(900), 1000, margin),
+            ExodusStatus::Imminent
+        );
+        assert_eq!(
+            ExodusStatus::from_contract_state(true, None, 1000, margin),
+            ExodusStatus::Active
+        );
+
+        assert!(!ExodusStatus::Imminent.halts_block_production());
+        assert!(ExodusStatus::Imminent.rejects_txs());
+        assert!(ExodusStatus::Active.halts_block_production());
+        assert!(!ExodusStatus::Inactive.rejects_txs());
+    }
+}
@@ -4,10 +4,75 @@
 //!
 //! Poll interval is configured using the `ETH_POLL_INTERVAL` constant.
 //! Number of confirmations is configured using the `CONFIRMATIONS_FOR_ETH_EVENT` environment variable.
+//!
+//! If the WebSocket endpoint of the Ethereum node is configured (`WEB3_WS_URL`), the node is
+//! polled upon every new block reported by the `newHeads` subscription instead. Once the
+//! subscription fails, the node is polled by the timer until the subscription is restored.
+//!
+//! Tokens added to the Governance contract (`NewToken` events) are stored in the database once
+//! confirmed, so the new tokens are available without the server restart. API token caches
+//! read through to the database on a miss, so they serve the added tokens right away.
+//! Precision of the token is queried from the token contract (see `NewTokenEvent::resolve_token`).
+//!
+//! Accepted priority operations are persisted in the database along with the last processed
+//! block, so after restart the priority queue is restored from the database, and the blocks
+//! after the last processed one (including the ones emitted while the server was down) are
+//! scanned in batches of `MAX_BLOCKS_PER_SCAN` blocks.
+//!
+//! Ethereum node endpoints configured by `WEB3_URL` and `WEB3_FALLBACK_URLS` are switched
+//! upon failures (see `FailoverTransport`). Processed blocks are checked against the new
+//! endpoint once it reaches them, so the reorgs are detected as usual.
+//!
+//! Failed requests to the Ethereum node are classified: upon transient errors (e.g. the node
+//! is unavailable or rate limits the requests) polling is paused with the jittered
+//! exponential backoff, while other errors are reported and the node is polled as usual.
+//!
+//! Hashes of the processed blocks are tracked to detect the chain reorganizations deeper than
+//! the confirmations threshold. Once the processed block is not in the canonical chain anymore,
+//! priority operations accepted from the orphaned blocks are removed from the queue (and from
+//! the database), the blocks after the last canonical one are scanned again, and the removed
+//! operations are sent to the reorg subscribers (see `EthWatchRequest::SubscribeRevertedOps`).
+//!
+//! Status of the contracts upgrade is tracked by the events of the upgrade gatekeeper contract
+//! (and is queried from the contract on startup). Once the upgrade may be finished at any
+//! moment (preparation is started), or is already finished, the block production is halted
+//! (see `CurrentZksyncInfo`), so no blocks are committed to the contracts of the version
+//! unknown to the server. Production is resumed if the upgrade is canceled, while the
+//! finished upgrade keeps it halted until the server (supporting the new version) is restarted.
+//! All the status changes are reported as errors, so the operator is alerted.
+//!
+//! Once the committed blocks are reverted in the contract (`BlocksRevert` event), the block
+//! production is halted, the blocks after the last one remaining committed are rolled back
+//! in the database (their transactions are returned to the mempool, and the priority
+//! operations are returned to the queue), and the server is stopped, so after the restart
+//! the blocks are proposed and committed again. If the blocks can't be rolled back
+//! automatically (e.g. they're already verified by the server), the production stays halted
+//! and the error is reported, so the operator is alerted.
+//!
+//! Exodus mode of the zkSync contract is checked on every poll. While the oldest priority
+//! request not verified in the contract expires within `PRIORITY_OP_DEADLINE_MARGIN` blocks,
+//! the exodus mode can be triggered by anyone, so new transactions are rejected (they would
+//! be lost), while the blocks keep being committed and verified, since verifying the request
+//! in time is the only way to avoid the exodus mode. Once the contract enters the exodus mode,
+//! the block production is halted for good, `eth_sender` stops sending the blocks, and the
+//! exit proofs are served (see `exit_proof_server`). All the status changes are reported as
+//! errors, so the operator is alerted.
+//!
+//! Ethereum watcher reports the priority queue metrics: the amount of the operations not
+//! executed yet and the age of the oldest one, the amount of the blocks it's behind the node
+//! head, and the time passed between the operation confirmation and its execution. Operations
+//! preceding the one requested by the state keeper are considered executed. Age and latency
+//! are measured from the moment the operation is confirmed, or from the restart for the
+//! operations confirmed before it.
+//!
+//! Execution latency is compared against the inclusion SLA (`PRIORITY_OP_SLA`). Once the oldest
+//! operation not executed yet breaches it, the error is reported (once per operation), so the
+//! operator is alerted about the stalled priority queue, and the breaches are counted in
+//! the metrics.
 
 // Built-in deps
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::TryFrom,
     time::{Duration, Instant},
 };
@@ -15,44 +80,72 @@ use std::{
 use failure::format_err;
 use futures::{
     channel::{mpsc, oneshot},
-    compat::Future01CompatExt,
+    compat::{Future01CompatExt, Stream01CompatExt},
     SinkExt, StreamExt,
 };
 use tokio::{runtime::Runtime, task::JoinHandle, time};
 use web3::{
     contract::{Contract, Options},
     transports::EventLoopHandle,
-    types::{Address, BlockNumber, Filter, FilterBuilder, H160},
+    types::{Address, BlockId, BlockNumber, Filter, FilterBuilder, H160, H256, U256},
     Transport, Web3,
 };
 // Workspace deps
 use models::{
-    abi::{eip1271_contract, zksync_contract},
+    abi::{eip1271_contract, governance_contract, upgrade_gatekeeper_contract, zksync_contract},
     config_options::ConfigurationOptions,
     misc::constants::EIP1271_SUCCESS_RETURN_VALUE,
     node::tx::EIP1271Signature,
     node::{FranklinPriorityOp, Nonce, PriorityOp, PubKeyHash},
     params::PRIORITY_EXPIRATION,
+    NewTokenEvent,
 };
+use storage::ConnectionPool;
 // Local deps
-use self::{eth_state::ETHState, received_ops::sift_outdated_ops};
+use self::{
+    blocks_revert::BlocksRevertEvent,
+    eth_state::ETHState,
+    exodus::ExodusStatus,
+    priority_sla::PrioritySlaTracker,
+    received_ops::sift_outdated_ops,
+    upgrade_events::{UpgradeEvent, UpgradeEventKind, UpgradeStatus},
+};
+use crate::utils::{
+    backoff::{is_transient_error, ExponentialBackoff},
+    current_zksync_info::CurrentZksyncInfo,
+    failover_transport::FailoverTransport,
+    metrics_registry::metrics_registry,
+};
 
+mod blocks_revert;
 mod eth_state;
+mod exodus;
+mod priority_sla;
 mod received_ops;
+mod upgrade_events;
+
+/// Minimum delay before repeating the request failed with the transient error.
+const MIN_BACKOFF_DELAY: Duration = Duration::from_secs(1);
 
-/// As `infura` may limit the requests, upon error we need to wait for a while
-/// before repeating the request.
-const RATE_LIMIT_DELAY: Duration = Duration::from_secs(30);
+/// Maximum delay before repeating the request failed with the transient error.
+const MAX_BACKOFF_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Maximum amount of blocks scanned by a single request while catching up after the restart.
+const MAX_BLOCKS_PER_SCAN: u64 = 1000;
+
+/// Interval between the attempts to restore the failed `newHeads` subscription,
+/// during which the Ethereum node is polled by the timer.
+const SUBSCRIPTION_RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
 pub type EthBlockId = u64;
 
 /// Ethereum Watcher operating mode.
 ///
 /// Normally Ethereum watcher will always poll the Ethereum node upon request,
-/// but unfortunately `infura` may decline requests if they are produced too
-/// often. Thus, upon receiving the order to limit amount of request, Ethereum
-/// watcher goes into "backoff" mode in which polling is disabled for a
-/// certain amount of time.
+/// but the node may be unavailable, or `infura` may decline requests if they are
+/// produced too often. Thus, upon the transient error, Ethereum watcher goes into
+/// "backoff" mode in which polling is disabled for a certain amount of time,
+/// growing exponentially while the errors repeat.
 #[derive(Debug)]
 enum WatcherMode {
     /// ETHWatcher operates normally.
@@ -78,7 +171,15 @@ pub enum EthWatchRequest {
         address: Address,
         resp: oneshot::Sender<Vec<(EthBlockId, PriorityOp)>>,
     },
-    GetUnconfirmedOpByHash {
+    /// Requests the deposits which have enough confirmations and are added to the priority
+    /// queue. Note that the queue may still contain the deposits already executed in zkSync.
+    GetConfirmedDeposits {
+        address: Address,
+        resp: oneshot::Sender<Vec<PriorityOp>>,
+    },
+    /// Requests the priority operation which is either awaiting confirmations or
+    /// confirmed and added to the priority queue.
+    GetOngoingOpByHash {
         eth_hash: Vec<u8>,
         resp: oneshot::Sender<Option<(EthBlockId, PriorityOp)>>,
     },
@@ -88,10 +189,20 @@ pub enum EthWatchRequest {
         signature: EIP1271Signature,
         resp: oneshot::Sender<Result<bool, failure::Error>>,
     },
+    GetLastEthereumBlock {
+        resp: oneshot::Sender<u64>,
+    },
+    /// Subscribes to the priority operations removed from the queue by the chain
+    /// reorganizations. Operations removed by every reorg are sent as a single batch.
+    SubscribeRevertedOps {
+        sender: mpsc::Sender<Vec<PriorityOp>>,
+    },
 }
 
 pub struct EthWatch<T: Transport> {
     zksync_contract: (ethabi::Contract, Contract<T>),
+    governance_contract: (ethabi::Contract, Contract<T>),
+    upgrade_gatekeeper_contract: (ethabi::Contract, Contract<T>),
     eth_state: ETHState,
     web3: Web3<T>,
     _web3_event_loop_handle: EventLoopHandle,
@@ -99,17 +210,54 @@ pub struct EthWatch<T: Transport> {
     number_of_confirmations_for_event: u64,
 
     mode: WatcherMode,
+    backoff: ExponentialBackoff,
 
     eth_watch_req: mpsc::Receiver<EthWatchRequest>,
+
+    /// Connection to the database used to persist the accepted priority operations.
+    /// If not set, the priority queue is restored from the Ethereum node only.
+    db_pool: Option<ConnectionPool>,
+
+    /// Subscribers notified about the priority operations removed by the reorgs.
+    reverted_ops_subscribers: Vec<mpsc::Sender<Vec<PriorityOp>>>,
+
+    /// Serial id of the first priority operation not executed yet, as requested
+    /// by the state keeper. Not known until the first request after the restart.
+    next_unexecuted_serial_id: Option<u64>,
+
+    /// Status of the contracts upgrade.
+    upgrade_status: UpgradeStatus,
+    /// Used to halt the block production during the contracts upgrade.
+    /// If not set, the upgrade status changes are only reported.
+    current_zksync_info: Option<CurrentZksyncInfo>,
+    /// Used to restart the server once the reverted blocks are rolled back.
+    stop_signal_sender: Option<mpsc::Sender<bool>>,
+
+    /// Status of the exodus mode of the zkSync contract.
+    exodus_status: ExodusStatus,
+    /// Amount of the Ethereum blocks before the priority request expiration once
+    /// the exodus mode is considered imminent.
+    exodus_margin: u64,
+
+    /// Inclusion SLA of the priority operations.
+    priority_sla: PrioritySlaTracker,
 }
 
 impl<T: Transport> EthWatch<T> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         web3: Web3<T>,
         web3_event_loop_handle: EventLoopHandle,
         zksync_contract_addr: H160,
+        governance_contract_addr: H160,
+        upgrade_gatekeeper_contract_addr: H160,
         number_of_confirmations_for_event: u64,
         eth_watch_req: mpsc::Receiver<EthWatchRequest>,
+        db_pool: Option<ConnectionPool>,
+        current_zksync_info: Option<CurrentZksyncInfo>,
+        stop_signal_sender: Option<mpsc::Sender<bool>>,
+        exodus_margin: u64,
+        priority_op_sla: Duration,
     ) -> Self {
         let zksync_contract = {
             (
@@ -117,17 +265,144 @@ impl<T: Transport> EthWatch<T> {
                 Contract::new(web3.eth(), zksync_contract_addr, zksync_contract()),
             )
         };
+        let governance_contract = {
+            (
+                governance_contract(),
+                Contract::new(web3.eth(), governance_contract_addr, governance_contract()),
+            )
+        };
+        let upgrade_gatekeeper_contract = {
+            (
+                upgrade_gatekeeper_contract(),
+                Contract::new(
+                    web3.eth(),
+                    upgrade_gatekeeper_contract_addr,
+                    upgrade_gatekeeper_contract(),
+                ),
+            )
+        };
 
         Self {
             zksync_contract,
+            governance_contract,
+            upgrade_gatekeeper_contract,
             eth_state: ETHState::default(),
             web3,
             _web3_event_loop_handle: web3_event_loop_handle,
             eth_watch_req,
 
             mode: WatcherMode::Working,
+            backoff: ExponentialBackoff::new(MIN_BACKOFF_DELAY, MAX_BACKOFF_DELAY),
             number_of_confirmations_for_event,
+            db_pool,
+            reverted_ops_subscribers: Vec::new(),
+            next_unexecuted_serial_id: None,
+            upgrade_status: UpgradeStatus::Idle,
+            current_zksync_info,
+            stop_signal_sender,
+            exodus_status: ExodusStatus::Inactive,
+            exodus_margin,
+            priority_sla: PrioritySlaTracker::new(priority_op_sla),
+        }
+    }
+
+    /// Loads the stored priority operations which were not executed yet.
+    fn load_stored_priority_ops(&self) -> Result<Vec<PriorityOp>, failure::Error> {
+        let db_pool = match &self.db_pool {
+            Some(db_pool) => db_pool,
+            None => return Ok(Vec::new()),
+        };
+
+        let storage = db_pool.access_storage()?;
+        let removed = storage.ethereum_schema().remove_executed_priority_ops()?;
+        debug!(
+            "Removed {} executed priority ops from the database",
+            removed
+        );
+
+        Ok(storage.ethereum_schema().load_priority_ops()?)
+    }
+
+    /// Persists the accepted priority operations.
+    fn store_priority_ops(&self, ops: &[PriorityOp]) -> Result<(), failure::Error> {
+        if let Some(db_pool) = &self.db_pool {
+            let storage = db_pool.access_storage()?;
+            storage.ethereum_schema().save_priority_ops(ops)?;
         }
+
+        Ok(())
+    }
+
+    /// Stores the tokens added to the Governance contract within the provided range of blocks.
+    /// Tokens are not tracked if the database is not available.
+    async fn store_new_tokens(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<(), failure::Error> {
+        let db_pool = match &self.db_pool {
+            Some(db_pool) => db_pool,
+            None => return Ok(()),
+        };
+
+        let token_events = self.get_new_token_events(from, to).await?;
+        if token_events.is_empty() {
+            return Ok(());
+        }
+
+        let storage = db_pool.access_storage()?;
+        for event in token_events {
+            let token = event.resolve_token(&self.web3).await;
+            info!("New token added to the Governance contract: {:?}", token);
+            storage.tokens_schema().store_token(token)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the stored priority operations accepted from the blocks after the provided one,
+    /// and moves the stored cursor back to it, so these blocks are scanned after the restart.
+    fn remove_stored_priority_ops_after(&self, block: EthBlockId) -> Result<(), failure::Error> {
+        if let Some(db_pool) = &self.db_pool {
+            let storage = db_pool.access_storage()?;
+            let removed = storage
+                .ethereum_schema()
+                .remove_priority_ops_after_block(block)?;
+            debug!("Removed {} reorged priority ops from the database", removed);
+
+            let last_processed_block = storage.ethereum_schema().load_last_processed_eth_block()?;
+            if last_processed_block.map_or(false, |last_processed| last_processed > block) {
+                storage
+                    .ethereum_schema()
+                    .update_last_processed_eth_block(block)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads the last block which events were processed before the restart.
+    fn load_last_processed_block(&self) -> Result<Option<EthBlockId>, failure::Error> {
+        let db_pool = match &self.db_pool {
+            Some(db_pool) => db_pool,
+            None => return Ok(None),
+        };
+
+        let storage = db_pool.access_storage()?;
+        Ok(storage.ethereum_schema().load_last_processed_eth_block()?)
+    }
+
+    /// Persists the last block which events were processed (and stored), so the scan
+    /// is continued from the next block after the restart.
+    fn store_last_processed_block(&self, block: EthBlockId) -> Result<(), failure::Error> {
+        if let Some(db_pool) = &self.db_pool {
+            let storage = db_pool.access_storage()?;
+            storage
+                .ethereum_schema()
+                .update_last_processed_eth_block(block)?;
+        }
+
+        Ok(())
     }
 
     /// Atomically replaces the stored Ethereum state.
@@ -154,6 +429,310 @@ impl<T: Transport> EthWatch<T> {
             .build()
     }
 
+    /// Filters and parses the `NewToken` events of the Governance contract
+    /// within the provided range of blocks.
+    async fn get_new_token_events(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<NewTokenEvent>, failure::Error> {
+        let new_token_event_topic = self
+            .governance_contract
+            .0
+            .event("NewToken")
+            .expect("governance contract abi error")
+            .signature();
+        let filter = FilterBuilder::default()
+            .address(vec![self.governance_contract.1.address()])
+            .from_block(from)
+            .to_block(to)
+            .topics(Some(vec![new_token_event_topic]), None, None, None)
+            .build();
+        self.web3
+            .eth()
+            .logs(filter)
+            .compat()
+            .await?
+            .into_iter()
+            .map(|event| {
+                NewTokenEvent::try_from(event)
+                    .map_err(|e| format_err!("Failed to parse NewToken event log from ETH: {}", e))
+            })
+            .collect()
+    }
+
+    /// Filters and parses the upgrade events of the upgrade gatekeeper contract
+    /// within the provided range of blocks.
+    async fn get_upgrade_events(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<UpgradeEvent>, failure::Error> {
+        let upgrade_event_topics = UpgradeEventKind::topics(&self.upgrade_gatekeeper_contract.0);
+        let filter = FilterBuilder::default()
+            .address(vec![self.upgrade_gatekeeper_contract.1.address()])
+            .from_block(from)
+            .to_block(to)
+            .topics(Some(upgrade_event_topics), None, None, None)
+            .build();
+        self.web3
+            .eth()
+            .logs(filter)
+            .compat()
+            .await?
+            .iter()
+            .map(|log| UpgradeEvent::parse(&self.upgrade_gatekeeper_contract.0, log))
+            .collect()
+    }
+
+    /// Queries the current status of the contracts upgrade from the upgrade gatekeeper contract.
+    async fn get_upgrade_status(&self) -> Result<UpgradeStatus, failure::Error> {
+        let status: U256 = self
+            .upgrade_gatekeeper_contract
+            .1
+            .query("upgradeStatus", (), None, Options::default(), None)
+            .compat()
+            .await
+            .map_err(|e| format_err!("Failed to query contract upgradeStatus: {}", e))?;
+        UpgradeStatus::from_contract_status(status)
+    }
+
+    /// Updates the status of the contracts upgrade, halting or resuming the block production
+    /// if needed, and alerting the operator about the change.
+    fn set_upgrade_status(&mut self, status: UpgradeStatus, version_id: Option<u64>) {
+        if status == self.upgrade_status {
+            return;
+        }
+
+        let version = version_id.map_or_else(|| "unknown".to_string(), |id| id.to_string());
+        match status {
+            UpgradeStatus::Idle => error!("Contracts upgrade is canceled (version {})", version),
+            UpgradeStatus::NoticePeriod => error!(
+                "Contracts upgrade is scheduled (version {}), the server supporting the new \
+                 version must be deployed before the upgrade is finished",
+                version
+            ),
+            UpgradeStatus::Preparation => error!(
+                "Contracts upgrade preparation is started (version {}), block production is halted",
+                version
+            ),
+            UpgradeStatus::Completed => error!(
+                "Contracts are upgraded to the version {}, block production is halted \
+                 until the server supporting the new version is restarted",
+                version
+            ),
+        }
+        metrics_registry().set_gauge(
+            "eth_watch_contract_upgrade_status",
+            "Status of the contracts upgrade: 0 - idle, 1 - notice period, 2 - preparation, \
+             3 - completed",
+            status as u8 as f64,
+        );
+
+        self.upgrade_status = status;
+        if let Some(current_zksync_info) = &self.current_zksync_info {
+            current_zksync_info.set_contract_upgrade_pending(status.halts_block_production());
+        }
+    }
+
+    /// Applies the upgrade events to the status of the contracts upgrade.
+    fn process_upgrade_events(&mut self, events: Vec<UpgradeEvent>) {
+        for event in events {
+            debug!("Contracts upgrade event: {:?}", event);
+            let status = self.upgrade_status.apply(event.kind);
+            self.set_upgrade_status(status, Some(event.version_id));
+        }
+    }
+
+    /// Queries the status of the exodus mode from the zkSync contract.
+    async fn get_exodus_status(
+        &self,
+        current_eth_block: EthBlockId,
+    ) -> Result<ExodusStatus, failure::Error> {
+        let contract = &self.zksync_contract.1;
+        let exodus_mode: bool = contract
+            .query("exodusMode", (), None, Options::default(), None)
+            .compat()
+            .await
+            .map_err(|e| format_err!("Failed to query contract exodusMode: {}", e))?;
+        let open_requests: u64 = contract
+            .query(
+                "totalOpenPriorityRequests",
+                (),
+                None,
+                Options::default(),
+                None,
+            )
+            .compat()
+            .await
+            .map_err(|e| {
+                format_err!("Failed to query contract totalOpenPriorityRequests: {}", e)
+            })?;
+        let first_request_expiration = if open_requests > 0 {
+            let first_request_id: u64 = contract
+                .query("firstPriorityRequestId", (), None, Options::default(), None)
+                .compat()
+                .await
+                .map_err(|e| {
+                    format_err!("Failed to query contract firstPriorityRequestId: {}", e)
+                })?;
+            let (_op_type, _pub_data, expiration_block): (U256, Vec<u8>, U256) = contract
+                .query(
+                    "priorityRequests",
+                    (first_request_id,),
+                    None,
+                    Options::default(),
+                    None,
+                )
+                .compat()
+                .await
+                .map_err(|e| format_err!("Failed to query contract priorityRequests: {}", e))?;
+            Some(expiration_block.as_u64())
+        } else {
+            None
+        };
+
+        Ok(ExodusStatus::from_contract_state(
+            exodus_mode,
+            first_request_expiration,
+            current_eth_block,
+            self.exodus_margin,
+        ))
+    }
+
+    /// Updates the status of the exodus mode, halting the block production and rejecting
+    /// the transactions if needed, and alerting the operator about the change.
+    fn set_exodus_status(&mut self, status: ExodusStatus) {
+        if status == self.exodus_status {
+            return;
+        }
+
+        match status {
+            ExodusStatus::Inactive => {
+                error!("Exodus mode can't be triggered anymore, transactions are accepted")
+            }
+            ExodusStatus::Imminent => error!(
+                "Priority request expires within {} Ethereum blocks, exodus mode can be \
+                 triggered soon, transactions are rejected",
+                self.exodus_margin
+            ),
+            ExodusStatus::Active => error!(
+                "Contract entered the exodus mode, block production is halted and exit proofs \
+                 are served"
+            ),
+        }
+        metrics_registry().set_gauge(
+            "eth_watch_exodus_status",
+            "Status of the exodus mode: 0 - inactive, 1 - imminent, 2 - active",
+            status as u8 as f64,
+        );
+
+        self.exodus_status = status;
+        if let Some(current_zksync_info) = &self.current_zksync_info {
+            current_zksync_info.set_exodus_imminent(status.rejects_txs());
+            if status.halts_block_production() {
+                current_zksync_info.set_exodus_mode();
+            }
+        }
+    }
+
+    /// Checks the status of the exodus mode unless it's already entered, since the contract
+    /// can't leave it.
+    async fn keep_exodus_status_checked(
+        &mut self,
+        current_eth_block: EthBlockId,
+    ) -> Result<(), failure::Error> {
+        if self.exodus_status == ExodusStatus::Active {
+            return Ok(());
+        }
+        let status = self.get_exodus_status(current_eth_block).await?;
+        self.set_exodus_status(status);
+        Ok(())
+    }
+
+    /// Filters and parses the `BlocksRevert` events of the zkSync contract
+    /// within the provided range of blocks.
+    async fn get_blocks_revert_events(
+        &self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<Vec<BlocksRevertEvent>, failure::Error> {
+        let filter = FilterBuilder::default()
+            .address(vec![self.zksync_contract.1.address()])
+            .from_block(from)
+            .to_block(to)
+            .topics(
+                Some(vec![BlocksRevertEvent::topic(&self.zksync_contract.0)]),
+                None,
+                None,
+                None,
+            )
+            .build();
+        self.web3
+            .eth()
+            .logs(filter)
+            .compat()
+            .await?
+            .iter()
+            .map(BlocksRevertEvent::parse)
+            .collect()
+    }
+
+    /// Rolls back the blocks reverted in the contract within the provided range of blocks,
+    /// and stops the server, so the blocks are committed again after the restart.
+    /// Reverts are not tracked if the database is not available.
+    ///
+    /// Must be called once the priority operations of the range are stored, since
+    /// the range is considered processed after the rollback.
+    async fn process_blocks_revert_events(
+        &mut self,
+        from: BlockNumber,
+        to: BlockNumber,
+    ) -> Result<(), failure::Error> {
+        let db_pool = match &self.db_pool {
+            Some(db_pool) => db_pool.clone(),
+            None => return Ok(()),
+        };
+
+        // Blocks are reverted starting from the last one, so only the last revert matters.
+        let event = match self.get_blocks_revert_events(from, to).await?.pop() {
+            Some(event) => event,
+            None => return Ok(()),
+        };
+        let storage = db_pool.access_storage()?;
+        let last_committed_block = storage.chain().block_schema().get_last_committed_block()?;
+        if last_committed_block <= event.total_blocks_committed {
+            // Reverted blocks are already rolled back.
+            return Ok(());
+        }
+
+        error!(
+            "Blocks after {} are reverted in the contract (last verified block is {}), \
+             block production is halted",
+            event.total_blocks_committed, event.total_blocks_verified
+        );
+        if let Some(current_zksync_info) = &self.current_zksync_info {
+            current_zksync_info.set_blocks_reverted(true);
+        }
+        let removed_blocks = storage
+            .chain()
+            .block_schema()
+            .rollback_blocks(event.total_blocks_committed)
+            .map_err(|e| format_err!("Failed to roll back the reverted blocks: {}", e))?;
+        // The event must not be processed again once the blocks are committed again.
+        self.store_last_processed_block(event.eth_block)?;
+
+        error!(
+            "{} reverted blocks are rolled back, restarting the server to commit them again",
+            removed_blocks
+        );
+        if let Some(stop_signal_sender) = &mut self.stop_signal_sender {
+            stop_signal_sender.send(true).await?;
+        }
+
+        Ok(())
+    }
+
     /// Filters and parses the priority operation events from the Ethereum
     /// within the provided range of blocks.
     /// Returns the list of priority operations together with the block
@@ -237,32 +816,85 @@ impl<T: Transport> EthWatch<T> {
         &mut self,
         last_ethereum_block: u64,
     ) -> Result<(), failure::Error> {
-        let current_ethereum_block =
-            last_ethereum_block.saturating_sub(self.number_of_confirmations_for_event);
-
+        // Blocks are scanned up to the same block as `process_new_blocks` would scan,
+        // so the next update continues right after it.
         let new_block_with_accepted_events =
-            current_ethereum_block.saturating_sub(self.number_of_confirmations_for_event);
+            last_ethereum_block.saturating_sub(self.number_of_confirmations_for_event);
         let previous_block_with_accepted_events =
             new_block_with_accepted_events.saturating_sub(PRIORITY_EXPIRATION);
 
         // restore pending queue
-        let unconfirmed_queue = self.get_unconfirmed_ops(current_ethereum_block).await?;
+        let unconfirmed_queue = self.get_unconfirmed_ops(last_ethereum_block).await?;
+
+        // restore priority queue, starting with the operations stored in the database
+        let stored_ops = self.load_stored_priority_ops()?;
+        let first_block_to_scan = match self.load_last_processed_block()? {
+            Some(last_processed_block) => last_processed_block + 1,
+            // Cursor is not stored yet, so the blocks up to the last block with the stored
+            // operation are considered processed.
+            None => stored_ops
+                .iter()
+                .map(|op| op.eth_block + 1)
+                .max()
+                .unwrap_or_default()
+                .max(previous_block_with_accepted_events),
+        };
 
-        // restore priority queue
-        let prior_queue_events = self
-            .get_priority_op_events(
-                BlockNumber::Number(previous_block_with_accepted_events.into()),
-                BlockNumber::Number(new_block_with_accepted_events.into()),
-            )
-            .await?;
         let mut priority_queue = HashMap::new();
-        for priority_op in prior_queue_events.into_iter() {
+        for priority_op in stored_ops.into_iter() {
             priority_queue.insert(priority_op.serial_id, priority_op.into());
         }
 
-        let new_state = ETHState::new(last_ethereum_block, unconfirmed_queue, priority_queue);
+        // Blocks emitted while the server was down are scanned in batches, so the range of
+        // a single request stays acceptable for the Ethereum node.
+        let mut batch_start = first_block_to_scan;
+        while batch_start <= new_block_with_accepted_events {
+            let batch_end = std::cmp::min(
+                batch_start + MAX_BLOCKS_PER_SCAN - 1,
+                new_block_with_accepted_events,
+            );
+            debug!("Scanning the blocks {}..={}", batch_start, batch_end);
+
+            let prior_queue_events = self
+                .get_priority_op_events(
+                    BlockNumber::Number(batch_start.into()),
+                    BlockNumber::Number(batch_end.into()),
+                )
+                .await?;
+            self.store_priority_ops(&prior_queue_events)?;
+            self.store_new_tokens(
+                BlockNumber::Number(batch_start.into()),
+                BlockNumber::Number(batch_end.into()),
+            )
+            .await?;
+            self.process_blocks_revert_events(
+                BlockNumber::Number(batch_start.into()),
+                BlockNumber::Number(batch_end.into()),
+            )
+            .await?;
+            self.store_last_processed_block(batch_end)?;
+
+            for priority_op in prior_queue_events.into_iter() {
+                priority_queue.insert(priority_op.serial_id, priority_op.into());
+            }
+            batch_start = batch_end + 1;
+        }
+
+        let block_hashes = self.tracked_block_hashes(last_ethereum_block).await?;
+        // Upgrade events emitted before the restart are not scanned, since the current
+        // status is known from the contract.
+        let upgrade_status = self.get_upgrade_status().await?;
+
+        let new_state = ETHState::new(
+            last_ethereum_block,
+            unconfirmed_queue,
+            priority_queue,
+            block_hashes,
+        );
 
         self.set_new_state(new_state);
+        self.set_upgrade_status(upgrade_status, None);
+        self.keep_exodus_status_checked(last_ethereum_block).await?;
 
         trace!("ETH state: {:#?}", self.eth_state);
 
@@ -284,6 +916,24 @@ impl<T: Transport> EthWatch<T> {
                 BlockNumber::Number(new_block_with_accepted_events.into()),
             )
             .await?;
+        self.store_priority_ops(&priority_op_events)?;
+        self.store_new_tokens(
+            BlockNumber::Number(previous_block_with_accepted_events.into()),
+            BlockNumber::Number(new_block_with_accepted_events.into()),
+        )
+        .await?;
+        self.process_blocks_revert_events(
+            BlockNumber::Number(previous_block_with_accepted_events.into()),
+            BlockNumber::Number(new_block_with_accepted_events.into()),
+        )
+        .await?;
+        self.store_last_processed_block(new_block_with_accepted_events)?;
+        let upgrade_events = self
+            .get_upgrade_events(
+                BlockNumber::Number(previous_block_with_accepted_events.into()),
+                BlockNumber::Number(new_block_with_accepted_events.into()),
+            )
+            .await?;
 
         // Extend the existing priority operations with the new ones.
         let mut priority_queue = sift_outdated_ops(self.eth_state.priority_queue());
@@ -294,17 +944,200 @@ impl<T: Transport> EthWatch<T> {
 
         // Get new pending ops
         let unconfirmed_queue = self.get_unconfirmed_ops(last_ethereum_block).await?;
+        let block_hashes = self.tracked_block_hashes(last_ethereum_block).await?;
 
         // Now, after we've received all the data from the Ethereum, we can safely
         // update the state. This is done atomically to avoid the situation when
         // due to error occurred mid-update the overall `ETHWatcher` state become
         // messed up.
-        let new_state = ETHState::new(last_ethereum_block, unconfirmed_queue, priority_queue);
+        let new_state = ETHState::new(
+            last_ethereum_block,
+            unconfirmed_queue,
+            priority_queue,
+            block_hashes,
+        );
         self.set_new_state(new_state);
+        self.process_upgrade_events(upgrade_events);
 
         Ok(())
     }
 
+    /// Obtains the hash of the canonical chain block with the provided number.
+    async fn get_block_hash(&self, block: EthBlockId) -> Result<Option<H256>, failure::Error> {
+        let block = self
+            .web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Number(block.into())))
+            .compat()
+            .await?;
+        Ok(block.and_then(|block| block.hash))
+    }
+
+    /// Returns the tracked block hashes along with the hash of the newly processed block.
+    async fn tracked_block_hashes(
+        &self,
+        last_ethereum_block: u64,
+    ) -> Result<BTreeMap<EthBlockId, H256>, failure::Error> {
+        Ok(match self.get_block_hash(last_ethereum_block).await? {
+            Some(hash) => self.eth_state.block_hashes_with(last_ethereum_block, hash),
+            None => self.eth_state.block_hashes().clone(),
+        })
+    }
+
+    /// Checks whether the processed blocks are still in the canonical chain.
+    /// If some of them were removed by a reorg, returns the last processed block
+    /// which remains canonical.
+    async fn find_reorg_fork_block(&self) -> Result<Option<EthBlockId>, failure::Error> {
+        let mut reorg_detected = false;
+        for (&block, &hash) in self.eth_state.block_hashes().iter().rev() {
+            if self.get_block_hash(block).await? == Some(hash) {
+                return Ok(if reorg_detected { Some(block) } else { None });
+            }
+            reorg_detected = true;
+        }
+
+        // None of the tracked blocks is canonical, so everything after the oldest one
+        // is processed again.
+        Ok(self
+            .eth_state
+            .block_hashes()
+            .keys()
+            .next()
+            .map(|oldest_block| oldest_block.saturating_sub(1)))
+    }
+
+    /// Rolls the state back to the last canonical block, so the blocks after it are scanned
+    /// again on the next update. Returns the priority operations removed from the queue.
+    fn revert_to_block(
+        &mut self,
+        fork_block: EthBlockId,
+    ) -> Result<Vec<PriorityOp>, failure::Error> {
+        warn!(
+            "Ethereum chain reorganization detected, rolling back to the block {}",
+            fork_block
+        );
+        let (new_state, reverted_ops) = self.eth_state.rolled_back(fork_block);
+        self.remove_stored_priority_ops_after(fork_block)?;
+        self.set_new_state(new_state);
+
+        Ok(reverted_ops)
+    }
+
+    /// Notifies the subscribers about the reverted priority operations which are not
+    /// included into the canonical chain.
+    fn notify_reverted_ops(&mut self, reverted_ops: Vec<PriorityOp>) {
+        let priority_queue = self.eth_state.priority_queue();
+        let reverted_ops: Vec<_> = reverted_ops
+            .into_iter()
+            .filter(|reverted| {
+                !priority_queue
+                    .values()
+                    .any(|op| op.as_ref().eth_hash == reverted.eth_hash)
+            })
+            .collect();
+        if reverted_ops.is_empty() {
+            return;
+        }
+
+        for op in &reverted_ops {
+            warn!("Priority op was removed by the reorg: {:?}", op);
+        }
+        self.reverted_ops_subscribers
+            .retain(|subscriber| !subscriber.is_closed());
+        for subscriber in &mut self.reverted_ops_subscribers {
+            if let Err(e) = subscriber.try_send(reverted_ops.clone()) {
+                warn!("Failed to notify about the reverted priority ops: {}", e);
+            }
+        }
+    }
+
+    /// Reports the amount of the priority operations not executed yet, and the age of the
+    /// oldest one. Alerts the operator once the oldest one breaches the inclusion SLA.
+    fn report_priority_queue_metrics(&mut self) {
+        let next_unexecuted_serial_id = self.next_unexecuted_serial_id.unwrap_or_default();
+        let unexecuted_ops: Vec<_> = self
+            .eth_state
+            .ops_starting_from(next_unexecuted_serial_id)
+            .collect();
+        let oldest_op = unexecuted_ops
+            .iter()
+            .map(|op| (op.as_ref().serial_id, op.received_at().elapsed()))
+            .max_by_key(|(_, age)| *age);
+        let oldest_op_age = oldest_op.map(|(_, age)| age).unwrap_or_default();
+
+        let registry = metrics_registry();
+        registry.set_gauge(
+            "eth_watch_priority_queue_depth",
+            "Amount of the confirmed priority operations not executed yet",
+            unexecuted_ops.len() as f64,
+        );
+        registry.set_gauge(
+            "eth_watch_oldest_priority_op_age_seconds",
+            "Time passed since the confirmation of the oldest priority operation not executed yet",
+            oldest_op_age.as_secs_f64(),
+        );
+        registry.set_gauge(
+            "eth_watch_priority_op_sla_breached",
+            "Whether the oldest priority operation not executed yet breaches the inclusion SLA",
+            if self.priority_sla.is_breached(oldest_op_age) {
+                1.0
+            } else {
+                0.0
+            },
+        );
+
+        if self.priority_sla.report_oldest_unexecuted(oldest_op) {
+            let (serial_id, age) = oldest_op.expect("Breaching operation must exist");
+            error!(
+                "Priority operation {} is not executed for {}s (SLA is {}s), \
+                 priority queue may be stalled",
+                serial_id,
+                age.as_secs(),
+                self.priority_sla.sla().as_secs()
+            );
+            registry.increment_counter(
+                "eth_watch_priority_op_sla_breaches",
+                "Amount of the priority operations not executed within the inclusion SLA",
+                1.0,
+            );
+        }
+    }
+
+    /// Reports the execution latency of the last priority operation executed since
+    /// the previous request of the state keeper.
+    fn report_executed_ops(&mut self, next_unexecuted_serial_id: u64) {
+        let previous = self
+            .next_unexecuted_serial_id
+            .replace(next_unexecuted_serial_id);
+        let executed_since_previous =
+            previous.map_or(false, |previous| previous < next_unexecuted_serial_id);
+        // Latency is not known for the operations executed before the restart.
+        if !executed_since_previous {
+            return;
+        }
+
+        let last_executed_op = self
+            .eth_state
+            .priority_queue()
+            .get(&(next_unexecuted_serial_id - 1));
+        if let Some(op) = last_executed_op {
+            let latency = op.received_at().elapsed();
+            metrics_registry().set_gauge(
+                "eth_watch_priority_op_execution_latency_seconds",
+                "Time passed between the confirmation and the execution of the last priority op",
+                latency.as_secs_f64(),
+            );
+            if self.priority_sla.is_breached(latency) {
+                warn!(
+                    "Priority operation {} is executed in {}s, beyond the SLA of {}s",
+                    op.as_ref().serial_id,
+                    latency.as_secs(),
+                    self.priority_sla.sla().as_secs()
+                );
+            }
+        }
+    }
+
     fn get_priority_requests(&self, first_serial_id: u64, max_chunks: usize) -> Vec<PriorityOp> {
         let mut res = Vec::new();
 
@@ -369,45 +1202,83 @@ impl<T: Transport> EthWatch<T> {
     }
 
     fn find_ongoing_op_by_hash(&self, eth_hash: &[u8]) -> Option<(EthBlockId, PriorityOp)> {
-        self.eth_state
+        let unconfirmed_op = self
+            .eth_state
             .unconfirmed_queue()
             .iter()
             .find(|(_block, op)| op.eth_hash.as_slice() == eth_hash)
-            .cloned()
+            .cloned();
+
+        unconfirmed_op.or_else(|| {
+            self.eth_state
+                .priority_queue()
+                .values()
+                .map(|op| op.as_ref())
+                .find(|op| op.eth_hash.as_slice() == eth_hash)
+                .map(|op| (op.eth_block, op.clone()))
+        })
     }
 
     fn get_ongoing_deposits_for(&self, address: Address) -> Vec<(EthBlockId, PriorityOp)> {
         self.eth_state
             .unconfirmed_queue()
             .iter()
-            .filter(|(_block, op)| match &op.data {
-                FranklinPriorityOp::Deposit(deposit) => {
-                    // Address may be set to either sender or recipient.
-                    deposit.from == address || deposit.to == address
-                }
-                _ => false,
-            })
+            .filter(|(_block, op)| is_deposit_for(op, address))
             .cloned()
             .collect()
     }
 
+    fn get_confirmed_deposits_for(&self, address: Address) -> Vec<PriorityOp> {
+        let mut deposits: Vec<_> = self
+            .eth_state
+            .priority_queue()
+            .values()
+            .map(|op| op.as_ref())
+            .filter(|op| is_deposit_for(op, address))
+            .cloned()
+            .collect();
+        deposits.sort_by_key(|op| op.serial_id);
+        deposits
+    }
+
     async fn poll_eth_node(&mut self) -> Result<(), failure::Error> {
         let last_block_number = self.web3.eth().block_number().compat().await?.as_u64();
+        metrics_registry().set_gauge(
+            "eth_watch_blocks_behind_head",
+            "Amount of the Ethereum blocks not processed by the Ethereum watcher yet",
+            last_block_number.saturating_sub(self.eth_state.last_ethereum_block()) as f64,
+        );
+        if last_block_number < self.eth_state.last_ethereum_block() {
+            // Node is behind the processed blocks (e.g. the fallback endpoint is used),
+            // so the processed blocks can't be checked against it until it catches up.
+            debug!(
+                "Ethereum node is at the block {}, while the block {} is already processed",
+                last_block_number,
+                self.eth_state.last_ethereum_block()
+            );
+            return Ok(());
+        }
+
+        let reverted_ops = match self.find_reorg_fork_block().await? {
+            Some(fork_block) => self.revert_to_block(fork_block)?,
+            None => Vec::new(),
+        };
 
         if last_block_number > self.eth_state.last_ethereum_block() {
             self.process_new_blocks(last_block_number).await?;
         }
+        // Operations may be included into the canonical chain as well, so the subscribers
+        // are notified once the canonical blocks are scanned.
+        self.notify_reverted_ops(reverted_ops);
+        self.keep_exodus_status_checked(last_block_number).await?;
 
         Ok(())
     }
 
-    fn is_backoff_requested(&self, error: &failure::Error) -> bool {
-        error.to_string().contains("429 Too Many Requests")
-    }
-
     fn enter_backoff_mode(&mut self) {
-        let backoff_until = Instant::now() + RATE_LIMIT_DELAY;
-        self.mode = WatcherMode::Backoff(backoff_until);
+        let delay = self.backoff.next_delay();
+        log::warn!("Entering the backoff mode for {}ms", delay.as_millis());
+        self.mode = WatcherMode::Backoff(Instant::now() + delay);
     }
 
     fn polling_allowed(&mut self) -> bool {
@@ -427,39 +1298,36 @@ impl<T: Transport> EthWatch<T> {
     }
 
     pub async fn run(mut self) {
-        // As infura may be not responsive, we want to retry the query until we've actually got the
-        // block number.
-        // Normally, however, this loop is not expected to last more than one iteration.
-        let block = loop {
-            let block = self.web3.eth().block_number().compat().await;
-
-            match block {
-                Ok(block) => {
-                    break block.as_u64();
-                }
-                Err(error) => {
+        // As infura may be not responsive, we want to retry the state restoring until we've
+        // actually got it. Normally, however, this loop is not expected to last more than
+        // one iteration. Errors other than transient ones are considered critical and
+        // irrecoverable.
+        loop {
+            let restore_result = match self.web3.eth().block_number().compat().await {
+                Ok(block) => self.restore_state_from_eth(block.as_u64()).await,
+                Err(error) => Err(error.into()),
+            };
+
+            match restore_result {
+                Ok(()) => break,
+                Err(error) if is_transient_error(&error) => {
+                    let delay = self.backoff.next_delay();
                     log::warn!(
-                        "Unable to fetch last block number: '{}'. Retrying again in {} seconds",
+                        "Unable to restore ETHWatcher state: '{}'. Retrying again in {}ms",
                         error,
-                        RATE_LIMIT_DELAY.as_secs()
+                        delay.as_millis()
                     );
-
-                    time::delay_for(RATE_LIMIT_DELAY).await;
+                    time::delay_for(delay).await;
                 }
+                Err(error) => panic!("Unable to restore ETHWatcher state: {}", error),
             }
-        };
-
-        // Code above is prepared for the possible rate limiting by `infura`, and will wait until we
-        // can interact with the node again. We're not expecting the rate limiting to be applied
-        // immediately after that, thus any error on this stage is considered critical and
-        // irrecoverable.
-        self.restore_state_from_eth(block)
-            .await
-            .expect("Unable to restore ETHWatcher state");
+        }
+        self.backoff.reset();
 
         while let Some(request) = self.eth_watch_req.next().await {
             match request {
                 EthWatchRequest::PollETHNode => {
+                    self.report_priority_queue_metrics();
                     if !self.polling_allowed() {
                         // Polling is currently disabled, skip it.
                         continue;
@@ -467,14 +1335,13 @@ impl<T: Transport> EthWatch<T> {
 
                     let poll_result = self.poll_eth_node().await;
 
-                    if let Err(error) = poll_result {
-                        if self.is_backoff_requested(&error) {
-                            log::warn!(
-                                "Rate limit was reached, as reported by Ethereum node. \
-                                Entering the backoff mode"
-                            );
+                    match poll_result {
+                        Ok(()) => self.backoff.reset(),
+                        Err(error) if is_transient_error(&error) => {
+                            log::warn!("Ethereum node is not available: {}", error);
                             self.enter_backoff_mode();
-                        } else {
+                        }
+                        Err(error) => {
                             // Some unexpected kind of error, we won't shutdown the node because of it,
                             // but rather expect node administrators to handle the situation.
                             log::error!("Failed to process new blocks {}", error);
@@ -486,16 +1353,28 @@ impl<T: Transport> EthWatch<T> {
                     max_chunks,
                     resp,
                 } => {
+                    self.report_executed_ops(op_start_id);
                     resp.send(self.get_priority_requests(op_start_id, max_chunks))
                         .unwrap_or_default();
                 }
+                EthWatchRequest::GetLastEthereumBlock { resp } => {
+                    resp.send(self.eth_state.last_ethereum_block())
+                        .unwrap_or_default();
+                }
                 EthWatchRequest::GetUnconfirmedDeposits { address, resp } => {
                     let deposits_for_address = self.get_ongoing_deposits_for(address);
                     resp.send(deposits_for_address).unwrap_or_default();
                 }
-                EthWatchRequest::GetUnconfirmedOpByHash { eth_hash, resp } => {
-                    let unconfirmed_op = self.find_ongoing_op_by_hash(&eth_hash);
-                    resp.send(unconfirmed_op).unwrap_or_default();
+                EthWatchRequest::SubscribeRevertedOps { sender } => {
+                    self.reverted_ops_subscribers.push(sender);
+                }
+                EthWatchRequest::GetConfirmedDeposits { address, resp } => {
+                    let deposits_for_address = self.get_confirmed_deposits_for(address);
+                    resp.send(deposits_for_address).unwrap_or_default();
+                }
+                EthWatchRequest::GetOngoingOpByHash { eth_hash, resp } => {
+                    let ongoing_op = self.find_ongoing_op_by_hash(&eth_hash);
+                    resp.send(ongoing_op).unwrap_or_default();
                 }
                 EthWatchRequest::IsPubkeyChangeAuthorized {
                     address,
@@ -526,36 +1405,101 @@ impl<T: Transport> EthWatch<T> {
     }
 }
 
+/// Checks whether the operation is a deposit from or to the provided address.
+fn is_deposit_for(op: &PriorityOp, address: Address) -> bool {
+    match &op.data {
+        FranklinPriorityOp::Deposit(deposit) => {
+            // Address may be set to either sender or recipient.
+            deposit.from == address || deposit.to == address
+        }
+        _ => false,
+    }
+}
+
 #[must_use]
 pub fn start_eth_watch(
     config_options: ConfigurationOptions,
     eth_req_sender: mpsc::Sender<EthWatchRequest>,
     eth_req_receiver: mpsc::Receiver<EthWatchRequest>,
+    db_pool: Option<ConnectionPool>,
+    current_zksync_info: CurrentZksyncInfo,
+    stop_signal_sender: mpsc::Sender<bool>,
     runtime: &Runtime,
 ) -> JoinHandle<()> {
     let (web3_event_loop_handle, transport) =
-        web3::transports::Http::new(&config_options.web3_url).unwrap();
+        FailoverTransport::from_config(&config_options).expect("Failed to create web3 transport");
+    runtime.spawn(transport.clone().run_health_checks());
     let web3 = web3::Web3::new(transport);
 
     let eth_watch = EthWatch::new(
         web3,
         web3_event_loop_handle,
         config_options.contract_eth_addr,
+        config_options.governance_eth_addr,
+        config_options.upgrade_gatekeeper_eth_addr,
         config_options.confirmations_for_eth_event,
         eth_req_receiver,
+        db_pool,
+        Some(current_zksync_info),
+        Some(stop_signal_sender),
+        config_options.block_sealing.priority_op_deadline_margin,
+        config_options.priority_op_sla,
     );
     runtime.spawn(eth_watch.run());
 
     runtime.spawn(async move {
+        let mut eth_req_sender = eth_req_sender;
         let mut timer = time::interval(config_options.eth_watch_poll_interval);
 
         loop {
-            timer.tick().await;
-            eth_req_sender
-                .clone()
-                .send(EthWatchRequest::PollETHNode)
-                .await
-                .expect("ETH watch receiver dropped");
+            if let Some(ws_url) = &config_options.web3_ws_url {
+                if let Err(e) = poll_on_new_heads(ws_url, &mut eth_req_sender).await {
+                    warn!(
+                        "New heads subscription failed, falling back to polling: {}",
+                        e
+                    );
+                }
+            }
+
+            // Poll by the timer until the next subscription attempt (if any).
+            let retry_subscription_at = Instant::now() + SUBSCRIPTION_RETRY_INTERVAL;
+            loop {
+                timer.tick().await;
+                eth_req_sender
+                    .send(EthWatchRequest::PollETHNode)
+                    .await
+                    .expect("ETH watch receiver dropped");
+
+                if config_options.web3_ws_url.is_some() && Instant::now() >= retry_subscription_at {
+                    break;
+                }
+            }
         }
     })
 }
+
+/// Subscribes to the new blocks via the WebSocket endpoint, and requests the Ethereum watcher
+/// to poll the node upon every new block. Returns once the subscription fails.
+async fn poll_on_new_heads(
+    ws_url: &str,
+    eth_req_sender: &mut mpsc::Sender<EthWatchRequest>,
+) -> Result<(), failure::Error> {
+    let (_event_loop, transport) = web3::transports::WebSocket::new(ws_url)?;
+    let web3 = Web3::new(transport);
+    let mut new_heads = web3
+        .eth_subscribe()
+        .subscribe_new_heads()
+        .compat()
+        .await?
+        .compat();
+    info!("Subscribed to the new Ethereum blocks via {}", ws_url);
+
+    while let Some(header) = new_heads.next().await {
+        header?;
+        eth_req_sender
+            .send(EthWatchRequest::PollETHNode)
+            .await
+            .expect("ETH watch receiver dropped");
+    }
+    failure::bail!("New heads subscription was closed by the Ethereum node")
+}
@@ -0,0 +1,61 @@
+// Built-in deps
+use std::time::Duration;
+
+/// Tracks the inclusion SLA of the priority operations: the time between the operation
+/// confirmation and its execution must not exceed the configured one.
+#[derive(Debug)]
+pub struct PrioritySlaTracker {
+    sla: Duration,
+    /// Serial id of the last operation reported as breaching the SLA,
+    /// so every stalled operation is reported only once.
+    last_reported_serial_id: Option<u64>,
+}
+
+impl PrioritySlaTracker {
+    pub fn new(sla: Duration) -> Self {
+        Self {
+            sla,
+            last_reported_serial_id: None,
+        }
+    }
+
+    pub fn sla(&self) -> Duration {
+        self.sla
+    }
+
+    pub fn is_breached(&self, age: Duration) -> bool {
+        age > self.sla
+    }
+
+    /// Checks the oldest operation not executed yet (its serial id and age).
+    /// Returns `true` if it breaches the SLA and wasn't reported before.
+    pub fn report_oldest_unexecuted(&mut self, oldest: Option<(u64, Duration)>) -> bool {
+        match oldest {
+            Some((serial_id, age)) if self.is_breached(age) => {
+                let reported = self.last_reported_serial_id == Some(serial_id);
+                self.last_reported_serial_id = Some(serial_id);
+                !reported
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn breach_reported_once() {
+        let sla = Duration::from_secs(60);
+        let mut tracker = PrioritySlaTracker::new(sla);
+
+        assert!(!tracker.report_oldest_unexecuted(None));
+        assert!(!tracker.report_oldest_unexecuted(Some((1, sla))));
+        assert!(tracker.report_oldest_unexecuted(Some((1, sla + Duration::from_secs(1)))));
+        // The same operation is still stalled.
+        assert!(!tracker.report_oldest_unexecuted(Some((1, sla * 2))));
+        // The next operation is stalled as well.
+        assert!(tracker.report_oldest_unexecuted(Some((2, sla * 2))));
+    }
+}
@@ -32,6 +32,12 @@ impl ReceivedPriorityOp {
     pub fn is_outdated(&self) -> bool {
         self.received_at.elapsed() >= PRIORITY_OP_EXPIRATION
     }
+
+    /// Returns the moment the operation was confirmed, or the restart moment if the
+    /// operation was confirmed before the restart.
+    pub fn received_at(&self) -> Instant {
+        self.received_at
+    }
 }
 
 impl From<PriorityOp> for ReceivedPriorityOp {
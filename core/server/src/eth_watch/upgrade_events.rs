@@ -0,0 +1,157 @@
+// External uses
+use failure::format_err;
+use web3::types::{Log, H256, U256};
+// Local deps
+use super::EthBlockId;
+
+/// Events of the upgrade gatekeeper contract changing the status of the contracts upgrade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpgradeEventKind {
+    NoticePeriodStart,
+    PreparationStart,
+    UpgradeCancel,
+    UpgradeComplete,
+}
+
+impl UpgradeEventKind {
+    pub const ALL: [UpgradeEventKind; 4] = [
+        UpgradeEventKind::NoticePeriodStart,
+        UpgradeEventKind::PreparationStart,
+        UpgradeEventKind::UpgradeCancel,
+        UpgradeEventKind::UpgradeComplete,
+    ];
+
+    /// Name of the event in the contract ABI.
+    pub fn name(self) -> &'static str {
+        match self {
+            UpgradeEventKind::NoticePeriodStart => "NoticePeriodStart",
+            UpgradeEventKind::PreparationStart => "PreparationStart",
+            UpgradeEventKind::UpgradeCancel => "UpgradeCancel",
+            UpgradeEventKind::UpgradeComplete => "UpgradeComplete",
+        }
+    }
+
+    /// Returns the topics of all the upgrade events.
+    pub fn topics(contract: &ethabi::Contract) -> Vec<H256> {
+        Self::ALL.iter().map(|kind| kind.topic(contract)).collect()
+    }
+
+    fn topic(self, contract: &ethabi::Contract) -> H256 {
+        contract
+            .event(self.name())
+            .expect("upgrade gatekeeper contract abi error")
+            .signature()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradeEvent {
+    pub kind: UpgradeEventKind,
+    /// Version of the contracts the upgrade status change relates to. For the completed
+    /// upgrade, it's the new version.
+    pub version_id: u64,
+    pub eth_block: EthBlockId,
+}
+
+impl UpgradeEvent {
+    /// Parses the upgrade event log of the upgrade gatekeeper contract.
+    pub fn parse(contract: &ethabi::Contract, log: &Log) -> Result<Self, failure::Error> {
+        let topic = log
+            .topics
+            .get(0)
+            .ok_or_else(|| format_err!("Upgrade event log without topics: {:?}", log))?;
+        let kind = UpgradeEventKind::ALL
+            .iter()
+            .cloned()
+            .find(|kind| kind.topic(contract) == *topic)
+            .ok_or_else(|| format_err!("Unknown upgrade event: {:?}", log))?;
+        // Version id is the first indexed argument of all the upgrade events.
+        let version_id = log
+            .topics
+            .get(1)
+            .map(|version_id| U256::from_big_endian(version_id.as_bytes()).as_u64())
+            .ok_or_else(|| format_err!("Upgrade event without the version id: {:?}", log))?;
+        let eth_block = log
+            .block_number
+            .ok_or_else(|| format_err!("Upgrade event without the block number: {:?}", log))?
+            .as_u64();
+
+        Ok(Self {
+            kind,
+            version_id,
+            eth_block,
+        })
+    }
+}
+
+/// Status of the contracts upgrade observed by the Ethereum watcher.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpgradeStatus {
+    /// No upgrade is scheduled.
+    Idle,
+    /// Upgrade is scheduled, the contracts work as usual until the notice period passes.
+    NoticePeriod,
+    /// Upgrade may be finished at any moment.
+    Preparation,
+    /// Contracts are upgraded, so the server may not support them anymore.
+    /// The status is kept until the restart.
+    Completed,
+}
+
+impl UpgradeStatus {
+    /// Decodes the `upgradeStatus` of the upgrade gatekeeper contract.
+    pub fn from_contract_status(status: U256) -> Result<Self, failure::Error> {
+        match status.as_u64() {
+            0 => Ok(UpgradeStatus::Idle),
+            1 => Ok(UpgradeStatus::NoticePeriod),
+            2 => Ok(UpgradeStatus::Preparation),
+            _ => Err(format_err!("Unknown contracts upgrade status: {}", status)),
+        }
+    }
+
+    /// Returns the status after the provided event.
+    pub fn apply(self, event: UpgradeEventKind) -> Self {
+        if self == UpgradeStatus::Completed {
+            return self;
+        }
+
+        match event {
+            UpgradeEventKind::NoticePeriodStart => UpgradeStatus::NoticePeriod,
+            UpgradeEventKind::PreparationStart => UpgradeStatus::Preparation,
+            UpgradeEventKind::UpgradeCancel => UpgradeStatus::Idle,
+            UpgradeEventKind::UpgradeComplete => UpgradeStatus::Completed,
+        }
+    }
+
+    /// Checks whether no new blocks should be proposed, since they may be committed
+    /// to the contracts of the unknown version.
+    pub fn halts_block_production(self) -> bool {
+        matches!(self, UpgradeStatus::Preparation | UpgradeStatus::Completed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade_status_transitions() {
+        let status = UpgradeStatus::Idle.apply(UpgradeEventKind::NoticePeriodStart);
+        assert_eq!(status, UpgradeStatus::NoticePeriod);
+        assert!(!status.halts_block_production());
+
+        let status = status.apply(UpgradeEventKind::PreparationStart);
+        assert!(status.halts_block_production());
+
+        let status = status.apply(UpgradeEventKind::UpgradeCancel);
+        assert_eq!(status, UpgradeStatus::Idle);
+        assert!(!status.halts_block_production());
+
+        // Completed upgrade is not reverted by the following events.
+        let status = status
+            .apply(UpgradeEventKind::UpgradeComplete)
+            .apply(UpgradeEventKind::UpgradeCancel);
+        assert_eq!(status, UpgradeStatus::Completed);
+        assert!(status.halts_block_production());
+    }
+}
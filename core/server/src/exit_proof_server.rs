@@ -0,0 +1,359 @@
+//! Service generating the exit proofs for the exodus mode.
+//!
+//! Once the contract enters the exodus mode, users withdraw their funds by calling its `exit`
+//! function with the proof of their balance in the last verified state. The service generates
+//! such proofs from the verified state stored in the database (which can be restored with the
+//! `data-restore` module), so users don't have to run the tooling themselves.
+//!
+//! Endpoint:
+//! - `GET /api/v0.1/exit_proof/{account_id}/{token}` - exit proof of the account balance in the
+//!   token (id, address or symbol). Proof generation takes minutes, so the first request only
+//!   schedules it and returns `202 Accepted` with the `pending` status. Once generated, the proof
+//!   is returned with the `ready` status and the arguments of the contract `exit` call,
+//!   which must be sent from the account address.
+//!
+//! The server is run either by the `exit_proof_server` binary, or by the main server once
+//! the contract enters the exodus mode (see `eth_watch`).
+
+// Built-in uses
+use std::collections::HashMap;
+use std::env;
+use std::net::SocketAddr;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+// External uses
+use actix_web::{middleware, web, App, HttpResponse, HttpServer, Result as ActixResult};
+use failure::format_err;
+use tokio::{runtime::Runtime, time};
+use web3::types::U256;
+// Workspace uses
+use models::config_options::{parse_env, DEFAULT_EXIT_PROOF_SERVER_BIND};
+use models::node::{AccountId, AccountMap, Address, BlockNumber, TokenId, TokenLike};
+use storage::{ConnectionPool, StorageProcessor};
+// Local uses
+use crate::utils::current_zksync_info::CurrentZksyncInfo;
+
+/// Maximum amount of the exit proofs waiting for the generation.
+const MAX_PENDING_EXIT_PROOFS: usize = 1000;
+
+/// Interval between the checks of the exodus mode by the main server.
+const EXODUS_MODE_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Arguments of the contract `exit` call, along with the state they're valid for.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExitProofData {
+    /// Last verified block, the proof is valid only while it remains the last verified one.
+    pub verified_block: BlockNumber,
+    pub account_id: AccountId,
+    /// Address the `exit` call must be sent from.
+    pub account_address: Address,
+    pub token_id: TokenId,
+    /// Exited amount as a decimal string.
+    pub amount: String,
+    /// Proof in the format accepted by the contract.
+    pub proof: Vec<U256>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ExitProofStatus {
+    Pending,
+    Ready(ExitProofData),
+    Failed { error: String },
+}
+
+/// Exit proof is identified by the verified state it's generated for.
+type ExitProofKey = (BlockNumber, AccountId, TokenId);
+
+/// Generates the exit proof of the account balance in the given verified state.
+pub fn generate_exit_proof(
+    verified_block: BlockNumber,
+    accounts: AccountMap,
+    account_id: AccountId,
+    token_id: TokenId,
+) -> Result<ExitProofData, failure::Error> {
+    let account_address = accounts
+        .get(&account_id)
+        .map(|account| account.address)
+        .ok_or_else(|| format_err!("Account {} not found in the verified state", account_id))?;
+    let (proof, amount) =
+        prover::exit_proof::create_exit_proof(accounts, account_id, account_address, token_id)?;
+    Ok(ExitProofData {
+        verified_block,
+        account_id,
+        account_address,
+        token_id,
+        amount: amount.to_string(),
+        proof: proof.proof,
+    })
+}
+
+/// Parses the token from the request path: token id, `0x`-prefixed address or symbol.
+fn parse_token_like(token: &str) -> TokenLike {
+    if let Ok(id) = token.parse::<TokenId>() {
+        TokenLike::Id(id)
+    } else if let Some(address) = Some(token)
+        .filter(|token| token.starts_with("0x"))
+        .and_then(|token| token[2..].parse::<Address>().ok())
+    {
+        TokenLike::Address(address)
+    } else {
+        TokenLike::Symbol(token.to_string())
+    }
+}
+
+#[derive(Clone)]
+struct ExitProofState {
+    connection_pool: ConnectionPool,
+    proofs: Arc<Mutex<HashMap<ExitProofKey, ExitProofStatus>>>,
+    request_sender: mpsc::SyncSender<ExitProofKey>,
+}
+
+impl ExitProofState {
+    fn access_storage(&self) -> ActixResult<StorageProcessor> {
+        self.connection_pool
+            .access_storage_fragile()
+            .map_err(|err| {
+                vlog::warn!("DB await timeout: '{}';", err);
+                HttpResponse::RequestTimeout().finish().into()
+            })
+    }
+
+    /// Returns the status of the proof, scheduling its generation if it's not known yet.
+    /// Failed proofs are reported once, so the next request retries the generation.
+    fn proof_status(&self, key: ExitProofKey) -> Option<ExitProofStatus> {
+        let mut proofs = self.proofs.lock().unwrap();
+        match proofs.get(&key).cloned() {
+            Some(ExitProofStatus::Failed { error }) => {
+                proofs.remove(&key);
+                Some(ExitProofStatus::Failed { error })
+            }
+            Some(status) => Some(status),
+            None => {
+                self.request_sender.try_send(key).ok()?;
+                proofs.insert(key, ExitProofStatus::Pending);
+                Some(ExitProofStatus::Pending)
+            }
+        }
+    }
+}
+
+fn handle_get_exit_proof(
+    data: web::Data<ExitProofState>,
+    path: web::Path<(AccountId, String)>,
+) -> ActixResult<HttpResponse> {
+    let (account_id, token) = path.into_inner();
+    let storage = data.access_storage()?;
+
+    let verified_block = storage
+        .chain()
+        .block_schema()
+        .get_last_verified_block()
+        .map_err(|err| {
+            vlog::warn!("Failed to load the last verified block: {}", err);
+            HttpResponse::InternalServerError().finish()
+        })?;
+    let account = storage
+        .chain()
+        .account_schema()
+        .last_verified_state_for_account(account_id)
+        .map_err(|err| {
+            vlog::warn!("Failed to load the account {}: {}", account_id, err);
+            HttpResponse::InternalServerError().finish()
+        })?;
+    if account.is_none() {
+        return Ok(HttpResponse::NotFound().body("account not found"));
+    }
+    let token = storage
+        .tokens_schema()
+        .get_token(parse_token_like(&token))
+        .map_err(|err| {
+            vlog::warn!("Failed to load the token {}: {}", token, err);
+            HttpResponse::InternalServerError().finish()
+        })?;
+    let token_id = match token {
+        Some(token) => token.id,
+        None => return Ok(HttpResponse::NotFound().body("token not found")),
+    };
+
+    let response = match data.proof_status((verified_block, account_id, token_id)) {
+        Some(status @ ExitProofStatus::Ready(_)) => HttpResponse::Ok().json(status),
+        Some(status @ ExitProofStatus::Pending) => HttpResponse::Accepted().json(status),
+        Some(status @ ExitProofStatus::Failed { .. }) => {
+            HttpResponse::InternalServerError().json(status)
+        }
+        None => HttpResponse::ServiceUnavailable().body("too many pending exit proofs"),
+    };
+    Ok(response)
+}
+
+/// Generates the requested exit proofs one by one.
+/// Verified state is loaded once and reloaded only if the last verified block changes.
+fn run_exit_proof_generator(
+    connection_pool: ConnectionPool,
+    proofs: Arc<Mutex<HashMap<ExitProofKey, ExitProofStatus>>>,
+    requests: mpsc::Receiver<ExitProofKey>,
+) {
+    let mut verified_state: Option<(BlockNumber, AccountMap)> = None;
+    for key in requests {
+        let (block, account_id, token_id) = key;
+        let status = match load_verified_state(&connection_pool, &mut verified_state, block) {
+            Ok(accounts) => {
+                log::info!(
+                    "Generating exit proof for account {} and token {} at block {}",
+                    account_id,
+                    token_id,
+                    block
+                );
+                generate_exit_proof(block, accounts, account_id, token_id)
+                    .map(ExitProofStatus::Ready)
+                    .unwrap_or_else(|e| ExitProofStatus::Failed {
+                        error: e.to_string(),
+                    })
+            }
+            Err(e) => ExitProofStatus::Failed {
+                error: e.to_string(),
+            },
+        };
+        if let ExitProofStatus::Failed { error } = &status {
+            log::warn!("Failed to generate exit proof {:?}: {}", key, error);
+        }
+        let mut proofs = proofs.lock().unwrap();
+        // Proofs for the outdated states can't be used anymore.
+        proofs.retain(|(proof_block, _, _), _| *proof_block >= block);
+        proofs.insert(key, status);
+    }
+}
+
+/// Returns the accounts of the verified state at the given block, reloading the cached state
+/// if it's outdated.
+fn load_verified_state(
+    connection_pool: &ConnectionPool,
+    cached: &mut Option<(BlockNumber, AccountMap)>,
+    block: BlockNumber,
+) -> Result<AccountMap, failure::Error> {
+    if cached.as_ref().map(|(cached_block, _)| *cached_block) != Some(block) {
+        let storage = connection_pool.access_storage()?;
+        *cached = Some(storage.chain().state_schema().load_verified_state()?);
+    }
+    let (cached_block, accounts) = cached.as_ref().unwrap();
+    if *cached_block != block {
+        failure::bail!(
+            "Verified state moved from block {} to {}, request the proof again",
+            block,
+            cached_block
+        );
+    }
+    Ok(accounts.clone())
+}
+
+/// Starts the exit proof generator and the HTTP server.
+/// Must be called inside the `actix` system.
+pub fn start_exit_proof_server(connection_pool: ConnectionPool, bind_to: SocketAddr) {
+    let proofs = Arc::new(Mutex::new(HashMap::new()));
+    let (request_sender, request_receiver) = mpsc::sync_channel(MAX_PENDING_EXIT_PROOFS);
+
+    let generator_pool = connection_pool.clone();
+    let generator_proofs = proofs.clone();
+    std::thread::Builder::new()
+        .name("exit-proof-generator".to_string())
+        .spawn(move || run_exit_proof_generator(generator_pool, generator_proofs, request_receiver))
+        .expect("Exit proof generator thread");
+
+    let state = ExitProofState {
+        connection_pool,
+        proofs,
+        request_sender,
+    };
+    HttpServer::new(move || {
+        App::new()
+            .data(state.clone())
+            .wrap(middleware::Logger::default())
+            .route(
+                "/api/v0.1/exit_proof/{account_id}/{token}",
+                web::get().to(handle_get_exit_proof),
+            )
+    })
+    .bind(bind_to)
+    .expect("failed to bind the exit proof server")
+    .shutdown_timeout(1)
+    .start();
+}
+
+/// Address of the exit proof server (`EXIT_PROOF_SERVER_BIND`).
+pub fn exit_proof_server_bind_address() -> SocketAddr {
+    if env::var("EXIT_PROOF_SERVER_BIND").is_ok() {
+        parse_env("EXIT_PROOF_SERVER_BIND")
+    } else {
+        DEFAULT_EXIT_PROOF_SERVER_BIND
+            .parse()
+            .expect("invalid default exit proof server address")
+    }
+}
+
+/// Starts the exit proof server in a separate thread once the contract enters the exodus mode.
+pub fn start_exit_proof_server_on_exodus(
+    connection_pool: ConnectionPool,
+    current_zksync_info: CurrentZksyncInfo,
+    runtime: &Runtime,
+) {
+    runtime.spawn(async move {
+        let mut timer = time::interval(EXODUS_MODE_CHECK_INTERVAL);
+        while !current_zksync_info.is_exodus_mode() {
+            timer.tick().await;
+        }
+
+        let bind_address = exit_proof_server_bind_address();
+        std::thread::Builder::new()
+            .name("exit-proof-server".to_string())
+            .spawn(move || {
+                let runtime = actix_rt::System::new("exit-proof-server");
+                start_exit_proof_server(connection_pool, bind_address);
+                log::info!("Exit proof server is listening on {}", bind_address);
+                runtime.run().unwrap_or_default();
+            })
+            .expect("Exit proof server thread");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_parsing() {
+        assert_eq!(parse_token_like("2"), TokenLike::Id(2));
+        assert_eq!(
+            parse_token_like("0x0000000000000000000000000000000000000001"),
+            TokenLike::Address(Address::from_low_u64_be(1))
+        );
+        assert_eq!(
+            parse_token_like("ETH"),
+            TokenLike::Symbol("ETH".to_string())
+        );
+        assert_eq!(
+            parse_token_like("0xG"),
+            TokenLike::Symbol("0xG".to_string())
+        );
+    }
+
+    #[test]
+    fn status_format() {
+        let ready = ExitProofStatus::Ready(ExitProofData {
+            verified_block: 10,
+            account_id: 1,
+            account_address: Address::from_low_u64_be(1),
+            token_id: 0,
+            amount: "1000000000000000000".to_string(),
+            proof: vec![U256::from(1)],
+        });
+        let json = serde_json::to_value(&ready).unwrap();
+        assert_eq!(json["status"], "ready");
+        assert_eq!(json["amount"], "1000000000000000000");
+        assert_eq!(json["proof"][0], "0x1");
+
+        let pending = serde_json::to_value(&ExitProofStatus::Pending).unwrap();
+        assert_eq!(pending, serde_json::json!({ "status": "pending" }));
+    }
+}
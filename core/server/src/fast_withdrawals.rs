@@ -0,0 +1,102 @@
+//! Fast withdrawals served by the liquidity provider.
+//!
+//! Regular withdrawal becomes available on Ethereum only once the block containing it
+//! is verified. To exit faster, user may send the withdrawal flagged for the fast
+//! processing: its recipient is the liquidity provider (LP), so the LP receives the
+//! eventual L1 withdrawal, and in exchange the LP sends the transfer of the same amount
+//! of the same token to the user.
+//!
+//! The mempool keeps the fast withdrawal in the queue until the matching LP transfer is
+//! ready to be proposed, and then proposes both of them as a batch, so the state keeper
+//! executes them atomically in the same block. Subsequent transactions of the withdrawing
+//! account wait in the queue as well, thus they are never executed before the withdrawal.
+//!
+//! Waiting withdrawals are persisted along with the rest of the mempool transactions.
+//! Withdrawal which is not going to be matched can be replaced by the user with another
+//! transaction with the same nonce and a higher fee, or discarded once its validity
+//! window ends.
+
+// Workspace uses
+use models::node::{Address, FranklinTx};
+
+/// Matcher of the fast withdrawals and the transfers of the liquidity provider.
+#[derive(Debug, Clone)]
+pub struct FastWithdrawals {
+    lp_address: Address,
+}
+
+impl FastWithdrawals {
+    pub fn new(lp_address: Address) -> Self {
+        Self { lp_address }
+    }
+
+    /// Checks whether the transaction is a withdrawal which must be paired with
+    /// the LP transfer. Withdrawals of the LP itself are processed as usual.
+    pub fn is_fast_withdrawal(&self, tx: &FranklinTx) -> bool {
+        match tx {
+            FranklinTx::Withdraw(withdraw) => {
+                withdraw.to == self.lp_address && withdraw.from != self.lp_address
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks whether the transaction is the LP transfer matching the fast withdrawal,
+    /// i.e. it's sent by the LP to the withdrawing account, and the token and the amount
+    /// are the same.
+    pub fn is_matching_transfer(&self, withdrawal: &FranklinTx, tx: &FranklinTx) -> bool {
+        match (withdrawal, tx) {
+            (FranklinTx::Withdraw(withdraw), FranklinTx::Transfer(transfer)) => {
+                self.is_fast_withdrawal(withdrawal)
+                    && transfer.from == self.lp_address
+                    && transfer.to == withdraw.from
+                    && transfer.token == withdraw.token
+                    && transfer.amount == withdraw.amount
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::test_utils::{self, address};
+
+    const LP: u64 = 1;
+    const USER: u64 = 2;
+
+    fn withdraw(from: u64, to: u64, amount: u32) -> FranklinTx {
+        let withdraw = test_utils::withdraw(address(from), address(to), amount.into(), 0);
+        FranklinTx::Withdraw(Box::new(withdraw))
+    }
+
+    fn transfer(from: u64, to: u64, amount: u32) -> FranklinTx {
+        let transfer = test_utils::transfer(address(from), address(to), amount.into(), 0);
+        FranklinTx::Transfer(Box::new(transfer))
+    }
+
+    #[test]
+    fn fast_withdrawal_detection() {
+        let fast_withdrawals = FastWithdrawals::new(address(LP));
+        assert!(fast_withdrawals.is_fast_withdrawal(&withdraw(USER, LP, 10)));
+        assert!(!fast_withdrawals.is_fast_withdrawal(&withdraw(USER, USER, 10)));
+        assert!(!fast_withdrawals.is_fast_withdrawal(&withdraw(LP, LP, 10)));
+    }
+
+    #[test]
+    fn matching() {
+        let fast_withdrawals = FastWithdrawals::new(address(LP));
+        let withdrawal = withdraw(USER, LP, 10);
+
+        // Transfer of the other amount, to the other account or not from the LP doesn't match.
+        assert!(!fast_withdrawals.is_matching_transfer(&withdrawal, &transfer(LP, USER, 20)));
+        assert!(!fast_withdrawals.is_matching_transfer(&withdrawal, &transfer(LP, 3, 10)));
+        assert!(!fast_withdrawals.is_matching_transfer(&withdrawal, &transfer(3, USER, 10)));
+        // Regular withdrawal is never matched.
+        assert!(!fast_withdrawals
+            .is_matching_transfer(&withdraw(USER, USER, 10), &transfer(LP, USER, 10)));
+
+        assert!(fast_withdrawals.is_matching_transfer(&withdrawal, &transfer(LP, USER, 10)));
+    }
+}
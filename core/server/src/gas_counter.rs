@@ -6,7 +6,7 @@
 // External deps
 use web3::types::U256;
 // Workspace deps
-use models::node::{config::MAX_WITHDRAWALS_TO_COMPLETE_IN_A_CALL, FranklinOp};
+use models::node::{block::Block, config::MAX_WITHDRAWALS_TO_COMPLETE_IN_A_CALL, FranklinOp};
 
 /// Amount of gas that we can afford to spend in one transaction.
 /// This value must be big enough to fit big blocks with expensive transactions,
@@ -29,6 +29,9 @@ impl CommitCost {
     pub const TRANSFER_TO_NEW_COST: u64 = 862;
     pub const FULL_EXIT_COST: u64 = 10_165;
     pub const WITHDRAW_COST: u64 = 2_167;
+    /// Cost of the calldata of one noop chunk padding the block public data
+    /// (9 zero bytes, 4 gas each).
+    pub const PADDING_CHUNK_COST: u64 = 36;
 
     pub fn base_cost() -> U256 {
         U256::from(Self::BASE_COST)
@@ -98,7 +101,7 @@ impl VerifyCost {
 /// operations in that block.
 ///
 /// These estimated costs were calculated using the `gas_price_test` from `testkit`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GasCounter {
     commit_cost: U256,
     verify_cost: U256,
@@ -151,6 +154,15 @@ impl GasCounter {
         self.verify_cost * U256::from(130) / U256::from(100)
     }
 
+    /// Returns the gas limit of the `commitBlock` call for the sealed block. Limit collected
+    /// while the block was built is increased by the cost of the chunks padding the block
+    /// up to its size, since the block size is chosen only once the block is sealed.
+    pub fn block_commit_gas_limit(block: &Block) -> U256 {
+        let padding_chunks = block.block_chunks_size.saturating_sub(block.chunks_used());
+        let padding_cost = U256::from(CommitCost::PADDING_CHUNK_COST) * U256::from(padding_chunks);
+        block.commit_gas_limit + Self::scale_up(padding_cost)
+    }
+
     pub fn complete_withdrawals_gas_limit() -> U256 {
         // Currently we always complete a constant amount of withdrawals in the contract call, so the upper limit
         // is predictable.
@@ -171,7 +183,7 @@ impl GasCounter {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use models::node::{operations::ChangePubKeyOp, tx::ChangePubKey};
+    use models::node::{operations::ChangePubKeyOp, tx::ChangePubKey, Fr};
 
     #[test]
     fn commit_cost() {
@@ -274,4 +286,28 @@ mod tests {
         assert_eq!(gas_counter.commit_gas_limit(), expected_commit_limit);
         assert_eq!(gas_counter.verify_gas_limit(), expected_verify_limit);
     }
+
+    /// Checks that the commit gas limit of the block accounts for the padding chunks.
+    #[test]
+    fn block_commit_gas_limit() {
+        let commit_gas_limit = U256::from(1_000_000);
+        let block_chunks_size = 10;
+        let block = Block::new(
+            1,
+            Fr::default(),
+            0,
+            Vec::new(),
+            (0, 0),
+            block_chunks_size,
+            commit_gas_limit,
+            U256::from(1_000_000),
+            None,
+        );
+
+        let padding_cost = CommitCost::PADDING_CHUNK_COST * block_chunks_size as u64;
+        assert_eq!(
+            GasCounter::block_commit_gas_limit(&block),
+            commit_gas_limit + GasCounter::scale_up(U256::from(padding_cost))
+        );
+    }
 }
@@ -10,6 +10,8 @@ pub mod block_proposer;
 pub mod committer;
 pub mod eth_sender;
 pub mod eth_watch;
+pub mod exit_proof_server;
+pub mod fast_withdrawals;
 pub mod fee_ticker;
 pub mod gas_counter;
 pub mod leader_election;
@@ -17,6 +19,8 @@ pub mod mempool;
 pub mod observer_mode;
 pub mod prometheus_exporter;
 pub mod prover_server;
+pub mod read_only;
+pub mod shutdown;
 pub mod signature_checker;
 pub mod state_keeper;
 pub mod utils;
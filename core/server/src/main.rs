@@ -14,21 +14,25 @@ use models::{
         tokens::{get_genesis_token_list, Token},
         TokenId,
     },
+    prover_utils::key_checks::check_verification_keys,
 };
-use storage::ConnectionPool;
+use storage::{ConnectionPool, StorageProcessor};
 // Local uses
 use server::prometheus_exporter::start_prometheus_exporter;
 use server::{
-    api_server::start_api_server,
+    api_server::{start_api_server, start_operations_listener},
     block_proposer::run_block_proposer_task,
     committer::run_committer,
     eth_sender,
     eth_watch::start_eth_watch,
+    exit_proof_server::start_exit_proof_server_on_exodus,
     fee_ticker::run_ticker_task,
     leader_election,
     mempool::run_mempool_task,
     observer_mode,
     prover_server::start_prover_server,
+    read_only::run_read_only_responders,
+    shutdown::GracefulShutdown,
     state_keeper::{start_state_keeper, PlasmaStateKeeper},
     utils::current_zksync_info::CurrentZksyncInfo,
 };
@@ -45,6 +49,11 @@ fn main() {
                 .long("genesis")
                 .help("Generate genesis block for the first contract deployment"),
         )
+        .arg(
+            Arg::with_name("read-only")
+                .long("read-only")
+                .help("Serve only the API, without running the sequencer actors"),
+        )
         .get_matches();
 
     if cli.is_present("genesis") {
@@ -78,6 +87,11 @@ fn main() {
         return;
     }
 
+    if cli.is_present("read-only") {
+        run_read_only_server(config_opts);
+        return;
+    }
+
     // Start observing the state and try to become leader.
     let observer_mode_final_state = {
         let (observed_state_tx, observed_state_rx) = std::sync::mpsc::channel();
@@ -107,43 +121,25 @@ fn main() {
     let storage = connection_pool
         .access_storage()
         .expect("db connection failed for committer");
-    let contract_addr: H160 = storage
-        .config_schema()
-        .load_config()
-        .expect("can not load server_config")
-        .contract_addr
-        .expect("contract_addr empty in server_config")[2..]
-        .parse()
-        .expect("contract_addr in db wrong");
-    if contract_addr != config_opts.contract_eth_addr {
-        panic!(
-            "Contract addresses mismatch! From DB = {}, from env = {}",
-            contract_addr, config_opts.contract_eth_addr
-        );
-    }
+    check_contract_address(&storage, &config_opts);
 
     let current_zksync_info = CurrentZksyncInfo::new(&connection_pool);
 
     log::info!("starting actors");
 
-    let mut main_runtime = Runtime::new().expect("main runtime start");
+    let main_runtime = Runtime::new().expect("main runtime start");
 
-    // handle ctrl+c
-    let (stop_signal_sender, stop_signal_receiver) = mpsc::channel(256);
-    {
-        let stop_signal_sender = RefCell::new(stop_signal_sender.clone());
-        ctrlc::set_handler(move || {
-            let mut sender = stop_signal_sender.borrow_mut();
-            block_on(sender.send(true)).expect("crtlc signal send");
-        })
-        .expect("Error setting Ctrl-C handler");
-    }
+    let (stop_signal_sender, stop_signal_receiver, shutdown_signal_receiver) =
+        set_stop_signal_handler();
 
     let (eth_watch_req_sender, eth_watch_req_receiver) = mpsc::channel(256);
     let eth_watch_task = start_eth_watch(
         config_opts.clone(),
         eth_watch_req_sender.clone(),
         eth_watch_req_receiver,
+        Some(connection_pool.clone()),
+        current_zksync_info.clone(),
+        stop_signal_sender.clone(),
         &main_runtime,
     );
 
@@ -151,7 +147,9 @@ fn main() {
     let (state_keeper_req_sender, state_keeper_req_receiver) = mpsc::channel(256);
     let (executed_tx_notify_sender, executed_tx_notify_receiver) = mpsc::channel(256);
     let (mempool_request_sender, mempool_request_receiver) = mpsc::channel(256);
+    let (replaced_tx_notify_sender, replaced_tx_notify_receiver) = mpsc::channel(256);
     let (ticker_request_sender, ticker_request_receiver) = mpsc::channel(512);
+    let (notifier_flush_sender, notifier_flush_receiver) = mpsc::channel(1);
 
     // Load the most recent pending block from the database.
     let pending_block = observer_mode_final_state
@@ -164,8 +162,8 @@ fn main() {
         proposed_blocks_sender,
         executed_tx_notify_sender,
         config_opts.available_block_chunk_sizes.clone(),
-        config_opts.max_miniblock_iterations,
-        config_opts.max_miniblock_iterations_withdraw_block,
+        config_opts.block_sealing.clone(),
+        config_opts.state_checkpoint_interval,
     );
     let state_keeper_task = start_state_keeper(state_keeper, pending_block, &main_runtime);
 
@@ -188,44 +186,69 @@ fn main() {
         connection_pool.clone(),
         &main_runtime,
     );
+    let api_op_notify_receiver = if config_opts.api_notifications_via_db {
+        // Operations are broadcast through the database, so the in-process
+        // notifications are not needed and can be discarded.
+        main_runtime.spawn(zksync_commit_notify_receiver.for_each(|_| async {}));
+        start_operations_listener(connection_pool.clone(), stop_signal_sender.clone())
+    } else {
+        zksync_commit_notify_receiver
+    };
     start_api_server(
-        zksync_commit_notify_receiver,
+        api_op_notify_receiver,
         connection_pool.clone(),
         stop_signal_sender.clone(),
         mempool_request_sender.clone(),
         executed_tx_notify_receiver,
+        replaced_tx_notify_receiver,
+        notifier_flush_receiver,
         state_keeper_req_sender.clone(),
         eth_watch_req_sender.clone(),
         ticker_request_sender,
         config_opts.clone(),
-        current_zksync_info,
+        current_zksync_info.clone(),
     );
 
     let prover_options = ProverOptions::from_env();
+    // Mock proofs are checked without the verification keys.
+    if prover_options.verify_proofs && !prover_options.mock_proofs {
+        check_verification_keys(&config_opts.available_block_chunk_sizes)
+            .expect("Refusing to start with invalid verification keys");
+    }
     start_prover_server(
         connection_pool.clone(),
         config_opts.prover_server_address,
-        prover_options.gone_timeout,
-        prover_options.prepare_data_interval,
         stop_signal_sender,
         observer_mode_final_state.circuit_acc_tree,
         observer_mode_final_state.circuit_tree_block,
         config_opts.idle_provers,
+        prover_options,
     );
 
     let mempool_task = run_mempool_task(
         connection_pool.clone(),
         mempool_request_receiver,
         eth_watch_req_sender,
+        replaced_tx_notify_sender,
         &config_opts,
         &main_runtime,
     );
     let proposer_task = run_block_proposer_task(
-        mempool_request_sender,
+        mempool_request_sender.clone(),
         state_keeper_req_sender.clone(),
+        current_zksync_info.clone(),
+        config_opts.block_sealing.miniblock_interval,
         &main_runtime,
     );
 
+    let shutdown = GracefulShutdown::new(
+        current_zksync_info.clone(),
+        mempool_request_sender,
+        state_keeper_req_sender.clone(),
+        eth_send_request_sender.clone(),
+        notifier_flush_sender,
+    );
+
     let ticker_task = run_ticker_task(
         config_opts.ticker_url.clone(),
         connection_pool.clone(),
@@ -235,6 +258,8 @@ fn main() {
         &main_runtime,
     );
 
+    start_exit_proof_server_on_exodus(connection_pool.clone(), current_zksync_info, &main_runtime);
+
     let prometheus_exporter =
         start_prometheus_exporter(connection_pool, &config_opts, &main_runtime);
 
@@ -249,6 +274,161 @@ fn main() {
         prometheus_exporter,
     ];
 
+    run_until_stopped(
+        main_runtime,
+        task_futures,
+        stop_signal_receiver,
+        shutdown_signal_receiver,
+        Some(shutdown),
+    );
+}
+
+/// Runs the server in the read-only mode: only the API is served, while the requests
+/// to the sequencer actors are answered using the database.
+fn run_read_only_server(config_opts: ConfigurationOptions) {
+    let connection_pool = ConnectionPool::new(None);
+
+    log::debug!("starting read-only server");
+
+    let storage = connection_pool
+        .access_storage()
+        .expect("db connection failed for read-only server");
+    check_contract_address(&storage, &config_opts);
+
+    let current_zksync_info = CurrentZksyncInfo::new(&connection_pool);
+
+    let main_runtime = Runtime::new().expect("main runtime start");
+
+    let (stop_signal_sender, stop_signal_receiver, shutdown_signal_receiver) =
+        set_stop_signal_handler();
+
+    let (eth_watch_req_sender, eth_watch_req_receiver) = mpsc::channel(256);
+    // Priority queue is persisted by the sequencer node, the replica database is read-only.
+    let eth_watch_task = start_eth_watch(
+        config_opts.clone(),
+        eth_watch_req_sender.clone(),
+        eth_watch_req_receiver,
+        None,
+        current_zksync_info.clone(),
+        stop_signal_sender.clone(),
+        &main_runtime,
+    );
+
+    let (state_keeper_req_sender, state_keeper_req_receiver) = mpsc::channel(256);
+    let (mempool_request_sender, mempool_request_receiver) = mpsc::channel(256);
+    let (eth_send_request_sender, eth_send_request_receiver) = mpsc::channel(256);
+    let (ticker_request_sender, ticker_request_receiver) = mpsc::channel(512);
+    // There is no state keeper to report the executed operations,
+    // thus the sender is dropped immediately.
+    let (_, executed_tx_notify_receiver) = mpsc::channel(256);
+    // Transactions are not accepted, so none of them can be replaced either.
+    let (_, replaced_tx_notify_receiver) = mpsc::channel(256);
+    // There is nothing to flush on the shutdown without the sequencer actors.
+    let (_, notifier_flush_receiver) = mpsc::channel(1);
+
+    let responders_task = run_read_only_responders(
+        connection_pool.clone(),
+        state_keeper_req_receiver,
+        mempool_request_receiver,
+        eth_send_request_receiver,
+        &main_runtime,
+    );
+
+    // Operations are committed by the sequencer node, so the notifications
+    // about them can be obtained from the database only.
+    let op_notify_receiver =
+        start_operations_listener(connection_pool.clone(), stop_signal_sender.clone());
+    start_api_server(
+        op_notify_receiver,
+        connection_pool.clone(),
+        stop_signal_sender,
+        mempool_request_sender,
+        executed_tx_notify_receiver,
+        replaced_tx_notify_receiver,
+        notifier_flush_receiver,
+        state_keeper_req_sender.clone(),
+        eth_watch_req_sender,
+        ticker_request_sender,
+        config_opts.clone(),
+        current_zksync_info,
+    );
+
+    let ticker_task = run_ticker_task(
+        config_opts.ticker_url.clone(),
+        connection_pool.clone(),
+        eth_send_request_sender,
+        state_keeper_req_sender,
+        ticker_request_receiver,
+        &main_runtime,
+    );
+
+    let prometheus_exporter =
+        start_prometheus_exporter(connection_pool, &config_opts, &main_runtime);
+
+    let task_futures = vec![
+        eth_watch_task,
+        responders_task,
+        ticker_task,
+        prometheus_exporter,
+    ];
+
+    run_until_stopped(
+        main_runtime,
+        task_futures,
+        stop_signal_receiver,
+        shutdown_signal_receiver,
+        None,
+    );
+}
+
+/// Checks that the contract address stored in the database matches the configured one.
+fn check_contract_address(storage: &StorageProcessor, config_opts: &ConfigurationOptions) {
+    let contract_addr: H160 = storage
+        .config_schema()
+        .load_config()
+        .expect("can not load server_config")
+        .contract_addr
+        .expect("contract_addr empty in server_config")[2..]
+        .parse()
+        .expect("contract_addr in db wrong");
+    if contract_addr != config_opts.contract_eth_addr {
+        panic!(
+            "Contract addresses mismatch! From DB = {}, from env = {}",
+            contract_addr, config_opts.contract_eth_addr
+        );
+    }
+}
+
+/// Creates the stop signal channel, which is used by the actors to stop the server right away,
+/// and sets the Ctrl-C (and SIGTERM) handler, which sends a message to the returned shutdown
+/// signal channel, so the server is stopped gracefully.
+fn set_stop_signal_handler() -> (mpsc::Sender<bool>, mpsc::Receiver<bool>, mpsc::Receiver<()>) {
+    let (stop_signal_sender, stop_signal_receiver) = mpsc::channel(256);
+    let (shutdown_signal_sender, shutdown_signal_receiver) = mpsc::channel(256);
+    {
+        let shutdown_signal_sender = RefCell::new(shutdown_signal_sender);
+        ctrlc::set_handler(move || {
+            let mut sender = shutdown_signal_sender.borrow_mut();
+            block_on(sender.send(())).expect("crtlc signal send");
+        })
+        .expect("Error setting Ctrl-C handler");
+    }
+    (
+        stop_signal_sender,
+        stop_signal_receiver,
+        shutdown_signal_receiver,
+    )
+}
+
+/// Blocks until either any of the actors finishes, or the stop or shutdown signal is received.
+/// On the shutdown signal, the graceful shutdown sequence is performed before stopping.
+fn run_until_stopped(
+    mut main_runtime: Runtime,
+    task_futures: Vec<JoinHandle<()>>,
+    stop_signal_receiver: mpsc::Receiver<bool>,
+    mut shutdown_signal_receiver: mpsc::Receiver<()>,
+    shutdown: Option<GracefulShutdown>,
+) {
     main_runtime.block_on(async move {
         /// Waits for *any* of the tokio tasks to be finished.
         /// Since the main tokio tasks are used as actors which should live as long
@@ -278,6 +458,7 @@ fn main() {
 
         let task_future = wait_for_tasks(task_futures);
         let signal_future = wait_for_stop_signal(stop_signal_receiver);
+        let shutdown_future = shutdown_signal_receiver.next();
 
         // Select either of futures: completion of the any will mean that
         // server has to be stopped.
@@ -288,6 +469,12 @@ fn main() {
             _ = signal_future => {
                 log::warn!("Stop signal received, shutting down");
             },
+            _ = shutdown_future => {
+                log::warn!("Shutdown signal received, shutting down gracefully");
+                if let Some(shutdown) = shutdown {
+                    shutdown.run().await;
+                }
+            },
         }
     });
     main_runtime.shutdown_timeout(Duration::from_secs(0));
@@ -0,0 +1,260 @@
+// Built-in deps
+use std::collections::BTreeMap;
+// Workspace uses
+use models::node::{Nonce, SignedFranklinTx};
+
+/// Transaction stored in the mempool along with its arrival order.
+#[derive(Debug, Clone)]
+pub struct QueuedTx {
+    pub tx: SignedFranklinTx,
+    /// Sequential number of the transaction among all the accepted ones,
+    /// used to select the transactions in the order of arrival.
+    pub arrival_id: u64,
+}
+
+/// Queue of the transactions sent by a single account, ordered by nonce.
+///
+/// Transaction is considered *ready* if it can be executed right after the
+/// previously proposed transactions of the account, i.e. its nonce is equal to
+/// the next expected one. Transactions with greater nonces are kept in the queue
+/// until the gap is filled.
+///
+/// Note that the next expected nonce may be greater than the committed nonce of the
+/// account, since the proposed transactions are not committed until the block is sealed.
+#[derive(Debug)]
+pub struct AccountTxQueue {
+    /// Nonce of the next transaction to be proposed for the block.
+    next_nonce: Nonce,
+    txs: BTreeMap<Nonce, QueuedTx>,
+}
+
+impl AccountTxQueue {
+    /// Creates an empty queue expecting the transaction with the provided nonce first.
+    pub fn new(next_nonce: Nonce) -> Self {
+        Self {
+            next_nonce,
+            txs: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the nonce of the next transaction to be proposed.
+    pub fn next_nonce(&self) -> Nonce {
+        self.next_nonce
+    }
+
+    /// Returns the amount of queued transactions.
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// Checks whether the transaction with the provided nonce is queued.
+    pub fn contains(&self, nonce: Nonce) -> bool {
+        self.txs.contains_key(&nonce)
+    }
+
+    /// Returns the amount of transactions that can be proposed one after another
+    /// starting from the next expected nonce.
+    pub fn ready_len(&self) -> usize {
+        let mut expected_nonce = self.next_nonce;
+        for nonce in self.txs.range(self.next_nonce..).map(|(nonce, _)| *nonce) {
+            if nonce != expected_nonce {
+                break;
+            }
+            expected_nonce += 1;
+        }
+        (expected_nonce - self.next_nonce) as usize
+    }
+
+    /// Checks whether some of the queued transactions are waiting for the missing nonces.
+    pub fn has_gap(&self) -> bool {
+        self.ready_len() < self.txs.len()
+    }
+
+    /// Checks whether the transaction with the provided nonce will be ready once inserted.
+    pub fn would_be_ready(&self, nonce: Nonce) -> bool {
+        nonce >= self.next_nonce && nonce - self.next_nonce <= self.ready_len() as Nonce
+    }
+
+    /// Inserts the transaction to the queue. Transaction with the same nonce must not
+    /// be queued already.
+    ///
+    /// If the nonce of the transaction is lower than the next expected one (e.g. the
+    /// previously proposed transaction with this nonce has failed and is sent again),
+    /// the queue will propose the transactions starting from this nonce.
+    pub fn insert(&mut self, tx: QueuedTx) {
+        let nonce = tx.tx.nonce();
+        assert!(
+            !self.txs.contains_key(&nonce),
+            "Transaction with the same nonce is already queued"
+        );
+        if nonce < self.next_nonce {
+            self.next_nonce = nonce;
+        }
+        self.txs.insert(nonce, tx);
+    }
+
+    /// Returns the queued transaction with the provided nonce.
+    pub fn get(&self, nonce: Nonce) -> Option<&QueuedTx> {
+        self.txs.get(&nonce)
+    }
+
+    /// Replaces the queued transaction with the same nonce, keeping its position
+    /// in the arrival order. Returns the replaced transaction.
+    pub fn replace(&mut self, tx: SignedFranklinTx) -> Option<QueuedTx> {
+        let queued = self.txs.get_mut(&tx.nonce())?;
+        let replaced_tx = std::mem::replace(&mut queued.tx, tx);
+        Some(QueuedTx {
+            tx: replaced_tx,
+            arrival_id: queued.arrival_id,
+        })
+    }
+
+    /// Removes the transaction with the provided nonce from the queue.
+    pub fn remove(&mut self, nonce: Nonce) -> Option<QueuedTx> {
+        self.txs.remove(&nonce)
+    }
+
+    /// Returns all the queued transactions in the order of nonces.
+    pub fn txs(&self) -> impl Iterator<Item = &QueuedTx> {
+        self.txs.values()
+    }
+
+    /// Returns the ready transaction to be proposed next, if any.
+    pub fn ready_head(&self) -> Option<&QueuedTx> {
+        self.txs.get(&self.next_nonce)
+    }
+
+    /// Takes the ready transaction from the queue, considering it to be proposed.
+    pub fn pop_ready(&mut self) -> Option<QueuedTx> {
+        let tx = self.txs.remove(&self.next_nonce)?;
+        self.next_nonce += 1;
+        Some(tx)
+    }
+
+    /// Removes the queued transaction with the highest nonce if it's not ready.
+    pub fn evict_last_unready(&mut self) -> Option<QueuedTx> {
+        if !self.has_gap() {
+            return None;
+        }
+        let last_nonce = *self.txs.keys().next_back()?;
+        self.txs.remove(&last_nonce)
+    }
+
+    /// Updates the queue after the account nonce has been committed.
+    /// Returns the transactions which can't be executed anymore since their nonces are too low.
+    pub fn update_committed_nonce(&mut self, committed_nonce: Nonce) -> Vec<QueuedTx> {
+        let actual_txs = self.txs.split_off(&committed_nonce);
+        let outdated_txs = std::mem::replace(&mut self.txs, actual_txs);
+        if self.next_nonce < committed_nonce {
+            self.next_nonce = committed_nonce;
+        }
+
+        outdated_txs.into_iter().map(|(_, tx)| tx).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::{FranklinTx, Transfer};
+
+    fn queued_tx(nonce: Nonce) -> QueuedTx {
+        let transfer = Transfer::new(
+            0,
+            Default::default(),
+            Default::default(),
+            0,
+            0u32.into(),
+            0u32.into(),
+            nonce,
+            None,
+        );
+        QueuedTx {
+            tx: FranklinTx::Transfer(Box::new(transfer)).into(),
+            arrival_id: u64::from(nonce),
+        }
+    }
+
+    /// Checks that transactions are proposed in the order of nonces, and the gaps are detected.
+    #[test]
+    fn nonce_ordering_and_gaps() {
+        let mut queue = AccountTxQueue::new(1);
+
+        queue.insert(queued_tx(2));
+        assert!(queue.has_gap());
+        assert!(queue.ready_head().is_none());
+        assert!(queue.would_be_ready(1));
+        assert!(!queue.would_be_ready(3));
+
+        queue.insert(queued_tx(1));
+        assert!(!queue.has_gap());
+        assert_eq!(queue.ready_len(), 2);
+        assert!(queue.would_be_ready(3));
+
+        assert_eq!(queue.pop_ready().unwrap().tx.nonce(), 1);
+        assert_eq!(queue.pop_ready().unwrap().tx.nonce(), 2);
+        assert!(queue.pop_ready().is_none());
+        assert_eq!(queue.next_nonce(), 3);
+    }
+
+    /// Checks that only the transactions waiting for the missing nonces are evicted.
+    #[test]
+    fn eviction() {
+        let mut queue = AccountTxQueue::new(0);
+        queue.insert(queued_tx(0));
+        assert!(queue.evict_last_unready().is_none());
+
+        queue.insert(queued_tx(5));
+        queue.insert(queued_tx(7));
+        assert_eq!(queue.evict_last_unready().unwrap().tx.nonce(), 7);
+        assert_eq!(queue.evict_last_unready().unwrap().tx.nonce(), 5);
+        assert!(queue.evict_last_unready().is_none());
+        assert_eq!(queue.len(), 1);
+    }
+
+    /// Checks that the replaced transaction keeps its position in the queue.
+    #[test]
+    fn replacement() {
+        let mut queue = AccountTxQueue::new(0);
+        queue.insert(queued_tx(0));
+
+        let mut new_tx = queued_tx(0);
+        new_tx.arrival_id = 10;
+        let replaced = queue.replace(new_tx.tx).unwrap();
+        assert_eq!(replaced.arrival_id, 0);
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.ready_head().unwrap().arrival_id, 0);
+
+        assert!(queue.replace(queued_tx(1).tx).is_none());
+    }
+
+    /// Checks that committed nonce removes the outdated transactions, and resent transaction
+    /// with the already proposed nonce is proposed again.
+    #[test]
+    fn committed_nonce_update() {
+        let mut queue = AccountTxQueue::new(0);
+        for nonce in 0..3 {
+            queue.insert(queued_tx(nonce));
+        }
+        queue.pop_ready().unwrap();
+        queue.pop_ready().unwrap();
+
+        // Only the first transaction was executed.
+        assert!(queue.update_committed_nonce(1).is_empty());
+        assert_eq!(queue.next_nonce(), 2);
+
+        // Failed transaction is sent again.
+        queue.insert(queued_tx(1));
+        assert_eq!(queue.next_nonce(), 1);
+        assert_eq!(queue.ready_len(), 2);
+
+        let outdated = queue.update_committed_nonce(2);
+        assert_eq!(outdated.len(), 1);
+        assert_eq!(outdated[0].tx.nonce(), 1);
+        assert_eq!(queue.ready_head().unwrap().tx.nonce(), 2);
+    }
+}
@@ -0,0 +1,72 @@
+//! Denylist of the addresses which are not allowed to send or receive transactions.
+//!
+//! The denylist is maintained by the operator through the admin API and stored in the
+//! database. Mempool loads it on start and reloads it upon the `ReloadDenylist` request,
+//! so the changes take effect without the server restart.
+
+// Built-in deps
+use std::collections::HashSet;
+// Workspace uses
+use models::node::{Address, FranklinTx};
+use storage::ConnectionPool;
+
+#[derive(Debug, Default)]
+pub struct Denylist {
+    addresses: HashSet<Address>,
+}
+
+impl Denylist {
+    pub fn new(addresses: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            addresses: addresses.into_iter().collect(),
+        }
+    }
+
+    /// Loads the denylisted addresses from the database.
+    pub fn load(db_pool: &ConnectionPool) -> Result<Self, failure::Error> {
+        let storage = db_pool.access_storage()?;
+        let stored = storage.denylist_schema().load_denylist()?;
+        Ok(Self::new(
+            stored
+                .into_iter()
+                .map(|stored| Address::from_slice(&stored.address)),
+        ))
+    }
+
+    /// Returns the amount of denylisted addresses.
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    /// Returns the denylisted address among the sender and the recipient of the transaction.
+    pub fn denied_address(&self, tx: &FranklinTx) -> Option<Address> {
+        std::iter::once(tx.account())
+            .chain(tx.recipient())
+            .find(|address| self.addresses.contains(address))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::test_utils::{self, address};
+
+    #[test]
+    fn denied_addresses() {
+        let (alice, bob, carol) = (address(1), address(2), address(3));
+        let denylist = Denylist::new(vec![bob]);
+        let transfer =
+            |from, to| FranklinTx::Transfer(Box::new(test_utils::transfer(from, to, 0, 0)));
+        let withdraw = FranklinTx::Withdraw(Box::new(test_utils::withdraw(alice, bob, 0, 0)));
+
+        assert_eq!(denylist.denied_address(&transfer(alice, carol)), None);
+        assert_eq!(denylist.denied_address(&transfer(bob, carol)), Some(bob));
+        assert_eq!(denylist.denied_address(&transfer(alice, bob)), Some(bob));
+        // Withdrawal to the denylisted Ethereum address is rejected as well.
+        assert_eq!(denylist.denied_address(&withdraw), Some(bob));
+    }
+}
@@ -0,0 +1,1263 @@
+//! Mempool is simple in memory buffer for transactions.
+//!
+//! Its role is to:
+//! 1) Accept transactions from api, check signatures and basic nonce correctness(nonce not too small).
+//! To do nonce correctness check mempool stores mapping `AccountAddress -> Nonce`, this mapping is updated
+//! when new block is committed.
+//! 2) When polled return vector of the transactions ready to be executed.
+//!
+//! Transactions are stored in the per-account queues ordered by nonce (see `AccountTxQueue`).
+//! Transaction is ready to be proposed only if all the transactions of the same account with
+//! lower nonces were already proposed, so transactions sent out of order are kept in the queue
+//! until the gap is filled. Ready transactions of the different accounts are proposed in the
+//! order defined by the `TxOrdering` strategy (see the `ordering` module for details).
+//!
+//! Queued transaction can be replaced with another one with the same nonce, if the new transaction
+//! pays a strictly higher fee in the same token. Subscribers of the replaced transaction are notified
+//! through the `ReplacedTxNotify` channel.
+//!
+//! Transactions may specify the validity window. Transactions which are not valid yet are kept
+//! in the queue, and the expired ones are discarded upon the block proposal.
+//!
+//! Fast withdrawals are kept in the queue until the matching transfer of the liquidity provider
+//! is ready, and then both are proposed as a batch (see the `fast_withdrawals` module).
+//!
+//! Amount of stored transactions is limited by the `MEMPOOL_CAPACITY` option. Once the limit is
+//! reached, transactions waiting for the missing nonces are evicted in favor of the new ready
+//! ones; if there are no such transactions, the new ones are rejected.
+//!
+//! Transactions sent by or to the addresses from the operator-maintained denylist are rejected,
+//! and the rejections are recorded in the database (see the `denylist` module for details).
+//!
+//! Accepted transactions are persisted in the database and restored on the node restart.
+//!
+//! Communication channel with other actors:
+//! Mempool does not push information to other actors, only accepts requests. (see `MempoolRequest`)
+//!
+//! Communication with db:
+//! on restart mempool restores nonces of the accounts that are stored in the account tree.
+
+// Built-in deps
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+// External uses
+use failure::Fail;
+use futures::{
+    channel::{mpsc, oneshot},
+    SinkExt, StreamExt,
+};
+use tokio::{runtime::Runtime, task::JoinHandle};
+// Workspace uses
+use models::node::{
+    tx::TxHash, AccountId, AccountUpdate, AccountUpdates, Address, FranklinTx, Nonce, PriorityOp,
+    SignedFranklinTx,
+};
+use storage::ConnectionPool;
+// Local uses
+use self::account_queue::{AccountTxQueue, QueuedTx};
+use self::denylist::Denylist;
+use self::ordering::{FeePerChunkOrdering, TxOrderKey, TxOrdering};
+use crate::{
+    eth_watch::EthWatchRequest, fast_withdrawals::FastWithdrawals, signature_checker::VerifiedTx,
+};
+use models::config_options::ConfigurationOptions;
+
+mod account_queue;
+mod denylist;
+mod ordering;
+
+/// Maximum amount of the transactions rejected because of the denylist kept in the database.
+const MAX_STORED_DENIED_TXS: i64 = 100_000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Fail)]
+pub enum TxAddError {
+    #[fail(display = "Tx nonce is too low.")]
+    NonceMismatch,
+
+    #[fail(display = "Tx is incorrect")]
+    IncorrectTx,
+
+    #[fail(display = "Transaction fee is too low")]
+    TxFeeTooLow,
+
+    #[fail(display = "EIP1271 signature could not be verified")]
+    EIP1271SignatureVerificationFail,
+
+    #[fail(display = "MissingEthSignature")]
+    MissingEthSignature,
+
+    #[fail(display = "Eth signature is incorrect")]
+    IncorrectEthSignature,
+
+    #[fail(display = "Change pubkey tx is not authorized onchain")]
+    ChangePkNotAuthorized,
+
+    #[fail(display = "Internal error")]
+    Other,
+
+    #[fail(display = "Database unavailable")]
+    DbError,
+
+    #[fail(display = "Server is in read-only mode")]
+    ReadOnlyMode,
+
+    #[fail(
+        display = "Tx with the same nonce is already queued, replacement must pay a higher fee"
+    )]
+    NonceAlreadyQueued,
+
+    #[fail(display = "Mempool is full")]
+    MempoolFull,
+
+    #[fail(display = "Transaction validity period has expired")]
+    TxExpired,
+
+    #[fail(display = "Transfer to the zero address is not allowed")]
+    TransferToZeroAddress,
+
+    #[fail(display = "Token is not supported")]
+    UnsupportedToken,
+
+    #[fail(display = "Transfer amount is not packable")]
+    AmountNotPackable,
+
+    #[fail(display = "Fast processing is supported only for withdrawals")]
+    FastProcessingNotSupported,
+
+    #[fail(display = "Fast withdrawal must be sent to the liquidity provider")]
+    IncorrectFastWithdrawal,
+
+    #[fail(display = "Address is not allowed to send or receive transactions")]
+    AddressDenied,
+
+    #[fail(display = "Server is shutting down")]
+    ShuttingDown,
+
+    #[fail(display = "Exodus mode can be triggered, transactions are not accepted")]
+    ExodusMode,
+}
+
+/// Transaction proposed for the block: either a single transaction, or a batch
+/// of transactions which must be executed atomically.
+#[derive(Clone, Debug)]
+pub enum SignedTxVariant {
+    Tx(SignedFranklinTx),
+    Batch(Vec<SignedFranklinTx>),
+}
+
+impl SignedTxVariant {
+    /// Returns the transactions of the variant.
+    pub fn txs(&self) -> &[SignedFranklinTx] {
+        match self {
+            SignedTxVariant::Tx(tx) => std::slice::from_ref(tx),
+            SignedTxVariant::Batch(txs) => txs,
+        }
+    }
+
+    pub fn txs_mut(&mut self) -> &mut [SignedFranklinTx] {
+        match self {
+            SignedTxVariant::Tx(tx) => std::slice::from_mut(tx),
+            SignedTxVariant::Batch(txs) => txs,
+        }
+    }
+}
+
+impl From<SignedFranklinTx> for SignedTxVariant {
+    fn from(tx: SignedFranklinTx) -> Self {
+        SignedTxVariant::Tx(tx)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProposedBlock {
+    pub priority_ops: Vec<PriorityOp>,
+    pub txs: Vec<SignedTxVariant>,
+    /// Last Ethereum block known at the moment of the proposal, used by the
+    /// state keeper to track the expiration deadlines of the priority operations.
+    pub current_eth_block: Option<u64>,
+}
+
+impl ProposedBlock {
+    pub fn is_empty(&self) -> bool {
+        self.priority_ops.is_empty() && self.txs.is_empty()
+    }
+}
+
+pub struct GetBlockRequest {
+    pub last_priority_op_number: u64,
+    pub response_sender: oneshot::Sender<ProposedBlock>,
+}
+
+/// Notification about the queued transaction replaced by another one with the same nonce.
+#[derive(Debug, Clone)]
+pub struct ReplacedTxNotify {
+    pub replaced_tx: TxHash,
+    pub new_tx: TxHash,
+}
+
+/// Changes in the mempool caused by the accepted transaction.
+#[derive(Debug, Default)]
+struct TxAddOutcome {
+    /// Queued transaction with the same nonce replaced by the accepted one.
+    replaced_tx: Option<SignedFranklinTx>,
+    /// Transactions evicted from the mempool to free the space for the accepted one.
+    evicted_txs: Vec<SignedFranklinTx>,
+}
+
+impl TxAddOutcome {
+    /// Returns the transactions removed from the mempool.
+    fn removed_txs(&self) -> impl Iterator<Item = &SignedFranklinTx> {
+        self.replaced_tx.iter().chain(self.evicted_txs.iter())
+    }
+}
+
+/// Checks whether the new transaction can replace the queued one:
+/// both must pay the fee in the same token, and the new fee must be strictly higher.
+fn pays_higher_fee(new_tx: &FranklinTx, queued_tx: &FranklinTx) -> bool {
+    match (new_tx.fee(), queued_tx.fee()) {
+        (Some((new_token, new_fee)), Some((queued_token, queued_fee))) => {
+            new_token == queued_token && new_fee > queued_fee
+        }
+        _ => false,
+    }
+}
+
+pub enum MempoolRequest {
+    /// Add new transaction to mempool, transaction should be previously checked
+    /// for correctness (including its Ethereum and ZKSync signatures).
+    /// oneshot is used to receive tx add result.
+    NewTx(Box<VerifiedTx>, oneshot::Sender<Result<(), TxAddError>>),
+    /// When block is committed, nonces of the account tree should be updated too.
+    UpdateNonces(AccountUpdates),
+    /// Get transactions from the mempool.
+    GetBlock(GetBlockRequest),
+    /// Reload the denylist from the database after it was changed by the operator.
+    ReloadDenylist,
+    /// Reject the new transactions, used on the server shutdown. Accepted transactions are
+    /// persisted right away, so the response means that all of them are in the database.
+    StopAcceptingTxs(oneshot::Sender<()>),
+}
+
+struct MempoolState {
+    // account and last committed nonce
+    account_nonces: HashMap<Address, Nonce>,
+    account_ids: HashMap<AccountId, Address>,
+    /// Queues of the transactions awaiting to be proposed.
+    account_queues: HashMap<Address, AccountTxQueue>,
+    /// Total amount of the transactions in the queues.
+    txs_count: usize,
+    /// Maximum amount of the transactions in the queues.
+    capacity: usize,
+    /// Arrival number to be assigned to the next accepted transaction.
+    next_arrival_id: u64,
+    /// Order of the ready transactions in the proposed block.
+    ordering: Box<dyn TxOrdering>,
+    /// `None` if the fast withdrawals are not supported.
+    fast_withdrawals: Option<FastWithdrawals>,
+}
+
+impl MempoolState {
+    fn chunks_for_tx(&self, tx: &FranklinTx) -> usize {
+        let recipient_exists = match tx {
+            FranklinTx::Transfer(tx) => self.account_nonces.contains_key(&tx.to),
+            _ => true,
+        };
+        tx.chunks(recipient_exists)
+    }
+
+    fn new(
+        account_nonces: HashMap<Address, Nonce>,
+        account_ids: HashMap<AccountId, Address>,
+        capacity: usize,
+        ordering: Box<dyn TxOrdering>,
+        fast_withdrawals: Option<FastWithdrawals>,
+    ) -> Self {
+        Self {
+            account_nonces,
+            account_ids,
+            account_queues: HashMap::new(),
+            txs_count: 0,
+            capacity,
+            next_arrival_id: 0,
+            ordering,
+            fast_withdrawals,
+        }
+    }
+
+    fn restore_from_db(
+        db_pool: &ConnectionPool,
+        capacity: usize,
+        ordering: Box<dyn TxOrdering>,
+        fast_withdrawals: Option<FastWithdrawals>,
+    ) -> Self {
+        let storage = db_pool.access_storage().expect("mempool db restore");
+        let (_, accounts) = storage
+            .chain()
+            .state_schema()
+            .load_committed_state(None)
+            .expect("mempool account state load");
+
+        let mut account_ids = HashMap::new();
+        let mut account_nonces = HashMap::new();
+
+        for (id, account) in accounts {
+            account_ids.insert(id, account.address);
+            account_nonces.insert(account.address, account.nonce);
+        }
+
+        // Remove any possible duplicates of already executed transactions
+        // from the database.
+        storage
+            .chain()
+            .mempool_schema()
+            .collect_garbage()
+            .expect("Collecting garbage in the mempool schema failed");
+
+        // Load transactions that were not yet processed and are awaiting in the
+        // mempool.
+        let stored_txs = storage
+            .chain()
+            .mempool_schema()
+            .load_txs()
+            .expect("Attempt to restore mempool txs from DB failed");
+
+        let mut state = Self::new(
+            account_nonces,
+            account_ids,
+            capacity,
+            ordering,
+            fast_withdrawals,
+        );
+        let mut removed_txs = Vec::new();
+        for tx in stored_txs {
+            match state.add_tx(tx.clone()) {
+                Ok(outcome) => removed_txs.extend(outcome.removed_txs().cloned()),
+                Err(err) => {
+                    log::warn!(
+                        "Stored mempool tx {} is discarded: {}",
+                        tx.hash().to_string(),
+                        err
+                    );
+                    removed_txs.push(tx);
+                }
+            }
+        }
+        for tx in removed_txs {
+            storage
+                .chain()
+                .mempool_schema()
+                .remove_tx(tx.hash().as_ref())
+                .expect("Failed to remove discarded tx from the mempool schema");
+        }
+
+        log::info!(
+            "{} transactions were restored from the persistent mempool storage",
+            state.txs_count
+        );
+
+        state
+    }
+
+    fn nonce(&self, address: &Address) -> Nonce {
+        *self.account_nonces.get(address).unwrap_or(&0)
+    }
+
+    /// Adds the transaction to the queue of its sender, replacing the queued
+    /// transaction with the same nonce if the new one pays a higher fee.
+    fn add_tx(&mut self, tx: SignedFranklinTx) -> Result<TxAddOutcome, TxAddError> {
+        // Correctness should be checked by `signature_checker`, thus
+        // `tx.check_correctness()` is not invoked here.
+
+        let address = tx.account();
+        let nonce = tx.nonce();
+        let committed_nonce = self.nonce(&address);
+        if nonce < committed_nonce {
+            return Err(TxAddError::NonceMismatch);
+        }
+        if tx.is_expired_at(unix_timestamp()) {
+            return Err(TxAddError::TxExpired);
+        }
+
+        let queue = self
+            .account_queues
+            .entry(address)
+            .or_insert_with(|| AccountTxQueue::new(committed_nonce));
+        if let Some(queued) = queue.get(nonce) {
+            if !pays_higher_fee(&tx, &queued.tx) {
+                return Err(TxAddError::NonceAlreadyQueued);
+            }
+            let replaced = queue.replace(tx).expect("queued tx is missing");
+            return Ok(TxAddOutcome {
+                replaced_tx: Some(replaced.tx),
+                evicted_txs: Vec::new(),
+            });
+        }
+        let will_be_ready = nonce < queue.next_nonce() || queue.would_be_ready(nonce);
+
+        let mut evicted_txs = Vec::new();
+        if self.txs_count >= self.capacity {
+            // There is no point in replacing the transaction waiting for the missing
+            // nonce with another one.
+            let evicted_tx = if will_be_ready {
+                self.evict_unready_tx()
+            } else {
+                None
+            };
+            match evicted_tx {
+                Some(evicted_tx) => evicted_txs.push(evicted_tx),
+                None => {
+                    self.remove_queue_if_unused(&address);
+                    return Err(TxAddError::MempoolFull);
+                }
+            }
+        }
+
+        let arrival_id = self.next_arrival_id;
+        self.next_arrival_id += 1;
+        self.account_queues
+            .entry(address)
+            .or_insert_with(|| AccountTxQueue::new(committed_nonce))
+            .insert(QueuedTx { tx, arrival_id });
+        self.txs_count += 1;
+
+        Ok(TxAddOutcome {
+            replaced_tx: None,
+            evicted_txs,
+        })
+    }
+
+    /// Adds the transaction and persists the changes with the provided function.
+    /// If the changes can't be persisted, the mempool is reverted to the previous state:
+    /// the transaction is removed, and the replaced and evicted ones are queued back.
+    fn add_tx_persisted(
+        &mut self,
+        tx: SignedFranklinTx,
+        persist: impl FnOnce(&SignedFranklinTx, &TxAddOutcome) -> Result<(), failure::Error>,
+    ) -> Result<TxAddOutcome, TxAddError> {
+        let outcome = self.add_tx(tx.clone())?;
+        if let Err(err) = persist(&tx, &outcome) {
+            log::warn!("Mempool storage access error: {}", err);
+            self.remove_tx(&tx);
+            for removed_tx in outcome.replaced_tx.into_iter().chain(outcome.evicted_txs) {
+                if let Err(err) = self.add_tx(removed_tx.clone()) {
+                    log::warn!(
+                        "Failed to restore tx {} in the mempool: {}",
+                        removed_tx.hash().to_string(),
+                        err
+                    );
+                }
+            }
+            return Err(TxAddError::DbError);
+        }
+        Ok(outcome)
+    }
+
+    /// Removes the queued transactions matching the predicate from the mempool.
+    fn remove_txs_where(
+        &mut self,
+        predicate: impl Fn(&SignedFranklinTx) -> bool,
+    ) -> Vec<SignedFranklinTx> {
+        let removed_txs: Vec<_> = self
+            .account_queues
+            .values()
+            .flat_map(|queue| queue.txs())
+            .filter(|queued| predicate(&queued.tx))
+            .map(|queued| queued.tx.clone())
+            .collect();
+        for tx in &removed_txs {
+            self.remove_tx(tx);
+        }
+        removed_txs
+    }
+
+    /// Removes the transaction from the mempool, e.g. if it failed to be persisted.
+    fn remove_tx(&mut self, tx: &SignedFranklinTx) {
+        let address = tx.account();
+        if let Some(queue) = self.account_queues.get_mut(&address) {
+            if queue.remove(tx.nonce()).is_some() {
+                self.txs_count -= 1;
+            }
+        }
+        self.remove_queue_if_unused(&address);
+    }
+
+    /// Evicts the most recent unready transaction of the account with the longest queue.
+    fn evict_unready_tx(&mut self) -> Option<SignedFranklinTx> {
+        let address = *self
+            .account_queues
+            .iter()
+            .filter(|(_, queue)| queue.has_gap())
+            .max_by_key(|(_, queue)| queue.len())?
+            .0;
+
+        let evicted_tx = self
+            .account_queues
+            .get_mut(&address)
+            .and_then(|queue| queue.evict_last_unready())?;
+        self.txs_count -= 1;
+        self.remove_queue_if_unused(&address);
+
+        log::debug!(
+            "Tx {} was evicted from the full mempool",
+            evicted_tx.tx.hash().to_string()
+        );
+        Some(evicted_tx.tx)
+    }
+
+    /// Removes the empty queue if there are no proposed transactions that are not committed yet.
+    fn remove_queue_if_unused(&mut self, address: &Address) {
+        let committed_nonce = self.nonce(address);
+        let unused = self
+            .account_queues
+            .get(address)
+            .map(|queue| queue.is_empty() && queue.next_nonce() <= committed_nonce)
+            .unwrap_or(false);
+        if unused {
+            self.account_queues.remove(address);
+        }
+    }
+
+    /// Removes the deleted account along with its queued transactions.
+    /// Returns the transactions that can't be executed anymore.
+    fn remove_account(&mut self, address: &Address) -> Vec<SignedFranklinTx> {
+        self.account_nonces.remove(address);
+        let outdated_txs: Vec<_> = self
+            .account_queues
+            .remove(address)
+            .map(|queue| queue.txs().map(|queued| queued.tx.clone()).collect())
+            .unwrap_or_default();
+        self.txs_count -= outdated_txs.len();
+        outdated_txs
+    }
+
+    /// Updates the committed nonce of the account.
+    /// Returns the transactions that can't be executed anymore.
+    fn update_nonce(&mut self, address: Address, nonce: Nonce) -> Vec<SignedFranklinTx> {
+        self.account_nonces.insert(address, nonce);
+
+        let outdated_txs = self
+            .account_queues
+            .get_mut(&address)
+            .map(|queue| queue.update_committed_nonce(nonce))
+            .unwrap_or_default();
+        self.txs_count -= outdated_txs.len();
+        self.remove_queue_if_unused(&address);
+
+        outdated_txs.into_iter().map(|queued| queued.tx).collect()
+    }
+
+    fn order_key(&self, queued: &QueuedTx) -> TxOrderKey {
+        self.ordering
+            .order_key(queued, self.chunks_for_tx(&queued.tx))
+    }
+
+    /// Checks whether the transaction is a fast withdrawal waiting for the LP transfer.
+    fn is_fast_withdrawal(&self, tx: &FranklinTx) -> bool {
+        self.fast_withdrawals
+            .as_ref()
+            .map(|fast_withdrawals| fast_withdrawals.is_fast_withdrawal(tx))
+            .unwrap_or(false)
+    }
+
+    /// Returns the position of the earliest waiting fast withdrawal matched
+    /// by the provided transaction, if any.
+    fn matched_fast_withdrawal(&self, waiting: &[Address], tx: &FranklinTx) -> Option<usize> {
+        let fast_withdrawals = self.fast_withdrawals.as_ref()?;
+        waiting.iter().position(|address| {
+            self.account_queues[address]
+                .ready_head()
+                .map(|queued| fast_withdrawals.is_matching_transfer(&queued.tx, tx))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Takes the ready transaction of the account, pushing the next one of the same
+    /// account to the ready heads, if any.
+    fn pop_ready_tx(
+        &mut self,
+        address: Address,
+        ready_heads: &mut BinaryHeap<Reverse<(TxOrderKey, Address)>>,
+    ) -> SignedFranklinTx {
+        let queue = self
+            .account_queues
+            .get_mut(&address)
+            .expect("account queue is missing");
+        let queued = queue.pop_ready().expect("ready tx is missing");
+        if let Some(next) = self.account_queues[&address].ready_head() {
+            ready_heads.push(Reverse((self.order_key(next), address)));
+        }
+        self.txs_count -= 1;
+        queued.tx
+    }
+
+    /// Selects the ready transactions fitting into the provided amount of chunks,
+    /// in the order defined by the ordering strategy. Transactions which are not valid
+    /// at the provided time yet are skipped, and the expired ones are removed from the mempool.
+    ///
+    /// Fast withdrawals are selected only along with the matching LP transfers, as batches.
+    /// Until then, the subsequent transactions of the withdrawing account are not selected.
+    ///
+    /// Returns: chunks left, selected transactions, expired transactions.
+    fn select_ready_txs(
+        &mut self,
+        mut chunks_left: usize,
+        timestamp: u64,
+    ) -> (usize, Vec<SignedTxVariant>, Vec<SignedFranklinTx>) {
+        // Accounts whose ready transaction is a valid fast withdrawal, in the order of arrival.
+        let mut waiting_withdrawals = Vec::new();
+        let mut ready_heads = BinaryHeap::new();
+        for (address, queue) in &self.account_queues {
+            let queued = match queue.ready_head() {
+                Some(queued) => queued,
+                None => continue,
+            };
+            if self.is_fast_withdrawal(&queued.tx) && queued.tx.is_valid_at(timestamp) {
+                waiting_withdrawals.push((queued.arrival_id, *address));
+            } else {
+                ready_heads.push(Reverse((self.order_key(queued), *address)));
+            }
+        }
+        waiting_withdrawals.sort();
+        let mut waiting_withdrawals: Vec<_> = waiting_withdrawals
+            .into_iter()
+            .map(|(_, address)| address)
+            .collect();
+
+        let mut selected_txs = Vec::new();
+        let mut expired_txs = Vec::new();
+        while let Some(Reverse((_, address))) = ready_heads.pop() {
+            let (chunks_for_tx, expired, valid, fast_withdrawal, matched) = {
+                let queued = self.account_queues[&address]
+                    .ready_head()
+                    .expect("ready tx is missing");
+                (
+                    self.chunks_for_tx(&queued.tx),
+                    queued.tx.is_expired_at(timestamp),
+                    queued.tx.is_valid_at(timestamp),
+                    self.is_fast_withdrawal(&queued.tx),
+                    self.matched_fast_withdrawal(&waiting_withdrawals, &queued.tx),
+                )
+            };
+            if expired {
+                // Subsequent transactions of the account will wait for the replacement
+                // of the expired one.
+                let queue = self
+                    .account_queues
+                    .get_mut(&address)
+                    .expect("account queue is missing");
+                let queued = queue
+                    .remove(queue.next_nonce())
+                    .expect("ready tx is missing");
+                self.txs_count -= 1;
+                expired_txs.push(queued.tx);
+                continue;
+            }
+            if fast_withdrawal {
+                // Fast withdrawal is selected only along with the matching LP transfer.
+                continue;
+            }
+            let chunks_for_withdrawal = matched
+                .map(|idx| {
+                    let queued = self.account_queues[&waiting_withdrawals[idx]]
+                        .ready_head()
+                        .expect("fast withdrawal is missing");
+                    self.chunks_for_tx(&queued.tx)
+                })
+                .unwrap_or(0);
+            if !valid || chunks_for_tx + chunks_for_withdrawal > chunks_left {
+                // Transaction is not valid yet or does not fit, so the subsequent
+                // transactions of the same account can't be proposed either.
+                continue;
+            }
+
+            let tx = self.pop_ready_tx(address, &mut ready_heads);
+            chunks_left -= chunks_for_tx + chunks_for_withdrawal;
+            match matched {
+                Some(idx) => {
+                    let withdrawal_address = waiting_withdrawals.remove(idx);
+                    let withdrawal = self.pop_ready_tx(withdrawal_address, &mut ready_heads);
+                    selected_txs.push(SignedTxVariant::Batch(vec![withdrawal, tx]));
+                }
+                None => selected_txs.push(SignedTxVariant::Tx(tx)),
+            }
+        }
+        for tx in &expired_txs {
+            self.remove_queue_if_unused(&tx.account());
+        }
+
+        (chunks_left, selected_txs, expired_txs)
+    }
+}
+
+struct Mempool {
+    db_pool: ConnectionPool,
+    mempool_state: MempoolState,
+    requests: mpsc::Receiver<MempoolRequest>,
+    eth_watch_req: mpsc::Sender<EthWatchRequest>,
+    replaced_tx_notify: mpsc::Sender<ReplacedTxNotify>,
+    max_block_size_chunks: usize,
+    denylist: Denylist,
+    /// Unset once the server is shutting down.
+    accepting_txs: bool,
+}
+
+impl Mempool {
+    async fn add_tx(&mut self, tx: VerifiedTx) -> Result<(), TxAddError> {
+        if !self.accepting_txs {
+            return Err(TxAddError::ShuttingDown);
+        }
+        let tx = tx.into_inner();
+        if let Some(address) = self.denylist.denied_address(&tx) {
+            log::warn!(
+                "Tx {} is rejected, address {:?} is denylisted",
+                tx.hash().to_string(),
+                address
+            );
+            if let Err(err) = self.log_denied_tx(&tx, &address) {
+                log::error!("Failed to record the denied tx: {}", err);
+            }
+            return Err(TxAddError::AddressDenied);
+        }
+
+        let db_pool = &self.db_pool;
+        let outcome = self
+            .mempool_state
+            .add_tx_persisted(tx.clone(), |tx, outcome| persist_tx(db_pool, tx, outcome))?;
+
+        if let Some(replaced_tx) = outcome.replaced_tx {
+            let notify = ReplacedTxNotify {
+                replaced_tx: replaced_tx.hash(),
+                new_tx: tx.hash(),
+            };
+            self.replaced_tx_notify
+                .send(notify)
+                .await
+                .map_err(|e| log::warn!("Failed to send replaced tx notification: {}", e))
+                .unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    fn log_denied_tx(
+        &self,
+        tx: &SignedFranklinTx,
+        address: &Address,
+    ) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        storage.denylist_schema().log_denied_tx(
+            tx.hash().as_ref(),
+            address,
+            MAX_STORED_DENIED_TXS,
+        )?;
+        Ok(())
+    }
+
+    /// Reloads the denylist from the database. If the denylist can't be loaded,
+    /// the previously loaded one is kept.
+    fn reload_denylist(&mut self) {
+        match Denylist::load(&self.db_pool) {
+            Ok(denylist) => {
+                log::info!("Denylist reloaded, {} addresses", denylist.len());
+                self.denylist = denylist;
+                self.remove_denied_txs();
+            }
+            Err(err) => log::error!("Failed to reload the denylist: {}", err),
+        }
+    }
+
+    /// Removes the queued transactions involving the denylisted addresses.
+    fn remove_denied_txs(&mut self) {
+        let denylist = &self.denylist;
+        let denied_txs = self
+            .mempool_state
+            .remove_txs_where(|tx| denylist.denied_address(tx).is_some());
+        if denied_txs.is_empty() {
+            return;
+        }
+
+        log::info!(
+            "{} queued txs involving the denylisted addresses were removed",
+            denied_txs.len()
+        );
+        if let Err(err) = self.remove_persisted_txs(&denied_txs) {
+            log::warn!(
+                "Failed to remove denied txs from the mempool storage: {}",
+                err
+            );
+        }
+    }
+
+    fn remove_persisted_txs(&self, txs: &[SignedFranklinTx]) -> Result<(), failure::Error> {
+        let storage = self.db_pool.access_storage()?;
+        for tx in txs {
+            storage
+                .chain()
+                .mempool_schema()
+                .remove_tx(tx.hash().as_ref())?;
+        }
+        Ok(())
+    }
+
+    fn update_nonces(&mut self, updates: AccountUpdates) {
+        let mut outdated_txs = Vec::new();
+        for (id, update) in updates {
+            match update {
+                AccountUpdate::Create { address, nonce } => {
+                    self.mempool_state.account_ids.insert(id, address);
+                    outdated_txs.extend(self.mempool_state.update_nonce(address, nonce));
+                }
+                AccountUpdate::Delete { address, .. } => {
+                    self.mempool_state.account_ids.remove(&id);
+                    outdated_txs.extend(self.mempool_state.remove_account(&address));
+                }
+                AccountUpdate::UpdateBalance { new_nonce, .. }
+                | AccountUpdate::ChangePubKeyHash { new_nonce, .. } => {
+                    if let Some(address) = self.mempool_state.account_ids.get(&id).cloned() {
+                        outdated_txs.extend(self.mempool_state.update_nonce(address, new_nonce));
+                    }
+                }
+            }
+        }
+
+        if outdated_txs.is_empty() {
+            return;
+        }
+        if let Err(err) = self.remove_persisted_txs(&outdated_txs) {
+            log::warn!(
+                "Failed to remove outdated txs from the mempool storage: {}",
+                err
+            );
+        }
+    }
+
+    async fn run(mut self) {
+        while let Some(request) = self.requests.next().await {
+            match request {
+                MempoolRequest::NewTx(tx, resp) => {
+                    let tx_add_result = self.add_tx(*tx).await;
+                    resp.send(tx_add_result).unwrap_or_default();
+                }
+                MempoolRequest::GetBlock(block) => {
+                    // Generate proposed block.
+                    let proposed_block =
+                        self.propose_new_block(block.last_priority_op_number).await;
+
+                    // Send the proposed block to the request initiator.
+                    block
+                        .response_sender
+                        .send(proposed_block)
+                        .expect("mempool proposed block response send failed");
+                }
+                MempoolRequest::UpdateNonces(updates) => {
+                    self.update_nonces(updates);
+                }
+                MempoolRequest::ReloadDenylist => {
+                    self.reload_denylist();
+                }
+                MempoolRequest::StopAcceptingTxs(response) => {
+                    log::info!(
+                        "Mempool stopped accepting transactions, {} transactions are persisted",
+                        self.mempool_state.txs_count
+                    );
+                    self.accepting_txs = false;
+                    response.send(()).unwrap_or_default();
+                }
+            }
+        }
+    }
+
+    async fn propose_new_block(&mut self, current_unprocessed_priority_op: u64) -> ProposedBlock {
+        let (chunks_left, priority_ops) = self
+            .select_priority_ops(current_unprocessed_priority_op)
+            .await;
+        let (_chunks_left, txs) = self.prepare_tx_for_block(chunks_left);
+        let current_eth_block = self.last_ethereum_block().await;
+
+        trace!("Proposed priority ops for block: {:#?}", priority_ops);
+        trace!("Proposed txs for block: {:#?}", txs);
+        ProposedBlock {
+            priority_ops,
+            txs,
+            current_eth_block: Some(current_eth_block),
+        }
+    }
+
+    async fn last_ethereum_block(&self) -> u64 {
+        let eth_watch_resp = oneshot::channel();
+        self.eth_watch_req
+            .clone()
+            .send(EthWatchRequest::GetLastEthereumBlock {
+                resp: eth_watch_resp.0,
+            })
+            .await
+            .expect("ETH watch req receiver dropped");
+
+        eth_watch_resp.1.await.expect("Err response from eth watch")
+    }
+
+    /// Returns: chunks left from max amount of chunks, ops selected
+    async fn select_priority_ops(
+        &self,
+        current_unprocessed_priority_op: u64,
+    ) -> (usize, Vec<PriorityOp>) {
+        let eth_watch_resp = oneshot::channel();
+        self.eth_watch_req
+            .clone()
+            .send(EthWatchRequest::GetPriorityQueueOps {
+                op_start_id: current_unprocessed_priority_op,
+                max_chunks: self.max_block_size_chunks,
+                resp: eth_watch_resp.0,
+            })
+            .await
+            .expect("ETH watch req receiver dropped");
+
+        let priority_ops = eth_watch_resp.1.await.expect("Err response from eth watch");
+
+        (
+            self.max_block_size_chunks
+                - priority_ops
+                    .iter()
+                    .map(|op| op.data.chunks())
+                    .sum::<usize>(),
+            priority_ops,
+        )
+    }
+
+    fn prepare_tx_for_block(&mut self, chunks_left: usize) -> (usize, Vec<SignedTxVariant>) {
+        let (chunks_left, txs, expired_txs) = self
+            .mempool_state
+            .select_ready_txs(chunks_left, unix_timestamp());
+
+        if !expired_txs.is_empty() {
+            log::debug!(
+                "{} expired txs were removed from the mempool",
+                expired_txs.len()
+            );
+            if let Err(err) = self.remove_persisted_txs(&expired_txs) {
+                log::warn!(
+                    "Failed to remove expired txs from the mempool storage: {}",
+                    err
+                );
+            }
+        }
+
+        (chunks_left, txs)
+    }
+}
+
+/// Stores the accepted transaction in the database, removing the replaced and evicted ones.
+fn persist_tx(
+    db_pool: &ConnectionPool,
+    tx: &SignedFranklinTx,
+    outcome: &TxAddOutcome,
+) -> Result<(), failure::Error> {
+    let storage = db_pool.access_storage()?;
+    storage.transaction(|| {
+        storage.chain().mempool_schema().insert_tx(tx)?;
+        for removed_tx in outcome.removed_txs() {
+            storage
+                .chain()
+                .mempool_schema()
+                .remove_tx(removed_tx.hash().as_ref())?;
+        }
+        Ok(())
+    })
+}
+
+/// Returns the current unix timestamp (in seconds) to check the transactions validity windows.
+fn unix_timestamp() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+#[must_use]
+pub fn run_mempool_task(
+    db_pool: ConnectionPool,
+    requests: mpsc::Receiver<MempoolRequest>,
+    eth_watch_req: mpsc::Sender<EthWatchRequest>,
+    replaced_tx_notify: mpsc::Sender<ReplacedTxNotify>,
+    config: &ConfigurationOptions,
+    runtime: &Runtime,
+) -> JoinHandle<()> {
+    let mempool_state = MempoolState::restore_from_db(
+        &db_pool,
+        config.mempool_capacity,
+        Box::new(FeePerChunkOrdering),
+        config.fast_withdrawal_lp_address.map(FastWithdrawals::new),
+    );
+
+    let denylist = Denylist::load(&db_pool).expect("Failed to load the denylist");
+
+    let mut mempool = Mempool {
+        db_pool,
+        mempool_state,
+        requests,
+        eth_watch_req,
+        replaced_tx_notify,
+        max_block_size_chunks: *config
+            .available_block_chunk_sizes
+            .iter()
+            .max()
+            .expect("failed to find max block chunks size"),
+        denylist,
+        accepting_txs: true,
+    };
+    // Transactions restored from the database could have been sent before
+    // the addresses were denylisted.
+    mempool.remove_denied_txs();
+    runtime.spawn(mempool.run())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::Transfer;
+    use models::test_utils::{self, address};
+    use num::BigUint;
+
+    fn transfer_with_fee(from: Address, nonce: Nonce, fee: u32) -> SignedFranklinTx {
+        let transfer = Transfer::new(
+            0,
+            from,
+            Default::default(),
+            0,
+            0u32.into(),
+            fee.into(),
+            nonce,
+            None,
+        );
+        FranklinTx::Transfer(Box::new(transfer)).into()
+    }
+
+    fn transfer(from: Address, nonce: Nonce) -> SignedFranklinTx {
+        transfer_with_fee(from, nonce, 0)
+    }
+
+    fn mempool_state(capacity: usize) -> MempoolState {
+        MempoolState::new(
+            HashMap::new(),
+            HashMap::new(),
+            capacity,
+            Box::new(FeePerChunkOrdering),
+            None,
+        )
+    }
+
+    /// Returns the selected transactions along with the account and the nonce.
+    fn selected(txs: &[SignedTxVariant]) -> Vec<(Address, Nonce)> {
+        txs.iter()
+            .flat_map(SignedTxVariant::txs)
+            .map(|tx| (tx.account(), tx.nonce()))
+            .collect()
+    }
+
+    /// Checks that ready transactions with equal fees are selected in the order of arrival,
+    /// and the transactions waiting for the missing nonce are not selected.
+    #[test]
+    fn ready_txs_selection() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(10);
+
+        state.add_tx(transfer(first, 1)).unwrap();
+        state.add_tx(transfer(second, 0)).unwrap();
+        state.add_tx(transfer(first, 0)).unwrap();
+        state.add_tx(transfer(second, 2)).unwrap();
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert_eq!(selected(&txs), vec![(second, 0), (first, 0), (first, 1)]);
+        assert_eq!(state.txs_count, 1);
+    }
+
+    /// Checks that transactions paying the higher fee are selected first,
+    /// while the transactions of the same account are still ordered by nonce.
+    #[test]
+    fn fee_per_chunk_selection() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(10);
+
+        state.add_tx(transfer_with_fee(first, 0, 10)).unwrap();
+        state.add_tx(transfer_with_fee(first, 1, 100)).unwrap();
+        state.add_tx(transfer_with_fee(second, 0, 50)).unwrap();
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert_eq!(selected(&txs), vec![(second, 0), (first, 0), (first, 1)]);
+    }
+
+    /// Checks that full mempool evicts the unready transactions in favor of the ready ones.
+    #[test]
+    fn eviction_when_full() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(2);
+
+        state.add_tx(transfer(first, 0)).unwrap();
+        state.add_tx(transfer(first, 5)).unwrap();
+
+        // Unready transaction can't replace the other one.
+        assert!(matches!(
+            state.add_tx(transfer(second, 3)),
+            Err(TxAddError::MempoolFull)
+        ));
+
+        let evicted = state.add_tx(transfer(second, 0)).unwrap().evicted_txs;
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].nonce(), 5);
+
+        // Now there are no transactions to evict.
+        assert!(matches!(
+            state.add_tx(transfer(second, 1)),
+            Err(TxAddError::MempoolFull)
+        ));
+    }
+
+    /// Checks that the queued transaction is replaced only by the transaction with a higher fee.
+    #[test]
+    fn replace_by_fee() {
+        let address = Address::from_low_u64_be(1);
+        let mut state = mempool_state(10);
+
+        let queued_tx = transfer_with_fee(address, 0, 10);
+        state.add_tx(queued_tx.clone()).unwrap();
+
+        assert!(matches!(
+            state.add_tx(transfer_with_fee(address, 0, 10)),
+            Err(TxAddError::NonceAlreadyQueued)
+        ));
+
+        let new_tx = transfer_with_fee(address, 0, 11);
+        let outcome = state.add_tx(new_tx.clone()).unwrap();
+        assert_eq!(outcome.replaced_tx.unwrap().hash(), queued_tx.hash());
+        assert_eq!(state.txs_count, 1);
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].txs()[0].hash(), new_tx.hash());
+    }
+
+    /// Checks that transactions are selected only within their validity windows.
+    #[test]
+    fn validity_window() {
+        let address = Address::from_low_u64_be(1);
+        let mut state = mempool_state(10);
+
+        let mut tx = transfer(address, 0);
+        if let FranklinTx::Transfer(transfer) = &mut tx.tx {
+            transfer.valid_from = Some(100);
+            transfer.valid_until = Some(unix_timestamp() + 1000);
+        }
+        state.add_tx(tx).unwrap();
+        state.add_tx(transfer(address, 1)).unwrap();
+
+        // Transaction is not valid yet, and the subsequent one has to wait for it.
+        let (_, txs, expired) = state.select_ready_txs(usize::max_value(), 50);
+        assert!(txs.is_empty() && expired.is_empty());
+
+        // Transaction has expired, so it's removed, but the next one is still waiting.
+        let (_, txs, expired) = state.select_ready_txs(usize::max_value(), u64::max_value());
+        assert!(txs.is_empty());
+        assert_eq!(expired.len(), 1);
+        assert_eq!(state.txs_count, 1);
+    }
+
+    /// Checks that if the accepted transaction can't be persisted, it's removed from the mempool,
+    /// and the evicted and replaced transactions are queued back.
+    #[test]
+    fn failed_persist_reverts_tx() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(2);
+        let failing_persist = |_: &SignedFranklinTx, _: &TxAddOutcome| {
+            Err::<(), _>(failure::format_err!("Database is unavailable"))
+        };
+
+        state.add_tx(transfer_with_fee(first, 0, 10)).unwrap();
+        state.add_tx(transfer(first, 5)).unwrap();
+
+        // Transaction evicting the unready one.
+        assert!(matches!(
+            state.add_tx_persisted(transfer(second, 0), failing_persist),
+            Err(TxAddError::DbError)
+        ));
+        // Transaction replacing the queued one.
+        assert!(matches!(
+            state.add_tx_persisted(transfer_with_fee(first, 0, 20), failing_persist),
+            Err(TxAddError::DbError)
+        ));
+        assert_eq!(state.txs_count, 2);
+        assert!(!state.account_queues.contains_key(&second));
+
+        let queued: Vec<_> = state.account_queues[&first]
+            .txs()
+            .map(|queued| (queued.tx.nonce(), queued.tx.fee().unwrap().1))
+            .collect();
+        assert_eq!(
+            queued,
+            vec![(0, BigUint::from(10u32)), (5, BigUint::from(0u32))]
+        );
+    }
+
+    /// Checks that the queued transactions of the deleted account are removed.
+    #[test]
+    fn deleted_account_txs_removal() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(10);
+        state.add_tx(transfer(first, 0)).unwrap();
+        state.add_tx(transfer(first, 2)).unwrap();
+        state.add_tx(transfer(second, 0)).unwrap();
+
+        let outdated = state.remove_account(&first);
+        assert_eq!(outdated.len(), 2);
+        assert_eq!(state.txs_count, 1);
+        assert!(!state.account_queues.contains_key(&first));
+    }
+
+    /// Checks that transactions involving the denylisted addresses are removed from the queues.
+    #[test]
+    fn denied_txs_removal() {
+        let (first, second) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut state = mempool_state(10);
+        state.add_tx(transfer(first, 0)).unwrap();
+        state.add_tx(transfer(first, 1)).unwrap();
+        state.add_tx(transfer(second, 0)).unwrap();
+
+        let denylist = Denylist::new(vec![first]);
+        let removed = state.remove_txs_where(|tx| denylist.denied_address(tx).is_some());
+        assert_eq!(removed.len(), 2);
+        assert_eq!(state.txs_count, 1);
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert_eq!(selected(&txs), vec![(second, 0)]);
+    }
+
+    /// Checks that the fast withdrawal is selected only along with the matching LP transfer,
+    /// and the subsequent transactions of the withdrawing account wait for it.
+    #[test]
+    fn fast_withdrawal_pairing() {
+        let (user, lp) = (address(1), address(2));
+        let mut state = mempool_state(10);
+        state.fast_withdrawals = Some(FastWithdrawals::new(lp));
+
+        let withdrawal: SignedFranklinTx =
+            FranklinTx::Withdraw(Box::new(test_utils::withdraw(user, lp, 100, 0))).into();
+        state.add_tx(withdrawal).unwrap();
+        state.add_tx(transfer(user, 1)).unwrap();
+        // Transfer of the other amount doesn't match the withdrawal.
+        let mut lp_transfer = test_utils::transfer(lp, user, 50, 0);
+        state
+            .add_tx(FranklinTx::Transfer(Box::new(lp_transfer.clone())).into())
+            .unwrap();
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert_eq!(selected(&txs), vec![(lp, 0)]);
+        assert_eq!(state.txs_count, 2);
+
+        lp_transfer.amount = 100u32.into();
+        lp_transfer.nonce = 1;
+        state
+            .add_tx(FranklinTx::Transfer(Box::new(lp_transfer)).into())
+            .unwrap();
+
+        let (_, txs, _) = state.select_ready_txs(usize::max_value(), 0);
+        assert!(matches!(&txs[0], SignedTxVariant::Batch(batch) if batch.len() == 2));
+        assert_eq!(selected(&txs), vec![(user, 0), (lp, 1), (user, 1)]);
+        assert_eq!(state.txs_count, 0);
+    }
+}
@@ -0,0 +1,128 @@
+//! Policies defining the order in which the ready transactions are proposed for the block.
+//!
+//! The overall order of the operations in the proposed block is the following:
+//!
+//! 1. Priority operations always go first, in the order of their serial IDs.
+//! 2. Ready transactions of the different accounts are ordered by the `TxOrdering`
+//!    strategy of the mempool.
+//! 3. Transactions of the same account are always proposed in the order of nonces,
+//!    regardless of the strategy.
+//!
+//! Strategies must be deterministic: the same set of transactions must always be
+//! ordered in the same way. Ties are resolved in the order of arrival, and then by the
+//! account address.
+
+// Built-in deps
+use std::cmp::Reverse;
+use std::fmt::Debug;
+// External uses
+use num::{BigUint, Zero};
+// Local uses
+use super::account_queue::QueuedTx;
+
+/// Position of the transaction among the ready ones, transactions with the lower
+/// keys are proposed first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TxOrderKey {
+    /// Primary criterion defined by the ordering strategy.
+    rank: Reverse<BigUint>,
+    /// Transactions with the equal rank are proposed in the order of arrival.
+    arrival_id: u64,
+}
+
+impl TxOrderKey {
+    /// Creates a key for the transaction with the provided rank,
+    /// transactions with the higher rank are proposed first.
+    pub fn new(rank: BigUint, arrival_id: u64) -> Self {
+        Self {
+            rank: Reverse(rank),
+            arrival_id,
+        }
+    }
+}
+
+/// Strategy defining the order of the ready transactions of the different accounts.
+pub trait TxOrdering: Debug + Send + Sync {
+    /// Returns the ordering key of the queued transaction, which occupies
+    /// the provided amount of chunks in the block.
+    fn order_key(&self, queued: &QueuedTx, chunks: usize) -> TxOrderKey;
+}
+
+/// Proposes the transactions in the order of their arrival.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ArrivalOrdering;
+
+impl TxOrdering for ArrivalOrdering {
+    fn order_key(&self, queued: &QueuedTx, _chunks: usize) -> TxOrderKey {
+        TxOrderKey::new(BigUint::zero(), queued.arrival_id)
+    }
+}
+
+/// Proposes the transactions paying the higher fee per chunk of the block first,
+/// so the block space is used in the most profitable way.
+///
+/// Note that the mempool has no access to the token prices, thus fees paid in the
+/// different tokens are compared by the amounts. The fee ticker ensures that any
+/// accepted fee covers the costs of the transaction anyway.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FeePerChunkOrdering;
+
+impl TxOrdering for FeePerChunkOrdering {
+    fn order_key(&self, queued: &QueuedTx, chunks: usize) -> TxOrderKey {
+        let fee_per_chunk = queued
+            .tx
+            .fee()
+            .map(|(_, fee)| fee / BigUint::from(chunks.max(1)))
+            .unwrap_or_default();
+        TxOrderKey::new(fee_per_chunk, queued.arrival_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::node::{FranklinTx, Transfer};
+
+    fn queued_tx(fee: u32, arrival_id: u64) -> QueuedTx {
+        let transfer = Transfer::new(
+            0,
+            Default::default(),
+            Default::default(),
+            0,
+            0u32.into(),
+            fee.into(),
+            0,
+            None,
+        );
+        QueuedTx {
+            tx: FranklinTx::Transfer(Box::new(transfer)).into(),
+            arrival_id,
+        }
+    }
+
+    #[test]
+    fn arrival_ordering() {
+        let ordering = ArrivalOrdering;
+        let early = ordering.order_key(&queued_tx(1, 0), 1);
+        let late = ordering.order_key(&queued_tx(100, 1), 1);
+        assert!(early < late);
+    }
+
+    #[test]
+    fn fee_per_chunk_ordering() {
+        let ordering = FeePerChunkOrdering;
+
+        // Higher fee goes first.
+        let cheap = ordering.order_key(&queued_tx(10, 0), 2);
+        let expensive = ordering.order_key(&queued_tx(30, 1), 2);
+        assert!(expensive < cheap);
+
+        // Higher fee occupying more chunks may go after the lower one.
+        let large = ordering.order_key(&queued_tx(30, 0), 6);
+        assert!(cheap < large);
+
+        // Equal fees per chunk are ordered by arrival.
+        let same_fee = ordering.order_key(&queued_tx(10, 2), 2);
+        assert!(cheap < same_fee);
+    }
+}
@@ -1,4 +1,7 @@
-//! This module handles metric export to the Prometheus server
+//! This module handles metric export to the Prometheus server.
+//!
+//! Along with the metrics loaded from the database, metrics reported by the
+//! server actors to the shared `MetricsRegistry` are exported.
 
 // External uses
 use prometheus_exporter_base::{render_prometheus, MetricType, PrometheusMetric};
@@ -8,6 +11,8 @@ use models::ActionType;
 use storage::ConnectionPool;
 use tokio::runtime::Runtime;
 use tokio::task::JoinHandle;
+// Local uses
+use crate::utils::metrics_registry::metrics_registry;
 
 #[must_use]
 pub fn start_prometheus_exporter(
@@ -69,6 +74,8 @@ pub fn start_prometheus_exporter(
             None,
         ));
 
+        s.push_str(&metrics_registry().render());
+
         Ok(s)
     }))
 }
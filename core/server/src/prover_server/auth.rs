@@ -0,0 +1,93 @@
+//! Authentication of the provers in the prover API.
+//!
+//! Provers authenticate with the `Authorization: Bearer <token>` header, where the token
+//! is issued by the operator via the admin API. Only the hashes of the tokens are stored,
+//! so the token can't be restored once issued; a lost token should be revoked instead.
+
+// External
+use actix_web::{http::header, HttpRequest};
+use rand::Rng;
+// Workspace deps
+use storage::ConnectionPool;
+
+/// Generates a new random token to be handed to a prover.
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    hex::encode(bytes)
+}
+
+/// Hash of the token which is stored in the database.
+pub fn token_hash(token: &str) -> Vec<u8> {
+    tiny_keccak::keccak256(token.as_bytes()).to_vec()
+}
+
+/// Extracts the bearer token from the request headers.
+fn bearer_token(req: &HttpRequest) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            if value.starts_with("Bearer ") {
+                Some(&value["Bearer ".len()..])
+            } else {
+                None
+            }
+        })
+}
+
+/// Checks that the request carries a valid and not revoked prover token.
+pub fn check_prover_auth(
+    req: &HttpRequest,
+    connection_pool: &ConnectionPool,
+) -> actix_web::Result<()> {
+    let token = bearer_token(req)
+        .ok_or_else(|| actix_web::error::ErrorUnauthorized("missing prover token"))?;
+    let storage = connection_pool.access_storage_fragile().map_err(|e| {
+        vlog::warn!("Failed to access storage: {}", e);
+        actix_web::error::ErrorInternalServerError(e)
+    })?;
+    let valid = storage
+        .prover_schema()
+        .is_prover_token_valid(&token_hash(token))
+        .map_err(|e| {
+            vlog::warn!("Failed to check the prover token: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+
+    if valid {
+        Ok(())
+    } else {
+        Err(actix_web::error::ErrorUnauthorized("invalid prover token"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn bearer_token_extraction() {
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Bearer some_token")
+            .to_http_request();
+        assert_eq!(bearer_token(&req), Some("some_token"));
+
+        let req = TestRequest::default()
+            .header(header::AUTHORIZATION, "Basic some_token")
+            .to_http_request();
+        assert_eq!(bearer_token(&req), None);
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(bearer_token(&req), None);
+    }
+
+    #[test]
+    fn generated_tokens() {
+        let token = generate_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, generate_token());
+        assert_eq!(token_hash(&token), token_hash(&token));
+        assert_ne!(token_hash(&token), token_hash(&generate_token()));
+    }
+}
@@ -1,23 +1,32 @@
 // Built-in
 use std::sync::{Arc, RwLock};
 use std::thread;
-use std::{
-    net,
-    time::{self, Duration},
-};
+use std::{net, time::Duration};
 // External
-use actix_web::{web, App, HttpResponse, HttpServer};
+use actix_web::{http::header, web, App, HttpRequest, HttpResponse, HttpServer};
 use futures::channel::mpsc;
 use log::{info, trace};
 // Workspace deps
-use models::{circuit::CircuitAccountTree, config_options::ThreadPanicNotify, node::BlockNumber};
-use prover::client;
-use storage::ConnectionPool;
+use models::{
+    circuit::CircuitAccountTree,
+    config_options::{ProverJobPolicy, ProverOptions, ThreadPanicNotify},
+    node::BlockNumber,
+    primitives::serialize_fe_for_ethereum,
+    prover_utils::{
+        proof_system::{MockProofSystem, PlonkProofSystem, ProofVerifier},
+        EncodedProofPlonk,
+    },
+};
+use prover::{client, prover_data::ProverData, transport};
+use storage::{prover::records::ProverJobOutcome, ConnectionPool};
 // Local deps
-use crate::prover_server::scaler::ScalerOracle;
+use crate::prover_server::scaler::{expected_proving_time_ms, ScalerOracle};
 
+pub mod auth;
 mod pool;
+mod reaper;
 mod scaler;
+mod witness_cache;
 
 #[derive(Debug)]
 struct AppState {
@@ -25,14 +34,25 @@ struct AppState {
     preparing_data_pool: Arc<RwLock<pool::ProversDataPool>>,
     scaler_oracle: Arc<RwLock<ScalerOracle>>,
     prover_timeout: Duration,
+    /// Whether the received proofs are verified before storing.
+    verify_proofs: bool,
+    job_policy: ProverJobPolicy,
+    /// Whether the provers must authenticate with the issued tokens.
+    auth_required: bool,
+    /// Circuit version of the provers which don't advertise their version.
+    circuit_version: String,
+    /// Verifier of the proof system the provers use.
+    proof_verifier: Arc<dyn ProofVerifier>,
+    /// Failure rate above which the prover gets the least urgent blocks.
+    max_failure_rate: f64,
 }
 
 impl AppState {
     pub fn new(
         connection_pool: ConnectionPool,
         preparing_data_pool: Arc<RwLock<pool::ProversDataPool>>,
-        prover_timeout: Duration,
         idle_provers: u32,
+        prover_options: &ProverOptions,
     ) -> Self {
         let scaler_oracle = Arc::new(RwLock::new(ScalerOracle::new(
             connection_pool.clone(),
@@ -43,10 +63,28 @@ impl AppState {
             connection_pool,
             preparing_data_pool,
             scaler_oracle,
-            prover_timeout,
+            prover_timeout: prover_options.gone_timeout,
+            verify_proofs: prover_options.verify_proofs,
+            job_policy: prover_options.job_policy,
+            auth_required: prover_options.api_auth,
+            circuit_version: prover_options.circuit_version.clone(),
+            proof_verifier: if prover_options.mock_proofs {
+                Arc::new(MockProofSystem)
+            } else {
+                Arc::new(PlonkProofSystem::default())
+            },
+            max_failure_rate: prover_options.max_failure_rate,
         }
     }
 
+    /// Checks the prover token of the request, if the authentication is required.
+    fn check_auth(&self, req: &HttpRequest) -> actix_web::Result<()> {
+        if self.auth_required {
+            auth::check_prover_auth(req, &self.connection_pool)?;
+        }
+        Ok(())
+    }
+
     fn access_storage(&self) -> actix_web::Result<storage::StorageProcessor> {
         self.connection_pool.access_storage_fragile().map_err(|e| {
             vlog::warn!("Failed to access storage: {}", e);
@@ -60,9 +98,11 @@ fn status() -> actix_web::Result<String> {
 }
 
 fn register(
+    req: HttpRequest,
     data: web::Data<AppState>,
     r: web::Json<client::ProverReq>,
 ) -> actix_web::Result<String> {
+    data.check_auth(&req)?;
     info!("register request for prover with name: {}", r.name);
     if r.name == "" {
         return Err(actix_web::error::ErrorBadRequest("empty name"));
@@ -75,21 +115,66 @@ fn register(
             vlog::warn!("Failed to register prover in the db: {}", e);
             actix_web::error::ErrorInternalServerError(e)
         })?;
+    // Registry is used only for the accounting, so the failure is not fatal.
+    let capabilities = serde_json::to_value(r.capabilities.clone().unwrap_or_default())
+        .expect("failed to serialize prover capabilities");
+    if let Err(e) = storage.prover_schema().record_prover_registration(
+        &r.name,
+        r.circuit_version.as_deref(),
+        &capabilities,
+    ) {
+        vlog::warn!("Failed to record prover {} in the registry: {}", r.name, e);
+    }
     Ok(id.to_string())
 }
 
 fn block_to_prove(
+    req: HttpRequest,
     data: web::Data<AppState>,
     r: web::Json<client::ProverReq>,
 ) -> actix_web::Result<HttpResponse> {
+    data.check_auth(&req)?;
     trace!("request block to prove from worker: {}", r.name);
     if r.name == "" {
         return Err(actix_web::error::ErrorBadRequest("empty name"));
     }
     let storage = data.access_storage()?;
+    // Draining prover finishes its current job and gets no new ones.
+    let draining = storage
+        .prover_schema()
+        .is_worker_draining(&r.name)
+        .map_err(|e| {
+            vlog::warn!("could not check whether the prover is draining: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    if draining {
+        trace!("worker {} is draining, no job is given", r.name);
+        return Ok(HttpResponse::Ok().json(client::BlockToProveRes {
+            prover_run_id: 0,
+            block: 0,
+        }));
+    }
+    let deprioritized = storage
+        .prover_schema()
+        .prover_registry_entry(&r.name)
+        .map_err(|e| {
+            vlog::warn!("could not load the prover registry entry: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?
+        .map(|entry| !entry.is_reliable(data.max_failure_rate))
+        .unwrap_or(false);
     let ret = storage
         .prover_schema()
-        .prover_run_for_next_commit(&r.name, data.prover_timeout, r.block_size)
+        .prover_run_for_next_commit(
+            &r.name,
+            data.prover_timeout,
+            r.block_size,
+            data.job_policy,
+            r.circuit_version
+                .as_deref()
+                .unwrap_or(&data.circuit_version),
+            deprioritized,
+        )
         .map_err(|e| {
             vlog::warn!("could not get next unverified commit operation: {}", e);
             actix_web::error::ErrorInternalServerError("storage layer error")
@@ -112,25 +197,155 @@ fn block_to_prove(
 }
 
 fn prover_data(
+    req: HttpRequest,
     data: web::Data<AppState>,
     block: web::Json<BlockNumber>,
 ) -> actix_web::Result<HttpResponse> {
+    data.check_auth(&req)?;
     trace!("Got request for prover_data for block {}", *block);
-    let data_pool = data
-        .preparing_data_pool
-        .read()
-        .expect("failed to get read lock on data");
-    let res = data_pool.get(*block);
+    let accept_encoding = req
+        .headers()
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if transport::accepts_zstd(accept_encoding) {
+        return compressed_prover_data(&req, &data, *block);
+    }
+    let res = get_prover_data(&data, *block)?;
     if res.is_some() {
         info!("Sent prover_data for block {}", *block);
     }
     Ok(HttpResponse::Ok().json(res))
 }
 
+/// Sends the stored compressed witness as is. `Range` requests are supported,
+/// so the provers download the large witnesses by chunks and resume the broken downloads.
+/// `ETag` of the witness changes if the witness is regenerated for the reverted block.
+fn compressed_prover_data(
+    req: &HttpRequest,
+    data: &AppState,
+    block: BlockNumber,
+) -> actix_web::Result<HttpResponse> {
+    let range = match req.headers().get(header::RANGE) {
+        Some(value) => Some(
+            value
+                .to_str()
+                .ok()
+                .and_then(transport::parse_range)
+                .ok_or_else(|| actix_web::error::ErrorBadRequest("unsupported range"))?,
+        ),
+        None => None,
+    };
+    let (offset, len) = range.unwrap_or((0, None));
+    let len = len
+        .map(|len| std::cmp::min(len, i32::max_value() as u64) as i32)
+        .unwrap_or_else(i32::max_value);
+
+    let storage = data.access_storage()?;
+    let chunk = storage
+        .prover_schema()
+        .get_compressed_witness_chunk(block, offset as i64, len)
+        .map_err(|e| {
+            vlog::warn!("failed to load witness for block {}: {}", block, e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    let chunk = match chunk {
+        Some(chunk) => chunk,
+        None => return Ok(HttpResponse::NotFound().finish()),
+    };
+    let total_len = chunk.total_len as u64;
+
+    let mut response = if range.is_some() {
+        if offset >= total_len {
+            return Ok(HttpResponse::RangeNotSatisfiable()
+                .header(header::CONTENT_RANGE, format!("bytes */{}", total_len))
+                .finish());
+        }
+        let last = offset + chunk.data.len() as u64 - 1;
+        let mut response = HttpResponse::PartialContent();
+        response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", offset, last, total_len),
+        );
+        response
+    } else {
+        HttpResponse::Ok()
+    };
+    if offset == 0 {
+        info!("Sent prover_data for block {}", block);
+    }
+    Ok(response
+        .header(header::CONTENT_ENCODING, transport::ZSTD_ENCODING)
+        .header(header::ETAG, format!("\"{}\"", chunk.hash))
+        .content_type("application/json")
+        .body(chunk.data))
+}
+
+/// Returns the witness generated for the block by the prover data pool maintainer.
+/// Witness is returned only if it matches the current root hash of the block.
+fn get_prover_data(data: &AppState, block: BlockNumber) -> actix_web::Result<Option<ProverData>> {
+    let storage = data.access_storage()?;
+    let root_hash = storage
+        .chain()
+        .block_schema()
+        .get_block_root_hash(block)
+        .map_err(|e| {
+            vlog::warn!("failed to load root hash of block {}: {}", block, e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    let root_hash = match root_hash {
+        Some(root_hash) => root_hash,
+        None => return Ok(None),
+    };
+
+    let prepared = {
+        let data_pool = data
+            .preparing_data_pool
+            .read()
+            .expect("failed to get read lock on data");
+        data_pool.get(block, &root_hash).cloned()
+    };
+    if prepared.is_some() {
+        return Ok(prepared);
+    }
+
+    // Data pool is not persisted, so after the restart the witness is loaded from the database.
+    let witness = storage.prover_schema().get_witness(block).map_err(|e| {
+        vlog::warn!("failed to load witness for block {}: {}", block, e);
+        actix_web::error::ErrorInternalServerError("storage layer error")
+    })?;
+    let witness: Option<ProverData> =
+        witness
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                vlog::warn!("failed to deserialize witness for block {}: {}", block, e);
+                actix_web::error::ErrorInternalServerError("invalid stored witness")
+            })?;
+    let witness = match witness {
+        Some(witness) if witness.new_root == root_hash => witness,
+        Some(_) => {
+            vlog::warn!(
+                "stored witness of block {} doesn't match its root hash",
+                block
+            );
+            return Ok(None);
+        }
+        None => return Ok(None),
+    };
+    data.preparing_data_pool
+        .write()
+        .expect("failed to get write lock on data")
+        .insert(block, witness.clone());
+    Ok(Some(witness))
+}
+
 fn working_on(
+    req: HttpRequest,
     data: web::Data<AppState>,
     r: web::Json<client::WorkingOnReq>,
 ) -> actix_web::Result<()> {
+    data.check_auth(&req)?;
     // These heartbeats aren't really important, as they're sent
     // continuously while prover is performing computations.
     trace!(
@@ -140,22 +355,170 @@ fn working_on(
     let storage = data
         .access_storage()
         .map_err(actix_web::error::ErrorInternalServerError)?;
-    storage
+    let lease_renewed = storage
         .prover_schema()
-        .record_prover_is_working(r.prover_run_id)
+        .record_prover_is_working(r.prover_run_id, data.prover_timeout)
         .map_err(|e| {
             vlog::warn!("failed to record prover work in progress request: {}", e);
             actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    if !lease_renewed {
+        // The block may be already reassigned to another prover.
+        vlog::warn!(
+            "lease of the prover_run with id {} has expired",
+            r.prover_run_id
+        );
+        return Err(actix_web::error::ErrorGone("prover job lease has expired"));
+    }
+    Ok(())
+}
+
+fn heartbeat(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    r: web::Json<client::HeartbeatReq>,
+) -> actix_web::Result<HttpResponse> {
+    data.check_auth(&req)?;
+    trace!("Received heartbeat for prover with id: {}", r.prover_id);
+    let storage = data
+        .access_storage()
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    let alive = storage
+        .prover_schema()
+        .record_prover_heartbeat(r.prover_id)
+        .map_err(|e| {
+            vlog::warn!("failed to record prover heartbeat: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    if !alive {
+        // Prover has to register again to get new jobs.
+        return Err(actix_web::error::ErrorGone("prover is stopped or unknown"));
+    }
+    let drain = storage
+        .prover_schema()
+        .is_prover_draining(r.prover_id)
+        .map_err(|e| {
+            vlog::warn!("failed to check whether the prover is draining: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    Ok(HttpResponse::Ok().json(client::HeartbeatRes { drain }))
+}
+
+/// Checks the proof received from the prover, so the invalid proofs are never sent
+/// to the contract. Returns the rejection reason if the proof is invalid.
+/// In the mock proofs mode, only the mock proof of the block is accepted.
+fn check_proof(
+    data: &AppState,
+    block: BlockNumber,
+    proof: &EncodedProofPlonk,
+) -> actix_web::Result<Option<String>> {
+    // Valid proof of another block must be rejected as well.
+    let prover_data = get_prover_data(data, block)?
+        .ok_or_else(|| actix_web::error::ErrorInternalServerError("no witness for block"))?;
+    let expected_inputs = vec![serialize_fe_for_ethereum(
+        &prover_data.public_data_commitment,
+    )];
+    if proof.inputs != expected_inputs {
+        return Ok(Some(format!(
+            "unexpected public inputs: {:?}, expected {:?}",
+            proof.inputs, expected_inputs
+        )));
+    }
+
+    let storage = data.access_storage()?;
+    let block_size = storage
+        .chain()
+        .block_schema()
+        .get_block(block)
+        .map_err(|e| {
+            vlog::warn!("failed to load block {}: {}", block, e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?
+        .ok_or_else(|| actix_web::error::ErrorBadRequest("unknown block"))?
+        .block_chunks_size;
+
+    data.proof_verifier
+        .verify(block_size, &prover_data.public_data_commitment, proof)
+        .map_err(|e| {
+            vlog::error!(
+                "failed to verify the proof for block size {}: {}",
+                block_size,
+                e
+            );
+            actix_web::error::ErrorInternalServerError("proof can't be verified")
         })
 }
 
-fn publish(data: web::Data<AppState>, r: web::Json<client::PublishReq>) -> actix_web::Result<()> {
+/// Parses the JSON body of the request, decompressing it first
+/// if it's sent with the `Content-Encoding: zstd` header.
+fn decode_json_body<T: serde::de::DeserializeOwned>(
+    req: &HttpRequest,
+    body: &[u8],
+) -> actix_web::Result<T> {
+    let compressed = req
+        .headers()
+        .get(header::CONTENT_ENCODING)
+        .map(|value| value == transport::ZSTD_ENCODING)
+        .unwrap_or(false);
+    if compressed {
+        let body = transport::decompress(body).map_err(actix_web::error::ErrorBadRequest)?;
+        serde_json::from_slice(&body).map_err(actix_web::error::ErrorBadRequest)
+    } else {
+        serde_json::from_slice(body).map_err(actix_web::error::ErrorBadRequest)
+    }
+}
+
+fn publish(req: HttpRequest, data: web::Data<AppState>, body: web::Bytes) -> actix_web::Result<()> {
+    data.check_auth(&req)?;
+    let r: client::PublishReq = decode_json_body(&req, &body)?;
     info!("Received a proof for block: {}", r.block);
     let storage = data
         .access_storage()
         .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    // Witness of the reverted block is removed along with its prover jobs.
+    let witness_exists = storage
+        .prover_schema()
+        .witness_exists(r.block)
+        .map_err(|e| {
+            vlog::warn!("failed to check witness for block {}: {}", r.block, e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    if !witness_exists {
+        vlog::warn!("Received a proof for the reverted block {}", r.block);
+        return Err(actix_web::error::ErrorGone("block was reverted"));
+    }
+
+    if data.verify_proofs {
+        if let Some(reason) = check_proof(&data, r.block, &r.proof)? {
+            vlog::warn!("Rejected the proof for block {}: {}", r.block, reason);
+            storage
+                .prover_schema()
+                .store_rejected_proof(r.block, &r.proof, &reason)
+                .map_err(|e| {
+                    vlog::error!("failed to store rejected proof: {}", e);
+                    actix_web::error::ErrorInternalServerError("storage layer error")
+                })?;
+            record_job_outcome(&storage, r.worker.as_deref(), ProverJobOutcome::Failed);
+            return Err(actix_web::error::ErrorBadRequest(format!(
+                "invalid proof: {}",
+                reason
+            )));
+        }
+    }
+
     match storage.prover_schema().store_proof(r.block, &r.proof) {
         Ok(_) => {
+            if let (Some(worker), Some(timings)) = (&r.worker, &r.timings) {
+                // Timings are used only for the reports, so the failure is not fatal.
+                if let Err(e) = storage
+                    .prover_schema()
+                    .store_proof_timings(r.block, worker, timings)
+                {
+                    vlog::warn!("failed to store proof timings for block {}: {}", r.block, e);
+                }
+            }
+            record_job_outcome(&storage, r.worker.as_deref(), ProverJobOutcome::Completed);
             let mut data_pool = data
                 .preparing_data_pool
                 .write()
@@ -175,7 +538,33 @@ fn publish(data: web::Data<AppState>, r: web::Json<client::PublishReq>) -> actix
     }
 }
 
-fn stopped(data: web::Data<AppState>, prover_id: web::Json<i32>) -> actix_web::Result<()> {
+/// Accounts the outcome of the prover job in the prover registry.
+/// Registry is used only for the accounting and job prioritization, so the failure is not fatal.
+fn record_job_outcome(
+    storage: &storage::StorageProcessor,
+    worker: Option<&str>,
+    outcome: ProverJobOutcome,
+) {
+    if let Some(worker) = worker {
+        if let Err(e) = storage
+            .prover_schema()
+            .record_prover_job_outcome(worker, outcome)
+        {
+            vlog::warn!(
+                "failed to record the job outcome of prover {}: {}",
+                worker,
+                e
+            );
+        }
+    }
+}
+
+fn stopped(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    prover_id: web::Json<i32>,
+) -> actix_web::Result<()> {
+    data.check_auth(&req)?;
     let prover_id = prover_id.into_inner();
 
     let storage = data
@@ -237,16 +626,102 @@ fn required_replicas(
     Ok(HttpResponse::Ok().json(response))
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Output of the `/api/internal/prover/queue` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvingQueueOutput {
+    /// Amount of the committed blocks without proofs.
+    queue_depth: u64,
+    /// Amount of the blocks being proved at the moment.
+    jobs_in_progress: u64,
+    /// Time since the oldest block without proof was created.
+    oldest_unproven_block_age_secs: Option<u64>,
+    /// Average proof computation time over the last day.
+    avg_proof_ms: Option<u64>,
+    /// Amount of the running provers which are not draining.
+    active_provers: u64,
+    /// Estimated time to prove the whole queue by the active provers.
+    expected_proving_ms: Option<u64>,
+}
+
+/// Reports the state of the proving queue to drive the external autoscalers.
+fn proving_queue(req: HttpRequest, data: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
+    data.check_auth(&req)?;
+    let oracle = data.scaler_oracle.read().expect("Expected read lock");
+    let stats = oracle
+        .proving_queue()
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+
+    let response = ProvingQueueOutput {
+        queue_depth: stats.queue_depth as u64,
+        jobs_in_progress: stats.jobs_in_progress as u64,
+        oldest_unproven_block_age_secs: stats.oldest_unproven_block_age_secs.map(|age| age as u64),
+        avg_proof_ms: stats.avg_proof_ms.map(|ms| ms as u64),
+        active_provers: stats.active_provers as u64,
+        expected_proving_ms: expected_proving_time_ms(&stats),
+    };
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Input of the `/api/internal/prover/drain` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrainProverInput {
+    prover_id: i32,
+}
+
+/// Requests the prover to finish its current job and stop, so it can be shut down
+/// by the autoscaler without losing the work.
+fn drain_prover(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    input: web::Json<DrainProverInput>,
+) -> actix_web::Result<HttpResponse> {
+    data.check_auth(&req)?;
+    let storage = data.access_storage()?;
+    let requested = storage
+        .prover_schema()
+        .request_prover_drain(input.prover_id)
+        .map_err(|e| {
+            vlog::warn!("failed to request prover drain: {}", e);
+            actix_web::error::ErrorInternalServerError("storage layer error")
+        })?;
+    if !requested {
+        return Ok(HttpResponse::NotFound().finish());
+    }
+    info!("Prover {} is requested to drain", input.prover_id);
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Records the circuit version the blocks starting from the next one are proposed under.
+fn record_circuit_version(connection_pool: &ConnectionPool, circuit_version: &str) {
+    let storage = connection_pool
+        .access_storage()
+        .expect("failed to access storage");
+    let next_block = storage
+        .chain()
+        .block_schema()
+        .get_last_committed_block()
+        .expect("failed to load last committed block")
+        + 1;
+    storage
+        .prover_schema()
+        .record_circuit_version(circuit_version, next_block)
+        .expect("failed to record circuit version");
+    info!(
+        "Blocks from {} are proved with the circuit version {}",
+        next_block, circuit_version
+    );
+}
+
 pub fn start_prover_server(
     connection_pool: storage::ConnectionPool,
     bind_to: net::SocketAddr,
-    prover_timeout: time::Duration,
-    rounds_interval: time::Duration,
     panic_notify: mpsc::Sender<bool>,
     account_tree: CircuitAccountTree,
     tree_block_number: BlockNumber,
     idle_provers: u32,
+    prover_options: ProverOptions,
 ) {
     thread::Builder::new()
         .name("prover_server".to_string())
@@ -257,23 +732,34 @@ pub fn start_prover_server(
                 10,
             )));
 
+            // Blocks proposed from now on are proved with the current circuit version.
+            record_circuit_version(&connection_pool, &prover_options.circuit_version);
+
             // Start pool maintainer thread.
             let pool_maintainer = pool::Maintainer::new(
                 connection_pool.clone(),
                 Arc::clone(&data_pool),
-                rounds_interval,
+                prover_options.prepare_data_interval,
                 account_tree,
                 tree_block_number,
+                prover_options.witness_threads,
+            );
+            pool_maintainer.start(panic_notify.clone());
+
+            // Start dead provers reaper thread.
+            let reaper = reaper::DeadProversReaper::new(
+                connection_pool.clone(),
+                prover_options.gone_timeout,
             );
-            pool_maintainer.start(panic_notify);
+            reaper.start(panic_notify);
 
             // Start HTTP server.
             HttpServer::new(move || {
                 let app_state = AppState::new(
                     connection_pool.clone(),
                     data_pool.clone(),
-                    prover_timeout,
                     idle_provers,
+                    &prover_options,
                 );
 
                 // By calling `register_data` instead of `data` we're avoiding double
@@ -285,6 +771,7 @@ pub fn start_prover_server(
                     .route("/register", web::post().to(register))
                     .route("/block_to_prove", web::get().to(block_to_prove))
                     .route("/working_on", web::post().to(working_on))
+                    .route("/heartbeat", web::post().to(heartbeat))
                     .route("/prover_data", web::get().to(prover_data))
                     .route("/publish", web::post().to(publish))
                     .route("/stopped", web::post().to(stopped))
@@ -292,6 +779,8 @@ pub fn start_prover_server(
                         "/api/internal/prover/replicas",
                         web::post().to(required_replicas),
                     )
+                    .route("/api/internal/prover/queue", web::get().to(proving_queue))
+                    .route("/api/internal/prover/drain", web::post().to(drain_prover))
             })
             .bind(&bind_to)
             .expect("failed to bind")
@@ -6,7 +6,9 @@ use std::{thread, time};
 use crate::franklin_crypto::bellman::pairing::ff::PrimeField;
 use futures::channel::mpsc;
 use log::info;
+use rayon::prelude::*;
 // Workspace deps
+use circuit::operation::Operation as CircuitOperation;
 use circuit::witness::{
     utils::{SigDataInput, WitnessBuilder},
     ChangePubkeyOffChainWitness, CloseAccountWitness, DepositWitness, FullExitWitness,
@@ -16,11 +18,13 @@ use models::params::CHUNK_BIT_WIDTH;
 use models::{
     circuit::CircuitAccountTree,
     config_options::ThreadPanicNotify,
-    node::{BlockNumber, Fr, FranklinOp},
+    node::{BlockNumber, CloseOp, Engine, Fr, FranklinOp, TransferOp, TransferToNewOp, WithdrawOp},
     Operation,
 };
 use plasma::state::CollectedFee;
 use prover::prover_data::ProverData;
+// Local deps
+use super::witness_cache::WitnessCache;
 
 #[derive(Debug, Clone)]
 struct OperationsQueue {
@@ -90,7 +94,7 @@ impl OperationsQueue {
 pub struct ProversDataPool {
     limit: i64,
     op_queue: OperationsQueue,
-    prepared: HashMap<BlockNumber, ProverData>,
+    prepared: WitnessCache,
 }
 
 impl ProversDataPool {
@@ -98,16 +102,69 @@ impl ProversDataPool {
         Self {
             limit,
             op_queue: OperationsQueue::new(last_loaded_block),
-            prepared: HashMap::new(),
+            prepared: WitnessCache::default(),
         }
     }
 
-    pub fn get(&self, block: BlockNumber) -> Option<&ProverData> {
-        self.prepared.get(&block)
+    /// Returns the witness of the block with the given root hash.
+    pub fn get(&self, block: BlockNumber, root_hash: &Fr) -> Option<&ProverData> {
+        self.prepared.get(block, root_hash)
+    }
+
+    /// Caches the witness loaded from the database, so it's not loaded again
+    /// if the block is reassigned to another prover.
+    pub fn insert(&mut self, block: BlockNumber, witness: ProverData) {
+        self.prepared.insert(block, witness);
     }
 
     pub fn clean_up(&mut self, block: BlockNumber) {
-        self.prepared.remove(&block);
+        self.prepared.remove(block);
+    }
+}
+
+/// Witness of a single block operation along with the operation itself,
+/// which is required to calculate the circuit operations.
+enum OpWitness<'a> {
+    Deposit(DepositWitness<Engine>),
+    Transfer(TransferWitness<Engine>, &'a TransferOp),
+    TransferToNew(TransferToNewWitness<Engine>, &'a TransferToNewOp),
+    Withdraw(WithdrawWitness<Engine>, &'a WithdrawOp),
+    Close(CloseAccountWitness<Engine>, &'a CloseOp),
+    FullExit(FullExitWitness<Engine>),
+    ChangePubKeyOffchain(ChangePubkeyOffChainWitness<Engine>),
+}
+
+impl OpWitness<'_> {
+    /// Calculates the circuit operations and the pubdata of the operation.
+    /// Doesn't access the account tree, so can be run for several operations in parallel.
+    fn calculate_operations(&self) -> Result<(Vec<CircuitOperation<Engine>>, Vec<bool>), String> {
+        Ok(match self {
+            OpWitness::Deposit(witness) => {
+                (witness.calculate_operations(()), witness.get_pubdata())
+            }
+            OpWitness::Transfer(witness, op) => {
+                let input = SigDataInput::from_transfer_op(op)?;
+                (witness.calculate_operations(input), witness.get_pubdata())
+            }
+            OpWitness::TransferToNew(witness, op) => {
+                let input = SigDataInput::from_transfer_to_new_op(op)?;
+                (witness.calculate_operations(input), witness.get_pubdata())
+            }
+            OpWitness::Withdraw(witness, op) => {
+                let input = SigDataInput::from_withdraw_op(op)?;
+                (witness.calculate_operations(input), witness.get_pubdata())
+            }
+            OpWitness::Close(witness, op) => {
+                let input = SigDataInput::from_close_op(op)?;
+                (witness.calculate_operations(input), witness.get_pubdata())
+            }
+            OpWitness::FullExit(witness) => {
+                (witness.calculate_operations(()), witness.get_pubdata())
+            }
+            OpWitness::ChangePubKeyOffchain(witness) => {
+                (witness.calculate_operations(()), witness.get_pubdata())
+            }
+        })
     }
 }
 
@@ -119,6 +176,10 @@ impl ProversDataPool {
 /// pool. It maintains its own circuit tree to generates witness.
 /// Initial circuit tree is substituted in constructor.
 ///
+/// Witnesses are generated as soon as the blocks are committed and stored
+/// in the database, so the blocks are given to the provers only once their
+/// witnesses are ready, and the witnesses survive the server restart.
+///
 /// `migrate` function is private and is invoked by the
 /// public `start` function, which starts
 /// the named thread dedicated for that routine only.
@@ -137,6 +198,8 @@ pub struct Maintainer {
     /// Maintainer prepared prover for blocks in order
     /// `next_block_number` stores next block number to prepare data for.
     next_block_number: BlockNumber,
+    /// Thread pool used to calculate the circuit operations of the block in parallel.
+    thread_pool: rayon::ThreadPool,
 }
 
 impl Maintainer {
@@ -147,13 +210,21 @@ impl Maintainer {
         rounds_interval: time::Duration,
         account_tree: CircuitAccountTree,
         block_number: BlockNumber,
+        witness_threads: usize,
     ) -> Self {
+        // `0` threads means the rayon default, i.e. the number of the logical CPUs.
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(witness_threads)
+            .thread_name(|i| format!("witness_generator_{}", i))
+            .build()
+            .expect("failed to create the witness generation thread pool");
         Self {
             conn_pool,
             data,
             rounds_interval,
             account_tree,
             next_block_number: block_number + 1,
+            thread_pool,
         }
     }
 
@@ -235,6 +306,12 @@ impl Maintainer {
                 // Always build prover data to update circuit tree to the next block, but store only
                 // if there is no proof for the block.
                 if !has_proof {
+                    let witness = serde_json::to_value(&pd)
+                        .map_err(|e| format!("failed to serialize witness: {}", e))?;
+                    storage
+                        .prover_schema()
+                        .store_witness(op.block.block_number, &witness)
+                        .map_err(|e| format!("failed to store witness: {}", e))?;
                     prepared.insert(op.block.block_number, pd);
                 }
                 self.next_block_number = op.block.block_number + 1;
@@ -276,101 +353,83 @@ impl Maintainer {
             .get_block_operations(block_number)
             .map_err(|e| format!("failed to get block operations {}", e))?;
 
-        let mut operations = vec![];
-        let mut pub_data = vec![];
+        // Operations are applied to the circuit tree sequentially, since each of them depends
+        // on the tree state left by the previous one. Circuit operations are calculated from
+        // the witnesses alone, so this (most expensive) part is done on the thread pool.
+        let mut witnesses = Vec::with_capacity(ops.len());
         let mut fees = vec![];
-        for op in ops {
-            match op {
-                FranklinOp::Deposit(deposit) => {
-                    let deposit_witness =
-                        DepositWitness::apply_tx(&mut witness_accum.account_tree, &deposit);
-
-                    let deposit_operations = deposit_witness.calculate_operations(());
-                    operations.extend(deposit_operations);
-                    pub_data.extend(deposit_witness.get_pubdata());
-                }
+        for op in &ops {
+            let witness = match op {
+                FranklinOp::Deposit(deposit) => OpWitness::Deposit(DepositWitness::apply_tx(
+                    &mut witness_accum.account_tree,
+                    deposit,
+                )),
                 FranklinOp::Transfer(transfer) => {
-                    let transfer_witness =
-                        TransferWitness::apply_tx(&mut witness_accum.account_tree, &transfer);
-
-                    let input = SigDataInput::from_transfer_op(&transfer)?;
-                    let transfer_operations = transfer_witness.calculate_operations(input);
-
-                    operations.extend(transfer_operations);
                     fees.push(CollectedFee {
                         token: transfer.tx.token,
-                        amount: transfer.tx.fee,
+                        amount: transfer.tx.fee.clone(),
                     });
-                    pub_data.extend(transfer_witness.get_pubdata());
+                    OpWitness::Transfer(
+                        TransferWitness::apply_tx(&mut witness_accum.account_tree, transfer),
+                        transfer,
+                    )
                 }
                 FranklinOp::TransferToNew(transfer_to_new) => {
-                    let transfer_to_new_witness = TransferToNewWitness::apply_tx(
-                        &mut witness_accum.account_tree,
-                        &transfer_to_new,
-                    );
-
-                    let input = SigDataInput::from_transfer_to_new_op(&transfer_to_new)?;
-                    let transfer_to_new_operations =
-                        transfer_to_new_witness.calculate_operations(input);
-
-                    operations.extend(transfer_to_new_operations);
                     fees.push(CollectedFee {
                         token: transfer_to_new.tx.token,
-                        amount: transfer_to_new.tx.fee,
+                        amount: transfer_to_new.tx.fee.clone(),
                     });
-                    pub_data.extend(transfer_to_new_witness.get_pubdata());
+                    OpWitness::TransferToNew(
+                        TransferToNewWitness::apply_tx(
+                            &mut witness_accum.account_tree,
+                            transfer_to_new,
+                        ),
+                        transfer_to_new,
+                    )
                 }
                 FranklinOp::Withdraw(withdraw) => {
-                    let withdraw_witness =
-                        WithdrawWitness::apply_tx(&mut witness_accum.account_tree, &withdraw);
-
-                    let input = SigDataInput::from_withdraw_op(&withdraw)?;
-                    let withdraw_operations = withdraw_witness.calculate_operations(input);
-
-                    operations.extend(withdraw_operations);
                     fees.push(CollectedFee {
                         token: withdraw.tx.token,
-                        amount: withdraw.tx.fee,
+                        amount: withdraw.tx.fee.clone(),
                     });
-                    pub_data.extend(withdraw_witness.get_pubdata());
-                }
-                FranklinOp::Close(close) => {
-                    let close_account_witness =
-                        CloseAccountWitness::apply_tx(&mut witness_accum.account_tree, &close);
-
-                    let input = SigDataInput::from_close_op(&close)?;
-                    let close_account_operations =
-                        close_account_witness.calculate_operations(input);
-
-                    operations.extend(close_account_operations);
-                    pub_data.extend(close_account_witness.get_pubdata());
+                    OpWitness::Withdraw(
+                        WithdrawWitness::apply_tx(&mut witness_accum.account_tree, withdraw),
+                        withdraw,
+                    )
                 }
+                FranklinOp::Close(close) => OpWitness::Close(
+                    CloseAccountWitness::apply_tx(&mut witness_accum.account_tree, close),
+                    close,
+                ),
                 FranklinOp::FullExit(full_exit_op) => {
                     let success = full_exit_op.withdraw_amount.is_some();
-
-                    let full_exit_witness = FullExitWitness::apply_tx(
+                    OpWitness::FullExit(FullExitWitness::apply_tx(
                         &mut witness_accum.account_tree,
-                        &(*full_exit_op, success),
-                    );
-
-                    let full_exit_operations = full_exit_witness.calculate_operations(());
-
-                    operations.extend(full_exit_operations);
-                    pub_data.extend(full_exit_witness.get_pubdata());
+                        &((**full_exit_op).clone(), success),
+                    ))
                 }
                 FranklinOp::ChangePubKeyOffchain(change_pkhash_op) => {
-                    let change_pkhash_witness = ChangePubkeyOffChainWitness::apply_tx(
+                    OpWitness::ChangePubKeyOffchain(ChangePubkeyOffChainWitness::apply_tx(
                         &mut witness_accum.account_tree,
-                        &change_pkhash_op,
-                    );
-
-                    let change_pkhash_operations = change_pkhash_witness.calculate_operations(());
-
-                    operations.extend(change_pkhash_operations);
-                    pub_data.extend(change_pkhash_witness.get_pubdata());
+                        change_pkhash_op,
+                    ))
                 }
-                FranklinOp::Noop(_) => {} // Noops are handled below
-            }
+                FranklinOp::Noop(_) => continue, // Noops are handled below
+            };
+            witnesses.push(witness);
+        }
+
+        let calculated = self.thread_pool.install(|| {
+            witnesses
+                .par_iter()
+                .map(OpWitness::calculate_operations)
+                .collect::<Result<Vec<_>, String>>()
+        })?;
+        let mut operations = vec![];
+        let mut pub_data = vec![];
+        for (op_operations, op_pub_data) in calculated {
+            operations.extend(op_operations);
+            pub_data.extend(op_pub_data);
         }
 
         witness_accum.add_operation_with_pubdata(operations, pub_data);
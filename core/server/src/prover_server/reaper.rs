@@ -0,0 +1,62 @@
+// Built-in
+use std::{thread, time};
+// External
+use futures::channel::mpsc;
+use log::{info, warn};
+// Workspace deps
+use models::config_options::ThreadPanicNotify;
+
+/// Interval between the checks for the dead provers.
+const REAP_INTERVAL: time::Duration = time::Duration::from_secs(10);
+
+/// `DeadProversReaper` periodically marks the provers which stopped sending
+/// heartbeats as stopped, so the blocks they were proving are reassigned to
+/// the other provers right away instead of waiting for the job leases to expire.
+pub struct DeadProversReaper {
+    conn_pool: storage::ConnectionPool,
+    /// Time without heartbeats after which the prover is considered dead.
+    heartbeat_timeout: time::Duration,
+}
+
+impl DeadProversReaper {
+    pub fn new(conn_pool: storage::ConnectionPool, heartbeat_timeout: time::Duration) -> Self {
+        Self {
+            conn_pool,
+            heartbeat_timeout,
+        }
+    }
+
+    /// Starts the thread running `reap` method in a loop.
+    pub fn start(self, panic_notify: mpsc::Sender<bool>) {
+        thread::Builder::new()
+            .name("prover_server_reaper".to_string())
+            .spawn(move || {
+                let _panic_sentinel = ThreadPanicNotify(panic_notify);
+                info!("dead provers reaper routine started");
+                loop {
+                    if let Err(e) = self.reap() {
+                        warn!("failed to reap dead provers: {}", e);
+                    }
+                    thread::sleep(REAP_INTERVAL);
+                }
+            })
+            .expect("failed to start dead provers reaper");
+    }
+
+    fn reap(&self) -> Result<(), failure::Error> {
+        let storage = self.conn_pool.access_storage()?;
+        let dead_provers = storage
+            .prover_schema()
+            .reap_dead_provers(self.heartbeat_timeout)?;
+        for prover in dead_provers {
+            warn!(
+                "Prover '{}' with ID {} hasn't sent heartbeats for {}s, \
+                 its jobs are reassigned",
+                prover.worker,
+                prover.id,
+                self.heartbeat_timeout.as_secs()
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Module with utilities for prover scaler service.
 
 // Workspace deps
+use storage::prover::records::ProvingQueueStats;
 use storage::ConnectionPool;
 
 /// Scaler oracle provides information for prover scaler
@@ -32,4 +33,48 @@ impl ScalerOracle {
 
         Ok(provers_required)
     }
+
+    /// Loads the state of the proving queue for the external autoscalers.
+    pub fn proving_queue(&self) -> Result<ProvingQueueStats, failure::Error> {
+        let storage = self.db.access_storage()?;
+        Ok(storage.prover_schema().proving_queue_stats()?)
+    }
+}
+
+/// Estimates the time required to prove all the blocks in the queue by the active provers,
+/// based on the recent proof computation times.
+/// Returns `None` if there are no recent proofs to estimate the proving time.
+pub fn expected_proving_time_ms(stats: &ProvingQueueStats) -> Option<u64> {
+    let avg_proof_ms = stats.avg_proof_ms? as u64;
+    let provers = std::cmp::max(stats.active_provers, 1) as u64;
+    let queue_depth = stats.queue_depth as u64;
+    // Every prover proves one block at a time.
+    let rounds = (queue_depth + provers - 1) / provers;
+    Some(rounds * avg_proof_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expected_proving_time() {
+        let mut stats = ProvingQueueStats {
+            queue_depth: 5,
+            jobs_in_progress: 2,
+            oldest_unproven_block_age_secs: Some(60),
+            avg_proof_ms: None,
+            active_provers: 2,
+        };
+        assert_eq!(expected_proving_time_ms(&stats), None);
+
+        stats.avg_proof_ms = Some(1000);
+        assert_eq!(expected_proving_time_ms(&stats), Some(3000));
+
+        stats.active_provers = 0;
+        assert_eq!(expected_proving_time_ms(&stats), Some(5000));
+
+        stats.queue_depth = 0;
+        assert_eq!(expected_proving_time_ms(&stats), Some(0));
+    }
 }
@@ -0,0 +1,78 @@
+//! In-memory cache of the block witnesses.
+//!
+//! Witnesses are identified by the block number along with the block root hash, since the
+//! reverted block is committed again with the same number and a different state. Cached witness
+//! is kept until the block is proved, so a job reassigned to another prover after a timeout
+//! gets the same witness without loading and decoding it from the database again.
+
+// Built-in deps
+use std::collections::HashMap;
+// Workspace deps
+use models::node::{BlockNumber, Fr};
+use prover::prover_data::ProverData;
+
+#[derive(Debug, Default)]
+pub struct WitnessCache {
+    /// Witnesses by the block number, the block root hash is the `new_root` of the witness.
+    witnesses: HashMap<BlockNumber, ProverData>,
+}
+
+impl WitnessCache {
+    /// Stores the witness of the block, replacing the witness of the same block with
+    /// another root hash.
+    pub fn insert(&mut self, block: BlockNumber, witness: ProverData) {
+        self.witnesses.insert(block, witness);
+    }
+
+    /// Returns the witness of the block with the given root hash.
+    pub fn get(&self, block: BlockNumber, root_hash: &Fr) -> Option<&ProverData> {
+        self.witnesses
+            .get(&block)
+            .filter(|witness| witness.new_root == *root_hash)
+    }
+
+    /// Removes the witness of the proved block.
+    pub fn remove(&mut self, block: BlockNumber) {
+        self.witnesses.remove(&block);
+    }
+}
+
+impl Extend<(BlockNumber, ProverData)> for WitnessCache {
+    fn extend<T: IntoIterator<Item = (BlockNumber, ProverData)>>(&mut self, iter: T) {
+        self.witnesses.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::franklin_crypto::bellman::pairing::ff::Field;
+    use models::node::operations::DepositOp;
+
+    #[test]
+    fn witness_cache() {
+        let (witness, _) = prover::bench::synthetic_block(DepositOp::CHUNKS).unwrap();
+        let root_hash = witness.new_root;
+        let mut other_root_hash = root_hash;
+        other_root_hash.add_assign(&Fr::one());
+
+        let mut cache = WitnessCache::default();
+        assert!(cache.get(1, &root_hash).is_none());
+
+        cache.insert(1, witness.clone());
+        assert!(cache.get(1, &root_hash).is_some());
+        // Witness of the reverted block is not used for the block committed again.
+        assert!(cache.get(1, &other_root_hash).is_none());
+        assert!(cache.get(2, &root_hash).is_none());
+
+        let mut reverted_witness = witness;
+        reverted_witness.new_root = other_root_hash;
+        cache.insert(1, reverted_witness);
+        assert!(cache.get(1, &root_hash).is_none());
+        assert!(cache.get(1, &other_root_hash).is_some());
+        assert_eq!(cache.witnesses.len(), 1);
+
+        cache.remove(1);
+        assert!(cache.witnesses.is_empty());
+    }
+}
@@ -0,0 +1,154 @@
+//! Read-only mode of the server.
+//!
+//! In the read-only mode the server doesn't run the actors that modify the
+//! sidechain state (state keeper, committer, `eth_sender`, mempool, block proposer,
+//! prover server), and serves only the API. Such a server can be pointed to
+//! the replica database, so the reads can be scaled independently of the single
+//! sequencer node. New blocks are announced to the API via the database notifications
+//! (see `api_server::start_operations_listener`).
+//!
+//! API relies on the requests to the sequencer actors, so this module provides
+//! substitutes for them, which answer the requests using the database only.
+
+// External uses
+use futures::{channel::mpsc, join, StreamExt};
+use tokio::{runtime::Runtime, task::JoinHandle};
+use web3::types::U256;
+// Workspace uses
+use models::node::{Account, AccountId, Address};
+use storage::ConnectionPool;
+// Local uses
+use crate::{
+    eth_sender::{average_gas_price, ETHSenderRequest},
+    mempool::{MempoolRequest, TxAddError},
+    state_keeper::{AccountStates, StateKeeperRequest},
+};
+
+fn load_committed_account(
+    db_pool: &ConnectionPool,
+    address: &Address,
+) -> Result<Option<(AccountId, Account)>, failure::Error> {
+    let storage = db_pool.access_storage()?;
+    let state = storage
+        .chain()
+        .account_schema()
+        .account_state_by_address(address)?;
+    Ok(state.committed)
+}
+
+fn load_gas_price_limit(db_pool: &ConnectionPool) -> Result<U256, failure::Error> {
+    let storage = db_pool.access_storage()?;
+    Ok(storage.ethereum_schema().load_gas_price_limit()?)
+}
+
+async fn handle_state_keeper_requests(
+    db_pool: ConnectionPool,
+    mut requests: mpsc::Receiver<StateKeeperRequest>,
+) {
+    while let Some(request) = requests.next().await {
+        match request {
+            StateKeeperRequest::GetAccount(address, response) => {
+                let account = load_committed_account(&db_pool, &address).unwrap_or_else(|e| {
+                    warn!("Failed to load account state: {}", e);
+                    None
+                });
+                response.send(account).unwrap_or_default();
+            }
+            StateKeeperRequest::GetAccountStates(address, response) => {
+                // There is no pending block without the state keeper.
+                let account = load_committed_account(&db_pool, &address).unwrap_or_else(|e| {
+                    warn!("Failed to load account state: {}", e);
+                    None
+                });
+                let states = account.map(|(id, account)| AccountStates {
+                    id,
+                    sealed: Some(account.clone()),
+                    pending: account,
+                });
+                response.send(states).unwrap_or_default();
+            }
+            StateKeeperRequest::GetExecutedInPendingBlock(_, response) => {
+                // Pending block is not observable without the state keeper,
+                // operations will be announced once the block is committed.
+                response.send(None).unwrap_or_default();
+            }
+            StateKeeperRequest::SealNonEmptyBlock(response) => {
+                // There is no pending block without the state keeper.
+                response.send(None).unwrap_or_default();
+            }
+            StateKeeperRequest::DryRunTx(_, response) => {
+                let error = "Dry run is not supported in the read-only mode".to_string();
+                response.send(Err(error)).unwrap_or_default();
+            }
+            StateKeeperRequest::GetLastUnprocessedPriorityOp(_)
+            | StateKeeperRequest::ExecuteMiniBlock(_)
+            | StateKeeperRequest::SealBlock => {
+                warn!("State keeper request is not supported in the read-only mode");
+            }
+        }
+    }
+}
+
+async fn handle_mempool_requests(mut requests: mpsc::Receiver<MempoolRequest>) {
+    while let Some(request) = requests.next().await {
+        match request {
+            MempoolRequest::NewTx(_, response) => {
+                response
+                    .send(Err(TxAddError::ReadOnlyMode))
+                    .unwrap_or_default();
+            }
+            MempoolRequest::StopAcceptingTxs(response) => {
+                // Transactions are not accepted in the read-only mode anyway.
+                response.send(()).unwrap_or_default();
+            }
+            MempoolRequest::UpdateNonces(_)
+            | MempoolRequest::GetBlock(_)
+            | MempoolRequest::ReloadDenylist => {
+                warn!("Mempool request is not supported in the read-only mode");
+            }
+        }
+    }
+}
+
+async fn handle_eth_sender_requests(
+    db_pool: ConnectionPool,
+    mut requests: mpsc::Receiver<ETHSenderRequest>,
+) {
+    while let Some(request) = requests.next().await {
+        match request {
+            ETHSenderRequest::GetAverageUsedGasPrice(response) => {
+                // Gas price limit is maintained by the `eth_sender` of the sequencer,
+                // so the average price can be restored from the stored value.
+                match load_gas_price_limit(&db_pool) {
+                    Ok(limit) => response.send(average_gas_price(limit)).unwrap_or_default(),
+                    Err(e) => warn!("Failed to load the gas price limit: {}", e),
+                }
+            }
+            ETHSenderRequest::Flush(response) => {
+                // Nothing is sent in the read-only mode.
+                response.send(()).unwrap_or_default();
+            }
+            ETHSenderRequest::SendOperation(_) => {
+                warn!("Sending operations is not supported in the read-only mode");
+            }
+        }
+    }
+}
+
+/// Starts the task serving the requests addressed to the sequencer actors,
+/// which are not running in the read-only mode.
+pub fn run_read_only_responders(
+    db_pool: ConnectionPool,
+    state_keeper_requests: mpsc::Receiver<StateKeeperRequest>,
+    mempool_requests: mpsc::Receiver<MempoolRequest>,
+    eth_sender_requests: mpsc::Receiver<ETHSenderRequest>,
+    runtime: &Runtime,
+) -> JoinHandle<()> {
+    runtime.spawn(async move {
+        join!(
+            handle_state_keeper_requests(db_pool.clone(), state_keeper_requests),
+            handle_mempool_requests(mempool_requests),
+            handle_eth_sender_requests(db_pool, eth_sender_requests),
+        );
+    })
+}
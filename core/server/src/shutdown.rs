@@ -0,0 +1,126 @@
+//! Graceful shutdown of the server.
+//!
+//! Once the server receives SIGTERM (or Ctrl-C), the actors are stopped in the following order,
+//! so deploys don't lose the in-progress block or the pending notifications:
+//!
+//! 1. Mempool stops accepting the transactions, and the block production is halted.
+//!    Accepted transactions are stored in the database right away, so the mempool is
+//!    persisted once it confirms that no transaction is being added.
+//! 2. Pending block is sealed (unless it's empty) and committed.
+//! 3. `eth_sender` sends the queued operations, including the commit of the sealed block.
+//! 4. Notifier sends the pending notifications to the subscribers.
+//!
+//! Every step is limited by `SHUTDOWN_STEP_TIMEOUT`, the server is stopped even if some step
+//! fails: the state is restored from the database on the next start anyway.
+
+// Built-in deps
+use std::time::Duration;
+// External deps
+use futures::{
+    channel::{mpsc, oneshot},
+    SinkExt,
+};
+use tokio::time;
+// Local deps
+use crate::{
+    eth_sender::ETHSenderRequest, mempool::MempoolRequest, state_keeper::StateKeeperRequest,
+    utils::current_zksync_info::CurrentZksyncInfo,
+};
+
+/// Maximum duration of a single shutdown step.
+const SHUTDOWN_STEP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Channels to the actors stopped on the shutdown.
+pub struct GracefulShutdown {
+    current_zksync_info: CurrentZksyncInfo,
+    mempool_requests: mpsc::Sender<MempoolRequest>,
+    state_keeper_requests: mpsc::Sender<StateKeeperRequest>,
+    eth_sender_requests: mpsc::Sender<ETHSenderRequest>,
+    notifier_flush_requests: mpsc::Sender<oneshot::Sender<()>>,
+}
+
+impl GracefulShutdown {
+    pub fn new(
+        current_zksync_info: CurrentZksyncInfo,
+        mempool_requests: mpsc::Sender<MempoolRequest>,
+        state_keeper_requests: mpsc::Sender<StateKeeperRequest>,
+        eth_sender_requests: mpsc::Sender<ETHSenderRequest>,
+        notifier_flush_requests: mpsc::Sender<oneshot::Sender<()>>,
+    ) -> Self {
+        Self {
+            current_zksync_info,
+            mempool_requests,
+            state_keeper_requests,
+            eth_sender_requests,
+            notifier_flush_requests,
+        }
+    }
+
+    /// Performs the shutdown sequence, the actors are left running afterwards.
+    pub async fn run(mut self) {
+        info!("Shutting down: stopping accepting transactions and persisting the mempool");
+        self.current_zksync_info.set_shutting_down();
+        request(
+            "stop accepting transactions",
+            &mut self.mempool_requests,
+            MempoolRequest::StopAcceptingTxs,
+        )
+        .await;
+
+        info!("Shutting down: sealing the pending block");
+        let sealed_block = request(
+            "seal the pending block",
+            &mut self.state_keeper_requests,
+            StateKeeperRequest::SealNonEmptyBlock,
+        )
+        .await;
+        match sealed_block {
+            Some(Some(block_number)) => info!("Block {} is sealed and committed", block_number),
+            Some(None) => info!("Pending block is empty, nothing to seal"),
+            None => {}
+        }
+
+        info!("Shutting down: flushing the eth_sender queue");
+        request(
+            "flush the eth_sender queue",
+            &mut self.eth_sender_requests,
+            ETHSenderRequest::Flush,
+        )
+        .await;
+
+        info!("Shutting down: sending the pending notifications");
+        request(
+            "send the pending notifications",
+            &mut self.notifier_flush_requests,
+            |response| response,
+        )
+        .await;
+
+        info!("Shutdown sequence is completed");
+    }
+}
+
+/// Sends the request to the actor and waits for the response.
+/// Returns `None` if the actor is stopped or doesn't respond in time.
+async fn request<R, T>(
+    step: &str,
+    requests: &mut mpsc::Sender<R>,
+    make_request: impl FnOnce(oneshot::Sender<T>) -> R,
+) -> Option<T> {
+    let (response_sender, response_receiver) = oneshot::channel();
+    let response = async {
+        requests.send(make_request(response_sender)).await.ok()?;
+        response_receiver.await.ok()
+    };
+    match time::timeout(SHUTDOWN_STEP_TIMEOUT, response).await {
+        Ok(Some(response)) => Some(response),
+        Ok(None) => {
+            warn!("Failed to {} on shutdown: the actor is stopped", step);
+            None
+        }
+        Err(_) => {
+            warn!("Failed to {} on shutdown: no response in time", step);
+            None
+        }
+    }
+}
@@ -3,12 +3,18 @@
 //! Main routine of this module operates a multithreaded event loop,
 //! which is used to spawn concurrent tasks to efficiently check the
 //! transactions signatures.
+//!
+//! zkSync signatures check is CPU-bound, so it's performed on the `rayon`
+//! thread pool rather than on the event loop threads. The same pool is used
+//! to verify the signatures of the transactions proposed for the block
+//! (see `verify_signatures_in_parallel`).
 
 // External uses
 use futures::{
     channel::{mpsc, oneshot},
     SinkExt, StreamExt,
 };
+use rayon::prelude::*;
 use tokio::runtime::{Builder, Handle};
 // Workspace uses
 use models::{
@@ -17,7 +23,7 @@ use models::{
 };
 // Local uses
 use crate::eth_watch::EthWatchRequest;
-use crate::mempool::TxAddError;
+use crate::mempool::{SignedTxVariant, TxAddError};
 use models::node::tx::EthSignData;
 
 /// Wrapper on a `FranklinTx` which guarantees that
@@ -36,15 +42,13 @@ impl VerifiedTx {
         request: &VerifyTxSignatureRequest,
         eth_watch_req: mpsc::Sender<EthWatchRequest>,
     ) -> Result<Self, TxAddError> {
-        verify_eth_signature(&request, eth_watch_req)
-            .await
-            .and_then(|_| verify_tx_correctness(request.tx.clone()))
-            .map(|tx| {
-                Self(SignedFranklinTx {
-                    tx,
-                    eth_sign_data: request.eth_sign_data.clone(),
-                })
-            })
+        verify_eth_signature(&request, eth_watch_req).await?;
+        let tx = verify_tx_correctness_on_pool(request.tx.clone()).await?;
+
+        Ok(Self(SignedFranklinTx {
+            tx,
+            eth_sign_data: request.eth_sign_data.clone(),
+        }))
     }
 
     /// Takes the `FranklinTx` out of the wrapper.
@@ -86,6 +90,12 @@ async fn verify_eth_signature(
         }
     }
 
+    // Validity window is not covered by the zkSync signature (see `validity_window_message`),
+    // so the transaction with a window can't be accepted without the Ethereum signature.
+    if request.tx.validity_window() != (None, None) && request.eth_sign_data.is_none() {
+        return Err(TxAddError::MissingEthSignature);
+    }
+
     // Check the signature.
     if let Some(sign_data) = &request.eth_sign_data {
         match &sign_data.signature {
@@ -144,6 +154,27 @@ fn verify_tx_correctness(mut tx: FranklinTx) -> Result<FranklinTx, TxAddError> {
     Ok(tx)
 }
 
+/// Runs the `verify_tx_correctness` on the `rayon` thread pool.
+async fn verify_tx_correctness_on_pool(tx: FranklinTx) -> Result<FranklinTx, TxAddError> {
+    let (sender, receiver) = oneshot::channel();
+    rayon::spawn(move || {
+        sender.send(verify_tx_correctness(tx)).unwrap_or_default();
+    });
+
+    receiver.await.unwrap_or(Err(TxAddError::Other))
+}
+
+/// Verifies the zkSync signatures of the transactions in parallel, caching the results
+/// within the transactions. Invalid signatures are not reported, such transactions
+/// will fail during the execution.
+pub fn verify_signatures_in_parallel(txs: &mut [SignedTxVariant]) {
+    txs.par_iter_mut().for_each(|variant| {
+        for tx in variant.txs_mut() {
+            tx.tx.cache_signer();
+        }
+    });
+}
+
 /// Request for the signature check.
 #[derive(Debug)]
 pub struct VerifyTxSignatureRequest {
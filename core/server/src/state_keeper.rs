@@ -1,4 +1,5 @@
 use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
 // External uses
 use futures::{
     channel::{mpsc, oneshot},
@@ -10,6 +11,7 @@ use web3::types::Address;
 // Workspace uses
 use crypto_exports::ff;
 use models::{
+    config_options::BlockSealingOptions,
     node::{
         block::{
             Block, ExecutedOperations, ExecutedPriorityOp, ExecutedTx,
@@ -20,10 +22,15 @@ use models::{
     },
     ActionType, BlockCommitRequest, CommitRequest,
 };
-use plasma::state::{OpSuccess, PlasmaState};
+use plasma::state::{CollectedFee, OpSuccess, PlasmaState};
 use storage::ConnectionPool;
 // Local uses
-use crate::{gas_counter::GasCounter, mempool::ProposedBlock};
+use crate::{
+    gas_counter::GasCounter,
+    mempool::{ProposedBlock, SignedTxVariant},
+    signature_checker::verify_signatures_in_parallel,
+    utils::metrics_registry::metrics_registry,
+};
 use models::node::SignedFranklinTx;
 
 /// Since withdraw is an expensive operation, we have to limit amount of
@@ -33,17 +40,43 @@ use models::node::SignedFranklinTx;
 /// the remaining withdrawals will go to the next block.
 pub const MAX_WITHDRAWALS_PER_BLOCK: u32 = 10;
 
+/// Minimal amount of the consecutive transactions executed in parallel. Fewer
+/// transactions are not worth the overhead of creating the worker states.
+const MIN_PARALLEL_TXS: usize = 8;
+
 pub enum ExecutedOpId {
     Transaction(TxHash),
     PriorityOp(u64),
 }
 
+/// State of the account known to the state keeper.
+#[derive(Debug, Clone)]
+pub struct AccountStates {
+    pub id: AccountId,
+    /// State as of the last sealed block, `None` if the account was created in the pending block.
+    pub sealed: Option<Account>,
+    /// State including the changes made by the operations of the pending block.
+    pub pending: Account,
+}
+
 pub enum StateKeeperRequest {
+    /// Returns the current account state, including the changes of the pending block.
     GetAccount(Address, oneshot::Sender<Option<(AccountId, Account)>>),
+    /// Returns both the sealed and the pending account states.
+    GetAccountStates(Address, oneshot::Sender<Option<AccountStates>>),
     GetLastUnprocessedPriorityOp(oneshot::Sender<u64>),
     ExecuteMiniBlock(ProposedBlock),
     GetExecutedInPendingBlock(ExecutedOpId, oneshot::Sender<Option<(BlockNumber, bool)>>),
     SealBlock,
+    /// Seals the pending block unless it's empty, used on the server shutdown. Responds with
+    /// the number of the sealed block once it's passed to `eth_sender`.
+    SealNonEmptyBlock(oneshot::Sender<Option<BlockNumber>>),
+    /// Executes the transaction against the current state without applying it, and
+    /// returns the account updates it would make or the reason of the failure.
+    DryRunTx(
+        Box<FranklinTx>,
+        oneshot::Sender<Result<AccountUpdates, String>>,
+    ),
 }
 
 pub struct ExecutedOpsNotify {
@@ -56,12 +89,21 @@ struct PendingBlock {
     success_operations: Vec<ExecutedOperations>,
     failed_txs: Vec<ExecutedTx>,
     account_updates: AccountUpdates,
+    /// Fees collected by the block operations, which are credited to the fee account
+    /// once the block is sealed (the same way it's done by the circuit).
+    collected_fees: Vec<CollectedFee>,
     chunks_left: usize,
     pending_op_block_index: u32,
     unprocessed_priority_op_before: u64,
     pending_block_iteration: usize,
     withdrawals_amount: u32,
     gas_counter: GasCounter,
+    /// Time when the first operation was added to the block.
+    first_op_added_at: Option<Instant>,
+    /// Earliest expiration Ethereum block of the priority operations in the block.
+    priority_op_deadline: Option<u64>,
+    /// Time when the block formation was started, i.e. the previous block was sealed.
+    started_at: Instant,
 }
 
 impl PendingBlock {
@@ -70,16 +112,79 @@ impl PendingBlock {
             success_operations: Vec::new(),
             failed_txs: Vec::new(),
             account_updates: Vec::new(),
+            collected_fees: Vec::new(),
             chunks_left,
             pending_op_block_index: 0,
             unprocessed_priority_op_before,
             pending_block_iteration: 0,
             withdrawals_amount: 0,
             gas_counter: GasCounter::new(),
+            first_op_added_at: None,
+            priority_op_deadline: None,
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Checks whether the block has no operations, including the failed transactions.
+    fn is_empty(&self) -> bool {
+        self.success_operations.is_empty() && self.failed_txs.is_empty()
+    }
+
+    /// Checks whether the block has been accumulating operations for longer than the deadline.
+    fn is_deadline_reached(&self, deadline: Duration) -> bool {
+        self.first_op_added_at
+            .map(|added_at| added_at.elapsed() >= deadline)
+            .unwrap_or(false)
+    }
+
+    /// Checks whether the operations fill at least the given share of the block chunks.
+    fn is_filled(&self, max_block_size: usize, min_fill_ratio: f64) -> bool {
+        let filled_chunks = max_block_size - self.chunks_left;
+        filled_chunks as f64 >= max_block_size as f64 * min_fill_ratio
+    }
+
+    /// Checks whether the block has no operations and the network has been idle
+    /// for longer than the padding interval.
+    fn is_padding_required(&self, padding_interval: Duration) -> bool {
+        self.is_empty() && self.started_at.elapsed() >= padding_interval
+    }
+}
+
+/// Position in the pending block, used to revert the partially applied batch of transactions.
+struct PendingBlockSnapshot {
+    success_operations: usize,
+    failed_txs: usize,
+    account_updates: usize,
+    collected_fees: usize,
+    chunks_left: usize,
+    pending_op_block_index: u32,
+    withdrawals_amount: u32,
+    gas_counter: GasCounter,
+}
+
+impl PendingBlockSnapshot {
+    fn new(pending_block: &PendingBlock) -> Self {
+        Self {
+            success_operations: pending_block.success_operations.len(),
+            failed_txs: pending_block.failed_txs.len(),
+            account_updates: pending_block.account_updates.len(),
+            collected_fees: pending_block.collected_fees.len(),
+            chunks_left: pending_block.chunks_left,
+            pending_op_block_index: pending_block.pending_op_block_index,
+            withdrawals_amount: pending_block.withdrawals_amount,
+            gas_counter: pending_block.gas_counter.clone(),
         }
     }
 }
 
+/// Transactions taken from the miniblock for the parallel execution, along with the chunks
+/// required by each of them and the pending block limits once all of them are added.
+struct ParallelTxs {
+    txs: Vec<(SignedFranklinTx, usize)>,
+    gas_counter: GasCounter,
+    withdrawals_amount: u32,
+}
+
 /// Responsible for tx processing and block forming.
 pub struct PlasmaStateKeeper {
     /// Current plasma state
@@ -95,8 +200,10 @@ pub struct PlasmaStateKeeper {
     executed_tx_notify_sender: mpsc::Sender<ExecutedOpsNotify>,
 
     available_block_chunk_sizes: Vec<usize>,
-    max_miniblock_iterations: usize,
-    max_miniblock_iterations_withdraw_block: usize,
+    /// Triggers of the pending block sealing.
+    sealing: BlockSealingOptions,
+    /// Amount of sealed blocks between the state checkpoints, `0` if checkpoints are disabled.
+    state_checkpoint_interval: u32,
 }
 
 pub struct PlasmaStateInitParams {
@@ -242,8 +349,8 @@ impl PlasmaStateKeeper {
         tx_for_commitments: mpsc::Sender<CommitRequest>,
         executed_tx_notify_sender: mpsc::Sender<ExecutedOpsNotify>,
         available_block_chunk_sizes: Vec<usize>,
-        max_miniblock_iterations: usize,
-        max_miniblock_iterations_withdraw_block: usize,
+        sealing: BlockSealingOptions,
+        state_checkpoint_interval: u32,
     ) -> Self {
         assert!(!available_block_chunk_sizes.is_empty());
 
@@ -274,8 +381,8 @@ impl PlasmaStateKeeper {
             pending_block: PendingBlock::new(initial_state.unprocessed_priority_op, max_block_size),
             executed_tx_notify_sender,
             available_block_chunk_sizes,
-            max_miniblock_iterations,
-            max_miniblock_iterations_withdraw_block,
+            sealing,
+            state_checkpoint_interval,
         };
 
         let root = keeper.state.root_hash();
@@ -383,6 +490,9 @@ impl PlasmaStateKeeper {
                         start.elapsed().as_millis()
                     );
                 }
+                StateKeeperRequest::GetAccountStates(addr, sender) => {
+                    sender.send(self.account_states(&addr)).unwrap_or_default();
+                }
                 StateKeeperRequest::GetLastUnprocessedPriorityOp(sender) => {
                     sender
                         .send(self.current_unprocessed_priority_op)
@@ -418,6 +528,24 @@ impl PlasmaStateKeeper {
                         start.elapsed().as_millis()
                     );
                 }
+                StateKeeperRequest::SealNonEmptyBlock(sender) => {
+                    let sealed_block = if self.pending_block.is_empty() {
+                        None
+                    } else {
+                        let block_number = self.state.block_number;
+                        self.seal_pending_block().await;
+                        Some(block_number)
+                    };
+                    sender.send(sealed_block).unwrap_or_default();
+                }
+                StateKeeperRequest::DryRunTx(tx, sender) => {
+                    sender.send(self.dry_run_tx(*tx)).unwrap_or_default();
+
+                    log::trace!(
+                        "DryRunTx request processed in {}ms",
+                        start.elapsed().as_millis()
+                    );
+                }
             }
 
             last_request_processed = std::time::Instant::now();
@@ -439,8 +567,21 @@ impl PlasmaStateKeeper {
         executed_ops.clear();
     }
 
-    async fn execute_tx_batch(&mut self, proposed_block: ProposedBlock) {
+    async fn execute_tx_batch(&mut self, mut proposed_block: ProposedBlock) {
         let mut executed_ops = Vec::new();
+        let current_eth_block = proposed_block.current_eth_block;
+        let mut executed_ops_count = 0;
+        let mut failed_txs_count = 0;
+
+        metrics_registry().set_gauge(
+            "state_keeper_miniblock_size",
+            "Amount of operations in the last miniblock proposed to the state keeper",
+            (proposed_block.priority_ops.len() + proposed_block.txs.len()) as f64,
+        );
+
+        // Signatures are checked before the state application, so the expensive
+        // checks are not performed sequentially during the execution.
+        verify_signatures_in_parallel(&mut proposed_block.txs);
 
         let mut priority_op_queue = proposed_block
             .priority_ops
@@ -449,6 +590,7 @@ impl PlasmaStateKeeper {
         while let Some(priority_op) = priority_op_queue.pop_front() {
             match self.apply_priority_op(priority_op) {
                 Ok(exec_op) => {
+                    executed_ops_count += 1;
                     executed_ops.push(exec_op);
                 }
                 Err(priority_op) => {
@@ -461,42 +603,164 @@ impl PlasmaStateKeeper {
         }
 
         let mut tx_queue = proposed_block.txs.into_iter().collect::<VecDeque<_>>();
-        while let Some(tx) = tx_queue.pop_front() {
-            match self.apply_tx(tx) {
-                Ok(exec_op) => {
-                    executed_ops.push(exec_op);
+        // Transactions returned by the failed parallel execution, which must be
+        // executed sequentially.
+        let mut sequential_txs_left = 0;
+        loop {
+            let parallel_txs = if sequential_txs_left == 0 {
+                self.take_parallel_txs(&mut tx_queue)
+            } else {
+                None
+            };
+            let result = if let Some(parallel_txs) = parallel_txs {
+                match self.apply_txs_in_parallel(parallel_txs) {
+                    Ok(exec_ops) => Ok(exec_ops),
+                    Err(txs) => {
+                        sequential_txs_left = txs.len();
+                        for tx in txs.into_iter().rev() {
+                            tx_queue.push_front(SignedTxVariant::Tx(tx));
+                        }
+                        continue;
+                    }
                 }
-                Err(tx) => {
+            } else {
+                let variant = match tx_queue.pop_front() {
+                    Some(variant) => variant,
+                    None => break,
+                };
+                sequential_txs_left = sequential_txs_left.saturating_sub(1);
+                match variant {
+                    SignedTxVariant::Tx(tx) => self
+                        .apply_new_tx(tx)
+                        .map(|exec_op| vec![exec_op])
+                        .map_err(SignedTxVariant::Tx),
+                    SignedTxVariant::Batch(txs) => {
+                        self.apply_batch(txs).map_err(SignedTxVariant::Batch)
+                    }
+                }
+            };
+            match result {
+                Ok(exec_ops) => {
+                    for exec_op in exec_ops {
+                        match exec_op.get_executed_tx() {
+                            Some(executed_tx) if !executed_tx.success => failed_txs_count += 1,
+                            _ => executed_ops_count += 1,
+                        }
+                        executed_ops.push(exec_op);
+                    }
+                }
+                Err(variant) => {
                     // We could not execute the tx due to either of block size limit
                     // or the withdraw operations limit, so we seal this block and
                     // the last transaction will go to the next block instead.
                     self.seal_pending_block().await;
                     self.notify_executed_ops(&mut executed_ops).await;
 
-                    tx_queue.push_front(tx);
+                    tx_queue.push_front(variant);
                 }
             }
         }
 
+        let registry = metrics_registry();
+        registry.increment_counter(
+            "state_keeper_executed_ops",
+            "Amount of operations successfully applied by the state keeper",
+            executed_ops_count as f64,
+        );
+        registry.increment_counter(
+            "state_keeper_failed_txs",
+            "Amount of transactions failed upon the application by the state keeper",
+            failed_txs_count as f64,
+        );
+
         if !self.pending_block.success_operations.is_empty() {
             self.pending_block.pending_block_iteration += 1;
         }
+        let has_operations = !self.pending_block.success_operations.is_empty()
+            || !self.pending_block.failed_txs.is_empty();
+        if has_operations && self.pending_block.first_op_added_at.is_none() {
+            self.pending_block.first_op_added_at = Some(Instant::now());
+        }
 
         // If pending block contains withdrawals we seal it faster
-        let max_miniblock_iterations = if self.pending_block.withdrawals_amount > 0 {
-            self.max_miniblock_iterations_withdraw_block
+        let has_withdrawals = self.pending_block.withdrawals_amount > 0;
+        let max_miniblock_iterations = if has_withdrawals {
+            self.sealing.max_miniblock_iterations_withdraw_block
         } else {
-            self.max_miniblock_iterations
+            self.sealing.max_miniblock_iterations
         };
-        if self.pending_block.pending_block_iteration > max_miniblock_iterations {
+        // Block is also sealed if it's not filled in time, so the users
+        // don't wait for their transactions for too long on a quiet network.
+        let deadline_reached = self
+            .sealing
+            .block_seal_deadline
+            .map(|deadline| self.pending_block.is_deadline_reached(deadline))
+            .unwrap_or(false);
+        // Block which is filled enough is not worth waiting for the few remaining chunks.
+        let max_block_size = self.max_block_size();
+        let filled = self
+            .sealing
+            .min_chunk_fill_ratio
+            .map(|ratio| self.pending_block.is_filled(max_block_size, ratio))
+            .unwrap_or(false);
+        let withdrawal_sealing = has_withdrawals && self.sealing.seal_on_withdrawal;
+        // On an idle network an empty block is sealed from time to time, so the already
+        // committed blocks keep being verified and don't age out in the queue.
+        let padding_required = self
+            .sealing
+            .padding_block_interval
+            .map(|interval| self.pending_block.is_padding_required(interval))
+            .unwrap_or(false);
+        if padding_required {
+            log::info!(
+                "Sealing padding block {} on an idle network",
+                self.state.block_number
+            );
+        }
+        // Priority operations must be committed before their expiration, otherwise
+        // the exodus mode can be triggered, so such a block is sealed without waiting.
+        let priority_op_deadline_approaching = current_eth_block
+            .map(|eth_block| self.is_priority_op_deadline_approaching(eth_block))
+            .unwrap_or(false);
+        if self.pending_block.pending_block_iteration > max_miniblock_iterations
+            || deadline_reached
+            || filled
+            || withdrawal_sealing
+            || padding_required
+            || priority_op_deadline_approaching
+        {
             self.seal_pending_block().await;
         } else {
             self.store_pending_block().await;
         }
+        registry.set_gauge(
+            "state_keeper_pending_block_ops",
+            "Amount of operations in the pending block",
+            (self.pending_block.success_operations.len() + self.pending_block.failed_txs.len())
+                as f64,
+        );
 
         self.notify_executed_ops(&mut executed_ops).await;
     }
 
+    /// Checks whether any of the priority operations in the pending block is
+    /// close to its expiration at the provided Ethereum block.
+    fn is_priority_op_deadline_approaching(&self, current_eth_block: u64) -> bool {
+        let deadline = match self.pending_block.priority_op_deadline {
+            Some(deadline) => deadline,
+            None => return false,
+        };
+
+        if current_eth_block >= deadline {
+            error!(
+                "Priority operation expires at Ethereum block {}, but it's not committed yet \
+                 (current Ethereum block: {}), exodus mode can be triggered",
+                deadline, current_eth_block
+            );
+        }
+        current_eth_block + self.sealing.priority_op_deadline_margin >= deadline
+    }
+
     // Err if there is no space in current block
     fn apply_priority_op(
         &mut self,
@@ -523,19 +787,46 @@ impl PlasmaStateKeeper {
             return Err(priority_op);
         }
 
+        // Invalid operation is still executed (e.g. full exit of the missing account
+        // doesn't withdraw anything), but it's recorded explicitly along with the reason.
+        let invalid_reason = self
+            .state
+            .validate_priority_op(&priority_op.data)
+            .err()
+            .map(|err| err.to_string());
+        if let Some(reason) = &invalid_reason {
+            warn!(
+                "Invalid priority operation #{}: {}",
+                priority_op.serial_id, reason
+            );
+        }
+
         let OpSuccess {
             fee,
             mut updates,
             executed_op,
         } = self.state.execute_priority_op(priority_op.data.clone());
+        // Priority operations can't be rejected, so the mismatch of the chunks
+        // accounting can only be caused by the inconsistent cost table.
+        assert_eq!(
+            executed_op.chunks(),
+            chunks_needed,
+            "Priority operation chunks mismatch: {:?}",
+            priority_op
+        );
+
+        let deadline = self
+            .pending_block
+            .priority_op_deadline
+            .map_or(priority_op.deadline_block, |deadline| {
+                deadline.min(priority_op.deadline_block)
+            });
+        self.pending_block.priority_op_deadline = Some(deadline);
 
         self.pending_block.chunks_left -= chunks_needed;
         self.pending_block.account_updates.append(&mut updates);
         if let Some(fee) = fee {
-            let fee_updates = self.state.collect_fee(&[fee], self.fee_account_id);
-            self.pending_block
-                .account_updates
-                .extend(fee_updates.into_iter());
+            self.pending_block.collected_fees.push(fee);
         }
         let block_index = self.pending_block.pending_op_block_index;
         self.pending_block.pending_op_block_index += 1;
@@ -545,6 +836,7 @@ impl PlasmaStateKeeper {
             priority_op,
             block_index,
             created_at: chrono::Utc::now(),
+            invalid_reason,
         }));
         self.pending_block
             .success_operations
@@ -553,9 +845,36 @@ impl PlasmaStateKeeper {
         Ok(exec_result)
     }
 
+    /// Applies the transaction proposed by the mempool.
+    ///
+    /// Transaction can't be executed outside of its validity window. Such a transaction
+    /// is considered failed and does not take place in the block. Transactions restored
+    /// from the pending block are applied with `apply_tx` directly, since their windows
+    /// were checked upon the first execution and may have passed since then.
+    fn apply_new_tx(
+        &mut self,
+        tx: SignedFranklinTx,
+    ) -> Result<ExecutedOperations, SignedFranklinTx> {
+        if !tx.is_valid_at(chrono::Utc::now().timestamp() as u64) {
+            warn!("Transaction is outside of its validity window: {:?}", tx);
+            return Ok(self.fail_tx(tx, "Transaction is outside of its validity window"));
+        }
+        self.apply_tx(tx)
+    }
+
     fn apply_tx(&mut self, tx: SignedFranklinTx) -> Result<ExecutedOperations, SignedFranklinTx> {
         let chunks_needed = self.state.chunks_for_tx(&tx);
 
+        // Transaction that doesn't fit even into the empty block would be postponed
+        // to the next block forever, so it's rejected instead.
+        if chunks_needed > self.max_block_size() {
+            warn!(
+                "Transaction requires {} chunks, which exceeds the block capacity: {:?}",
+                chunks_needed, tx
+            );
+            return Ok(self.fail_tx(tx, "Transaction does not fit into the block"));
+        }
+
         // If we can't add the tx to the block due to the size limit, we return this tx,
         // seal the block and execute it again.
         if self.pending_block.chunks_left < chunks_needed {
@@ -592,20 +911,39 @@ impl PlasmaStateKeeper {
         }
 
         let tx_updates = self.state.execute_tx(tx.tx.clone());
+        Ok(self.add_executed_tx(tx, chunks_needed, tx_updates))
+    }
 
-        let exec_result = match tx_updates {
+    /// Adds the result of the transaction execution to the pending block.
+    fn add_executed_tx(
+        &mut self,
+        tx: SignedFranklinTx,
+        chunks_needed: usize,
+        tx_updates: Result<OpSuccess, failure::Error>,
+    ) -> ExecutedOperations {
+        match tx_updates {
             Ok(OpSuccess {
                 fee,
                 mut updates,
                 executed_op,
             }) => {
+                // Chunks accounting must match the circuit exactly, otherwise the block
+                // would fail only at the proving stage. Such an operation is reverted.
+                if executed_op.chunks() != chunks_needed {
+                    error!(
+                        "Executed operation occupies {} chunks, while {} were expected: {:?}",
+                        executed_op.chunks(),
+                        chunks_needed,
+                        tx
+                    );
+                    self.state.revert_account_updates(&updates);
+                    return self.fail_tx(tx, "Operation chunks mismatch");
+                }
+
                 self.pending_block.chunks_left -= chunks_needed;
                 self.pending_block.account_updates.append(&mut updates);
                 if let Some(fee) = fee {
-                    let fee_updates = self.state.collect_fee(&[fee], self.fee_account_id);
-                    self.pending_block
-                        .account_updates
-                        .extend(fee_updates.into_iter());
+                    self.pending_block.collected_fees.push(fee);
                 }
                 let block_index = self.pending_block.pending_op_block_index;
                 self.pending_block.pending_op_block_index += 1;
@@ -625,33 +963,232 @@ impl PlasmaStateKeeper {
             }
             Err(e) => {
                 warn!("Failed to execute transaction: {:?}, {}", tx, e);
-                let failed_tx = ExecutedTx {
-                    signed_tx: tx,
-                    success: false,
-                    op: None,
-                    fail_reason: Some(e.to_string()),
-                    block_index: None,
-                    created_at: chrono::Utc::now(),
-                };
-                self.pending_block.failed_txs.push(failed_tx.clone());
-                ExecutedOperations::Tx(Box::new(failed_tx))
+                self.fail_tx(tx, &e.to_string())
+            }
+        }
+    }
+
+    /// Takes the longest sequence of the single transactions from the front of the queue
+    /// that fits into the pending block even if all of them succeed. Returns `None` if the
+    /// sequence is too short to be executed in parallel.
+    ///
+    /// Transfers to the new accounts end the sequence, since the chunks they require depend
+    /// on the preceding transactions. Transactions outside of their validity window end it
+    /// as well, so they are failed by `apply_new_tx`.
+    fn take_parallel_txs(&self, tx_queue: &mut VecDeque<SignedTxVariant>) -> Option<ParallelTxs> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut chunks_left = self.pending_block.chunks_left;
+        let mut gas_counter = self.pending_block.gas_counter.clone();
+        let mut withdrawals_amount = self.pending_block.withdrawals_amount;
+        let mut txs_chunks = Vec::new();
+        for variant in tx_queue.iter() {
+            let tx = match variant {
+                SignedTxVariant::Tx(tx) => tx,
+                SignedTxVariant::Batch(_) => break,
+            };
+            if !tx.is_valid_at(now) {
+                break;
+            }
+            if let FranklinTx::Transfer(transfer) = &tx.tx {
+                if self.state.get_account_by_address(&transfer.to).is_none() {
+                    break;
+                }
+            }
+
+            let chunks_needed = self.state.chunks_for_tx(tx);
+            if chunks_needed > chunks_left {
+                break;
+            }
+            let is_withdraw = matches!(tx.tx, FranklinTx::Withdraw(_));
+            if is_withdraw && withdrawals_amount >= MAX_WITHDRAWALS_PER_BLOCK {
+                break;
+            }
+            // Gas counter is changed only if the operation fits.
+            if let Ok(non_executed_op) = self.state.franklin_tx_to_franklin_op(tx.tx.clone()) {
+                if gas_counter.add_op(&non_executed_op).is_err() {
+                    break;
+                }
+            }
+
+            if is_withdraw {
+                withdrawals_amount += 1;
             }
+            chunks_left -= chunks_needed;
+            txs_chunks.push(chunks_needed);
+        }
+
+        if txs_chunks.len() < MIN_PARALLEL_TXS {
+            return None;
+        }
+        let txs = txs_chunks
+            .into_iter()
+            .map(|chunks_needed| match tx_queue.pop_front() {
+                Some(SignedTxVariant::Tx(tx)) => (tx, chunks_needed),
+                _ => unreachable!("Only the single transactions are taken"),
+            })
+            .collect();
+        Some(ParallelTxs {
+            txs,
+            gas_counter,
+            withdrawals_amount,
+        })
+    }
+
+    /// Executes the transactions in parallel and adds the results to the pending block
+    /// in the original order.
+    ///
+    /// If the chunks occupied by any of the operations don't match the expected ones,
+    /// the changes are reverted and the transactions are returned, so they can be
+    /// executed sequentially.
+    fn apply_txs_in_parallel(
+        &mut self,
+        parallel_txs: ParallelTxs,
+    ) -> Result<Vec<ExecutedOperations>, Vec<SignedFranklinTx>> {
+        let snapshot = PendingBlockSnapshot::new(&self.pending_block);
+        self.pending_block.gas_counter = parallel_txs.gas_counter;
+        self.pending_block.withdrawals_amount = parallel_txs.withdrawals_amount;
+
+        let (txs, txs_chunks): (Vec<_>, Vec<_>) = parallel_txs.txs.into_iter().unzip();
+        let results = self
+            .state
+            .execute_txs_in_parallel(txs.iter().map(|tx| tx.tx.clone()).collect());
+
+        let chunks_mismatch = results.iter().zip(&txs_chunks).any(|(result, chunks)| {
+            matches!(result, Ok(success) if success.executed_op.chunks() != *chunks)
+        });
+        if chunks_mismatch {
+            warn!(
+                "Chunks mismatch upon the parallel execution, executing transactions sequentially"
+            );
+            // Transactions of the different groups don't touch the same accounts, so
+            // the updates can be reverted in the reversed original order.
+            let updates: AccountUpdates = results
+                .into_iter()
+                .filter_map(Result::ok)
+                .flat_map(|success| success.updates)
+                .collect();
+            self.state.revert_account_updates(&updates);
+            self.revert_pending_block(snapshot);
+            return Err(txs);
+        }
+
+        Ok(txs
+            .into_iter()
+            .zip(txs_chunks)
+            .zip(results)
+            .map(|((tx, chunks_needed), result)| self.add_executed_tx(tx, chunks_needed, result))
+            .collect())
+    }
+
+    /// Adds the transaction to the block as a failed one.
+    fn fail_tx(&mut self, tx: SignedFranklinTx, fail_reason: &str) -> ExecutedOperations {
+        let failed_tx = ExecutedTx {
+            signed_tx: tx,
+            success: false,
+            op: None,
+            fail_reason: Some(fail_reason.to_string()),
+            block_index: None,
+            created_at: chrono::Utc::now(),
         };
+        self.pending_block.failed_txs.push(failed_tx.clone());
+        ExecutedOperations::Tx(Box::new(failed_tx))
+    }
 
-        Ok(exec_result)
+    /// Executes the batch of transactions atomically: if any of the transactions fails,
+    /// the changes made by the batch are reverted and all of its transactions are
+    /// considered failed.
+    ///
+    /// Returns `Err` if the batch doesn't fit into the pending block, so it should be
+    /// executed in the next one.
+    fn apply_batch(
+        &mut self,
+        batch: Vec<SignedFranklinTx>,
+    ) -> Result<Vec<ExecutedOperations>, Vec<SignedFranklinTx>> {
+        let snapshot = PendingBlockSnapshot::new(&self.pending_block);
+        let block_was_empty = self.pending_block.success_operations.is_empty()
+            && self.pending_block.failed_txs.is_empty();
+
+        let mut executed_ops = Vec::with_capacity(batch.len());
+        let mut fail_reason = None;
+        for tx in batch.iter().cloned() {
+            match self.apply_new_tx(tx) {
+                Ok(exec_op) => {
+                    if let Some(failed_tx) = exec_op.get_executed_tx().filter(|tx| !tx.success) {
+                        fail_reason = Some(failed_tx.fail_reason.clone().unwrap_or_default());
+                        break;
+                    }
+                    executed_ops.push(exec_op);
+                }
+                Err(_) => {
+                    self.revert_pending_block(snapshot);
+                    if !block_was_empty {
+                        return Err(batch);
+                    }
+                    // Batch can't fit even into the empty block.
+                    return Ok(self.fail_batch(batch, "Batch does not fit into the block"));
+                }
+            }
+        }
+
+        match fail_reason {
+            Some(fail_reason) => {
+                self.revert_pending_block(snapshot);
+                let fail_reason = format!("Batch transaction failed: {}", fail_reason);
+                Ok(self.fail_batch(batch, &fail_reason))
+            }
+            None => Ok(executed_ops),
+        }
+    }
+
+    /// Reverts the changes made to the pending block and the state after the snapshot.
+    fn revert_pending_block(&mut self, snapshot: PendingBlockSnapshot) {
+        let reverted_updates = self
+            .pending_block
+            .account_updates
+            .split_off(snapshot.account_updates);
+        self.state.revert_account_updates(&reverted_updates);
+
+        let pending_block = &mut self.pending_block;
+        pending_block
+            .success_operations
+            .truncate(snapshot.success_operations);
+        pending_block.failed_txs.truncate(snapshot.failed_txs);
+        pending_block
+            .collected_fees
+            .truncate(snapshot.collected_fees);
+        pending_block.chunks_left = snapshot.chunks_left;
+        pending_block.pending_op_block_index = snapshot.pending_op_block_index;
+        pending_block.withdrawals_amount = snapshot.withdrawals_amount;
+        pending_block.gas_counter = snapshot.gas_counter;
+    }
+
+    /// Adds all the transactions of the batch to the block as failed ones.
+    fn fail_batch(
+        &mut self,
+        batch: Vec<SignedFranklinTx>,
+        fail_reason: &str,
+    ) -> Vec<ExecutedOperations> {
+        warn!("Failed to execute transactions batch: {}", fail_reason);
+        batch
+            .into_iter()
+            .map(|tx| self.fail_tx(tx, fail_reason))
+            .collect()
+    }
+
+    /// Returns the capacity of the largest supported block in chunks.
+    fn max_block_size(&self) -> usize {
+        *self
+            .available_block_chunk_sizes
+            .last()
+            .expect("failed to get max block size")
     }
 
     /// Finalizes the pending block, transforming it into a full block.
     async fn seal_pending_block(&mut self) {
+        let max_block_size = self.max_block_size();
         let pending_block = std::mem::replace(
             &mut self.pending_block,
-            PendingBlock::new(
-                self.current_unprocessed_priority_op,
-                *self
-                    .available_block_chunk_sizes
-                    .last()
-                    .expect("failed to get max block size"),
-            ),
+            PendingBlock::new(self.current_unprocessed_priority_op, max_block_size),
         );
 
         let mut block_transactions = pending_block.success_operations;
@@ -662,9 +1199,18 @@ impl PlasmaStateKeeper {
                 .map(|tx| ExecutedOperations::Tx(Box::new(tx))),
         );
 
+        Self::report_sealed_block_metrics(&block_transactions);
+
         let commit_gas_limit = pending_block.gas_counter.commit_gas_limit();
         let verify_gas_limit = pending_block.gas_counter.verify_gas_limit();
 
+        // Fees are credited to the fee account after all the block operations are applied.
+        let mut account_updates = pending_block.account_updates;
+        account_updates.extend(
+            self.state
+                .collect_fee(&pending_block.collected_fees, self.fee_account_id),
+        );
+
         let block_commit_request = BlockCommitRequest {
             block: Block::new_from_availabe_block_sizes(
                 self.state.block_number,
@@ -678,21 +1224,42 @@ impl PlasmaStateKeeper {
                 &self.available_block_chunk_sizes,
                 commit_gas_limit,
                 verify_gas_limit,
+                Some(chrono::Utc::now().timestamp() as u64),
             ),
-            accounts_updated: pending_block.account_updates,
+            accounts_updated: account_updates,
         };
         self.state.block_number += 1;
 
+        assert_eq!(
+            block_commit_request.block.chunks_used(),
+            max_block_size - pending_block.chunks_left,
+            "Chunks accounting of the pending block doesn't match its operations"
+        );
+        debug!(
+            "Fees collected by the block {}: {:?}",
+            block_commit_request.block.block_number,
+            block_commit_request.block.collected_fees()
+        );
+
         info!(
-            "Creating full block: {}, operations: {}, chunks_left: {}, miniblock iterations: {}",
+            "Creating full block: {}, operations: {}, chunks_left: {}, block size: {}, miniblock iterations: {}",
             block_commit_request.block.block_number,
             block_commit_request.block.block_transactions.len(),
             pending_block.chunks_left,
+            block_commit_request.block.block_chunks_size,
             pending_block.pending_block_iteration
         );
 
+        metrics_registry().set_gauge(
+            "state_keeper_block_fill_ratio",
+            "Ratio of the chunks used by the operations in the last sealed block",
+            block_commit_request.block.chunks_used() as f64
+                / block_commit_request.block.block_chunks_size as f64,
+        );
+
         let (notification_sender, notification_receiver) = oneshot::channel::<()>();
 
+        let block_number = block_commit_request.block.block_number;
         let commit_request = CommitRequest::Block(block_commit_request, notification_sender);
         self.tx_for_commitments
             .send(commit_request)
@@ -702,6 +1269,55 @@ impl PlasmaStateKeeper {
         notification_receiver
             .await
             .expect("committer sender dropped");
+
+        let checkpoint_interval = self.state_checkpoint_interval;
+        if checkpoint_interval != 0 && block_number % checkpoint_interval == 0 {
+            self.send_state_checkpoint(block_number).await;
+        }
+    }
+
+    /// Sends the checkpoint of the state as of the just sealed block to the committer.
+    /// Once stored, the state is restored from the checkpoint on the server start instead of
+    /// applying the updates of all the blocks since the last verified one.
+    async fn send_state_checkpoint(&mut self, block_number: BlockNumber) {
+        let accounts = self.state.get_accounts().into_iter().collect();
+        self.tx_for_commitments
+            .send(CommitRequest::StateCheckpoint(block_number, accounts))
+            .await
+            .expect("committer receiver dropped");
+    }
+
+    /// Reports the amount of sealed blocks and the time operations spent in the
+    /// pending block from their execution up to the block seal.
+    fn report_sealed_block_metrics(block_transactions: &[ExecutedOperations]) {
+        let now = chrono::Utc::now();
+        let seal_latency_sum: f64 = block_transactions
+            .iter()
+            .map(|op| {
+                let executed_at = match op {
+                    ExecutedOperations::Tx(tx) => tx.created_at,
+                    ExecutedOperations::PriorityOp(op) => op.created_at,
+                };
+                (now - executed_at).num_milliseconds() as f64 / 1000.0
+            })
+            .sum();
+
+        let registry = metrics_registry();
+        registry.increment_counter(
+            "state_keeper_sealed_blocks",
+            "Amount of blocks sealed by the state keeper",
+            1.0,
+        );
+        registry.increment_counter(
+            "state_keeper_seal_latency_seconds_sum",
+            "Total time operations spent in the pending block before the block seal",
+            seal_latency_sum,
+        );
+        registry.increment_counter(
+            "state_keeper_seal_latency_seconds_count",
+            "Amount of operations included into the sealed blocks",
+            block_transactions.len() as f64,
+        );
     }
 
     /// Stores intermediate representation of a pending block in the database,
@@ -772,9 +1388,39 @@ impl PlasmaStateKeeper {
         None
     }
 
+    /// Executes the transaction against the current state (including the pending block)
+    /// and reverts its updates right away, so neither the state nor the pending block
+    /// observe the transaction. Signatures are not checked.
+    fn dry_run_tx(&mut self, tx: FranklinTx) -> Result<AccountUpdates, String> {
+        let OpSuccess { updates, .. } = self.state.execute_tx(tx).map_err(|e| e.to_string())?;
+        self.state.revert_account_updates(&updates);
+        Ok(updates)
+    }
+
     fn account(&self, address: &Address) -> Option<(AccountId, Account)> {
         self.state.get_account_by_address(address)
     }
+
+    /// Returns the account state along with its state as of the last sealed block,
+    /// which is restored by reverting the account updates of the pending block.
+    fn account_states(&self, address: &Address) -> Option<AccountStates> {
+        let (id, pending) = self.account(address)?;
+        let reversed_updates: Vec<_> = self
+            .pending_block
+            .account_updates
+            .iter()
+            .rev()
+            .filter(|(account_id, _)| *account_id == id)
+            .map(|(_, update)| update.reversed_update())
+            .collect();
+        let sealed = Account::apply_updates(Some(pending.clone()), &reversed_updates);
+
+        Some(AccountStates {
+            id,
+            sealed,
+            pending,
+        })
+    }
 }
 
 #[must_use]
@@ -785,3 +1431,304 @@ pub fn start_state_keeper(
 ) -> JoinHandle<()> {
     runtime.spawn(sk.run(pending_block))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use models::config_options::DEFAULT_PRIORITY_OP_DEADLINE_MARGIN;
+    use models::node::{Nonce, PrivateKey};
+    use models::test_utils::{accounts_with_keys, address, signed_transfer};
+
+    const ACCOUNTS: u64 = 3;
+    const BLOCK_SIZE: usize = 20;
+
+    /// Creates the state keeper with the fee account (`0`) and the accounts
+    /// having some ETH balance, along with the keys of the latter.
+    fn state_keeper() -> (PlasmaStateKeeper, Vec<PrivateKey>) {
+        let (mut accounts, keys) = accounts_with_keys(ACCOUNTS + 1, 1000);
+        accounts.insert(0, Account::default_with_address(&address(0)));
+        let mut init_params = PlasmaStateInitParams::new();
+        for (account_id, account) in accounts {
+            init_params.insert_account(account_id, account);
+        }
+
+        let sealing = BlockSealingOptions {
+            miniblock_interval: Duration::from_millis(200),
+            max_miniblock_iterations: 10,
+            max_miniblock_iterations_withdraw_block: 10,
+            block_seal_deadline: None,
+            min_chunk_fill_ratio: None,
+            seal_on_withdrawal: false,
+            padding_block_interval: None,
+            priority_op_deadline_margin: DEFAULT_PRIORITY_OP_DEADLINE_MARGIN,
+        };
+        let state_keeper = PlasmaStateKeeper::new(
+            init_params,
+            address(0),
+            mpsc::channel(1).1,
+            mpsc::channel(1).0,
+            mpsc::channel(1).0,
+            vec![BLOCK_SIZE],
+            sealing,
+            0,
+        );
+        (state_keeper, keys)
+    }
+
+    fn transfer(
+        keys: &[PrivateKey],
+        from: u64,
+        to: u64,
+        amount: u32,
+        fee: u32,
+        nonce: Nonce,
+    ) -> SignedFranklinTx {
+        let transfer = signed_transfer(keys, from, to, amount, fee, nonce);
+        FranklinTx::Transfer(Box::new(transfer)).into()
+    }
+
+    /// Checks that the transactions of the restored pending block are applied again
+    /// even if their validity windows have passed, while the new ones are failed.
+    #[test]
+    fn restored_txs_ignore_validity_window() {
+        let (mut state_keeper, keys) = state_keeper();
+        let mut expired_tx = transfer(&keys, 1, 2, 10, 0, 0);
+        if let FranklinTx::Transfer(transfer) = &mut expired_tx.tx {
+            transfer.valid_until = Some(1);
+        }
+
+        let pending_block = SendablePendingBlock {
+            number: 1,
+            chunks_left: BLOCK_SIZE - expired_tx.min_chunks(),
+            unprocessed_priority_op_before: 0,
+            pending_block_iteration: 1,
+            success_operations: vec![ExecutedOperations::Tx(Box::new(ExecutedTx {
+                signed_tx: expired_tx.clone(),
+                success: true,
+                op: None,
+                fail_reason: None,
+                block_index: Some(0),
+                created_at: chrono::Utc::now(),
+            }))],
+            failed_txs: Vec::new(),
+        };
+        futures::executor::block_on(state_keeper.initialize(Some(pending_block)));
+        assert_eq!(state_keeper.pending_block.success_operations.len(), 1);
+        assert!(state_keeper.pending_block.failed_txs.is_empty());
+
+        let mut new_expired_tx = transfer(&keys, 1, 2, 10, 0, 1);
+        if let FranklinTx::Transfer(transfer) = &mut new_expired_tx.tx {
+            transfer.valid_until = Some(1);
+        }
+        let exec_op = state_keeper
+            .apply_new_tx(new_expired_tx)
+            .expect("Expired tx must be failed, not postponed");
+        assert!(!exec_op.get_executed_tx().unwrap().success);
+        assert_eq!(state_keeper.pending_block.success_operations.len(), 1);
+        assert_eq!(state_keeper.pending_block.failed_txs.len(), 1);
+    }
+
+    fn balances(state_keeper: &PlasmaStateKeeper) -> Vec<num::BigUint> {
+        (0..=ACCOUNTS)
+            .map(|id| {
+                state_keeper
+                    .state
+                    .get_account(id as AccountId)
+                    .unwrap()
+                    .get_balance(0)
+            })
+            .collect()
+    }
+
+    /// Checks that if a transaction in the middle of the batch fails, the state and
+    /// the pending block are restored to the state before the batch, and all of the
+    /// batch transactions are failed.
+    #[test]
+    fn failed_batch_is_reverted() {
+        let (mut state_keeper, keys) = state_keeper();
+        state_keeper
+            .apply_new_tx(transfer(&keys, 1, 2, 10, 1, 0))
+            .expect("Tx must fit into the block");
+
+        let snapshot_balances = balances(&state_keeper);
+        let pending_block = &state_keeper.pending_block;
+        let (success_operations, chunks_left, collected_fees, block_index) = (
+            pending_block.success_operations.len(),
+            pending_block.chunks_left,
+            pending_block.collected_fees.len(),
+            pending_block.pending_op_block_index,
+        );
+
+        // Second transaction exceeds the balance of the sender.
+        let batch = vec![
+            transfer(&keys, 2, 3, 10, 1, 0),
+            transfer(&keys, 3, 1, 5000, 1, 0),
+            transfer(&keys, 1, 3, 10, 1, 1),
+        ];
+        let exec_ops = state_keeper
+            .apply_batch(batch)
+            .expect("Batch must fit into the block");
+        assert_eq!(exec_ops.len(), 3);
+        assert!(exec_ops
+            .iter()
+            .all(|op| !op.get_executed_tx().unwrap().success));
+
+        let pending_block = &state_keeper.pending_block;
+        assert_eq!(pending_block.success_operations.len(), success_operations);
+        assert_eq!(pending_block.chunks_left, chunks_left);
+        assert_eq!(pending_block.collected_fees.len(), collected_fees);
+        assert_eq!(pending_block.pending_op_block_index, block_index);
+        assert_eq!(pending_block.failed_txs.len(), 3);
+        assert_eq!(balances(&state_keeper), snapshot_balances);
+
+        // The same transactions without the failing one are applied.
+        let batch = vec![
+            transfer(&keys, 2, 3, 10, 1, 0),
+            transfer(&keys, 1, 3, 10, 1, 1),
+        ];
+        let exec_ops = state_keeper
+            .apply_batch(batch)
+            .expect("Batch must fit into the block");
+        assert!(exec_ops
+            .iter()
+            .all(|op| op.get_executed_tx().unwrap().success));
+        let pending_block = &state_keeper.pending_block;
+        assert_eq!(
+            pending_block.success_operations.len(),
+            success_operations + 2
+        );
+        assert_eq!(pending_block.collected_fees.len(), collected_fees + 2);
+        assert_ne!(balances(&state_keeper), snapshot_balances);
+    }
+
+    /// Checks that the batch which doesn't fit into the non-empty pending block is postponed
+    /// without changing the block, and the one not fitting into the empty block is failed.
+    #[test]
+    fn oversized_batch_is_reverted() {
+        let (mut state_keeper, keys) = state_keeper();
+        let chunks_for_tx = transfer(&keys, 1, 2, 10, 1, 0).min_chunks();
+        let batch_len = BLOCK_SIZE / chunks_for_tx + 1;
+        let batch = |from_nonce: Nonce| {
+            (0..batch_len as Nonce)
+                .map(|i| transfer(&keys, 1, 2, 1, 0, from_nonce + i))
+                .collect::<Vec<_>>()
+        };
+
+        // Batch can't fit even into the empty block.
+        let exec_ops = state_keeper
+            .apply_batch(batch(0))
+            .expect("Batch must be failed, not postponed");
+        assert!(exec_ops
+            .iter()
+            .all(|op| !op.get_executed_tx().unwrap().success));
+        assert!(state_keeper.pending_block.success_operations.is_empty());
+        assert_eq!(state_keeper.pending_block.chunks_left, BLOCK_SIZE);
+
+        state_keeper
+            .apply_new_tx(transfer(&keys, 2, 1, 10, 1, 0))
+            .expect("Tx must fit into the block");
+        let snapshot_balances = balances(&state_keeper);
+        let chunks_left = state_keeper.pending_block.chunks_left;
+        let failed_txs = state_keeper.pending_block.failed_txs.len();
+
+        // Batch is postponed to the next block.
+        let postponed = state_keeper
+            .apply_batch(batch(0))
+            .expect_err("Batch must be postponed");
+        assert_eq!(postponed.len(), batch_len);
+        assert_eq!(state_keeper.pending_block.success_operations.len(), 1);
+        assert_eq!(state_keeper.pending_block.failed_txs.len(), failed_txs);
+        assert_eq!(state_keeper.pending_block.chunks_left, chunks_left);
+        assert_eq!(balances(&state_keeper), snapshot_balances);
+    }
+
+    /// Checks that the transactions executed in parallel are added to the pending block
+    /// the same way as the sequentially executed ones, and that the batch ends the
+    /// sequence of the transactions taken for the parallel execution.
+    #[test]
+    fn parallel_execution_matches_sequential() {
+        let (mut parallel_keeper, keys) = state_keeper();
+        let (mut sequential_keeper, _) = state_keeper();
+
+        let mut txs = Vec::new();
+        for nonce in 0..3 {
+            txs.push(transfer(&keys, 1, 2, 10, 1, nonce));
+            // The last transfer of the second account exceeds its balance.
+            let amount = if nonce == 2 { 5000 } else { 20 };
+            txs.push(transfer(&keys, 2, 3, amount, 1, nonce));
+            txs.push(transfer(&keys, 3, 1, 30, 1, nonce));
+        }
+        let mut tx_queue = txs
+            .iter()
+            .cloned()
+            .map(SignedTxVariant::Tx)
+            .chain(std::iter::once(SignedTxVariant::Batch(vec![transfer(
+                &keys, 1, 2, 1, 0, 3,
+            )])))
+            .collect::<VecDeque<_>>();
+
+        let parallel_txs = parallel_keeper
+            .take_parallel_txs(&mut tx_queue)
+            .expect("Transactions must be taken for the parallel execution");
+        assert_eq!(parallel_txs.txs.len(), txs.len());
+        assert_eq!(tx_queue.len(), 1);
+        let parallel_ops = parallel_keeper
+            .apply_txs_in_parallel(parallel_txs)
+            .expect("Chunks must match");
+
+        let sequential_ops = txs
+            .into_iter()
+            .map(|tx| {
+                sequential_keeper
+                    .apply_new_tx(tx)
+                    .expect("Tx must fit into the block")
+            })
+            .collect::<Vec<_>>();
+
+        let summary = |ops: &[ExecutedOperations]| {
+            ops.iter()
+                .map(|op| {
+                    let tx = op.get_executed_tx().unwrap();
+                    (tx.signed_tx.hash(), tx.success, tx.block_index)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(summary(&parallel_ops), summary(&sequential_ops));
+        assert_eq!(balances(&parallel_keeper), balances(&sequential_keeper));
+
+        let (parallel_block, sequential_block) = (
+            &parallel_keeper.pending_block,
+            &sequential_keeper.pending_block,
+        );
+        assert_eq!(parallel_block.failed_txs.len(), 1);
+        assert_eq!(parallel_block.chunks_left, sequential_block.chunks_left);
+        assert_eq!(
+            parallel_block.collected_fees.len(),
+            sequential_block.collected_fees.len()
+        );
+        assert_eq!(
+            parallel_block.account_updates.len(),
+            sequential_block.account_updates.len()
+        );
+        assert_eq!(
+            parallel_block.gas_counter.commit_gas_limit(),
+            sequential_block.gas_counter.commit_gas_limit()
+        );
+    }
+
+    #[test]
+    fn pending_block_fill_ratio() {
+        let mut pending_block = PendingBlock::new(0, 100);
+        assert!(!pending_block.is_filled(100, 0.8));
+
+        pending_block.chunks_left = 21;
+        assert!(!pending_block.is_filled(100, 0.8));
+
+        pending_block.chunks_left = 20;
+        assert!(pending_block.is_filled(100, 0.8));
+        assert!(!pending_block.is_filled(100, 1.0));
+
+        pending_block.chunks_left = 0;
+        assert!(pending_block.is_filled(100, 1.0));
+    }
+}
@@ -0,0 +1,233 @@
+//! Utilities for handling the failures of the Ethereum node calls.
+//!
+//! Errors are classified as transient (the node is unavailable or overloaded, so the call
+//! may succeed later) or fatal (the call itself is wrong, so repeating it won't help).
+//! Transient failures are retried with the jittered exponential backoff, so the failing
+//! node isn't hammered by the retries, and the retries of the different components are
+//! spread in time. Circuit breaker rejects the calls right away once the node keeps failing.
+
+// Built-in deps
+use std::time::{Duration, Instant};
+// External uses
+use jsonrpc_core::ErrorCode;
+use rand::Rng;
+
+/// Rate limit error will contain this response code.
+const RATE_LIMIT_HTTP_CODE: &str = "429";
+
+/// RPC error code used by the Ethereum nodes to report the exceeded request limit.
+const LIMIT_EXCEEDED_RPC_CODE: i64 = -32005;
+
+/// Checks whether the failed Ethereum node call may succeed if repeated later.
+pub fn is_transient_error(error: &failure::Error) -> bool {
+    if let Some(error) = error.downcast_ref::<web3::Error>() {
+        return is_transient_web3_error(error);
+    }
+
+    // Some errors are only passed as messages, so the rate limit is detected by the code.
+    error.to_string().contains(RATE_LIMIT_HTTP_CODE)
+}
+
+/// Checks whether the web3 error is caused by the node unavailability or overload rather
+/// than by the request.
+pub fn is_transient_web3_error(error: &web3::Error) -> bool {
+    match error {
+        web3::Error::Unreachable
+        | web3::Error::Transport(_)
+        | web3::Error::Io(_)
+        | web3::Error::InvalidResponse(_) => true,
+        web3::Error::Rpc(error) => matches!(
+            error.code,
+            ErrorCode::InternalError | ErrorCode::ServerError(LIMIT_EXCEEDED_RPC_CODE)
+        ),
+        _ => false,
+    }
+}
+
+/// Exponentially growing delay between the retries of the failing call.
+///
+/// The delay is doubled on every retry up to the maximum one, and only a random
+/// amount between the half of the delay and the whole delay is actually waited.
+#[derive(Debug, Clone)]
+pub struct ExponentialBackoff {
+    min_delay: Duration,
+    max_delay: Duration,
+    /// Amount of the retries since the last success.
+    retries: u32,
+}
+
+impl ExponentialBackoff {
+    pub fn new(min_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            min_delay,
+            max_delay,
+            retries: 0,
+        }
+    }
+
+    /// Returns the delay to wait before the next retry.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.current_max_delay();
+        self.retries = self.retries.saturating_add(1);
+
+        let delay_ms = delay.as_millis() as u64;
+        let jittered_ms = rand::thread_rng().gen_range(delay_ms / 2, delay_ms + 1);
+        Duration::from_millis(jittered_ms)
+    }
+
+    /// Resets the delay to the minimum one once the call succeeds.
+    pub fn reset(&mut self) {
+        self.retries = 0;
+    }
+
+    /// Returns the delay without the jitter applied.
+    fn current_max_delay(&self) -> Duration {
+        // Doubling is capped to not overflow, the delay is already at the maximum at this point.
+        let factor = 1u32 << self.retries.min(16);
+        self.min_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Circuit breaker rejecting the calls to the failing Ethereum node.
+///
+/// Once the amount of the consecutive failures reaches the threshold, the circuit is opened
+/// and the calls are rejected until the open period passes. After that a single probe call
+/// is allowed per open period: success closes the circuit, and failure keeps it open.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    failure_threshold: usize,
+    open_period: Duration,
+    consecutive_failures: usize,
+    /// Moment until which the calls are rejected, if the circuit is open.
+    open_until: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: usize, open_period: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_period,
+            consecutive_failures: 0,
+            open_until: None,
+        }
+    }
+
+    /// Checks whether the call can be made at the provided moment.
+    pub fn is_call_allowed(&mut self, now: Instant) -> bool {
+        match self.open_until {
+            None => true,
+            Some(open_until) if now < open_until => false,
+            Some(_) => {
+                // Probe call, other calls are rejected until it's done.
+                self.open_until = Some(now + self.open_period);
+                true
+            }
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open_until.is_some()
+    }
+
+    pub fn report_success(&mut self) {
+        if self.is_open() {
+            info!("Ethereum node recovered, closing the circuit breaker");
+        }
+        self.consecutive_failures = 0;
+        self.open_until = None;
+    }
+
+    pub fn report_failure(&mut self, now: Instant) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= self.failure_threshold {
+            if !self.is_open() {
+                warn!(
+                    "Ethereum node failed {} times in a row, rejecting the calls for {}s",
+                    self.consecutive_failures,
+                    self.open_period.as_secs()
+                );
+            }
+            self.open_until = Some(now + self.open_period);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delays() {
+        let min_delay = Duration::from_secs(1);
+        let max_delay = Duration::from_secs(10);
+        let mut backoff = ExponentialBackoff::new(min_delay, max_delay);
+
+        for expected_max in &[1, 2, 4, 8, 10, 10] {
+            let expected_max = Duration::from_secs(*expected_max);
+            let delay = backoff.next_delay();
+            assert!(delay <= expected_max && delay >= expected_max / 2);
+        }
+
+        // Delay doesn't overflow after many retries.
+        for _ in 0..100 {
+            assert!(backoff.next_delay() <= max_delay);
+        }
+
+        backoff.reset();
+        assert!(backoff.next_delay() <= min_delay);
+    }
+
+    #[test]
+    fn circuit_breaker() {
+        let open_period = Duration::from_secs(30);
+        let mut breaker = CircuitBreaker::new(2, open_period);
+        let start = Instant::now();
+
+        breaker.report_failure(start);
+        assert!(breaker.is_call_allowed(start));
+        breaker.report_failure(start);
+        assert!(!breaker.is_call_allowed(start));
+
+        // Single probe call is allowed once the open period passes.
+        let probe_time = start + open_period;
+        assert!(breaker.is_call_allowed(probe_time));
+        assert!(!breaker.is_call_allowed(probe_time));
+
+        breaker.report_failure(probe_time);
+        assert!(!breaker.is_call_allowed(probe_time + open_period / 2));
+
+        let probe_time = probe_time + open_period;
+        assert!(breaker.is_call_allowed(probe_time));
+        breaker.report_success();
+        assert!(!breaker.is_open());
+        assert!(breaker.is_call_allowed(probe_time));
+    }
+
+    #[test]
+    fn errors_classification() {
+        let transient = vec![
+            web3::Error::Unreachable,
+            web3::Error::Transport("Unexpected response status code: 429".into()),
+            web3::Error::Rpc(jsonrpc_core::Error::new(ErrorCode::ServerError(
+                LIMIT_EXCEEDED_RPC_CODE,
+            ))),
+        ];
+        for error in transient {
+            assert!(is_transient_error(&error.into()));
+        }
+
+        let fatal = vec![
+            web3::Error::Decoder("Invalid data".into()),
+            web3::Error::Rpc(jsonrpc_core::Error::invalid_params("nonce too low")),
+        ];
+        for error in fatal {
+            assert!(!is_transient_error(&error.into()));
+        }
+
+        let rate_limited = failure::format_err!("Signer responded with 429 Too Many Requests");
+        assert!(is_transient_error(&rate_limited));
+    }
+}
@@ -1,16 +1,43 @@
-use models::node::BlockNumber;
+use chrono::{DateTime, Utc};
+use models::node::{Address, BlockNumber};
 use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc,
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Mutex,
 };
 use storage::ConnectionPool;
+use web3::types::U256;
 
-/// This struct knows current verified block number.
+/// Balance of the operator account reported by the last `eth_sender` check.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorBalance {
+    pub address: Address,
+    pub balance: U256,
+    pub checked_at: DateTime<Utc>,
+}
+
+/// This struct knows current verified block number and whether the block production
+/// is halted.
 /// It's like storage, but in memory.
 /// In the future, more fields might be added.
 #[derive(Debug, Clone)]
 pub struct CurrentZksyncInfo {
     last_verified_block: Arc<AtomicU32>,
+    /// Set by `eth_sender` when the operator account can't pay for the transactions anymore.
+    operator_balance_low: Arc<AtomicBool>,
+    /// Set by `eth_watch` when the contracts may be upgraded to the unknown version.
+    contract_upgrade_pending: Arc<AtomicBool>,
+    /// Set by `eth_watch` once the committed blocks are reverted in the contract,
+    /// the server is restarted to commit them again.
+    blocks_reverted: Arc<AtomicBool>,
+    /// Set once the server is shutting down, so no new block is started.
+    shutting_down: Arc<AtomicBool>,
+    /// Set by `eth_watch` once the contract enters the exodus mode.
+    exodus_mode: Arc<AtomicBool>,
+    /// Set by `eth_watch` while the exodus mode can be triggered soon,
+    /// new transactions are rejected meanwhile.
+    exodus_imminent: Arc<AtomicBool>,
+    /// Set by `eth_sender` on every operator balance check.
+    operator_balance: Arc<Mutex<Option<OperatorBalance>>>,
 }
 
 impl CurrentZksyncInfo {
@@ -31,6 +58,13 @@ impl CurrentZksyncInfo {
 
         Self {
             last_verified_block,
+            operator_balance_low: Arc::new(AtomicBool::new(false)),
+            contract_upgrade_pending: Arc::new(AtomicBool::new(false)),
+            blocks_reverted: Arc::new(AtomicBool::new(false)),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            exodus_mode: Arc::new(AtomicBool::new(false)),
+            exodus_imminent: Arc::new(AtomicBool::new(false)),
+            operator_balance: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -41,4 +75,76 @@ impl CurrentZksyncInfo {
     pub fn set_new_verified_block(&self, new_block: BlockNumber) {
         self.last_verified_block.store(new_block, Ordering::SeqCst);
     }
+
+    /// Block production is halted if either of the halt reasons is set.
+    pub fn is_block_production_halted(&self) -> bool {
+        self.is_operator_balance_low()
+            || self.is_contract_upgrade_pending()
+            || self.are_blocks_reverted()
+            || self.is_shutting_down()
+            || self.is_exodus_mode()
+    }
+
+    pub fn is_operator_balance_low(&self) -> bool {
+        self.operator_balance_low.load(Ordering::SeqCst)
+    }
+
+    pub fn set_operator_balance_low(&self, low: bool) {
+        self.operator_balance_low.store(low, Ordering::SeqCst);
+    }
+
+    pub fn is_contract_upgrade_pending(&self) -> bool {
+        self.contract_upgrade_pending.load(Ordering::SeqCst)
+    }
+
+    pub fn set_contract_upgrade_pending(&self, pending: bool) {
+        self.contract_upgrade_pending
+            .store(pending, Ordering::SeqCst);
+    }
+
+    pub fn are_blocks_reverted(&self) -> bool {
+        self.blocks_reverted.load(Ordering::SeqCst)
+    }
+
+    pub fn set_blocks_reverted(&self, reverted: bool) {
+        self.blocks_reverted.store(reverted, Ordering::SeqCst);
+    }
+
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    pub fn set_shutting_down(&self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_exodus_mode(&self) -> bool {
+        self.exodus_mode.load(Ordering::SeqCst)
+    }
+
+    /// Exodus mode can't be left, so the flag is never unset.
+    pub fn set_exodus_mode(&self) {
+        self.exodus_mode.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_exodus_imminent(&self) -> bool {
+        self.exodus_imminent.load(Ordering::SeqCst)
+    }
+
+    pub fn set_exodus_imminent(&self, imminent: bool) {
+        self.exodus_imminent.store(imminent, Ordering::SeqCst);
+    }
+
+    /// Returns the last known operator balance, `None` if it wasn't checked yet.
+    pub fn operator_balance(&self) -> Option<OperatorBalance> {
+        self.operator_balance.lock().unwrap().clone()
+    }
+
+    pub fn set_operator_balance(&self, address: Address, balance: U256) {
+        *self.operator_balance.lock().unwrap() = Some(OperatorBalance {
+            address,
+            balance,
+            checked_at: Utc::now(),
+        });
+    }
 }
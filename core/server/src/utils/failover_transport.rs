@@ -0,0 +1,265 @@
+//! Transport switching between the multiple Ethereum node endpoints.
+//!
+//! Requests are sent to the active endpoint. Once the endpoint fails to respond (as opposed
+//! to responding with an RPC error), the request is retried on the next endpoints, and the
+//! first one responding becomes active.
+//!
+//! Endpoints are also checked periodically (see `FailoverTransport::run_health_checks`):
+//! the first endpoint in the order of preference which responds and doesn't lag behind the
+//! most up-to-date one becomes active, so the primary endpoint is used again once it recovers.
+//!
+//! Switches are counted, so the users of the transport can reconcile their state with the
+//! new endpoint (e.g. resend the transactions it doesn't know about).
+//!
+//! Once the requests keep failing on all the endpoints, the circuit breaker is opened, and
+//! the requests are rejected without reaching the endpoints until the probe request succeeds.
+
+// Built-in deps
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant};
+// External uses
+use futures::{compat::Future01CompatExt, FutureExt, TryFutureExt};
+use jsonrpc_core::{Call, Value};
+use tokio::time;
+use web3::{
+    transports::{EventLoopHandle, Http},
+    Error, RequestId, Transport, Web3,
+};
+// Workspace uses
+use models::config_options::ConfigurationOptions;
+// Local uses
+use super::backoff::CircuitBreaker;
+
+/// Maximum amount of the parallel requests to every endpoint.
+const MAX_PARALLEL_REQUESTS: usize = 64;
+
+/// Interval between the health checks of the endpoints.
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Maximum amount of blocks the endpoint may lag behind the most up-to-date one
+/// to be considered healthy.
+const MAX_BLOCKS_BEHIND: u64 = 3;
+
+/// Amount of the consecutive requests failed on all the endpoints to open the circuit breaker.
+const CIRCUIT_BREAKER_THRESHOLD: usize = 5;
+
+/// Period during which the requests are rejected once the circuit breaker is opened.
+const CIRCUIT_BREAKER_OPEN_PERIOD: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct FailoverTransport {
+    urls: Arc<Vec<String>>,
+    endpoints: Arc<Vec<Http>>,
+    /// Index of the endpoint the requests are sent to.
+    active: Arc<AtomicUsize>,
+    /// Amount of the active endpoint switches.
+    switches: Arc<AtomicUsize>,
+    circuit_breaker: Arc<Mutex<CircuitBreaker>>,
+}
+
+impl FailoverTransport {
+    /// Creates the transport for the provided endpoints, the first one is preferred.
+    pub fn new(urls: Vec<String>) -> Result<(EventLoopHandle, Self), failure::Error> {
+        failure::ensure!(!urls.is_empty(), "No Ethereum node endpoints provided");
+
+        let (event_loop, endpoints) = EventLoopHandle::spawn(|handle| {
+            urls.iter()
+                .map(|url| Http::with_event_loop(url, handle, MAX_PARALLEL_REQUESTS))
+                .collect::<Result<Vec<_>, _>>()
+        })?;
+
+        let transport = Self {
+            urls: Arc::new(urls),
+            endpoints: Arc::new(endpoints),
+            active: Arc::new(AtomicUsize::new(0)),
+            switches: Arc::new(AtomicUsize::new(0)),
+            circuit_breaker: Arc::new(Mutex::new(CircuitBreaker::new(
+                CIRCUIT_BREAKER_THRESHOLD,
+                CIRCUIT_BREAKER_OPEN_PERIOD,
+            ))),
+        };
+        Ok((event_loop, transport))
+    }
+
+    /// Creates the transport for `WEB3_URL` along with the `WEB3_FALLBACK_URLS`.
+    pub fn from_config(
+        options: &ConfigurationOptions,
+    ) -> Result<(EventLoopHandle, Self), failure::Error> {
+        let urls = std::iter::once(options.web3_url.clone())
+            .chain(options.web3_fallback_urls.iter().cloned())
+            .collect();
+        Self::new(urls)
+    }
+
+    /// Returns the amount of the active endpoint switches since the transport creation.
+    pub fn switches(&self) -> usize {
+        self.switches.load(Ordering::SeqCst)
+    }
+
+    fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Makes the provided endpoint active, unless the active one was already changed
+    /// since the `current` one was observed.
+    fn switch(&self, current: usize, new: usize) {
+        let switched = self
+            .active
+            .compare_exchange(current, new, Ordering::SeqCst, Ordering::SeqCst)
+            .is_ok();
+        if current == new || !switched {
+            return;
+        }
+
+        warn!(
+            "Switched the Ethereum node endpoint from {} to {}",
+            self.urls[current], self.urls[new]
+        );
+        self.switches.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Checks the last block of every endpoint, and makes the first healthy one active.
+    pub async fn check_health(&self) {
+        let mut last_blocks = Vec::with_capacity(self.endpoints.len());
+        for (endpoint, url) in self.endpoints.iter().zip(self.urls.iter()) {
+            let last_block = Web3::new(endpoint.clone())
+                .eth()
+                .block_number()
+                .compat()
+                .await;
+            match last_block {
+                Ok(last_block) => last_blocks.push(Some(last_block.as_u64())),
+                Err(e) => {
+                    warn!("Ethereum node endpoint {} is not available: {}", url, e);
+                    last_blocks.push(None);
+                }
+            }
+        }
+
+        let best_block = match last_blocks.iter().flatten().max() {
+            Some(best_block) => *best_block,
+            None => {
+                error!("None of the Ethereum node endpoints is available");
+                return;
+            }
+        };
+        let healthy = last_blocks
+            .iter()
+            .position(|last_block| {
+                last_block.map_or(false, |last_block| {
+                    last_block + MAX_BLOCKS_BEHIND >= best_block
+                })
+            })
+            .expect("Endpoint with the best block is healthy");
+
+        self.switch(self.active(), healthy);
+    }
+
+    /// Checks the endpoints health periodically. Does nothing if there is a single endpoint.
+    pub async fn run_health_checks(self) {
+        if self.endpoints.len() < 2 {
+            return;
+        }
+
+        let mut timer = time::interval(HEALTH_CHECK_INTERVAL);
+        loop {
+            timer.tick().await;
+            self.check_health().await;
+        }
+    }
+}
+
+/// Checks whether the error is caused by the endpoint failure rather than by the request.
+fn is_endpoint_failure(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::Unreachable | Error::Transport(_) | Error::Io(_) | Error::InvalidResponse(_)
+    )
+}
+
+impl Transport for FailoverTransport {
+    type Out = Box<dyn futures01::Future<Item = Value, Error = Error> + Send>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.endpoints[self.active()].prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let transport = self.clone();
+        let response = async move {
+            let call_allowed = transport
+                .circuit_breaker
+                .lock()
+                .unwrap()
+                .is_call_allowed(Instant::now());
+            if !call_allowed {
+                return Err(Error::Transport(
+                    "Circuit breaker is open, Ethereum node endpoints are failing".into(),
+                ));
+            }
+
+            let endpoints_count = transport.endpoints.len();
+            let first = transport.active();
+            let mut last_error = None;
+            for attempt in 0..endpoints_count {
+                let current = (first + attempt) % endpoints_count;
+                let response = transport.endpoints[current]
+                    .send(id, request.clone())
+                    .compat()
+                    .await;
+                match response {
+                    Err(e) if is_endpoint_failure(&e) => {
+                        warn!(
+                            "Request to the Ethereum node endpoint {} failed: {}",
+                            transport.urls[current], e
+                        );
+                        transport.switch(current, (current + 1) % endpoints_count);
+                        last_error = Some(e);
+                    }
+                    response => {
+                        transport.circuit_breaker.lock().unwrap().report_success();
+                        return response;
+                    }
+                }
+            }
+            transport
+                .circuit_breaker
+                .lock()
+                .unwrap()
+                .report_failure(Instant::now());
+            Err(last_error.expect("At least one endpoint is requested"))
+        };
+
+        Box::new(response.boxed().compat())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the switch is ignored if the active endpoint was already changed.
+    #[test]
+    fn endpoint_switch() {
+        let urls = vec![
+            "http://localhost:8545".into(),
+            "http://localhost:8546".into(),
+        ];
+        let (_event_loop, transport) = FailoverTransport::new(urls).unwrap();
+
+        transport.switch(0, 1);
+        assert_eq!(transport.active(), 1);
+        assert_eq!(transport.switches(), 1);
+
+        // Another request observed the failure of the same endpoint.
+        transport.switch(0, 1);
+        assert_eq!(transport.switches(), 1);
+
+        transport.switch(1, 0);
+        assert_eq!(transport.active(), 0);
+        assert_eq!(transport.switches(), 2);
+    }
+}
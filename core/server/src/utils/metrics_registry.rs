@@ -0,0 +1,119 @@
+//! Registry of the metrics reported by the server actors.
+//!
+//! Unlike the metrics loaded from the database upon the export, these metrics are
+//! collected in memory by the actors themselves (e.g. the state keeper), and are
+//! rendered by the `prometheus_exporter` along with the database ones.
+
+// Built-in deps
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+// External uses
+use lazy_static::lazy_static;
+use prometheus_exporter_base::{MetricType, PrometheusMetric};
+
+lazy_static! {
+    static ref METRICS_REGISTRY: MetricsRegistry = MetricsRegistry::default();
+}
+
+/// Returns the metrics registry shared by all the server actors.
+pub fn metrics_registry() -> &'static MetricsRegistry {
+    &METRICS_REGISTRY
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MetricKind {
+    Counter,
+    Gauge,
+}
+
+#[derive(Debug)]
+struct Metric {
+    kind: MetricKind,
+    help: &'static str,
+    value: f64,
+}
+
+/// Collection of the named metrics. Metric is registered upon its first update.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    metrics: Mutex<BTreeMap<&'static str, Metric>>,
+}
+
+impl MetricsRegistry {
+    /// Increases the value of the counter.
+    pub fn increment_counter(&self, name: &'static str, help: &'static str, value: f64) {
+        self.update(name, help, MetricKind::Counter, |current| *current += value);
+    }
+
+    /// Sets the current value of the gauge.
+    pub fn set_gauge(&self, name: &'static str, help: &'static str, value: f64) {
+        self.update(name, help, MetricKind::Gauge, |current| *current = value);
+    }
+
+    /// Returns the current value of the metric, if it was reported.
+    pub fn value(&self, name: &str) -> Option<f64> {
+        let metrics = self.metrics.lock().expect("metrics registry lock poisoned");
+        metrics.get(name).map(|metric| metric.value)
+    }
+
+    /// Renders all the reported metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let metrics = self.metrics.lock().expect("metrics registry lock poisoned");
+
+        let mut s = String::new();
+        for (name, metric) in metrics.iter() {
+            let metric_type = match metric.kind {
+                MetricKind::Counter => MetricType::Counter,
+                MetricKind::Gauge => MetricType::Gauge,
+            };
+            let pc = PrometheusMetric::new(name, metric_type, metric.help);
+            s.push_str(&pc.render_header());
+            s.push_str(&pc.render_sample(None, metric.value, None));
+        }
+        s
+    }
+
+    fn update(
+        &self,
+        name: &'static str,
+        help: &'static str,
+        kind: MetricKind,
+        update_fn: impl FnOnce(&mut f64),
+    ) {
+        let mut metrics = self.metrics.lock().expect("metrics registry lock poisoned");
+        let metric = metrics.entry(name).or_insert(Metric {
+            kind,
+            help,
+            value: 0.0,
+        });
+        assert_eq!(
+            metric.kind, kind,
+            "Metric {} is already reported with another type",
+            name
+        );
+        update_fn(&mut metric.value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_and_gauges() {
+        let registry = MetricsRegistry::default();
+        assert!(registry.value("ops").is_none());
+
+        registry.increment_counter("ops", "Executed operations", 2.0);
+        registry.increment_counter("ops", "Executed operations", 3.0);
+        registry.set_gauge("queue", "Queue depth", 10.0);
+        registry.set_gauge("queue", "Queue depth", 4.0);
+
+        assert_eq!(registry.value("ops"), Some(5.0));
+        assert_eq!(registry.value("queue"), Some(4.0));
+
+        let rendered = registry.render();
+        assert!(rendered.contains("# TYPE ops counter"));
+        assert!(rendered.contains("# TYPE queue gauge"));
+    }
+}
@@ -1,4 +1,7 @@
+pub mod backoff;
 pub mod current_zksync_info;
+pub mod failover_transport;
 pub mod metrics_counter;
+pub mod metrics_registry;
 pub mod shared_lru_cache;
 pub mod token_db_cache;
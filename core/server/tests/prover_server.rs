@@ -7,7 +7,7 @@ use futures::channel::mpsc;
 use circuit::witness::{deposit::DepositWitness, Witness};
 use models::{
     circuit::CircuitAccountTree,
-    config_options::ConfigurationOptions,
+    config_options::{ConfigurationOptions, ProverJobPolicy, ProverOptions},
     node::{block::Block, Address},
     params::{account_tree_depth, total_tokens},
     prover_utils::EncodedProofPlonk,
@@ -25,17 +25,21 @@ fn spawn_server(prover_timeout: time::Duration, rounds_interval: time::Duration)
     let addr = net::SocketAddr::from_str(bind_to).unwrap();
     let (tx, _rx) = mpsc::channel(1);
     let tree = CircuitAccountTree::new(account_tree_depth());
+    let prover_options = ProverOptions {
+        prepare_data_interval: rounds_interval,
+        heartbeat_interval: time::Duration::from_secs(1),
+        cycle_wait: time::Duration::from_millis(500),
+        gone_timeout: prover_timeout,
+        verify_proofs: false,
+        job_policy: ProverJobPolicy::Fifo,
+        api_auth: false,
+        mock_proofs: false,
+        circuit_version: "test".to_string(),
+        witness_threads: 0,
+        max_failure_rate: 0.3,
+    };
     thread::spawn(move || {
-        prover_server::start_prover_server(
-            conn_pool,
-            addr,
-            prover_timeout,
-            rounds_interval,
-            tx,
-            tree,
-            0,
-            0,
-        );
+        prover_server::start_prover_server(conn_pool, addr, tx, tree, 0, 0, prover_options);
     });
     bind_to.to_string()
 }
@@ -53,6 +57,8 @@ fn client_with_empty_worker_name_panics() {
         &"http:://example.com".parse().unwrap(),
         "",
         Duration::from_secs(1),
+        None,
+        None,
     );
 }
 
@@ -65,15 +71,22 @@ fn api_client_register_start_and_stop_of_prover() {
         &format!("http://{}", &addr).parse().unwrap(),
         "foo",
         Duration::from_secs(1),
+        None,
+        None,
     );
     let id = client
-        .register_prover(block_size_chunks)
+        .register_prover(block_size_chunks, Default::default())
         .expect("failed to register");
     let storage = access_storage();
     storage
         .prover_schema()
         .prover_by_id(id)
         .expect("failed to select registered prover");
+    storage
+        .prover_schema()
+        .prover_registry_entry("foo")
+        .expect("failed to load prover registry")
+        .expect("registered prover is not in the registry");
     client.prover_stopped(id).expect("unexpected error");
     let prover = storage
         .prover_schema()
@@ -95,6 +108,8 @@ fn api_client_simple_simulation() {
         &format!("http://{}", &addr).parse().unwrap(),
         "foo",
         time::Duration::from_secs(1),
+        None,
+        None,
     );
 
     // call block_to_prove and check its none
@@ -141,7 +156,7 @@ fn api_client_simple_simulation() {
     let (block, job) = to_prove.unwrap();
     // sleep for prover_timeout and send heartbeat
     thread::sleep(prover_timeout * 2);
-    client.working_on(job).unwrap();
+    assert!(client.working_on(job).unwrap());
 
     let to_prove = client
         .block_to_prove(block_size_chunks)
@@ -233,6 +248,7 @@ pub fn test_operation_and_wanted_prover_data(
             },
             block_index: 0,
             created_at: chrono::Utc::now(),
+            invalid_reason: None,
         },
     )));
 
@@ -248,6 +264,7 @@ pub fn test_operation_and_wanted_prover_data(
         &ConfigurationOptions::from_env().available_block_chunk_sizes,
         1_000_000.into(),
         1_500_000.into(),
+        None,
     );
 
     let mut pub_data = vec![];
@@ -338,6 +355,8 @@ fn api_server_publish_dummy() {
         .json(&client::PublishReq {
             block: 1,
             proof: EncodedProofPlonk::default(),
+            worker: None,
+            timings: None,
         })
         .send()
         .expect("failed to send publish request");
@@ -0,0 +1,2 @@
+ALTER TABLE blocks DROP COLUMN created_at;
+ALTER TABLE blocks DROP COLUMN chunks_used;
@@ -0,0 +1,2 @@
+ALTER TABLE blocks ADD COLUMN chunks_used BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE blocks ADD COLUMN created_at TIMESTAMP with time zone NOT NULL DEFAULT NOW();
@@ -0,0 +1,11 @@
+-- Public data of the block, compressed with zstd.
+CREATE TABLE block_public_data (
+    block_number BIGINT PRIMARY KEY,
+    data BYTEA NOT NULL
+);
+
+-- Serialized prover witness for the block, compressed with zstd.
+CREATE TABLE block_witness (
+    block_number BIGINT PRIMARY KEY,
+    witness BYTEA NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE eth_operations ADD COLUMN gas_used BIGINT;
@@ -0,0 +1,7 @@
+-- Priority operations observed on Ethereum, but not executed yet.
+CREATE TABLE eth_priority_queue (
+    serial_id BIGINT PRIMARY KEY,
+    eth_block BIGINT NOT NULL,
+    operation JSONB NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1 @@
+ALTER TABLE blocks ADD COLUMN collected_fees JSONB NOT NULL DEFAULT '{}';
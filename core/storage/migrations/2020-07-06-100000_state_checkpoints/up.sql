@@ -0,0 +1,5 @@
+-- Checkpoint of the committed state: serialized list of accounts, compressed with zstd.
+CREATE TABLE state_checkpoints (
+    block_number BIGINT PRIMARY KEY,
+    accounts BYTEA NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS denied_txs;
+DROP TABLE IF EXISTS address_denylist;
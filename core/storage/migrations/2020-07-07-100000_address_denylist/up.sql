@@ -0,0 +1,14 @@
+-- Addresses which are not allowed to send or receive transactions.
+CREATE TABLE address_denylist (
+    address BYTEA PRIMARY KEY,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
+
+-- Audit log of the transactions rejected because of the denylisted addresses.
+CREATE TABLE denied_txs (
+    id BIGSERIAL PRIMARY KEY,
+    tx_hash BYTEA NOT NULL,
+    address BYTEA NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
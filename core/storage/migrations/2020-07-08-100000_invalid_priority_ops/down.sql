@@ -0,0 +1 @@
+ALTER TABLE executed_priority_operations DROP COLUMN invalid_reason;
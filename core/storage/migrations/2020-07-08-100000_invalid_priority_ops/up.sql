@@ -0,0 +1 @@
+ALTER TABLE executed_priority_operations ADD COLUMN invalid_reason TEXT;
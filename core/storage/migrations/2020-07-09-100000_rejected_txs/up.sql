@@ -0,0 +1,11 @@
+-- Transactions rejected upon submission, along with the rejection reasons.
+-- Only the latest records are kept, older ones are removed by the server.
+CREATE TABLE rejected_txs (
+    id BIGSERIAL PRIMARY KEY,
+    tx_hash BYTEA NOT NULL,
+    sender BYTEA NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX rejected_txs_sender_index ON rejected_txs (sender);
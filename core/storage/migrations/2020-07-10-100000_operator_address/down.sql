@@ -0,0 +1 @@
+ALTER TABLE eth_parameters DROP COLUMN operator_address;
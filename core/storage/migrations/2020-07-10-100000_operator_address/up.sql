@@ -0,0 +1,3 @@
+-- Address of the operator account currently used to send the Ethereum transactions.
+-- Not set until the server starts with the multiple operator keys support.
+ALTER TABLE eth_parameters ADD COLUMN operator_address BYTEA;
@@ -0,0 +1 @@
+ALTER TABLE eth_tx_hashes DROP COLUMN signed_raw_tx;
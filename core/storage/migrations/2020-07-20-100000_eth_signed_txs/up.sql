@@ -0,0 +1,3 @@
+-- Signed Ethereum transactions, so they can be sent again after the restart.
+-- Not set for the transactions sent before the signed transactions were stored.
+ALTER TABLE eth_tx_hashes ADD COLUMN signed_raw_tx BYTEA;
@@ -0,0 +1,12 @@
+-- Withdrawals added to the pending withdrawals queue of the contract once their blocks
+-- are verified, in the order of the queue. `withdrawal_hash` is the hash of the withdraw
+-- transaction, or the hash of the Ethereum transaction for the full exit.
+-- `complete_tx_hash` is the hash of the `completeWithdrawals` transaction that completed
+-- the withdrawal.
+CREATE TABLE pending_withdrawals (
+    id BIGSERIAL PRIMARY KEY,
+    withdrawal_hash BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    complete_tx_hash BYTEA
+);
+CREATE INDEX pending_withdrawals_hash_index ON pending_withdrawals (withdrawal_hash);
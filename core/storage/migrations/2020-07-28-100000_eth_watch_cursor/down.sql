@@ -0,0 +1 @@
+ALTER TABLE eth_parameters DROP COLUMN last_processed_eth_block;
@@ -0,0 +1,3 @@
+-- Last Ethereum block which events were processed by the Ethereum watcher,
+-- so the events emitted while the server was down are scanned after the restart.
+ALTER TABLE eth_parameters ADD COLUMN last_processed_eth_block BIGINT;
@@ -0,0 +1 @@
+ALTER TABLE prover_runs DROP COLUMN lease_expires_at;
@@ -0,0 +1,3 @@
+-- Moment until which the prover job is leased to the worker. Once the lease expires
+-- without being renewed, the block is assigned to another prover.
+ALTER TABLE prover_runs ADD COLUMN lease_expires_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
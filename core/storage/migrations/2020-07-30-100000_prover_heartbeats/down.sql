@@ -0,0 +1 @@
+ALTER TABLE active_provers DROP COLUMN last_heartbeat_at;
@@ -0,0 +1,3 @@
+-- Last heartbeat of the prover. Provers which are silent for too long are considered dead,
+-- and the blocks leased to them are reassigned.
+ALTER TABLE active_provers ADD COLUMN last_heartbeat_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now();
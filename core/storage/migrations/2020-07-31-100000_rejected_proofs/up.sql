@@ -0,0 +1,10 @@
+-- Proofs received from the provers which didn't pass the verification, along with the reasons.
+CREATE TABLE rejected_proofs (
+    id BIGSERIAL PRIMARY KEY,
+    block_number BIGINT NOT NULL,
+    proof JSONB NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX rejected_proofs_block_number_index ON rejected_proofs (block_number);
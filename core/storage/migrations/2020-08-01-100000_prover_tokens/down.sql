@@ -0,0 +1 @@
+DROP TABLE IF EXISTS prover_tokens;
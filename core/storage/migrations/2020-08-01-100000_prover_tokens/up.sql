@@ -0,0 +1,9 @@
+-- Tokens used by the provers to authenticate in the prover API.
+-- Only the hashes of the tokens are stored.
+CREATE TABLE prover_tokens (
+    id BIGSERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    token_hash BYTEA NOT NULL UNIQUE,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now(),
+    revoked_at TIMESTAMP with time zone
+);
@@ -0,0 +1,12 @@
+-- Time spent by the provers on the stages of the block proofs computation.
+CREATE TABLE proof_timings (
+    block_number BIGINT PRIMARY KEY,
+    worker TEXT NOT NULL,
+    witness_load_ms BIGINT NOT NULL,
+    synthesis_ms BIGINT NOT NULL,
+    proving_ms BIGINT NOT NULL,
+    total_ms BIGINT NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX proof_timings_created_at_index ON proof_timings (created_at);
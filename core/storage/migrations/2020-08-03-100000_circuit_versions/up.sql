@@ -0,0 +1,9 @@
+-- Versions of the circuit used by the server, along with the first block proposed under each one.
+-- Block is proved with the latest version recorded before or at its number.
+CREATE TABLE circuit_versions (
+    version TEXT PRIMARY KEY,
+    first_block BIGINT NOT NULL,
+    created_at TIMESTAMP with time zone NOT NULL DEFAULT now()
+);
+
+CREATE INDEX circuit_versions_first_block_index ON circuit_versions (first_block);
@@ -0,0 +1 @@
+ALTER TABLE active_provers DROP COLUMN IF EXISTS drain_requested_at;
@@ -0,0 +1,3 @@
+-- Moment when the operator requested the prover to finish its current job and stop.
+-- Draining provers get no new jobs.
+ALTER TABLE active_provers ADD COLUMN drain_requested_at TIMESTAMP with time zone;
@@ -0,0 +1,12 @@
+-- Provers which have ever registered, along with the outcomes of their jobs.
+-- Unlike `active_provers`, there is a single row per worker name, kept across the restarts.
+CREATE TABLE prover_registry (
+    worker TEXT NOT NULL PRIMARY KEY,
+    circuit_version TEXT,
+    capabilities jsonb NOT NULL DEFAULT '{}',
+    first_registered_at TIMESTAMP with time zone NOT NULL DEFAULT now(),
+    last_registered_at TIMESTAMP with time zone NOT NULL DEFAULT now(),
+    completed_jobs BIGINT NOT NULL DEFAULT 0,
+    failed_jobs BIGINT NOT NULL DEFAULT 0,
+    timed_out_jobs BIGINT NOT NULL DEFAULT 0
+);
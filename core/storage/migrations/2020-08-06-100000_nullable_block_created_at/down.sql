@@ -0,0 +1,3 @@
+UPDATE blocks SET created_at = now() WHERE created_at IS NULL;
+ALTER TABLE blocks ALTER COLUMN created_at SET NOT NULL;
+ALTER TABLE blocks ALTER COLUMN created_at SET DEFAULT now();
@@ -0,0 +1,3 @@
+-- Seal time of the blocks restored from Ethereum is unknown, since it's not published there.
+ALTER TABLE blocks ALTER COLUMN created_at DROP DEFAULT;
+ALTER TABLE blocks ALTER COLUMN created_at DROP NOT NULL;
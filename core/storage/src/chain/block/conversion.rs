@@ -96,6 +96,7 @@ impl StoredExecutedPriorityOperation {
             op: franklin_op,
             block_index: self.block_index as u32,
             created_at: DateTime::<Utc>::from_utc(self.created_at, Utc),
+            invalid_reason: self.invalid_reason,
         }
     }
 }
@@ -130,6 +131,7 @@ impl NewExecutedPriorityOperation {
             eth_hash: exec_prior_op.priority_op.eth_hash,
             eth_block: exec_prior_op.priority_op.eth_block as i64,
             created_at: exec_prior_op.created_at,
+            invalid_reason: exec_prior_op.invalid_reason,
         }
     }
 }
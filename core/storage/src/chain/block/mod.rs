@@ -1,5 +1,6 @@
 // Built-in deps
 // External imports
+use chrono::{TimeZone, Utc};
 use diesel::dsl::max;
 use diesel::prelude::*;
 use diesel::result::Error as DieselError;
@@ -7,15 +8,19 @@ use web3::types::U256;
 // Workspace imports
 use models::node::{
     block::{Block, ExecutedOperations},
-    AccountId, BlockNumber, FranklinOp,
+    AccountId, BlockNumber, Fr, FranklinOp, PriorityOp, SignedFranklinTx,
 };
 use models::{
     fe_from_bytes, fe_to_bytes, node::block::PendingBlock, Action, ActionType, Operation,
 };
 // Local imports
-use self::records::{BlockDetails, BlockTransactionItem, StorageBlock, StoragePendingBlock};
+use self::records::{
+    BlockDetails, BlockTransactionItem, ChainLagStats, StorageBlock, StorageBlockPublicData,
+    StoragePendingBlock,
+};
 use crate::{
     chain::{
+        mempool::MempoolSchema,
         operations::{
             records::{
                 NewExecutedPriorityOperation, NewExecutedTransaction, NewOperation,
@@ -25,7 +30,10 @@ use crate::{
         },
         state::StateSchema,
     },
-    ethereum::records::ETHBinding,
+    compression::{compress, decompress_stored},
+    ethereum::{records::ETHBinding, EthereumSchema},
+    notifications::{NotificationsSchema, OperationNotification},
+    pagination::SearchDirection,
     prover::{records::StoredProof, ProverSchema},
     schema::*,
     StorageProcessor,
@@ -55,6 +63,10 @@ impl<'a> BlockSchema<'a> {
                 Action::Commit => {
                     StateSchema(self.0).commit_state_update(block_number, &op.accounts_updated)?;
                     self.save_block(op.block)?;
+                    NotificationsSchema(self.0).notify_operation(OperationNotification::new(
+                        block_number,
+                        ActionType::COMMIT,
+                    ))?;
                 }
                 Action::Verify { proof } => {
                     let stored_proof = ProverSchema(self.0).load_proof(block_number);
@@ -138,6 +150,9 @@ impl<'a> BlockSchema<'a> {
             stored_block.block_size as usize,
             U256::from(stored_block.commit_gas_limit as u64),
             U256::from(stored_block.verify_gas_limit as u64),
+            stored_block
+                .created_at
+                .map(|created_at| created_at.timestamp() as u64),
         )))
     }
 
@@ -257,6 +272,10 @@ impl<'a> BlockSchema<'a> {
         // - joins the `blocks` table with result of the join twice: once for committed operations
         //   and verified operations;
         // - collects the {limit} blocks in the descending order with the data gathered above.
+        //
+        // Blocks are paginated by their numbers: the page contains the blocks older
+        // than the one following `max_block`.
+        let direction = SearchDirection::Older;
         let query = format!(
             " \
             with eth_ops as ( \
@@ -274,7 +293,11 @@ impl<'a> BlockSchema<'a> {
             select \
                 blocks.number as block_number, \
                 blocks.root_hash as new_state_root, \
+                blocks.fee_account_id as fee_account_id, \
                 blocks.block_size as block_size, \
+                blocks.chunks_used as chunks_used, \
+                blocks.collected_fees as collected_fees, \
+                blocks.created_at as created_at, \
                 committed.tx_hash as commit_tx_hash, \
                 verified.tx_hash as verify_tx_hash, \
                 committed.created_at as committed_at, \
@@ -285,11 +308,12 @@ impl<'a> BlockSchema<'a> {
             left join eth_ops verified on \
                 verified.block_number = blocks.number and verified.action_type = 'VERIFY' and verified.confirmed = true \
             where \
-                blocks.number <= {max_block} \
-            order by blocks.number desc \
+                {blocks_filter} \
+            order by blocks.number {order} \
             limit {limit}; \
             ",
-            max_block = i64::from(max_block),
+            blocks_filter = direction.sql_filter("blocks.number", u64::from(max_block) + 1),
+            order = direction.sql_order(),
             limit = i64::from(limit)
         );
         diesel::sql_query(query).load(self.0.conn())
@@ -378,7 +402,11 @@ impl<'a> BlockSchema<'a> {
             select \
                 blocks.number as block_number, \
                 blocks.root_hash as new_state_root, \
+                blocks.fee_account_id as fee_account_id, \
                 blocks.block_size as block_size, \
+                blocks.chunks_used as chunks_used, \
+                blocks.collected_fees as collected_fees, \
+                blocks.created_at as created_at, \
                 committed.tx_hash as commit_tx_hash, \
                 verified.tx_hash as verify_tx_hash, \
                 committed.created_at as committed_at, \
@@ -459,6 +487,17 @@ impl<'a> BlockSchema<'a> {
             .map(|max| max.unwrap_or(0) as BlockNumber)
     }
 
+    /// Returns the last block which commit is confirmed on Ethereum.
+    pub fn get_last_confirmed_committed_block(&self) -> QueryResult<BlockNumber> {
+        use crate::schema::operations::dsl::*;
+        operations
+            .filter(action_type.eq(&ActionType::COMMIT.to_string()))
+            .filter(confirmed.eq(true))
+            .select(max(block_number))
+            .get_result::<Option<i64>>(self.0.conn())
+            .map(|max| max.unwrap_or(0) as BlockNumber)
+    }
+
     pub fn get_last_verified_block(&self) -> QueryResult<BlockNumber> {
         use crate::schema::operations::dsl::*;
         operations
@@ -468,6 +507,31 @@ impl<'a> BlockSchema<'a> {
             .map(|max| max.unwrap_or(0) as BlockNumber)
     }
 
+    /// Returns how far the confirmation of the blocks on Ethereum lags behind the server.
+    pub fn load_chain_lag_stats(&self) -> QueryResult<ChainLagStats> {
+        diesel::sql_query(
+            " \
+            WITH confirmed AS ( \
+                SELECT \
+                    (SELECT COALESCE(max(block_number),0) FROM operations \
+                        WHERE action_type = 'COMMIT' AND confirmed) AS commit_block, \
+                    (SELECT COALESCE(max(block_number),0) FROM operations \
+                        WHERE action_type = 'VERIFY' AND confirmed) AS verify_block \
+            ) \
+            SELECT \
+                (SELECT COALESCE(max(block_number),0) FROM operations \
+                    WHERE action_type = 'COMMIT') AS last_committed_block, \
+                confirmed.commit_block AS last_confirmed_commit_block, \
+                confirmed.verify_block AS last_confirmed_verify_block, \
+                (SELECT EXTRACT(EPOCH FROM now() - min(created_at))::BIGINT FROM blocks \
+                    WHERE number > confirmed.commit_block) AS oldest_unconfirmed_commit_age_secs, \
+                (SELECT EXTRACT(EPOCH FROM now() - min(created_at))::BIGINT FROM blocks \
+                    WHERE number > confirmed.verify_block) AS oldest_unconfirmed_verify_age_secs \
+            FROM confirmed",
+        )
+        .get_result(self.0.conn())
+    }
+
     pub fn load_pending_block(&self) -> QueryResult<Option<PendingBlock>> {
         use crate::schema::pending_block::dsl::*;
         self.0.conn().transaction(|| {
@@ -571,6 +635,15 @@ impl<'a> BlockSchema<'a> {
             let block_size = block.block_chunks_size as i64;
             let commit_gas_limit = block.commit_gas_limit.as_u64() as i64;
             let verify_gas_limit = block.verify_gas_limit.as_u64() as i64;
+            let chunks_used = block.chunks_used() as i64;
+            let collected_fees = collected_fees_to_json(&block);
+            let created_at = block
+                .timestamp
+                .map(|timestamp| Utc.timestamp(timestamp as i64, 0));
+            let public_data = StorageBlockPublicData {
+                block_number: number,
+                data: compress(&block.get_eth_public_data()),
+            };
 
             self.save_block_transactions(block.block_number, block.block_transactions)?;
 
@@ -583,6 +656,9 @@ impl<'a> BlockSchema<'a> {
                 block_size,
                 commit_gas_limit,
                 verify_gas_limit,
+                chunks_used,
+                created_at,
+                collected_fees,
             };
 
             // Remove pending block (as it's now completed).
@@ -594,7 +670,213 @@ impl<'a> BlockSchema<'a> {
                 .values(&new_block)
                 .execute(self.0.conn())?;
 
+            diesel::insert_into(block_public_data::table)
+                .values(&public_data)
+                .execute(self.0.conn())?;
+
             Ok(())
         })
     }
+
+    /// Loads the public data of the block, as it is sent to the Ethereum contract.
+    /// Returns `None` if there is no public data stored for the block.
+    pub fn get_block_public_data(&self, block: BlockNumber) -> QueryResult<Option<Vec<u8>>> {
+        let stored: Option<StorageBlockPublicData> = block_public_data::table
+            .filter(block_public_data::block_number.eq(i64::from(block)))
+            .first(self.0.conn())
+            .optional()?;
+
+        stored
+            .map(|stored| decompress_stored(&stored.data))
+            .transpose()
+    }
+
+    /// Loads the root hash of the committed block without loading its operations.
+    /// Returns `None` if the block with provided number does not exist yet.
+    pub fn get_block_root_hash(&self, block: BlockNumber) -> QueryResult<Option<Fr>> {
+        let root_hash: Option<Vec<u8>> = blocks::table
+            .find(i64::from(block))
+            .select(blocks::root_hash)
+            .first(self.0.conn())
+            .optional()?;
+
+        Ok(root_hash.map(|root_hash| fe_from_bytes(&root_hash).expect("Unparsable root hash")))
+    }
+
+    /// Removes the blocks after the provided one (along with the pending block), so they're
+    /// proposed and committed again, e.g. after the blocks were reverted in the contract.
+    /// Returns the amount of removed blocks.
+    ///
+    /// Successful transactions of the removed blocks are returned to the mempool, and the
+    /// executed priority operations are returned to the priority queue. Prover jobs of the
+    /// removed blocks are cancelled (see `ProverSchema::cancel_reverted_blocks_jobs`).
+    /// Unconfirmed Ethereum operations of the removed blocks are removed as well, and the nonce
+    /// is moved back to the lowest of their nonces, so the new operations replace them.
+    ///
+    /// Blocks can't be removed if they're verified, since the verified state is already
+    /// applied, or if there are unconfirmed Ethereum operations after the removed ones
+    /// (e.g. withdrawals completion), since their nonces would be reused.
+    pub fn rollback_blocks(&self, last_block: BlockNumber) -> Result<usize, failure::Error> {
+        self.0.conn().transaction(|| {
+            let last_verified_block = self.get_last_verified_block()?;
+            failure::ensure!(
+                last_verified_block <= last_block,
+                "Can't roll back to the block {}, block {} is already verified",
+                last_block,
+                last_verified_block
+            );
+            let last_block = i64::from(last_block);
+
+            let removed_op_ids: Vec<i64> = operations::table
+                .filter(operations::block_number.gt(last_block))
+                .select(operations::id)
+                .load(self.0.conn())?;
+            let unconfirmed_eth_ops: Vec<(i64, i64)> = eth_ops_binding::table
+                .inner_join(
+                    eth_operations::table.on(eth_operations::id.eq(eth_ops_binding::eth_op_id)),
+                )
+                .filter(eth_ops_binding::op_id.eq_any(&removed_op_ids))
+                .filter(eth_operations::confirmed.eq(false))
+                .select((eth_operations::id, eth_operations::nonce))
+                .load(self.0.conn())?;
+            let unconfirmed_eth_op_ids: Vec<i64> =
+                unconfirmed_eth_ops.iter().map(|(id, _)| *id).collect();
+            let lowest_nonce = unconfirmed_eth_ops.iter().map(|(_, nonce)| *nonce).min();
+            if let Some(lowest_nonce) = lowest_nonce {
+                let later_eth_ops: i64 = eth_operations::table
+                    .filter(eth_operations::confirmed.eq(false))
+                    .filter(eth_operations::nonce.gt(lowest_nonce))
+                    .filter(diesel::dsl::not(
+                        eth_operations::id.eq_any(&unconfirmed_eth_op_ids),
+                    ))
+                    .count()
+                    .get_result(self.0.conn())?;
+                failure::ensure!(
+                    later_eth_ops == 0,
+                    "Can't roll back to the block {}, {} unconfirmed Ethereum operations \
+                     are sent after the operations of the removed blocks",
+                    last_block,
+                    later_eth_ops
+                );
+            }
+
+            // Confirmed Ethereum operations are kept in the history, only the bindings
+            // to the removed operations are removed.
+            diesel::delete(
+                eth_ops_binding::table.filter(eth_ops_binding::op_id.eq_any(&removed_op_ids)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                eth_tx_hashes::table
+                    .filter(eth_tx_hashes::eth_op_id.eq_any(&unconfirmed_eth_op_ids)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                eth_operations::table.filter(eth_operations::id.eq_any(&unconfirmed_eth_op_ids)),
+            )
+            .execute(self.0.conn())?;
+            if let Some(lowest_nonce) = lowest_nonce {
+                diesel::update(eth_parameters::table.filter(eth_parameters::id.eq(true)))
+                    .set(eth_parameters::nonce.eq(lowest_nonce))
+                    .execute(self.0.conn())?;
+            }
+
+            // Transactions are returned to the mempool in the order of execution.
+            // Failed transactions are not returned, since they were already rejected.
+            let executed_txs: Vec<StoredExecutedTransaction> = executed_transactions::table
+                .filter(executed_transactions::block_number.gt(last_block))
+                .filter(executed_transactions::success.eq(true))
+                .order((
+                    executed_transactions::block_number.asc(),
+                    executed_transactions::block_index.asc(),
+                ))
+                .load(self.0.conn())?;
+            for executed_tx in executed_txs {
+                let tx = SignedFranklinTx {
+                    tx: serde_json::from_value(executed_tx.tx)?,
+                    eth_sign_data: executed_tx
+                        .eth_sign_data
+                        .map(serde_json::from_value)
+                        .transpose()?,
+                };
+                MempoolSchema(self.0).insert_tx(&tx)?;
+            }
+
+            let executed_priority_ops: Vec<StoredExecutedPriorityOperation> =
+                executed_priority_operations::table
+                    .filter(executed_priority_operations::block_number.gt(last_block))
+                    .load(self.0.conn())?;
+            let priority_ops: Vec<PriorityOp> = executed_priority_ops
+                .into_iter()
+                .map(|op| op.into_executed().priority_op)
+                .collect();
+            EthereumSchema(self.0).save_priority_ops(&priority_ops)?;
+
+            diesel::delete(
+                executed_transactions::table
+                    .filter(executed_transactions::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                executed_priority_operations::table
+                    .filter(executed_priority_operations::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                account_balance_updates::table
+                    .filter(account_balance_updates::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                account_creates::table.filter(account_creates::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                account_pubkey_updates::table
+                    .filter(account_pubkey_updates::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                state_checkpoints::table.filter(state_checkpoints::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                pending_withdrawals::table.filter(pending_withdrawals::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            diesel::delete(
+                block_public_data::table.filter(block_public_data::block_number.gt(last_block)),
+            )
+            .execute(self.0.conn())?;
+            ProverSchema(self.0).cancel_reverted_blocks_jobs(last_block as BlockNumber)?;
+            diesel::delete(proof_timings::table.filter(proof_timings::block_number.gt(last_block)))
+                .execute(self.0.conn())?;
+            diesel::delete(pending_block::table.filter(pending_block::number.gt(last_block)))
+                .execute(self.0.conn())?;
+            diesel::delete(operations::table.filter(operations::block_number.gt(last_block)))
+                .execute(self.0.conn())?;
+            let removed_blocks =
+                diesel::delete(blocks::table.filter(blocks::number.gt(last_block)))
+                    .execute(self.0.conn())?;
+
+            Ok(removed_blocks)
+        })
+    }
+}
+
+/// Converts the fees collected by the block into the `{ "<token_id>": "<amount>" }` map
+/// stored in the database. Amounts are stored as decimal strings, since they may not fit
+/// into the JSON numbers.
+fn collected_fees_to_json(block: &Block) -> serde_json::Value {
+    let fees = block
+        .collected_fees()
+        .into_iter()
+        .map(|(token, amount)| {
+            (
+                token.to_string(),
+                serde_json::Value::from(amount.to_string()),
+            )
+        })
+        .collect::<serde_json::Map<_, _>>();
+    serde_json::Value::Object(fees)
 }
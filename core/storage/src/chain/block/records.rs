@@ -1,6 +1,6 @@
 // External imports
 use chrono::prelude::*;
-use diesel::sql_types::{BigInt, Binary, Jsonb, Nullable, Text, Timestamp};
+use diesel::sql_types::{BigInt, Binary, Jsonb, Nullable, Text, Timestamp, Timestamptz};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::value::Value;
 // Workspace imports
@@ -19,6 +19,20 @@ pub struct StorageBlock {
     pub block_size: i64,
     pub commit_gas_limit: i64,
     pub verify_gas_limit: i64,
+    pub chunks_used: i64,
+    /// Moment the block was sealed, unknown for the blocks restored from Ethereum.
+    pub created_at: Option<DateTime<Utc>>,
+    /// Total fees collected by the block operations, as a `{ "<token_id>": "<amount>" }` map.
+    pub collected_fees: Value,
+}
+
+/// Public data of the block. `data` field contains compressed bytes,
+/// see the `compression` module for details.
+#[derive(Debug, Insertable, Queryable)]
+#[table_name = "block_public_data"]
+pub struct StorageBlockPublicData {
+    pub block_number: i64,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Insertable, Queryable, AsChangeset)]
@@ -39,9 +53,23 @@ pub struct BlockDetails {
     #[serde(with = "BytesToHexSerde::<SyncBlockPrefix>")]
     pub new_state_root: Vec<u8>,
 
+    #[sql_type = "BigInt"]
+    pub fee_account_id: i64,
+
     #[sql_type = "BigInt"]
     pub block_size: i64,
 
+    #[sql_type = "BigInt"]
+    pub chunks_used: i64,
+
+    #[sql_type = "Jsonb"]
+    pub collected_fees: Value,
+
+    /// Moment the block was sealed by the state keeper, unknown for the blocks
+    /// restored from Ethereum.
+    #[sql_type = "Nullable<Timestamptz>"]
+    pub created_at: Option<DateTime<Utc>>,
+
     #[sql_type = "Nullable<Binary>"]
     #[serde(with = "OptionBytesToHexSerde::<ZeroxPrefix>")]
     pub commit_tx_hash: Option<Vec<u8>>,
@@ -71,3 +99,23 @@ pub struct BlockTransactionItem {
     #[sql_type = "Timestamp"]
     pub created_at: NaiveDateTime,
 }
+
+/// Progress of the blocks confirmation on Ethereum.
+#[derive(Debug, Clone, QueryableByName, Serialize, Deserialize)]
+pub struct ChainLagStats {
+    /// Last block committed by the server.
+    #[sql_type = "BigInt"]
+    pub last_committed_block: i64,
+    /// Last block which commit is confirmed on Ethereum.
+    #[sql_type = "BigInt"]
+    pub last_confirmed_commit_block: i64,
+    /// Last block which verification is confirmed on Ethereum.
+    #[sql_type = "BigInt"]
+    pub last_confirmed_verify_block: i64,
+    /// Time since the oldest block with unconfirmed commit was sealed.
+    #[sql_type = "Nullable<BigInt>"]
+    pub oldest_unconfirmed_commit_age_secs: Option<i64>,
+    /// Time since the oldest block with unconfirmed verification was sealed.
+    #[sql_type = "Nullable<BigInt>"]
+    pub oldest_unconfirmed_verify_age_secs: Option<i64>,
+}
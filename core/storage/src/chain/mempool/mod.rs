@@ -3,21 +3,27 @@ use std::collections::VecDeque;
 // External imports
 use diesel::prelude::*;
 // Workspace imports
-use models::node::{tx::TxHash, SignedFranklinTx};
+use models::node::{tx::TxHash, Address, SignedFranklinTx};
 // Local imports
-use self::records::{MempoolTx, NewMempoolTx};
+use self::records::{MempoolTx, NewMempoolTx, NewRejectedTx, StoredRejectedTx};
 use crate::{schema::*, StorageProcessor};
 
 pub mod records;
 
-/// Schema for TODO
+/// Mempool schema persists the transactions accepted by the mempool but not executed yet,
+/// so they can be restored once the server is restarted.
+///
+/// It also keeps the bounded log of the transactions rejected upon submission.
 #[derive(Debug)]
 pub struct MempoolSchema<'a>(pub &'a StorageProcessor);
 
 impl<'a> MempoolSchema<'a> {
-    /// Loads all the transactions stored in the mempool schema.
+    /// Loads all the transactions stored in the mempool schema in the order of insertion,
+    /// so the restored mempool proposes them in the original order of arrival.
     pub fn load_txs(&self) -> Result<VecDeque<SignedFranklinTx>, failure::Error> {
-        let txs: Vec<MempoolTx> = mempool_txs::table.load(self.0.conn())?;
+        let txs: Vec<MempoolTx> = mempool_txs::table
+            .order_by(mempool_txs::id)
+            .load(self.0.conn())?;
 
         let txs = txs
             .into_iter()
@@ -37,6 +43,11 @@ impl<'a> MempoolSchema<'a> {
         Ok(txs)
     }
 
+    /// Returns the amount of the transactions stored in the mempool schema.
+    pub fn count_txs(&self) -> QueryResult<i64> {
+        mempool_txs::table.count().get_result(self.0.conn())
+    }
+
     /// Adds a new transaction to the mempool schema.
     pub fn insert_tx(&self, tx_data: &SignedFranklinTx) -> Result<(), failure::Error> {
         let tx_hash = hex::encode(tx_data.tx.hash().as_ref());
@@ -104,4 +115,64 @@ impl<'a> MempoolSchema<'a> {
 
         Ok(())
     }
+
+    /// Records the transaction rejected upon submission. Only `max_stored` latest
+    /// records, and `max_stored_per_sender` latest records of the same sender are kept,
+    /// older ones are removed.
+    pub fn store_rejected_tx(
+        &self,
+        tx_hash: &[u8],
+        sender: &Address,
+        reason: &str,
+        max_stored: i64,
+        max_stored_per_sender: i64,
+    ) -> QueryResult<()> {
+        let rejected_tx = NewRejectedTx {
+            tx_hash: tx_hash.to_vec(),
+            sender: sender.as_bytes().to_vec(),
+            reason: reason.to_string(),
+        };
+
+        self.0.conn().transaction(|| {
+            let id: i64 = diesel::insert_into(rejected_txs::table)
+                .values(&rejected_tx)
+                .returning(rejected_txs::id)
+                .get_result(self.0.conn())?;
+
+            diesel::delete(rejected_txs::table.filter(rejected_txs::id.le(id - max_stored)))
+                .execute(self.0.conn())?;
+
+            let oldest_kept_id: Option<i64> = rejected_txs::table
+                .filter(rejected_txs::sender.eq(sender.as_bytes()))
+                .select(rejected_txs::id)
+                .order(rejected_txs::id.desc())
+                .offset(max_stored_per_sender - 1)
+                .first(self.0.conn())
+                .optional()?;
+            if let Some(oldest_kept_id) = oldest_kept_id {
+                diesel::delete(
+                    rejected_txs::table
+                        .filter(rejected_txs::sender.eq(sender.as_bytes()))
+                        .filter(rejected_txs::id.lt(oldest_kept_id)),
+                )
+                .execute(self.0.conn())?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Loads the latest transactions of the account rejected upon submission,
+    /// most recent first.
+    pub fn load_rejected_txs(
+        &self,
+        sender: &Address,
+        limit: i64,
+    ) -> QueryResult<Vec<StoredRejectedTx>> {
+        rejected_txs::table
+            .filter(rejected_txs::sender.eq(sender.as_bytes()))
+            .order(rejected_txs::id.desc())
+            .limit(limit)
+            .load(self.0.conn())
+    }
 }
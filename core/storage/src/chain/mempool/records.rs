@@ -22,3 +22,20 @@ pub struct NewMempoolTx {
     pub created_at: DateTime<Utc>,
     pub eth_sign_data: Option<serde_json::Value>,
 }
+
+#[derive(Debug, Clone, Queryable)]
+pub struct StoredRejectedTx {
+    pub id: i64,
+    pub tx_hash: Vec<u8>,
+    pub sender: Vec<u8>,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "rejected_txs"]
+pub struct NewRejectedTx {
+    pub tx_hash: Vec<u8>,
+    pub sender: Vec<u8>,
+    pub reason: String,
+}
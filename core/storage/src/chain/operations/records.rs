@@ -35,6 +35,7 @@ pub struct NewExecutedPriorityOperation {
     pub eth_hash: Vec<u8>,
     pub eth_block: i64,
     pub created_at: DateTime<Utc>,
+    pub invalid_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Queryable, QueryableByName)]
@@ -50,6 +51,7 @@ pub struct StoredExecutedPriorityOperation {
     pub eth_hash: Vec<u8>,
     pub eth_block: i64,
     pub created_at: NaiveDateTime,
+    pub invalid_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Insertable, AsChangeset)]
@@ -2,12 +2,13 @@
 // External imports
 use diesel::prelude::*;
 // Workspace imports
-use models::node::{Address, TokenId};
+use models::node::{Address, FranklinOp, TokenId};
 use models::ActionType;
 // Local imports
 use self::records::{
     PriorityOpReceiptResponse, TransactionsHistoryItem, TxByHashResponse, TxReceiptResponse,
 };
+use crate::pagination::OperationCursor;
 use crate::schema::*;
 use crate::tokens::TokensSchema;
 use crate::StorageProcessor;
@@ -21,14 +22,7 @@ use crate::{
 
 pub mod records;
 
-/// Direction to perform search of transactions to.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum SearchDirection {
-    /// Find transactions older than specified one.
-    Older,
-    /// Find transactions newer than specified one.
-    Newer,
-}
+pub use crate::pagination::SearchDirection;
 
 /// `OperationsExt` schema is a logical extension for an `Operations` schema,
 /// which provides more getters for transactions.
@@ -57,6 +51,11 @@ impl<'a> OperationsExtSchema<'a> {
                 .first::<ProverRun>(self.0.conn())
                 .optional()?;
 
+            let created_account_id = serde_json::from_value::<Option<FranklinOp>>(tx.operation)
+                .map_err(|err| diesel::result::Error::DeserializationError(Box::new(err)))?
+                .and_then(|op| op.created_account_id())
+                .map(i64::from);
+
             Ok(Some(TxReceiptResponse {
                 tx_hash: hex::encode(hash),
                 block_number: tx.block_number,
@@ -64,6 +63,7 @@ impl<'a> OperationsExtSchema<'a> {
                 verified,
                 fail_reason: tx.fail_reason,
                 prover_run,
+                created_account_id,
             }))
         } else {
             Ok(None)
@@ -253,7 +253,7 @@ impl<'a> OperationsExtSchema<'a> {
                 block_number,
                 nonce: -1,
                 created_at,
-                fail_reason: None,
+                fail_reason: tx.invalid_reason,
                 tx: operation,
             }));
         };
@@ -261,166 +261,25 @@ impl<'a> OperationsExtSchema<'a> {
         Ok(None)
     }
 
-    /// Loads the range of the transactions applied to the account starting
-    /// from the block with number $(offset) up to $(offset + limit).
-    pub fn get_account_transactions_history(
-        &self,
-        address: &Address,
-        offset: u64,
-        limit: u64,
-    ) -> QueryResult<Vec<TransactionsHistoryItem>> {
-        // This query does the following:
-        // - creates a union of `executed_transactions` and the `executed_priority_operations`
-        // - unifies the information to match the `TransactionsHistoryItem`
-        //   structure layout
-        // - returns the obtained results.
-        //
-        // Additional note:
-        // - previously for "committed" flag we've checked the operation "confirmed" field the
-        //   same way as it done for "verified" flag. Later we've decided that if tx was added
-        //   to the `executed_*` table, it actually **is** committed, thus now we just add
-        //   `true`.
-        let query = format!(
-            "
-            with eth_ops as (
-                select distinct on (block_number, action_type)
-                    operations.block_number,
-                    operations.action_type,
-                    confirmed
-                from operations
-                order by block_number desc, action_type, confirmed
-            ), transactions as (
-                select
-                    *
-                from (
-                    with vars (address_bytes) as ( select decode('{address}', 'hex') )
-                    select
-                        concat_ws(',', block_number, block_index) as tx_id,
-                        tx,
-                        'sync-tx:' || encode(tx_hash, 'hex') as hash,
-                        null as pq_id,
-                        null as eth_block,
-                        success,
-                        fail_reason,
-                        block_number,
-                        created_at
-                    from
-                        executed_transactions, vars
-                    where
-                        from_account = address_bytes
-                        or
-                        to_account = address_bytes
-                        or
-                        primary_account_address = address_bytes
-                    union all
-                    select
-                        concat_ws(',', block_number, block_index) as tx_id,
-                        operation as tx,
-                        '0x' || encode(eth_hash, 'hex') as hash,
-                        priority_op_serialid as pq_id,
-                        eth_block,
-                        null as success,
-                        null as fail_reason,
-                        block_number,
-                        created_at
-                    from 
-                        executed_priority_operations, vars
-                    where 
-                        from_account = address_bytes
-                        or
-                        to_account = address_bytes) t
-                order by
-                    block_number desc, created_at desc
-                offset 
-                    {offset}
-                limit 
-                    {limit}
-            )
-            select
-                tx_id,
-                hash,
-                eth_block,
-                pq_id,
-                tx,
-                success,
-                fail_reason,
-                true as commited,
-                coalesce(verified.confirmed, false) as verified,
-                created_at
-            from transactions
-            left join eth_ops verified on
-                verified.block_number = transactions.block_number and verified.action_type = 'VERIFY' and verified.confirmed = true
-            order by transactions.block_number desc, created_at desc
-            ",
-            address = hex::encode(address.as_ref().to_vec()),
-            offset = offset,
-            limit = limit
-        );
-        let mut tx_history =
-            diesel::sql_query(query).load::<TransactionsHistoryItem>(self.0.conn())?;
-        if !tx_history.is_empty() {
-            let tokens = TokensSchema(self.0).load_tokens()?;
-            for tx_item in &mut tx_history {
-                let tx_info = match tx_item.tx["type"].as_str().unwrap_or("NONE") {
-                    "NONE" => {
-                        log::warn!("Tx history item type not found, tx: {:?}", tx_item);
-                        continue;
-                    }
-                    "Deposit" | "FullExit" => tx_item.tx.get_mut("priority_op"),
-                    _ => Some(&mut tx_item.tx),
-                };
-
-                let tx_info = if let Some(tx_info) = tx_info {
-                    tx_info
-                } else {
-                    log::warn!("tx_info not found for tx: {:?}", tx_item);
-                    continue;
-                };
-
-                if let Some(tok_val) = tx_info.get_mut("token") {
-                    if let Some(token_id) = tok_val.as_u64() {
-                        let token_id = token_id as TokenId;
-                        let token_symbol = tokens
-                            .get(&token_id)
-                            .map(|t| t.symbol.clone())
-                            .unwrap_or_else(|| "UNKNOWN".to_string());
-                        *tok_val =
-                            serde_json::to_value(token_symbol).expect("json string to value");
-                    };
-                };
-            }
-        }
-        Ok(tx_history)
-    }
-
     /// Loads the range of the transactions applied to the account starting
     /// from the specified transaction ID.
     ///
     /// This method can be used to get transactions "older" than some transaction
-    /// or "newer" than one.
+    /// or "newer" than one. The page is adjacent to the cursor, while the transactions
+    /// in it are ordered from the newest to the oldest one.
     ///
-    /// Unlike `get_account_transactions_history`, this method does not use
-    /// a relative offset, and thus not prone to report the same tx twice if new
-    /// transactions were added to the database.
+    /// Since the position of the transaction is used instead of a relative offset,
+    /// the same tx is not reported twice if new transactions were added to the database.
     pub fn get_account_transactions_history_from(
         &self,
         address: &Address,
-        tx_id: (u64, u64),
+        cursor: OperationCursor,
         direction: SearchDirection,
         limit: u64,
     ) -> QueryResult<Vec<TransactionsHistoryItem>> {
-        let direction_sign = match direction {
-            SearchDirection::Older => "<", // Older blocks have lesser block ID.
-            SearchDirection::Newer => ">", // Newer blocks have greater block ID.
-        };
-
         // Filter for txs that older/newer than provided tx ID.
-        let ordered_filter = format!(
-            "(block_number {sign} {block_id} or (block_number = {block_id} and block_index {sign} {block_tx_id}))",
-            sign = direction_sign,
-            block_id = tx_id.0,
-            block_tx_id = tx_id.1
-        );
+        let ordered_filter = cursor.sql_filter(direction, "block_number", "block_index");
+        let order = OperationCursor::sql_order(direction, "block_number", "block_index");
 
         // This query does the following:
         // - creates a union of `executed_transactions` and the `executed_priority_operations`
@@ -456,6 +315,7 @@ impl<'a> OperationsExtSchema<'a> {
                         success,
                         fail_reason,
                         block_number,
+                        block_index,
                         created_at
                     from
                         executed_transactions, vars
@@ -479,6 +339,7 @@ impl<'a> OperationsExtSchema<'a> {
                         null as success,
                         null as fail_reason,
                         block_number,
+                        block_index,
                         created_at
                     from 
                         executed_priority_operations, vars
@@ -492,7 +353,7 @@ impl<'a> OperationsExtSchema<'a> {
                         {ordered_filter}
                     ) t
                 order by
-                    block_number desc, created_at desc
+                    {order}
                 limit 
                     {limit}
             )
@@ -512,10 +373,11 @@ impl<'a> OperationsExtSchema<'a> {
                 committed.block_number = transactions.block_number and committed.action_type = 'COMMIT'
             left join eth_ops verified on
                 verified.block_number = transactions.block_number and verified.action_type = 'VERIFY' and verified.confirmed = true
-            order by transactions.block_number desc, created_at desc
+            order by transactions.block_number desc, transactions.block_index desc
             ",
             address = hex::encode(address.as_ref().to_vec()),
             ordered_filter = ordered_filter,
+            order = order,
             limit = limit
         );
         let mut tx_history =
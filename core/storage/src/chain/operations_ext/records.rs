@@ -17,7 +17,7 @@ pub struct AccountTransaction {
     pub verified: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize, QueryableByName, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, QueryableByName, PartialEq)]
 pub struct TransactionsHistoryItem {
     #[sql_type = "Text"]
     pub tx_id: String,
@@ -58,6 +58,8 @@ pub struct TxReceiptResponse {
     pub verified: bool,
     pub fail_reason: Option<String>,
     pub prover_run: Option<ProverRun>,
+    /// ID of the account created by the transaction (e.g. transfer to the new address).
+    pub created_account_id: Option<i64>,
 }
 
 // TODO: jazzandrock add more info(?)
@@ -4,9 +4,12 @@ use std::cmp;
 use diesel::dsl::{delete, insert_into, max, update};
 use diesel::prelude::*;
 // Workspace imports
-use models::node::PubKeyHash;
-use models::node::{apply_updates, reverse_updates, AccountMap, AccountUpdate, AccountUpdates};
+use models::node::{
+    apply_updates, reverse_updates, Account, AccountId, AccountMap, AccountUpdate, AccountUpdates,
+    BlockNumber, PubKeyHash,
+};
 // Local imports
+use self::records::StorageStateCheckpoint;
 use crate::chain::{
     account::{
         records::{
@@ -18,10 +21,13 @@ use crate::chain::{
     },
     block::BlockSchema,
 };
+use crate::compression::{compress, decompress_stored};
 use crate::diff::StorageAccountDiff;
 use crate::schema::*;
 use crate::StorageProcessor;
 
+pub mod records;
+
 /// State schema is capable of managing... well, the state of the chain.
 ///
 /// This roughly includes the two main topics:
@@ -39,6 +45,10 @@ use crate::StorageProcessor;
 /// This way we have the following advantages:
 /// - Easy access to state for any block (useful for provers which work on different blocks)
 /// - We can rewind any `committed` state (which is not final)
+///
+/// Since the verified state may lag behind the committed one significantly, the state
+/// keeper also stores the checkpoints of the committed state (table: `state_checkpoints`),
+/// which are used as the starting point when the committed state is restored.
 #[derive(Debug)]
 pub struct StateSchema<'a>(pub &'a StorageProcessor);
 
@@ -240,24 +250,101 @@ impl<'a> StateSchema<'a> {
     /// state will be loaded.
     pub fn load_committed_state(&self, block: Option<u32>) -> QueryResult<(u32, AccountMap)> {
         self.0.conn().transaction(|| {
-            let (verif_block, mut accounts) = self.load_verified_state()?;
-            log::debug!(
-                "Verified state block: {}, accounts: {:#?}",
-                verif_block,
-                accounts
-            );
+            // Start from the latest checkpoint if it's more recent than the verified state,
+            // so fewer stored updates have to be applied.
+            let to_block = match block {
+                Some(block) => block,
+                None => self.last_block_number()?,
+            };
+            let verified_block = BlockSchema(self.0).get_last_verified_block()?;
+            let checkpoint = self
+                .load_last_checkpoint(to_block)?
+                .filter(|(checkpoint_block, _)| *checkpoint_block >= verified_block);
 
-            // Fetch updates from blocks: verif_block +/- 1, ... , block
-            if let Some((block, state_diff)) = self.load_state_diff(verif_block, block)? {
+            let (from_block, mut accounts) = match checkpoint {
+                Some((checkpoint_block, accounts)) => {
+                    log::debug!("Loaded state checkpoint for the block {}", checkpoint_block);
+                    (checkpoint_block, accounts)
+                }
+                None => {
+                    let (verif_block, accounts) = self.load_verified_state()?;
+                    log::debug!(
+                        "Verified state block: {}, accounts: {:#?}",
+                        verif_block,
+                        accounts
+                    );
+                    (verif_block, accounts)
+                }
+            };
+
+            // Fetch updates from blocks: from_block +/- 1, ... , block
+            if let Some((block, state_diff)) = self.load_state_diff(from_block, Some(to_block))? {
                 log::debug!("Loaded state diff: {:#?}", state_diff);
                 apply_updates(&mut accounts, state_diff);
                 Ok((block, accounts))
             } else {
-                Ok((verif_block, accounts))
+                Ok((from_block, accounts))
             }
         })
     }
 
+    /// Stores the checkpoint of the state as of the provided block, so the state can be
+    /// restored without applying the updates of all the blocks since the last verified one.
+    /// Only the latest checkpoint is kept.
+    pub fn store_checkpoint(&self, block: BlockNumber, accounts: &AccountMap) -> QueryResult<()> {
+        let accounts: Vec<(&AccountId, &Account)> = accounts.iter().collect();
+        let serialized = serde_json::to_vec(&accounts).expect("failed to serialize accounts");
+        let checkpoint = StorageStateCheckpoint {
+            block_number: i64::from(block),
+            accounts: compress(&serialized),
+        };
+
+        self.0.conn().transaction(|| {
+            insert_into(state_checkpoints::table)
+                .values(&checkpoint)
+                .on_conflict_do_nothing()
+                .execute(self.0.conn())?;
+            delete(
+                state_checkpoints::table
+                    .filter(state_checkpoints::block_number.lt(i64::from(block))),
+            )
+            .execute(self.0.conn())?;
+            Ok(())
+        })
+    }
+
+    /// Loads the latest stored checkpoint of the state which doesn't exceed the provided block.
+    pub fn load_last_checkpoint(
+        &self,
+        max_block: BlockNumber,
+    ) -> QueryResult<Option<(BlockNumber, AccountMap)>> {
+        let stored: Option<StorageStateCheckpoint> = state_checkpoints::table
+            .filter(state_checkpoints::block_number.le(i64::from(max_block)))
+            .order(state_checkpoints::block_number.desc())
+            .first(self.0.conn())
+            .optional()?;
+
+        stored
+            .map(|stored| {
+                let serialized = decompress_stored(&stored.accounts)?;
+                let accounts: Vec<(AccountId, Account)> = serde_json::from_slice(&serialized)
+                    .map_err(|err| diesel::result::Error::DeserializationError(Box::new(err)))?;
+                Ok((
+                    stored.block_number as BlockNumber,
+                    accounts.into_iter().collect(),
+                ))
+            })
+            .transpose()
+    }
+
+    /// Returns the number of the latest stored block.
+    fn last_block_number(&self) -> QueryResult<BlockNumber> {
+        let last_block = blocks::table
+            .select(max(blocks::number))
+            .first::<Option<i64>>(self.0.conn())?;
+        Ok(last_block.map(|n| n as BlockNumber).unwrap_or(0))
+    }
+
     /// Loads the verified account map state along with a block number
     /// to which this state applies.
     /// If the provided block number is `None`, then the latest committed
@@ -301,10 +388,7 @@ impl<'a> StateSchema<'a> {
             let to_block_resolved = if let Some(to_block) = to_block {
                 to_block
             } else {
-                let last_block = blocks::table
-                    .select(max(blocks::number))
-                    .first::<Option<i64>>(self.0.conn())?;
-                last_block.map(|n| n as u32).unwrap_or(0)
+                self.last_block_number()?
             };
 
             // Determine the order: are we going forward or backwards.
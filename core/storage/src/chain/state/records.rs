@@ -0,0 +1,11 @@
+// Local imports
+use crate::schema::*;
+
+/// Checkpoint of the committed state, which contains the compressed serialized
+/// list of accounts, see the `compression` module for details.
+#[derive(Debug, Insertable, Queryable)]
+#[table_name = "state_checkpoints"]
+pub struct StorageStateCheckpoint {
+    pub block_number: i64,
+    pub accounts: Vec<u8>,
+}
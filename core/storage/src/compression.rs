@@ -0,0 +1,32 @@
+//! Compression of the large binary blobs stored in the database.
+//!
+//! Block public data and prover witnesses are the largest rows in the database,
+//! while being highly redundant (e.g. sequences of zero bytes for the noop chunks).
+//! Such blobs are stored compressed with `zstd`, and are decompressed transparently
+//! by the schema methods, so the users of the storage API never see the compressed data.
+
+// Built-in deps
+use std::io;
+// External imports
+use diesel::result::Error as DieselError;
+
+/// Compression level used for the stored blobs.
+/// Blobs are written once and read rarely, but the level is kept moderate
+/// in order not to slow down the block sealing.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Compresses the data to be stored in the database.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    zstd::encode_all(data, COMPRESSION_LEVEL).expect("in-memory zstd compression failed")
+}
+
+/// Decompresses the data loaded from the database.
+pub fn decompress(data: &[u8]) -> io::Result<Vec<u8>> {
+    zstd::decode_all(data)
+}
+
+/// Decompresses the data loaded from the database, reporting
+/// the malformed blob as a deserialization error of the query.
+pub(crate) fn decompress_stored(data: &[u8]) -> Result<Vec<u8>, DieselError> {
+    decompress(data).map_err(|err| DieselError::DeserializationError(Box::new(err)))
+}
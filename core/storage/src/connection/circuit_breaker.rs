@@ -0,0 +1,80 @@
+// Built-in deps
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Circuit breaker shared by all the connections of the pool.
+///
+/// Once the database fails to respond to several queries in a row (either because the
+/// connection was lost or the query exceeded the statement timeout), the circuit is
+/// considered "open" and the queries fail immediately without reaching the database.
+/// This way the API workers don't get stuck waiting for the unresponsive database.
+///
+/// After the cooldown period, the next query is let through: if it succeeds, the circuit
+/// is closed again, otherwise it stays open for another cooldown period.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    /// Amount of consecutive failures required to open the circuit.
+    /// Value of `0` disables the circuit breaking.
+    failures_threshold: usize,
+    /// Period during which the circuit stays open.
+    cooldown: Duration,
+    state: Mutex<CircuitState>,
+}
+
+#[derive(Debug, Default)]
+struct CircuitState {
+    consecutive_failures: usize,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    pub fn new(failures_threshold: usize, cooldown: Duration) -> Self {
+        Self {
+            failures_threshold,
+            cooldown,
+            state: Mutex::new(CircuitState::default()),
+        }
+    }
+
+    /// Checks whether the query can be sent to the database.
+    pub fn allows_request(&self) -> bool {
+        let state = self.state.lock().expect("circuit breaker lock poisoned");
+        match state.opened_at {
+            Some(opened_at) => opened_at.elapsed() >= self.cooldown,
+            None => true,
+        }
+    }
+
+    /// Returns `true` if the circuit is currently open.
+    pub fn is_open(&self) -> bool {
+        !self.allows_request()
+    }
+
+    /// Reports that the database responded to the query.
+    pub fn report_success(&self) {
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+    }
+
+    /// Reports that the database didn't respond to the query.
+    pub fn report_failure(&self) {
+        if self.failures_threshold == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().expect("circuit breaker lock poisoned");
+        state.consecutive_failures += 1;
+        if state.consecutive_failures >= self.failures_threshold {
+            if state.opened_at.is_none() {
+                log::error!(
+                    "Database failed to respond to {} queries in a row, \
+                     rejecting queries for {:?}",
+                    state.consecutive_failures,
+                    self.cooldown
+                );
+            }
+            state.opened_at = Some(Instant::now());
+        }
+    }
+}
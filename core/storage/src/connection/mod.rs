@@ -2,25 +2,46 @@
 use std::env;
 use std::fmt;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::Duration;
 // External imports
 use diesel::pg::PgConnection;
 use diesel::r2d2::{ConnectionManager, Pool, PoolError};
 // Local imports
-use self::recoverable_connection::RecoverableConnection;
+use self::{
+    circuit_breaker::CircuitBreaker,
+    recoverable_connection::{RecoverableConnection, RetryPolicy},
+};
 use crate::StorageProcessor;
 use models::config_options::parse_env;
 
+pub mod circuit_breaker;
 pub mod holder;
 pub mod recoverable_connection;
 
+/// Default amount of consecutive database failures required to open the circuit.
+const DEFAULT_CIRCUIT_BREAKER_THRESHOLD: usize = 5;
+/// Default period during which the circuit stays open.
+const DEFAULT_CIRCUIT_BREAKER_COOLDOWN: Duration = Duration::from_secs(10);
+
 /// `ConnectionPool` is a wrapper over a `diesel`s `Pool`, encapsulating
 /// the fixed size pool of connection to the database.
 ///
 /// The size of the pool and the database URL are configured via environment
 /// variables `DB_POOL_SIZE` and `DATABASE_URL` respectively.
+///
+/// Behavior of the connections can be tuned with the following optional variables:
+/// - `DB_STATEMENT_TIMEOUT_MS`: maximum execution time of a single query (unlimited by default).
+/// - `DB_RETRIES_AMOUNT` and `DB_RETRY_INTERVAL_MS`: amount of attempts to repeat the query
+///   failed due to a transient error, and the base interval between them.
+/// - `DB_CIRCUIT_BREAKER_THRESHOLD` and `DB_CIRCUIT_BREAKER_COOLDOWN_MS`: amount of consecutive
+///   database failures after which the queries are rejected without reaching the database,
+///   and the period of such a rejection. Threshold of `0` disables the circuit breaking.
 #[derive(Clone)]
 pub struct ConnectionPool {
     pool: Pool<ConnectionManager<RecoverableConnection<PgConnection>>>,
+    retry_policy: RetryPolicy,
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl fmt::Debug for ConnectionPool {
@@ -34,7 +55,12 @@ impl ConnectionPool {
     /// creates a new `ConnectionPool` object.
     /// pool_max_size - number of connections in pool, if not set env variable "DB_POOL_SIZE" is going to be used.
     pub fn new(pool_max_size: Option<u32>) -> Self {
-        let database_url = Self::get_database_url();
+        let mut database_url = Self::get_database_url();
+        let statement_timeout = optional_env::<u64>("DB_STATEMENT_TIMEOUT_MS").unwrap_or(0);
+        if statement_timeout != 0 {
+            let timeout = Duration::from_millis(statement_timeout);
+            database_url = with_statement_timeout(&database_url, timeout);
+        }
         let max_size = pool_max_size.unwrap_or_else(|| parse_env("DB_POOL_SIZE"));
         let manager = ConnectionManager::<RecoverableConnection<PgConnection>>::new(database_url);
         let pool = Pool::builder()
@@ -42,7 +68,33 @@ impl ConnectionPool {
             .build(manager)
             .expect("Failed to create connection pool");
 
-        Self { pool }
+        let default_policy = RetryPolicy::default();
+        let retry_policy = RetryPolicy {
+            retries_amount: optional_env("DB_RETRIES_AMOUNT")
+                .unwrap_or(default_policy.retries_amount),
+            retry_quantile: optional_env("DB_RETRY_INTERVAL_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default_policy.retry_quantile),
+        };
+        let circuit_breaker = CircuitBreaker::new(
+            optional_env("DB_CIRCUIT_BREAKER_THRESHOLD")
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_THRESHOLD),
+            optional_env("DB_CIRCUIT_BREAKER_COOLDOWN_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_CIRCUIT_BREAKER_COOLDOWN),
+        );
+
+        Self {
+            pool,
+            retry_policy,
+            circuit_breaker: Arc::new(circuit_breaker),
+        }
+    }
+
+    /// Returns `true` if the database is considered unavailable and the queries are
+    /// rejected without reaching it.
+    pub fn is_circuit_open(&self) -> bool {
+        self.circuit_breaker.is_open()
     }
 
     /// Creates a `StorageProcessor` entity over a recoverable connection.
@@ -56,6 +108,10 @@ impl ConnectionPool {
     pub fn access_storage(&self) -> Result<StorageProcessor, PoolError> {
         let connection = self.pool.get()?;
         connection.deref().enable_retrying();
+        connection.deref().set_retry_policy(self.retry_policy);
+        connection
+            .deref()
+            .set_circuit_breaker(self.circuit_breaker.clone());
 
         Ok(StorageProcessor::from_pool(connection))
     }
@@ -66,6 +122,9 @@ impl ConnectionPool {
     pub fn access_storage_fragile(&self) -> Result<StorageProcessor, PoolError> {
         let connection = self.pool.get()?;
         connection.deref().disable_retrying();
+        connection
+            .deref()
+            .set_circuit_breaker(self.circuit_breaker.clone());
 
         Ok(StorageProcessor::from_pool(connection))
     }
@@ -75,3 +134,24 @@ impl ConnectionPool {
         env::var("DATABASE_URL").expect("DATABASE_URL must be set")
     }
 }
+
+/// Parses the optional environment variable.
+fn optional_env<T: std::str::FromStr>(name: &str) -> Option<T> {
+    if env::var(name).is_ok() {
+        Some(parse_env(name))
+    } else {
+        None
+    }
+}
+
+/// Adds the statement timeout to the connection parameters in the database URL,
+/// so it's applied to every connection established with this URL.
+pub fn with_statement_timeout(database_url: &str, timeout: Duration) -> String {
+    let separator = if database_url.contains('?') { '&' } else { '?' };
+    format!(
+        "{}{}options=-c%20statement_timeout%3D{}",
+        database_url,
+        separator,
+        timeout.as_millis()
+    )
+}
@@ -1,13 +1,19 @@
 // Built-in deps
 use std::cell::{Cell, RefCell};
+use std::sync::Arc;
 use std::time::Duration;
 // External uses
 use diesel::backend::UsesAnsiSavepointSyntax;
-use diesel::connection::{AnsiTransactionManager, Connection, SimpleConnection};
+use diesel::connection::{
+    AnsiTransactionManager, Connection, SimpleConnection, TransactionManager,
+};
 use diesel::prelude::*;
 use diesel::query_builder::{AsQuery, QueryFragment, QueryId};
 use diesel::query_source::QueryableByName;
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
 use diesel::types::HasSqlType;
+// Local imports
+use super::circuit_breaker::CircuitBreaker;
 
 /// Amount of attempts to re-establish the connection.
 const RETRIES_AMOUNT: usize = 10;
@@ -15,6 +21,48 @@ const RETRIES_AMOUNT: usize = 10;
 /// This amount of time is increased after each unsuccessful attempt.
 const RETRY_QUANTILE: Duration = Duration::from_millis(200);
 
+/// Policy of retrying the queries failed due to transient errors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// Amount of attempts to repeat the query.
+    pub retries_amount: usize,
+    /// Quantile of the interval between attempts.
+    /// This amount of time is increased after each unsuccessful attempt.
+    pub retry_quantile: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries_amount: RETRIES_AMOUNT,
+            retry_quantile: RETRY_QUANTILE,
+        }
+    }
+}
+
+/// Outcome of the failed query, used to decide whether the query should be retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FailureKind {
+    /// Concurrent transactions conflicted, the query can be repeated as is.
+    SerializationFailure,
+    /// Connection with the database was lost.
+    ConnectionLost,
+    /// Query exceeded the statement timeout.
+    Timeout,
+    /// Database responded with an error, repeating the query won't help.
+    Permanent,
+}
+
+impl FailureKind {
+    /// Whether the database was unresponsive, which should be reported to the circuit breaker.
+    fn is_database_unresponsive(self) -> bool {
+        match self {
+            Self::ConnectionLost | Self::Timeout => true,
+            Self::SerializationFailure | Self::Permanent => false,
+        }
+    }
+}
+
 /// `RecoverableConnection` is a generic wrapper over Diesel's connection types
 /// which is capable of reestablishment of the connection and retrying the same
 /// query multiple times in case of database being unavailable for short periods
@@ -43,14 +91,30 @@ const RETRY_QUANTILE: Duration = Duration::from_millis(200);
 /// connection pool can re-create connections on its own, and we don't want the connection
 /// to retry the operation unless it was explicitly enabled. Instead, the `ConnectionPool`
 /// structure manages this setting upon every storage access request.
+///
+/// Only the transient errors (lost connection and serialization failures) are retried,
+/// and only outside of the database transaction: once the transaction is started, the
+/// error is returned to the caller, since repeating a single query of the aborted
+/// transaction makes no sense.
+///
+/// # Circuit Breaking
+///
+/// If the circuit breaker is set, the connection reports the database failures to it,
+/// and rejects the queries without sending them while the circuit is open.
 pub struct RecoverableConnection<Conn: Connection> {
     database_url: String,
     connection: RefCell<Conn>,
     transaction_manager: AnsiTransactionManager,
     retrying_enabled: Cell<bool>,
+    retry_policy: Cell<RetryPolicy>,
+    circuit_breaker: RefCell<Option<Arc<CircuitBreaker>>>,
 }
 
-impl<Conn: Connection> SimpleConnection for RecoverableConnection<Conn> {
+impl<Conn> SimpleConnection for RecoverableConnection<Conn>
+where
+    Conn: Connection,
+    Conn::Backend: UsesAnsiSavepointSyntax,
+{
     fn batch_execute(&self, query: &str) -> QueryResult<()> {
         self.exec_with_retries(|| self.connection.borrow().batch_execute(query))
     }
@@ -70,6 +134,8 @@ where
             connection: RefCell::new(connection),
             transaction_manager: AnsiTransactionManager::new(),
             retrying_enabled: Cell::new(false),
+            retry_policy: Cell::new(RetryPolicy::default()),
+            circuit_breaker: RefCell::new(None),
         })
     }
 
@@ -111,6 +177,7 @@ where
 impl<Conn> RecoverableConnection<Conn>
 where
     Conn: Connection,
+    Conn::Backend: UsesAnsiSavepointSyntax,
 {
     /// Performs the query (represented as a closure) with a prior knowledge
     /// that the database can sometimes stop responding for short periods of time.
@@ -118,6 +185,34 @@ where
     /// In case of the database unavailability, the same request is repeated with
     /// increasing time intervals.
     fn exec_with_retries<F, T>(&self, f: F) -> QueryResult<T>
+    where
+        F: Fn() -> QueryResult<T>,
+    {
+        let circuit_breaker = self.circuit_breaker.borrow().clone();
+        if let Some(circuit_breaker) = &circuit_breaker {
+            if !circuit_breaker.allows_request() {
+                return Err(DieselError::DatabaseError(
+                    DatabaseErrorKind::UnableToSendCommand,
+                    Box::new("Database is unavailable (circuit breaker is open)".to_string()),
+                ));
+            }
+        }
+
+        let result = self.exec_with_retries_inner(f);
+
+        if let Some(circuit_breaker) = &circuit_breaker {
+            match &result {
+                Err(error) if self.classify_failure(error).is_database_unresponsive() => {
+                    circuit_breaker.report_failure()
+                }
+                _ => circuit_breaker.report_success(),
+            }
+        }
+
+        result
+    }
+
+    fn exec_with_retries_inner<F, T>(&self, f: F) -> QueryResult<T>
     where
         F: Fn() -> QueryResult<T>,
     {
@@ -126,37 +221,89 @@ where
             return f();
         }
 
-        for attempt in 1..=RETRIES_AMOUNT {
-            match f() {
+        let retry_policy = self.retry_policy.get();
+        let mut attempt = 0;
+        loop {
+            let error = match f() {
                 Ok(result) => {
                     return Ok(result);
                 }
-                Err(error) => {
-                    // In case of error encountered, we're gonna retry the connection, since
-                    // most likely the error is caused by some issue with the database server.
-                    log::warn!(
-                        "Error while interacting with database ({}), retry attempt #{}",
-                        error,
+                Err(error) => error,
+            };
+
+            // Queries within the transaction are not retried, as well as the queries
+            // that failed for the reasons other than the database availability.
+            let failure = self.classify_failure(&error);
+            let transient = match failure {
+                FailureKind::SerializationFailure | FailureKind::ConnectionLost => true,
+                FailureKind::Timeout | FailureKind::Permanent => false,
+            };
+            if self.in_transaction() || !transient {
+                return Err(error);
+            }
+
+            attempt += 1;
+            if attempt > retry_policy.retries_amount {
+                break;
+            }
+
+            log::warn!(
+                "Error while interacting with database ({}), retry attempt #{}",
+                error,
+                attempt
+            );
+
+            std::thread::sleep(scale_retry_period(retry_policy.retry_quantile, attempt));
+            if failure == FailureKind::ConnectionLost {
+                if let Ok(conn) = Conn::establish(self.database_url.as_ref()) {
+                    log::info!(
+                        "Connection with the database reestablished after {} retries",
                         attempt
                     );
-
-                    std::thread::sleep(scale_retry_period(attempt));
-                    if let Ok(conn) = Conn::establish(self.database_url.as_ref()) {
-                        log::info!(
-                            "Connection with the database reestablished after {} retries",
-                            attempt
-                        );
-                        *self.connection.borrow_mut() = conn;
-                    }
+                    *self.connection.borrow_mut() = conn;
                 }
             }
         }
 
-        // At this point we are sure that either database is down
-        // or the query is incorrect; we cannot work without a database.
+        // At this point we are sure that the database is down; we cannot work without a database.
         panic!("Cannot connect to the database after several retries, it is probably down");
     }
 
+    /// Determines the cause of the query failure.
+    fn classify_failure(&self, error: &DieselError) -> FailureKind {
+        match error {
+            DieselError::DatabaseError(DatabaseErrorKind::SerializationFailure, _) => {
+                FailureKind::SerializationFailure
+            }
+            DieselError::DatabaseError(DatabaseErrorKind::UnableToSendCommand, _) => {
+                FailureKind::ConnectionLost
+            }
+            DieselError::DatabaseError(_, info) if info.message().contains("statement timeout") => {
+                FailureKind::Timeout
+            }
+            DieselError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)
+            | DieselError::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
+                FailureKind::Permanent
+            }
+            DieselError::DatabaseError(_, _) => {
+                // Diesel doesn't distinguish the lost connection from the other errors
+                // reported by the database, so we check whether the connection is alive.
+                // Within the failed transaction every query fails, so the check is skipped.
+                if self.in_transaction() || self.connection.borrow().execute("SELECT 1").is_ok() {
+                    FailureKind::Permanent
+                } else {
+                    FailureKind::ConnectionLost
+                }
+            }
+            _ => FailureKind::Permanent,
+        }
+    }
+
+    /// Checks whether there is an ongoing transaction on the connection.
+    fn in_transaction(&self) -> bool {
+        TransactionManager::<Self>::get_transaction_depth(&self.transaction_manager) > 0
+    }
+
     /// Disables the retrying functionality (effectively making the connection
     /// the equivalent of the underlying connection).
     pub fn disable_retrying(&self) {
@@ -167,11 +314,21 @@ where
     pub fn enable_retrying(&self) {
         self.retrying_enabled.set(true);
     }
+
+    /// Sets the policy used to retry the failed queries.
+    pub fn set_retry_policy(&self, retry_policy: RetryPolicy) {
+        self.retry_policy.set(retry_policy);
+    }
+
+    /// Sets the circuit breaker to report the database failures to.
+    pub fn set_circuit_breaker(&self, circuit_breaker: Arc<CircuitBreaker>) {
+        *self.circuit_breaker.borrow_mut() = Some(circuit_breaker);
+    }
 }
 
 // Scales the retry interval, so that we will have smaller retry intervals in the beginning
 // (hoping that the connection will be restored almost immediately), but then we will wait longer
 // not to spam the (hopefully) initializing database with many requests.
-fn scale_retry_period(n_attempt: usize) -> Duration {
-    RETRY_QUANTILE * n_attempt as u32
+fn scale_retry_period(retry_quantile: Duration, n_attempt: usize) -> Duration {
+    retry_quantile * n_attempt as u32
 }
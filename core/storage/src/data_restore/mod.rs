@@ -6,7 +6,7 @@ use itertools::Itertools;
 // Workspace imports
 use models::node::block::Block;
 use models::node::{AccountId, AccountUpdate, BlockNumber, FranklinOp, Token};
-use models::{NewTokenEvent, Operation};
+use models::Operation;
 // Local imports
 use self::records::{
     NewBlockEvent, NewFranklinOp, NewLastWatchedEthBlockNumber, NewStorageState, StoredBlockEvent,
@@ -125,18 +125,14 @@ impl<'a> DataRestoreSchema<'a> {
     pub fn save_events_state(
         &self,
         block_events: &[NewBlockEvent],
-        token_events: &[NewTokenEvent],
+        tokens: &[Token],
         last_watched_eth_number: &NewLastWatchedEthBlockNumber,
     ) -> QueryResult<()> {
         self.0.conn().transaction(|| {
             self.update_block_events(block_events)?;
 
-            for &NewTokenEvent { id, address } in token_events.iter() {
-                // The only way to know decimals is to query ERC20 contract 'decimals' function
-                // that may or may not (in most cases, may not) be there, so we just assume it to be 18
-                let decimals = 18;
-                let token = Token::new(id, address, &format!("ERC20-{}", id), decimals);
-                TokensSchema(self.0).store_token(token)?;
+            for token in tokens {
+                TokensSchema(self.0).store_token(token.clone())?;
             }
 
             self.update_last_watched_block_number(last_watched_eth_number)?;
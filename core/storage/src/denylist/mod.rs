@@ -0,0 +1,88 @@
+// Built-in deps
+// External imports
+use diesel::prelude::*;
+// Workspace imports
+use models::node::Address;
+// Local imports
+use self::records::{NewDeniedAddress, NewDeniedTx, StoredDeniedAddress, StoredDeniedTx};
+use crate::schema::*;
+use crate::StorageProcessor;
+
+pub mod records;
+
+/// Denylist schema manages the addresses which are not allowed to send or receive
+/// transactions, and the audit log of the transactions rejected because of them.
+///
+/// The denylist is maintained by the operator, and loaded by the mempool which checks
+/// the transactions upon admission.
+#[derive(Debug)]
+pub struct DenylistSchema<'a>(pub &'a StorageProcessor);
+
+impl<'a> DenylistSchema<'a> {
+    /// Loads all the denylisted addresses.
+    pub fn load_denylist(&self) -> QueryResult<Vec<StoredDeniedAddress>> {
+        address_denylist::table
+            .order(address_denylist::created_at.asc())
+            .load(self.0.conn())
+    }
+
+    /// Adds the address to the denylist. If the address is already denylisted,
+    /// the reason is updated.
+    pub fn add_address(&self, address: &Address, reason: &str) -> QueryResult<()> {
+        let new_address = NewDeniedAddress {
+            address: address.as_bytes().to_vec(),
+            reason: reason.to_string(),
+        };
+        diesel::insert_into(address_denylist::table)
+            .values(&new_address)
+            .on_conflict(address_denylist::address)
+            .do_update()
+            .set(address_denylist::reason.eq(reason))
+            .execute(self.0.conn())
+            .map(drop)
+    }
+
+    /// Removes the address from the denylist.
+    /// Returns `false` if the address was not denylisted.
+    pub fn remove_address(&self, address: &Address) -> QueryResult<bool> {
+        let removed = diesel::delete(
+            address_denylist::table.filter(address_denylist::address.eq(address.as_bytes())),
+        )
+        .execute(self.0.conn())?;
+        Ok(removed > 0)
+    }
+
+    /// Records the transaction rejected because of the denylisted address.
+    /// Only `max_stored` latest records are kept, older ones are removed.
+    pub fn log_denied_tx(
+        &self,
+        tx_hash: &[u8],
+        address: &Address,
+        max_stored: i64,
+    ) -> QueryResult<()> {
+        let denied_tx = NewDeniedTx {
+            tx_hash: tx_hash.to_vec(),
+            address: address.as_bytes().to_vec(),
+        };
+
+        self.0.conn().transaction(|| {
+            let id: i64 = diesel::insert_into(denied_txs::table)
+                .values(&denied_tx)
+                .returning(denied_txs::id)
+                .get_result(self.0.conn())?;
+
+            diesel::delete(denied_txs::table.filter(denied_txs::id.le(id - max_stored)))
+                .execute(self.0.conn())?;
+
+            Ok(())
+        })
+    }
+
+    /// Loads the latest records of the rejected transactions, most recent first.
+    pub fn load_denied_txs(&self, limit: i64) -> QueryResult<Vec<StoredDeniedTx>> {
+        denied_txs::table
+            .order(denied_txs::id.desc())
+            .limit(limit)
+            .load(self.0.conn())
+    }
+}
@@ -0,0 +1,34 @@
+// External imports
+use chrono::{DateTime, Utc};
+use serde_derive::{Deserialize, Serialize};
+// Local imports
+use crate::schema::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct StoredDeniedAddress {
+    pub address: Vec<u8>,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "address_denylist"]
+pub struct NewDeniedAddress {
+    pub address: Vec<u8>,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable)]
+pub struct StoredDeniedTx {
+    pub id: i64,
+    pub tx_hash: Vec<u8>,
+    pub address: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "denied_txs"]
+pub struct NewDeniedTx {
+    pub tx_hash: Vec<u8>,
+    pub address: Vec<u8>,
+}
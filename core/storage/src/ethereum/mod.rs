@@ -1,21 +1,24 @@
 // Built-in deps
 use std::{collections::VecDeque, convert::TryFrom, str::FromStr};
 // External imports
-use diesel::dsl::{insert_into, update};
+use diesel::dsl::{delete, insert_into, update};
 use diesel::prelude::*;
 use num::BigUint;
-use web3::types::{H256, U256};
+use web3::types::{Address, H256, U256};
 // Workspace imports
 use models::{
     ethereum::{ETHOperation, InsertedOperationResponse, OperationType},
-    Operation,
+    node::{BlockNumber, PriorityOp},
+    ActionType, Operation,
 };
 // Local imports
 use self::records::{
-    ETHBinding, ETHParams, ETHStats, ETHTxHash, NewETHBinding, NewETHOperation, NewETHTxHash,
-    StorageETHOperation,
+    ETHBinding, ETHParams, ETHQueueStats, ETHStats, ETHTxHash, NewETHBinding, NewETHOperation,
+    NewETHTxHash, NewPendingWithdrawal, NewPriorityQueueOp, StorageETHOperation,
+    StoredPendingWithdrawal, StoredPriorityQueueOp,
 };
 use crate::chain::operations::records::StoredOperation;
+use crate::notifications::{NotificationsSchema, OperationNotification};
 use crate::schema::*;
 use crate::utils::StoredBigUint;
 use crate::StorageProcessor;
@@ -217,13 +220,32 @@ impl<'a> EthereumSchema<'a> {
         Ok(hash_entry.eth_op_id)
     }
 
+    /// Loads the signed Ethereum transaction with the provided hash. Returns `None` if the
+    /// transaction is unknown, or it was stored without the signed data.
+    pub fn load_signed_tx(&self, hash: &H256) -> QueryResult<Option<Vec<u8>>> {
+        let hash_entry = eth_tx_hashes::table
+            .filter(eth_tx_hashes::tx_hash.eq(hash.as_bytes()))
+            .first::<ETHTxHash>(self.0.conn())
+            .optional()?;
+
+        Ok(hash_entry.and_then(|entry| entry.signed_raw_tx))
+    }
+
     /// Adds a tx hash entry associated with some Ethereum operation to the database.
-    pub fn add_hash_entry(&self, eth_op_id: i64, hash: &H256) -> QueryResult<()> {
+    /// Signed transaction is stored along with the hash, so it can be sent again
+    /// after the restart.
+    pub fn add_hash_entry(
+        &self,
+        eth_op_id: i64,
+        hash: &H256,
+        signed_raw_tx: &[u8],
+    ) -> QueryResult<()> {
         self.0.conn().transaction(|| {
             // Insert the new hash entry.
             let hash_entry = NewETHTxHash {
                 eth_op_id,
                 tx_hash: hash.as_bytes().to_vec(),
+                signed_raw_tx: Some(signed_raw_tx.to_vec()),
             };
             let inserted_hashes_rows = insert_into(eth_tx_hashes::table)
                 .values(&hash_entry)
@@ -257,6 +279,16 @@ impl<'a> EthereumSchema<'a> {
         })
     }
 
+    /// Changes the nonce of the unconfirmed operation. Used when the previously assigned
+    /// nonce can't be used anymore (e.g. it was consumed by another transaction).
+    pub fn update_eth_tx_nonce(&self, eth_op_id: i64, new_nonce: i64) -> QueryResult<()> {
+        update(eth_operations::table.filter(eth_operations::id.eq(eth_op_id)))
+            .set(eth_operations::nonce.eq(new_nonce))
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
     /// Updates the stats counter with the new operation reported.
     /// This method should be called once **per operation**. It means that if transaction
     /// for some operation was stuck, and another transaction was created for it, this method
@@ -330,6 +362,19 @@ impl<'a> EthereumSchema<'a> {
             .map(ETHStats::from)
     }
 
+    /// Loads the amount of the operations waiting to be sent and confirmed on Ethereum.
+    pub fn load_queue_stats(&self) -> QueryResult<ETHQueueStats> {
+        diesel::sql_query(
+            " \
+            SELECT \
+                (SELECT COUNT(*) FROM operations o WHERE NOT confirmed \
+                    AND NOT EXISTS (SELECT * FROM eth_ops_binding WHERE op_id = o.id)) \
+                    AS queued_ops, \
+                (SELECT COUNT(*) FROM eth_operations WHERE NOT confirmed) AS unconfirmed_txs",
+        )
+        .get_result(self.0.conn())
+    }
+
     /// Marks the stored Ethereum transaction as confirmed (and thus the associated `Operation`
     /// is marked as confirmed as well).
     pub fn confirm_eth_tx(&self, hash: &H256) -> QueryResult<()> {
@@ -369,12 +414,149 @@ impl<'a> EthereumSchema<'a> {
                     .set(operations::confirmed.eq(true))
                     .execute(self.0.conn())
                     .map(drop)?;
+
+                // Verify operations are announced only once they're confirmed on Ethereum.
+                if op.action_type == ActionType::VERIFY.to_string() {
+                    NotificationsSchema(self.0).notify_operation(OperationNotification::new(
+                        op.block_number as u32,
+                        ActionType::VERIFY,
+                    ))?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Reverts the confirmation of the stored Ethereum operation (and the associated
+    /// `Operation`), e.g. once its transaction was removed from the chain by a reorg.
+    pub fn revert_eth_tx_confirmation(&self, eth_op_id: i64) -> QueryResult<()> {
+        self.0.conn().transaction(|| {
+            update(eth_operations::table.filter(eth_operations::id.eq(eth_op_id)))
+                .set((
+                    eth_operations::confirmed.eq(false),
+                    eth_operations::final_hash.eq(None::<Vec<u8>>),
+                ))
+                .execute(self.0.conn())?;
+
+            let binding: Option<ETHBinding> = eth_ops_binding::table
+                .filter(eth_ops_binding::eth_op_id.eq(eth_op_id))
+                .first::<ETHBinding>(self.0.conn())
+                .optional()?;
+
+            if let Some(binding) = binding {
+                update(operations::table.filter(operations::id.eq(binding.op_id)))
+                    .set(operations::confirmed.eq(false))
+                    .execute(self.0.conn())?;
             }
 
             Ok(())
         })
     }
 
+    /// Stores the amount of gas actually used by the confirmed Ethereum transaction.
+    pub fn report_gas_used(&self, hash: &H256, gas_used: U256) -> QueryResult<()> {
+        self.0.conn().transaction(|| {
+            let eth_op_id = self.get_eth_op_id(hash)?;
+            let gas_used = i64::try_from(gas_used).expect("Can't convert U256 to i64");
+
+            update(eth_operations::table.filter(eth_operations::id.eq(eth_op_id)))
+                .set(eth_operations::gas_used.eq(Some(gas_used)))
+                .execute(self.0.conn())?;
+
+            Ok(())
+        })
+    }
+
+    /// Calculates the average amount of gas used by the latest `window` confirmed
+    /// Ethereum operations of the given type.
+    /// Returns `None` if there are no operations with known gas usage.
+    pub fn load_average_gas_used(
+        &self,
+        op_type: OperationType,
+        window: usize,
+    ) -> QueryResult<Option<U256>> {
+        let gas_used: Vec<Option<i64>> = eth_operations::table
+            .filter(eth_operations::op_type.eq(op_type.to_string()))
+            .filter(eth_operations::gas_used.is_not_null())
+            .order(eth_operations::id.desc())
+            .limit(window as i64)
+            .select(eth_operations::gas_used)
+            .load(self.0.conn())?;
+
+        if gas_used.is_empty() {
+            return Ok(None);
+        }
+
+        let total: i64 = gas_used.iter().map(|gas| gas.unwrap_or_default()).sum();
+        Ok(Some(U256::from(total as u64 / gas_used.len() as u64)))
+    }
+
+    /// Stores the priority operations observed on Ethereum, so the priority queue
+    /// can be restored after restart without scanning the Ethereum blocks again.
+    /// Already stored operations are ignored.
+    pub fn save_priority_ops(&self, ops: &[PriorityOp]) -> QueryResult<()> {
+        let new_ops: Vec<_> = ops
+            .iter()
+            .map(|op| NewPriorityQueueOp {
+                serial_id: op.serial_id as i64,
+                eth_block: op.eth_block as i64,
+                operation: serde_json::to_value(op).expect("Can't serialize priority op"),
+            })
+            .collect();
+
+        insert_into(eth_priority_queue::table)
+            .values(&new_ops)
+            .on_conflict_do_nothing()
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Loads the stored priority operations ordered by their serial ID.
+    pub fn load_priority_ops(&self) -> QueryResult<Vec<PriorityOp>> {
+        let stored_ops: Vec<StoredPriorityQueueOp> = eth_priority_queue::table
+            .order(eth_priority_queue::serial_id.asc())
+            .load(self.0.conn())?;
+
+        let ops = stored_ops
+            .into_iter()
+            .map(|stored| {
+                serde_json::from_value(stored.operation).expect("Can't deserialize priority op")
+            })
+            .collect();
+
+        Ok(ops)
+    }
+
+    /// Removes the stored priority operations that were already executed.
+    /// Returns the amount of removed operations.
+    pub fn remove_executed_priority_ops(&self) -> QueryResult<usize> {
+        self.0.conn().transaction(|| {
+            let last_executed_serial_id: Option<i64> = executed_priority_operations::table
+                .select(diesel::dsl::max(
+                    executed_priority_operations::priority_op_serialid,
+                ))
+                .first(self.0.conn())?;
+
+            match last_executed_serial_id {
+                Some(serial_id) => delete(
+                    eth_priority_queue::table.filter(eth_priority_queue::serial_id.le(serial_id)),
+                )
+                .execute(self.0.conn()),
+                None => Ok(0),
+            }
+        })
+    }
+
+    /// Removes the stored priority operations observed in the blocks after the provided one,
+    /// e.g. because these blocks were removed from the chain by a reorg.
+    /// Returns the amount of removed operations.
+    pub fn remove_priority_ops_after_block(&self, eth_block: u64) -> QueryResult<usize> {
+        delete(eth_priority_queue::table.filter(eth_priority_queue::eth_block.gt(eth_block as i64)))
+            .execute(self.0.conn())
+    }
+
     /// Obtains the next nonce to use and updates the corresponding entry in the database
     /// for the next invocation.
     ///
@@ -395,6 +577,126 @@ impl<'a> EthereumSchema<'a> {
         Ok(old_nonce_value)
     }
 
+    /// Loads the nonce to be assigned to the next operation without incrementing it.
+    pub fn load_next_nonce(&self) -> QueryResult<i64> {
+        let params: ETHParams = eth_parameters::table.first(self.0.conn())?;
+
+        Ok(params.nonce)
+    }
+
+    /// Overrides the nonce to be assigned to the next operation, so it matches
+    /// the actual nonce of the operator account.
+    pub fn update_next_nonce(&self, nonce: i64) -> QueryResult<()> {
+        update(eth_parameters::table.filter(eth_parameters::id.eq(true)))
+            .set(eth_parameters::nonce.eq(nonce))
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Loads the address of the operator account currently used to send the transactions.
+    pub fn load_operator_address(&self) -> QueryResult<Option<Address>> {
+        let params: ETHParams = eth_parameters::table.first(self.0.conn())?;
+
+        Ok(params
+            .operator_address
+            .map(|address| Address::from_slice(&address)))
+    }
+
+    /// Updates the address of the operator account used to send the transactions.
+    pub fn update_operator_address(&self, address: &Address) -> QueryResult<()> {
+        update(eth_parameters::table.filter(eth_parameters::id.eq(true)))
+            .set(eth_parameters::operator_address.eq(address.as_bytes().to_vec()))
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Loads the last Ethereum block which events were processed by the Ethereum watcher.
+    pub fn load_last_processed_eth_block(&self) -> QueryResult<Option<u64>> {
+        let params: ETHParams = eth_parameters::table.first(self.0.conn())?;
+
+        Ok(params.last_processed_eth_block.map(|block| block as u64))
+    }
+
+    /// Updates the last Ethereum block which events were processed by the Ethereum watcher.
+    pub fn update_last_processed_eth_block(&self, eth_block: u64) -> QueryResult<()> {
+        update(eth_parameters::table.filter(eth_parameters::id.eq(true)))
+            .set(eth_parameters::last_processed_eth_block.eq(eth_block as i64))
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Adds the withdrawals of the verified block to the end of the pending withdrawals queue.
+    /// Withdrawals are expected to be provided in the order of the block operations.
+    /// Nothing is added if the withdrawals of the block are already stored (e.g. the verify
+    /// transaction is confirmed again after a chain reorganization).
+    pub fn add_pending_withdrawals(
+        &self,
+        block_number: BlockNumber,
+        withdrawal_hashes: &[Vec<u8>],
+    ) -> QueryResult<()> {
+        let already_stored = pending_withdrawals::table
+            .filter(pending_withdrawals::block_number.eq(i64::from(block_number)))
+            .count()
+            .get_result::<i64>(self.0.conn())?
+            > 0;
+        if already_stored {
+            return Ok(());
+        }
+
+        let new_withdrawals: Vec<_> = withdrawal_hashes
+            .iter()
+            .map(|withdrawal_hash| NewPendingWithdrawal {
+                withdrawal_hash: withdrawal_hash.clone(),
+                block_number: i64::from(block_number),
+            })
+            .collect();
+        insert_into(pending_withdrawals::table)
+            .values(&new_withdrawals)
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Marks up to `max_withdrawals` oldest not completed withdrawals as completed by the
+    /// `completeWithdrawals` transaction with the provided hash, the same way the contract
+    /// processes its pending withdrawals queue. Returns the amount of completed withdrawals.
+    pub fn complete_withdrawals(
+        &self,
+        complete_tx_hash: &H256,
+        max_withdrawals: u64,
+    ) -> QueryResult<usize> {
+        self.0.conn().transaction(|| {
+            let withdrawal_ids: Vec<i64> = pending_withdrawals::table
+                .select(pending_withdrawals::id)
+                .filter(pending_withdrawals::complete_tx_hash.is_null())
+                .order(pending_withdrawals::id.asc())
+                .limit(max_withdrawals as i64)
+                .load(self.0.conn())?;
+
+            let withdrawals =
+                pending_withdrawals::table.filter(pending_withdrawals::id.eq_any(withdrawal_ids));
+            update(withdrawals)
+                .set(pending_withdrawals::complete_tx_hash.eq(complete_tx_hash.as_bytes().to_vec()))
+                .execute(self.0.conn())
+        })
+    }
+
+    /// Loads the hash of the `completeWithdrawals` transaction that completed the withdrawal.
+    /// Returns `None` if the withdrawal is unknown or is not completed yet.
+    pub fn load_withdrawal_complete_tx(&self, withdrawal_hash: &[u8]) -> QueryResult<Option<H256>> {
+        let withdrawal = pending_withdrawals::table
+            .filter(pending_withdrawals::withdrawal_hash.eq(withdrawal_hash))
+            .first::<StoredPendingWithdrawal>(self.0.conn())
+            .optional()?;
+
+        Ok(withdrawal
+            .and_then(|withdrawal| withdrawal.complete_tx_hash)
+            .map(|hash| H256::from_slice(&hash)))
+    }
+
     /// Method that internally initializes the `eth_parameters` table.
     /// Since in db tests the database is empty, we must provide a possibility
     /// to initialize required db fields.
@@ -1,4 +1,7 @@
 // External imports
+use chrono::prelude::*;
+use diesel::sql_types::BigInt;
+use serde_json::value::Value;
 // Workspace imports
 // Local imports
 use crate::schema::*;
@@ -15,6 +18,7 @@ pub struct StorageETHOperation {
     pub final_hash: Option<Vec<u8>>,
     pub last_deadline_block: i64,
     pub last_used_gas_price: StoredBigUint,
+    pub gas_used: Option<i64>,
 }
 
 #[derive(Debug, Clone, Queryable, QueryableByName, PartialEq)]
@@ -23,6 +27,7 @@ pub struct ETHTxHash {
     pub id: i64,
     pub eth_op_id: i64,
     pub tx_hash: Vec<u8>,
+    pub signed_raw_tx: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Clone, Insertable, PartialEq)]
@@ -30,6 +35,7 @@ pub struct ETHTxHash {
 pub struct NewETHTxHash {
     pub eth_op_id: i64,
     pub tx_hash: Vec<u8>,
+    pub signed_raw_tx: Option<Vec<u8>>,
 }
 
 #[derive(Debug, Insertable, PartialEq)]
@@ -66,6 +72,8 @@ pub struct ETHParams {
     pub commit_ops: i64,
     pub verify_ops: i64,
     pub withdraw_ops: i64,
+    pub operator_address: Option<Vec<u8>>,
+    pub last_processed_eth_block: Option<i64>,
 }
 
 /// A slice of `ETHParams` structure with only stats part in it.
@@ -85,3 +93,46 @@ impl From<ETHParams> for ETHStats {
         }
     }
 }
+
+/// State of the `eth_sender` queue.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ETHQueueStats {
+    /// Amount of the operations which aren't sent to Ethereum yet.
+    #[sql_type = "BigInt"]
+    pub queued_ops: i64,
+    /// Amount of the sent Ethereum transactions which aren't confirmed yet.
+    #[sql_type = "BigInt"]
+    pub unconfirmed_txs: i64,
+}
+
+#[derive(Debug, Insertable, PartialEq)]
+#[table_name = "eth_priority_queue"]
+pub struct NewPriorityQueueOp {
+    pub serial_id: i64,
+    pub eth_block: i64,
+    pub operation: Value,
+}
+
+#[derive(Debug, Queryable, QueryableByName, PartialEq)]
+#[table_name = "eth_priority_queue"]
+pub struct StoredPriorityQueueOp {
+    pub serial_id: i64,
+    pub eth_block: i64,
+    pub operation: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Queryable, PartialEq)]
+pub struct StoredPendingWithdrawal {
+    pub id: i64,
+    pub withdrawal_hash: Vec<u8>,
+    pub block_number: i64,
+    pub complete_tx_hash: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Insertable, PartialEq)]
+#[table_name = "pending_withdrawals"]
+pub struct NewPendingWithdrawal {
+    pub withdrawal_hash: Vec<u8>,
+    pub block_number: i64,
+}
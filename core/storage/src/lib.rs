@@ -33,6 +33,7 @@
 //! - config, for the server config.
 //! - data_restore, for the data_restore crate.
 //! - ethereum, for the data associated with the Ethereum blockchain.
+//! - notifications, for broadcasting the new operations to the other database clients.
 //! - prover, for the data on prover jobs, proofs, etc.
 //! - tokens, for storing and loading known tokens.
 //! - chain - the biggest one, which includes several schemas for the ZKSync sidechain itself.
@@ -87,11 +88,15 @@ mod schema;
 mod tests;
 
 pub mod chain;
+pub mod compression;
 pub mod config;
 pub mod connection;
 pub mod data_restore;
+pub mod denylist;
 pub mod diff;
 pub mod ethereum;
+pub mod notifications;
+pub mod pagination;
 pub mod prover;
 pub mod tokens;
 pub mod utils;
@@ -142,11 +147,21 @@ impl StorageProcessor {
         data_restore::DataRestoreSchema(self)
     }
 
+    /// Gains access to the `Denylist` schema.
+    pub fn denylist_schema(&self) -> denylist::DenylistSchema<'_> {
+        denylist::DenylistSchema(self)
+    }
+
     /// Gains access to the `Ethereum` schema.
     pub fn ethereum_schema(&self) -> ethereum::EthereumSchema<'_> {
         ethereum::EthereumSchema(self)
     }
 
+    /// Gains access to the `Notifications` schema.
+    pub fn notifications_schema(&self) -> notifications::NotificationsSchema<'_> {
+        notifications::NotificationsSchema(self)
+    }
+
     /// Gains access to the `Prover` schema.
     pub fn prover_schema(&self) -> prover::ProverSchema<'_> {
         prover::ProverSchema(self)
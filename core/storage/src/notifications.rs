@@ -0,0 +1,130 @@
+//! Notifications about the new operations stored in the database.
+//!
+//! Whenever a block is committed or its verification is confirmed on Ethereum, a
+//! notification is published to the Postgres channel via `NOTIFY`. Since `NOTIFY` is
+//! transactional, the notification is delivered only once the changes are actually
+//! committed to the database.
+//!
+//! This allows several API servers (which don't share memory with the server core) to
+//! `LISTEN` to the channel and serve subscriptions against the same database.
+
+// Built-in deps
+use std::env;
+use std::time::Duration;
+// External imports
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use fallible_iterator::FallibleIterator;
+use postgres::{Client, NoTls};
+// Workspace imports
+use models::{node::BlockNumber, ActionType};
+// Local imports
+use crate::StorageProcessor;
+
+/// Name of the Postgres channel used to broadcast the operation notifications.
+pub const OPERATIONS_CHANNEL: &str = "zksync_operations";
+
+/// Notification about the operation stored in the database.
+#[derive(Clone, Copy, PartialEq)]
+pub struct OperationNotification {
+    pub block_number: BlockNumber,
+    pub action: ActionType,
+}
+
+impl OperationNotification {
+    pub fn new(block_number: BlockNumber, action: ActionType) -> Self {
+        Self {
+            block_number,
+            action,
+        }
+    }
+
+    /// Encodes the notification as a `NOTIFY` payload, e.g. `COMMIT:42`.
+    pub fn to_payload(&self) -> String {
+        format!("{}:{}", self.action.to_string(), self.block_number)
+    }
+
+    /// Decodes the notification from the `NOTIFY` payload.
+    /// Returns `None` if payload is malformed.
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        let mut parts = payload.split(':');
+        let action = parts.next()?.parse().ok()?;
+        let block_number = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self::new(block_number, action))
+    }
+}
+
+/// Notifications schema publishes the notifications about stored operations.
+#[derive(Debug)]
+pub struct NotificationsSchema<'a>(pub &'a StorageProcessor);
+
+impl<'a> NotificationsSchema<'a> {
+    /// Publishes the notification about the operation.
+    /// If invoked within a database transaction, the notification will be
+    /// delivered only after the transaction is committed.
+    pub fn notify_operation(&self, notification: OperationNotification) -> QueryResult<()> {
+        // Payload consists of the action type and the block number only,
+        // so it's safe to format it into the query directly.
+        self.0.conn().batch_execute(&format!(
+            "NOTIFY {}, '{}'",
+            OPERATIONS_CHANNEL,
+            notification.to_payload()
+        ))
+    }
+}
+
+/// Dedicated connection listening to the operation notifications.
+///
+/// `diesel` is not capable of receiving asynchronous notifications, thus the
+/// listener uses a separate connection established via the `postgres` crate.
+pub struct OperationsListener {
+    client: Client,
+}
+
+impl OperationsListener {
+    /// Establishes a new connection to the database (configured via the
+    /// `DATABASE_URL` environment variable) and subscribes to the operations channel.
+    pub fn connect() -> Result<Self, failure::Error> {
+        let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let mut client = Client::connect(&database_url, NoTls)?;
+        client.batch_execute(&format!("LISTEN {}", OPERATIONS_CHANNEL))?;
+
+        Ok(Self { client })
+    }
+
+    /// Blocks the thread until at least one notification is received or the
+    /// timeout is reached. Returns all the notifications received so far.
+    pub fn wait_for_notifications(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<Vec<OperationNotification>, failure::Error> {
+        let mut notifications = self.client.notifications();
+        let mut payloads = Vec::new();
+
+        if let Some(notification) = notifications.timeout_iter(timeout).next()? {
+            payloads.push(notification.payload().to_string());
+        }
+        // Collect the notifications that arrived in the meantime without blocking.
+        let mut pending = notifications.iter();
+        while let Some(notification) = pending.next()? {
+            payloads.push(notification.payload().to_string());
+        }
+
+        let notifications = payloads
+            .iter()
+            .filter_map(|payload| {
+                let notification = OperationNotification::from_payload(payload);
+                if notification.is_none() {
+                    log::warn!("Malformed operation notification payload: {}", payload);
+                }
+                notification
+            })
+            .collect();
+
+        Ok(notifications)
+    }
+}
@@ -0,0 +1,127 @@
+//! Helpers for the keyset pagination.
+//!
+//! Unlike `OFFSET`-based pagination, which makes the database to scan and drop all the
+//! rows preceding the requested page, keyset pagination filters rows relatively to the
+//! key of the last seen row. This keeps the cost of loading a page independent of its
+//! depth, and the results are not shifted when new rows are inserted meanwhile.
+//!
+//! Operations in the chain are identified by the `(block_number, block_index)` pair,
+//! which is represented by the `OperationCursor` structure, while blocks are identified
+//! by their numbers.
+
+// Built-in deps
+use std::fmt;
+use std::str::FromStr;
+// External imports
+use failure::format_err;
+
+/// Direction to perform search of transactions to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchDirection {
+    /// Find transactions older than specified one.
+    Older,
+    /// Find transactions newer than specified one.
+    Newer,
+}
+
+impl SearchDirection {
+    /// Returns the SQL comparison operator selecting the rows in this direction.
+    pub fn sql_operator(self) -> &'static str {
+        match self {
+            SearchDirection::Older => "<", // Older blocks have lesser block ID.
+            SearchDirection::Newer => ">", // Newer blocks have greater block ID.
+        }
+    }
+
+    /// Creates an SQL condition selecting the rows which `column` is located
+    /// in this direction relatively to `key`, e.g. blocks older than the provided one.
+    pub fn sql_filter(self, column: &str, key: u64) -> String {
+        format!("{} {} {}", column, self.sql_operator(), key)
+    }
+
+    /// Returns the SQL sort order yielding the rows closest to the key first.
+    pub fn sql_order(self) -> &'static str {
+        match self {
+            SearchDirection::Older => "desc",
+            SearchDirection::Newer => "asc",
+        }
+    }
+}
+
+/// Position of the operation in the chain, used as a key for the pagination.
+///
+/// Cursor is encoded as a `"<block_number>,<block_index>"` string, the same way
+/// as the `tx_id` field of the transactions history items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct OperationCursor {
+    pub block_number: u64,
+    pub block_index: u64,
+}
+
+impl OperationCursor {
+    pub fn new(block_number: u64, block_index: u64) -> Self {
+        Self {
+            block_number,
+            block_index,
+        }
+    }
+
+    /// Creates an SQL condition selecting the rows located in the provided
+    /// direction relatively to the cursor.
+    ///
+    /// `block_column` and `index_column` are the names of the columns storing
+    /// the block number and the index of the operation in the block respectively.
+    pub fn sql_filter(
+        &self,
+        direction: SearchDirection,
+        block_column: &str,
+        index_column: &str,
+    ) -> String {
+        format!(
+            "({block_column} {sign} {block_number} or ({block_column} = {block_number} and {index_column} {sign} {block_index}))",
+            block_column = block_column,
+            index_column = index_column,
+            sign = direction.sql_operator(),
+            block_number = self.block_number,
+            block_index = self.block_index,
+        )
+    }
+
+    /// Creates an SQL `order by` clause listing the rows located in the provided
+    /// direction starting from the closest to the cursor, so the page selected
+    /// with `limit` is adjacent to the cursor.
+    pub fn sql_order(direction: SearchDirection, block_column: &str, index_column: &str) -> String {
+        format!(
+            "{block_column} {order}, {index_column} {order}",
+            block_column = block_column,
+            index_column = index_column,
+            order = direction.sql_order(),
+        )
+    }
+}
+
+impl fmt::Display for OperationCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.block_number, self.block_index)
+    }
+}
+
+impl FromStr for OperationCursor {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(',')
+            .map(|part| part.trim().parse::<u64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format_err!("Incorrect cursor '{}': {}", s, err))?;
+
+        match parts.as_slice() {
+            [block_number, block_index] => Ok(Self::new(*block_number, *block_index)),
+            _ => Err(format_err!(
+                "Incorrect cursor '{}': expected <block_number>,<block_index>",
+                s
+            )),
+        }
+    }
+}
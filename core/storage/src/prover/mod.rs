@@ -4,13 +4,23 @@ use std::time;
 use diesel::{
     dsl::{insert_into, now, sql_query},
     prelude::*,
+    sql_types::{Array, BigInt, Integer, Jsonb, Nullable, Text},
 };
 // Workspace imports
+use models::config_options::ProverJobPolicy;
 use models::node::BlockNumber;
-use models::prover_utils::EncodedProofPlonk;
+use models::prover_utils::{EncodedProofPlonk, ProofTimings};
 // Local imports
-use self::records::{ActiveProver, IntegerNumber, NewProof, ProverRun, StoredProof};
-use crate::{chain::block::BlockSchema, StorageProcessor};
+use self::records::{
+    ActiveProver, IntegerNumber, NewProof, NewProofTimings, NewProverToken, NewRejectedProof,
+    ProofTimingsStats, ProverJobOutcome, ProverRegistryEntry, ProverRun, ProvingQueueStats,
+    StorageBlockWitness, StoredProof, StoredProverToken, StoredRejectedProof, StoredWitnessChunk,
+};
+use crate::{
+    chain::block::BlockSchema,
+    compression::{compress, decompress_stored},
+    StorageProcessor,
+};
 
 pub mod records;
 
@@ -23,8 +33,6 @@ impl<'a> ProverSchema<'a> {
     /// Returns the amount of blocks which await for proof, but have
     /// no assigned prover run.
     pub fn unstarted_jobs_count(&self) -> QueryResult<u64> {
-        use crate::schema::prover_runs::dsl::*;
-
         self.0.conn().transaction(|| {
             let mut last_committed_block = BlockSchema(&self.0).get_last_committed_block()? as u64;
 
@@ -35,10 +43,20 @@ impl<'a> ProverSchema<'a> {
 
             let last_verified_block = BlockSchema(&self.0).get_last_verified_block()? as u64;
 
-            let num_ongoing_jobs = prover_runs
-                .filter(block_number.gt(last_verified_block as i64))
-                .count()
-                .first::<i64>(self.0.conn())? as u64;
+            // Blocks with the expired leases await for another prover, unless they're proved.
+            // The same block may be leased several times, so the blocks are counted only once.
+            let query = format!(
+                " \
+                SELECT COUNT(DISTINCT block_number) AS integer_value FROM prover_runs r \
+                WHERE block_number > {last_verified_block} \
+                    AND (lease_expires_at > now() \
+                        OR EXISTS (SELECT * FROM proofs WHERE block_number = r.block_number)) \
+                ",
+                last_verified_block = last_verified_block
+            );
+            let num_ongoing_jobs = diesel::sql_query(query)
+                .get_result::<IntegerNumber>(self.0.conn())?
+                .integer_value as u64;
 
             assert!(
                 last_verified_block + num_ongoing_jobs <= last_committed_block,
@@ -72,14 +90,51 @@ impl<'a> ProverSchema<'a> {
         })
     }
 
-    /// Given the block size, chooses the next block to prove for the certain prover.
-    /// Returns `None` if either there are no blocks of given size to prove, or
-    /// there is already an ongoing job for non-proved block.
+    /// Returns the state of the proving queue, used to decide how many provers are needed.
+    pub fn proving_queue_stats(&self) -> QueryResult<ProvingQueueStats> {
+        sql_query(
+            " \
+            WITH unproven_blocks AS ( \
+                SELECT block_number FROM operations o \
+                WHERE action_type = 'COMMIT' \
+                    AND block_number > (SELECT COALESCE(max(block_number),0) FROM operations \
+                        WHERE action_type = 'VERIFY') \
+                    AND NOT EXISTS \
+                        (SELECT * FROM proofs WHERE block_number = o.block_number) \
+            ) \
+            SELECT \
+                (SELECT COUNT(*) FROM unproven_blocks) AS queue_depth, \
+                (SELECT COUNT(DISTINCT block_number) FROM prover_runs \
+                    WHERE lease_expires_at > now() \
+                        AND block_number IN (SELECT block_number FROM unproven_blocks)) \
+                    AS jobs_in_progress, \
+                (SELECT EXTRACT(EPOCH FROM now() - min(created_at))::BIGINT FROM blocks \
+                    WHERE number IN (SELECT block_number FROM unproven_blocks)) \
+                    AS oldest_unproven_block_age_secs, \
+                (SELECT AVG(total_ms)::BIGINT FROM proof_timings \
+                    WHERE created_at > now() - interval '1 day') AS avg_proof_ms, \
+                (SELECT COUNT(*) FROM active_provers \
+                    WHERE stopped_at IS NULL AND drain_requested_at IS NULL) AS active_provers",
+        )
+        .get_result(self.0.conn())
+    }
+
+    /// Given the block size, chooses the next block to prove for the certain prover,
+    /// and leases the job to it for the `prover_timeout`.
+    /// Blocks are chosen in the order defined by the `job_policy`, and only the blocks
+    /// proposed under the `circuit_version` of the prover are chosen.
+    /// The `deprioritized` provers get the blocks in the reverse order, so the most urgent
+    /// blocks are left for the reliable provers.
+    /// Returns `None` if either there are no blocks of given size with generated witness
+    /// to prove, or all such blocks are leased to the other provers.
     pub fn prover_run_for_next_commit(
         &self,
         worker_: &str,
         prover_timeout: time::Duration,
         block_size: usize,
+        job_policy: ProverJobPolicy,
+        circuit_version: &str,
+        deprioritized: bool,
     ) -> QueryResult<Option<ProverRun>> {
         // Select the block to prove.
         self
@@ -91,7 +146,10 @@ impl<'a> ProverSchema<'a> {
                 // Find the block that satisfies the following criteria:
                 // - Block number is greater than the index of last verified block.
                 // - There is no proof for block.
-                // - Either there is no ongoing job for the block, or the job exceeded the timeout.
+                // - Witness for the block is already generated, so the prover can start right away.
+                // - Either there is no ongoing job for the block, or the job lease has expired.
+                // - Block was proposed under the circuit version of the prover. Blocks proposed
+                //   before the circuit versions were recorded can be proved by any prover.
                 let query = format!(" \
                     WITH unsized_blocks AS ( \
                         SELECT * FROM operations o \
@@ -100,30 +158,51 @@ impl<'a> ProverSchema<'a> {
                                 (SELECT COALESCE(max(block_number),0) FROM operations WHERE action_type = 'VERIFY') \
                             AND NOT EXISTS \
                                 (SELECT * FROM proofs WHERE block_number = o.block_number) \
+                            AND EXISTS \
+                                (SELECT * FROM block_witness WHERE block_number = o.block_number) \
                             AND NOT EXISTS \
                                 (SELECT * FROM prover_runs \
-                                    WHERE block_number = o.block_number AND (now() - updated_at) < interval '{prover_timeout_secs} seconds') \
+                                    WHERE block_number = o.block_number AND lease_expires_at > now()) \
+                            AND COALESCE( \
+                                (SELECT version FROM circuit_versions \
+                                    WHERE first_block <= o.block_number \
+                                    ORDER BY first_block DESC LIMIT 1), \
+                                $1) = $1 \
                     ) \
-                    SELECT min(block_number) AS integer_value FROM unsized_blocks \
-                    INNER JOIN blocks \
-                        ON unsized_blocks.block_number = blocks.number AND blocks.block_size = {block_size} \
+                    SELECT block_number AS integer_value FROM ( \
+                        SELECT block_number, row_number() OVER (ORDER BY {order}) AS priority \
+                        FROM unsized_blocks \
+                        INNER JOIN blocks \
+                            ON unsized_blocks.block_number = blocks.number AND blocks.block_size = {block_size} \
+                    ) prioritized_blocks \
+                    ORDER BY priority {direction} \
+                    LIMIT 1 \
                     ",
-                    prover_timeout_secs=prover_timeout.as_secs(), block_size=block_size
+                    block_size=block_size,
+                    order=Self::job_order(job_policy),
+                    direction=if deprioritized { "DESC" } else { "ASC" },
                 );
 
                 // Return the index of such a block.
-                let job = diesel::sql_query(query).get_result::<Option<IntegerNumber>>(self.0.conn())?
-                .map(|i| i.integer_value as BlockNumber);
+                let job = diesel::sql_query(query)
+                    .bind::<Text, _>(circuit_version)
+                    .get_result::<IntegerNumber>(self.0.conn())
+                    .optional()?
+                    .map(|i| i.integer_value as BlockNumber);
 
                 // If there is a block to prove, create a job and store it
                 // in the `prover_runs` table; otherwise do nothing and return `None`.
                 if let Some(block_number_) = job {
-                    use crate::schema::prover_runs::dsl::*;
-                    let inserted: ProverRun = insert_into(prover_runs)
-                        .values(&vec![(
-                            block_number.eq(i64::from(block_number_)),
-                            worker.eq(worker_.to_string()),
-                        )])
+                    let query = format!(
+                        " \
+                        INSERT INTO prover_runs (block_number, worker, lease_expires_at) \
+                        VALUES ($1, $2, now() + interval '{prover_timeout_secs} seconds') \
+                        RETURNING *",
+                        prover_timeout_secs = prover_timeout.as_secs()
+                    );
+                    let inserted: ProverRun = diesel::sql_query(query)
+                        .bind::<BigInt, _>(i64::from(block_number_))
+                        .bind::<Text, _>(worker_)
                         .get_result(self.0.conn())?;
                     Ok(Some(inserted))
                 } else {
@@ -132,15 +211,79 @@ impl<'a> ProverSchema<'a> {
             })
     }
 
-    /// Updates the state of ongoing prover job.
-    pub fn record_prover_is_working(&self, job_id: i32) -> QueryResult<()> {
-        use crate::schema::prover_runs::dsl::*;
+    /// Returns the `ORDER BY` clause of the blocks to prove for the job policy.
+    fn job_order(job_policy: ProverJobPolicy) -> String {
+        match job_policy {
+            ProverJobPolicy::Fifo => "block_number".to_string(),
+            ProverJobPolicy::WithdrawalsFirst { max_age } => format!(
+                " \
+                COALESCE(blocks.created_at < now() - interval '{max_age_secs} seconds', false) DESC, \
+                ( \
+                    EXISTS (SELECT * FROM executed_transactions \
+                        WHERE block_number = unsized_blocks.block_number \
+                            AND success AND tx->>'type' = 'Withdraw') \
+                    OR EXISTS (SELECT * FROM executed_priority_operations \
+                        WHERE block_number = unsized_blocks.block_number \
+                            AND operation->>'type' = 'FullExit') \
+                ) DESC, \
+                block_number",
+                max_age_secs = max_age.as_secs()
+            ),
+        }
+    }
 
-        let target = prover_runs.filter(id.eq(job_id));
-        diesel::update(target)
-            .set(updated_at.eq(now))
-            .execute(self.0.conn())
-            .map(|_| ())
+    /// Records the circuit version the blocks starting from `first_block` are proposed under.
+    /// Does nothing if the version is already recorded.
+    pub fn record_circuit_version(
+        &self,
+        circuit_version: &str,
+        first_block_: BlockNumber,
+    ) -> QueryResult<()> {
+        use crate::schema::circuit_versions::dsl::*;
+
+        insert_into(circuit_versions)
+            .values((
+                version.eq(circuit_version),
+                first_block.eq(i64::from(first_block_)),
+            ))
+            .on_conflict_do_nothing()
+            .execute(self.0.conn())?;
+        Ok(())
+    }
+
+    /// Returns the circuit version the block was proposed under.
+    /// Returns `None` if the block was proposed before the circuit versions were recorded.
+    pub fn block_circuit_version(&self, block: BlockNumber) -> QueryResult<Option<String>> {
+        use crate::schema::circuit_versions::dsl::*;
+
+        circuit_versions
+            .filter(first_block.le(i64::from(block)))
+            .order(first_block.desc())
+            .select(version)
+            .first(self.0.conn())
+            .optional()
+    }
+
+    /// Updates the state of ongoing prover job and renews its lease for the `prover_timeout`.
+    /// Returns `false` if the lease has already expired, so the block may be proved by
+    /// another prover, and the lease can't be renewed.
+    pub fn record_prover_is_working(
+        &self,
+        job_id: i32,
+        prover_timeout: time::Duration,
+    ) -> QueryResult<bool> {
+        let query = format!(
+            " \
+            UPDATE prover_runs \
+            SET updated_at = now(), \
+                lease_expires_at = now() + interval '{prover_timeout_secs} seconds' \
+            WHERE id = $1 AND lease_expires_at > now()",
+            prover_timeout_secs = prover_timeout.as_secs()
+        );
+        let updated = diesel::sql_query(query)
+            .bind::<Integer, _>(job_id)
+            .execute(self.0.conn())?;
+        Ok(updated > 0)
     }
 
     /// Adds a prover to the database.
@@ -155,6 +298,74 @@ impl<'a> ProverSchema<'a> {
         Ok(inserted.id)
     }
 
+    /// Records the registration of the prover in the prover registry, updating the reported
+    /// circuit version and capabilities of the already known worker.
+    pub fn record_prover_registration(
+        &self,
+        worker_: &str,
+        circuit_version: Option<&str>,
+        capabilities: &serde_json::Value,
+    ) -> QueryResult<()> {
+        sql_query(
+            " \
+            INSERT INTO prover_registry (worker, circuit_version, capabilities) \
+            VALUES ($1, $2, $3) \
+            ON CONFLICT (worker) DO UPDATE \
+            SET circuit_version = EXCLUDED.circuit_version, \
+                capabilities = EXCLUDED.capabilities, \
+                last_registered_at = now()",
+        )
+        .bind::<Text, _>(worker_)
+        .bind::<Nullable<Text>, _>(circuit_version)
+        .bind::<Jsonb, _>(capabilities)
+        .execute(self.0.conn())?;
+        Ok(())
+    }
+
+    /// Accounts the outcome of the prover job in the prover registry.
+    /// Workers which never registered are added to the registry.
+    pub fn record_prover_job_outcome(
+        &self,
+        worker_: &str,
+        outcome: ProverJobOutcome,
+    ) -> QueryResult<()> {
+        let query = format!(
+            " \
+            INSERT INTO prover_registry (worker, {column}) VALUES ($1, 1) \
+            ON CONFLICT (worker) DO UPDATE SET {column} = prover_registry.{column} + 1",
+            column = outcome.counter_column()
+        );
+        sql_query(query)
+            .bind::<Text, _>(worker_)
+            .execute(self.0.conn())?;
+        Ok(())
+    }
+
+    fn prover_registry_query(filter: &str) -> String {
+        format!(
+            " \
+            SELECT r.*, \
+                (SELECT AVG(total_ms)::BIGINT FROM proof_timings WHERE worker = r.worker) \
+                    AS avg_proving_ms \
+            FROM prover_registry r {filter} \
+            ORDER BY worker",
+            filter = filter
+        )
+    }
+
+    /// Loads all the provers which have ever registered, along with their job statistics.
+    pub fn load_prover_registry(&self) -> QueryResult<Vec<ProverRegistryEntry>> {
+        sql_query(Self::prover_registry_query("")).load(self.0.conn())
+    }
+
+    /// Loads the prover registry entry of the worker, if it's known.
+    pub fn prover_registry_entry(&self, worker_: &str) -> QueryResult<Option<ProverRegistryEntry>> {
+        sql_query(Self::prover_registry_query("WHERE worker = $1"))
+            .bind::<Text, _>(worker_)
+            .get_result(self.0.conn())
+            .optional()
+    }
+
     /// Gets a prover descriptor by its numeric ID.
     pub fn prover_by_id(&self, prover_id: i32) -> QueryResult<ActiveProver> {
         use crate::schema::active_provers::dsl::*;
@@ -180,6 +391,112 @@ impl<'a> ProverSchema<'a> {
             .map(|_| ())
     }
 
+    /// Requests the prover to finish its current job and stop.
+    /// Returns `false` if the prover is unknown or has been already stopped.
+    pub fn request_prover_drain(&self, prover_id: i32) -> QueryResult<bool> {
+        use crate::schema::active_provers::dsl::*;
+
+        let target = active_provers
+            .filter(id.eq(prover_id))
+            .filter(stopped_at.is_null());
+        let updated = diesel::update(target)
+            .set(drain_requested_at.eq(now))
+            .execute(self.0.conn())?;
+        Ok(updated > 0)
+    }
+
+    /// Checks whether the prover was requested to drain.
+    pub fn is_prover_draining(&self, prover_id: i32) -> QueryResult<bool> {
+        use crate::schema::active_provers::dsl::*;
+
+        let count: i64 = active_provers
+            .filter(id.eq(prover_id))
+            .filter(drain_requested_at.is_not_null())
+            .count()
+            .get_result(self.0.conn())?;
+        Ok(count > 0)
+    }
+
+    /// Checks whether all the running provers of the worker are draining,
+    /// so no new jobs must be given to it.
+    pub fn is_worker_draining(&self, worker_: &str) -> QueryResult<bool> {
+        use crate::schema::active_provers::dsl::*;
+
+        let running = active_provers
+            .filter(worker.eq(worker_))
+            .filter(stopped_at.is_null());
+        let running_count: i64 = running.count().get_result(self.0.conn())?;
+        let draining_count: i64 = running
+            .filter(drain_requested_at.is_not_null())
+            .count()
+            .get_result(self.0.conn())?;
+        Ok(running_count > 0 && running_count == draining_count)
+    }
+
+    /// Records the heartbeat of the working prover.
+    /// Returns `false` if the prover is unknown or has been already stopped (e.g. considered dead).
+    pub fn record_prover_heartbeat(&self, prover_id: i32) -> QueryResult<bool> {
+        use crate::schema::active_provers::dsl::*;
+
+        let target = active_provers
+            .filter(id.eq(prover_id))
+            .filter(stopped_at.is_null());
+        let updated = diesel::update(target)
+            .set(last_heartbeat_at.eq(now))
+            .execute(self.0.conn())?;
+        Ok(updated > 0)
+    }
+
+    /// Marks the provers which didn't send a heartbeat within the `heartbeat_timeout` as stopped,
+    /// and expires the leases of their jobs, so the blocks are reassigned to the other provers.
+    /// Returns the provers considered dead.
+    pub fn reap_dead_provers(
+        &self,
+        heartbeat_timeout: time::Duration,
+    ) -> QueryResult<Vec<ActiveProver>> {
+        self.0.conn().transaction(|| {
+            let query = format!(
+                " \
+                UPDATE active_provers SET stopped_at = now() \
+                WHERE stopped_at IS NULL \
+                    AND last_heartbeat_at < now() - interval '{timeout_secs} seconds' \
+                RETURNING *",
+                timeout_secs = heartbeat_timeout.as_secs()
+            );
+            let dead_provers: Vec<ActiveProver> = sql_query(query).load(self.0.conn())?;
+            if dead_provers.is_empty() {
+                return Ok(dead_provers);
+            }
+
+            // Jobs are leased by the worker name, so the jobs of the restarted prover
+            // registered with the same name are kept.
+            // Expired jobs are accounted as timed out in the prover registry.
+            let dead_workers: Vec<String> = dead_provers
+                .iter()
+                .map(|prover| prover.worker.clone())
+                .collect();
+            sql_query(
+                " \
+                WITH expired_runs AS ( \
+                    UPDATE prover_runs SET lease_expires_at = now() \
+                    WHERE lease_expires_at > now() \
+                        AND worker = ANY($1) \
+                        AND worker NOT IN \
+                            (SELECT worker FROM active_provers WHERE stopped_at IS NULL) \
+                    RETURNING worker \
+                ) \
+                INSERT INTO prover_registry (worker, timed_out_jobs) \
+                SELECT worker, COUNT(*) FROM expired_runs GROUP BY worker \
+                ON CONFLICT (worker) DO UPDATE \
+                SET timed_out_jobs = prover_registry.timed_out_jobs + EXCLUDED.timed_out_jobs",
+            )
+            .bind::<Array<Text>, _>(&dead_workers)
+            .execute(self.0.conn())?;
+
+            Ok(dead_provers)
+        })
+    }
+
     /// Stores the proof for a block.
     pub fn store_proof(
         &self,
@@ -202,4 +519,251 @@ impl<'a> ProverSchema<'a> {
             .get_result(self.0.conn())?;
         Ok(serde_json::from_value(stored.proof).unwrap())
     }
+
+    /// Stores the time spent by the prover on the proof computation stages for a block.
+    /// If the timings for the block are already stored, they won't be overwritten.
+    pub fn store_proof_timings(
+        &self,
+        block: BlockNumber,
+        worker_: &str,
+        timings: &ProofTimings,
+    ) -> QueryResult<()> {
+        use crate::schema::proof_timings::dsl::*;
+
+        let to_store = NewProofTimings {
+            block_number: i64::from(block),
+            worker: worker_.to_string(),
+            witness_load_ms: timings.witness_load_ms as i64,
+            synthesis_ms: timings.synthesis_ms as i64,
+            proving_ms: timings.proving_ms as i64,
+            total_ms: timings.total_ms as i64,
+        };
+        insert_into(proof_timings)
+            .values(&to_store)
+            .on_conflict_do_nothing()
+            .execute(self.0.conn())?;
+        Ok(())
+    }
+
+    /// Loads the average proof computation timings grouped by the prover and block size.
+    /// Only the proofs computed within the `period` are taken into account, if it's provided.
+    pub fn load_proof_timings_stats(
+        &self,
+        period: Option<time::Duration>,
+    ) -> QueryResult<Vec<ProofTimingsStats>> {
+        let period_filter = period
+            .map(|period| {
+                format!(
+                    "WHERE t.created_at > now() - interval '{} seconds'",
+                    period.as_secs()
+                )
+            })
+            .unwrap_or_default();
+        let query = format!(
+            "SELECT t.worker, b.block_size, COUNT(*) AS proofs_count, \
+                AVG(t.witness_load_ms)::BIGINT AS avg_witness_load_ms, \
+                AVG(t.synthesis_ms)::BIGINT AS avg_synthesis_ms, \
+                AVG(t.proving_ms)::BIGINT AS avg_proving_ms, \
+                AVG(t.total_ms)::BIGINT AS avg_total_ms, \
+                MAX(t.total_ms) AS max_total_ms \
+            FROM proof_timings t \
+            INNER JOIN blocks b ON b.number = t.block_number \
+            {period_filter} \
+            GROUP BY t.worker, b.block_size \
+            ORDER BY t.worker, b.block_size",
+            period_filter = period_filter
+        );
+        sql_query(query).load(self.0.conn())
+    }
+
+    /// Records the proof which didn't pass the verification, along with the reason.
+    pub fn store_rejected_proof(
+        &self,
+        block: BlockNumber,
+        proof: &EncodedProofPlonk,
+        reason: &str,
+    ) -> QueryResult<()> {
+        use crate::schema::rejected_proofs::dsl::rejected_proofs;
+
+        let to_store = NewRejectedProof {
+            block_number: i64::from(block),
+            proof: serde_json::to_value(proof).unwrap(),
+            reason: reason.to_string(),
+        };
+        insert_into(rejected_proofs)
+            .values(&to_store)
+            .execute(self.0.conn())?;
+        Ok(())
+    }
+
+    /// Loads the rejected proofs for a block, most recent first.
+    pub fn load_rejected_proofs(
+        &self,
+        block: BlockNumber,
+    ) -> QueryResult<Vec<StoredRejectedProof>> {
+        use crate::schema::rejected_proofs::dsl::*;
+
+        rejected_proofs
+            .filter(block_number.eq(i64::from(block)))
+            .order(id.desc())
+            .load(self.0.conn())
+    }
+
+    /// Stores the hash of the new prover API token and returns the id of the token.
+    pub fn add_prover_token(&self, token_name: &str, hash: &[u8]) -> QueryResult<i64> {
+        use crate::schema::prover_tokens::dsl::*;
+
+        let to_store = NewProverToken {
+            name: token_name.to_string(),
+            token_hash: hash.to_vec(),
+        };
+        insert_into(prover_tokens)
+            .values(&to_store)
+            .returning(id)
+            .get_result(self.0.conn())
+    }
+
+    /// Revokes the prover API token.
+    /// Returns `false` if there is no such token or it is already revoked.
+    pub fn revoke_prover_token(&self, token_id: i64) -> QueryResult<bool> {
+        use crate::schema::prover_tokens::dsl::*;
+
+        let updated = diesel::update(
+            prover_tokens
+                .filter(id.eq(token_id))
+                .filter(revoked_at.is_null()),
+        )
+        .set(revoked_at.eq(now))
+        .execute(self.0.conn())?;
+        Ok(updated > 0)
+    }
+
+    /// Loads all the issued prover API tokens, including the revoked ones.
+    pub fn load_prover_tokens(&self) -> QueryResult<Vec<StoredProverToken>> {
+        use crate::schema::prover_tokens::dsl::*;
+
+        prover_tokens.order(id.asc()).load(self.0.conn())
+    }
+
+    /// Checks whether there is an issued and not revoked token with the given hash.
+    pub fn is_prover_token_valid(&self, hash: &[u8]) -> QueryResult<bool> {
+        use crate::schema::prover_tokens::dsl::*;
+
+        let count: i64 = prover_tokens
+            .filter(token_hash.eq(hash))
+            .filter(revoked_at.is_null())
+            .count()
+            .get_result(self.0.conn())?;
+        Ok(count > 0)
+    }
+
+    /// Stores the prover witness for a block.
+    /// If the witness for the block is already stored, it won't be overwritten.
+    pub fn store_witness(
+        &self,
+        block: BlockNumber,
+        witness_data: &serde_json::Value,
+    ) -> QueryResult<()> {
+        use crate::schema::block_witness::dsl::*;
+
+        let serialized = serde_json::to_vec(witness_data).expect("failed to serialize witness");
+        let to_store = StorageBlockWitness {
+            block_number: i64::from(block),
+            witness: compress(&serialized),
+        };
+        insert_into(block_witness)
+            .values(&to_store)
+            .on_conflict_do_nothing()
+            .execute(self.0.conn())?;
+
+        Ok(())
+    }
+
+    /// Loads the part of the compressed witness for the block, starting from `offset` byte
+    /// and at most `len` bytes long. Compressed witness is sent to the provers as is,
+    /// so it's neither decompressed nor parsed here.
+    pub fn get_compressed_witness_chunk(
+        &self,
+        block: BlockNumber,
+        offset: i64,
+        len: i32,
+    ) -> QueryResult<Option<StoredWitnessChunk>> {
+        // Postgres `substring` counts bytes starting from 1.
+        sql_query(
+            "SELECT substring(witness FROM $2::INT FOR $3) AS data, \
+                octet_length(witness)::BIGINT AS total_len, md5(witness) AS hash \
+            FROM block_witness WHERE block_number = $1",
+        )
+        .bind::<BigInt, _>(i64::from(block))
+        .bind::<BigInt, _>(offset + 1)
+        .bind::<Integer, _>(len)
+        .get_result(self.0.conn())
+        .optional()
+    }
+
+    /// Checks whether the prover witness for a block is stored.
+    pub fn witness_exists(&self, block: BlockNumber) -> QueryResult<bool> {
+        use crate::schema::block_witness::dsl::*;
+
+        let count: i64 = block_witness
+            .filter(block_number.eq(i64::from(block)))
+            .count()
+            .get_result(self.0.conn())?;
+        Ok(count > 0)
+    }
+
+    /// Cancels the prover jobs of the reverted blocks, i.e. the blocks after `last_block`.
+    /// Witnesses and proofs of these blocks are removed, and the leases of the ongoing jobs
+    /// are expired, so the provers abandon them and the blocks are not proved until their
+    /// witnesses are generated again. Verified blocks are never affected.
+    /// Returns the ongoing jobs that were cancelled.
+    pub fn cancel_reverted_blocks_jobs(
+        &self,
+        last_block: BlockNumber,
+    ) -> QueryResult<Vec<ProverRun>> {
+        self.0.conn().transaction(|| {
+            let first_reverted_block = sql_query(
+                " \
+                SELECT GREATEST($1, COALESCE(max(block_number), 0)) + 1 AS integer_value \
+                FROM operations WHERE action_type = 'VERIFY'",
+            )
+            .bind::<BigInt, _>(i64::from(last_block))
+            .get_result::<IntegerNumber>(self.0.conn())?
+            .integer_value;
+
+            sql_query("DELETE FROM block_witness WHERE block_number >= $1")
+                .bind::<BigInt, _>(first_reverted_block)
+                .execute(self.0.conn())?;
+            sql_query("DELETE FROM proofs WHERE block_number >= $1")
+                .bind::<BigInt, _>(first_reverted_block)
+                .execute(self.0.conn())?;
+            sql_query(
+                " \
+                UPDATE prover_runs SET lease_expires_at = now() \
+                WHERE block_number >= $1 AND lease_expires_at > now() \
+                RETURNING *",
+            )
+            .bind::<BigInt, _>(first_reverted_block)
+            .load(self.0.conn())
+        })
+    }
+
+    /// Gets the stored prover witness for a block.
+    /// Returns `None` if there is no witness stored for the block.
+    pub fn get_witness(&self, block: BlockNumber) -> QueryResult<Option<serde_json::Value>> {
+        use crate::schema::block_witness::dsl::*;
+
+        let stored: Option<StorageBlockWitness> = block_witness
+            .filter(block_number.eq(i64::from(block)))
+            .first(self.0.conn())
+            .optional()?;
+
+        stored
+            .map(|stored| {
+                let serialized = decompress_stored(&stored.witness)?;
+                serde_json::from_slice(&serialized)
+                    .map_err(|err| diesel::result::Error::DeserializationError(Box::new(err)))
+            })
+            .transpose()
+    }
 }
@@ -1,6 +1,6 @@
 // External imports
 use chrono::prelude::*;
-use diesel::sql_types::BigInt;
+use diesel::sql_types::{BigInt, Binary, Jsonb, Nullable, Text, Timestamptz};
 use serde_derive::{Deserialize, Serialize};
 // Workspace imports
 // Local imports
@@ -14,6 +14,9 @@ pub struct ActiveProver {
     pub created_at: NaiveDateTime,
     pub stopped_at: Option<NaiveDateTime>,
     pub block_size: i64,
+    pub last_heartbeat_at: NaiveDateTime,
+    /// Moment when the prover was requested to finish its current job and stop.
+    pub drain_requested_at: Option<NaiveDateTime>,
 }
 
 #[derive(Debug, Insertable, Queryable, QueryableByName)]
@@ -31,7 +34,29 @@ pub struct StoredProof {
     pub created_at: NaiveDateTime,
 }
 
+/// Prover witness for the block. `witness` field contains compressed
+/// serialized data, see the `compression` module for details.
+#[derive(Debug, Insertable, Queryable)]
+#[table_name = "block_witness"]
+pub struct StorageBlockWitness {
+    pub block_number: i64,
+    pub witness: Vec<u8>,
+}
+
 // Every time before a prover worker starts generating the proof, a prover run is recorded for monitoring purposes
+/// Part of the compressed prover witness, used for the chunked witness downloads.
+#[derive(Debug, QueryableByName)]
+pub struct StoredWitnessChunk {
+    #[sql_type = "Binary"]
+    pub data: Vec<u8>,
+    /// Length of the whole compressed witness.
+    #[sql_type = "BigInt"]
+    pub total_len: i64,
+    /// Hash of the whole compressed witness, which changes if the witness is regenerated.
+    #[sql_type = "Text"]
+    pub hash: String,
+}
+
 #[derive(Debug, Clone, Insertable, Queryable, QueryableByName, Serialize, Deserialize)]
 #[table_name = "prover_runs"]
 pub struct ProverRun {
@@ -40,6 +65,163 @@ pub struct ProverRun {
     pub worker: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Moment until which the job is leased to the worker.
+    pub lease_expires_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "rejected_proofs"]
+pub struct NewRejectedProof {
+    pub block_number: i64,
+    pub proof: serde_json::Value,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Queryable)]
+pub struct StoredRejectedProof {
+    pub id: i64,
+    pub block_number: i64,
+    pub proof: serde_json::Value,
+    pub reason: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "prover_tokens"]
+pub struct NewProverToken {
+    pub name: String,
+    pub token_hash: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Queryable)]
+pub struct StoredProverToken {
+    pub id: i64,
+    pub name: String,
+    pub token_hash: Vec<u8>,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "proof_timings"]
+pub struct NewProofTimings {
+    pub block_number: i64,
+    pub worker: String,
+    pub witness_load_ms: i64,
+    pub synthesis_ms: i64,
+    pub proving_ms: i64,
+    pub total_ms: i64,
+}
+
+/// Average time of the proof computation stages for the certain prover and block size.
+#[derive(Debug, Clone, QueryableByName, Serialize, Deserialize)]
+pub struct ProofTimingsStats {
+    #[sql_type = "Text"]
+    pub worker: String,
+    #[sql_type = "BigInt"]
+    pub block_size: i64,
+    #[sql_type = "BigInt"]
+    pub proofs_count: i64,
+    #[sql_type = "BigInt"]
+    pub avg_witness_load_ms: i64,
+    #[sql_type = "BigInt"]
+    pub avg_synthesis_ms: i64,
+    #[sql_type = "BigInt"]
+    pub avg_proving_ms: i64,
+    #[sql_type = "BigInt"]
+    pub avg_total_ms: i64,
+    #[sql_type = "BigInt"]
+    pub max_total_ms: i64,
+}
+
+/// Minimum amount of the finished jobs required to judge the prover reliability.
+pub const RELIABILITY_MIN_FINISHED_JOBS: i64 = 5;
+
+/// Outcome of the prover job, accounted in the prover registry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProverJobOutcome {
+    /// Proof is published and accepted.
+    Completed,
+    /// Published proof is rejected.
+    Failed,
+    /// Prover stopped sending heartbeats while working on the job.
+    TimedOut,
+}
+
+impl ProverJobOutcome {
+    /// Column of the `prover_registry` table counting the jobs with this outcome.
+    pub(crate) fn counter_column(self) -> &'static str {
+        match self {
+            ProverJobOutcome::Completed => "completed_jobs",
+            ProverJobOutcome::Failed => "failed_jobs",
+            ProverJobOutcome::TimedOut => "timed_out_jobs",
+        }
+    }
+}
+
+/// Prover which has ever registered, along with the outcomes of its jobs.
+#[derive(Debug, Clone, QueryableByName, Serialize, Deserialize)]
+pub struct ProverRegistryEntry {
+    #[sql_type = "Text"]
+    pub worker: String,
+    /// Circuit version reported by the prover on the last registration.
+    #[sql_type = "Nullable<Text>"]
+    pub circuit_version: Option<String>,
+    /// Capabilities reported by the prover on the last registration.
+    #[sql_type = "Jsonb"]
+    pub capabilities: serde_json::Value,
+    #[sql_type = "Timestamptz"]
+    pub first_registered_at: NaiveDateTime,
+    #[sql_type = "Timestamptz"]
+    pub last_registered_at: NaiveDateTime,
+    #[sql_type = "BigInt"]
+    pub completed_jobs: i64,
+    #[sql_type = "BigInt"]
+    pub failed_jobs: i64,
+    #[sql_type = "BigInt"]
+    pub timed_out_jobs: i64,
+    /// Average proof computation time of the prover, `None` if it never reported timings.
+    #[sql_type = "Nullable<BigInt>"]
+    pub avg_proving_ms: Option<i64>,
+}
+
+impl ProverRegistryEntry {
+    /// Share of the failed and timed out jobs among all the finished jobs of the prover.
+    pub fn failure_rate(&self) -> f64 {
+        let finished = self.completed_jobs + self.failed_jobs + self.timed_out_jobs;
+        if finished == 0 {
+            return 0.0;
+        }
+        (self.failed_jobs + self.timed_out_jobs) as f64 / finished as f64
+    }
+
+    /// Checks whether the failure rate of the prover doesn't exceed the `max_failure_rate`.
+    /// Provers with less than `RELIABILITY_MIN_FINISHED_JOBS` finished jobs are considered
+    /// reliable, so a single failure of the new prover doesn't affect it.
+    pub fn is_reliable(&self, max_failure_rate: f64) -> bool {
+        let finished = self.completed_jobs + self.failed_jobs + self.timed_out_jobs;
+        finished < RELIABILITY_MIN_FINISHED_JOBS || self.failure_rate() <= max_failure_rate
+    }
+}
+
+/// State of the proving queue, used to scale the provers.
+#[derive(Debug, Clone, QueryableByName, Serialize, Deserialize)]
+pub struct ProvingQueueStats {
+    /// Amount of the committed blocks without proofs.
+    #[sql_type = "BigInt"]
+    pub queue_depth: i64,
+    /// Amount of the blocks being proved at the moment.
+    #[sql_type = "BigInt"]
+    pub jobs_in_progress: i64,
+    /// Time since the oldest block without proof was created.
+    #[sql_type = "Nullable<BigInt>"]
+    pub oldest_unproven_block_age_secs: Option<i64>,
+    /// Average proof computation time over the last day.
+    #[sql_type = "Nullable<BigInt>"]
+    pub avg_proof_ms: Option<i64>,
+    /// Amount of the running provers which are not draining.
+    #[sql_type = "BigInt"]
+    pub active_provers: i64,
 }
 
 #[derive(Debug, QueryableByName)]
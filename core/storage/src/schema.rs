@@ -53,6 +53,16 @@ table! {
         created_at -> Timestamptz,
         stopped_at -> Nullable<Timestamptz>,
         block_size -> Int8,
+        last_heartbeat_at -> Timestamptz,
+        drain_requested_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    address_denylist (address) {
+        address -> Bytea,
+        reason -> Text,
+        created_at -> Timestamptz,
     }
 }
 
@@ -64,6 +74,20 @@ table! {
     }
 }
 
+table! {
+    block_public_data (block_number) {
+        block_number -> Int8,
+        data -> Bytea,
+    }
+}
+
+table! {
+    block_witness (block_number) {
+        block_number -> Int8,
+        witness -> Bytea,
+    }
+}
+
 table! {
     blocks (number) {
         number -> Int8,
@@ -74,6 +98,17 @@ table! {
         block_size -> Int8,
         commit_gas_limit -> Int8,
         verify_gas_limit -> Int8,
+        chunks_used -> Int8,
+        created_at -> Nullable<Timestamptz>,
+        collected_fees -> Jsonb,
+    }
+}
+
+table! {
+    circuit_versions (version) {
+        version -> Text,
+        first_block -> Int8,
+        created_at -> Timestamptz,
     }
 }
 
@@ -109,6 +144,15 @@ table! {
     }
 }
 
+table! {
+    denied_txs (id) {
+        id -> Int8,
+        tx_hash -> Bytea,
+        address -> Bytea,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     eth_operations (id) {
         id -> Int8,
@@ -119,6 +163,7 @@ table! {
         final_hash -> Nullable<Bytea>,
         last_deadline_block -> Int8,
         last_used_gas_price -> Numeric,
+        gas_used -> Nullable<Int8>,
     }
 }
 
@@ -138,6 +183,17 @@ table! {
         commit_ops -> Int8,
         verify_ops -> Int8,
         withdraw_ops -> Int8,
+        operator_address -> Nullable<Bytea>,
+        last_processed_eth_block -> Nullable<Int8>,
+    }
+}
+
+table! {
+    eth_priority_queue (serial_id) {
+        serial_id -> Int8,
+        eth_block -> Int8,
+        operation -> Jsonb,
+        created_at -> Timestamptz,
     }
 }
 
@@ -146,6 +202,7 @@ table! {
         id -> Int8,
         eth_op_id -> Int8,
         tx_hash -> Bytea,
+        signed_raw_tx -> Nullable<Bytea>,
     }
 }
 
@@ -161,6 +218,7 @@ table! {
         eth_hash -> Bytea,
         eth_block -> Int8,
         created_at -> Timestamptz,
+        invalid_reason -> Nullable<Text>,
     }
 }
 
@@ -211,6 +269,27 @@ table! {
     }
 }
 
+table! {
+    pending_withdrawals (id) {
+        id -> Int8,
+        withdrawal_hash -> Bytea,
+        block_number -> Int8,
+        complete_tx_hash -> Nullable<Bytea>,
+    }
+}
+
+table! {
+    proof_timings (block_number) {
+        block_number -> Int8,
+        worker -> Text,
+        witness_load_ms -> Int8,
+        synthesis_ms -> Int8,
+        proving_ms -> Int8,
+        total_ms -> Int8,
+        created_at -> Timestamptz,
+    }
+}
+
 table! {
     proofs (block_number) {
         block_number -> Int8,
@@ -219,6 +298,19 @@ table! {
     }
 }
 
+table! {
+    prover_registry (worker) {
+        worker -> Text,
+        circuit_version -> Nullable<Text>,
+        capabilities -> Jsonb,
+        first_registered_at -> Timestamptz,
+        last_registered_at -> Timestamptz,
+        completed_jobs -> Int8,
+        failed_jobs -> Int8,
+        timed_out_jobs -> Int8,
+    }
+}
+
 table! {
     prover_runs (id) {
         id -> Int4,
@@ -226,6 +318,37 @@ table! {
         worker -> Nullable<Text>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        lease_expires_at -> Timestamptz,
+    }
+}
+
+table! {
+    prover_tokens (id) {
+        id -> Int8,
+        name -> Text,
+        token_hash -> Bytea,
+        created_at -> Timestamptz,
+        revoked_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    rejected_proofs (id) {
+        id -> Int8,
+        block_number -> Int8,
+        proof -> Jsonb,
+        reason -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+table! {
+    rejected_txs (id) {
+        id -> Int8,
+        tx_hash -> Bytea,
+        sender -> Bytea,
+        reason -> Text,
+        created_at -> Timestamptz,
     }
 }
 
@@ -237,6 +360,13 @@ table! {
     }
 }
 
+table! {
+    state_checkpoints (block_number) {
+        block_number -> Int8,
+        accounts -> Bytea,
+    }
+}
+
 table! {
     ticker_price (token_id) {
         token_id -> Int4,
@@ -268,24 +398,37 @@ allow_tables_to_appear_in_same_query!(
     account_pubkey_updates,
     accounts,
     active_provers,
+    address_denylist,
     balances,
+    block_public_data,
+    block_witness,
     blocks,
+    circuit_versions,
     data_restore_events_state,
     data_restore_last_watched_eth_block,
     data_restore_rollup_ops,
     data_restore_storage_state_update,
+    denied_txs,
     eth_operations,
     eth_ops_binding,
     eth_parameters,
+    eth_priority_queue,
     eth_tx_hashes,
     executed_priority_operations,
     executed_transactions,
     mempool_txs,
     operations,
     pending_block,
+    pending_withdrawals,
+    proof_timings,
     proofs,
+    prover_registry,
     prover_runs,
+    prover_tokens,
+    rejected_proofs,
+    rejected_txs,
     server_config,
+    state_checkpoints,
     ticker_price,
     tokens,
 );
@@ -1,4 +1,5 @@
 // External imports
+use chrono::{TimeZone, Utc};
 use diesel::prelude::*;
 use web3::types::H256;
 // Workspace imports
@@ -157,6 +158,7 @@ fn get_unique_operation(
             100,
             1_000_000.into(),
             1_500_000.into(),
+            Some(1_600_000_000 + u64::from(block_number)),
         ),
         accounts_updated,
     }
@@ -201,6 +203,22 @@ fn find_block_by_height_or_hash() {
                 actual_block_detail.new_state_root,
                 expected_block_detail.new_state_root
             );
+            assert_eq!(
+                actual_block_detail.fee_account_id,
+                expected_block_detail.fee_account_id
+            );
+            assert_eq!(
+                actual_block_detail.chunks_used,
+                expected_block_detail.chunks_used
+            );
+            assert_eq!(
+                actual_block_detail.collected_fees,
+                expected_block_detail.collected_fees
+            );
+            assert_eq!(
+                actual_block_detail.created_at,
+                expected_block_detail.created_at
+            );
             assert_eq!(
                 actual_block_detail.commit_tx_hash,
                 expected_block_detail.commit_tx_hash
@@ -237,7 +255,11 @@ fn find_block_by_height_or_hash() {
             let mut current_block_detail = BlockDetails {
                 block_number: 0,
                 new_state_root: Default::default(),
+                fee_account_id: 0,
                 block_size: 0,
+                chunks_used: 0,
+                collected_fees: Default::default(),
+                created_at: None,
                 commit_tx_hash: None,
                 verify_tx_hash: None,
                 committed_at: chrono::NaiveDateTime::from_timestamp(0, 0),
@@ -265,13 +287,20 @@ fn find_block_by_height_or_hash() {
                 100u32.into(),
                 Default::default(),
             )?;
-            EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash)?;
+            EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash, &[])?;
             EthereumSchema(&conn).confirm_eth_tx(&eth_tx_hash)?;
 
             // Initialize reference sample fields.
             current_block_detail.block_number = operation.block.block_number as i64;
             current_block_detail.new_state_root = fe_to_bytes(&operation.block.new_root_hash);
+            current_block_detail.fee_account_id = i64::from(operation.block.fee_account);
             current_block_detail.block_size = operation.block.block_transactions.len() as i64;
+            current_block_detail.chunks_used = operation.block.chunks_used() as i64;
+            current_block_detail.collected_fees = serde_json::json!({});
+            current_block_detail.created_at = operation
+                .block
+                .timestamp
+                .map(|timestamp| Utc.timestamp(timestamp as i64, 0));
             current_block_detail.commit_tx_hash = Some(eth_tx_hash.as_ref().to_vec());
 
             // Add verification for the block if required.
@@ -298,7 +327,7 @@ fn find_block_by_height_or_hash() {
                         100u32.into(),
                         Default::default(),
                     )?;
-                    EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash)?;
+                    EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash, &[])?;
                     EthereumSchema(&conn).confirm_eth_tx(&eth_tx_hash)?;
                     current_block_detail.verify_tx_hash = Some(eth_tx_hash.as_ref().to_vec());
                 }
@@ -393,7 +422,7 @@ fn block_range() {
                 100u32.into(),
                 Default::default(),
             )?;
-            EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash)?;
+            EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash, &[])?;
 
             // Add verification for the block if required.
             if block_number <= n_verified {
@@ -414,7 +443,7 @@ fn block_range() {
                     100u32.into(),
                     Default::default(),
                 )?;
-                EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash)?;
+                EthereumSchema(&conn).add_hash_entry(response.id, &eth_tx_hash, &[])?;
                 EthereumSchema(&conn).confirm_eth_tx(&eth_tx_hash)?;
             }
         }
@@ -508,6 +537,209 @@ fn load_commits_after_block() {
     });
 }
 
+/// Checks that the public data of the committed block is stored and
+/// loaded back unchanged by `get_block_public_data`.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn block_public_data() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        // No public data is stored for the non-existent block.
+        assert!(BlockSchema(&conn).get_block_public_data(1)?.is_none());
+
+        let operation = get_operation(1, Action::Commit, Vec::new(), BLOCK_SIZE_CHUNKS);
+        let expected_public_data = operation.block.get_eth_public_data();
+        BlockSchema(&conn).execute_operation(operation)?;
+
+        let public_data = BlockSchema(&conn).get_block_public_data(1)?;
+        assert_eq!(public_data, Some(expected_public_data));
+
+        Ok(())
+    });
+}
+
+/// Checks that `get_block_root_hash` loads the root hash of the committed block.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn block_root_hash() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        assert!(BlockSchema(&conn).get_block_root_hash(1)?.is_none());
+
+        BlockSchema(&conn).execute_operation(get_unique_operation(
+            1,
+            Action::Commit,
+            Vec::new(),
+        ))?;
+
+        let root_hash = BlockSchema(&conn).get_block_root_hash(1)?;
+        assert_eq!(root_hash, Some(root_hash_for_block(1)));
+
+        Ok(())
+    });
+}
+
+/// Checks that the seal timestamp of the block is stored as its creation time,
+/// and that the unknown one (e.g. of the restored block) is kept unknown.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn block_timestamp() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let operation = get_unique_operation(1, Action::Commit, Vec::new());
+        let expected_timestamp = operation.block.timestamp;
+        BlockSchema(&conn).execute_operation(operation)?;
+
+        let block = BlockSchema(&conn).get_block(1)?.unwrap();
+        assert_eq!(block.timestamp, expected_timestamp);
+
+        let mut operation = get_unique_operation(2, Action::Commit, Vec::new());
+        operation.block.timestamp = None;
+        BlockSchema(&conn).execute_operation(operation)?;
+
+        let block = BlockSchema(&conn).get_block(2)?.unwrap();
+        assert_eq!(block.timestamp, None);
+        let block_details = BlockSchema(&conn).find_block_by_height_or_hash("2".into());
+        assert_eq!(block_details.unwrap().created_at, None);
+
+        Ok(())
+    });
+}
+
+/// Checks that the chain lag is measured from the last blocks confirmed on Ethereum.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn chain_lag_stats() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        let stats = BlockSchema(&conn).load_chain_lag_stats()?;
+        assert_eq!(stats.last_committed_block, 0);
+        assert_eq!(stats.oldest_unconfirmed_commit_age_secs, None);
+        assert_eq!(stats.oldest_unconfirmed_verify_age_secs, None);
+
+        let operation = BlockSchema(&conn).execute_operation(get_unique_operation(
+            1,
+            Action::Commit,
+            Vec::new(),
+        ))?;
+        BlockSchema(&conn).execute_operation(get_unique_operation(
+            2,
+            Action::Commit,
+            Vec::new(),
+        ))?;
+        // Commit of the first block is confirmed.
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(operation.id.unwrap()),
+            100,
+            100u32.into(),
+            Default::default(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &ethereum_tx_hash(1), &[])?;
+        EthereumSchema(&conn).confirm_eth_tx(&ethereum_tx_hash(1))?;
+
+        let stats = BlockSchema(&conn).load_chain_lag_stats()?;
+        assert_eq!(stats.last_committed_block, 2);
+        assert_eq!(stats.last_confirmed_commit_block, 1);
+        assert_eq!(stats.last_confirmed_verify_block, 0);
+        // Blocks are sealed one second apart, the first one is the oldest unverified block.
+        let commit_age = stats.oldest_unconfirmed_commit_age_secs.unwrap();
+        assert_eq!(
+            stats.oldest_unconfirmed_verify_age_secs,
+            Some(commit_age + 1)
+        );
+
+        Ok(())
+    });
+}
+
+/// Checks that the blocks after the provided one are removed along with their state updates
+/// and unconfirmed Ethereum operations, while the verified blocks can't be removed.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn rollback_blocks() {
+    let mut rng = create_rng();
+
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        let (accounts_block_1, updates_block_1) =
+            apply_random_updates(AccountMap::default(), &mut rng);
+        let (accounts_block_2, updates_block_2) =
+            apply_random_updates(accounts_block_1.clone(), &mut rng);
+        let operation = BlockSchema(&conn).execute_operation(get_unique_operation(
+            1,
+            Action::Commit,
+            updates_block_1,
+        ))?;
+        // Commit of the first block is confirmed.
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(operation.id.unwrap()),
+            100,
+            100u32.into(),
+            Default::default(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &ethereum_tx_hash(1), &[])?;
+        EthereumSchema(&conn).confirm_eth_tx(&ethereum_tx_hash(1))?;
+
+        let operation = BlockSchema(&conn).execute_operation(get_unique_operation(
+            2,
+            Action::Commit,
+            updates_block_2,
+        ))?;
+        assert_eq!(
+            StateSchema(&conn).load_committed_state(None).unwrap(),
+            (2, accounts_block_2)
+        );
+
+        // Commit of the second block is sent, but not confirmed.
+        let nonce = EthereumSchema(&conn).load_next_nonce()?;
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(operation.id.unwrap()),
+            100,
+            100u32.into(),
+            Default::default(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &ethereum_tx_hash(2), &[])?;
+        assert_eq!(BlockSchema(&conn).get_last_confirmed_committed_block()?, 1);
+
+        ProverSchema(&conn).store_proof(1, &Default::default())?;
+        BlockSchema(&conn).execute_operation(get_unique_operation(
+            1,
+            Action::Verify {
+                proof: Default::default(),
+            },
+            Vec::new(),
+        ))?;
+
+        // Verified block can't be removed.
+        assert!(BlockSchema(&conn).rollback_blocks(0).is_err());
+        assert_eq!(BlockSchema(&conn).get_last_committed_block()?, 2);
+
+        assert_eq!(BlockSchema(&conn).rollback_blocks(1).unwrap(), 1);
+        assert_eq!(BlockSchema(&conn).get_last_committed_block()?, 1);
+        assert!(BlockSchema(&conn).get_block(2)?.is_none());
+        assert_eq!(
+            StateSchema(&conn).load_committed_state(None).unwrap(),
+            (1, accounts_block_1)
+        );
+        assert!(EthereumSchema(&conn)
+            .load_unconfirmed_operations()?
+            .is_empty());
+        assert_eq!(EthereumSchema(&conn).load_next_nonce()?, nonce);
+
+        // Nothing is removed once the blocks are rolled back.
+        assert_eq!(BlockSchema(&conn).rollback_blocks(1).unwrap(), 0);
+
+        Ok(())
+    });
+}
+
 /// Checks the pending block workflow:
 /// - Transactions from the pending block are available for getting.
 /// - `load_pending_block` loads the block correctly.
@@ -98,6 +98,7 @@ fn store_load() {
         // Load the txs and check that they match the expected list.
         let txs_from_db = MempoolSchema(&conn).load_txs().expect("Can't load txs");
         assert_eq!(txs_from_db.len(), txs.len());
+        assert_eq!(MempoolSchema(&conn).count_txs()?, txs.len() as i64);
 
         for (tx, tx_from_db) in txs.iter().zip(txs_from_db) {
             assert_eq!(tx_from_db.hash(), tx.hash(), "transaction changed");
@@ -202,3 +203,68 @@ fn collect_garbage() {
         Ok(())
     });
 }
+
+/// Checks that the rejected txs are loaded for the sender only, and only the
+/// latest records are kept.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn rejected_txs() {
+    const MAX_STORED: i64 = 3;
+    const MAX_STORED_PER_SENDER: i64 = 10;
+
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let sender = Address::random();
+        let other = Address::random();
+
+        for i in 0..MAX_STORED {
+            MempoolSchema(&conn).store_rejected_tx(
+                &[i as u8],
+                &sender,
+                "Mempool is full",
+                MAX_STORED,
+                MAX_STORED_PER_SENDER,
+            )?;
+        }
+        MempoolSchema(&conn).store_rejected_tx(
+            &[0xFF],
+            &other,
+            "Tx is incorrect",
+            MAX_STORED,
+            MAX_STORED_PER_SENDER,
+        )?;
+
+        let rejected = MempoolSchema(&conn).load_rejected_txs(&sender, 10)?;
+        assert_eq!(rejected.len(), 2, "oldest record should be removed");
+        assert_eq!(rejected[0].tx_hash, vec![2]);
+        assert_eq!(rejected[0].reason, "Mempool is full");
+        assert_eq!(rejected[1].tx_hash, vec![1]);
+
+        let rejected = MempoolSchema(&conn).load_rejected_txs(&other, 10)?;
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].reason, "Tx is incorrect");
+
+        // Limit is respected.
+        let rejected = MempoolSchema(&conn).load_rejected_txs(&sender, 1)?;
+        assert_eq!(rejected.len(), 1);
+
+        // Records of the sender exceeding the per-sender limit are removed,
+        // while the records of the other senders are kept.
+        for i in 0..3 {
+            MempoolSchema(&conn).store_rejected_tx(
+                &[0x10 + i],
+                &sender,
+                "Mempool is full",
+                100,
+                2,
+            )?;
+        }
+        let rejected = MempoolSchema(&conn).load_rejected_txs(&sender, 10)?;
+        assert_eq!(rejected.len(), 2);
+        assert_eq!(rejected[0].tx_hash, vec![0x12]);
+        assert_eq!(rejected[1].tx_hash, vec![0x11]);
+        assert_eq!(MempoolSchema(&conn).load_rejected_txs(&other, 10)?.len(), 1);
+
+        Ok(())
+    });
+}
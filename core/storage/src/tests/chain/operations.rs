@@ -103,6 +103,7 @@ fn executed_priority_operations() {
             eth_hash: vec![0xDE, 0xAD, 0xBE, 0xEF],
             eth_block: 10,
             created_at: chrono::Utc::now(),
+            invalid_reason: Some("Full exit account 0 does not exist".into()),
         };
         OperationsSchema(&conn).store_executed_priority_operation(executed_tx.clone())?;
 
@@ -121,6 +122,7 @@ fn executed_priority_operations() {
         );
         assert_eq!(stored_operation.deadline_block, executed_tx.deadline_block);
         assert_eq!(stored_operation.eth_hash, executed_tx.eth_hash);
+        assert_eq!(stored_operation.invalid_reason, executed_tx.invalid_reason);
 
         Ok(())
     });
@@ -159,6 +161,7 @@ fn duplicated_operations() {
         eth_hash: vec![0xDE, 0xAD, 0xBE, 0xEF],
         eth_block: 10,
         created_at: chrono::Utc::now(),
+        invalid_reason: None,
     };
 
     let conn = StorageProcessor::establish_connection().unwrap();
@@ -4,7 +4,12 @@ use std::collections::HashMap;
 // Workspace imports
 // Local imports
 use self::setup::TransactionsHistoryTestSetup;
-use crate::{chain::operations_ext::SearchDirection, tests::db_test, StorageProcessor};
+use crate::{
+    chain::operations_ext::{records::TransactionsHistoryItem, SearchDirection},
+    pagination::OperationCursor,
+    tests::db_test,
+    StorageProcessor,
+};
 
 mod setup;
 
@@ -27,7 +32,7 @@ fn commit_schema_data(
 }
 
 /// Here we take the account transactions using `get_account_transactions` and
-/// check `get_account_transactions_history` to match obtained results.
+/// check `get_account_transactions_history_from` to match obtained results.
 #[test]
 #[cfg_attr(not(feature = "db_test"), ignore)]
 fn get_account_transactions_history() {
@@ -74,10 +79,17 @@ fn get_account_transactions_history() {
     db_test(conn.conn(), || {
         commit_schema_data(&conn, &setup)?;
 
+        // Cursor pointing after the only block in the chain.
+        let cursor = OperationCursor::new(2, 0);
         let from_history = conn
             .chain()
             .operations_ext_schema()
-            .get_account_transactions_history(&setup.from_zksync_account.address, 0, 10)?;
+            .get_account_transactions_history_from(
+                &setup.from_zksync_account.address,
+                cursor,
+                SearchDirection::Older,
+                10,
+            )?;
 
         for tx in &from_history {
             let tx_type: &str = tx.tx["type"].as_str().expect("no tx_type");
@@ -104,7 +116,12 @@ fn get_account_transactions_history() {
         let to_history = conn
             .chain()
             .operations_ext_schema()
-            .get_account_transactions_history(&setup.to_zksync_account.address, 0, 10)?;
+            .get_account_transactions_history_from(
+                &setup.to_zksync_account.address,
+                cursor,
+                SearchDirection::Older,
+                10,
+            )?;
 
         assert_eq!(from_history.len(), 7);
         assert_eq!(to_history.len(), 4);
@@ -113,9 +130,40 @@ fn get_account_transactions_history() {
     });
 }
 
-/// Checks that all the transactions related to account address can be loaded
-/// with the `get_account_transactions_history_from` method and the result will
-/// be the same as if it'll be gotten via `get_account_transactions_history`.
+/// Returns the transactions of the history located in the provided direction relatively
+/// to the cursor, in the same order as `get_account_transactions_history_from` does.
+fn expected_page(
+    history: &[TransactionsHistoryItem],
+    cursor: OperationCursor,
+    direction: SearchDirection,
+    limit: u64,
+) -> Vec<TransactionsHistoryItem> {
+    let position = |item: &TransactionsHistoryItem| -> OperationCursor {
+        item.tx_id.parse().expect("Incorrect tx_id")
+    };
+    // `history` goes from the newest transaction to the oldest one.
+    let mut page: Vec<_> = match direction {
+        SearchDirection::Older => history
+            .iter()
+            .filter(|item| position(item) < cursor)
+            .take(limit as usize)
+            .cloned()
+            .collect(),
+        SearchDirection::Newer => history
+            .iter()
+            .rev()
+            .filter(|item| position(item) > cursor)
+            .take(limit as usize)
+            .cloned()
+            .collect(),
+    };
+    page.sort_by_key(|item| std::cmp::Reverse(position(item)));
+    page
+}
+
+/// Checks that the transactions related to account address are loaded page by page
+/// with the `get_account_transactions_history_from` method, and every page is adjacent
+/// to the provided cursor.
 #[test]
 #[cfg_attr(not(feature = "db_test"), ignore)]
 fn get_account_transactions_history_from() {
@@ -133,48 +181,55 @@ fn get_account_transactions_history_from() {
     db_test(conn.conn(), || {
         commit_schema_data(&conn, &setup)?;
 
+        // Whole history of the accounts, starting from the newest transaction.
+        let after_last_block = OperationCursor::new(3, 0);
+        let full_from_history = conn
+            .chain()
+            .operations_ext_schema()
+            .get_account_transactions_history_from(
+                &setup.from_zksync_account.address,
+                after_last_block,
+                SearchDirection::Older,
+                100,
+            )?;
+        let full_to_history = conn
+            .chain()
+            .operations_ext_schema()
+            .get_account_transactions_history_from(
+                &setup.to_zksync_account.address,
+                after_last_block,
+                SearchDirection::Older,
+                100,
+            )?;
+        assert_eq!(full_from_history.len() as u64, 2 * txs_from);
+        assert_eq!(full_to_history.len() as u64, 2 * txs_to);
+
         let test_vector = vec![
             // Go back from the second block and fetch all the txs of the first block.
-            (1, 1, 2, 0, SearchDirection::Older),
+            (2, 0, 1, SearchDirection::Older),
             // Go back from the third block and fetch all the txs of the second block.
-            (0, 1, 3, 0, SearchDirection::Older),
+            (3, 0, 1, SearchDirection::Older),
             // Go back from the third block and fetch all the txs of the first two blocks.
-            (0, 2, 3, 0, SearchDirection::Older),
+            (3, 0, 2, SearchDirection::Older),
             // Load all the transactions newer than genesis.
-            (0, 2, 0, 0, SearchDirection::Newer),
+            (0, 0, 2, SearchDirection::Newer),
+            // Load the transactions of the first block only, newer than genesis.
+            (0, 0, 1, SearchDirection::Newer),
             // Load all the transactions newer than the last tx of the first block.
-            (0, 1, 1, block_size, SearchDirection::Newer),
+            (1, block_size, 1, SearchDirection::Newer),
         ];
 
-        for (start_block, n_blocks, block_id, tx_id, direction) in test_vector {
-            let offset_from = start_block * txs_from;
+        for (block_id, tx_id, n_blocks, direction) in test_vector {
+            let cursor = OperationCursor::new(block_id, tx_id);
             let limit_from = n_blocks * txs_from;
-            let offset_to = start_block * txs_to;
             let limit_to = n_blocks * txs_to;
 
-            let expected_from_history = conn
-                .chain()
-                .operations_ext_schema()
-                .get_account_transactions_history(
-                    &setup.from_zksync_account.address,
-                    offset_from,
-                    limit_from,
-                )?;
-            let expected_to_history = conn
-                .chain()
-                .operations_ext_schema()
-                .get_account_transactions_history(
-                    &setup.to_zksync_account.address,
-                    offset_to,
-                    limit_to,
-                )?;
-
             let from_history = conn
                 .chain()
                 .operations_ext_schema()
                 .get_account_transactions_history_from(
                     &setup.from_zksync_account.address,
-                    (block_id, tx_id),
+                    cursor,
                     direction,
                     limit_from,
                 )?;
@@ -183,22 +238,28 @@ fn get_account_transactions_history_from() {
                 .operations_ext_schema()
                 .get_account_transactions_history_from(
                     &setup.to_zksync_account.address,
-                    (block_id, tx_id),
+                    cursor,
                     direction,
                     limit_to,
                 )?;
 
             assert_eq!(
-                from_history, expected_from_history,
+                from_history,
+                expected_page(&full_from_history, cursor, direction, limit_from),
                 "Assertion 'from' failed for the following input: \
-                [ offset {}, limit: {}, block_id: {}, tx_id: {}, direction: {:?} ]",
-                offset_from, limit_from, block_id, tx_id, direction
+                [ limit: {}, cursor: {}, direction: {:?} ]",
+                limit_from,
+                cursor,
+                direction
             );
             assert_eq!(
-                to_history, expected_to_history,
+                to_history,
+                expected_page(&full_to_history, cursor, direction, limit_to),
                 "Assertion 'to' failed for the following input: \
-                [ offset {}, limit: {}, block_id: {}, tx_id: {}, direction: {:?} ]",
-                offset_to, limit_to, block_id, tx_id, direction
+                [ limit: {}, cursor: {}, direction: {:?} ]",
+                limit_to,
+                cursor,
+                direction
             );
         }
 
@@ -85,6 +85,7 @@ impl TransactionsHistoryTestSetup {
             100,
             1_000_000.into(), // Not important
             1_500_000.into(), // Not important
+            None,             // Not important
         );
 
         self.blocks.push(block);
@@ -112,6 +113,7 @@ impl TransactionsHistoryTestSetup {
             op: deposit_op,
             block_index,
             created_at: self.get_tx_time(),
+            invalid_reason: None,
         };
 
         ExecutedOperations::PriorityOp(Box::new(executed_op))
@@ -138,6 +140,7 @@ impl TransactionsHistoryTestSetup {
             op: full_exit_op,
             block_index,
             created_at: self.get_tx_time(),
+            invalid_reason: None,
         };
 
         ExecutedOperations::PriorityOp(Box::new(executed_op))
@@ -168,3 +168,52 @@ fn state_diff() {
         Ok(())
     });
 }
+
+/// Checks that the committed state is restored correctly from the stored checkpoint,
+/// and that only the latest checkpoint is kept.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn state_checkpoints() {
+    let mut rng = create_rng();
+
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let (accounts_block_1, updates_block_1) =
+            apply_random_updates(AccountMap::default(), &mut rng);
+        let (accounts_block_2, updates_block_2) =
+            apply_random_updates(accounts_block_1.clone(), &mut rng);
+        let (accounts_block_3, updates_block_3) =
+            apply_random_updates(accounts_block_2.clone(), &mut rng);
+
+        StateSchema(&conn).commit_state_update(1, &updates_block_1)?;
+        StateSchema(&conn).commit_state_update(2, &updates_block_2)?;
+        StateSchema(&conn).commit_state_update(3, &updates_block_3)?;
+
+        StateSchema(&conn).store_checkpoint(2, &accounts_block_2)?;
+        assert_eq!(
+            StateSchema(&conn).load_last_checkpoint(3)?,
+            Some((2, accounts_block_2.clone()))
+        );
+        // Checkpoint of the later block can't be used to restore the earlier state.
+        assert_eq!(StateSchema(&conn).load_last_checkpoint(1)?, None);
+
+        // State is restored from the checkpoint, or from the verified state if the
+        // checkpoint is more recent than the requested block.
+        let (block, state) = StateSchema(&conn).load_committed_state(Some(3))?;
+        assert_eq!((block, &state), (3, &accounts_block_3));
+        let (block, state) = StateSchema(&conn).load_committed_state(Some(2))?;
+        assert_eq!((block, &state), (2, &accounts_block_2));
+        let (block, state) = StateSchema(&conn).load_committed_state(Some(1))?;
+        assert_eq!((block, &state), (1, &accounts_block_1));
+
+        // Newer checkpoint replaces the older one.
+        StateSchema(&conn).store_checkpoint(3, &accounts_block_3)?;
+        assert_eq!(StateSchema(&conn).load_last_checkpoint(2)?, None);
+        assert_eq!(
+            StateSchema(&conn).load_last_checkpoint(3)?,
+            Some((3, accounts_block_3))
+        );
+
+        Ok(())
+    });
+}
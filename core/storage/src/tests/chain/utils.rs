@@ -80,6 +80,7 @@ pub fn get_operation(
             block_size,
             1_000_000.into(),
             1_500_000.into(),
+            None,
         ),
         accounts_updated,
     }
@@ -104,6 +105,7 @@ pub fn get_operation_with_txs(
             block_size,
             1_000_000.into(),
             1_500_000.into(),
+            None,
         ),
         accounts_updated,
     }
@@ -0,0 +1,56 @@
+// Built-in deps
+use std::time::Duration;
+// Local imports
+use crate::connection::{circuit_breaker::CircuitBreaker, with_statement_timeout};
+
+/// Checks that the circuit is opened after the configured amount of failures in a row
+/// and closed again once the database responds.
+#[test]
+fn circuit_breaker_opens_and_closes() {
+    let breaker = CircuitBreaker::new(2, Duration::from_secs(60));
+    assert!(breaker.allows_request());
+
+    breaker.report_failure();
+    assert!(breaker.allows_request());
+    breaker.report_success();
+    breaker.report_failure();
+    assert!(breaker.allows_request(), "Failures are not consecutive");
+
+    breaker.report_failure();
+    assert!(breaker.is_open());
+
+    breaker.report_success();
+    assert!(breaker.allows_request());
+}
+
+/// Checks that the query is let through once the cooldown period has passed.
+#[test]
+fn circuit_breaker_cooldown() {
+    let breaker = CircuitBreaker::new(1, Duration::from_millis(0));
+    breaker.report_failure();
+    assert!(breaker.allows_request());
+}
+
+/// Checks that the circuit breaker with zero threshold never opens.
+#[test]
+fn circuit_breaker_disabled() {
+    let breaker = CircuitBreaker::new(0, Duration::from_secs(60));
+    for _ in 0..10 {
+        breaker.report_failure();
+    }
+    assert!(breaker.allows_request());
+}
+
+/// Checks that the statement timeout is added to the connection parameters.
+#[test]
+fn statement_timeout_url() {
+    let timeout = Duration::from_millis(1500);
+    assert_eq!(
+        with_statement_timeout("postgres://postgres@localhost/plasma", timeout),
+        "postgres://postgres@localhost/plasma?options=-c%20statement_timeout%3D1500"
+    );
+    assert_eq!(
+        with_statement_timeout("postgres://localhost/plasma?sslmode=disable", timeout),
+        "postgres://localhost/plasma?sslmode=disable&options=-c%20statement_timeout%3D1500"
+    );
+}
@@ -0,0 +1,59 @@
+// External imports
+// Workspace imports
+use models::node::Address;
+// Local imports
+use crate::tests::db_test;
+use crate::{denylist::DenylistSchema, StorageProcessor};
+
+/// Checks that the denylisted addresses are stored, updated and removed.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn denylist_storage() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let address = Address::from_low_u64_be(1);
+        assert!(DenylistSchema(&conn).load_denylist()?.is_empty());
+
+        DenylistSchema(&conn).add_address(&address, "sanctioned")?;
+        // Adding the same address again updates the reason.
+        DenylistSchema(&conn).add_address(&address, "compromised")?;
+
+        let denylist = DenylistSchema(&conn).load_denylist()?;
+        assert_eq!(denylist.len(), 1);
+        assert_eq!(denylist[0].address, address.as_bytes().to_vec());
+        assert_eq!(denylist[0].reason, "compromised");
+
+        assert!(DenylistSchema(&conn).remove_address(&address)?);
+        assert!(!DenylistSchema(&conn).remove_address(&address)?);
+        assert!(DenylistSchema(&conn).load_denylist()?.is_empty());
+
+        Ok(())
+    });
+}
+
+/// Checks that the rejected transactions are recorded in the audit log.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn denied_txs_log() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let address = Address::from_low_u64_be(1);
+        DenylistSchema(&conn).log_denied_tx(&[1; 32], &address, 10)?;
+        DenylistSchema(&conn).log_denied_tx(&[2; 32], &address, 10)?;
+
+        let denied_txs = DenylistSchema(&conn).load_denied_txs(10)?;
+        assert_eq!(denied_txs.len(), 2);
+        assert_eq!(denied_txs[0].tx_hash, vec![2; 32]);
+        assert_eq!(denied_txs[1].address, address.as_bytes().to_vec());
+
+        assert_eq!(DenylistSchema(&conn).load_denied_txs(1)?.len(), 1);
+
+        // Only the latest records are kept.
+        DenylistSchema(&conn).log_denied_tx(&[3; 32], &address, 2)?;
+        let denied_txs = DenylistSchema(&conn).load_denied_txs(10)?;
+        assert_eq!(denied_txs.len(), 2);
+        assert_eq!(denied_txs[1].tx_hash, vec![2; 32]);
+
+        Ok(())
+    });
+}
@@ -1,16 +1,20 @@
 // Built-in deps
 use std::str::FromStr;
 // External imports
-use web3::types::{H256, U256};
+use web3::types::{Address, H256, U256};
 // Workspace imports
 use models::{
     ethereum::{ETHOperation, OperationType},
-    node::{block::Block, BlockNumber, Fr},
-    Action, Operation,
+    node::{block::Block, BlockNumber, Deposit, Fr, FranklinPriorityOp, PriorityOp},
+    Action, ActionType, Operation,
 };
 // Local imports
 use crate::tests::db_test;
-use crate::{chain::block::BlockSchema, ethereum::EthereumSchema, StorageProcessor};
+use crate::{
+    chain::{block::BlockSchema, operations::OperationsSchema},
+    ethereum::EthereumSchema,
+    StorageProcessor,
+};
 use num::BigUint;
 
 /// Creates a sample operation to be stored in `operations` table.
@@ -29,6 +33,7 @@ pub fn get_operation(block_number: BlockNumber) -> Operation {
             100,
             1_000_000.into(),
             1_500_000.into(),
+            None,
         ),
         accounts_updated: Default::default(),
     }
@@ -92,6 +97,40 @@ fn ethereum_empty_load() {
     });
 }
 
+/// Checks that the operations are counted as queued until they're sent,
+/// and as unconfirmed until their transactions are confirmed.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_queue_stats() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        let first_op = BlockSchema(&conn).execute_operation(get_operation(1))?;
+        BlockSchema(&conn).execute_operation(get_operation(2))?;
+        let stats = EthereumSchema(&conn).load_queue_stats()?;
+        assert_eq!((stats.queued_ops, stats.unconfirmed_txs), (2, 0));
+
+        let params = EthereumTxParams::new("commit".into(), first_op);
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(params.op.id.unwrap()),
+            params.deadline_block as i64,
+            params.gas_price.clone(),
+            params.raw_tx.clone(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &params.hash, &params.raw_tx)?;
+        let stats = EthereumSchema(&conn).load_queue_stats()?;
+        assert_eq!((stats.queued_ops, stats.unconfirmed_txs), (1, 1));
+
+        EthereumSchema(&conn).confirm_eth_tx(&params.hash)?;
+        let stats = EthereumSchema(&conn).load_queue_stats()?;
+        assert_eq!((stats.queued_ops, stats.unconfirmed_txs), (1, 0));
+
+        Ok(())
+    });
+}
+
 /// Checks the basic Ethereum storage workflow:
 /// - Store the operations in the block schema.
 /// - Save the Ethereum tx.
@@ -123,7 +162,12 @@ fn ethereum_storage() {
             params.gas_price.clone(),
             params.raw_tx.clone(),
         )?;
-        EthereumSchema(&conn).add_hash_entry(response.id, &params.hash)?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &params.hash, &params.raw_tx)?;
+
+        // Check that the signed transaction is stored.
+        let signed_tx = EthereumSchema(&conn).load_signed_tx(&params.hash)?;
+        assert_eq!(signed_tx, Some(params.raw_tx.clone()));
+        assert_eq!(EthereumSchema(&conn).load_signed_tx(&H256::zero())?, None);
 
         // Check that it can be loaded.
         let unconfirmed_operations = EthereumSchema(&conn).load_unconfirmed_operations()?;
@@ -149,7 +193,7 @@ fn ethereum_storage() {
             params_2.gas_price.clone(),
             params_2.raw_tx.clone(),
         )?;
-        EthereumSchema(&conn).add_hash_entry(response_2.id, &params_2.hash)?;
+        EthereumSchema(&conn).add_hash_entry(response_2.id, &params_2.hash, &params_2.raw_tx)?;
 
         // Check that we now can load two operations.
         let unconfirmed_operations = EthereumSchema(&conn).load_unconfirmed_operations()?;
@@ -180,6 +224,143 @@ fn ethereum_storage() {
     });
 }
 
+/// Checks that the gas used by confirmed transactions is stored and
+/// `load_average_gas_used` averages it over the requested window.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_gas_used() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        // There is no data on gas usage initially.
+        let average = EthereumSchema(&conn).load_average_gas_used(OperationType::Commit, 10)?;
+        assert_eq!(average, None);
+
+        let gas_used = [100_000u64, 200_000, 400_000];
+        for (idx, gas) in gas_used.iter().enumerate() {
+            let operation =
+                BlockSchema(&conn).execute_operation(get_operation(idx as BlockNumber + 1))?;
+            let params = EthereumTxParams::new("commit".into(), operation);
+            let response = EthereumSchema(&conn).save_new_eth_tx(
+                OperationType::Commit,
+                Some(params.op.id.unwrap()),
+                params.deadline_block as i64,
+                params.gas_price.clone(),
+                params.raw_tx.clone(),
+            )?;
+            EthereumSchema(&conn).add_hash_entry(response.id, &params.hash, &params.raw_tx)?;
+            EthereumSchema(&conn).confirm_eth_tx(&params.hash)?;
+            EthereumSchema(&conn).report_gas_used(&params.hash, (*gas).into())?;
+        }
+
+        // Average is calculated over the latest operations only.
+        let average = EthereumSchema(&conn).load_average_gas_used(OperationType::Commit, 2)?;
+        assert_eq!(average, Some(300_000.into()));
+        let average = EthereumSchema(&conn).load_average_gas_used(OperationType::Commit, 10)?;
+        assert_eq!(average, Some(233_333.into()));
+
+        // Other operation types are not affected.
+        let average = EthereumSchema(&conn).load_average_gas_used(OperationType::Verify, 10)?;
+        assert_eq!(average, None);
+
+        Ok(())
+    });
+}
+
+/// Checks that the reverted confirmation makes both the Ethereum operation and
+/// the associated `Operation` unconfirmed again.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_confirmation_revert() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        let block_number = 1;
+        let operation = BlockSchema(&conn).execute_operation(get_operation(block_number))?;
+        let params = EthereumTxParams::new("commit".into(), operation);
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(params.op.id.unwrap()),
+            params.deadline_block as i64,
+            params.gas_price.clone(),
+            params.raw_tx.clone(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &params.hash, &params.raw_tx)?;
+        EthereumSchema(&conn).confirm_eth_tx(&params.hash)?;
+        assert!(EthereumSchema(&conn)
+            .load_unconfirmed_operations()?
+            .is_empty());
+
+        EthereumSchema(&conn).revert_eth_tx_confirmation(response.id)?;
+
+        let unconfirmed_operations = EthereumSchema(&conn).load_unconfirmed_operations()?;
+        assert_eq!(unconfirmed_operations.len(), 1);
+        assert!(!unconfirmed_operations[0].confirmed);
+        assert_eq!(unconfirmed_operations[0].final_hash, None);
+        let stored_operation = OperationsSchema(&conn)
+            .get_operation(block_number, ActionType::COMMIT)
+            .expect("Operation must be stored");
+        assert!(!stored_operation.confirmed);
+
+        Ok(())
+    });
+}
+
+/// Creates a sample deposit priority operation.
+fn get_priority_op(serial_id: u64, eth_block: u64) -> PriorityOp {
+    PriorityOp {
+        serial_id,
+        data: FranklinPriorityOp::Deposit(Deposit {
+            from: Default::default(),
+            token: 0,
+            amount: 100u32.into(),
+            to: Default::default(),
+        }),
+        deadline_block: eth_block + 100,
+        eth_hash: vec![serial_id as u8; 32],
+        eth_block,
+    }
+}
+
+/// Checks that the observed priority operations can be stored, loaded and removed,
+/// and that storing the same operation twice doesn't duplicate it.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_priority_queue() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        assert!(EthereumSchema(&conn).load_priority_ops()?.is_empty());
+
+        let ops = vec![get_priority_op(1, 10), get_priority_op(0, 10)];
+        EthereumSchema(&conn).save_priority_ops(&ops)?;
+        let ops = vec![get_priority_op(1, 10), get_priority_op(2, 12)];
+        EthereumSchema(&conn).save_priority_ops(&ops)?;
+
+        // Operations are loaded ordered by the serial ID.
+        let loaded = EthereumSchema(&conn).load_priority_ops()?;
+        let loaded: Vec<_> = loaded
+            .iter()
+            .map(|op| (op.serial_id, op.eth_block))
+            .collect();
+        assert_eq!(loaded, vec![(0, 10), (1, 10), (2, 12)]);
+
+        // There are no executed operations, so nothing should be removed.
+        assert_eq!(EthereumSchema(&conn).remove_executed_priority_ops()?, 0);
+        assert_eq!(EthereumSchema(&conn).load_priority_ops()?.len(), 3);
+
+        // Operations from the reorged blocks are removed.
+        assert_eq!(
+            EthereumSchema(&conn).remove_priority_ops_after_block(10)?,
+            1
+        );
+        assert_eq!(EthereumSchema(&conn).load_priority_ops()?.len(), 2);
+
+        Ok(())
+    });
+}
+
 /// Check that stored nonce starts with 0 and is incremented after every getting.
 #[test]
 #[cfg_attr(not(feature = "db_test"), ignore)]
@@ -197,3 +378,117 @@ fn eth_nonce() {
         Ok(())
     });
 }
+
+/// Check that the next nonce can be overridden and the operation nonce can be changed.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_nonce_update() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+
+        EthereumSchema(&conn).update_next_nonce(10)?;
+        assert_eq!(EthereumSchema(&conn).load_next_nonce()?, 10);
+        assert_eq!(EthereumSchema(&conn).get_next_nonce()?, 10);
+        assert_eq!(EthereumSchema(&conn).load_next_nonce()?, 11);
+
+        let operation = BlockSchema(&conn).execute_operation(get_operation(1))?;
+        let params = EthereumTxParams::new("commit".into(), operation);
+        let response = EthereumSchema(&conn).save_new_eth_tx(
+            OperationType::Commit,
+            Some(params.op.id.unwrap()),
+            params.deadline_block as i64,
+            params.gas_price.clone(),
+            params.raw_tx.clone(),
+        )?;
+        EthereumSchema(&conn).add_hash_entry(response.id, &params.hash, &params.raw_tx)?;
+        EthereumSchema(&conn).update_eth_tx_nonce(response.id, 5)?;
+
+        let unconfirmed_operations = EthereumSchema(&conn).load_unconfirmed_operations()?;
+        assert_eq!(unconfirmed_operations.len(), 1);
+        assert_eq!(unconfirmed_operations[0].nonce, 5.into());
+
+        Ok(())
+    });
+}
+
+/// Check that the operator address is not set by default and can be updated.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_operator_address() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+        assert_eq!(EthereumSchema(&conn).load_operator_address()?, None);
+
+        let address = Address::from_low_u64_be(1);
+        EthereumSchema(&conn).update_operator_address(&address)?;
+        assert_eq!(
+            EthereumSchema(&conn).load_operator_address()?,
+            Some(address)
+        );
+
+        Ok(())
+    });
+}
+
+/// Check that the Ethereum watcher cursor is not set by default and can be updated.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn eth_watch_cursor() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        EthereumSchema(&conn).initialize_eth_data()?;
+        assert_eq!(EthereumSchema(&conn).load_last_processed_eth_block()?, None);
+
+        EthereumSchema(&conn).update_last_processed_eth_block(100)?;
+        assert_eq!(
+            EthereumSchema(&conn).load_last_processed_eth_block()?,
+            Some(100)
+        );
+
+        Ok(())
+    });
+}
+
+/// Check that withdrawals are completed in the order of the pending withdrawals queue,
+/// and the `completeWithdrawals` transaction hash is stored for each of them.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn pending_withdrawals_completion() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let withdrawals: Vec<Vec<u8>> = (0u8..3).map(|idx| vec![idx; 32]).collect();
+        EthereumSchema(&conn).add_pending_withdrawals(1, &withdrawals[..2])?;
+        EthereumSchema(&conn).add_pending_withdrawals(2, &withdrawals[2..])?;
+        // Withdrawals of the same block are not added twice.
+        EthereumSchema(&conn).add_pending_withdrawals(2, &withdrawals[2..])?;
+
+        let first_tx = H256::from_low_u64_be(1);
+        assert_eq!(EthereumSchema(&conn).complete_withdrawals(&first_tx, 2)?, 2);
+        let second_tx = H256::from_low_u64_be(2);
+        assert_eq!(
+            EthereumSchema(&conn).complete_withdrawals(&second_tx, 2)?,
+            1
+        );
+        assert_eq!(
+            EthereumSchema(&conn).complete_withdrawals(&second_tx, 2)?,
+            0
+        );
+
+        let complete_txs = withdrawals
+            .iter()
+            .map(|withdrawal| EthereumSchema(&conn).load_withdrawal_complete_tx(withdrawal))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            complete_txs,
+            vec![Some(first_tx), Some(first_tx), Some(second_tx)]
+        );
+        assert_eq!(
+            EthereumSchema(&conn).load_withdrawal_complete_tx(&[0xff; 32])?,
+            None
+        );
+
+        Ok(())
+    });
+}
@@ -32,8 +32,12 @@ use diesel::Connection;
 
 mod chain;
 mod config;
+mod connection;
 mod data_restore;
+mod denylist;
 mod ethereum;
+mod notifications;
+mod pagination;
 mod prover;
 mod tokens;
 
@@ -0,0 +1,22 @@
+// Workspace imports
+use models::ActionType;
+// Local imports
+use crate::notifications::OperationNotification;
+
+/// Checks that notification can be encoded into the `NOTIFY` payload and decoded back.
+#[test]
+fn operation_notification_payload_roundtrip() {
+    for &action in &[ActionType::COMMIT, ActionType::VERIFY] {
+        let notification = OperationNotification::new(42, action);
+        let payload = notification.to_payload();
+
+        let decoded = OperationNotification::from_payload(&payload)
+            .expect("Can't decode the encoded notification");
+        assert!(decoded == notification);
+    }
+
+    assert!(OperationNotification::from_payload("COMMIT").is_none());
+    assert!(OperationNotification::from_payload("COMMIT:abc").is_none());
+    assert!(OperationNotification::from_payload("REVERT:1").is_none());
+    assert!(OperationNotification::from_payload("COMMIT:1:2").is_none());
+}
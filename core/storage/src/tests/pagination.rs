@@ -0,0 +1,62 @@
+// Local imports
+use crate::pagination::{OperationCursor, SearchDirection};
+
+/// Checks that cursor can be encoded into a string and parsed back.
+#[test]
+fn operation_cursor_roundtrip() {
+    let cursor = OperationCursor::new(12, 3);
+    assert_eq!(cursor.to_string(), "12,3");
+    assert_eq!("12,3".parse::<OperationCursor>().unwrap(), cursor);
+
+    assert!("12".parse::<OperationCursor>().is_err());
+    assert!("12,3,4".parse::<OperationCursor>().is_err());
+    assert!("a,b".parse::<OperationCursor>().is_err());
+}
+
+/// Checks that cursors are ordered the same way as operations in the chain.
+#[test]
+fn operation_cursor_ordering() {
+    assert!(OperationCursor::new(1, 10) < OperationCursor::new(2, 0));
+    assert!(OperationCursor::new(2, 0) < OperationCursor::new(2, 1));
+}
+
+/// Checks the generated SQL filter for the single-column key.
+#[test]
+fn search_direction_sql_filter() {
+    assert_eq!(
+        SearchDirection::Older.sql_filter("blocks.number", 10),
+        "blocks.number < 10"
+    );
+    assert_eq!(
+        SearchDirection::Newer.sql_filter("blocks.number", 10),
+        "blocks.number > 10"
+    );
+}
+
+/// Checks the generated SQL filter for both search directions.
+#[test]
+fn operation_cursor_sql_filter() {
+    let cursor = OperationCursor::new(5, 2);
+
+    assert_eq!(
+        cursor.sql_filter(SearchDirection::Older, "block_number", "block_index"),
+        "(block_number < 5 or (block_number = 5 and block_index < 2))"
+    );
+    assert_eq!(
+        cursor.sql_filter(SearchDirection::Newer, "block_number", "block_index"),
+        "(block_number > 5 or (block_number = 5 and block_index > 2))"
+    );
+}
+
+/// Checks that the pages are ordered starting from the row closest to the cursor.
+#[test]
+fn operation_cursor_sql_order() {
+    assert_eq!(
+        OperationCursor::sql_order(SearchDirection::Older, "block_number", "block_index"),
+        "block_number desc, block_index desc"
+    );
+    assert_eq!(
+        OperationCursor::sql_order(SearchDirection::Newer, "block_number", "block_index"),
+        "block_number asc, block_index asc"
+    );
+}
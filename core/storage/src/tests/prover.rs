@@ -1,13 +1,25 @@
 /// Std imports
 use std::time::Duration;
 // External imports
+use diesel::prelude::*;
 // Workspace imports
-use models::{node::block::PendingBlock, Action};
+use models::node::{
+    block::{ExecutedOperations, ExecutedTx, PendingBlock},
+    Address, FranklinOp, Withdraw, WithdrawOp,
+};
+use models::Action;
 // Local imports
-use crate::tests::{chain::utils::get_operation, db_test};
-use crate::{chain::block::BlockSchema, prover::ProverSchema, StorageProcessor};
-use models::config_options::ConfigurationOptions;
-use models::prover_utils::EncodedProofPlonk;
+use crate::tests::{
+    chain::utils::{get_operation, get_operation_with_txs},
+    db_test,
+};
+use crate::{
+    chain::block::BlockSchema,
+    prover::{records::ProverJobOutcome, ProverSchema},
+    StorageProcessor,
+};
+use models::config_options::{ConfigurationOptions, ProverJobPolicy};
+use models::prover_utils::{EncodedProofPlonk, ProofTimings};
 
 /// Checks that the proof can be stored and loaded.
 #[test]
@@ -30,6 +42,74 @@ fn test_store_proof() {
     });
 }
 
+/// Checks that the witness can be stored and loaded, and that
+/// storing the witness for the same block twice doesn't overwrite it.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn test_store_witness() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        // No witness is stored initially.
+        assert!(ProverSchema(&conn).get_witness(1)?.is_none());
+
+        let witness = serde_json::json!({ "operations": vec!["0x00"; 100] });
+        ProverSchema(&conn).store_witness(1, &witness)?;
+        assert_eq!(ProverSchema(&conn).get_witness(1)?, Some(witness.clone()));
+
+        // Attempt to store another witness for the same block is ignored.
+        let other_witness = serde_json::json!({ "operations": [] });
+        ProverSchema(&conn).store_witness(1, &other_witness)?;
+        assert_eq!(ProverSchema(&conn).get_witness(1)?, Some(witness));
+
+        Ok(())
+    });
+}
+
+/// Checks that the compressed witness can be loaded by chunks.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn compressed_witness_chunks() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        assert!(ProverSchema(&conn)
+            .get_compressed_witness_chunk(1, 0, 10)?
+            .is_none());
+
+        let witness = serde_json::json!({ "operations": vec!["0x00"; 100] });
+        ProverSchema(&conn).store_witness(1, &witness)?;
+
+        let whole = ProverSchema(&conn)
+            .get_compressed_witness_chunk(1, 0, i32::max_value())?
+            .expect("witness is stored");
+        assert_eq!(whole.data.len() as i64, whole.total_len);
+        let decompressed = crate::compression::decompress(&whole.data).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&decompressed).unwrap(),
+            witness
+        );
+
+        // Chunks are concatenated into the whole witness.
+        let mut chunks = Vec::new();
+        while (chunks.len() as i64) < whole.total_len {
+            let chunk = ProverSchema(&conn)
+                .get_compressed_witness_chunk(1, chunks.len() as i64, 7)?
+                .expect("witness is stored");
+            assert_eq!(chunk.hash, whole.hash);
+            assert!(!chunk.data.is_empty() && chunk.data.len() <= 7);
+            chunks.extend(chunk.data);
+        }
+        assert_eq!(chunks, whole.data);
+
+        // Chunk past the end is empty.
+        let chunk = ProverSchema(&conn)
+            .get_compressed_witness_chunk(1, whole.total_len, 7)?
+            .expect("witness is stored");
+        assert!(chunk.data.is_empty());
+
+        Ok(())
+    });
+}
+
 /// Checks the prover registration workflow, including
 /// adding a new prover, stopping and resuming it.
 #[test]
@@ -71,7 +151,8 @@ fn prover_registration() {
 /// - Check that we won't create another prover run for that block.
 /// - Store a proof.
 /// - Check that we won't create another prover run for that block once it's verified.
-/// - Create a new block & obtain a prover run for it.
+/// - Create a new block & check that there is no prover run for it until the witness is stored.
+/// - Store the witness & obtain a prover run for the new block.
 #[test]
 #[cfg_attr(not(feature = "db_test"), ignore)]
 fn prover_run() {
@@ -89,12 +170,16 @@ fn prover_run() {
             Vec::new(),
             block_size,
         ))?;
+        ProverSchema(&conn).store_witness(1, &serde_json::Value::Null)?;
 
         // Get a prover run.
         let maybe_run = ProverSchema(&conn).prover_run_for_next_commit(
             prover_name,
             Duration::from_secs(1),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
         let run = maybe_run.expect("Can't get a prover run with a block committed");
 
@@ -108,6 +193,9 @@ fn prover_run() {
             prover_name,
             Duration::from_secs(1),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
         assert!(
             maybe_run.is_none(),
@@ -123,6 +211,9 @@ fn prover_run() {
             prover_name,
             Duration::from_secs(1),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
         assert!(
             maybe_run.is_none(),
@@ -137,11 +228,29 @@ fn prover_run() {
             block_size,
         ))?;
 
+        // Block can't be proved until its witness is generated.
+        let maybe_run = ProverSchema(&conn).prover_run_for_next_commit(
+            prover_name,
+            Duration::from_secs(1),
+            block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
+        )?;
+        assert!(
+            maybe_run.is_none(),
+            "There should be no run for the block without witness"
+        );
+        ProverSchema(&conn).store_witness(2, &serde_json::Value::Null)?;
+
         // Now we should get a prover run for the second block.
         let maybe_run = ProverSchema(&conn).prover_run_for_next_commit(
             prover_name,
             Duration::from_secs(1),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
         let run = maybe_run.expect("Can't get a prover run with a block committed");
 
@@ -176,18 +285,21 @@ fn unstarted_prover_jobs_count() {
             Vec::new(),
             block_size,
         ))?;
+        ProverSchema(&conn).store_witness(1, &serde_json::Value::Null)?;
         BlockSchema(&conn).execute_operation(get_operation(
             2,
             Action::Commit,
             Vec::new(),
             block_size,
         ))?;
+        ProverSchema(&conn).store_witness(2, &serde_json::Value::Null)?;
         BlockSchema(&conn).execute_operation(get_operation(
             3,
             Action::Commit,
             Vec::new(),
             block_size,
         ))?;
+        ProverSchema(&conn).store_witness(3, &serde_json::Value::Null)?;
 
         // We've created 3 blocks and no jobs were assigned yet.
         let blocks_count = ProverSchema(&conn).unstarted_jobs_count()?;
@@ -198,6 +310,9 @@ fn unstarted_prover_jobs_count() {
             prover_name,
             Duration::from_secs(1),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
 
         // Now, as the job started, the number of not started jobs must be 2.
@@ -217,6 +332,9 @@ fn unstarted_prover_jobs_count() {
             prover_name,
             Duration::from_secs(2),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
 
         let blocks_count = ProverSchema(&conn).unstarted_jobs_count()?;
@@ -231,6 +349,9 @@ fn unstarted_prover_jobs_count() {
             prover_name,
             Duration::from_secs(3),
             block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
         )?;
 
         let blocks_count = ProverSchema(&conn).unstarted_jobs_count()?;
@@ -267,3 +388,641 @@ fn unstarted_prover_jobs_count() {
         Ok(())
     });
 }
+
+/// Checks that the prover job is leased to a single prover until the lease expires.
+/// - Obtain a prover run with an already expired lease.
+/// - Check that the lease can't be renewed, and the block is reassigned to another prover.
+/// - Check that the renewed lease keeps the block assigned.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn prover_job_lease() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0]; //smallest block size
+        ProverSchema(&conn).register_prover("prover_1", block_size)?;
+        ProverSchema(&conn).register_prover("prover_2", block_size)?;
+
+        BlockSchema(&conn).execute_operation(get_operation(
+            1,
+            Action::Commit,
+            Vec::new(),
+            block_size,
+        ))?;
+        ProverSchema(&conn).store_witness(1, &serde_json::Value::Null)?;
+
+        // Lease expires right away.
+        let expired_run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_1",
+                Duration::from_secs(0),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with a block committed");
+        assert_eq!(expired_run.block_number, 1);
+        assert_eq!(ProverSchema(&conn).unstarted_jobs_count()?, 1);
+
+        let renewed = ProverSchema(&conn)
+            .record_prover_is_working(expired_run.id, Duration::from_secs(60))?;
+        assert!(!renewed, "Expired lease should not be renewed");
+
+        // The block is reassigned to another prover.
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_2",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Block with the expired lease should be reassigned");
+        assert_eq!(run.block_number, 1);
+        assert_eq!(run.worker, Some("prover_2".into()));
+        assert_eq!(ProverSchema(&conn).unstarted_jobs_count()?, 0);
+
+        // Renewed lease keeps the block assigned to the same prover.
+        let renewed =
+            ProverSchema(&conn).record_prover_is_working(run.id, Duration::from_secs(60))?;
+        assert!(renewed);
+        let maybe_run = ProverSchema(&conn).prover_run_for_next_commit(
+            "prover_1",
+            Duration::from_secs(60),
+            block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
+        )?;
+        assert!(maybe_run.is_none(), "Leased block should not be reassigned");
+
+        Ok(())
+    });
+}
+
+/// Checks the proving queue stats reported to the provers autoscaler,
+/// and that the draining provers are excluded from them.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn proving_queue_stats_and_drain() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0];
+
+        let stats = ProverSchema(&conn).proving_queue_stats()?;
+        assert_eq!(stats.queue_depth, 0);
+        assert_eq!(stats.oldest_unproven_block_age_secs, None);
+        assert_eq!(stats.avg_proof_ms, None);
+        assert_eq!(stats.active_provers, 0);
+
+        let prover_1 = ProverSchema(&conn).register_prover("prover_1", block_size)?;
+        let prover_2 = ProverSchema(&conn).register_prover("prover_2", block_size)?;
+        for block_number in 1..=3 {
+            BlockSchema(&conn).execute_operation(get_operation(
+                block_number,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+            ))?;
+            ProverSchema(&conn).store_witness(block_number, &serde_json::Value::Null)?;
+        }
+        ProverSchema(&conn).store_proof(1, &Default::default())?;
+        let timings = ProofTimings {
+            witness_load_ms: 10,
+            synthesis_ms: 20,
+            proving_ms: 70,
+            total_ms: 100,
+        };
+        ProverSchema(&conn).store_proof_timings(1, "prover_1", &timings)?;
+        ProverSchema(&conn).prover_run_for_next_commit(
+            "prover_1",
+            Duration::from_secs(60),
+            block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
+        )?;
+
+        let stats = ProverSchema(&conn).proving_queue_stats()?;
+        assert_eq!(stats.queue_depth, 2);
+        assert_eq!(stats.jobs_in_progress, 1);
+        // `now()` is constant within the transaction.
+        assert_eq!(stats.oldest_unproven_block_age_secs, Some(0));
+        assert_eq!(stats.avg_proof_ms, Some(100));
+        assert_eq!(stats.active_provers, 2);
+
+        // Draining prover gets no new jobs and is not counted as active.
+        assert!(!ProverSchema(&conn).is_worker_draining("prover_2")?);
+        assert!(ProverSchema(&conn).request_prover_drain(prover_2)?);
+        assert!(ProverSchema(&conn).is_prover_draining(prover_2)?);
+        assert!(!ProverSchema(&conn).is_prover_draining(prover_1)?);
+        assert!(ProverSchema(&conn).is_worker_draining("prover_2")?);
+        assert!(!ProverSchema(&conn).is_worker_draining("prover_1")?);
+        assert_eq!(ProverSchema(&conn).proving_queue_stats()?.active_provers, 1);
+
+        // Stopped prover can't be drained.
+        ProverSchema(&conn).record_prover_stop(prover_1)?;
+        assert!(!ProverSchema(&conn).request_prover_drain(prover_1)?);
+
+        Ok(())
+    });
+}
+
+/// Checks that the jobs of the reverted blocks are cancelled:
+/// - Ongoing job lease is expired, so the prover abandons it.
+/// - Witnesses and proofs of the reverted blocks are removed, so the blocks are not proved.
+/// - Verified blocks are not affected even if the requested last block is below them.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn reverted_blocks_jobs_cancellation() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0];
+        for block_number in 1..=4 {
+            BlockSchema(&conn).execute_operation(get_operation(
+                block_number,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+            ))?;
+            ProverSchema(&conn).store_witness(block_number, &serde_json::Value::Null)?;
+        }
+        ProverSchema(&conn).store_proof(1, &Default::default())?;
+        BlockSchema(&conn).execute_operation(get_operation(
+            1,
+            Action::Verify {
+                proof: Default::default(),
+            },
+            Vec::new(),
+            block_size,
+        ))?;
+        ProverSchema(&conn).store_proof(2, &Default::default())?;
+
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with a block committed");
+        assert_eq!(run.block_number, 3);
+
+        // Verified block 1 is kept, blocks 2-4 are reverted.
+        let cancelled = ProverSchema(&conn).cancel_reverted_blocks_jobs(0)?;
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].id, run.id);
+
+        assert!(ProverSchema(&conn).witness_exists(1)?);
+        assert!(ProverSchema(&conn).load_proof(1).is_ok());
+        for block_number in 2..=4 {
+            assert!(!ProverSchema(&conn).witness_exists(block_number)?);
+        }
+        assert!(ProverSchema(&conn).load_proof(2).is_err());
+
+        let renewed =
+            ProverSchema(&conn).record_prover_is_working(run.id, Duration::from_secs(60))?;
+        assert!(!renewed, "Lease of the cancelled job should not be renewed");
+        let maybe_run = ProverSchema(&conn).prover_run_for_next_commit(
+            "prover",
+            Duration::from_secs(60),
+            block_size,
+            ProverJobPolicy::Fifo,
+            "v1",
+            false,
+        )?;
+        assert!(maybe_run.is_none(), "Reverted blocks should not be proved");
+
+        Ok(())
+    });
+}
+
+/// Checks that the provers without heartbeats are considered dead and their jobs are reassigned.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn dead_provers_reaping() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0]; //smallest block size
+        let dead_prover_id = ProverSchema(&conn).register_prover("prover_1", block_size)?;
+        let alive_prover_id = ProverSchema(&conn).register_prover("prover_2", block_size)?;
+
+        BlockSchema(&conn).execute_operation(get_operation(
+            1,
+            Action::Commit,
+            Vec::new(),
+            block_size,
+        ))?;
+        ProverSchema(&conn).store_witness(1, &serde_json::Value::Null)?;
+        ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_1",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with a block committed");
+
+        // Recently registered provers are alive.
+        let dead_provers = ProverSchema(&conn).reap_dead_provers(Duration::from_secs(60))?;
+        assert!(dead_provers.is_empty());
+
+        // The first prover stops sending heartbeats.
+        diesel::sql_query(format!(
+            "UPDATE active_provers SET last_heartbeat_at = now() - interval '1 hour' WHERE id = {}",
+            dead_prover_id
+        ))
+        .execute(conn.conn())?;
+        assert!(ProverSchema(&conn).record_prover_heartbeat(alive_prover_id)?);
+
+        let dead_provers = ProverSchema(&conn).reap_dead_provers(Duration::from_secs(60))?;
+        assert_eq!(dead_provers.len(), 1);
+        assert_eq!(dead_provers[0].id, dead_prover_id);
+        assert!(ProverSchema(&conn)
+            .prover_by_id(dead_prover_id)?
+            .stopped_at
+            .is_some());
+        // Job of the dead prover is accounted as timed out.
+        let dead_prover_entry = ProverSchema(&conn)
+            .prover_registry_entry("prover_1")?
+            .expect("Prover with timed out job should be in the registry");
+        assert_eq!(dead_prover_entry.timed_out_jobs, 1);
+
+        // Dead prover can't send heartbeats anymore, and its block is reassigned.
+        assert!(!ProverSchema(&conn).record_prover_heartbeat(dead_prover_id)?);
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_2",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Block of the dead prover should be reassigned");
+        assert_eq!(run.block_number, 1);
+
+        Ok(())
+    });
+}
+
+/// Checks the prover registry accounting and the job assignment to the unreliable provers.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn prover_registry() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0]; //smallest block size
+        assert!(ProverSchema(&conn).load_prover_registry()?.is_empty());
+
+        let capabilities = serde_json::json!({ "blockSizes": [block_size] });
+        ProverSchema(&conn).record_prover_registration("prover_1", Some("v1"), &capabilities)?;
+        // Restarted prover is kept in the registry as a single entry.
+        ProverSchema(&conn).record_prover_registration("prover_1", Some("v2"), &capabilities)?;
+
+        for _ in 0..4 {
+            ProverSchema(&conn)
+                .record_prover_job_outcome("prover_1", ProverJobOutcome::Completed)?;
+        }
+        ProverSchema(&conn).record_prover_job_outcome("prover_1", ProverJobOutcome::Failed)?;
+        // Unregistered workers are accounted as well.
+        ProverSchema(&conn).record_prover_job_outcome("prover_2", ProverJobOutcome::TimedOut)?;
+
+        let registry = ProverSchema(&conn).load_prover_registry()?;
+        assert_eq!(registry.len(), 2);
+        let prover_1 = &registry[0];
+        assert_eq!(prover_1.worker, "prover_1");
+        assert_eq!(prover_1.circuit_version, Some("v2".to_string()));
+        assert_eq!(prover_1.capabilities, capabilities);
+        assert_eq!(
+            (
+                prover_1.completed_jobs,
+                prover_1.failed_jobs,
+                prover_1.timed_out_jobs
+            ),
+            (4, 1, 0)
+        );
+        assert_eq!(prover_1.avg_proving_ms, None);
+        assert!((prover_1.failure_rate() - 0.2).abs() < 1e-9);
+        assert!(prover_1.is_reliable(0.2));
+        assert!(!prover_1.is_reliable(0.1));
+        // Single failure of the new prover doesn't make it unreliable.
+        let prover_2 = &registry[1];
+        assert_eq!(prover_2.failure_rate(), 1.0);
+        assert!(prover_2.is_reliable(0.1));
+
+        // Deprioritized prover gets the least urgent block.
+        for block in 1..=2 {
+            BlockSchema(&conn).execute_operation(get_operation(
+                block,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+            ))?;
+            ProverSchema(&conn).store_witness(block, &serde_json::Value::Null)?;
+        }
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_1",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                true,
+            )?
+            .expect("Can't get a prover run with a block committed");
+        assert_eq!(run.block_number, 2);
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover_2",
+                Duration::from_secs(60),
+                block_size,
+                ProverJobPolicy::Fifo,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with a block committed");
+        assert_eq!(run.block_number, 1);
+
+        Ok(())
+    });
+}
+
+/// Checks that the rejected proofs are stored along with the rejection reasons.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn rejected_proofs() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let proof = EncodedProofPlonk::default();
+        ProverSchema(&conn).store_rejected_proof(1, &proof, "invalid proof")?;
+        ProverSchema(&conn).store_rejected_proof(1, &proof, "unexpected public inputs")?;
+        ProverSchema(&conn).store_rejected_proof(2, &proof, "invalid proof")?;
+
+        let rejected = ProverSchema(&conn).load_rejected_proofs(1)?;
+        let reasons: Vec<_> = rejected.iter().map(|proof| proof.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["unexpected public inputs", "invalid proof"]);
+        assert_eq!(
+            serde_json::from_value::<EncodedProofPlonk>(rejected[0].proof.clone()).unwrap(),
+            proof
+        );
+
+        // Rejected proof is not considered as a proof for the block.
+        assert!(ProverSchema(&conn).load_proof(1).is_err());
+
+        Ok(())
+    });
+}
+
+/// Checks that the prover API tokens can be issued and revoked.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn prover_tokens() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        assert!(!ProverSchema(&conn).is_prover_token_valid(b"first")?);
+
+        let first_id = ProverSchema(&conn).add_prover_token("first_prover", b"first")?;
+        let second_id = ProverSchema(&conn).add_prover_token("second_prover", b"second")?;
+        assert!(ProverSchema(&conn).is_prover_token_valid(b"first")?);
+        assert!(ProverSchema(&conn).is_prover_token_valid(b"second")?);
+
+        // Revoked token is no longer valid, but is still listed.
+        assert!(ProverSchema(&conn).revoke_prover_token(first_id)?);
+        assert!(!ProverSchema(&conn).revoke_prover_token(first_id)?);
+        assert!(!ProverSchema(&conn).is_prover_token_valid(b"first")?);
+        assert!(ProverSchema(&conn).is_prover_token_valid(b"second")?);
+
+        let tokens = ProverSchema(&conn).load_prover_tokens()?;
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].name, "first_prover");
+        assert!(tokens[0].revoked_at.is_some());
+        assert_eq!(tokens[1].id, second_id);
+        assert!(tokens[1].revoked_at.is_none());
+
+        // Unknown token can't be revoked.
+        assert!(!ProverSchema(&conn).revoke_prover_token(second_id + 1)?);
+
+        Ok(())
+    });
+}
+
+/// Checks that the proof timings are stored and aggregated per prover and block size.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn proof_timings_stats() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0];
+        for block_number in 1..=3 {
+            BlockSchema(&conn).execute_operation(get_operation(
+                block_number,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+            ))?;
+        }
+
+        let timings = |total_ms| ProofTimings {
+            witness_load_ms: 10,
+            synthesis_ms: 20,
+            proving_ms: total_ms - 30,
+            total_ms,
+        };
+        ProverSchema(&conn).store_proof_timings(1, "gpu_prover", &timings(100))?;
+        ProverSchema(&conn).store_proof_timings(2, "gpu_prover", &timings(200))?;
+        ProverSchema(&conn).store_proof_timings(3, "cpu_prover", &timings(1000))?;
+        // Timings of the already proved block are not overwritten.
+        ProverSchema(&conn).store_proof_timings(3, "gpu_prover", &timings(300))?;
+
+        let stats = ProverSchema(&conn).load_proof_timings_stats(None)?;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].worker, "cpu_prover");
+        assert_eq!(stats[0].block_size, block_size as i64);
+        assert_eq!(stats[0].proofs_count, 1);
+        assert_eq!(stats[0].avg_total_ms, 1000);
+        assert_eq!(stats[1].worker, "gpu_prover");
+        assert_eq!(stats[1].proofs_count, 2);
+        assert_eq!(stats[1].avg_witness_load_ms, 10);
+        assert_eq!(stats[1].avg_synthesis_ms, 20);
+        assert_eq!(stats[1].avg_proving_ms, 120);
+        assert_eq!(stats[1].avg_total_ms, 150);
+        assert_eq!(stats[1].max_total_ms, 200);
+
+        // Old timings are not taken into account if the period is set.
+        diesel::sql_query(
+            "UPDATE proof_timings SET created_at = now() - interval '2 hours' \
+            WHERE block_number = 1",
+        )
+        .execute(conn.conn())?;
+        let stats =
+            ProverSchema(&conn).load_proof_timings_stats(Some(Duration::from_secs(3600)))?;
+        assert_eq!(stats[1].worker, "gpu_prover");
+        assert_eq!(stats[1].proofs_count, 1);
+        assert_eq!(stats[1].avg_total_ms, 200);
+
+        Ok(())
+    });
+}
+
+/// Checks that the blocks are given only to the provers of the circuit version
+/// the block was proposed under.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn circuit_versions_routing() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0];
+        let next_run = |circuit_version| {
+            ProverSchema(&conn)
+                .prover_run_for_next_commit(
+                    "prover",
+                    Duration::from_secs(60),
+                    block_size,
+                    ProverJobPolicy::Fifo,
+                    circuit_version,
+                    false,
+                )
+                .map(|run| run.map(|run| run.block_number))
+        };
+
+        // Block 1 is proposed before the versions are recorded, blocks 2 and 3 under
+        // the old version, and block 4 under the new one.
+        ProverSchema(&conn).record_circuit_version("v1", 2)?;
+        ProverSchema(&conn).record_circuit_version("v2", 4)?;
+        // Already recorded version is not moved.
+        ProverSchema(&conn).record_circuit_version("v1", 5)?;
+        for block_number in 1..=4 {
+            BlockSchema(&conn).execute_operation(get_operation(
+                block_number,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+            ))?;
+            ProverSchema(&conn).store_witness(block_number, &serde_json::Value::Null)?;
+        }
+        assert_eq!(ProverSchema(&conn).block_circuit_version(1)?, None);
+        assert_eq!(
+            ProverSchema(&conn).block_circuit_version(3)?,
+            Some("v1".to_string())
+        );
+        assert_eq!(
+            ProverSchema(&conn).block_circuit_version(5)?,
+            Some("v2".to_string())
+        );
+
+        assert_eq!(next_run("v2")?, Some(1));
+        assert_eq!(next_run("v2")?, Some(4));
+        assert_eq!(next_run("v2")?, None);
+        assert_eq!(next_run("v1")?, Some(2));
+        assert_eq!(next_run("v1")?, Some(3));
+        assert_eq!(next_run("v1")?, None);
+
+        Ok(())
+    });
+}
+
+/// Creates an executed withdrawal operation.
+fn executed_withdraw() -> ExecutedOperations {
+    let withdraw = Withdraw::new(
+        1,
+        Address::random(),
+        Address::random(),
+        0,
+        100u32.into(),
+        10u32.into(),
+        0,
+        None,
+    );
+    let withdraw_op = FranklinOp::Withdraw(Box::new(WithdrawOp {
+        tx: withdraw,
+        account_id: 1,
+    }));
+    ExecutedOperations::Tx(Box::new(ExecutedTx {
+        signed_tx: withdraw_op.try_get_tx().unwrap().into(),
+        success: true,
+        op: Some(withdraw_op),
+        fail_reason: None,
+        block_index: None,
+        created_at: chrono::Utc::now(),
+    }))
+}
+
+/// Checks that the `withdrawals_first` job policy gives the blocks with withdrawals first,
+/// unless there are blocks waiting for too long.
+#[test]
+#[cfg_attr(not(feature = "db_test"), ignore)]
+fn withdrawals_first_job_policy() {
+    let conn = StorageProcessor::establish_connection().unwrap();
+    db_test(conn.conn(), || {
+        let block_size = ConfigurationOptions::from_env().available_block_chunk_sizes[0]; //smallest block size
+        let policy = ProverJobPolicy::WithdrawalsFirst {
+            max_age: Duration::from_secs(3600),
+        };
+
+        // Only the second block contains a withdrawal.
+        for block_number in 1..=3 {
+            let txs = if block_number == 2 {
+                vec![executed_withdraw()]
+            } else {
+                Vec::new()
+            };
+            BlockSchema(&conn).execute_operation(get_operation_with_txs(
+                block_number,
+                Action::Commit,
+                Vec::new(),
+                block_size,
+                txs,
+            ))?;
+            ProverSchema(&conn).store_witness(block_number, &serde_json::Value::Null)?;
+        }
+
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover",
+                Duration::from_secs(60),
+                block_size,
+                policy,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with blocks committed");
+        assert_eq!(run.block_number, 2);
+
+        // The third block waits for too long, so it's proved before the older first one.
+        diesel::sql_query(
+            "UPDATE blocks SET created_at = now() - interval '2 hours' WHERE number = 3",
+        )
+        .execute(conn.conn())?;
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover",
+                Duration::from_secs(60),
+                block_size,
+                policy,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with blocks committed");
+        assert_eq!(run.block_number, 3);
+
+        let run = ProverSchema(&conn)
+            .prover_run_for_next_commit(
+                "prover",
+                Duration::from_secs(60),
+                block_size,
+                policy,
+                "v1",
+                false,
+            )?
+            .expect("Can't get a prover run with blocks committed");
+        assert_eq!(run.block_number, 1);
+
+        Ok(())
+    });
+}
@@ -1,5 +1,5 @@
 use crate::external_commands::js_revert_reason;
-use eth_client::ETHClient;
+use eth_client::{ETHClient, TxPricing};
 use ethabi::ParamType;
 use failure::{bail, ensure, format_err};
 use futures::compat::Future01CompatExt;
@@ -78,6 +78,7 @@ impl<T: Transport> EthereumAccount<T> {
             contract_address,
             chain_id,
             gas_price_factor,
+            TxPricing::Legacy,
         );
 
         Self {
@@ -316,6 +317,7 @@ impl<T: Transport> EthereumAccount<T> {
             token_contract,
             self.main_contract_eth_client.chain_id,
             self.main_contract_eth_client.gas_price_factor,
+            self.main_contract_eth_client.tx_pricing,
         );
 
         let signed_tx = erc20_client
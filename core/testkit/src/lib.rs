@@ -9,21 +9,24 @@ use futures::{
     executor::block_on,
     SinkExt, StreamExt,
 };
-use models::config_options::ConfigurationOptions;
+use models::config_options::{
+    BlockSealingOptions, ConfigurationOptions, DEFAULT_MINIBLOCK_INTERVAL_MS,
+    DEFAULT_PRIORITY_OP_DEADLINE_MARGIN,
+};
 use models::node::{
     Account, AccountId, AccountMap, Address, DepositOp, FranklinTx, FullExitOp, Nonce, PriorityOp,
     TokenId, TransferOp, TransferToNewOp, WithdrawOp,
 };
 use models::{BlockCommitRequest, CommitRequest};
 use num::BigUint;
-use server::mempool::ProposedBlock;
+use server::mempool::{ProposedBlock, SignedTxVariant};
 use server::state_keeper::{
     start_state_keeper, PlasmaStateInitParams, PlasmaStateKeeper, StateKeeperRequest,
     MAX_WITHDRAWALS_PER_BLOCK,
 };
 use std::collections::HashMap;
 use std::thread::JoinHandle;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::runtime::Runtime;
 use web3::transports::Http;
 use web3::Transport;
@@ -338,7 +341,18 @@ pub fn spawn_state_keeper(
     block_chunks_sizes.sort();
     block_chunks_sizes.dedup();
 
+    // Blocks are sealed explicitly by the tests, so only the miniblock iterations are limited.
     let max_miniblock_iterations = *block_chunks_sizes.iter().max().unwrap();
+    let sealing = BlockSealingOptions {
+        miniblock_interval: Duration::from_millis(DEFAULT_MINIBLOCK_INTERVAL_MS),
+        max_miniblock_iterations,
+        max_miniblock_iterations_withdraw_block: max_miniblock_iterations,
+        block_seal_deadline: None,
+        min_chunk_fill_ratio: None,
+        seal_on_withdrawal: false,
+        padding_block_interval: None,
+        priority_op_deadline_margin: DEFAULT_PRIORITY_OP_DEADLINE_MARGIN,
+    };
     let state_keeper = PlasmaStateKeeper::new(
         genesis_state(fee_account),
         *fee_account,
@@ -346,8 +360,8 @@ pub fn spawn_state_keeper(
         proposed_blocks_sender,
         executed_tx_notify_sender,
         block_chunks_sizes,
-        max_miniblock_iterations,
-        max_miniblock_iterations,
+        sealing,
+        0,
     );
 
     let (stop_state_keeper_sender, stop_state_keeper_receiver) = oneshot::channel::<()>();
@@ -733,7 +747,8 @@ impl TestSetup {
     fn execute_tx(&mut self, tx: FranklinTx) {
         let block = ProposedBlock {
             priority_ops: Vec::new(),
-            txs: vec![tx.into()],
+            txs: vec![SignedTxVariant::Tx(tx.into())],
+            current_eth_block: None,
         };
         let block_sender = async {
             self.state_keeper_request_sender
@@ -801,6 +816,7 @@ impl TestSetup {
         let block = ProposedBlock {
             priority_ops: vec![op],
             txs: Vec::new(),
+            current_eth_block: None,
         };
         let block_sender = async {
             self.state_keeper_request_sender
@@ -1066,6 +1082,9 @@ impl TestSetup {
                     // Pending blocks are ignored.
                     receiver.send(()).unwrap();
                 }
+                CommitRequest::StateCheckpoint(..) => {
+                    // State checkpoints are ignored.
+                }
             }
         }
         panic!("Proposed blocks receiver dropped");
@@ -1090,6 +1109,12 @@ impl TestSetup {
                 // Notify state keeper that we've processed the request.
                 receiver.send(()).unwrap();
             }
+            CommitRequest::StateCheckpoint(block_number, _) => {
+                panic!(
+                    "Expected pending block, got state checkpoint for block {}",
+                    block_number
+                );
+            }
         }
     }
 
@@ -5,8 +5,9 @@ export class BlockExplorerClient {
     	this.providerAddress = providerAddress
     }
 
-    async getAccountTransactions(address, offset, limit) {
-        const transactionsUrl = `${this.providerAddress}/api/v0.1/account/${address}/history/${offset}/${limit}`;
+    // `txId` is the position of the last seen transaction, `-` loads the newest ones.
+    async getAccountTransactions(address, txId, limit) {
+        const transactionsUrl = `${this.providerAddress}/api/v0.1/account/${address}/history/older_than?tx_id=${txId}&limit=${limit}`;
         return await Axios
             .get(transactionsUrl)
             .then(res => res.data);
@@ -136,12 +136,16 @@ export class Client {
         return `${symbol.toString()}`;
     }
 
-    async transactionsAsRenderableList(address, offset, limit) {
+    async transactionsAsRenderableList(address, txId, limit) {
         if (!address) {
             console.log(address);
-            return [];
+            return { transactions: [], nextTxId: null };
         }
-        const transactions = await this.blockExplorerClient.getAccountTransactions(address, offset, limit);
+        const transactions = await this.blockExplorerClient.getAccountTransactions(address, txId, limit);
+        // Ongoing operations don't have a position in the chain yet, so the next page
+        // starts after the last executed transaction.
+        const executed = transactions.filter(tx => tx.tx_id.includes(','));
+        const nextTxId = executed.length ? executed[executed.length - 1].tx_id : null;
         const res = transactions.map(async (tx, index) => {
             const elem_id      = `history_${index}`;
             const type         = tx.tx.type || '';
@@ -285,7 +289,7 @@ export class Client {
         });
 
         const txs = await Promise.all(res);
-        return txs.filter(Boolean);
+        return { transactions: txs.filter(Boolean), nextTxId };
     }
 
     async loadTokens() {
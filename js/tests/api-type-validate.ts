@@ -161,12 +161,11 @@ export async function checkStatusResponseType(): Promise<StatusInterface> {
 }
 
 /**
- * Check `/account/${address}/history/${offset}/${limit}` method of our rest api
+ * Check `/account/${address}/history/older_than` method of our rest api
  */
 export async function checkTxHistoryResponseType(address: string): Promise<TxHistoryInterface> {
-    const offset = 0;
     const limit = 20;
-    const url = `${process.env.REST_API_ADDR}/api/v0.1/account/${address}/history/${offset}/${limit}`;
+    const url = `${process.env.REST_API_ADDR}/api/v0.1/account/${address}/history/older_than?limit=${limit}`;
     const typeFilePath = `${apiTypesFolder}/tx-history.ts`;
     const data: TxHistoryInterface = await validateResponseFromUrl(typeFilePath, url);
 
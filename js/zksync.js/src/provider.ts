@@ -79,8 +79,17 @@ export class Provider {
     }
 
     // return transaction hash (e.g. sync-tx:dead..beef)
-    async submitTx(tx: any, signature?: TxEthSignature): Promise<string> {
-        return await this.transport.request("tx_submit", [tx, signature]);
+    async submitTx(
+        tx: any,
+        signature?: TxEthSignature,
+        fastProcessing?: boolean
+    ): Promise<string> {
+        return await this.transport.request("tx_submit", [tx, signature, fastProcessing]);
+    }
+
+    // Executes the transaction without applying it, returns the account changes it would make.
+    async dryRunTx(tx: any): Promise<any> {
+        return await this.transport.request("tx_dry_run", [tx]);
     }
 
     async getContractAddress(): Promise<ContractAddress> {
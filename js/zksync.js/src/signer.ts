@@ -33,6 +33,8 @@ export class Signer {
         amount: BigNumberish;
         fee: BigNumberish;
         nonce: number;
+        validFrom?: number;
+        validUntil?: number;
     }): Transfer {
         const type = new Uint8Array([5]); // tx type
         const accountId = serializeAccountId(transfer.accountId);
@@ -55,6 +57,8 @@ export class Signer {
             amount: BigNumber.from(transfer.amount).toString(),
             fee: BigNumber.from(transfer.fee).toString(),
             nonce: transfer.nonce,
+            validFrom: transfer.validFrom,
+            validUntil: transfer.validUntil,
             signature
         };
     }
@@ -67,6 +71,8 @@ export class Signer {
         amount: BigNumberish;
         fee: BigNumberish;
         nonce: number;
+        validFrom?: number;
+        validUntil?: number;
     }): Withdraw {
         const typeBytes = new Uint8Array([3]);
         const accountId = serializeAccountId(withdraw.accountId);
@@ -96,6 +102,8 @@ export class Signer {
             amount: BigNumber.from(withdraw.amount).toString(),
             fee: BigNumber.from(withdraw.fee).toString(),
             nonce: withdraw.nonce,
+            validFrom: withdraw.validFrom,
+            validUntil: withdraw.validUntil,
             signature
         };
     }
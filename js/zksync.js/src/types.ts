@@ -73,6 +73,9 @@ export interface Transfer {
     amount: BigNumberish;
     fee: BigNumberish;
     nonce: number;
+    // Unix timestamps (in seconds) of the validity window, authenticated by the Ethereum signature.
+    validFrom?: number;
+    validUntil?: number;
     signature: Signature;
 }
 
@@ -85,6 +88,9 @@ export interface Withdraw {
     amount: BigNumberish;
     fee: BigNumberish;
     nonce: number;
+    // Unix timestamps (in seconds) of the validity window, authenticated by the Ethereum signature.
+    validFrom?: number;
+    validUntil?: number;
     signature: Signature;
 }
 
@@ -120,6 +126,7 @@ export interface TransactionReceipt {
     success?: boolean;
     failReason?: string;
     block?: BlockInfo;
+    createdAccountId?: number;
 }
 
 export interface PriorityOperationReceipt {
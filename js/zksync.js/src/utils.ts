@@ -292,6 +292,21 @@ export function getChangePubkeyMessage(pubKeyHash: PubKeyHash, nonce: number, ac
     return message;
 }
 
+/**
+ * Formats the validity window to be appended to the message signed by the Ethereum keys.
+ * Must match `validity_window_message` on the server, message is unchanged if the window is not set.
+ */
+export function getValidityWindowMessage(validFrom?: number, validUntil?: number): string {
+    let message = "";
+    if (validFrom != null) {
+        message += `\nValid from: ${validFrom}`;
+    }
+    if (validUntil != null) {
+        message += `\nValid until: ${validUntil}`;
+    }
+    return message;
+}
+
 export function getSignedBytesFromMessage(message: utils.BytesLike | string, addPrefix: boolean): Uint8Array {
     let messageBytes = typeof message === "string" ? utils.toUtf8Bytes(message) : utils.arrayify(message);
     if (addPrefix) {
@@ -20,6 +20,7 @@ import {
     isTokenETH,
     MAX_ERC20_APPROVE_AMOUNT,
     getChangePubkeyMessage,
+    getValidityWindowMessage,
     SYNC_MAIN_CONTRACT_INTERFACE,
     getSignedBytesFromMessage,
     signMessagePersonalAPI,
@@ -101,6 +102,8 @@ export class Wallet {
         amount: BigNumberish;
         fee: BigNumberish;
         nonce: number;
+        validFrom?: number;
+        validUntil?: number;
     }): Promise<SignedTransaction> {
         if (!this.signer) {
             throw new Error("ZKSync signer is required for sending zksync transactions.");
@@ -117,7 +120,9 @@ export class Wallet {
             tokenId,
             amount: transfer.amount,
             fee: transfer.fee,
-            nonce: transfer.nonce
+            nonce: transfer.nonce,
+            validFrom: transfer.validFrom,
+            validUntil: transfer.validUntil
         };
 
         const stringAmount = this.provider.tokenSet.formatToken(transfer.token, transfer.amount);
@@ -128,7 +133,8 @@ export class Wallet {
             `To: ${transfer.to.toLowerCase()}\n` +
             `Nonce: ${transfer.nonce}\n` +
             `Fee: ${stringFee} ${stringToken}\n` +
-            `Account Id: ${this.accountId}`;
+            `Account Id: ${this.accountId}` +
+            getValidityWindowMessage(transfer.validFrom, transfer.validUntil);
 
         const txMessageEthSignature = await this.getEthMessageSignature(humanReadableTxInfo);
         const signedTransferTransaction = this.signer.signSyncTransfer(transactionData);
@@ -144,6 +150,8 @@ export class Wallet {
         amount: BigNumberish;
         fee?: BigNumberish;
         nonce?: Nonce;
+        validFrom?: number;
+        validUntil?: number;
     }): Promise<Transaction> {
         transfer.nonce = transfer.nonce != null ? await this.getNonce(transfer.nonce) : await this.getNonce();
 
@@ -161,6 +169,8 @@ export class Wallet {
         amount: BigNumberish;
         fee: BigNumberish;
         nonce: number;
+        validFrom?: number;
+        validUntil?: number;
     }): Promise<SignedTransaction> {
         if (!this.signer) {
             throw new Error("ZKSync signer is required for sending zksync transactions.");
@@ -175,7 +185,9 @@ export class Wallet {
             tokenId,
             amount: withdraw.amount,
             fee: withdraw.fee,
-            nonce: withdraw.nonce
+            nonce: withdraw.nonce,
+            validFrom: withdraw.validFrom,
+            validUntil: withdraw.validUntil
         };
 
         const stringAmount = this.provider.tokenSet.formatToken(withdraw.token, withdraw.amount);
@@ -186,7 +198,8 @@ export class Wallet {
             `To: ${withdraw.ethAddress.toLowerCase()}\n` +
             `Nonce: ${withdraw.nonce}\n` +
             `Fee: ${stringFee} ${stringToken}\n` +
-            `Account Id: ${this.accountId}`;
+            `Account Id: ${this.accountId}` +
+            getValidityWindowMessage(withdraw.validFrom, withdraw.validUntil);
 
         const txMessageEthSignature = await this.getEthMessageSignature(humanReadableTxInfo);
 
@@ -204,6 +217,8 @@ export class Wallet {
         amount: BigNumberish;
         fee?: BigNumberish;
         nonce?: Nonce;
+        validFrom?: number;
+        validUntil?: number;
     }): Promise<Transaction> {
         withdraw.nonce = withdraw.nonce != null ? await this.getNonce(withdraw.nonce) : await this.getNonce();
 